@@ -0,0 +1,36 @@
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use rust_audio_engine::{AudioBuffer, OwnedAudioBuffer, SampleLocation};
+
+const SAMPLE_RATE: usize = 48_000;
+const NUM_FRAMES: usize = 512;
+const NUM_CHANNELS: usize = 2;
+
+fn mixing(criterion: &mut Criterion) {
+    let mut source = OwnedAudioBuffer::new(NUM_FRAMES, NUM_CHANNELS, SAMPLE_RATE);
+    source.fill_with_value(0.25);
+    let mut destination = OwnedAudioBuffer::new(NUM_FRAMES, NUM_CHANNELS, SAMPLE_RATE);
+
+    criterion.bench_function("buffer_add_from", |bencher| {
+        bencher.iter(|| {
+            destination.add_from(
+                black_box(&source),
+                SampleLocation::new(0, 0),
+                SampleLocation::new(0, 0),
+                NUM_CHANNELS,
+                NUM_FRAMES,
+            );
+        })
+    });
+
+    criterion.bench_function("buffer_fill_and_clear", |bencher| {
+        bencher.iter(|| {
+            destination.fill_with_value(black_box(0.5));
+            destination.clear();
+        })
+    });
+}
+
+criterion_group!(benches, mixing);
+criterion_main!(benches);
@@ -0,0 +1,85 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rust_audio_engine::{AudioProcess, Context, Gain, NodeHandle, Oscillator, OwnedAudioBuffer};
+
+const SAMPLE_RATE: usize = 48_000;
+const NUM_FRAMES: usize = 512;
+const GRAPH_SIZES: [usize; 4] = [1, 8, 32, 128];
+
+struct RunningGraph {
+    context: Context,
+    audio_process: Box<dyn AudioProcess + Send>,
+    buffer: OwnedAudioBuffer,
+}
+
+impl RunningGraph {
+    fn new(setup: impl FnOnce(&mut Context)) -> Self {
+        let mut context = Context::new(SAMPLE_RATE);
+        let audio_process = context.get_audio_process();
+        setup(&mut context);
+        context.start();
+
+        Self {
+            context,
+            audio_process,
+            buffer: OwnedAudioBuffer::new(NUM_FRAMES, 2, SAMPLE_RATE),
+        }
+    }
+
+    fn render(&mut self) {
+        self.audio_process.process(&mut self.buffer);
+        self.context.process_notifications();
+    }
+}
+
+fn fan_in(context: &mut Context, size: usize) {
+    let gain = context
+        .add_node(Gain::new(context.get_command_queue()))
+        .unwrap();
+
+    for index in 0..size {
+        let oscillator = Oscillator::new(context.get_command_queue(), 110.0 * (index + 1) as f64);
+        let oscillator = context.add_node(oscillator).unwrap();
+        context.connect_nodes(&oscillator, &gain).unwrap();
+    }
+
+    context.connect_node_to_output(&gain).unwrap();
+}
+
+fn chain(context: &mut Context, size: usize) {
+    let oscillator = Oscillator::new(context.get_command_queue(), 440.0);
+    let oscillator = context.add_node(oscillator).unwrap();
+    let gains: Vec<NodeHandle<Gain>> = (0..size)
+        .map(|_| {
+            context
+                .add_node(Gain::new(context.get_command_queue()))
+                .unwrap()
+        })
+        .collect();
+
+    context.connect_nodes(&oscillator, &gains[0]).unwrap();
+    for pair in gains.windows(2) {
+        context.connect_nodes(&pair[0], &pair[1]).unwrap();
+    }
+    context.connect_node_to_output(&gains[size - 1]).unwrap();
+}
+
+fn process_graph(criterion: &mut Criterion) {
+    let mut group = criterion.benchmark_group("graph_process");
+
+    for size in GRAPH_SIZES {
+        group.bench_with_input(BenchmarkId::new("fan_in", size), &size, |bencher, size| {
+            let mut graph = RunningGraph::new(|context| fan_in(context, *size));
+            bencher.iter(|| graph.render());
+        });
+
+        group.bench_with_input(BenchmarkId::new("chain", size), &size, |bencher, size| {
+            let mut graph = RunningGraph::new(|context| chain(context, *size));
+            bencher.iter(|| graph.render());
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, process_graph);
+criterion_main!(benches);
@@ -0,0 +1,63 @@
+use std::f64::consts::TAU;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use rust_audio_engine::{
+    AudioBuffer, AudioProcess, Context, Node, Oscillator, OwnedAudioBuffer, SampleLocation,
+    Sampler, Timestamp,
+};
+
+const SAMPLE_RATE: usize = 48_000;
+const NUM_FRAMES: usize = 512;
+
+fn render_loop(
+    context: &mut Context,
+    mut audio_process: Box<dyn AudioProcess + Send>,
+) -> impl FnMut() + '_ {
+    let mut buffer = OwnedAudioBuffer::new(NUM_FRAMES, 2, SAMPLE_RATE);
+
+    move || {
+        audio_process.process(&mut buffer);
+        context.process_notifications();
+    }
+}
+
+fn oscillator(criterion: &mut Criterion) {
+    criterion.bench_function("oscillator", |bencher| {
+        let mut context = Context::new(SAMPLE_RATE);
+        let audio_process = context.get_audio_process();
+
+        let oscillator = Oscillator::new(context.get_command_queue(), 440.0);
+        oscillator.connect_to_output().unwrap();
+        context.start();
+
+        let mut render = render_loop(&mut context, audio_process);
+        bencher.iter(&mut render);
+    });
+}
+
+fn sampler(criterion: &mut Criterion) {
+    criterion.bench_function("sampler_looping", |bencher| {
+        let mut context = Context::new(SAMPLE_RATE);
+        let audio_process = context.get_audio_process();
+
+        let mut sample = OwnedAudioBuffer::new(SAMPLE_RATE, 2, SAMPLE_RATE);
+        for frame in 0..sample.num_frames() {
+            let value = (TAU * 220.0 * frame as f64 / SAMPLE_RATE as f64).sin() as f32;
+            for channel in 0..sample.num_channels() {
+                sample.set_sample(SampleLocation::new(channel, frame), value);
+            }
+        }
+
+        let mut sampler = Sampler::new(context.get_command_queue(), SAMPLE_RATE, sample);
+        sampler.enable_loop(Timestamp::zero(), Timestamp::from_seconds(1.0));
+        sampler.start_now();
+        sampler.connect_to_output().unwrap();
+        context.start();
+
+        let mut render = render_loop(&mut context, audio_process);
+        bencher.iter(&mut render);
+    });
+}
+
+criterion_group!(benches, oscillator, sampler);
+criterion_main!(benches);
@@ -0,0 +1,72 @@
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use rust_audio_engine::{
+    AutomationCurve, AutomationLane, AutomationPoint, ConstantSource, Context, Gain,
+    OwnedAudioBuffer, TempoMap, Timestamp,
+};
+
+const SAMPLE_RATE: usize = 48_000;
+const NUM_FRAMES: usize = 512;
+
+fn ramped_gain(criterion: &mut Criterion) {
+    let mut group = criterion.benchmark_group("gain_parameter");
+
+    for ramped in [false, true] {
+        let name = if ramped { "linear_ramp" } else { "constant" };
+
+        group.bench_function(name, |bencher| {
+            let mut context = Context::new(SAMPLE_RATE);
+            let mut audio_process = context.get_audio_process();
+
+            let source = ConstantSource::new(context.get_command_queue(), 1.0);
+            let mut gain = Gain::new(context.get_command_queue());
+            if ramped {
+                gain.gain.set_value_at_time(0.0, Timestamp::zero());
+                gain.gain
+                    .linear_ramp_to_value(1.0, Timestamp::from_seconds(3600.0));
+            }
+
+            let source = context.add_node(source).unwrap();
+            let gain = context.add_node(gain).unwrap();
+            context.connect_nodes(&source, &gain).unwrap();
+            context.connect_node_to_output(&gain).unwrap();
+            context.start();
+
+            let mut buffer = OwnedAudioBuffer::new(NUM_FRAMES, 2, SAMPLE_RATE);
+            bencher.iter(|| {
+                audio_process.process(&mut buffer);
+                context.process_notifications();
+            });
+        });
+    }
+
+    group.finish();
+}
+
+fn automation_lane(criterion: &mut Criterion) {
+    let mut lane = AutomationLane::new(TempoMap::new(120.0), Timestamp::zero());
+    for point in 0..256 {
+        let curve = if point % 2 == 0 {
+            AutomationCurve::Linear
+        } else {
+            AutomationCurve::Step
+        };
+        lane.add_point(AutomationPoint::new(
+            point as f64,
+            (point % 7) as f64,
+            curve,
+        ));
+    }
+
+    criterion.bench_function("automation_value_at_beat", |bencher| {
+        bencher.iter(|| {
+            for frame in 0..NUM_FRAMES {
+                black_box(lane.value_at_beat(black_box(frame as f64 * 0.5)));
+            }
+        })
+    });
+}
+
+criterion_group!(benches, ramped_gain, automation_lane);
+criterion_main!(benches);
@@ -1,7 +1,7 @@
 use std::{thread, time};
 
 use rust_audio_engine::{
-    AudioBuffer, Context, Gain, Level, Node, OwnedAudioBuffer, SampleLocation, Sampler, Timestamp,
+    AudioBuffer, Context, Gain, Level, OwnedAudioBuffer, SampleLocation, Sampler, Timestamp,
 };
 use structopt::StructOpt;
 
@@ -31,18 +31,20 @@ fn play_file(file_to_play: &str) {
     let mut sampler = Sampler::new(context.get_command_queue(), sample_rate, sample);
     let mut gain = Gain::new(context.get_command_queue());
 
-    sampler.connect_to(gain.get_id());
     sampler.start_now();
     sampler.enable_loop(
         Timestamp::zero(),
         Timestamp::from_samples(length_in_samples as f64, sample_rate),
     );
 
-    gain.connect_to_output();
-
     gain.gain
         .set_value_at_time(Level::from_db(-6.0).as_gain(), Timestamp::zero());
 
+    let sampler = context.add_node(sampler).unwrap();
+    let gain = context.add_node(gain).unwrap();
+    context.connect_nodes(&sampler, &gain).unwrap();
+    context.connect_node_to_output(&gain).unwrap();
+
     context.start();
 
     thread::sleep(time::Duration::from_secs(4 * length_in_seconds));
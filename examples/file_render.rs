@@ -1,7 +1,4 @@
-use rust_audio_engine::{
-    AudioBuffer, AudioBufferSlice, Context, Gain, Node, Oscillator, OwnedAudioBuffer,
-    SampleLocation, Timestamp,
-};
+use rust_audio_engine::{AudioBuffer, Context, Gain, Oscillator, SampleLocation, Timestamp};
 use structopt::StructOpt;
 
 #[derive(Debug, StructOpt)]
@@ -17,7 +14,6 @@ fn main() {
 fn render_file(output_file: &str) {
     let sample_rate = 44100;
     let mut context = Context::new(sample_rate);
-    let mut audio_process = context.get_audio_process();
 
     let mut oscillator_1 = Oscillator::new(context.get_command_queue(), 440.0);
     oscillator_1.gain.set_value_at_time(0.4, Timestamp::zero());
@@ -33,18 +29,19 @@ fn render_file(output_file: &str) {
 
     let mut gain = Gain::new(context.get_command_queue());
 
-    oscillator_1.connect_to(gain.get_id());
-    oscillator_2.connect_to(gain.get_id());
-    oscillator_3.connect_to(gain.get_id());
-    oscillator_4.connect_to(gain.get_id());
-
-    gain.connect_to_output();
-
     gain.gain.set_value_at_time(0.9, Timestamp::zero());
 
     gain.gain
         .linear_ramp_to_value(0.0, Timestamp::from_seconds(4.0));
 
+    let gain = context.add_node(gain).unwrap();
+    for oscillator in [oscillator_1, oscillator_2, oscillator_3, oscillator_4] {
+        let oscillator = context.add_node(oscillator).unwrap();
+        context.connect_nodes(&oscillator, &gain).unwrap();
+    }
+
+    context.connect_node_to_output(&gain).unwrap();
+
     context.start();
 
     let bits_per_sample = 24;
@@ -59,30 +56,15 @@ fn render_file(output_file: &str) {
 
     let mut writer = hound::WavWriter::create(output_file, file_spec).unwrap();
 
-    let length_in_seconds = 4.0;
-    let total_num_frames = sample_rate * length_in_seconds as usize;
+    let audio_buffer = context.render_offline(Timestamp::from_seconds(4.0), 2);
 
-    let max_number_of_frames = 1024;
-    let mut audio_buffer = OwnedAudioBuffer::new(total_num_frames, 2, sample_rate);
-
-    let mut position = 0;
-    while position < total_num_frames {
-        let frames_this_time = std::cmp::min(max_number_of_frames, total_num_frames - position);
-
-        let mut frame_buffer = AudioBufferSlice::new(&mut audio_buffer, position, frames_this_time);
-
-        audio_process.process(&mut frame_buffer);
-
-        for frame in 0..frame_buffer.num_frames() {
-            for channel in 0..frame_buffer.num_channels() {
-                let sample = frame_buffer.get_sample(SampleLocation::new(channel, frame));
-                let sample = sample.clamp(-1.0, 1.0);
-                let sample = (sample * max_value as f32) as i32;
-                writer.write_sample(sample).expect("Failed to write sample");
-            }
+    for frame in 0..audio_buffer.num_frames() {
+        for channel in 0..audio_buffer.num_channels() {
+            let sample = audio_buffer.get_sample(SampleLocation::new(channel, frame));
+            let sample = sample.clamp(-1.0, 1.0);
+            let sample = (sample * max_value as f32) as i32;
+            writer.write_sample(sample).expect("Failed to write sample");
         }
-
-        position += frames_this_time;
     }
 
     context.stop();
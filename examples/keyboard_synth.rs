@@ -0,0 +1,214 @@
+use std::io::{self, BufRead};
+
+use rust_audio_engine::{
+    Context, Gain, Level, NodeHandle, NoteEvent, NoteEventType, NoteExpression, NoteId, Oscillator,
+    Timestamp,
+};
+
+use crate::audio_callback::AudioCallback;
+
+#[path = "./lib/audio_callback.rs"]
+mod audio_callback;
+
+const SAMPLE_RATE: usize = 48_000;
+const NUM_VOICES: usize = 8;
+const SCHEDULING_LATENCY: f64 = 0.05;
+const NOTE_SPACING: f64 = 0.2;
+const NOTE_LENGTH: f64 = 0.18;
+
+const ATTACK: f64 = 0.01;
+const DECAY: f64 = 0.1;
+const SUSTAIN: f64 = 0.6;
+const RELEASE: f64 = 0.3;
+
+const KEYS: &str = "awsedftgyhujk";
+
+struct Voice {
+    oscillator: NodeHandle<Oscillator>,
+    amplitude: NodeHandle<Gain>,
+    note_id: Option<NoteId>,
+    sustain_level: f64,
+    released_until: Timestamp,
+}
+
+impl Voice {
+    fn is_free_at(&self, time: Timestamp) -> bool {
+        self.note_id.is_none() && self.released_until <= time
+    }
+}
+
+struct PolySynth {
+    voices: Vec<Voice>,
+}
+
+impl PolySynth {
+    fn new(context: &mut Context) -> Self {
+        let mut master = Gain::new(context.get_command_queue());
+        master
+            .gain
+            .set_value_at_time(Level::from_db(-12.0).as_gain(), Timestamp::zero());
+        let master = context.add_node(master).unwrap();
+        context.connect_node_to_output(&master).unwrap();
+
+        let voices = (0..NUM_VOICES)
+            .map(|_| {
+                let oscillator = Oscillator::new(context.get_command_queue(), 440.0);
+                let mut amplitude = Gain::new(context.get_command_queue());
+                amplitude.gain.set_value_at_time(0.0, Timestamp::zero());
+
+                let oscillator = context.add_node(oscillator).unwrap();
+                let amplitude = context.add_node(amplitude).unwrap();
+                context.connect_nodes(&oscillator, &amplitude).unwrap();
+                context.connect_nodes(&amplitude, &master).unwrap();
+
+                Voice {
+                    oscillator,
+                    amplitude,
+                    note_id: None,
+                    sustain_level: 0.0,
+                    released_until: Timestamp::zero(),
+                }
+            })
+            .collect();
+
+        Self { voices }
+    }
+
+    fn handle_event(&mut self, context: &mut Context, event: &NoteEvent) {
+        match event.event_type {
+            NoteEventType::NoteOn { .. } => self.note_on(context, event),
+            NoteEventType::NoteOff { .. } => self.note_off(context, event),
+            _ => (),
+        }
+    }
+
+    fn note_on(&mut self, context: &mut Context, event: &NoteEvent) {
+        let mut expression = NoteExpression::new(0.0, 0.0);
+        expression.apply(&event.event_type);
+
+        let voice = match self
+            .voices
+            .iter_mut()
+            .find(|voice| voice.is_free_at(event.time))
+        {
+            Some(voice) => voice,
+            None => {
+                println!("All voices busy, dropping note");
+                return;
+            }
+        };
+        let start_time = event.time;
+
+        let oscillator = context.get_node_mut(&voice.oscillator).unwrap();
+        oscillator
+            .frequency
+            .set_value_at_time(expression.frequency(), start_time);
+
+        let peak_level = expression.velocity;
+        voice.sustain_level = peak_level * SUSTAIN;
+        let amplitude = context.get_node_mut(&voice.amplitude).unwrap();
+        amplitude.gain.set_value_at_time(0.0, start_time);
+        amplitude
+            .gain
+            .linear_ramp_to_value(peak_level, start_time.incremented_by_seconds(ATTACK));
+        amplitude.gain.linear_ramp_to_value(
+            voice.sustain_level,
+            start_time.incremented_by_seconds(ATTACK + DECAY),
+        );
+
+        voice.note_id = Some(event.note_id);
+    }
+
+    fn note_off(&mut self, context: &mut Context, event: &NoteEvent) {
+        if let Some(voice) = self
+            .voices
+            .iter_mut()
+            .find(|voice| voice.note_id == Some(event.note_id))
+        {
+            let amplitude = context.get_node_mut(&voice.amplitude).unwrap();
+            amplitude
+                .gain
+                .linear_ramp_to_value(voice.sustain_level, event.time);
+            amplitude
+                .gain
+                .linear_ramp_to_value(0.0, event.time.incremented_by_seconds(RELEASE));
+
+            voice.note_id = None;
+            voice.released_until = event.time.incremented_by_seconds(RELEASE);
+        }
+    }
+}
+
+fn print_instructions() {
+    println!("Type keys and press enter to play them in sequence:");
+    println!();
+    println!("   w e   t y u");
+    println!("  a s d f g h j k");
+    println!();
+    println!("z / x: octave down / up, '-': rest, q: quit");
+}
+
+fn main() {
+    let mut context = Context::new(SAMPLE_RATE);
+    let _audio_callback = AudioCallback::new(context.get_audio_process(), SAMPLE_RATE);
+
+    let mut synth = PolySynth::new(&mut context);
+    context.start();
+
+    print_instructions();
+
+    let mut octave = 4;
+    let mut next_note_id: NoteId = 0;
+
+    for line in io::stdin().lock().lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(_) => break,
+        };
+
+        context.process_notifications();
+        let mut time = context
+            .current_time()
+            .incremented_by_seconds(SCHEDULING_LATENCY);
+
+        for key in line.chars() {
+            match key {
+                'q' => return quit(&mut context),
+                'z' => octave = (octave - 1).max(0),
+                'x' => octave = (octave + 1).min(8),
+                '-' => time = time.incremented_by_seconds(NOTE_SPACING),
+                _ => {
+                    if let Some(semitone) = KEYS.find(key) {
+                        let pitch = (12 * (octave + 1) + semitone as i32) as f64;
+                        let note_id = next_note_id;
+                        next_note_id = next_note_id.wrapping_add(1);
+
+                        synth.handle_event(
+                            &mut context,
+                            &NoteEvent::note_on(time, note_id, pitch, 0.8),
+                        );
+                        synth.handle_event(
+                            &mut context,
+                            &NoteEvent::note_off(
+                                time.incremented_by_seconds(NOTE_LENGTH),
+                                note_id,
+                                0.0,
+                            ),
+                        );
+
+                        time = time.incremented_by_seconds(NOTE_SPACING);
+                    }
+                }
+            }
+        }
+
+        println!("Octave {}", octave);
+    }
+
+    quit(&mut context);
+}
+
+fn quit(context: &mut Context) {
+    context.process_notifications();
+    context.stop();
+}
@@ -0,0 +1,269 @@
+use std::time::Duration;
+
+use rust_audio_engine::{
+    AudioBuffer, AudioBufferSlice, AutomationCurve, AutomationLane, AutomationPoint, Context,
+    DynamicEq, DynamicEqBandType, Emitter, Gain, Level, NodeHandle, OwnedAudioBuffer,
+    SampleLocation, Sampler, TempoMap, Timestamp,
+};
+use structopt::StructOpt;
+
+const NUM_CHANNELS: usize = 2;
+const BLOCK_SIZE: usize = 1024;
+const TAIL_SECONDS: f64 = 1.0;
+const BEATS_PER_MINUTE: f64 = 120.0;
+const FADE_IN_BEATS: f64 = 2.0;
+const FADE_OUT_BEATS: f64 = 4.0;
+
+#[derive(Debug, StructOpt)]
+struct Options {
+    output_file: String,
+
+    #[structopt(required = true, min_values = 1)]
+    stems: Vec<String>,
+
+    #[structopt(long, default_value = "-6")]
+    track_gain_db: f64,
+
+    #[structopt(long, default_value = "-1")]
+    ceiling_db: f64,
+}
+
+fn add_track(
+    context: &mut Context,
+    stem: OwnedAudioBuffer,
+    pan: f64,
+    gain_db: f64,
+    bus: &NodeHandle<Gain>,
+) {
+    let sample_rate = context.get_sample_rate();
+    let mut sampler = Sampler::new(context.get_command_queue(), sample_rate, stem);
+
+    let mut eq = DynamicEq::new(
+        context.get_command_queue(),
+        &[(DynamicEqBandType::Bell, 300.0)],
+    );
+    let mud = &mut eq.bands[0];
+    mud.q.set_value_at_time(1.0, Timestamp::zero());
+    mud.threshold.set_value_at_time(-30.0, Timestamp::zero());
+    mud.ratio.set_value_at_time(3.0, Timestamp::zero());
+    mud.range.set_value_at_time(-4.0, Timestamp::zero());
+
+    let mut strip = Emitter::new(context.get_command_queue(), Duration::ZERO);
+    strip.pan.set_value_at_time(pan, Timestamp::zero());
+    strip
+        .gain
+        .set_value_at_time(Level::from_db(gain_db).as_gain(), Timestamp::zero());
+    strip.cutoff.set_value_at_time(18_000.0, Timestamp::zero());
+
+    sampler.start_now();
+
+    let sampler = context.add_node(sampler).unwrap();
+    let eq = context.add_node(eq).unwrap();
+    let strip = context.add_node(strip).unwrap();
+    context.connect_nodes(&sampler, &eq).unwrap();
+    context.connect_nodes(&eq, &strip).unwrap();
+    context.connect_nodes(&strip, bus).unwrap();
+}
+
+struct PeakLimiter {
+    ceiling: f32,
+    gain: f32,
+    release_coefficient: f32,
+}
+
+impl PeakLimiter {
+    fn new(ceiling_db: f64, release_seconds: f64, sample_rate: usize) -> Self {
+        Self {
+            ceiling: Level::from_db(ceiling_db).as_gain() as f32,
+            gain: 1.0,
+            release_coefficient: (1.0 - (-1.0 / (release_seconds * sample_rate as f64)).exp())
+                as f32,
+        }
+    }
+
+    fn process(&mut self, buffer: &mut dyn AudioBuffer) {
+        for frame in 0..buffer.num_frames() {
+            let peak = (0..buffer.num_channels())
+                .map(|channel| buffer.get_sample(SampleLocation::new(channel, frame)).abs())
+                .fold(0.0, f32::max);
+
+            let target = if peak > self.ceiling {
+                self.ceiling / peak
+            } else {
+                1.0
+            };
+
+            if target < self.gain {
+                self.gain = target;
+            } else {
+                self.gain += self.release_coefficient * (target - self.gain);
+            }
+
+            for channel in 0..buffer.num_channels() {
+                let location = SampleLocation::new(channel, frame);
+                buffer.set_sample(location, buffer.get_sample(location) * self.gain);
+            }
+        }
+    }
+}
+
+fn main() {
+    let options = Options::from_args();
+
+    let stems: Vec<(OwnedAudioBuffer, usize)> =
+        options.stems.iter().map(|path| read_stem(path)).collect();
+
+    let sample_rate = stems[0].1;
+    assert!(
+        stems.iter().all(|(_, rate)| *rate == sample_rate),
+        "All stems must have the same sample rate"
+    );
+
+    let length_in_frames = stems
+        .iter()
+        .map(|(stem, _)| stem.num_frames())
+        .max()
+        .unwrap()
+        + (TAIL_SECONDS * sample_rate as f64) as usize;
+
+    mixdown(
+        &options,
+        stems.into_iter().map(|(stem, _)| stem).collect(),
+        sample_rate,
+        length_in_frames,
+    );
+}
+
+fn mixdown(
+    options: &Options,
+    stems: Vec<OwnedAudioBuffer>,
+    sample_rate: usize,
+    length_in_frames: usize,
+) {
+    let mut context = Context::new(sample_rate);
+    let mut audio_process = context.get_audio_process();
+
+    let mut bus = Gain::new(context.get_command_queue());
+    let end = Timestamp::from_samples(length_in_frames as f64, sample_rate);
+    let mut fade = bus_fade(end);
+    fade.stream_to(&mut bus.gain, end.incremented_by_seconds(TAIL_SECONDS));
+
+    let bus = context.add_node(bus).unwrap();
+    context.connect_node_to_output(&bus).unwrap();
+
+    let num_stems = stems.len();
+    for (index, stem) in stems.into_iter().enumerate() {
+        let pan = if num_stems == 1 {
+            0.0
+        } else {
+            -0.6 + 1.2 * index as f64 / (num_stems - 1) as f64
+        };
+        add_track(&mut context, stem, pan, options.track_gain_db, &bus);
+    }
+
+    context.start();
+
+    let mut writer = hound::WavWriter::create(
+        &options.output_file,
+        hound::WavSpec {
+            channels: NUM_CHANNELS as u16,
+            sample_rate: sample_rate as u32,
+            bits_per_sample: 24,
+            sample_format: hound::SampleFormat::Int,
+        },
+    )
+    .expect("Failed to create output file");
+    let max_value = 2_i32.pow(23) - 1;
+
+    let mut limiter = PeakLimiter::new(options.ceiling_db, 0.1, sample_rate);
+    let mut buffer = OwnedAudioBuffer::new(BLOCK_SIZE, NUM_CHANNELS, sample_rate);
+
+    let mut position = 0;
+    while position < length_in_frames {
+        let num_frames = BLOCK_SIZE.min(length_in_frames - position);
+        let mut block = AudioBufferSlice::new(&mut buffer, 0, num_frames);
+
+        audio_process.process(&mut block);
+        limiter.process(&mut block);
+
+        for frame in 0..num_frames {
+            for channel in 0..NUM_CHANNELS {
+                let sample = block.get_sample(SampleLocation::new(channel, frame));
+                writer
+                    .write_sample((sample.clamp(-1.0, 1.0) * max_value as f32) as i32)
+                    .expect("Failed to write sample");
+            }
+        }
+
+        context.process_notifications();
+        position += num_frames;
+    }
+
+    writer.finalize().expect("Failed to finalise output file");
+    context.stop();
+
+    println!(
+        "Rendered {} stems to {} ({:.1} s)",
+        num_stems,
+        options.output_file,
+        end.get_seconds()
+    );
+}
+
+fn bus_fade(end: Timestamp) -> AutomationLane {
+    let tempo_map = TempoMap::new(BEATS_PER_MINUTE);
+    let last_beat = tempo_map.beat_at_seconds(end.get_seconds());
+
+    let mut lane = AutomationLane::new(tempo_map, Timestamp::zero());
+    lane.add_point(AutomationPoint::new(0.0, 0.0, AutomationCurve::Step));
+    lane.add_point(AutomationPoint::new(
+        FADE_IN_BEATS,
+        1.0,
+        AutomationCurve::Linear,
+    ));
+    let fade_out_beat = last_beat - FADE_OUT_BEATS;
+    if fade_out_beat > FADE_IN_BEATS {
+        lane.add_point(AutomationPoint::new(
+            fade_out_beat,
+            1.0,
+            AutomationCurve::Step,
+        ));
+    }
+    lane.add_point(AutomationPoint::new(
+        last_beat,
+        0.0,
+        AutomationCurve::Linear,
+    ));
+    lane
+}
+
+fn read_stem(path: &str) -> (OwnedAudioBuffer, usize) {
+    let mut reader = hound::WavReader::open(path).expect("Failed to open stem");
+    let specification = reader.spec();
+    let num_channels = specification.channels as usize;
+    let sample_rate = specification.sample_rate as usize;
+
+    let samples: Vec<f32> = match specification.sample_format {
+        hound::SampleFormat::Float => reader.samples::<f32>().map(Result::unwrap).collect(),
+        hound::SampleFormat::Int => {
+            let max_value = 2_i32.pow(specification.bits_per_sample as u32 - 1) as f32;
+            reader
+                .samples::<i32>()
+                .map(|sample| sample.unwrap() as f32 / max_value)
+                .collect()
+        }
+    };
+
+    let num_frames = samples.len() / num_channels;
+    let mut stem = OwnedAudioBuffer::new(num_frames, NUM_CHANNELS, sample_rate);
+
+    for frame in 0..num_frames {
+        for channel in 0..NUM_CHANNELS {
+            let source_channel = channel.min(num_channels - 1);
+            let sample = samples[frame * num_channels + source_channel];
+            stem.set_sample(SampleLocation::new(channel, frame), sample);
+        }
+    }
+
+    (stem, sample_rate)
+}
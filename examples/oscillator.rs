@@ -1,6 +1,6 @@
 use std::{thread, time};
 
-use rust_audio_engine::{Context, Gain, Level, Node, Oscillator, Timestamp};
+use rust_audio_engine::{Context, Gain, Level, Oscillator, Timestamp};
 
 use crate::audio_callback::AudioCallback;
 
@@ -34,13 +34,6 @@ fn main() {
 
     let mut gain = Gain::new(context.get_command_queue());
 
-    oscillator_1.connect_to(gain.get_id());
-    oscillator_2.connect_to(gain.get_id());
-    oscillator_3.connect_to(gain.get_id());
-    oscillator_4.connect_to(gain.get_id());
-
-    gain.connect_to_output();
-
     gain.gain.set_value_at_time(0.0, Timestamp::zero());
 
     gain.gain
@@ -49,6 +42,14 @@ fn main() {
     gain.gain
         .linear_ramp_to_value(0.0, Timestamp::from_seconds(4.0));
 
+    let gain = context.add_node(gain).unwrap();
+    for oscillator in [oscillator_1, oscillator_2, oscillator_3, oscillator_4] {
+        let oscillator = context.add_node(oscillator).unwrap();
+        context.connect_nodes(&oscillator, &gain).unwrap();
+    }
+
+    context.connect_node_to_output(&gain).unwrap();
+
     context.start();
 
     thread::sleep(time::Duration::from_secs(4));
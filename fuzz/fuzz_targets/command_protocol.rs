@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rust_audio_engine::fuzzing;
+
+fuzz_target!(|data: &[u8]| {
+    fuzzing::run_command_sequence(data);
+});
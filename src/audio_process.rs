@@ -1,5 +1,15 @@
-use crate::buffer::audio_buffer::AudioBuffer;
+use crate::{buffer::audio_buffer::AudioBuffer, host_transport::HostTransport};
 
 pub trait AudioProcess {
     fn process(&mut self, output_buffer: &mut dyn AudioBuffer);
+
+    fn process_with_input(
+        &mut self,
+        _input_buffer: &dyn AudioBuffer,
+        output_buffer: &mut dyn AudioBuffer,
+    ) {
+        self.process(output_buffer);
+    }
+
+    fn set_host_transport(&mut self, _transport: &HostTransport) {}
 }
@@ -0,0 +1,312 @@
+use ::cpal::{
+    traits::{DeviceTrait, HostTrait, StreamTrait},
+    BufferSize, Device, Host, Sample, SampleFormat, SampleRate, Stream, StreamConfig,
+    SupportedBufferSize, SupportedStreamConfigRange,
+};
+
+use crate::{
+    audio_process::AudioProcess,
+    buffer::borrowed_audio_buffer::BorrowedAudioBuffer,
+    graph::{channel_adaptation::ChannelAdaptation, channel_matrix::ChannelMatrix},
+    realtime::processor::MAXIMUM_NUMBER_OF_CHANNELS,
+};
+
+const ENGINE_CHANNELS: usize = MAXIMUM_NUMBER_OF_CHANNELS;
+const DEFAULT_BUFFER_SIZE: usize = 512;
+
+#[derive(Clone, Debug)]
+pub enum CpalBackendError {
+    NoOutputDevice,
+    DeviceNotFound(String),
+    UnsupportedConfiguration,
+    Device(String),
+    Stream(String),
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct CpalBackendOptions {
+    pub device_name: Option<String>,
+    pub buffer_size: Option<usize>,
+    pub channel_adaptation: ChannelAdaptation,
+}
+
+pub fn output_device_names() -> Result<Vec<String>, CpalBackendError> {
+    let devices = ::cpal::default_host()
+        .output_devices()
+        .map_err(|error| CpalBackendError::Device(error.to_string()))?;
+
+    Ok(devices.filter_map(|device| device.name().ok()).collect())
+}
+
+pub struct CpalBackend {
+    _stream: Stream,
+    device_name: String,
+    sample_rate: usize,
+    num_device_channels: usize,
+    buffer_size: Option<usize>,
+}
+
+impl CpalBackend {
+    pub fn new(
+        audio_process: Box<dyn AudioProcess + Send>,
+        sample_rate: usize,
+    ) -> Result<Self, CpalBackendError> {
+        Self::with_options(audio_process, sample_rate, CpalBackendOptions::default())
+    }
+
+    pub fn with_options(
+        audio_process: Box<dyn AudioProcess + Send>,
+        sample_rate: usize,
+        options: CpalBackendOptions,
+    ) -> Result<Self, CpalBackendError> {
+        let host = ::cpal::default_host();
+        let device = find_output_device(&host, options.device_name.as_deref())?;
+        let device_name = device
+            .name()
+            .map_err(|error| CpalBackendError::Device(error.to_string()))?;
+
+        let supported_configs = device
+            .supported_output_configs()
+            .map_err(|error| CpalBackendError::Device(error.to_string()))?;
+        let supported_config = choose_config(supported_configs.collect(), sample_rate)?;
+
+        let buffer_size =
+            negotiate_buffer_size(supported_config.buffer_size(), options.buffer_size);
+        let num_device_channels = usize::from(supported_config.channels());
+
+        let config = StreamConfig {
+            channels: supported_config.channels(),
+            sample_rate: SampleRate(sample_rate as u32),
+            buffer_size: match buffer_size {
+                Some(buffer_size) => BufferSize::Fixed(buffer_size as u32),
+                None => BufferSize::Default,
+            },
+        };
+
+        let renderer = OutputRenderer::new(
+            audio_process,
+            sample_rate,
+            num_device_channels,
+            options.channel_adaptation,
+            buffer_size.unwrap_or(DEFAULT_BUFFER_SIZE),
+        );
+
+        let stream = match supported_config.sample_format() {
+            SampleFormat::F32 => build_stream::<f32>(&device, &config, renderer),
+            SampleFormat::I16 => build_stream::<i16>(&device, &config, renderer),
+            SampleFormat::U16 => build_stream::<u16>(&device, &config, renderer),
+        }?;
+
+        stream
+            .play()
+            .map_err(|error| CpalBackendError::Stream(error.to_string()))?;
+
+        Ok(Self {
+            _stream: stream,
+            device_name,
+            sample_rate,
+            num_device_channels,
+            buffer_size,
+        })
+    }
+
+    pub fn get_device_name(&self) -> &str {
+        &self.device_name
+    }
+
+    pub fn get_sample_rate(&self) -> usize {
+        self.sample_rate
+    }
+
+    pub fn get_num_device_channels(&self) -> usize {
+        self.num_device_channels
+    }
+
+    pub fn get_buffer_size(&self) -> Option<usize> {
+        self.buffer_size
+    }
+}
+
+fn find_output_device(host: &Host, device_name: Option<&str>) -> Result<Device, CpalBackendError> {
+    let device_name = match device_name {
+        Some(device_name) => device_name,
+        None => {
+            return host
+                .default_output_device()
+                .ok_or(CpalBackendError::NoOutputDevice)
+        }
+    };
+
+    host.output_devices()
+        .map_err(|error| CpalBackendError::Device(error.to_string()))?
+        .find(|device| device.name().is_ok_and(|name| name == device_name))
+        .ok_or_else(|| CpalBackendError::DeviceNotFound(device_name.to_string()))
+}
+
+fn choose_config(
+    configs: Vec<SupportedStreamConfigRange>,
+    sample_rate: usize,
+) -> Result<SupportedStreamConfigRange, CpalBackendError> {
+    let sample_rate = SampleRate(sample_rate as u32);
+
+    configs
+        .into_iter()
+        .filter(|config| {
+            config.min_sample_rate() <= sample_rate && sample_rate <= config.max_sample_rate()
+        })
+        .min_by_key(|config| {
+            let format_rank = match config.sample_format() {
+                SampleFormat::F32 => 0,
+                SampleFormat::I16 => 1,
+                SampleFormat::U16 => 2,
+            };
+            let channel_distance = usize::from(config.channels()).abs_diff(ENGINE_CHANNELS);
+            (channel_distance, format_rank)
+        })
+        .ok_or(CpalBackendError::UnsupportedConfiguration)
+}
+
+fn negotiate_buffer_size(
+    supported: &SupportedBufferSize,
+    requested: Option<usize>,
+) -> Option<usize> {
+    let requested = requested?;
+
+    match supported {
+        SupportedBufferSize::Range { min, max } => {
+            Some(requested.clamp(*min as usize, *max as usize))
+        }
+        SupportedBufferSize::Unknown => Some(requested),
+    }
+}
+
+fn build_stream<T: Sample>(
+    device: &Device,
+    config: &StreamConfig,
+    mut renderer: OutputRenderer,
+) -> Result<Stream, CpalBackendError> {
+    device
+        .build_output_stream(
+            config,
+            move |data: &mut [T], _: &::cpal::OutputCallbackInfo| renderer.render(data),
+            |error| eprintln!("Stream error: {:?}", error),
+        )
+        .map_err(|error| CpalBackendError::Stream(error.to_string()))
+}
+
+struct OutputRenderer {
+    audio_process: Box<dyn AudioProcess + Send>,
+    sample_rate: usize,
+    num_device_channels: usize,
+    channel_matrix: Option<ChannelMatrix>,
+    engine_buffer: Vec<f32>,
+}
+
+impl OutputRenderer {
+    fn new(
+        audio_process: Box<dyn AudioProcess + Send>,
+        sample_rate: usize,
+        num_device_channels: usize,
+        channel_adaptation: ChannelAdaptation,
+        buffer_size: usize,
+    ) -> Self {
+        Self {
+            audio_process,
+            sample_rate,
+            num_device_channels,
+            channel_matrix: channel_adaptation.matrix(ENGINE_CHANNELS, num_device_channels),
+            engine_buffer: vec![0.0; buffer_size.max(1) * ENGINE_CHANNELS],
+        }
+    }
+
+    fn render<T: Sample>(&mut self, data: &mut [T]) {
+        let num_frames = data.len() / self.num_device_channels;
+        let maximum_frames = self.engine_buffer.len() / ENGINE_CHANNELS;
+
+        let mut offset = 0;
+        while offset < num_frames {
+            let block_frames = (num_frames - offset).min(maximum_frames);
+            let engine_samples = &mut self.engine_buffer[..block_frames * ENGINE_CHANNELS];
+
+            self.audio_process.process(&mut BorrowedAudioBuffer::new(
+                engine_samples,
+                ENGINE_CHANNELS,
+                self.sample_rate,
+            ));
+
+            for frame in 0..block_frames {
+                let engine_frame = &engine_samples[frame * ENGINE_CHANNELS..][..ENGINE_CHANNELS];
+                let device_frame = &mut data[(offset + frame) * self.num_device_channels..]
+                    [..self.num_device_channels];
+
+                for (channel, sample) in device_frame.iter_mut().enumerate() {
+                    let value = match &self.channel_matrix {
+                        Some(matrix) => engine_frame
+                            .iter()
+                            .enumerate()
+                            .map(|(source, value)| value * matrix.get_gain(source, channel))
+                            .sum(),
+                        None => engine_frame[channel],
+                    };
+
+                    *sample = T::from(&value);
+                }
+            }
+
+            offset += block_frames;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{AudioBuffer, DownmixLaw, SampleLocation, UpmixLaw};
+
+    use super::*;
+
+    struct Ramp {
+        frame: usize,
+    }
+
+    impl AudioProcess for Ramp {
+        fn process(&mut self, output_buffer: &mut dyn AudioBuffer) {
+            for frame in 0..output_buffer.num_frames() {
+                let value = self.frame as f32 / 1000.0;
+                output_buffer.set_sample(SampleLocation::new(0, frame), value);
+                output_buffer.set_sample(SampleLocation::new(1, frame), -value);
+                self.frame += 1;
+            }
+        }
+    }
+
+    fn renderer(num_device_channels: usize, adaptation: ChannelAdaptation) -> OutputRenderer {
+        OutputRenderer::new(
+            Box::new(Ramp { frame: 0 }),
+            48_000,
+            num_device_channels,
+            adaptation,
+            64,
+        )
+    }
+
+    #[test]
+    fn adapts_engine_output_to_device_channels() {
+        let mut stereo = renderer(2, ChannelAdaptation::default());
+        let mut data = vec![0.0_f32; 200 * 2];
+        stereo.render(&mut data);
+        assert_eq!(&data[2 * 150..2 * 151], &[0.15, -0.15]);
+
+        let mut mono = renderer(
+            1,
+            ChannelAdaptation::new(DownmixLaw::LeftOnly, UpmixLaw::default()),
+        );
+        let mut data = vec![0_i16; 200];
+        mono.render(&mut data);
+        assert_eq!(data[150], 0.15_f32.to_i16());
+
+        let mut quad = renderer(4, ChannelAdaptation::default());
+        let mut data = vec![1.0_f32; 100 * 4];
+        quad.render(&mut data);
+        assert_eq!(&data[4 * 70..4 * 71], &[0.07, -0.07, 0.0, 0.0]);
+    }
+}
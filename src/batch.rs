@@ -0,0 +1,220 @@
+use crate::{
+    buffer::{
+        audio_buffer::AudioBuffer, audio_buffer_slice::AudioBufferSlice,
+        immutable_audio_buffer_slice::ImmutableAudioBufferSlice,
+        owned_audio_buffer::OwnedAudioBuffer,
+    },
+    context::Context,
+    graph::{render_quality::RenderQuality, validation::GraphError},
+    utility::{
+        audio_file::{read_audio_file, write_wav_file, AudioFileError},
+        loudness::{normalize_loudness, LoudnessTarget},
+    },
+};
+
+const BLOCK_SIZE: usize = 512;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BatchProgress {
+    pub file_index: usize,
+    pub num_files: usize,
+    pub file_fraction: f64,
+}
+
+impl BatchProgress {
+    pub fn overall_fraction(&self) -> f64 {
+        if self.num_files == 0 {
+            return 1.0;
+        }
+
+        (self.file_index as f64 + self.file_fraction) / self.num_files as f64
+    }
+}
+
+#[derive(Debug)]
+pub enum BatchError {
+    MismatchedOutputs {
+        num_inputs: usize,
+        num_outputs: usize,
+    },
+    AudioFile(String, AudioFileError),
+    Graph(GraphError),
+}
+
+impl From<GraphError> for BatchError {
+    fn from(error: GraphError) -> Self {
+        BatchError::Graph(error)
+    }
+}
+
+pub fn process_files<G, K>(
+    inputs: &[&str],
+    graph_description: G,
+    outputs: &[&str],
+    progress: impl FnMut(BatchProgress),
+) -> Result<(), BatchError>
+where
+    G: Fn(&mut Context) -> Result<K, GraphError>,
+{
+    process_files_with_target(inputs, graph_description, outputs, None, progress)
+}
+
+pub fn process_files_normalized<G, K>(
+    inputs: &[&str],
+    graph_description: G,
+    outputs: &[&str],
+    target: &LoudnessTarget,
+    progress: impl FnMut(BatchProgress),
+) -> Result<(), BatchError>
+where
+    G: Fn(&mut Context) -> Result<K, GraphError>,
+{
+    process_files_with_target(inputs, graph_description, outputs, Some(target), progress)
+}
+
+fn process_files_with_target<G, K>(
+    inputs: &[&str],
+    graph_description: G,
+    outputs: &[&str],
+    target: Option<&LoudnessTarget>,
+    mut progress: impl FnMut(BatchProgress),
+) -> Result<(), BatchError>
+where
+    G: Fn(&mut Context) -> Result<K, GraphError>,
+{
+    if inputs.len() != outputs.len() {
+        return Err(BatchError::MismatchedOutputs {
+            num_inputs: inputs.len(),
+            num_outputs: outputs.len(),
+        });
+    }
+
+    let num_files = inputs.len();
+
+    for (file_index, (input_path, output_path)) in inputs.iter().zip(outputs.iter()).enumerate() {
+        let input = read_audio_file(input_path)
+            .map_err(|error| BatchError::AudioFile(input_path.to_string(), error))?;
+
+        let mut output = process_buffer(&input, &graph_description, |file_fraction| {
+            progress(BatchProgress {
+                file_index,
+                num_files,
+                file_fraction,
+            })
+        })?;
+
+        if let Some(target) = target {
+            normalize_loudness(&mut output, target);
+        }
+
+        write_wav_file(output_path, &output)
+            .map_err(|error| BatchError::AudioFile(output_path.to_string(), error))?;
+    }
+
+    Ok(())
+}
+
+fn process_buffer<G, K>(
+    input: &OwnedAudioBuffer,
+    graph_description: &G,
+    mut progress: impl FnMut(f64),
+) -> Result<OwnedAudioBuffer, GraphError>
+where
+    G: Fn(&mut Context) -> Result<K, GraphError>,
+{
+    let num_frames = input.num_frames();
+
+    let mut context = Context::new(input.sample_rate());
+    context.set_render_quality(RenderQuality::Best);
+    let mut audio_process = context.get_audio_process();
+
+    let _graph = graph_description(&mut context)?;
+
+    context.start();
+
+    let mut output = OwnedAudioBuffer::new(num_frames, input.num_channels(), input.sample_rate());
+
+    let mut position = 0;
+    while position < num_frames {
+        let block_size = BLOCK_SIZE.min(num_frames - position);
+
+        let input_block = ImmutableAudioBufferSlice::new(input, position);
+        let mut output_block = AudioBufferSlice::new(&mut output, position, block_size);
+        audio_process.process_with_input(&input_block, &mut output_block);
+
+        context.process_notifications();
+
+        position += block_size;
+        progress(position as f64 / num_frames as f64);
+    }
+
+    context.stop();
+
+    if num_frames == 0 {
+        progress(1.0);
+    }
+
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        buffer::sample_location::SampleLocation, dsp::gain::node::GainNode, graph::node::Node,
+        timestamp::Timestamp,
+    };
+
+    use super::*;
+
+    const SAMPLE_RATE: usize = 48_000;
+
+    #[test]
+    fn processes_each_file_through_the_graph() {
+        let directory = std::env::temp_dir().join(format!("batch-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let path = |name: &str| directory.join(name).to_string_lossy().into_owned();
+
+        for (name, value) in [("one.wav", 0.5), ("two.wav", -0.8)] {
+            let mut buffer = OwnedAudioBuffer::new(SAMPLE_RATE / 10, 2, SAMPLE_RATE);
+            buffer.fill_with_value(value);
+            write_wav_file(&path(name), &buffer).unwrap();
+        }
+
+        let mut reports = Vec::new();
+        process_files(
+            &[&path("one.wav"), &path("two.wav")],
+            |context| {
+                let mut gain = GainNode::new(context.get_command_queue());
+                gain.gain.set_value_at_time(0.5, Timestamp::zero());
+                context.connect_input_to(gain.get_id())?;
+                gain.connect_to_output()?;
+                Ok(gain)
+            },
+            &[&path("one-out.wav"), &path("two-out.wav")],
+            |progress| reports.push(progress),
+        )
+        .unwrap();
+
+        for (name, expected) in [("one-out.wav", 0.25), ("two-out.wav", -0.4)] {
+            let output = read_audio_file(&path(name)).unwrap();
+            assert_eq!(output.num_frames(), SAMPLE_RATE / 10);
+            assert_eq!(output.num_channels(), 2);
+            assert!((0..output.num_frames()).all(|frame| {
+                (output.get_sample(SampleLocation::new(1, frame)) - expected).abs() < 1e-6
+            }));
+        }
+
+        assert_eq!(reports.first().unwrap().file_index, 0);
+        assert_eq!(reports.last().unwrap().overall_fraction(), 1.0);
+        assert!(reports
+            .windows(2)
+            .all(|pair| pair[0].overall_fraction() <= pair[1].overall_fraction()));
+
+        assert!(matches!(
+            process_files(&[&path("one.wav")], |_| Ok(()), &[], |_| {}),
+            Err(BatchError::MismatchedOutputs { .. })
+        ));
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -0,0 +1,163 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+};
+
+use memmap2::Mmap;
+
+use super::{audio_buffer::AudioBuffer, sample_location::SampleLocation};
+
+const CACHE_FILE_MAGIC: &[u8; 8] = b"RAECACHE";
+const CACHE_FILE_HEADER_SIZE: usize = 16;
+const BYTES_PER_SAMPLE: usize = std::mem::size_of::<f32>();
+
+pub struct MappedAudioBuffer {
+    map: Mmap,
+    data_offset: usize,
+    num_channels: usize,
+    num_frames: usize,
+    sample_rate: usize,
+}
+
+impl MappedAudioBuffer {
+    pub fn open_raw(path: &str, num_channels: usize, sample_rate: usize) -> io::Result<Self> {
+        let map = Self::map_file(path)?;
+        Self::from_map(map, 0, num_channels, sample_rate)
+    }
+
+    pub fn open_cache_file(path: &str) -> io::Result<Self> {
+        let map = Self::map_file(path)?;
+
+        if map.len() < CACHE_FILE_HEADER_SIZE || &map[0..8] != CACHE_FILE_MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Not an audio cache file",
+            ));
+        }
+
+        let num_channels = u32::from_le_bytes([map[8], map[9], map[10], map[11]]) as usize;
+        let sample_rate = u32::from_le_bytes([map[12], map[13], map[14], map[15]]) as usize;
+
+        Self::from_map(map, CACHE_FILE_HEADER_SIZE, num_channels, sample_rate)
+    }
+
+    pub fn write_cache_file(path: &str, buffer: &dyn AudioBuffer) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+
+        writer.write_all(CACHE_FILE_MAGIC)?;
+        writer.write_all(&(buffer.num_channels() as u32).to_le_bytes())?;
+        writer.write_all(&(buffer.sample_rate() as u32).to_le_bytes())?;
+
+        for frame in 0..buffer.num_frames() {
+            for channel in 0..buffer.num_channels() {
+                let value = buffer.get_sample(SampleLocation::new(channel, frame));
+                writer.write_all(&value.to_le_bytes())?;
+            }
+        }
+
+        writer.flush()
+    }
+
+    fn map_file(path: &str) -> io::Result<Mmap> {
+        let file = File::open(path)?;
+
+        // Safety: the mapping is read-only, and cache files are not expected to be
+        // modified while they are in use
+        unsafe { Mmap::map(&file) }
+    }
+
+    fn from_map(
+        map: Mmap,
+        data_offset: usize,
+        num_channels: usize,
+        sample_rate: usize,
+    ) -> io::Result<Self> {
+        if num_channels == 0 || sample_rate == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Invalid audio format",
+            ));
+        }
+
+        let num_frames = (map.len() - data_offset) / (num_channels * BYTES_PER_SAMPLE);
+
+        Ok(Self {
+            map,
+            data_offset,
+            num_channels,
+            num_frames,
+            sample_rate,
+        })
+    }
+}
+
+impl AudioBuffer for MappedAudioBuffer {
+    fn num_channels(&self) -> usize {
+        self.num_channels
+    }
+
+    fn num_frames(&self) -> usize {
+        self.num_frames
+    }
+
+    fn sample_rate(&self) -> usize {
+        self.sample_rate
+    }
+
+    fn clear(&mut self) {
+        debug_assert!(false)
+    }
+
+    fn set_sample(&mut self, _sample_location: SampleLocation, _value: f32) {
+        debug_assert!(false)
+    }
+
+    fn add_sample(&mut self, _sample_location: SampleLocation, _value: f32) {
+        debug_assert!(false)
+    }
+
+    fn get_sample(&self, sample_location: SampleLocation) -> f32 {
+        debug_assert!(sample_location.channel < self.num_channels);
+        debug_assert!(sample_location.frame < self.num_frames);
+
+        let index = sample_location.frame * self.num_channels + sample_location.channel;
+        let offset = self.data_offset + index * BYTES_PER_SAMPLE;
+
+        f32::from_le_bytes([
+            self.map[offset],
+            self.map[offset + 1],
+            self.map[offset + 2],
+            self.map[offset + 3],
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::owned_audio_buffer::OwnedAudioBuffer;
+
+    use super::*;
+
+    #[test]
+    fn reads_back_cache_file() {
+        let mut buffer = OwnedAudioBuffer::new(64, 2, 48000);
+        for frame in 0..64 {
+            buffer.set_sample(SampleLocation::new(0, frame), frame as f32);
+            buffer.set_sample(SampleLocation::new(1, frame), -(frame as f32));
+        }
+
+        let path = std::env::temp_dir().join(format!("mapped-{}.cache", std::process::id()));
+        let path = path.to_str().unwrap();
+
+        MappedAudioBuffer::write_cache_file(path, &buffer).unwrap();
+        let mapped = MappedAudioBuffer::open_cache_file(path).unwrap();
+
+        assert_eq!(mapped.num_channels(), 2);
+        assert_eq!(mapped.num_frames(), 64);
+        assert_eq!(mapped.sample_rate(), 48000);
+        assert_eq!(mapped.get_sample(SampleLocation::new(0, 10)), 10.0);
+        assert_eq!(mapped.get_sample(SampleLocation::new(1, 63)), -63.0);
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -2,5 +2,8 @@ pub mod audio_buffer;
 pub mod audio_buffer_slice;
 pub mod borrowed_audio_buffer;
 pub mod immutable_audio_buffer_slice;
+pub mod mapped_audio_buffer;
 pub mod owned_audio_buffer;
 pub mod sample_location;
+pub mod sample_store;
+pub mod streaming_audio_buffer;
@@ -1,3 +1,5 @@
+use crate::utility::audio_file::{read_audio_file, AudioFileError};
+
 use super::{audio_buffer::AudioBuffer, sample_location::SampleLocation};
 
 pub struct OwnedAudioBuffer {
@@ -23,6 +25,14 @@ impl OwnedAudioBuffer {
         }
     }
 
+    pub fn from_wav_file(path: &str) -> Result<Self, AudioFileError> {
+        read_audio_file(path)
+    }
+
+    pub fn truncate(&mut self, num_frames: usize) {
+        self.data.truncate(num_frames * self.num_channels);
+    }
+
     fn get_offset(&self, sample_location: SampleLocation) -> usize {
         debug_assert!(sample_location.channel < self.num_channels);
         debug_assert!(sample_location.frame < self.num_frames());
@@ -0,0 +1,216 @@
+use std::{collections::HashMap, sync::Arc};
+
+use crate::utility::audio_file::{read_audio_file, AudioFileError};
+
+use super::{audio_buffer::AudioBuffer, owned_audio_buffer::OwnedAudioBuffer};
+
+pub type EvictionHook = Box<dyn FnMut(&str, usize) + Send>;
+
+struct StoredSample {
+    buffer: Arc<OwnedAudioBuffer>,
+    size_in_bytes: usize,
+    last_used: u64,
+}
+
+#[derive(Default)]
+pub struct SampleStore {
+    samples: HashMap<String, StoredSample>,
+    memory_budget: Option<usize>,
+    eviction_hooks: Vec<EvictionHook>,
+    use_counter: u64,
+}
+
+fn size_in_bytes(buffer: &OwnedAudioBuffer) -> usize {
+    buffer.num_frames() * buffer.num_channels() * std::mem::size_of::<f32>()
+}
+
+impl SampleStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_memory_budget(memory_budget_in_bytes: usize) -> Self {
+        Self {
+            memory_budget: Some(memory_budget_in_bytes),
+            ..Default::default()
+        }
+    }
+
+    pub fn add_eviction_hook(&mut self, hook: impl FnMut(&str, usize) + Send + 'static) {
+        self.eviction_hooks.push(Box::new(hook));
+    }
+
+    pub fn load(&mut self, path: &str) -> Result<Arc<OwnedAudioBuffer>, AudioFileError> {
+        if let Some(buffer) = self.get(path) {
+            return Ok(buffer);
+        }
+
+        let buffer = read_audio_file(path)?;
+        Ok(self.insert(path, buffer))
+    }
+
+    pub fn insert(&mut self, key: &str, buffer: OwnedAudioBuffer) -> Arc<OwnedAudioBuffer> {
+        self.use_counter += 1;
+
+        let size_in_bytes = size_in_bytes(&buffer);
+        let buffer = Arc::new(buffer);
+
+        if let Some(previous) = self.samples.insert(
+            key.to_string(),
+            StoredSample {
+                buffer: buffer.clone(),
+                size_in_bytes,
+                last_used: self.use_counter,
+            },
+        ) {
+            self.notify_eviction(key, previous.size_in_bytes);
+        }
+
+        self.enforce_memory_budget();
+        buffer
+    }
+
+    pub fn get(&mut self, key: &str) -> Option<Arc<OwnedAudioBuffer>> {
+        self.use_counter += 1;
+        let use_counter = self.use_counter;
+
+        self.samples.get_mut(key).map(|sample| {
+            sample.last_used = use_counter;
+            sample.buffer.clone()
+        })
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.samples.contains_key(key)
+    }
+
+    pub fn remove(&mut self, key: &str) -> bool {
+        match self.samples.remove(key) {
+            Some(sample) => {
+                self.notify_eviction(key, sample.size_in_bytes);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn memory_usage(&self) -> usize {
+        self.samples
+            .values()
+            .map(|sample| sample.size_in_bytes)
+            .sum()
+    }
+
+    pub fn num_samples(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_in_use(&self, key: &str) -> bool {
+        self.samples
+            .get(key)
+            .map(|sample| Arc::strong_count(&sample.buffer) > 1)
+            .unwrap_or(false)
+    }
+
+    pub fn evict_unused(&mut self) -> usize {
+        let unused: Vec<String> = self
+            .samples
+            .iter()
+            .filter(|(_, sample)| Arc::strong_count(&sample.buffer) == 1)
+            .map(|(key, _)| key.clone())
+            .collect();
+
+        let mut freed = 0;
+        for key in unused {
+            if let Some(sample) = self.samples.remove(&key) {
+                freed += sample.size_in_bytes;
+                self.notify_eviction(&key, sample.size_in_bytes);
+            }
+        }
+
+        freed
+    }
+
+    fn enforce_memory_budget(&mut self) {
+        let memory_budget = match self.memory_budget {
+            Some(memory_budget) => memory_budget,
+            None => return,
+        };
+
+        while self.memory_usage() > memory_budget {
+            let least_recently_used = self
+                .samples
+                .iter()
+                .filter(|(_, sample)| Arc::strong_count(&sample.buffer) == 1)
+                .min_by_key(|(_, sample)| sample.last_used)
+                .map(|(key, _)| key.clone());
+
+            match least_recently_used {
+                Some(key) => {
+                    self.remove(&key);
+                }
+                None => break,
+            }
+        }
+    }
+
+    fn notify_eviction(&mut self, key: &str, size_in_bytes: usize) {
+        for hook in self.eviction_hooks.iter_mut() {
+            hook(key, size_in_bytes);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    fn make_buffer(num_frames: usize) -> OwnedAudioBuffer {
+        OwnedAudioBuffer::new(num_frames, 2, 44100)
+    }
+
+    #[test]
+    fn shares_buffers_and_accounts_memory() {
+        let mut store = SampleStore::new();
+
+        let first = store.insert("kick", make_buffer(100));
+        let second = store.get("kick").unwrap();
+
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(store.memory_usage(), 100 * 2 * 4);
+        assert!(store.is_in_use("kick"));
+    }
+
+    #[test]
+    fn evicts_only_unused_samples() {
+        let evicted = Arc::new(Mutex::new(Vec::new()));
+
+        let mut store = SampleStore::new();
+        let hook_evicted = evicted.clone();
+        store.add_eviction_hook(move |key, _| hook_evicted.lock().unwrap().push(key.to_string()));
+
+        let _kick = store.insert("kick", make_buffer(100));
+        store.insert("snare", make_buffer(100));
+
+        assert_eq!(store.evict_unused(), 100 * 2 * 4);
+        assert!(store.contains("kick"));
+        assert!(!store.contains("snare"));
+        assert_eq!(*evicted.lock().unwrap(), vec!["snare".to_string()]);
+    }
+
+    #[test]
+    fn memory_budget_evicts_least_recently_used() {
+        let mut store = SampleStore::with_memory_budget(2 * 100 * 2 * 4);
+
+        store.insert("a", make_buffer(100));
+        store.insert("b", make_buffer(100));
+        store.get("a");
+        store.insert("c", make_buffer(100));
+
+        assert!(store.contains("a"));
+        assert!(!store.contains("b"));
+        assert!(store.contains("c"));
+    }
+}
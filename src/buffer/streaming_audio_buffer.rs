@@ -0,0 +1,109 @@
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+
+use super::{audio_buffer::AudioBuffer, sample_location::SampleLocation};
+
+pub struct StreamingAudioBuffer {
+    data: Vec<AtomicU32>,
+    num_channels: usize,
+    sample_rate: usize,
+    frames_available: AtomicUsize,
+}
+
+impl StreamingAudioBuffer {
+    pub fn new(num_frames: usize, num_channels: usize, sample_rate: usize) -> Self {
+        Self {
+            data: (0..num_frames * num_channels)
+                .map(|_| AtomicU32::new(0))
+                .collect(),
+            num_channels,
+            sample_rate,
+            frames_available: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn frames_available(&self) -> usize {
+        self.frames_available.load(Ordering::Acquire)
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.frames_available() >= self.num_frames()
+    }
+
+    pub fn progress(&self) -> f64 {
+        if self.num_frames() == 0 {
+            return 1.0;
+        }
+
+        self.frames_available() as f64 / self.num_frames() as f64
+    }
+
+    pub fn append_interleaved(&self, samples: &[f32]) -> usize {
+        let start_frame = self.frames_available();
+        let start = start_frame * self.num_channels;
+        let end = (start + samples.len()).min(self.data.len());
+
+        for (destination, value) in self.data[start..end].iter().zip(samples) {
+            destination.store(value.to_bits(), Ordering::Relaxed);
+        }
+
+        let frames_written = (end - start) / self.num_channels;
+        self.frames_available
+            .store(start_frame + frames_written, Ordering::Release);
+        frames_written
+    }
+}
+
+impl AudioBuffer for StreamingAudioBuffer {
+    fn num_channels(&self) -> usize {
+        self.num_channels
+    }
+
+    fn num_frames(&self) -> usize {
+        self.data.len() / self.num_channels
+    }
+
+    fn sample_rate(&self) -> usize {
+        self.sample_rate
+    }
+
+    fn clear(&mut self) {
+        debug_assert!(false)
+    }
+
+    fn set_sample(&mut self, _sample_location: SampleLocation, _value: f32) {
+        debug_assert!(false)
+    }
+
+    fn add_sample(&mut self, _sample_location: SampleLocation, _value: f32) {
+        debug_assert!(false)
+    }
+
+    fn get_sample(&self, sample_location: SampleLocation) -> f32 {
+        if sample_location.frame >= self.frames_available() {
+            return 0.0;
+        }
+
+        let index = sample_location.frame * self.num_channels + sample_location.channel;
+        f32::from_bits(self.data[index].load(Ordering::Relaxed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unwritten_frames_are_silent() {
+        let buffer = StreamingAudioBuffer::new(4, 2, 44100);
+        assert_eq!(buffer.get_sample(SampleLocation::new(0, 0)), 0.0);
+
+        assert_eq!(buffer.append_interleaved(&[1.0, 2.0, 3.0, 4.0]), 2);
+        assert_eq!(buffer.frames_available(), 2);
+        assert_eq!(buffer.get_sample(SampleLocation::new(1, 1)), 4.0);
+        assert_eq!(buffer.get_sample(SampleLocation::new(0, 2)), 0.0);
+
+        buffer.append_interleaved(&[5.0, 6.0, 7.0, 8.0, 9.0, 10.0]);
+        assert!(buffer.is_complete());
+        assert_eq!(buffer.get_sample(SampleLocation::new(0, 3)), 7.0);
+    }
+}
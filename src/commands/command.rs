@@ -1,8 +1,18 @@
 use crate::{
-    graph::{connection::Connection, dsp::Dsp, endpoint::Endpoint},
-    parameter::ParameterChange,
+    graph::{
+        connection::Connection, dsp::Dsp, endpoint::Endpoint, input_mapping::InputMapping,
+        realtime_budget::RealtimeBudget, render_quality::RenderQuality,
+    },
+    parameter::{
+        gesture::ParameterGesture, modulation::ModulationCommand, tempo_sync::TempoSync,
+        ParameterChange,
+    },
+    tempo_map::TempoMap,
+    timestamp::Timestamp,
 };
 
+use std::time::Duration;
+
 use super::id::Id;
 
 pub struct ParameterChangeRequest {
@@ -11,16 +21,95 @@ pub struct ParameterChangeRequest {
     pub change: ParameterChange,
 }
 
+pub struct ParameterTempoSyncRequest {
+    pub dsp_id: Id,
+    pub parameter_id: Id,
+    pub sync: Option<TempoSync>,
+    pub minimum: f64,
+    pub maximum: f64,
+}
+
 pub enum Command {
     Start,
     Stop,
+    Suspend,
+    Resume,
+
+    EnableProfiling,
+    DisableProfiling,
 
     AddDsp(Box<Dsp>),
     RemoveDsp(Id),
+    DetachDsp(Id),
+    ReattachDsp(Id),
+    StopDsp(Id, Timestamp, bool),
+    SetDspPriority(Id, i32),
 
     ParameterValueChange(ParameterChangeRequest),
+    ParameterValueChanges(Vec<ParameterChangeRequest>),
+    ParameterTempoSync(ParameterTempoSyncRequest),
+    ParameterGesture(ParameterGesture),
+    SetAutomationRecording(bool),
+    Modulation(ModulationCommand),
+    SetTempoMap(Box<TempoMap>),
+    SetTransportLoop(Option<(f64, f64)>),
+    SetRenderQuality(RenderQuality),
+    SetRealtimeBudget(Option<RealtimeBudget>),
 
     AddConnection(Connection),
     RemoveConnection(Connection),
+    ScheduleConnection(Connection, Timestamp),
+    ScheduleDisconnection(Connection, Timestamp),
+    SetConnectionFadeTime(Duration),
     ConnectToOutput(Endpoint),
+    DisconnectFromOutput,
+    ConnectInput(Id),
+    DisconnectInput(Id),
+    ConnectOutputMix(Id),
+    DisconnectOutputMix(Id),
+    SetInputMapping(Option<InputMapping>),
+
+    At(Timestamp, Box<Command>),
+}
+
+impl Command {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Command::Start => "Start",
+            Command::Stop => "Stop",
+            Command::Suspend => "Suspend",
+            Command::Resume => "Resume",
+            Command::EnableProfiling => "EnableProfiling",
+            Command::DisableProfiling => "DisableProfiling",
+            Command::AddDsp(_) => "AddDsp",
+            Command::RemoveDsp(_) => "RemoveDsp",
+            Command::DetachDsp(_) => "DetachDsp",
+            Command::ReattachDsp(_) => "ReattachDsp",
+            Command::StopDsp(..) => "StopDsp",
+            Command::SetDspPriority(..) => "SetDspPriority",
+            Command::ParameterValueChange(_) => "ParameterValueChange",
+            Command::ParameterValueChanges(_) => "ParameterValueChanges",
+            Command::ParameterTempoSync(_) => "ParameterTempoSync",
+            Command::ParameterGesture(_) => "ParameterGesture",
+            Command::SetAutomationRecording(_) => "SetAutomationRecording",
+            Command::Modulation(_) => "Modulation",
+            Command::SetTempoMap(_) => "SetTempoMap",
+            Command::SetTransportLoop(_) => "SetTransportLoop",
+            Command::SetRenderQuality(_) => "SetRenderQuality",
+            Command::SetRealtimeBudget(_) => "SetRealtimeBudget",
+            Command::AddConnection(_) => "AddConnection",
+            Command::RemoveConnection(_) => "RemoveConnection",
+            Command::ScheduleConnection(..) => "ScheduleConnection",
+            Command::ScheduleDisconnection(..) => "ScheduleDisconnection",
+            Command::SetConnectionFadeTime(_) => "SetConnectionFadeTime",
+            Command::ConnectToOutput(_) => "ConnectToOutput",
+            Command::DisconnectFromOutput => "DisconnectFromOutput",
+            Command::ConnectInput(_) => "ConnectInput",
+            Command::DisconnectInput(_) => "DisconnectInput",
+            Command::ConnectOutputMix(_) => "ConnectOutputMix",
+            Command::DisconnectOutputMix(_) => "DisconnectOutputMix",
+            Command::SetInputMapping(_) => "SetInputMapping",
+            Command::At(..) => "At",
+        }
+    }
 }
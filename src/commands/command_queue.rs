@@ -0,0 +1,246 @@
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use atomic_float::AtomicF64;
+use lockfree::channel::mpsc::{self, Receiver, Sender};
+
+use crate::{
+    graph::{
+        connection::Connection,
+        endpoint::Endpoint,
+        validation::{self, GraphError, GraphSnapshot, GraphState},
+    },
+    timestamp::Timestamp,
+};
+
+use super::{
+    command::Command,
+    id::Id,
+    journal::{CommandJournal, JournalEntry},
+};
+
+#[derive(Clone)]
+pub struct CommandQueue {
+    sender: Sender<Command>,
+    graph_state: Arc<Mutex<GraphState>>,
+    journal: Arc<Mutex<CommandJournal>>,
+    clock: Arc<AtomicF64>,
+}
+
+impl CommandQueue {
+    pub fn create() -> (Self, Receiver<Command>) {
+        Self::with_clock(Arc::new(AtomicF64::new(0.0)))
+    }
+
+    pub fn create_subgraph(&self) -> (Self, Receiver<Command>) {
+        Self::with_clock(self.clock.clone())
+    }
+
+    fn with_clock(clock: Arc<AtomicF64>) -> (Self, Receiver<Command>) {
+        let (sender, receiver) = mpsc::create();
+
+        let queue = Self {
+            sender,
+            graph_state: validation::create_graph_state(),
+            journal: Arc::new(Mutex::new(CommandJournal::default())),
+            clock,
+        };
+
+        (queue, receiver)
+    }
+
+    pub fn send(&self, command: Command) -> bool {
+        self.sender.send(command).is_ok()
+    }
+
+    pub fn shares_graph_with(&self, other: &CommandQueue) -> bool {
+        Arc::ptr_eq(&self.graph_state, &other.graph_state)
+    }
+
+    pub fn set_time(&self, time: Timestamp) {
+        self.clock
+            .store(time.get_seconds(), std::sync::atomic::Ordering::Release);
+    }
+
+    fn graph_state(&self) -> MutexGuard<'_, GraphState> {
+        let mut state = validation::lock(&self.graph_state);
+        let time = self.clock.load(std::sync::atomic::Ordering::Acquire);
+        state.advance_to(Timestamp::from_seconds(time));
+        state
+    }
+
+    pub fn validate(&self, command: &Command) -> Result<(), GraphError> {
+        self.graph_state().validate(command)
+    }
+
+    pub fn record(&self, command: &Command) {
+        self.graph_state().record(command);
+    }
+
+    pub fn send_recorded(&self, command: Command) {
+        self.record(&command);
+        let _ = self.send(command);
+    }
+
+    pub fn send_validated(&self, command: Command) -> Result<(), GraphError> {
+        self.graph_state().validate_and_record(&command)?;
+        let _ = self.send(command);
+        Ok(())
+    }
+
+    pub fn try_apply(&self, entry: JournalEntry) -> Result<(), GraphError> {
+        self.validate(&entry.to_command())?;
+        self.apply(entry);
+        Ok(())
+    }
+
+    pub(crate) fn connect(&self, connection: Connection) -> Result<(), GraphError> {
+        self.try_apply(JournalEntry::AddConnection(connection))
+    }
+
+    pub(crate) fn disconnect(&self, source: Id, destination: Id) -> Result<(), GraphError> {
+        let connection = self
+            .connection(source, destination)
+            .unwrap_or_else(|| Connection::new(source, destination));
+        self.try_apply(JournalEntry::RemoveConnection(connection))
+    }
+
+    pub fn try_apply_at(&self, time: Timestamp, entry: JournalEntry) -> Result<(), GraphError> {
+        let command = Command::At(time, Box::new(entry.to_command()));
+        self.validate(&command)?;
+        self.send_recorded(command);
+        self.journal(entry);
+        Ok(())
+    }
+
+    pub fn apply(&self, entry: JournalEntry) {
+        self.send_recorded(entry.to_command());
+        self.journal(entry);
+    }
+
+    pub fn journal(&self, entry: JournalEntry) {
+        validation::lock(&self.journal).record(entry);
+        self.release_dsps();
+    }
+
+    pub fn undo(&self) -> bool {
+        let command = validation::lock(&self.journal).undo();
+        command.map(|command| self.send_recorded(command)).is_some()
+    }
+
+    pub fn redo(&self) -> bool {
+        let command = validation::lock(&self.journal).redo();
+        command.map(|command| self.send_recorded(command)).is_some()
+    }
+
+    pub fn can_undo(&self) -> bool {
+        validation::lock(&self.journal).can_undo()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        validation::lock(&self.journal).can_redo()
+    }
+
+    pub fn clear_journal(&self) {
+        validation::lock(&self.journal).clear();
+        self.release_dsps();
+    }
+
+    fn release_dsps(&self) {
+        let released = validation::lock(&self.journal).take_released();
+        for id in released {
+            self.send_recorded(Command::RemoveDsp(id));
+        }
+    }
+
+    pub fn contains(&self, id: Id) -> bool {
+        self.graph_state().contains(id)
+    }
+
+    pub fn output_endpoint(&self) -> Option<Endpoint> {
+        self.graph_state().output()
+    }
+
+    pub fn is_connected(&self, source: Id, destination: Id) -> bool {
+        self.graph_state().is_connected(source, destination)
+    }
+
+    pub fn connection(&self, source: Id, destination: Id) -> Option<Connection> {
+        self.graph_state().connection(source, destination)
+    }
+
+    pub fn snapshot_upstream(&self, root: Id) -> GraphSnapshot {
+        self.graph_state().snapshot_upstream(root)
+    }
+
+    pub fn describe_upstream(&self, root: Id) -> String {
+        self.graph_state().describe_upstream(root)
+    }
+
+    pub fn set_tag(&self, id: Id, tag: &str) {
+        self.graph_state().set_tag(id, tag);
+    }
+
+    pub fn clear_tag(&self, id: Id) {
+        self.graph_state().clear_tag(id);
+    }
+
+    pub fn tag(&self, id: Id) -> Option<String> {
+        self.graph_state().tag(id)
+    }
+
+    pub fn set_channel_counts(
+        &self,
+        id: Id,
+        num_input_channels: usize,
+        num_output_channels: usize,
+    ) {
+        self.graph_state()
+            .set_channel_counts(id, num_input_channels, num_output_channels);
+    }
+
+    pub fn clear_channel_counts(&self, id: Id) {
+        self.graph_state().clear_channel_counts(id);
+    }
+
+    pub fn input_channel_count(&self, id: Id) -> Option<usize> {
+        self.graph_state().input_channel_count(id)
+    }
+
+    pub fn output_channel_count(&self, id: Id) -> Option<usize> {
+        self.graph_state().output_channel_count(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        commands::{command::Command, journal::JournalEntry},
+        Gain, Node, Oscillator,
+    };
+
+    use super::CommandQueue;
+
+    #[test]
+    fn journals_node_connections_and_releases_removed_dsps() {
+        let (command_queue, mut receiver) = CommandQueue::create();
+
+        let oscillator = Oscillator::new(command_queue.clone(), 440.0);
+        let gain = Gain::new(command_queue.clone());
+
+        assert_eq!(oscillator.connect_to_with(gain.get_id(), 1.0, None), Ok(()));
+        assert!(command_queue.is_connected(oscillator.get_id(), gain.get_id()));
+        assert!(command_queue.undo());
+        assert!(!command_queue.is_connected(oscillator.get_id(), gain.get_id()));
+
+        command_queue.apply(JournalEntry::DetachDsp(gain.get_id()));
+        command_queue.clear_journal();
+
+        let mut removed = 0;
+        while let Ok(command) = receiver.recv() {
+            if matches!(command, Command::RemoveDsp(id) if id == gain.get_id()) {
+                removed += 1;
+            }
+        }
+        assert_eq!(removed, 1);
+    }
+}
@@ -0,0 +1,265 @@
+use crate::{
+    graph::{connection::Connection, endpoint::Endpoint},
+    parameter::ParameterChange,
+    timestamp::Timestamp,
+};
+
+use super::{
+    command::{Command, ParameterChangeRequest},
+    id::Id,
+};
+
+const DEFAULT_JOURNAL_CAPACITY: usize = 256;
+
+#[derive(Clone, PartialEq)]
+pub enum JournalEntry {
+    AddConnection(Connection),
+    RemoveConnection(Connection),
+    ConnectToOutput {
+        previous: Option<Endpoint>,
+        endpoint: Option<Endpoint>,
+    },
+    AddDsp(Id),
+    DetachDsp(Id),
+    ReattachDsp(Id),
+    ParameterValueChange {
+        dsp_id: Id,
+        parameter_id: Id,
+        previous_value: f64,
+        value: f64,
+    },
+}
+
+impl JournalEntry {
+    pub fn inverse(&self) -> Self {
+        match self {
+            JournalEntry::AddConnection(connection) => {
+                JournalEntry::RemoveConnection(connection.clone())
+            }
+            JournalEntry::RemoveConnection(connection) => {
+                JournalEntry::AddConnection(connection.clone())
+            }
+            JournalEntry::ConnectToOutput { previous, endpoint } => JournalEntry::ConnectToOutput {
+                previous: *endpoint,
+                endpoint: *previous,
+            },
+            JournalEntry::AddDsp(id) => JournalEntry::DetachDsp(*id),
+            JournalEntry::DetachDsp(id) => JournalEntry::ReattachDsp(*id),
+            JournalEntry::ReattachDsp(id) => JournalEntry::DetachDsp(*id),
+            JournalEntry::ParameterValueChange {
+                dsp_id,
+                parameter_id,
+                previous_value,
+                value,
+            } => JournalEntry::ParameterValueChange {
+                dsp_id: *dsp_id,
+                parameter_id: *parameter_id,
+                previous_value: *value,
+                value: *previous_value,
+            },
+        }
+    }
+
+    pub fn to_command(&self) -> Command {
+        match self {
+            JournalEntry::AddConnection(connection) => Command::AddConnection(connection.clone()),
+            JournalEntry::RemoveConnection(connection) => {
+                Command::RemoveConnection(connection.clone())
+            }
+            JournalEntry::ConnectToOutput { endpoint, .. } => match endpoint {
+                Some(endpoint) => Command::ConnectToOutput(*endpoint),
+                None => Command::DisconnectFromOutput,
+            },
+            JournalEntry::AddDsp(id) | JournalEntry::ReattachDsp(id) => Command::ReattachDsp(*id),
+            JournalEntry::DetachDsp(id) => Command::DetachDsp(*id),
+            JournalEntry::ParameterValueChange {
+                dsp_id,
+                parameter_id,
+                value,
+                ..
+            } => Command::ParameterValueChange(ParameterChangeRequest {
+                dsp_id: *dsp_id,
+                parameter_id: *parameter_id,
+                change: ParameterChange::immediate(*value, Timestamp::zero()),
+            }),
+        }
+    }
+}
+
+pub struct CommandJournal {
+    undo_stack: Vec<JournalEntry>,
+    redo_stack: Vec<JournalEntry>,
+    released: Vec<Id>,
+    capacity: usize,
+}
+
+impl Default for CommandJournal {
+    fn default() -> Self {
+        Self::with_capacity(DEFAULT_JOURNAL_CAPACITY)
+    }
+}
+
+impl CommandJournal {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            undo_stack: Vec::with_capacity(capacity),
+            redo_stack: Vec::with_capacity(capacity),
+            released: Vec::new(),
+            capacity,
+        }
+    }
+
+    pub fn record(&mut self, entry: JournalEntry) {
+        self.clear_redo();
+
+        if self.undo_stack.len() == self.capacity {
+            let evicted = self.undo_stack.remove(0);
+            self.release_if_detached(&evicted);
+        }
+
+        self.undo_stack.push(entry);
+    }
+
+    fn clear_redo(&mut self) {
+        for entry in std::mem::take(&mut self.redo_stack) {
+            if let JournalEntry::AddDsp(id) = entry {
+                self.released.push(id);
+            }
+        }
+    }
+
+    fn release_if_detached(&mut self, entry: &JournalEntry) {
+        if let JournalEntry::DetachDsp(id) = entry {
+            self.released.push(*id);
+        }
+    }
+
+    pub fn take_released(&mut self) -> Vec<Id> {
+        std::mem::take(&mut self.released)
+    }
+
+    pub fn undo(&mut self) -> Option<Command> {
+        let entry = self.undo_stack.pop()?;
+        let command = entry.inverse().to_command();
+        self.redo_stack.push(entry);
+        Some(command)
+    }
+
+    pub fn redo(&mut self) -> Option<Command> {
+        let entry = self.redo_stack.pop()?;
+        let command = entry.to_command();
+        self.undo_stack.push(entry);
+        Some(command)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.clear_redo();
+
+        for entry in std::mem::take(&mut self.undo_stack) {
+            self.release_if_detached(&entry);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn connection() -> Connection {
+        Connection::new(Id::generate(), Id::generate())
+    }
+
+    #[test]
+    fn undo_adding_a_connection_removes_it() {
+        let mut journal = CommandJournal::default();
+        let connection = connection();
+        journal.record(JournalEntry::AddConnection(connection.clone()));
+
+        match journal.undo() {
+            Some(Command::RemoveConnection(removed)) => assert!(removed == connection),
+            _ => panic!("Expected RemoveConnection"),
+        }
+
+        match journal.redo() {
+            Some(Command::AddConnection(added)) => assert!(added == connection),
+            _ => panic!("Expected AddConnection"),
+        }
+    }
+
+    #[test]
+    fn undo_parameter_change_restores_previous_value() {
+        let mut journal = CommandJournal::default();
+        journal.record(JournalEntry::ParameterValueChange {
+            dsp_id: Id::generate(),
+            parameter_id: Id::generate(),
+            previous_value: 0.25,
+            value: 0.75,
+        });
+
+        let entry = journal.undo_stack.last().unwrap().inverse();
+        match entry {
+            JournalEntry::ParameterValueChange {
+                previous_value,
+                value,
+                ..
+            } => {
+                approx::assert_relative_eq!(previous_value, 0.75);
+                approx::assert_relative_eq!(value, 0.25);
+            }
+            _ => panic!("Expected ParameterValueChange"),
+        }
+    }
+
+    #[test]
+    fn recording_clears_redo() {
+        let mut journal = CommandJournal::default();
+        journal.record(JournalEntry::DetachDsp(Id::generate()));
+        assert!(journal.undo().is_some());
+        assert!(journal.can_redo());
+
+        journal.record(JournalEntry::DetachDsp(Id::generate()));
+        assert!(!journal.can_redo());
+        assert!(journal.redo().is_none());
+    }
+
+    #[test]
+    fn discards_oldest_entries_beyond_capacity() {
+        let mut journal = CommandJournal::with_capacity(2);
+        let first = Id::generate();
+        journal.record(JournalEntry::DetachDsp(first));
+        journal.record(JournalEntry::DetachDsp(Id::generate()));
+        journal.record(JournalEntry::DetachDsp(Id::generate()));
+
+        assert!(journal.undo().is_some());
+        assert!(journal.undo().is_some());
+        assert!(journal.undo().is_none());
+    }
+
+    #[test]
+    fn releases_detached_dsps_when_entries_leave_the_journal() {
+        let mut journal = CommandJournal::with_capacity(1);
+        let removed = Id::generate();
+        let added = Id::generate();
+
+        journal.record(JournalEntry::DetachDsp(removed));
+        journal.record(JournalEntry::AddDsp(added));
+        assert_eq!(journal.take_released(), vec![removed]);
+
+        match journal.undo() {
+            Some(Command::DetachDsp(id)) => assert_eq!(id, added),
+            _ => panic!("Expected DetachDsp"),
+        }
+
+        journal.clear();
+        assert_eq!(journal.take_released(), vec![added]);
+        assert!(journal.take_released().is_empty());
+    }
+}
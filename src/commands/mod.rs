@@ -1,3 +1,5 @@
 pub(crate) mod command;
+pub(crate) mod command_queue;
 pub(crate) mod id;
+pub(crate) mod journal;
 pub(crate) mod notification;
@@ -1,5 +1,25 @@
-use crate::timestamp;
+use crate::{
+    host_transport::HostTransport,
+    midi::midi_file_player::ScheduledMidiMessage,
+    parameter::{gesture::ParameterGesture, ParameterChange},
+    realtime::profiler::NodeProfile,
+    timestamp,
+};
+
+use super::id::Id;
 
 pub enum Notification {
     Position(timestamp::Timestamp),
+    HostTransport(HostTransport),
+    NodeProfile(NodeProfile),
+    MidiOutput(ScheduledMidiMessage),
+    SampleLoadProgress(Id, f64),
+    SampleLoaded(Id),
+    SampleLoadFailed(Id, String),
+    NodeQuarantined(Id),
+    NodeIdle(Id),
+    NodeDegraded(Id, bool),
+    BudgetOverrun,
+    ParameterChange(Id, ParameterChange),
+    ParameterGesture(ParameterGesture),
 }
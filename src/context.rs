@@ -1,8 +1,60 @@
+use std::{
+    any::Any,
+    collections::{HashMap, HashSet},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
 use crate::{
     audio_process::AudioProcess,
-    commands::{command::Command, notification::Notification},
-    realtime::processor::Processor,
+    buffer::audio_buffer_slice::AudioBufferSlice,
+    commands::{
+        command::Command, command_queue::CommandQueue, id::Id, journal::JournalEntry,
+        notification::Notification,
+    },
+    dsp::{
+        compressor::node::CompressorNode,
+        sampler::{
+            one_shot::{OneShotOptions, DEFAULT_MAX_VOICES, MAX_ONE_SHOT_VOICES},
+            one_shot_node::OneShotPoolNode,
+            processor::SharedSample,
+            voice_limiter::VoiceBudget,
+        },
+    },
+    graph::{
+        analysis::GraphAnalyzer,
+        channel_adaptation::ChannelAdaptation,
+        connection::Connection,
+        endpoint::{Endpoint, EndpointType},
+        input_mapping::InputMapping,
+        node::Node,
+        node_handle::NodeHandle,
+        realtime_budget::RealtimeBudget,
+        render_quality::RenderQuality,
+        validation::{self, GraphError},
+    },
+    host_transport::HostTransport,
+    midi::output::MidiOutputPort,
+    parameter::{
+        audio_parameter::AudioParameter,
+        automation::AutomationLane,
+        automation_recorder::AutomationRecorder,
+        mix_state::{MixState, MixStateBank},
+    },
+    preview_player::PreviewPlayer,
+    realtime::{
+        dsp_graph::BUFFER_POOL_CAPACITY,
+        processor::{Processor, MAXIMUM_NUMBER_OF_CHANNELS, MAXIMUM_NUMBER_OF_FRAMES},
+        profiler::NodeProfile,
+    },
+    tempo_map::TempoMap,
     timestamp::Timestamp,
+    utility::{
+        audio_file::{read_audio_file, AudioFileError},
+        realtime_log::{self, LogHandler},
+        sample_loader::{load_sample_async, SampleHandle, SampleLoadStatus},
+    },
+    OwnedAudioBuffer,
 };
 
 use lockfree::channel::{
@@ -10,34 +62,680 @@ use lockfree::channel::{
     spsc::{self, Receiver},
 };
 
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ContextOptions {
+    pub input_mapping: Option<InputMapping>,
+}
+
 pub struct Context {
     sample_rate: usize,
     timestamp: Timestamp,
-    command_tx: Sender<Command>,
+    host_transport: Option<HostTransport>,
+    command_queue: CommandQueue,
     notification_rx: Receiver<Notification>,
+    loader_notification_tx: Sender<Notification>,
+    loader_notification_rx: mpsc::Receiver<Notification>,
+    sample_load_status: HashMap<Id, SampleLoadStatus>,
     realtime_processor: Option<Processor>,
+    profiling_report: HashMap<Id, NodeProfile>,
+    render_quality: RenderQuality,
+    channel_adaptation: Option<ChannelAdaptation>,
+    input_mapping: Option<InputMapping>,
+    automation_recorder: Option<AutomationRecorder>,
+    midi_output: Option<Box<dyn MidiOutputPort + Send>>,
+    midi_output_latency: f64,
+    preview_player: PreviewPlayer,
+    one_shot_pool: Option<OneShotPoolNode>,
+    voice_budget: VoiceBudget,
+    mix_states: MixStateBank,
+    quarantined_nodes: HashSet<Id>,
+    idle_nodes: HashSet<Id>,
+    degraded_nodes: HashSet<Id>,
+    budget_overruns: usize,
+    context_id: Id,
+    suspended: bool,
+    owned_nodes: HashMap<Id, Box<dyn Any + Send>>,
 }
 
 impl Context {
     pub fn new(sample_rate: usize) -> Self {
-        let (command_tx, command_rx) = mpsc::create();
+        Self::with_options(sample_rate, ContextOptions::default())
+    }
+
+    pub fn with_options(sample_rate: usize, options: ContextOptions) -> Self {
+        realtime_log::start_logger_thread();
+
+        let (command_queue, command_rx) = CommandQueue::create();
         let (notification_tx, notification_rx) = spsc::create();
+        let (loader_notification_tx, loader_notification_rx) = mpsc::create();
 
-        Self {
+        let mut context = Self {
             sample_rate,
             timestamp: Timestamp::default(),
-            command_tx,
+            host_transport: None,
+            command_queue,
             notification_rx,
+            loader_notification_tx,
+            loader_notification_rx,
+            sample_load_status: HashMap::new(),
             realtime_processor: Some(Processor::new(sample_rate, command_rx, notification_tx)),
+            profiling_report: HashMap::new(),
+            render_quality: RenderQuality::default(),
+            channel_adaptation: None,
+            input_mapping: None,
+            automation_recorder: None,
+            midi_output: None,
+            midi_output_latency: 0.0,
+            preview_player: PreviewPlayer::default(),
+            one_shot_pool: None,
+            voice_budget: VoiceBudget::new(AtomicUsize::new(DEFAULT_MAX_VOICES)),
+            mix_states: MixStateBank::default(),
+            quarantined_nodes: HashSet::new(),
+            idle_nodes: HashSet::new(),
+            degraded_nodes: HashSet::new(),
+            budget_overruns: 0,
+            context_id: Id::generate(),
+            suspended: false,
+            owned_nodes: HashMap::new(),
+        };
+
+        if options.input_mapping.is_some() {
+            context.set_input_mapping(options.input_mapping);
         }
+
+        context
     }
 
     pub fn start(&mut self) {
-        let _ = self.command_tx.send(Command::Start);
+        let _ = self.command_queue.send(Command::Start);
     }
 
     pub fn stop(&mut self) {
-        let _ = self.command_tx.send(Command::Stop);
+        let _ = self.command_queue.send(Command::Stop);
+    }
+
+    pub fn start_at(&mut self, time: Timestamp) {
+        let _ = self
+            .command_queue
+            .send(Command::At(time, Box::new(Command::Start)));
+    }
+
+    pub fn stop_at(&mut self, time: Timestamp) {
+        let _ = self
+            .command_queue
+            .send(Command::At(time, Box::new(Command::Stop)));
+    }
+
+    pub fn suspend(&mut self) {
+        if !self.suspended {
+            self.suspended = true;
+            let _ = self.command_queue.send(Command::Suspend);
+        }
+    }
+
+    pub fn resume(&mut self) {
+        if self.suspended {
+            self.suspended = false;
+            let _ = self.command_queue.send(Command::Resume);
+        }
+    }
+
+    pub fn is_suspended(&self) -> bool {
+        self.suspended
+    }
+
+    pub fn enable_profiling(&mut self) {
+        let _ = self.command_queue.send(Command::EnableProfiling);
+    }
+
+    pub fn disable_profiling(&mut self) {
+        self.profiling_report.clear();
+        let _ = self.command_queue.send(Command::DisableProfiling);
+    }
+
+    pub fn set_midi_output(&mut self, midi_output: Option<Box<dyn MidiOutputPort + Send>>) {
+        self.midi_output = midi_output;
+    }
+
+    pub fn set_midi_output_latency(&mut self, latency_in_seconds: f64) {
+        self.midi_output_latency = latency_in_seconds.max(0.0);
+    }
+
+    pub fn set_realtime_log_handler(&mut self, handler: Option<LogHandler>) {
+        realtime_log::set_log_handler(handler);
+    }
+
+    pub fn set_connection_fade_time(&mut self, fade_time: Duration) {
+        let _ = self
+            .command_queue
+            .send(Command::SetConnectionFadeTime(fade_time));
+    }
+
+    pub fn set_tempo_map(&mut self, tempo_map: &TempoMap) {
+        let _ = self
+            .command_queue
+            .send(Command::SetTempoMap(Box::new(tempo_map.clone())));
+    }
+
+    pub fn set_transport_loop(&mut self, loop_beats: Option<(f64, f64)>) {
+        let _ = self
+            .command_queue
+            .send(Command::SetTransportLoop(loop_beats));
+    }
+
+    pub fn set_render_quality(&mut self, quality: RenderQuality) {
+        self.render_quality = quality;
+        let _ = self.command_queue.send(Command::SetRenderQuality(quality));
+    }
+
+    pub fn get_render_quality(&self) -> RenderQuality {
+        self.render_quality
+    }
+
+    pub fn set_realtime_budget(&mut self, budget: Option<RealtimeBudget>) {
+        let _ = self.command_queue.send(Command::SetRealtimeBudget(budget));
+    }
+
+    pub fn start_automation_recording(&mut self, tempo_map: &TempoMap) {
+        self.automation_recorder = Some(AutomationRecorder::new(
+            tempo_map.clone(),
+            Timestamp::zero(),
+        ));
+        let _ = self
+            .command_queue
+            .send(Command::SetAutomationRecording(true));
+    }
+
+    pub fn stop_automation_recording(&mut self) -> HashMap<Id, AutomationLane> {
+        let _ = self
+            .command_queue
+            .send(Command::SetAutomationRecording(false));
+        self.process_notifications();
+
+        self.automation_recorder
+            .take()
+            .map(AutomationRecorder::into_lanes)
+            .unwrap_or_default()
+    }
+
+    pub fn is_recording_automation(&self) -> bool {
+        self.automation_recorder.is_some()
+    }
+
+    pub fn is_parameter_in_gesture(&self, parameter_id: Id) -> bool {
+        self.automation_recorder
+            .as_ref()
+            .is_some_and(|recorder| recorder.is_in_gesture(parameter_id))
+    }
+
+    #[deprecated(note = "add the nodes to the context and use connect_nodes")]
+    pub fn connect(&mut self, source_id: Id, destination_id: Id) -> Result<(), GraphError> {
+        self.connect_with(Connection::new(source_id, destination_id))
+    }
+
+    pub fn connect_with(&mut self, connection: Connection) -> Result<(), GraphError> {
+        let connection = self.adapt_channels(connection);
+        self.try_apply(JournalEntry::AddConnection(connection))
+    }
+
+    pub fn set_channel_adaptation(&mut self, adaptation: Option<ChannelAdaptation>) {
+        self.channel_adaptation = adaptation;
+    }
+
+    pub fn get_channel_adaptation(&self) -> Option<ChannelAdaptation> {
+        self.channel_adaptation
+    }
+
+    fn adapt_channels(&self, connection: Connection) -> Connection {
+        if connection.channel_matrix.is_some() {
+            return connection;
+        }
+
+        let adaptation = match self.channel_adaptation {
+            Some(adaptation) => adaptation,
+            None => return connection,
+        };
+
+        match (
+            self.command_queue
+                .output_channel_count(connection.source.dsp_id),
+            self.command_queue
+                .input_channel_count(connection.destination.dsp_id),
+        ) {
+            (Some(num_source_channels), Some(num_destination_channels)) => {
+                match adaptation.matrix(num_source_channels, num_destination_channels) {
+                    Some(matrix) => connection.with_channel_matrix(matrix),
+                    None => connection,
+                }
+            }
+            _ => connection,
+        }
+    }
+
+    #[deprecated(note = "add the nodes to the context and use disconnect_nodes")]
+    pub fn disconnect(&mut self, source_id: Id, destination_id: Id) -> Result<(), GraphError> {
+        self.command_queue.disconnect(source_id, destination_id)
+    }
+
+    pub fn connect_at(
+        &mut self,
+        source_id: Id,
+        destination_id: Id,
+        time: Timestamp,
+    ) -> Result<(), GraphError> {
+        let connection = self.adapt_channels(Connection::new(source_id, destination_id));
+        self.try_send(Command::ScheduleConnection(connection, time))
+    }
+
+    pub fn disconnect_at(
+        &mut self,
+        source_id: Id,
+        destination_id: Id,
+        time: Timestamp,
+    ) -> Result<(), GraphError> {
+        let connection = Connection::new(source_id, destination_id);
+        self.try_send(Command::ScheduleDisconnection(connection, time))
+    }
+
+    pub fn connect_to_output(&mut self, source_id: Id) -> Result<(), GraphError> {
+        self.try_apply(JournalEntry::ConnectToOutput {
+            previous: self.command_queue.output_endpoint(),
+            endpoint: Some(Endpoint::new(source_id, EndpointType::Output)),
+        })
+    }
+
+    pub fn connect_to_output_at(
+        &mut self,
+        source_id: Id,
+        time: Timestamp,
+    ) -> Result<(), GraphError> {
+        self.try_send(Command::At(
+            time,
+            Box::new(Command::ConnectToOutput(Endpoint::new(
+                source_id,
+                EndpointType::Output,
+            ))),
+        ))
+    }
+
+    pub fn remove_node_at(&mut self, id: Id, time: Timestamp) -> Result<(), GraphError> {
+        self.command_queue
+            .try_apply_at(time, JournalEntry::DetachDsp(id))
+    }
+
+    pub fn connect_input_to(&mut self, destination_id: Id) -> Result<(), GraphError> {
+        self.try_send(Command::ConnectInput(destination_id))
+    }
+
+    pub fn disconnect_input_from(&mut self, destination_id: Id) -> Result<(), GraphError> {
+        self.try_send(Command::DisconnectInput(destination_id))
+    }
+
+    pub fn set_input_mapping(&mut self, mapping: Option<InputMapping>) {
+        self.input_mapping = mapping;
+        let _ = self.command_queue.send(Command::SetInputMapping(mapping));
+    }
+
+    pub fn get_input_mapping(&self) -> Option<InputMapping> {
+        self.input_mapping
+    }
+
+    pub fn disconnect_from_output(&mut self) {
+        self.apply(JournalEntry::ConnectToOutput {
+            previous: self.command_queue.output_endpoint(),
+            endpoint: None,
+        });
+    }
+
+    pub fn duck(
+        &mut self,
+        target_id: Id,
+        trigger_id: Id,
+        amount: f64,
+        attack: Duration,
+        release: Duration,
+    ) -> Result<CompressorNode, GraphError> {
+        let mut ducker = CompressorNode::new(self.command_queue.clone());
+        ducker.set_ducking(amount, attack, release);
+
+        let connections = [
+            Connection::to_sidechain(trigger_id, ducker.get_id()),
+            Connection::new(target_id, ducker.get_id()),
+        ];
+
+        for connection in connections.iter() {
+            self.command_queue
+                .validate(&Command::AddConnection(connection.clone()))?;
+        }
+
+        for connection in connections {
+            self.apply(JournalEntry::AddConnection(connection));
+        }
+
+        if self
+            .command_queue
+            .output_endpoint()
+            .map(|endpoint| endpoint.dsp_id)
+            == Some(target_id)
+        {
+            self.connect_to_output(ducker.get_id())?;
+        }
+
+        Ok(ducker)
+    }
+
+    pub fn add_node<T: Node + Send + 'static>(
+        &mut self,
+        node: T,
+    ) -> Result<NodeHandle<T>, GraphError> {
+        let id = node.get_id();
+        if !node
+            .get_command_queue()
+            .shares_graph_with(&self.command_queue)
+        {
+            return Err(GraphError::ForeignContext(id));
+        }
+
+        self.owned_nodes.insert(id, Box::new(node));
+        Ok(NodeHandle::new(id, self.context_id))
+    }
+
+    pub fn get_node<T: Node + 'static>(&self, handle: &NodeHandle<T>) -> Option<&T> {
+        if handle.get_context_id() != self.context_id {
+            return None;
+        }
+
+        self.owned_nodes
+            .get(&handle.get_id())
+            .and_then(|node| node.downcast_ref::<T>())
+    }
+
+    pub fn get_node_mut<T: Node + 'static>(&mut self, handle: &NodeHandle<T>) -> Option<&mut T> {
+        if handle.get_context_id() != self.context_id {
+            return None;
+        }
+
+        self.owned_nodes
+            .get_mut(&handle.get_id())
+            .and_then(|node| node.downcast_mut::<T>())
+    }
+
+    pub fn release_node<T: Node + 'static>(&mut self, handle: NodeHandle<T>) -> Option<T> {
+        if handle.get_context_id() != self.context_id {
+            return None;
+        }
+
+        let node = self.owned_nodes.remove(&handle.get_id())?;
+
+        match node.downcast::<T>() {
+            Ok(node) => Some(*node),
+            Err(node) => {
+                self.owned_nodes.insert(handle.get_id(), node);
+                None
+            }
+        }
+    }
+
+    pub fn connect_nodes<S, D>(
+        &mut self,
+        source: &NodeHandle<S>,
+        destination: &NodeHandle<D>,
+    ) -> Result<(), GraphError> {
+        let source_id = self.check_handle(source)?;
+        let destination_id = self.check_handle(destination)?;
+        self.connect_with(Connection::new(source_id, destination_id))
+    }
+
+    pub fn disconnect_nodes<S, D>(
+        &mut self,
+        source: &NodeHandle<S>,
+        destination: &NodeHandle<D>,
+    ) -> Result<(), GraphError> {
+        let source_id = self.check_handle(source)?;
+        let destination_id = self.check_handle(destination)?;
+        self.command_queue.disconnect(source_id, destination_id)
+    }
+
+    pub fn connect_node_to_output<T>(&mut self, node: &NodeHandle<T>) -> Result<(), GraphError> {
+        let id = self.check_handle(node)?;
+        self.connect_to_output(id)
+    }
+
+    fn check_handle<T>(&self, handle: &NodeHandle<T>) -> Result<Id, GraphError> {
+        if handle.get_context_id() != self.context_id {
+            return Err(GraphError::ForeignContext(handle.get_id()));
+        }
+
+        if !self.owned_nodes.contains_key(&handle.get_id()) {
+            return Err(GraphError::RemovedNode(handle.get_id()));
+        }
+
+        Ok(handle.get_id())
+    }
+
+    pub fn remove_node(&mut self, id: Id) -> Result<(), GraphError> {
+        self.try_apply(JournalEntry::DetachDsp(id))
+    }
+
+    pub fn is_connected(&self, source_id: Id, destination_id: Id) -> bool {
+        self.command_queue.is_connected(source_id, destination_id)
+    }
+
+    pub fn set_parameter_value(&mut self, parameter: &AudioParameter, value: f64) {
+        let value = value.clamp(parameter.get_minimum_value(), parameter.get_maximum_value());
+
+        self.apply(JournalEntry::ParameterValueChange {
+            dsp_id: parameter.get_dsp_id(),
+            parameter_id: parameter.get_id(),
+            previous_value: parameter.get_value().load(Ordering::Acquire),
+            value,
+        });
+    }
+
+    pub fn add_mix_state(&mut self, name: &str, state: MixState) {
+        self.mix_states.insert(name, state);
+    }
+
+    pub fn remove_mix_state(&mut self, name: &str) -> Option<MixState> {
+        self.mix_states.remove(name)
+    }
+
+    pub fn set_mix_state(&mut self, name: &str, parameters: &[&AudioParameter]) -> bool {
+        match self
+            .mix_states
+            .transition_to(name, parameters, self.timestamp)
+        {
+            Some(changes) => {
+                let _ = self
+                    .command_queue
+                    .send(Command::ParameterValueChanges(changes));
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn current_mix_state(&self) -> Option<&str> {
+        self.mix_states.current()
+    }
+
+    pub fn undo(&mut self) -> bool {
+        self.command_queue.undo()
+    }
+
+    pub fn redo(&mut self) -> bool {
+        self.command_queue.redo()
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.command_queue.can_undo()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.command_queue.can_redo()
+    }
+
+    pub fn clear_journal(&mut self) {
+        self.command_queue.clear_journal();
+    }
+
+    fn try_apply(&mut self, entry: JournalEntry) -> Result<(), GraphError> {
+        self.command_queue.try_apply(entry)
+    }
+
+    fn try_send(&mut self, command: Command) -> Result<(), GraphError> {
+        self.command_queue.send_validated(command)
+    }
+
+    fn apply(&mut self, entry: JournalEntry) {
+        self.command_queue.apply(entry);
+    }
+
+    pub fn preview_file(&mut self, path: &str, gain: f64) -> Result<(), AudioFileError> {
+        let sample = read_audio_file(path)?;
+        self.preview_sample(sample, gain);
+        Ok(())
+    }
+
+    pub fn preview_sample(&mut self, sample: OwnedAudioBuffer, gain: f64) {
+        self.stop_preview();
+        self.preview_player.play(
+            &self.command_queue,
+            self.sample_rate,
+            sample,
+            gain,
+            self.timestamp,
+        );
+    }
+
+    pub fn stop_preview(&mut self) {
+        self.preview_player.stop(&self.command_queue);
+    }
+
+    pub fn is_previewing(&self) -> bool {
+        self.preview_player.is_playing()
+    }
+
+    pub fn play_one_shot(&mut self, sample: SharedSample, options: OneShotOptions) {
+        let start_time = self
+            .timestamp
+            .incremented_by_seconds(options.delay.as_secs_f64());
+
+        self.get_one_shot_pool()
+            .play_at_time(sample, options, start_time);
+    }
+
+    pub fn play_one_shots_at(
+        &mut self,
+        one_shots: impl IntoIterator<Item = (SharedSample, OneShotOptions, Timestamp)>,
+    ) {
+        self.get_one_shot_pool().play_batch(one_shots);
+    }
+
+    fn get_one_shot_pool(&mut self) -> &mut OneShotPoolNode {
+        if self.one_shot_pool.is_none() {
+            let pool = OneShotPoolNode::new(self.command_queue.clone(), self.voice_budget.clone());
+            let _ = self
+                .command_queue
+                .send_validated(Command::ConnectOutputMix(pool.get_id()));
+
+            self.one_shot_pool = Some(pool);
+        }
+
+        self.one_shot_pool.as_mut().unwrap()
+    }
+
+    pub fn set_max_voices(&mut self, max_voices: usize) {
+        self.voice_budget
+            .store(max_voices.min(MAX_ONE_SHOT_VOICES), Ordering::Release);
+    }
+
+    pub fn get_max_voices(&self) -> usize {
+        self.voice_budget.load(Ordering::Acquire)
+    }
+
+    pub fn load_sample_async(&mut self, path: &str) -> Result<SampleHandle, AudioFileError> {
+        let handle = load_sample_async(path, self.loader_notification_tx.clone())?;
+        self.sample_load_status
+            .insert(handle.get_id(), SampleLoadStatus::Loading(0.0));
+        Ok(handle)
+    }
+
+    pub fn get_sample_load_status(&self, handle: &SampleHandle) -> Option<&SampleLoadStatus> {
+        self.sample_load_status.get(&handle.get_id())
+    }
+
+    pub fn is_quarantined(&self, id: Id) -> bool {
+        self.quarantined_nodes.contains(&id)
+    }
+
+    pub fn get_quarantined_nodes(&self) -> &HashSet<Id> {
+        &self.quarantined_nodes
+    }
+
+    pub fn is_idle(&self, id: Id) -> bool {
+        self.idle_nodes.contains(&id)
+    }
+
+    pub fn get_idle_nodes(&self) -> &HashSet<Id> {
+        &self.idle_nodes
+    }
+
+    pub fn is_degraded(&self, id: Id) -> bool {
+        self.degraded_nodes.contains(&id)
+    }
+
+    pub fn get_degraded_nodes(&self) -> &HashSet<Id> {
+        &self.degraded_nodes
+    }
+
+    pub fn get_budget_overrun_count(&self) -> usize {
+        self.budget_overruns
+    }
+
+    pub fn get_profiling_report(&self) -> &HashMap<Id, NodeProfile> {
+        &self.profiling_report
+    }
+
+    pub fn get_labelled_profiling_report(&self) -> Vec<(String, NodeProfile)> {
+        let mut report: Vec<(String, NodeProfile)> = self
+            .profiling_report
+            .values()
+            .map(|profile| (validation::label(profile.dsp_id), *profile))
+            .collect();
+
+        report.sort_by_key(|(_, profile)| std::cmp::Reverse(profile.mean));
+        report
+    }
+
+    pub fn get_quarantined_node_labels(&self) -> Vec<String> {
+        let mut labels: Vec<String> = self
+            .quarantined_nodes
+            .iter()
+            .map(|id| validation::label(*id))
+            .collect();
+
+        labels.sort();
+        labels
+    }
+
+    pub fn node_label(&self, id: Id) -> String {
+        validation::label(id)
+    }
+
+    pub fn describe_graph(&self, root_id: Id) -> String {
+        self.command_queue.describe_upstream(root_id)
+    }
+
+    pub fn graph_analyzer(&self, root_id: Id) -> GraphAnalyzer {
+        GraphAnalyzer::new(
+            self.sample_rate,
+            MAXIMUM_NUMBER_OF_FRAMES,
+            MAXIMUM_NUMBER_OF_CHANNELS,
+            BUFFER_POOL_CAPACITY,
+        )
+        .with_snapshot(self.command_queue.snapshot_upstream(root_id))
+        .with_profiles(&self.profiling_report)
     }
 
     pub fn current_time(&self) -> Timestamp {
@@ -51,19 +749,645 @@ impl Context {
         Box::new(other.unwrap())
     }
 
+    pub fn render_offline(&mut self, duration: Timestamp, num_channels: usize) -> OwnedAudioBuffer {
+        let mut processor = self
+            .realtime_processor
+            .take()
+            .expect("The audio process has already been taken from the context");
+
+        let num_frames = duration.get_samples(self.sample_rate).round() as usize;
+        let mut output = OwnedAudioBuffer::new(num_frames, num_channels, self.sample_rate);
+
+        let mut position = 0;
+        while position < num_frames {
+            let block_size = MAXIMUM_NUMBER_OF_FRAMES.min(num_frames - position);
+            let mut block = AudioBufferSlice::new(&mut output, position, block_size);
+            processor.process(&mut block);
+
+            self.process_notifications();
+            position += block_size;
+        }
+
+        self.realtime_processor = Some(processor);
+        output
+    }
+
     pub fn get_sample_rate(&self) -> usize {
         self.sample_rate
     }
 
+    pub fn get_id(&self) -> Id {
+        self.context_id
+    }
+
     pub fn process_notifications(&mut self) {
         while let Ok(notification) = self.notification_rx.recv() {
-            match notification {
-                Notification::Position(timestamp) => self.timestamp = timestamp,
+            self.handle_notification(notification);
+        }
+
+        while let Ok(notification) = self.loader_notification_rx.recv() {
+            self.handle_notification(notification);
+        }
+
+        if self.preview_player.is_finished(self.timestamp) {
+            self.stop_preview();
+        }
+    }
+
+    fn handle_notification(&mut self, notification: Notification) {
+        match notification {
+            Notification::Position(timestamp) => {
+                self.timestamp = timestamp;
+                self.command_queue.set_time(timestamp);
+            }
+            Notification::HostTransport(transport) => self.host_transport = Some(transport),
+            Notification::NodeProfile(profile) => {
+                self.profiling_report.insert(profile.dsp_id, profile);
+            }
+            Notification::MidiOutput(scheduled) => {
+                if let Some(midi_output) = self.midi_output.as_mut() {
+                    midi_output.send(
+                        scheduled
+                            .time
+                            .incremented_by_seconds(self.midi_output_latency),
+                        &scheduled.message,
+                    );
+                }
+            }
+            Notification::SampleLoadProgress(id, progress) => {
+                self.sample_load_status
+                    .insert(id, SampleLoadStatus::Loading(progress));
+            }
+            Notification::SampleLoaded(id) => {
+                self.sample_load_status.insert(id, SampleLoadStatus::Loaded);
+            }
+            Notification::SampleLoadFailed(id, error) => {
+                self.sample_load_status
+                    .insert(id, SampleLoadStatus::Failed(error));
+            }
+            Notification::NodeQuarantined(id) => {
+                self.quarantined_nodes.insert(id);
+            }
+            Notification::NodeIdle(id) => {
+                self.idle_nodes.insert(id);
+            }
+            Notification::NodeDegraded(id, true) => {
+                self.degraded_nodes.insert(id);
+            }
+            Notification::NodeDegraded(id, false) => {
+                self.degraded_nodes.remove(&id);
+            }
+            Notification::BudgetOverrun => self.budget_overruns += 1,
+            Notification::ParameterChange(parameter_id, change) => {
+                if let Some(recorder) = self.automation_recorder.as_mut() {
+                    recorder.handle_change(parameter_id, change);
+                }
+            }
+            Notification::ParameterGesture(gesture) => {
+                if let Some(recorder) = self.automation_recorder.as_mut() {
+                    recorder.handle_gesture(gesture);
+                }
             }
         }
     }
 
-    pub fn get_command_queue(&self) -> Sender<Command> {
-        self.command_tx.clone()
+    pub fn get_host_transport(&self) -> Option<HostTransport> {
+        self.host_transport
+    }
+
+    pub fn get_command_queue(&self) -> CommandQueue {
+        self.command_queue.clone()
+    }
+
+    #[cfg(feature = "trace")]
+    pub fn start_trace(&mut self) {
+        crate::utility::trace::start();
+    }
+
+    #[cfg(feature = "trace")]
+    pub fn write_trace(&mut self, path: &str) -> std::io::Result<()> {
+        crate::utility::trace::stop();
+        let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        crate::utility::trace::write_chrome_trace(&mut file)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use crate::{
+        graph::{connection::Connection, validation::GraphError},
+        midi::{mapping::MidiSource, midi_file::tests::make_test_file},
+        AudioBuffer, AutomationCurve, ChannelAdaptation, ConstantSource, Context, ContextOptions,
+        Degradation, DownmixLaw, Gain, HostTransport, InputChannel, InputMapping, MidiFile,
+        MidiFilePlayerNode, MidiMessage, ModulationCurve, ModulationMatrix, ModulationSource, Node,
+        OneShotOptions, Oscillator, OwnedAudioBuffer, RealtimeBudget, SampleLocation, Sampler,
+        TempoMap, Timestamp, UpmixLaw,
+    };
+
+    fn peak(buffer: &OwnedAudioBuffer) -> f32 {
+        (0..buffer.num_frames())
+            .map(|frame| buffer.get_sample(SampleLocation::new(0, frame)).abs())
+            .fold(0.0, f32::max)
+    }
+
+    #[test]
+    fn typed_handles_reject_foreign_and_released_nodes() {
+        let mut context = Context::new(44100);
+        let mut other_context = Context::new(44100);
+
+        let oscillator = Oscillator::new(context.get_command_queue(), 440.0);
+        let oscillator = context.add_node(oscillator).unwrap();
+        let gain = context
+            .add_node(Gain::new(context.get_command_queue()))
+            .unwrap();
+        let foreign_gain = other_context
+            .add_node(Gain::new(other_context.get_command_queue()))
+            .unwrap();
+        let foreign_node = Gain::new(other_context.get_command_queue());
+        let foreign_id = foreign_node.get_id();
+
+        assert_eq!(
+            context.add_node(foreign_node).err(),
+            Some(GraphError::ForeignContext(foreign_id))
+        );
+
+        assert_eq!(context.connect_nodes(&oscillator, &gain), Ok(()));
+        assert_eq!(
+            context.connect_nodes(&oscillator, &foreign_gain),
+            Err(GraphError::ForeignContext(foreign_gain.get_id()))
+        );
+
+        assert!(context.get_node_mut(&gain).is_some());
+        assert!(context.release_node(gain).is_some());
+        assert!(context.get_node(&gain).is_none());
+        assert_eq!(
+            context.connect_node_to_output(&gain),
+            Err(GraphError::RemovedNode(gain.get_id()))
+        );
+    }
+
+    #[test]
+    fn validates_connections_against_each_context_separately() {
+        let mut context = Context::new(44100);
+        let other_context = Context::new(44100);
+
+        let oscillator = Oscillator::new(context.get_command_queue(), 440.0);
+        let foreign_gain = Gain::new(other_context.get_command_queue());
+
+        assert_eq!(
+            oscillator.connect_to_with(foreign_gain.get_id(), 1.0, None),
+            Err(GraphError::UnknownNode(foreign_gain.get_id()))
+        );
+        assert_eq!(
+            context.connect_with(Connection::new(oscillator.get_id(), foreign_gain.get_id())),
+            Err(GraphError::UnknownNode(foreign_gain.get_id()))
+        );
+        assert!(!other_context.is_connected(oscillator.get_id(), foreign_gain.get_id()));
+    }
+
+    #[test]
+    fn reports_nodes_degraded_by_realtime_budget() {
+        let mut context = Context::new(44100);
+        let mut audio_process = context.get_audio_process();
+        let mut buffer = OwnedAudioBuffer::new(512, 2, 44100);
+
+        let oscillator = Oscillator::new(context.get_command_queue(), 440.0);
+        oscillator.connect_to_output().unwrap();
+        oscillator.set_priority(-1).unwrap();
+        context.set_realtime_budget(Some(RealtimeBudget {
+            max_load: 0.0,
+            degradation: Degradation::Skip,
+            recovery_blocks: 1,
+        }));
+        context.start();
+
+        audio_process.process(&mut buffer);
+        context.process_notifications();
+        assert!(context.is_degraded(oscillator.get_id()));
+        assert_eq!(context.get_budget_overrun_count(), 1);
+
+        audio_process.process(&mut buffer);
+        context.process_notifications();
+        assert_eq!(peak(&buffer), 0.0);
+
+        context.set_realtime_budget(None);
+        audio_process.process(&mut buffer);
+        context.process_notifications();
+        assert!(!context.is_degraded(oscillator.get_id()));
+        assert!(peak(&buffer) > 0.0);
+    }
+
+    #[test]
+    fn suspend_outputs_silence_and_preserves_position() {
+        let mut context = Context::new(44100);
+        let mut audio_process = context.get_audio_process();
+        let mut buffer = OwnedAudioBuffer::new(512, 2, 44100);
+
+        let oscillator = Oscillator::new(context.get_command_queue(), 440.0);
+        oscillator.connect_to_output().unwrap();
+        context.start();
+
+        audio_process.process(&mut buffer);
+        context.process_notifications();
+        let position = context.current_time();
+
+        context.suspend();
+        assert!(context.is_suspended());
+        audio_process.process(&mut buffer);
+        context.process_notifications();
+        assert_eq!(peak(&buffer), 0.0);
+        assert_eq!(context.current_time(), position);
+
+        context.resume();
+        audio_process.process(&mut buffer);
+        assert!(peak(&buffer) > 0.0);
+    }
+
+    #[test]
+    fn midi_file_player_drives_sampler_notes_around_the_transport_loop() {
+        let sample_rate = 1_000;
+        let mut context = Context::new(sample_rate);
+
+        let mut sample = OwnedAudioBuffer::new(10 * sample_rate, 1, sample_rate);
+        sample.fill_with_value(1.0);
+        let sampler = Sampler::new(context.get_command_queue(), sample_rate, sample);
+
+        let midi_file = MidiFile::from_bytes(&make_test_file()).unwrap();
+        let mut player = MidiFilePlayerNode::new(context.get_command_queue(), &midi_file);
+        player.add_note_output(sampler.note_input());
+        player.start();
+
+        let player = context.add_node(player).unwrap();
+        let sampler = context.add_node(sampler).unwrap();
+        context.connect_nodes(&player, &sampler).unwrap();
+        context.connect_node_to_output(&sampler).unwrap();
+        context.set_transport_loop(Some((0.0, 0.5)));
+        context.start();
+
+        let output = context.render_offline(Timestamp::from_seconds(2.0), 1);
+        let velocity = 100.0 / 127.0;
+        assert!((output.get_sample(SampleLocation::new(0, 200)) - velocity).abs() < 1e-3);
+        assert!((output.get_sample(SampleLocation::new(0, 1_900)) - velocity).abs() < 1e-3);
+    }
+
+    #[test]
+    fn renders_offline_in_blocks() {
+        let mut context = Context::new(44100);
+
+        let oscillator = Oscillator::new(context.get_command_queue(), 440.0);
+        oscillator.connect_to_output().unwrap();
+        context.start();
+
+        let output = context.render_offline(Timestamp::from_seconds(0.1), 2);
+        assert_eq!(output.num_frames(), 4410);
+        assert_eq!(output.num_channels(), 2);
+        assert!(peak(&output) > 0.0);
+        assert!(output.get_sample(SampleLocation::new(1, 4409)).abs() > 0.0);
+        assert!(context.current_time() > Timestamp::zero());
+
+        let output = context.render_offline(Timestamp::from_samples(100.0, 44100), 1);
+        assert_eq!(output.num_frames(), 100);
+        assert_eq!(output.num_channels(), 1);
+
+        let _audio_process = context.get_audio_process();
+    }
+
+    #[test]
+    fn ducks_target_by_amount_while_trigger_sounds() {
+        let mut context = Context::new(1000);
+
+        let target = ConstantSource::new(context.get_command_queue(), 1.0);
+        let trigger = ConstantSource::new(context.get_command_queue(), 1.0);
+        target.connect_to_output().unwrap();
+
+        let ducker = context
+            .duck(
+                target.get_id(),
+                trigger.get_id(),
+                0.5,
+                Duration::ZERO,
+                Duration::from_millis(100),
+            )
+            .unwrap();
+        assert_eq!(
+            context
+                .get_command_queue()
+                .output_endpoint()
+                .unwrap()
+                .dsp_id,
+            ducker.get_id()
+        );
+        context.start();
+
+        let output = context.render_offline(Timestamp::from_seconds(0.1), 1);
+        let level = output.get_sample(SampleLocation::new(0, 99));
+        assert!((level - 0.5).abs() < 1e-3);
+    }
+
+    #[test]
+    fn modulation_matrix_offsets_parameters_per_block() {
+        let mut context = Context::new(44100);
+        let mut audio_process = context.get_audio_process();
+        let mut buffer = OwnedAudioBuffer::new(512, 2, 44100);
+
+        let constant = ConstantSource::new(context.get_command_queue(), 0.0);
+        constant.connect_to_output().unwrap();
+
+        let mut matrix = ModulationMatrix::new(context.get_command_queue());
+        matrix.set_crossfade_time(std::time::Duration::ZERO);
+        let wheel = matrix.add_source(ModulationSource::Midi(MidiSource::ControlChange {
+            channel: 0,
+            controller: 1,
+        }));
+        let slot = matrix
+            .connect(wheel, &constant.offset, 0.00001, ModulationCurve::Linear)
+            .unwrap();
+        matrix.handle_midi(&MidiMessage::ControlChange {
+            channel: 0,
+            controller: 1,
+            value: 127,
+        });
+        context.start();
+
+        audio_process.process(&mut buffer);
+        assert!((buffer.get_sample(SampleLocation::new(0, 511)) - 2.0).abs() < 1e-3);
+
+        matrix.disconnect(slot);
+        audio_process.process(&mut buffer);
+        assert_eq!(peak(&buffer), 0.0);
+    }
+
+    #[test]
+    fn adapts_declared_channel_counts_on_connect() {
+        let mut context = Context::new(44100);
+
+        let mono = Gain::new(context.get_command_queue());
+        let stereo = Gain::new(context.get_command_queue());
+        let unknown = Gain::new(context.get_command_queue());
+        mono.set_channel_count(1);
+        stereo.set_channel_count(2);
+        let mono = context.add_node(mono).unwrap();
+        let stereo = context.add_node(stereo).unwrap();
+        let unknown = context.add_node(unknown).unwrap();
+
+        context.connect_nodes(&mono, &stereo).unwrap();
+        let connection = context
+            .get_command_queue()
+            .connection(mono.get_id(), stereo.get_id())
+            .unwrap();
+        assert!(connection.channel_matrix.is_none());
+        context.disconnect_nodes(&mono, &stereo).unwrap();
+
+        let adaptation = ChannelAdaptation::new(DownmixLaw::EqualPower, UpmixLaw::EqualPower);
+        context.set_channel_adaptation(Some(adaptation));
+
+        context.connect_nodes(&mono, &stereo).unwrap();
+        context.connect_nodes(&stereo, &unknown).unwrap();
+
+        let connection = context
+            .get_command_queue()
+            .connection(mono.get_id(), stereo.get_id())
+            .unwrap();
+        assert_eq!(connection.channel_matrix, adaptation.matrix(1, 2));
+        let connection = context
+            .get_command_queue()
+            .connection(stereo.get_id(), unknown.get_id())
+            .unwrap();
+        assert!(connection.channel_matrix.is_none());
+    }
+
+    #[test]
+    fn routes_live_input_to_connected_nodes() {
+        let mut context = Context::new(44100);
+        let mut audio_process = context.get_audio_process();
+
+        let mut input_buffer = OwnedAudioBuffer::new(1024, 2, 44100);
+        input_buffer.fill_with_value(0.25);
+        let mut output_buffer = OwnedAudioBuffer::new(1024, 2, 44100);
+
+        let gain = Gain::new(context.get_command_queue());
+        gain.connect_to_output().unwrap();
+        context.start();
+
+        audio_process.process_with_input(&input_buffer, &mut output_buffer);
+        assert_eq!(peak(&output_buffer), 0.0);
+
+        context.connect_input_to(gain.get_id()).unwrap();
+        audio_process.process_with_input(&input_buffer, &mut output_buffer);
+        assert!((0..1024)
+            .all(|frame| { output_buffer.get_sample(SampleLocation::new(1, frame)) == 0.25 }));
+
+        audio_process.process(&mut output_buffer);
+        assert_eq!(peak(&output_buffer), 0.0);
+
+        gain.disconnect_from_input().unwrap();
+        audio_process.process_with_input(&input_buffer, &mut output_buffer);
+        assert_eq!(peak(&output_buffer), 0.0);
+    }
+
+    #[test]
+    fn maps_input_channels_with_trim_and_polarity() {
+        let mapping = InputMapping::stereo(1, 0);
+        let mut context = Context::with_options(
+            44100,
+            ContextOptions {
+                input_mapping: Some(mapping),
+            },
+        );
+        let mut audio_process = context.get_audio_process();
+
+        let mut input_buffer = OwnedAudioBuffer::new(1024, 2, 44100);
+        for frame in 0..1024 {
+            input_buffer.set_sample(SampleLocation::new(0, frame), 0.25);
+            input_buffer.set_sample(SampleLocation::new(1, frame), 0.5);
+        }
+        let mut output_buffer = OwnedAudioBuffer::new(1024, 2, 44100);
+        let sample = |buffer: &OwnedAudioBuffer, channel| {
+            buffer.get_sample(SampleLocation::new(channel, 1000))
+        };
+
+        let gain = Gain::new(context.get_command_queue());
+        gain.connect_to_output().unwrap();
+        context.connect_input_to(gain.get_id()).unwrap();
+        context.start();
+
+        audio_process.process_with_input(&input_buffer, &mut output_buffer);
+        assert_eq!(sample(&output_buffer, 0), 0.5);
+        assert_eq!(sample(&output_buffer, 1), 0.25);
+
+        let mapping = mapping.with_stereo_link(true).with_channel(
+            0,
+            InputChannel::new(1).with_trim(-6.0).with_phase_invert(true),
+        );
+        context.set_input_mapping(Some(mapping));
+        assert_eq!(context.get_input_mapping(), Some(mapping));
+
+        audio_process.process_with_input(&input_buffer, &mut output_buffer);
+        let trim = crate::Level::from_db(-6.0).as_gain() as f32;
+        assert!((sample(&output_buffer, 0) + 0.5 * trim).abs() < 1e-6);
+        assert!((sample(&output_buffer, 1) + 0.25 * trim).abs() < 1e-6);
+
+        context.set_input_mapping(None);
+        audio_process.process_with_input(&input_buffer, &mut output_buffer);
+        assert_eq!(sample(&output_buffer, 0), 0.25);
+    }
+
+    #[test]
+    fn records_parameter_gestures_as_automation() {
+        let mut context = Context::new(44100);
+        let mut audio_process = context.get_audio_process();
+        let mut buffer = OwnedAudioBuffer::new(512, 2, 44100);
+
+        let mut gain = Gain::new(context.get_command_queue());
+        let parameter_id = gain.gain.get_id();
+        context.start_automation_recording(&TempoMap::new(120.0));
+
+        gain.gain.begin_gesture(Timestamp::from_seconds(0.5));
+        gain.gain
+            .set_value_at_time(0.5, Timestamp::from_seconds(0.5));
+        gain.gain
+            .set_value_at_time(0.75, Timestamp::from_seconds(1.0));
+        audio_process.process(&mut buffer);
+        context.process_notifications();
+        assert!(context.is_parameter_in_gesture(parameter_id));
+
+        gain.gain.end_gesture(Timestamp::from_seconds(1.0));
+        gain.gain
+            .set_value_at_time(0.25, Timestamp::from_seconds(2.0));
+        audio_process.process(&mut buffer);
+
+        let lanes = context.stop_automation_recording();
+        assert!(!context.is_recording_automation());
+
+        let points: Vec<(f64, AutomationCurve)> = lanes[&parameter_id]
+            .points()
+            .iter()
+            .map(|point| (point.beat, point.curve))
+            .collect();
+        assert_eq!(
+            points,
+            vec![
+                (1.0, AutomationCurve::Step),
+                (2.0, AutomationCurve::Linear),
+                (4.0, AutomationCurve::Step),
+            ]
+        );
+    }
+
+    #[test]
+    fn follows_host_transport_position_and_jumps() {
+        let mut context = Context::new(48000);
+        let mut audio_process = context.get_audio_process();
+        let mut buffer = OwnedAudioBuffer::new(480, 2, 48000);
+
+        let mut transport = HostTransport {
+            playing: true,
+            beats_per_minute: 120.0,
+            beat_position: 4.0,
+            loop_beats: Some((0.0, 8.0)),
+            ..Default::default()
+        };
+        audio_process.set_host_transport(&transport);
+        audio_process.process(&mut buffer);
+        context.process_notifications();
+        assert_eq!(context.current_time(), Timestamp::from_seconds(2.0));
+        assert_eq!(context.get_host_transport(), Some(transport));
+
+        transport.beat_position += 0.02;
+        audio_process.set_host_transport(&transport);
+        audio_process.process(&mut buffer);
+        context.process_notifications();
+        assert_eq!(context.current_time(), Timestamp::from_seconds(2.0));
+
+        transport.beat_position = 0.0;
+        transport.beats_per_minute = 60.0;
+        audio_process.set_host_transport(&transport);
+        audio_process.process(&mut buffer);
+        context.process_notifications();
+        assert_eq!(context.current_time(), Timestamp::zero());
+        assert_eq!(context.get_host_transport(), Some(transport));
+    }
+
+    #[test]
+    fn applies_scheduled_commands_on_exact_frames() {
+        let mut context = Context::new(48000);
+        let mut audio_process = context.get_audio_process();
+        let mut buffer = OwnedAudioBuffer::new(512, 2, 48000);
+        let sample =
+            |buffer: &OwnedAudioBuffer, frame| buffer.get_sample(SampleLocation::new(0, frame));
+
+        let constant = ConstantSource::new(context.get_command_queue(), 1.0);
+        context
+            .connect_to_output_at(constant.get_id(), Timestamp::from_samples(200.0, 48000))
+            .unwrap();
+        context.start_at(Timestamp::from_samples(100.0, 48000));
+        context.stop_at(Timestamp::from_samples(700.0, 48000));
+
+        audio_process.process(&mut buffer);
+        assert_eq!(sample(&buffer, 199), 0.0);
+        assert_eq!(sample(&buffer, 200), 1.0);
+        assert_eq!(sample(&buffer, 511), 1.0);
+
+        audio_process.process(&mut buffer);
+        assert_eq!(sample(&buffer, 187), 1.0);
+        assert_eq!(sample(&buffer, 188), 0.0);
+
+        audio_process.process(&mut buffer);
+        assert_eq!(peak(&buffer), 0.0);
+    }
+
+    #[test]
+    fn removes_nodes_at_scheduled_time_through_the_journal() {
+        let mut context = Context::new(48000);
+        let mut audio_process = context.get_audio_process();
+        let mut buffer = OwnedAudioBuffer::new(512, 2, 48000);
+
+        let constant = ConstantSource::new(context.get_command_queue(), 1.0);
+        constant.connect_to_output().unwrap();
+        context.clear_journal();
+        context.start();
+
+        context
+            .remove_node_at(constant.get_id(), Timestamp::from_samples(256.0, 48000))
+            .unwrap();
+        assert!(context.can_undo());
+
+        audio_process.process(&mut buffer);
+        assert_eq!(buffer.get_sample(SampleLocation::new(0, 255)), 1.0);
+        assert_eq!(buffer.get_sample(SampleLocation::new(0, 256)), 0.0);
+
+        assert!(context.undo());
+        audio_process.process(&mut buffer);
+        assert_eq!(peak(&buffer), 1.0);
+    }
+
+    #[test]
+    fn one_shots_mix_into_the_output_stage_without_touching_the_journal() {
+        let sample_rate = 1000;
+        let mut context = Context::new(sample_rate);
+        let mut audio_process = context.get_audio_process();
+        let mut buffer = OwnedAudioBuffer::new(100, 2, sample_rate);
+
+        let mut sample = OwnedAudioBuffer::new(200, 1, sample_rate);
+        sample.fill_with_value(0.5);
+
+        let source = context
+            .add_node(ConstantSource::new(context.get_command_queue(), 0.0))
+            .unwrap();
+        context.connect_node_to_output(&source).unwrap();
+        context.clear_journal();
+        context.start();
+
+        context.play_one_shot(Arc::new(sample), OneShotOptions::default());
+        assert!(!context.can_undo());
+
+        audio_process.process(&mut buffer);
+        assert!(peak(&buffer) > 0.3);
+
+        assert!(!context.undo());
+        audio_process.process(&mut buffer);
+        assert!(peak(&buffer) > 0.3);
     }
 }
@@ -0,0 +1,76 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    commands::{command_queue::CommandQueue, id::Id},
+    dsp::sampler::processor::SharedSample,
+    graph::{dsp::Dsp, node::Node},
+    parameter::audio_parameter::AudioParameter,
+};
+
+use super::processor::AmbiencePlayerProcessor;
+
+pub struct AmbiencePlayerNode {
+    id: Id,
+    command_queue: CommandQueue,
+    pub gain: AudioParameter,
+}
+
+const MIN_GAIN: f64 = 0.0;
+const MAX_GAIN: f64 = 2.0;
+
+impl AmbiencePlayerNode {
+    pub fn new(command_queue: CommandQueue, sample: SharedSample, crossfade: Duration) -> Self {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|time| time.as_nanos() as u64)
+            .unwrap_or(1);
+
+        Self::with_seed(command_queue, sample, crossfade, seed)
+    }
+
+    pub fn with_seed(
+        command_queue: CommandQueue,
+        sample: SharedSample,
+        crossfade: Duration,
+        seed: u64,
+    ) -> Self {
+        let mut parameters = HashMap::new();
+
+        let id = Id::generate();
+
+        let (gain, realtime_gain) =
+            AudioParameter::new(id, 1.0, MIN_GAIN, MAX_GAIN, command_queue.clone());
+        parameters.insert(realtime_gain.get_id(), realtime_gain);
+
+        let processor = AmbiencePlayerProcessor::new(sample, gain.get_id(), crossfade, seed);
+
+        let dsp = Dsp::new(id, Box::new(processor), parameters);
+
+        Dsp::add_to_audio_process(dsp, &command_queue);
+
+        Self {
+            id,
+            command_queue,
+            gain,
+        }
+    }
+}
+
+impl Node for AmbiencePlayerNode {
+    fn get_id(&self) -> Id {
+        self.id
+    }
+
+    fn get_command_queue(&self) -> CommandQueue {
+        self.command_queue.clone()
+    }
+}
+
+impl Drop for AmbiencePlayerNode {
+    fn drop(&mut self) {
+        Dsp::remove_from_audio_process(self.id, &self.command_queue);
+    }
+}
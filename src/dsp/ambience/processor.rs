@@ -0,0 +1,181 @@
+use std::{f64::consts::FRAC_PI_2, time::Duration};
+
+use crate::{
+    commands::id::Id,
+    dsp::sampler::processor::SharedSample,
+    graph::dsp::{DspParameterMap, DspProcessor},
+    utility::{fast_math, random::Random},
+    AudioBuffer, SampleLocation, Timestamp,
+};
+
+pub struct AmbiencePlayerProcessor {
+    sample: SharedSample,
+    gain_id: Id,
+    crossfade: Duration,
+    random: Random,
+    position: Option<usize>,
+}
+
+impl AmbiencePlayerProcessor {
+    pub fn new(sample: SharedSample, gain_id: Id, crossfade: Duration, seed: u64) -> Self {
+        fast_math::initialise();
+
+        Self {
+            sample,
+            gain_id,
+            crossfade,
+            random: Random::with_seed(seed),
+            position: None,
+        }
+    }
+
+    fn crossfade_frames(&self, sample_rate: usize) -> usize {
+        let frames = (self.crossfade.as_secs_f64() * sample_rate as f64) as usize;
+        frames.min(self.sample.num_frames() / 2)
+    }
+
+    fn start_position(&mut self, crossfade_frames: usize) -> usize {
+        let loop_length = self.sample.num_frames() - crossfade_frames;
+        let offset = (self.random.next_unipolar() * loop_length as f64) as usize;
+        offset.min(loop_length.saturating_sub(1))
+    }
+
+    fn read_frame(
+        &self,
+        output_buffer: &mut dyn AudioBuffer,
+        frame: usize,
+        position: usize,
+        gain: f64,
+    ) {
+        let num_channels = self.sample.num_channels().min(output_buffer.num_channels());
+
+        for channel in 0..num_channels {
+            let value = self
+                .sample
+                .get_sample(SampleLocation::new(channel, position));
+            output_buffer.add_sample(SampleLocation::new(channel, frame), value * gain as f32);
+        }
+    }
+}
+
+impl DspProcessor for AmbiencePlayerProcessor {
+    fn process_audio(
+        &mut self,
+        _input_buffer: &dyn AudioBuffer,
+        output_buffer: &mut dyn AudioBuffer,
+        start_time: &Timestamp,
+        parameters: &DspParameterMap,
+    ) {
+        let sample_rate = output_buffer.sample_rate();
+
+        let gain = match parameters.get(&self.gain_id) {
+            Some(param) => param,
+            None => return,
+        };
+
+        if self.sample.num_frames() < 2 {
+            return;
+        }
+
+        let crossfade_frames = self.crossfade_frames(sample_rate);
+        let fade_start = self.sample.num_frames() - crossfade_frames;
+
+        let mut position = match self.position {
+            Some(position) => position,
+            None => self.start_position(crossfade_frames),
+        };
+
+        for frame in 0..output_buffer.num_frames() {
+            let frame_time = start_time.incremented_by_samples(frame, sample_rate);
+            let gain = gain.get_value_at_time(&frame_time);
+
+            if position >= fade_start {
+                let fade_position = position - fade_start;
+                let fade = (fade_position as f64 + 0.5) / crossfade_frames as f64;
+                let angle = fade * FRAC_PI_2;
+
+                self.read_frame(output_buffer, frame, position, gain * fast_math::cos(angle));
+                self.read_frame(
+                    output_buffer,
+                    frame,
+                    fade_position,
+                    gain * fast_math::sin(angle),
+                );
+            } else {
+                self.read_frame(output_buffer, frame, position, gain);
+            }
+
+            position += 1;
+
+            if position >= self.sample.num_frames() {
+                position = crossfade_frames;
+            }
+        }
+
+        self.position = Some(position);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use atomic_float::AtomicF64;
+
+    use crate::{parameter::realtime_parameter::RealtimeAudioParameter, OwnedAudioBuffer};
+
+    use super::*;
+
+    fn make_player(seed: u64) -> (AmbiencePlayerProcessor, DspParameterMap) {
+        let mut sample = OwnedAudioBuffer::new(100, 1, 100);
+        sample.fill_with_value(1.0);
+
+        let gain_id = Id::generate();
+        let mut parameters = DspParameterMap::new();
+        parameters.insert(
+            gain_id,
+            RealtimeAudioParameter::new(gain_id, Arc::new(AtomicF64::new(1.0))),
+        );
+
+        let player = AmbiencePlayerProcessor::new(
+            Arc::new(sample),
+            gain_id,
+            Duration::from_millis(200),
+            seed,
+        );
+
+        (player, parameters)
+    }
+
+    #[test]
+    fn loops_without_dropping_level_at_seam() {
+        let (mut player, parameters) = make_player(1);
+
+        let input_buffer = OwnedAudioBuffer::new(50, 1, 100);
+        let mut time = Timestamp::zero();
+
+        for _ in 0..10 {
+            let mut output_buffer = OwnedAudioBuffer::new(50, 1, 100);
+            player.process_audio(&input_buffer, &mut output_buffer, &time, &parameters);
+
+            for frame in 0..50 {
+                let value = output_buffer.get_sample(SampleLocation::new(0, frame));
+                assert!(value > 0.99 && value < 1.42);
+            }
+
+            time = time.incremented_by_samples(50, 100);
+        }
+    }
+
+    #[test]
+    fn randomizes_start_offset() {
+        let (mut player, _) = make_player(1);
+        let first = player.start_position(20);
+
+        let (mut player, _) = make_player(2);
+        let second = player.start_position(20);
+
+        assert_ne!(first, second);
+        assert!(first < 80 && second < 80);
+    }
+}
@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+
+use crate::{
+    commands::{command_queue::CommandQueue, id::Id},
+    dsp::sampler::processor::SharedSample,
+    graph::{dsp::Dsp, node::Node},
+    parameter::{
+        audio_parameter::AudioParameter,
+        preset::{NodePreset, PresetError, PresetNode},
+    },
+};
+
+use super::processor::{AmpSimEvent, AmpSimEventTransmitter, AmpSimParameterIds, AmpSimProcessor};
+
+pub struct AmpSimNode {
+    id: Id,
+    command_queue: CommandQueue,
+    event_transmitter: AmpSimEventTransmitter,
+    pub input_gain: AudioParameter,
+    pub bass: AudioParameter,
+    pub middle: AudioParameter,
+    pub treble: AudioParameter,
+    pub output_gain: AudioParameter,
+}
+
+const MIN_INPUT_GAIN: f64 = 0.0;
+const MAX_INPUT_GAIN: f64 = 48.0;
+const MIN_TONE: f64 = -12.0;
+const MAX_TONE: f64 = 12.0;
+const MIN_OUTPUT_GAIN: f64 = -48.0;
+const MAX_OUTPUT_GAIN: f64 = 12.0;
+
+const FACTORY_PRESETS: [(&str, [f64; 5]); 3] = [
+    ("Clean", [6.0, 2.0, 0.0, 2.0, -3.0]),
+    ("Crunch", [24.0, 0.0, 3.0, 1.0, -9.0]),
+    ("Lead", [40.0, -2.0, 5.0, 3.0, -14.0]),
+];
+
+const PARAMETER_NAMES: [&str; 5] = ["input_gain", "bass", "middle", "treble", "output_gain"];
+
+impl AmpSimNode {
+    pub fn new(command_queue: CommandQueue) -> Self {
+        let mut parameters = HashMap::new();
+
+        let id = Id::generate();
+
+        let (input_gain, realtime_input_gain) = AudioParameter::new(
+            id,
+            12.0,
+            MIN_INPUT_GAIN,
+            MAX_INPUT_GAIN,
+            command_queue.clone(),
+        );
+        parameters.insert(realtime_input_gain.get_id(), realtime_input_gain);
+
+        let (bass, realtime_bass) =
+            AudioParameter::new(id, 0.0, MIN_TONE, MAX_TONE, command_queue.clone());
+        parameters.insert(realtime_bass.get_id(), realtime_bass);
+
+        let (middle, realtime_middle) =
+            AudioParameter::new(id, 0.0, MIN_TONE, MAX_TONE, command_queue.clone());
+        parameters.insert(realtime_middle.get_id(), realtime_middle);
+
+        let (treble, realtime_treble) =
+            AudioParameter::new(id, 0.0, MIN_TONE, MAX_TONE, command_queue.clone());
+        parameters.insert(realtime_treble.get_id(), realtime_treble);
+
+        let (output_gain, realtime_output_gain) = AudioParameter::new(
+            id,
+            -6.0,
+            MIN_OUTPUT_GAIN,
+            MAX_OUTPUT_GAIN,
+            command_queue.clone(),
+        );
+        parameters.insert(realtime_output_gain.get_id(), realtime_output_gain);
+
+        let (event_transmitter, event_receiver) = lockfree::channel::spsc::create();
+
+        let processor = AmpSimProcessor::new(
+            AmpSimParameterIds {
+                input_gain_id: input_gain.get_id(),
+                bass_id: bass.get_id(),
+                middle_id: middle.get_id(),
+                treble_id: treble.get_id(),
+                output_gain_id: output_gain.get_id(),
+            },
+            event_receiver,
+        );
+
+        let dsp = Dsp::new(id, Box::new(processor), parameters);
+
+        Dsp::add_to_audio_process(dsp, &command_queue);
+
+        Self {
+            id,
+            command_queue,
+            event_transmitter,
+            input_gain,
+            bass,
+            middle,
+            treble,
+            output_gain,
+        }
+    }
+
+    pub fn set_cabinet_impulse(&mut self, impulse: SharedSample) {
+        let _ = self
+            .event_transmitter
+            .send(AmpSimEvent::SetCabinet(Some(impulse)));
+    }
+
+    pub fn clear_cabinet_impulse(&mut self) {
+        let _ = self.event_transmitter.send(AmpSimEvent::SetCabinet(None));
+    }
+
+    pub fn factory_presets() -> Vec<NodePreset> {
+        FACTORY_PRESETS
+            .iter()
+            .map(|(name, values)| {
+                PARAMETER_NAMES.iter().zip(values.iter()).fold(
+                    NodePreset::new("amp_sim", name),
+                    |preset, (parameter_name, value)| preset.with_parameter(parameter_name, *value),
+                )
+            })
+            .collect()
+    }
+
+    fn parameters_mut(&mut self) -> [&mut AudioParameter; 5] {
+        [
+            &mut self.input_gain,
+            &mut self.bass,
+            &mut self.middle,
+            &mut self.treble,
+            &mut self.output_gain,
+        ]
+    }
+}
+
+impl Node for AmpSimNode {
+    fn get_id(&self) -> Id {
+        self.id
+    }
+
+    fn get_command_queue(&self) -> CommandQueue {
+        self.command_queue.clone()
+    }
+}
+
+impl PresetNode for AmpSimNode {
+    fn preset_type(&self) -> &'static str {
+        "amp_sim"
+    }
+
+    fn capture_preset(&self, name: &str) -> NodePreset {
+        let mut preset = NodePreset::new(self.preset_type(), name);
+        let parameters = [
+            &self.input_gain,
+            &self.bass,
+            &self.middle,
+            &self.treble,
+            &self.output_gain,
+        ];
+
+        for (parameter_name, parameter) in PARAMETER_NAMES.iter().zip(parameters) {
+            preset.capture_parameter(parameter_name, parameter);
+        }
+
+        preset
+    }
+
+    fn apply_preset(&mut self, preset: &NodePreset) -> Result<(), PresetError> {
+        preset.check_node_type(self.preset_type())?;
+
+        for (parameter_name, parameter) in PARAMETER_NAMES.iter().zip(self.parameters_mut()) {
+            preset.apply_parameter(parameter_name, parameter);
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for AmpSimNode {
+    fn drop(&mut self) {
+        Dsp::remove_from_audio_process(self.id, &self.command_queue);
+    }
+}
@@ -0,0 +1,295 @@
+use crate::{
+    commands::id::Id,
+    dsp::sampler::processor::SharedSample,
+    graph::dsp::{DspParameterMap, DspProcessor},
+    parameter::realtime_parameter::RealtimeAudioParameter,
+    utility::{
+        fast_math,
+        state_variable_filter::{FilterState, StateVariableFilter},
+    },
+    AudioBuffer, SampleLocation, Timestamp,
+};
+
+pub type AmpSimEventReceiver = lockfree::channel::spsc::Receiver<AmpSimEvent>;
+pub type AmpSimEventTransmitter = lockfree::channel::spsc::Sender<AmpSimEvent>;
+
+pub const MAX_CABINET_LENGTH: usize = 1024;
+const MAXIMUM_NUMBER_OF_CHANNELS: usize = 2;
+const TONE_Q: f64 = 0.7;
+const BASS_FREQUENCY: f64 = 120.0;
+const MIDDLE_FREQUENCY: f64 = 800.0;
+const TREBLE_FREQUENCY: f64 = 3200.0;
+const POWER_STAGE_BIAS: f64 = 0.2;
+
+pub enum AmpSimEvent {
+    SetCabinet(Option<SharedSample>),
+}
+
+#[derive(Clone, Copy)]
+pub struct AmpSimParameterIds {
+    pub input_gain_id: Id,
+    pub bass_id: Id,
+    pub middle_id: Id,
+    pub treble_id: Id,
+    pub output_gain_id: Id,
+}
+
+#[derive(Clone, Copy, Default)]
+struct ToneStackState {
+    bass: FilterState,
+    middle: FilterState,
+    treble: FilterState,
+}
+
+struct Cabinet {
+    impulse: SharedSample,
+    length: usize,
+}
+
+pub struct AmpSimProcessor {
+    ids: AmpSimParameterIds,
+    event_receiver: AmpSimEventReceiver,
+    sample_rate: usize,
+    bass: StateVariableFilter,
+    middle: StateVariableFilter,
+    treble: StateVariableFilter,
+    tone_states: [ToneStackState; MAXIMUM_NUMBER_OF_CHANNELS],
+    cabinet: Option<Cabinet>,
+    history: [Vec<f32>; MAXIMUM_NUMBER_OF_CHANNELS],
+    history_position: usize,
+}
+
+impl AmpSimProcessor {
+    pub fn new(ids: AmpSimParameterIds, event_receiver: AmpSimEventReceiver) -> Self {
+        Self {
+            ids,
+            event_receiver,
+            sample_rate: 0,
+            bass: StateVariableFilter::default(),
+            middle: StateVariableFilter::default(),
+            treble: StateVariableFilter::default(),
+            tone_states: [ToneStackState::default(); MAXIMUM_NUMBER_OF_CHANNELS],
+            cabinet: None,
+            history: [vec![0.0; MAX_CABINET_LENGTH], vec![0.0; MAX_CABINET_LENGTH]],
+            history_position: 0,
+        }
+    }
+
+    fn prepare(&mut self, sample_rate: usize) {
+        if self.sample_rate == sample_rate {
+            return;
+        }
+
+        self.bass = StateVariableFilter::new(BASS_FREQUENCY, TONE_Q, sample_rate);
+        self.middle = StateVariableFilter::new(MIDDLE_FREQUENCY, TONE_Q, sample_rate);
+        self.treble = StateVariableFilter::new(TREBLE_FREQUENCY, TONE_Q, sample_rate);
+        self.sample_rate = sample_rate;
+    }
+
+    fn read_events(&mut self) {
+        while let Ok(event) = self.event_receiver.recv() {
+            match event {
+                AmpSimEvent::SetCabinet(impulse) => {
+                    self.cabinet = impulse.map(|impulse| Cabinet {
+                        length: impulse.num_frames().min(MAX_CABINET_LENGTH),
+                        impulse,
+                    });
+                }
+            }
+        }
+    }
+
+    pub fn preamp_stage(input: f64, gain: f64) -> f64 {
+        fast_math::tanh(gain * input)
+    }
+
+    pub fn power_stage(input: f64) -> f64 {
+        let bias = POWER_STAGE_BIAS.tanh();
+        (fast_math::tanh(input + POWER_STAGE_BIAS) - bias) / (1.0 - bias)
+    }
+
+    fn tone_stack(&mut self, channel: usize, input: f64, gains: [f64; 3]) -> f64 {
+        let state = &mut self.tone_states[channel];
+        let mut sample = input;
+
+        let bass = self.bass.process(&mut state.bass, sample).low;
+        sample += (gains[0] - 1.0) * bass;
+
+        let middle = self.middle.process(&mut state.middle, sample).band;
+        sample += (gains[1] - 1.0) * middle;
+
+        let treble = self.treble.process(&mut state.treble, sample).high;
+        sample + (gains[2] - 1.0) * treble
+    }
+
+    fn convolve(&mut self, channel: usize, input: f64) -> f64 {
+        let history = &mut self.history[channel];
+        history[self.history_position] = input as f32;
+
+        let cabinet = match self.cabinet.as_ref() {
+            Some(cabinet) => cabinet,
+            None => return input,
+        };
+
+        let impulse_channel = channel.min(cabinet.impulse.num_channels() - 1);
+        let mut output = 0.0;
+
+        for tap in 0..cabinet.length {
+            let index = (self.history_position + MAX_CABINET_LENGTH - tap) % MAX_CABINET_LENGTH;
+            let coefficient = cabinet
+                .impulse
+                .get_sample(SampleLocation::new(impulse_channel, tap));
+            output += coefficient as f64 * history[index] as f64;
+        }
+
+        output
+    }
+}
+
+impl DspProcessor for AmpSimProcessor {
+    fn process_audio(
+        &mut self,
+        input_buffer: &dyn AudioBuffer,
+        output_buffer: &mut dyn AudioBuffer,
+        start_time: &Timestamp,
+        parameters: &DspParameterMap,
+    ) {
+        let sample_rate = output_buffer.sample_rate();
+        self.prepare(sample_rate);
+        self.read_events();
+
+        let ids = self.ids;
+        let (input_gain, bass, middle, treble, output_gain) = match (
+            parameters.get(&ids.input_gain_id),
+            parameters.get(&ids.bass_id),
+            parameters.get(&ids.middle_id),
+            parameters.get(&ids.treble_id),
+            parameters.get(&ids.output_gain_id),
+        ) {
+            (Some(input_gain), Some(bass), Some(middle), Some(treble), Some(output_gain)) => {
+                (input_gain, bass, middle, treble, output_gain)
+            }
+            _ => return,
+        };
+
+        let num_channels = output_buffer
+            .num_channels()
+            .min(input_buffer.num_channels())
+            .min(MAXIMUM_NUMBER_OF_CHANNELS);
+
+        let gain = |parameter: &RealtimeAudioParameter, time: &Timestamp| {
+            fast_math::db_to_gain(parameter.get_value_at_time(time))
+        };
+
+        for frame in 0..output_buffer.num_frames() {
+            let frame_time = start_time.incremented_by_samples(frame, sample_rate);
+
+            let drive = gain(input_gain, &frame_time);
+            let tone_gains = [
+                gain(bass, &frame_time),
+                gain(middle, &frame_time),
+                gain(treble, &frame_time),
+            ];
+            let level = gain(output_gain, &frame_time);
+
+            for channel in 0..num_channels {
+                let location = SampleLocation::new(channel, frame);
+                let input = input_buffer.get_sample(location) as f64;
+
+                let preamp = Self::preamp_stage(input, drive);
+                let toned = self.tone_stack(channel, preamp, tone_gains);
+                let power = Self::power_stage(toned);
+                let cabinet = self.convolve(channel, power);
+
+                output_buffer.set_sample(location, (level * cabinet) as f32);
+            }
+
+            self.history_position = (self.history_position + 1) % MAX_CABINET_LENGTH;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{f64::consts::TAU, sync::Arc};
+
+    use approx::assert_relative_eq;
+    use atomic_float::AtomicF64;
+
+    use crate::OwnedAudioBuffer;
+
+    use super::*;
+
+    const SAMPLE_RATE: usize = 48_000;
+
+    fn parameters(input_gain: f64) -> (DspParameterMap, AmpSimParameterIds) {
+        let mut parameters = DspParameterMap::new();
+        let mut parameter = |value: f64| {
+            let id = Id::generate();
+            parameters.insert(
+                id,
+                RealtimeAudioParameter::new(id, Arc::new(AtomicF64::new(value))),
+            );
+            id
+        };
+
+        let ids = AmpSimParameterIds {
+            input_gain_id: parameter(input_gain),
+            bass_id: parameter(0.0),
+            middle_id: parameter(0.0),
+            treble_id: parameter(0.0),
+            output_gain_id: parameter(0.0),
+        };
+
+        (parameters, ids)
+    }
+
+    fn process(processor: &mut AmpSimProcessor, parameters: &DspParameterMap) -> Vec<f32> {
+        let mut input_buffer = OwnedAudioBuffer::new(512, 1, SAMPLE_RATE);
+        for frame in 0..512 {
+            let value = (TAU * 220.0 * frame as f64 / SAMPLE_RATE as f64).sin();
+            input_buffer.set_sample(SampleLocation::new(0, frame), value as f32);
+        }
+
+        let mut output_buffer = OwnedAudioBuffer::new(512, 1, SAMPLE_RATE);
+        processor.process_audio(
+            &input_buffer,
+            &mut output_buffer,
+            &Timestamp::zero(),
+            parameters,
+        );
+
+        (0..512)
+            .map(|frame| output_buffer.get_sample(SampleLocation::new(0, frame)))
+            .collect()
+    }
+
+    #[test]
+    fn saturates_and_applies_cabinet_impulse() {
+        let (parameters, ids) = parameters(36.0);
+
+        let (_, event_receiver) = lockfree::channel::spsc::create();
+        let mut reference = AmpSimProcessor::new(ids, event_receiver);
+        let dry = process(&mut reference, &parameters);
+        let peak = dry
+            .iter()
+            .fold(0.0_f32, |peak, value| peak.max(value.abs()));
+        assert!(peak > 0.9 && peak < 1.5);
+
+        let (mut event_transmitter, event_receiver) = lockfree::channel::spsc::create();
+        let mut processor = AmpSimProcessor::new(ids, event_receiver);
+
+        let mut impulse = OwnedAudioBuffer::new(8, 1, SAMPLE_RATE);
+        impulse.set_sample(SampleLocation::new(0, 7), 0.5);
+        let _ = event_transmitter.send(AmpSimEvent::SetCabinet(Some(Arc::new(impulse))));
+
+        let wet = process(&mut processor, &parameters);
+
+        for value in wet.iter().take(7) {
+            assert_relative_eq!(*value, 0.0);
+        }
+        for frame in 7..512 {
+            assert_relative_eq!(wet[frame], 0.5 * dry[frame - 7], epsilon = 1e-5);
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+
+use crate::{
+    commands::{command_queue::CommandQueue, id::Id},
+    graph::{
+        channel_adaptation::{ChannelAdaptation, DownmixLaw, UpmixLaw},
+        channel_matrix::MAXIMUM_MATRIX_CHANNELS,
+        dsp::Dsp,
+        node::Node,
+    },
+};
+
+use super::processor::ChannelAdapterProcessor;
+
+pub struct ChannelAdapterNode {
+    id: Id,
+    command_queue: CommandQueue,
+    num_input_channels: usize,
+    num_output_channels: usize,
+}
+
+impl ChannelAdapterNode {
+    pub fn new(
+        command_queue: CommandQueue,
+        num_input_channels: usize,
+        num_output_channels: usize,
+        adaptation: ChannelAdaptation,
+    ) -> Self {
+        let id = Id::generate();
+
+        let num_input_channels = num_input_channels.clamp(1, MAXIMUM_MATRIX_CHANNELS);
+        let num_output_channels = num_output_channels.clamp(1, MAXIMUM_MATRIX_CHANNELS);
+        let matrix = adaptation
+            .matrix(num_input_channels, num_output_channels)
+            .unwrap_or_default();
+
+        let dsp = Dsp::new(
+            id,
+            Box::new(ChannelAdapterProcessor::new(
+                matrix,
+                num_input_channels,
+                num_output_channels,
+            )),
+            HashMap::new(),
+        );
+
+        Dsp::add_to_audio_process(dsp, &command_queue);
+        command_queue.set_channel_counts(id, num_input_channels, num_output_channels);
+
+        Self {
+            id,
+            command_queue,
+            num_input_channels,
+            num_output_channels,
+        }
+    }
+
+    pub fn stereo_to_mono(command_queue: CommandQueue, law: DownmixLaw) -> Self {
+        Self::new(
+            command_queue,
+            2,
+            1,
+            ChannelAdaptation::new(law, UpmixLaw::default()),
+        )
+    }
+
+    pub fn mono_to_stereo(command_queue: CommandQueue, law: UpmixLaw) -> Self {
+        Self::new(
+            command_queue,
+            1,
+            2,
+            ChannelAdaptation::new(DownmixLaw::default(), law),
+        )
+    }
+
+    pub fn num_input_channels(&self) -> usize {
+        self.num_input_channels
+    }
+
+    pub fn num_output_channels(&self) -> usize {
+        self.num_output_channels
+    }
+}
+
+impl Node for ChannelAdapterNode {
+    fn get_id(&self) -> Id {
+        self.id
+    }
+
+    fn get_command_queue(&self) -> CommandQueue {
+        self.command_queue.clone()
+    }
+}
+
+impl Drop for ChannelAdapterNode {
+    fn drop(&mut self) {
+        Dsp::remove_from_audio_process(self.id, &self.command_queue);
+    }
+}
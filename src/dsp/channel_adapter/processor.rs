@@ -0,0 +1,130 @@
+use std::time::Duration;
+
+use crate::{
+    graph::{
+        channel_matrix::ChannelMatrix,
+        dsp::{DspParameterMap, DspProcessor},
+    },
+    AudioBuffer, SampleLocation, Timestamp,
+};
+
+pub struct ChannelAdapterProcessor {
+    matrix: ChannelMatrix,
+    num_input_channels: usize,
+    num_output_channels: usize,
+}
+
+impl ChannelAdapterProcessor {
+    pub fn new(
+        matrix: ChannelMatrix,
+        num_input_channels: usize,
+        num_output_channels: usize,
+    ) -> Self {
+        Self {
+            matrix,
+            num_input_channels,
+            num_output_channels,
+        }
+    }
+}
+
+impl DspProcessor for ChannelAdapterProcessor {
+    fn process_audio(
+        &mut self,
+        input_buffer: &dyn AudioBuffer,
+        output_buffer: &mut dyn AudioBuffer,
+        _start_time: &Timestamp,
+        _parameters: &DspParameterMap,
+    ) {
+        let num_input_channels = self.num_input_channels.min(input_buffer.num_channels());
+        let num_output_channels = self.num_output_channels.min(output_buffer.num_channels());
+
+        for frame in 0..output_buffer.num_frames() {
+            for channel in 0..output_buffer.num_channels() {
+                let location = SampleLocation::new(channel, frame);
+
+                if channel >= num_output_channels {
+                    output_buffer.set_sample(location, 0.0);
+                    continue;
+                }
+
+                let value = self
+                    .matrix
+                    .destination_gains(channel)
+                    .iter()
+                    .take(num_input_channels)
+                    .enumerate()
+                    .map(|(source_channel, gain)| {
+                        gain * input_buffer.get_sample(SampleLocation::new(source_channel, frame))
+                    })
+                    .sum();
+
+                output_buffer.set_sample(location, value);
+            }
+        }
+    }
+
+    fn tail_time(&self) -> Option<Duration> {
+        Some(Duration::ZERO)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        graph::channel_adaptation::{ChannelAdaptation, DownmixLaw, UpmixLaw},
+        OwnedAudioBuffer,
+    };
+
+    use super::*;
+
+    fn process(adaptation: ChannelAdaptation, inputs: usize, outputs: usize) -> [f32; 2] {
+        let mut processor = ChannelAdapterProcessor::new(
+            adaptation.matrix(inputs, outputs).unwrap(),
+            inputs,
+            outputs,
+        );
+
+        let mut input_buffer = OwnedAudioBuffer::new(4, 2, 48_000);
+        for frame in 0..4 {
+            input_buffer.set_sample(SampleLocation::new(0, frame), 0.8);
+            input_buffer.set_sample(SampleLocation::new(1, frame), 0.4);
+        }
+
+        let mut output_buffer = OwnedAudioBuffer::new(4, 2, 48_000);
+        output_buffer.fill_with_value(1.0);
+        processor.process_audio(
+            &input_buffer,
+            &mut output_buffer,
+            &Timestamp::zero(),
+            &DspParameterMap::new(),
+        );
+
+        [
+            output_buffer.get_sample(SampleLocation::new(0, 3)),
+            output_buffer.get_sample(SampleLocation::new(1, 3)),
+        ]
+    }
+
+    #[test]
+    fn downmixes_stereo_to_mono_on_first_channel() {
+        let average = ChannelAdaptation::new(DownmixLaw::Average, UpmixLaw::default());
+        let [mono, silent] = process(average, 2, 1);
+        assert!((mono - 0.6).abs() < 1e-6);
+        assert_eq!(silent, 0.0);
+
+        let right = ChannelAdaptation::new(DownmixLaw::RightOnly, UpmixLaw::default());
+        assert_eq!(process(right, 2, 1), [0.4, 0.0]);
+    }
+
+    #[test]
+    fn upmixes_first_channel_to_stereo() {
+        let duplicate = ChannelAdaptation::new(DownmixLaw::default(), UpmixLaw::Duplicate);
+        assert_eq!(process(duplicate, 1, 2), [0.8, 0.8]);
+
+        let equal_power = ChannelAdaptation::new(DownmixLaw::default(), UpmixLaw::EqualPower);
+        let [left, right] = process(equal_power, 1, 2);
+        assert!((left - 0.8 * 0.5_f32.sqrt()).abs() < 1e-6);
+        assert_eq!(left, right);
+    }
+}
@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+
+use crate::{
+    commands::{command_queue::CommandQueue, id::Id},
+    dsp::sampler::processor::SharedSample,
+    graph::{dsp::Dsp, node::Node},
+};
+
+use super::processor::{
+    Clip, ClipEvent, ClipEventTransmitter, ClipPlayerDspProcess, LaunchQuantization, MAX_CLIPS,
+};
+
+pub struct ClipPlayerNode {
+    command_queue: CommandQueue,
+    id: Id,
+    event_transmitter: ClipEventTransmitter,
+}
+
+impl Node for ClipPlayerNode {
+    fn get_id(&self) -> Id {
+        self.id
+    }
+
+    fn get_command_queue(&self) -> CommandQueue {
+        self.command_queue.clone()
+    }
+}
+
+impl ClipPlayerNode {
+    pub fn new(command_queue: CommandQueue) -> Self {
+        let id = Id::generate();
+
+        let (event_transmitter, event_receiver) = lockfree::channel::spsc::create();
+
+        let clip_player_process = ClipPlayerDspProcess::new(event_receiver);
+
+        let dsp = Dsp::new(id, Box::new(clip_player_process), HashMap::new());
+
+        Dsp::add_to_audio_process(dsp, &command_queue);
+
+        Self {
+            command_queue,
+            id,
+            event_transmitter,
+        }
+    }
+
+    pub fn max_clips(&self) -> usize {
+        MAX_CLIPS
+    }
+
+    pub fn set_clip(&mut self, index: usize, sample: SharedSample, looping: bool) {
+        let _ = self
+            .event_transmitter
+            .send(ClipEvent::SetClip(index, Some(Clip::new(sample, looping))));
+    }
+
+    pub fn remove_clip(&mut self, index: usize) {
+        let _ = self.event_transmitter.send(ClipEvent::SetClip(index, None));
+    }
+
+    pub fn launch(&mut self, index: usize, quantization: LaunchQuantization) {
+        let _ = self
+            .event_transmitter
+            .send(ClipEvent::Launch(index, quantization));
+    }
+
+    pub fn stop(&mut self, quantization: LaunchQuantization) {
+        let _ = self.event_transmitter.send(ClipEvent::Stop(quantization));
+    }
+}
+
+impl Drop for ClipPlayerNode {
+    fn drop(&mut self) {
+        Dsp::remove_from_audio_process(self.id, &self.command_queue);
+    }
+}
@@ -0,0 +1,413 @@
+use crate::{
+    dsp::sampler::{processor::SharedSample, voice::read_interpolated},
+    graph::{
+        dsp::{DspParameterMap, DspProcessor},
+        transport::Transport,
+    },
+    realtime::garbage_collector::GarbageCollectionCommand,
+    AudioBuffer, SampleLocation, Timestamp,
+};
+
+pub type ClipEventReceiver = lockfree::channel::spsc::Receiver<ClipEvent>;
+pub type ClipEventTransmitter = lockfree::channel::spsc::Sender<ClipEvent>;
+
+pub const MAX_CLIPS: usize = 64;
+const BOUNDARY_TOLERANCE: f64 = 1e-9;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LaunchQuantization {
+    Immediate,
+    Beats(f64),
+}
+
+impl LaunchQuantization {
+    pub fn beat() -> Self {
+        Self::Beats(1.0)
+    }
+
+    pub fn bar(beats_per_bar: f64) -> Self {
+        Self::Beats(beats_per_bar)
+    }
+
+    fn pending_launch(&self, transport: &Transport, clip: Option<usize>) -> PendingLaunch {
+        let beats = match *self {
+            LaunchQuantization::Beats(beats) if beats > 0.0 => beats,
+            _ => {
+                return PendingLaunch {
+                    beat: None,
+                    after_wrap: false,
+                    clip,
+                }
+            }
+        };
+
+        let grid = |beat: f64| (beat / beats - BOUNDARY_TOLERANCE).ceil() * beats;
+        let beat = transport.beat_position;
+        let boundary = grid(beat);
+
+        match transport.loop_beats {
+            Some((loop_start, loop_end)) if beat < loop_end && boundary >= loop_end => {
+                let first_boundary = grid(loop_start);
+                PendingLaunch {
+                    beat: Some(if first_boundary < loop_end {
+                        first_boundary
+                    } else {
+                        loop_start
+                    }),
+                    after_wrap: true,
+                    clip,
+                }
+            }
+            _ => PendingLaunch {
+                beat: Some(boundary),
+                after_wrap: false,
+                clip,
+            },
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Clip {
+    sample: SharedSample,
+    looping: bool,
+}
+
+impl Clip {
+    pub fn new(sample: SharedSample, looping: bool) -> Self {
+        Self { sample, looping }
+    }
+}
+
+pub enum ClipEvent {
+    SetClip(usize, Option<Clip>),
+    Launch(usize, LaunchQuantization),
+    Stop(LaunchQuantization),
+}
+
+struct PendingLaunch {
+    beat: Option<f64>,
+    after_wrap: bool,
+    clip: Option<usize>,
+}
+
+impl PendingLaunch {
+    fn advance(&mut self, previous_beat: Option<f64>, beat: f64) -> bool {
+        let boundary = match self.beat {
+            Some(boundary) => boundary,
+            None => return true,
+        };
+
+        let jumped_back = previous_beat.is_some_and(|previous| beat < previous);
+
+        if self.after_wrap {
+            self.after_wrap = !jumped_back;
+            return jumped_back && boundary <= beat;
+        }
+
+        match previous_beat {
+            Some(previous) if !jumped_back => previous < boundary && boundary <= beat,
+            _ => boundary <= beat,
+        }
+    }
+}
+
+struct PlayingClip {
+    index: usize,
+    position: f64,
+}
+
+pub struct ClipPlayerDspProcess {
+    transport: Transport,
+    previous_beat: Option<f64>,
+    clips: Vec<Option<Clip>>,
+    retired_samples: Vec<SharedSample>,
+    pending_launch: Option<PendingLaunch>,
+    playing: Option<PlayingClip>,
+    event_receiver: ClipEventReceiver,
+}
+
+impl ClipPlayerDspProcess {
+    pub fn new(event_receiver: ClipEventReceiver) -> Self {
+        Self {
+            transport: Transport::default(),
+            previous_beat: None,
+            clips: vec![None; MAX_CLIPS],
+            retired_samples: Vec::with_capacity(MAX_CLIPS),
+            pending_launch: None,
+            playing: None,
+            event_receiver,
+        }
+    }
+
+    fn read_events(&mut self) {
+        while self.retired_samples.len() < self.retired_samples.capacity() {
+            let event = match self.event_receiver.recv() {
+                Ok(event) => event,
+                Err(_) => break,
+            };
+
+            match event {
+                ClipEvent::SetClip(index, clip) => self.set_clip(index, clip),
+                ClipEvent::Launch(index, quantization) => {
+                    self.pending_launch =
+                        Some(quantization.pending_launch(&self.transport, Some(index)))
+                }
+                ClipEvent::Stop(quantization) => {
+                    self.pending_launch = Some(quantization.pending_launch(&self.transport, None))
+                }
+            }
+        }
+    }
+
+    fn set_clip(&mut self, index: usize, clip: Option<Clip>) {
+        let previous = match self.clips.get_mut(index) {
+            Some(slot) => {
+                if self.playing.as_ref().map(|playing| playing.index) == Some(index) {
+                    self.playing = None;
+                }
+                std::mem::replace(slot, clip)
+            }
+            None => clip,
+        };
+
+        if let Some(previous) = previous {
+            self.retired_samples.push(previous.sample);
+        }
+    }
+
+    fn apply_pending_launch(&mut self, beat: f64) {
+        let previous_beat = self.previous_beat;
+        let is_due = self
+            .pending_launch
+            .as_mut()
+            .is_some_and(|launch| launch.advance(previous_beat, beat));
+
+        if !is_due {
+            return;
+        }
+
+        if let Some(launch) = self.pending_launch.take() {
+            self.playing = launch
+                .clip
+                .filter(|index| self.clips.get(*index).map(Option::is_some).unwrap_or(false))
+                .map(|index| PlayingClip {
+                    index,
+                    position: 0.0,
+                });
+        }
+    }
+
+    fn render_frame(&mut self, output_buffer: &mut dyn AudioBuffer, frame: usize) {
+        let playing = match self.playing.as_mut() {
+            Some(playing) => playing,
+            None => return,
+        };
+
+        let clip = match &self.clips[playing.index] {
+            Some(clip) => clip,
+            None => return,
+        };
+
+        let num_frames = clip.sample.num_frames() as f64;
+        if playing.position >= num_frames {
+            if clip.looping && num_frames > 0.0 {
+                playing.position %= num_frames;
+            } else {
+                self.playing = None;
+                return;
+            }
+        }
+
+        let num_channels = clip.sample.num_channels().min(output_buffer.num_channels());
+
+        for channel in 0..num_channels {
+            let value = read_interpolated(clip.sample.as_ref(), channel, playing.position);
+            output_buffer.add_sample(SampleLocation::new(channel, frame), value);
+        }
+
+        playing.position += clip.sample.sample_rate() as f64 / output_buffer.sample_rate() as f64;
+    }
+}
+
+impl DspProcessor for ClipPlayerDspProcess {
+    fn process_audio(
+        &mut self,
+        _input_buffer: &dyn AudioBuffer,
+        output_buffer: &mut dyn AudioBuffer,
+        _start_time: &Timestamp,
+        _parameters: &DspParameterMap,
+    ) {
+        let sample_rate = output_buffer.sample_rate();
+
+        self.read_events();
+
+        for frame in 0..output_buffer.num_frames() {
+            let beat = self.transport.beat_at_frame(frame, sample_rate);
+            self.apply_pending_launch(beat);
+            self.render_frame(output_buffer, frame);
+            self.previous_beat = Some(beat);
+        }
+    }
+
+    fn drain_garbage(&mut self, dispose: &mut dyn FnMut(GarbageCollectionCommand)) {
+        for sample in self.retired_samples.drain(..) {
+            dispose(GarbageCollectionCommand::DisposeSample(sample));
+        }
+    }
+
+    fn set_transport(&mut self, transport: &Transport) {
+        self.transport = *transport;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::OwnedAudioBuffer;
+
+    use super::*;
+
+    const FRAMES_PER_BEAT: f64 = 50.0;
+
+    fn make_clip(num_frames: usize, value: f32, looping: bool) -> Option<Clip> {
+        let mut sample = OwnedAudioBuffer::new(num_frames, 1, 100);
+        sample.fill_with_value(value);
+        Some(Clip::new(Arc::new(sample), looping))
+    }
+
+    fn process_with_transport(
+        player: &mut ClipPlayerDspProcess,
+        start_frame: usize,
+        transport: Transport,
+    ) -> OwnedAudioBuffer {
+        let input_buffer = OwnedAudioBuffer::new(50, 1, 100);
+        let mut output_buffer = OwnedAudioBuffer::new(50, 1, 100);
+        player.set_transport(&transport);
+        player.process_audio(
+            &input_buffer,
+            &mut output_buffer,
+            &Timestamp::from_samples(start_frame as f64, 100),
+            &DspParameterMap::new(),
+        );
+        output_buffer
+    }
+
+    fn process(player: &mut ClipPlayerDspProcess, start_frame: usize) -> OwnedAudioBuffer {
+        let beat = start_frame as f64 / FRAMES_PER_BEAT;
+        process_with_transport(player, start_frame, Transport::new(120.0, beat, None))
+    }
+
+    #[test]
+    fn launches_on_next_beat() {
+        let (mut event_transmitter, event_receiver) = lockfree::channel::spsc::create();
+        let mut player = ClipPlayerDspProcess::new(event_receiver);
+
+        let _ = event_transmitter.send(ClipEvent::SetClip(0, make_clip(20, 1.0, true)));
+        let _ = event_transmitter.send(ClipEvent::Launch(0, LaunchQuantization::beat()));
+
+        let output = process(&mut player, 10);
+        assert_eq!(output.get_sample(SampleLocation::new(0, 39)), 0.0);
+        assert_eq!(output.get_sample(SampleLocation::new(0, 40)), 1.0);
+
+        let output = process(&mut player, 60);
+        assert_eq!(output.get_sample(SampleLocation::new(0, 49)), 1.0);
+    }
+
+    #[test]
+    fn quantized_stop_and_switch() {
+        let (mut event_transmitter, event_receiver) = lockfree::channel::spsc::create();
+        let mut player = ClipPlayerDspProcess::new(event_receiver);
+
+        let _ = event_transmitter.send(ClipEvent::SetClip(0, make_clip(200, 1.0, false)));
+        let _ = event_transmitter.send(ClipEvent::SetClip(1, make_clip(200, 2.0, false)));
+        let _ = event_transmitter.send(ClipEvent::Launch(0, LaunchQuantization::Immediate));
+
+        let output = process(&mut player, 0);
+        assert_eq!(output.get_sample(SampleLocation::new(0, 0)), 1.0);
+
+        let _ = event_transmitter.send(ClipEvent::Launch(1, LaunchQuantization::bar(4.0)));
+        let output = process(&mut player, 150);
+        assert_eq!(output.get_sample(SampleLocation::new(0, 49)), 1.0);
+
+        let output = process(&mut player, 200);
+        assert_eq!(output.get_sample(SampleLocation::new(0, 0)), 2.0);
+        assert_eq!(output.get_sample(SampleLocation::new(0, 49)), 2.0);
+
+        let _ = event_transmitter.send(ClipEvent::Stop(LaunchQuantization::beat()));
+        let output = process(&mut player, 250);
+        assert_eq!(output.get_sample(SampleLocation::new(0, 0)), 0.0);
+    }
+
+    #[test]
+    fn follows_transport_tempo() {
+        let (mut event_transmitter, event_receiver) = lockfree::channel::spsc::create();
+        let mut player = ClipPlayerDspProcess::new(event_receiver);
+
+        let _ = event_transmitter.send(ClipEvent::SetClip(0, make_clip(200, 1.0, false)));
+        let _ = event_transmitter.send(ClipEvent::Launch(0, LaunchQuantization::beat()));
+
+        let output = process_with_transport(&mut player, 10, Transport::new(60.0, 0.1, None));
+        assert_eq!(output.get_sample(SampleLocation::new(0, 49)), 0.0);
+
+        let output = process_with_transport(&mut player, 60, Transport::new(60.0, 0.6, None));
+        assert_eq!(output.get_sample(SampleLocation::new(0, 39)), 0.0);
+        assert_eq!(output.get_sample(SampleLocation::new(0, 40)), 1.0);
+    }
+
+    #[test]
+    fn launches_at_the_loop_start_when_the_boundary_is_past_the_loop_end() {
+        let (mut event_transmitter, event_receiver) = lockfree::channel::spsc::create();
+        let mut player = ClipPlayerDspProcess::new(event_receiver);
+
+        let _ = event_transmitter.send(ClipEvent::SetClip(0, make_clip(200, 1.0, false)));
+        let _ = event_transmitter.send(ClipEvent::Launch(0, LaunchQuantization::bar(4.0)));
+
+        let transport = Transport::new(120.0, 1.9, Some((0.0, 2.0)));
+        let output = process_with_transport(&mut player, 95, transport);
+        assert_eq!(output.get_sample(SampleLocation::new(0, 4)), 0.0);
+        assert_eq!(output.get_sample(SampleLocation::new(0, 5)), 1.0);
+    }
+
+    #[test]
+    fn resamples_clips_recorded_at_another_rate() {
+        let (mut event_transmitter, event_receiver) = lockfree::channel::spsc::create();
+        let mut player = ClipPlayerDspProcess::new(event_receiver);
+
+        let mut sample = OwnedAudioBuffer::new(50, 1, 50);
+        for frame in 0..50 {
+            sample.set_sample(SampleLocation::new(0, frame), frame as f32);
+        }
+
+        let clip = Clip::new(Arc::new(sample), false);
+        let _ = event_transmitter.send(ClipEvent::SetClip(0, Some(clip)));
+        let _ = event_transmitter.send(ClipEvent::Launch(0, LaunchQuantization::Immediate));
+
+        let output = process(&mut player, 0);
+        assert_eq!(output.get_sample(SampleLocation::new(0, 2)), 1.0);
+        assert!((output.get_sample(SampleLocation::new(0, 7)) - 3.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn replaced_clips_are_handed_to_the_garbage_collector() {
+        let (mut event_transmitter, event_receiver) = lockfree::channel::spsc::create();
+        let mut player = ClipPlayerDspProcess::new(event_receiver);
+
+        let sample: SharedSample = Arc::new(OwnedAudioBuffer::new(10, 1, 100));
+        let _ = event_transmitter.send(ClipEvent::SetClip(
+            0,
+            Some(Clip::new(sample.clone(), false)),
+        ));
+        let _ = event_transmitter.send(ClipEvent::SetClip(0, make_clip(10, 1.0, false)));
+        process(&mut player, 0);
+
+        let mut disposed = Vec::new();
+        player.drain_garbage(&mut |command| disposed.push(command));
+
+        assert_eq!(disposed.len(), 1);
+        assert_eq!(Arc::strong_count(&sample), 2);
+        drop(disposed);
+        assert_eq!(Arc::strong_count(&sample), 1);
+    }
+}
@@ -0,0 +1,126 @@
+use std::{collections::HashMap, sync::atomic::Ordering, time::Duration};
+
+use crate::{
+    commands::{command_queue::CommandQueue, id::Id},
+    graph::{dsp::Dsp, node::Node},
+    parameter::audio_parameter::AudioParameter,
+    utility::fast_math,
+    Timestamp,
+};
+
+use super::processor::{CompressorGainReduction, CompressorParameterIds, CompressorProcessor};
+
+pub struct CompressorNode {
+    id: Id,
+    command_queue: CommandQueue,
+    gain_reduction: CompressorGainReduction,
+    pub threshold: AudioParameter,
+    pub ratio: AudioParameter,
+    pub attack: AudioParameter,
+    pub release: AudioParameter,
+    pub knee: AudioParameter,
+    pub makeup: AudioParameter,
+}
+
+const MIN_THRESHOLD: f64 = -60.0;
+const MAX_THRESHOLD: f64 = 0.0;
+const MIN_RATIO: f64 = 1.0;
+const MAX_RATIO: f64 = 20.0;
+const MIN_ATTACK_MS: f64 = 0.0;
+const MAX_ATTACK_MS: f64 = 500.0;
+const MIN_RELEASE_MS: f64 = 0.0;
+const MAX_RELEASE_MS: f64 = 5000.0;
+const MIN_KNEE: f64 = 0.0;
+const MAX_KNEE: f64 = 24.0;
+const MIN_MAKEUP: f64 = 0.0;
+const MAX_MAKEUP: f64 = 24.0;
+
+const DUCKING_THRESHOLD_DB: f64 = -40.0;
+
+impl CompressorNode {
+    pub fn new(command_queue: CommandQueue) -> Self {
+        let mut parameters = HashMap::new();
+
+        let id = Id::generate();
+
+        let mut add_parameter = |value, minimum, maximum| {
+            let (parameter, realtime_parameter) =
+                AudioParameter::new(id, value, minimum, maximum, command_queue.clone());
+            parameters.insert(realtime_parameter.get_id(), realtime_parameter);
+            parameter
+        };
+
+        let threshold = add_parameter(-20.0, MIN_THRESHOLD, MAX_THRESHOLD);
+        let ratio = add_parameter(4.0, MIN_RATIO, MAX_RATIO);
+        let attack = add_parameter(10.0, MIN_ATTACK_MS, MAX_ATTACK_MS);
+        let release = add_parameter(100.0, MIN_RELEASE_MS, MAX_RELEASE_MS);
+        let knee = add_parameter(6.0, MIN_KNEE, MAX_KNEE);
+        let makeup = add_parameter(0.0, MIN_MAKEUP, MAX_MAKEUP);
+
+        let gain_reduction = CompressorGainReduction::default();
+
+        let processor = CompressorProcessor::new(
+            CompressorParameterIds {
+                threshold: threshold.get_id(),
+                ratio: ratio.get_id(),
+                attack: attack.get_id(),
+                release: release.get_id(),
+                knee: knee.get_id(),
+                makeup: makeup.get_id(),
+            },
+            gain_reduction.clone(),
+        );
+
+        let dsp = Dsp::new(id, Box::new(processor), parameters);
+
+        Dsp::add_to_audio_process(dsp, &command_queue);
+
+        Self {
+            id,
+            command_queue,
+            gain_reduction,
+            threshold,
+            ratio,
+            attack,
+            release,
+            knee,
+            makeup,
+        }
+    }
+
+    pub fn set_ducking(&mut self, amount: f64, attack: Duration, release: Duration) {
+        let depth_db = -fast_math::gain_to_db(1.0 - amount.clamp(0.0, 1.0));
+        let slope = (depth_db / -DUCKING_THRESHOLD_DB).min(1.0 - 1.0 / MAX_RATIO);
+
+        self.threshold
+            .set_value_at_time(DUCKING_THRESHOLD_DB, Timestamp::zero());
+        self.ratio
+            .set_value_at_time(1.0 / (1.0 - slope), Timestamp::zero());
+        self.attack
+            .set_value_at_time(attack.as_secs_f64() * 1000.0, Timestamp::zero());
+        self.release
+            .set_value_at_time(release.as_secs_f64() * 1000.0, Timestamp::zero());
+        self.knee.set_value_at_time(MIN_KNEE, Timestamp::zero());
+        self.makeup.set_value_at_time(MIN_MAKEUP, Timestamp::zero());
+    }
+
+    pub fn get_gain_reduction_db(&self) -> f64 {
+        self.gain_reduction.load(Ordering::Acquire)
+    }
+}
+
+impl Node for CompressorNode {
+    fn get_id(&self) -> Id {
+        self.id
+    }
+
+    fn get_command_queue(&self) -> CommandQueue {
+        self.command_queue.clone()
+    }
+}
+
+impl Drop for CompressorNode {
+    fn drop(&mut self) {
+        Dsp::remove_from_audio_process(self.id, &self.command_queue);
+    }
+}
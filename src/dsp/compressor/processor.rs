@@ -0,0 +1,290 @@
+use std::sync::{atomic::Ordering, Arc};
+
+use atomic_float::AtomicF64;
+
+use crate::{
+    commands::id::Id,
+    graph::dsp::{DspParameterMap, DspProcessor},
+    utility::fast_math,
+    AudioBuffer, SampleLocation, Timestamp,
+};
+
+pub type CompressorGainReduction = Arc<AtomicF64>;
+
+pub struct CompressorParameterIds {
+    pub threshold: Id,
+    pub ratio: Id,
+    pub attack: Id,
+    pub release: Id,
+    pub knee: Id,
+    pub makeup: Id,
+}
+
+pub struct CompressorProcessor {
+    parameter_ids: CompressorParameterIds,
+    gain_reduction: CompressorGainReduction,
+    envelope_db: f64,
+}
+
+impl CompressorProcessor {
+    pub fn new(
+        parameter_ids: CompressorParameterIds,
+        gain_reduction: CompressorGainReduction,
+    ) -> Self {
+        Self {
+            parameter_ids,
+            gain_reduction,
+            envelope_db: 0.0,
+        }
+    }
+
+    fn coefficient(time_ms: f64, sample_rate: usize) -> f64 {
+        let num_samples = time_ms * 0.001 * sample_rate as f64;
+
+        if num_samples <= 1.0 {
+            return 0.0;
+        }
+
+        (-1.0 / num_samples).exp()
+    }
+
+    fn gain_reduction_db(level_db: f64, threshold: f64, ratio: f64, knee: f64) -> f64 {
+        let overshoot = level_db - threshold;
+        let slope = 1.0 - 1.0 / ratio;
+
+        if 2.0 * overshoot <= -knee {
+            return 0.0;
+        }
+
+        if 2.0 * overshoot.abs() < knee {
+            let distance = overshoot + knee / 2.0;
+            return slope * distance * distance / (2.0 * knee);
+        }
+
+        slope * overshoot
+    }
+
+    fn peak_at_frame(buffer: &dyn AudioBuffer, frame: usize) -> f64 {
+        (0..buffer.num_channels())
+            .map(|channel| buffer.get_sample(SampleLocation::new(channel, frame)).abs())
+            .fold(0.0_f32, f32::max) as f64
+    }
+
+    fn compress(
+        &mut self,
+        input_buffer: &dyn AudioBuffer,
+        detector_buffer: &dyn AudioBuffer,
+        output_buffer: &mut dyn AudioBuffer,
+        start_time: &Timestamp,
+        parameters: &DspParameterMap,
+    ) {
+        let sample_rate = output_buffer.sample_rate();
+
+        let ids = &self.parameter_ids;
+        let (threshold, ratio, attack, release, knee, makeup) = match (
+            parameters.get(&ids.threshold),
+            parameters.get(&ids.ratio),
+            parameters.get(&ids.attack),
+            parameters.get(&ids.release),
+            parameters.get(&ids.knee),
+            parameters.get(&ids.makeup),
+        ) {
+            (
+                Some(threshold),
+                Some(ratio),
+                Some(attack),
+                Some(release),
+                Some(knee),
+                Some(makeup),
+            ) => (threshold, ratio, attack, release, knee, makeup),
+            _ => return,
+        };
+
+        let attack = Self::coefficient(attack.get_value_at_time(start_time), sample_rate);
+        let release = Self::coefficient(release.get_value_at_time(start_time), sample_rate);
+
+        let num_channels = output_buffer
+            .num_channels()
+            .min(input_buffer.num_channels());
+        let num_detector_frames = detector_buffer.num_frames();
+
+        for frame in 0..output_buffer.num_frames() {
+            let frame_time = start_time.incremented_by_samples(frame, sample_rate);
+
+            let level_db = if frame < num_detector_frames {
+                fast_math::gain_to_db(Self::peak_at_frame(detector_buffer, frame))
+            } else {
+                fast_math::gain_to_db(0.0)
+            };
+
+            let target_db = Self::gain_reduction_db(
+                level_db,
+                threshold.get_value_at_time(&frame_time),
+                ratio.get_value_at_time(&frame_time),
+                knee.get_value_at_time(&frame_time),
+            );
+
+            let coefficient = if target_db > self.envelope_db {
+                attack
+            } else {
+                release
+            };
+            self.envelope_db = target_db + coefficient * (self.envelope_db - target_db);
+
+            let gain =
+                fast_math::db_to_gain(makeup.get_value_at_time(&frame_time) - self.envelope_db)
+                    as f32;
+
+            for channel in 0..num_channels {
+                let location = SampleLocation::new(channel, frame);
+                output_buffer.set_sample(location, input_buffer.get_sample(location) * gain);
+            }
+        }
+
+        self.gain_reduction
+            .store(self.envelope_db, Ordering::Release);
+    }
+}
+
+impl DspProcessor for CompressorProcessor {
+    fn process_audio(
+        &mut self,
+        input_buffer: &dyn AudioBuffer,
+        output_buffer: &mut dyn AudioBuffer,
+        start_time: &Timestamp,
+        parameters: &DspParameterMap,
+    ) {
+        self.compress(
+            input_buffer,
+            input_buffer,
+            output_buffer,
+            start_time,
+            parameters,
+        );
+    }
+
+    fn process_audio_with_sidechain(
+        &mut self,
+        input_buffer: &dyn AudioBuffer,
+        sidechain_buffer: &dyn AudioBuffer,
+        output_buffer: &mut dyn AudioBuffer,
+        start_time: &Timestamp,
+        parameters: &DspParameterMap,
+    ) {
+        self.compress(
+            input_buffer,
+            sidechain_buffer,
+            output_buffer,
+            start_time,
+            parameters,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parameter::realtime_parameter::RealtimeAudioParameter, OwnedAudioBuffer};
+
+    use super::*;
+
+    fn make_parameters(values: [f64; 6]) -> (CompressorParameterIds, DspParameterMap) {
+        let ids = CompressorParameterIds {
+            threshold: Id::generate(),
+            ratio: Id::generate(),
+            attack: Id::generate(),
+            release: Id::generate(),
+            knee: Id::generate(),
+            makeup: Id::generate(),
+        };
+
+        let mut parameters = DspParameterMap::new();
+        for (id, value) in [
+            ids.threshold,
+            ids.ratio,
+            ids.attack,
+            ids.release,
+            ids.knee,
+            ids.makeup,
+        ]
+        .into_iter()
+        .zip(values)
+        {
+            parameters.insert(
+                id,
+                RealtimeAudioParameter::new(id, Arc::new(AtomicF64::new(value))),
+            );
+        }
+
+        (ids, parameters)
+    }
+
+    fn constant_buffer(value: f32) -> OwnedAudioBuffer {
+        let mut buffer = OwnedAudioBuffer::new(1000, 1, 1000);
+        buffer.fill_with_value(value);
+        buffer
+    }
+
+    #[test]
+    fn reduces_level_above_threshold_by_ratio() {
+        let (ids, parameters) = make_parameters([-20.0, 4.0, 0.0, 0.0, 0.0, 0.0]);
+        let gain_reduction = CompressorGainReduction::default();
+        let mut processor = CompressorProcessor::new(ids, gain_reduction.clone());
+
+        let input_buffer = constant_buffer(fast_math::db_to_gain(-8.0) as f32);
+        let mut output_buffer = constant_buffer(0.0);
+        processor.process_audio(
+            &input_buffer,
+            &mut output_buffer,
+            &Timestamp::zero(),
+            &parameters,
+        );
+
+        let output_db =
+            fast_math::gain_to_db(output_buffer.get_sample(SampleLocation::new(0, 999)) as f64);
+        assert!((output_db + 17.0).abs() < 0.01);
+        assert!((gain_reduction.load(Ordering::Acquire) - 9.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn soft_knee_is_continuous_with_hard_regions() {
+        let below = CompressorProcessor::gain_reduction_db(-23.0, -20.0, 4.0, 6.0);
+        let above = CompressorProcessor::gain_reduction_db(-17.0, -20.0, 4.0, 6.0);
+        let at_threshold = CompressorProcessor::gain_reduction_db(-20.0, -20.0, 4.0, 6.0);
+
+        assert!(below.abs() < 1e-9);
+        assert!((above - 2.25).abs() < 1e-9);
+        assert!(at_threshold > 0.0 && at_threshold < 2.25);
+    }
+
+    #[test]
+    fn sidechain_keys_the_detector() {
+        let (ids, parameters) = make_parameters([-20.0, 10.0, 0.0, 0.0, 0.0, 6.0]);
+        let mut processor = CompressorProcessor::new(ids, CompressorGainReduction::default());
+
+        let input_buffer = constant_buffer(0.01);
+        let silent_sidechain = constant_buffer(0.0);
+        let mut output_buffer = constant_buffer(0.0);
+        processor.process_audio_with_sidechain(
+            &input_buffer,
+            &silent_sidechain,
+            &mut output_buffer,
+            &Timestamp::zero(),
+            &parameters,
+        );
+
+        let makeup_only = output_buffer.get_sample(SampleLocation::new(0, 999));
+        assert!((makeup_only - 0.01 * fast_math::db_to_gain(6.0) as f32).abs() < 1e-5);
+
+        let loud_sidechain = constant_buffer(1.0);
+        processor.process_audio_with_sidechain(
+            &input_buffer,
+            &loud_sidechain,
+            &mut output_buffer,
+            &Timestamp::zero(),
+            &parameters,
+        );
+
+        let keyed = output_buffer.get_sample(SampleLocation::new(0, 999));
+        assert!((fast_math::gain_to_db(keyed as f64) - (-40.0 + 6.0 - 18.0)).abs() < 0.01);
+    }
+}
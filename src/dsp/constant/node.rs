@@ -0,0 +1,85 @@
+use std::collections::HashMap;
+
+use crate::{
+    commands::{command_queue::CommandQueue, id::Id},
+    graph::{dsp::Dsp, node::Node},
+    parameter::{
+        audio_parameter::AudioParameter,
+        preset::{NodePreset, PresetError, PresetNode},
+    },
+};
+
+use super::processor::ConstantSourceProcessor;
+
+pub const MIN_SIGNAL_VALUE: f64 = -100_000.0;
+pub const MAX_SIGNAL_VALUE: f64 = 100_000.0;
+
+pub struct ConstantSourceNode {
+    id: Id,
+    command_queue: CommandQueue,
+    pub offset: AudioParameter,
+}
+
+impl ConstantSourceNode {
+    pub fn new(command_queue: CommandQueue, value: f64) -> Self {
+        let mut parameters = HashMap::new();
+
+        let id = Id::generate();
+
+        let (offset, realtime_offset) = AudioParameter::new(
+            id,
+            value,
+            MIN_SIGNAL_VALUE,
+            MAX_SIGNAL_VALUE,
+            command_queue.clone(),
+        );
+        parameters.insert(realtime_offset.get_id(), realtime_offset);
+
+        let dsp = Dsp::new(
+            id,
+            Box::new(ConstantSourceProcessor::new(offset.get_id())),
+            parameters,
+        );
+
+        Dsp::add_to_audio_process(dsp, &command_queue);
+        Self {
+            id,
+            command_queue,
+            offset,
+        }
+    }
+}
+
+impl Node for ConstantSourceNode {
+    fn get_id(&self) -> Id {
+        self.id
+    }
+
+    fn get_command_queue(&self) -> CommandQueue {
+        self.command_queue.clone()
+    }
+}
+
+impl Drop for ConstantSourceNode {
+    fn drop(&mut self) {
+        Dsp::remove_from_audio_process(self.id, &self.command_queue);
+    }
+}
+
+impl PresetNode for ConstantSourceNode {
+    fn preset_type(&self) -> &'static str {
+        "constant_source"
+    }
+
+    fn capture_preset(&self, name: &str) -> NodePreset {
+        let mut preset = NodePreset::new(self.preset_type(), name);
+        preset.capture_parameter("offset", &self.offset);
+        preset
+    }
+
+    fn apply_preset(&mut self, preset: &NodePreset) -> Result<(), PresetError> {
+        preset.check_node_type(self.preset_type())?;
+        preset.apply_parameter("offset", &mut self.offset);
+        Ok(())
+    }
+}
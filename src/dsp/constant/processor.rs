@@ -0,0 +1,41 @@
+use crate::{
+    commands::id::Id,
+    graph::dsp::{DspParameterMap, DspProcessor},
+    AudioBuffer, SampleLocation, Timestamp,
+};
+
+pub struct ConstantSourceProcessor {
+    offset_id: Id,
+}
+
+impl ConstantSourceProcessor {
+    pub fn new(offset_id: Id) -> Self {
+        Self { offset_id }
+    }
+}
+
+impl DspProcessor for ConstantSourceProcessor {
+    fn process_audio(
+        &mut self,
+        _input_buffer: &dyn AudioBuffer,
+        output_buffer: &mut dyn AudioBuffer,
+        start_time: &Timestamp,
+        parameters: &DspParameterMap,
+    ) {
+        let sample_rate = output_buffer.sample_rate();
+
+        let offset = match parameters.get(&self.offset_id) {
+            Some(param) => param,
+            None => return,
+        };
+
+        for frame in 0..output_buffer.num_frames() {
+            let frame_time = start_time.incremented_by_samples(frame, sample_rate);
+            let value = offset.get_value_at_time(&frame_time) as f32;
+
+            for channel in 0..output_buffer.num_channels() {
+                output_buffer.set_sample(SampleLocation::new(channel, frame), value);
+            }
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+
+use crate::{
+    buffer::owned_audio_buffer::OwnedAudioBuffer,
+    commands::{command_queue::CommandQueue, id::Id},
+    graph::{dsp::Dsp, node::Node},
+    parameter::audio_parameter::AudioParameter,
+    timestamp::Timestamp,
+};
+
+use super::processor::ConvolverProcessor;
+
+pub struct ConvolverNode {
+    id: Id,
+    command_queue: CommandQueue,
+    pub mix: AudioParameter,
+}
+
+const MIN_MIX: f64 = 0.0;
+const MAX_MIX: f64 = 1.0;
+
+impl ConvolverNode {
+    pub fn new(command_queue: CommandQueue, impulse_response: OwnedAudioBuffer) -> Self {
+        let mut parameters = HashMap::new();
+
+        let id = Id::generate();
+
+        let (mut mix, realtime_mix) =
+            AudioParameter::new(id, MIN_MIX, MIN_MIX, MAX_MIX, command_queue.clone());
+        parameters.insert(realtime_mix.get_id(), realtime_mix);
+
+        let processor = ConvolverProcessor::new(mix.get_id(), &impulse_response);
+        let dsp = Dsp::new(id, Box::new(processor), parameters);
+
+        Dsp::add_to_audio_process(dsp, &command_queue);
+        mix.set_value_at_time(MAX_MIX, Timestamp::zero());
+
+        Self {
+            id,
+            command_queue,
+            mix,
+        }
+    }
+}
+
+impl Node for ConvolverNode {
+    fn get_id(&self) -> Id {
+        self.id
+    }
+
+    fn get_command_queue(&self) -> CommandQueue {
+        self.command_queue.clone()
+    }
+}
+
+impl Drop for ConvolverNode {
+    fn drop(&mut self) {
+        Dsp::remove_from_audio_process(self.id, &self.command_queue);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AudioBuffer, ConstantSource, Context, SampleLocation};
+
+    #[test]
+    fn renders_fully_wet_by_default() {
+        let mut context = Context::new(48000);
+        let mut audio_process = context.get_audio_process();
+        let mut output_buffer = OwnedAudioBuffer::new(512, 2, 48000);
+
+        let mut impulse_response = OwnedAudioBuffer::new(1, 2, 48000);
+        impulse_response.fill_with_value(0.5);
+
+        let constant = ConstantSource::new(context.get_command_queue(), 0.5);
+        let constant = context.add_node(constant).unwrap();
+        let convolver = ConvolverNode::new(context.get_command_queue(), impulse_response);
+        let convolver = context.add_node(convolver).unwrap();
+        context.connect_nodes(&constant, &convolver).unwrap();
+        context.connect_node_to_output(&convolver).unwrap();
+        context.start();
+
+        audio_process.process(&mut output_buffer);
+
+        let last_sample = output_buffer.get_sample(SampleLocation::new(0, 511));
+        assert!((last_sample - 0.25).abs() < 1e-6);
+    }
+}
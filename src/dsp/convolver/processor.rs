@@ -0,0 +1,340 @@
+use std::time::Duration;
+
+use crate::{
+    commands::id::Id,
+    graph::dsp::{DspParameterMap, DspProcessor},
+    utility::fft::Fft,
+    AudioBuffer, SampleLocation, Timestamp,
+};
+
+pub const PARTITION_SIZE: usize = 256;
+const FFT_SIZE: usize = 2 * PARTITION_SIZE;
+const NUM_BINS: usize = PARTITION_SIZE + 1;
+const MAXIMUM_NUMBER_OF_CHANNELS: usize = 2;
+
+struct Spectrum {
+    real: Vec<f32>,
+    imaginary: Vec<f32>,
+}
+
+impl Spectrum {
+    fn new() -> Self {
+        Self {
+            real: vec![0.0; NUM_BINS],
+            imaginary: vec![0.0; NUM_BINS],
+        }
+    }
+
+    fn clear(&mut self) {
+        self.real.fill(0.0);
+        self.imaginary.fill(0.0);
+    }
+}
+
+struct ImpulseResponse {
+    head: Vec<f32>,
+    partitions: Vec<Spectrum>,
+}
+
+impl ImpulseResponse {
+    fn new(fft: &Fft, samples: &[f32]) -> Self {
+        let mut head = vec![0.0; PARTITION_SIZE];
+        let head_length = samples.len().min(PARTITION_SIZE);
+        head[..head_length].copy_from_slice(&samples[..head_length]);
+
+        let mut real = vec![0.0; FFT_SIZE];
+        let mut imaginary = vec![0.0; FFT_SIZE];
+
+        let partitions = samples
+            .chunks(PARTITION_SIZE)
+            .skip(1)
+            .map(|partition| {
+                real.fill(0.0);
+                imaginary.fill(0.0);
+                real[..partition.len()].copy_from_slice(partition);
+                fft.forward(&mut real, &mut imaginary);
+
+                Spectrum {
+                    real: real[..NUM_BINS].to_vec(),
+                    imaginary: imaginary[..NUM_BINS].to_vec(),
+                }
+            })
+            .collect();
+
+        Self { head, partitions }
+    }
+}
+
+struct ChannelState {
+    input: Vec<f32>,
+    tail: Vec<f32>,
+    delay_line: Vec<Spectrum>,
+    newest: usize,
+}
+
+impl ChannelState {
+    fn new(num_partitions: usize) -> Self {
+        Self {
+            input: vec![0.0; FFT_SIZE],
+            tail: vec![0.0; PARTITION_SIZE],
+            delay_line: (0..num_partitions).map(|_| Spectrum::new()).collect(),
+            newest: 0,
+        }
+    }
+
+    fn head_at(&self, impulse_response: &ImpulseResponse, position: usize) -> f32 {
+        let newest = PARTITION_SIZE + position;
+
+        impulse_response
+            .head
+            .iter()
+            .enumerate()
+            .map(|(offset, coefficient)| coefficient * self.input[newest - offset])
+            .sum()
+    }
+
+    fn advance(&mut self, impulse_response: &ImpulseResponse, fft: &Fft, scratch: &mut Scratch) {
+        if !self.delay_line.is_empty() {
+            self.newest = (self.newest + 1) % self.delay_line.len();
+
+            scratch.real.copy_from_slice(&self.input);
+            scratch.imaginary.fill(0.0);
+            fft.forward(&mut scratch.real, &mut scratch.imaginary);
+
+            let spectrum = &mut self.delay_line[self.newest];
+            spectrum.real.copy_from_slice(&scratch.real[..NUM_BINS]);
+            spectrum
+                .imaginary
+                .copy_from_slice(&scratch.imaginary[..NUM_BINS]);
+
+            scratch.sum.clear();
+            let num_partitions = self.delay_line.len();
+
+            for (age, partition) in impulse_response.partitions.iter().enumerate() {
+                let input = &self.delay_line[(self.newest + num_partitions - age) % num_partitions];
+
+                for bin in 0..NUM_BINS {
+                    scratch.sum.real[bin] += input.real[bin] * partition.real[bin]
+                        - input.imaginary[bin] * partition.imaginary[bin];
+                    scratch.sum.imaginary[bin] += input.real[bin] * partition.imaginary[bin]
+                        + input.imaginary[bin] * partition.real[bin];
+                }
+            }
+
+            scratch.real[..NUM_BINS].copy_from_slice(&scratch.sum.real);
+            scratch.imaginary[..NUM_BINS].copy_from_slice(&scratch.sum.imaginary);
+            for bin in NUM_BINS..FFT_SIZE {
+                scratch.real[bin] = scratch.sum.real[FFT_SIZE - bin];
+                scratch.imaginary[bin] = -scratch.sum.imaginary[FFT_SIZE - bin];
+            }
+
+            fft.inverse(&mut scratch.real, &mut scratch.imaginary);
+            self.tail.copy_from_slice(&scratch.real[PARTITION_SIZE..]);
+        }
+
+        self.input.copy_within(PARTITION_SIZE.., 0);
+    }
+}
+
+struct Scratch {
+    real: Vec<f32>,
+    imaginary: Vec<f32>,
+    sum: Spectrum,
+}
+
+pub struct ConvolverProcessor {
+    mix_id: Id,
+    fft: Fft,
+    impulse_responses: Vec<ImpulseResponse>,
+    channels: Vec<ChannelState>,
+    scratch: Scratch,
+    position: usize,
+    tail_time: Duration,
+}
+
+impl ConvolverProcessor {
+    pub fn new(mix_id: Id, impulse_response: &dyn AudioBuffer) -> Self {
+        let fft = Fft::new(FFT_SIZE);
+
+        let num_frames = impulse_response.num_frames();
+        let impulse_responses: Vec<ImpulseResponse> = (0..impulse_response
+            .num_channels()
+            .clamp(1, MAXIMUM_NUMBER_OF_CHANNELS))
+            .map(|channel| {
+                let samples: Vec<f32> = (0..num_frames)
+                    .map(|frame| {
+                        if channel < impulse_response.num_channels() {
+                            impulse_response.get_sample(SampleLocation::new(channel, frame))
+                        } else {
+                            0.0
+                        }
+                    })
+                    .collect();
+                ImpulseResponse::new(&fft, &samples)
+            })
+            .collect();
+
+        let num_partitions = num_frames.div_ceil(PARTITION_SIZE).saturating_sub(1);
+
+        Self {
+            mix_id,
+            fft,
+            impulse_responses,
+            channels: (0..MAXIMUM_NUMBER_OF_CHANNELS)
+                .map(|_| ChannelState::new(num_partitions))
+                .collect(),
+            scratch: Scratch {
+                real: vec![0.0; FFT_SIZE],
+                imaginary: vec![0.0; FFT_SIZE],
+                sum: Spectrum::new(),
+            },
+            position: 0,
+            tail_time: Duration::from_secs_f64(
+                num_frames as f64 / impulse_response.sample_rate() as f64,
+            ),
+        }
+    }
+
+    fn impulse_response_for(
+        impulse_responses: &[ImpulseResponse],
+        channel: usize,
+    ) -> &ImpulseResponse {
+        &impulse_responses[channel.min(impulse_responses.len() - 1)]
+    }
+}
+
+impl DspProcessor for ConvolverProcessor {
+    fn process_audio(
+        &mut self,
+        input_buffer: &dyn AudioBuffer,
+        output_buffer: &mut dyn AudioBuffer,
+        start_time: &Timestamp,
+        parameters: &DspParameterMap,
+    ) {
+        let mix = match parameters.get(&self.mix_id) {
+            Some(mix) => mix,
+            None => return,
+        };
+
+        let sample_rate = output_buffer.sample_rate();
+        let num_channels = output_buffer
+            .num_channels()
+            .min(input_buffer.num_channels())
+            .min(MAXIMUM_NUMBER_OF_CHANNELS);
+
+        for frame in 0..output_buffer.num_frames() {
+            let frame_time = start_time.incremented_by_samples(frame, sample_rate);
+            let mix = mix.get_value_at_time(&frame_time) as f32;
+
+            for channel in 0..num_channels {
+                let location = SampleLocation::new(channel, frame);
+                let dry = input_buffer.get_sample(location);
+
+                let impulse_response = Self::impulse_response_for(&self.impulse_responses, channel);
+                let state = &mut self.channels[channel];
+                state.input[PARTITION_SIZE + self.position] = dry;
+                let wet =
+                    state.head_at(impulse_response, self.position) + state.tail[self.position];
+
+                output_buffer.set_sample(location, dry + mix * (wet - dry));
+            }
+
+            self.position += 1;
+
+            if self.position == PARTITION_SIZE {
+                self.position = 0;
+
+                for channel in 0..MAXIMUM_NUMBER_OF_CHANNELS {
+                    let impulse_response =
+                        Self::impulse_response_for(&self.impulse_responses, channel);
+                    self.channels[channel].advance(impulse_response, &self.fft, &mut self.scratch);
+                }
+            }
+        }
+    }
+
+    fn tail_time(&self) -> Option<Duration> {
+        Some(self.tail_time)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use atomic_float::AtomicF64;
+
+    use crate::{parameter::realtime_parameter::RealtimeAudioParameter, OwnedAudioBuffer};
+
+    use super::*;
+
+    fn pseudo_random(length: usize, seed: u32) -> Vec<f32> {
+        let mut state = seed;
+        (0..length)
+            .map(|_| {
+                state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                (state >> 8) as f32 / (1 << 24) as f32 - 0.5
+            })
+            .collect()
+    }
+
+    #[test]
+    fn matches_direct_convolution_across_partitions() {
+        let impulse_response = pseudo_random(3 * PARTITION_SIZE + 37, 1);
+        let input = pseudo_random(6 * PARTITION_SIZE, 2);
+
+        let mut impulse_response_buffer = OwnedAudioBuffer::new(impulse_response.len(), 1, 48_000);
+        for (frame, sample) in impulse_response.iter().enumerate() {
+            impulse_response_buffer.set_sample(SampleLocation::new(0, frame), *sample);
+        }
+
+        let mix_id = Id::generate();
+        let mut parameters = DspParameterMap::new();
+        parameters.insert(
+            mix_id,
+            RealtimeAudioParameter::new(mix_id, Arc::new(AtomicF64::new(1.0))),
+        );
+
+        let mut processor = ConvolverProcessor::new(mix_id, &impulse_response_buffer);
+        assert_eq!(
+            processor.tail_time(),
+            Some(Duration::from_secs_f64(
+                impulse_response.len() as f64 / 48_000.0
+            ))
+        );
+
+        let mut output = Vec::new();
+        for block in input.chunks(100) {
+            let mut input_buffer = OwnedAudioBuffer::new(block.len(), 2, 48_000);
+            for (frame, sample) in block.iter().enumerate() {
+                input_buffer.set_sample(SampleLocation::new(0, frame), *sample);
+                input_buffer.set_sample(SampleLocation::new(1, frame), -*sample);
+            }
+
+            let mut output_buffer = OwnedAudioBuffer::new(block.len(), 2, 48_000);
+            processor.process_audio(
+                &input_buffer,
+                &mut output_buffer,
+                &Timestamp::zero(),
+                &parameters,
+            );
+
+            for frame in 0..block.len() {
+                let left = output_buffer.get_sample(SampleLocation::new(0, frame));
+                let right = output_buffer.get_sample(SampleLocation::new(1, frame));
+                assert!((left + right).abs() < 1e-4);
+                output.push(left);
+            }
+        }
+
+        for (index, actual) in output.iter().enumerate() {
+            let expected: f32 = impulse_response
+                .iter()
+                .take(index + 1)
+                .enumerate()
+                .map(|(offset, coefficient)| coefficient * input[index - offset])
+                .sum();
+            assert!((actual - expected).abs() < 1e-3, "frame {}", index);
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+
+use crate::{
+    commands::{command_queue::CommandQueue, id::Id},
+    graph::{dsp::Dsp, node::Node},
+    parameter::audio_parameter::AudioParameter,
+    utility::gain_reduction::{self, GainReduction, GainReductionReceiver},
+};
+
+use super::processor::DeEsserProcessor;
+
+pub struct DeEsserNode {
+    id: Id,
+    command_queue: CommandQueue,
+    gain_reduction: GainReductionReceiver,
+    pub frequency: AudioParameter,
+    pub threshold: AudioParameter,
+    pub amount: AudioParameter,
+}
+
+const MIN_FREQUENCY: f64 = 2000.0;
+const MAX_FREQUENCY: f64 = 16000.0;
+const MIN_THRESHOLD: f64 = -60.0;
+const MAX_THRESHOLD: f64 = 0.0;
+const MIN_AMOUNT: f64 = 0.0;
+const MAX_AMOUNT: f64 = 24.0;
+
+impl DeEsserNode {
+    pub fn new(command_queue: CommandQueue) -> Self {
+        let mut parameters = HashMap::new();
+
+        let id = Id::generate();
+
+        let (frequency, realtime_frequency) = AudioParameter::new(
+            id,
+            6500.0,
+            MIN_FREQUENCY,
+            MAX_FREQUENCY,
+            command_queue.clone(),
+        );
+        parameters.insert(realtime_frequency.get_id(), realtime_frequency);
+
+        let (threshold, realtime_threshold) = AudioParameter::new(
+            id,
+            -30.0,
+            MIN_THRESHOLD,
+            MAX_THRESHOLD,
+            command_queue.clone(),
+        );
+        parameters.insert(realtime_threshold.get_id(), realtime_threshold);
+
+        let (amount, realtime_amount) =
+            AudioParameter::new(id, 6.0, MIN_AMOUNT, MAX_AMOUNT, command_queue.clone());
+        parameters.insert(realtime_amount.get_id(), realtime_amount);
+
+        let (meter, gain_reduction) = gain_reduction::create();
+
+        let processor = DeEsserProcessor::new(
+            frequency.get_id(),
+            threshold.get_id(),
+            amount.get_id(),
+            meter,
+        );
+
+        let dsp = Dsp::new(id, Box::new(processor), parameters);
+
+        Dsp::add_to_audio_process(dsp, &command_queue);
+
+        Self {
+            id,
+            command_queue,
+            gain_reduction,
+            frequency,
+            threshold,
+            amount,
+        }
+    }
+
+    pub fn take_gain_reduction(&mut self) -> Vec<GainReduction> {
+        self.gain_reduction.take()
+    }
+
+    pub fn get_gain_reduction_db(&mut self) -> f64 {
+        self.gain_reduction.latest_reduction_db()
+    }
+}
+
+impl Node for DeEsserNode {
+    fn get_id(&self) -> Id {
+        self.id
+    }
+
+    fn get_command_queue(&self) -> CommandQueue {
+        self.command_queue.clone()
+    }
+}
+
+impl Drop for DeEsserNode {
+    fn drop(&mut self) {
+        Dsp::remove_from_audio_process(self.id, &self.command_queue);
+    }
+}
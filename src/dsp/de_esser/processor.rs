@@ -0,0 +1,197 @@
+use std::time::Duration;
+
+use crate::{
+    commands::id::Id,
+    graph::dsp::{DspParameterMap, DspProcessor},
+    utility::{
+        envelope_follower::EnvelopeFollower,
+        fast_math,
+        gain_reduction::GainReductionMeter,
+        state_variable_filter::{FilterState, StateVariableFilter},
+    },
+    AudioBuffer, SampleLocation, Timestamp,
+};
+
+const MAXIMUM_NUMBER_OF_CHANNELS: usize = 2;
+const BAND_Q: f64 = 1.4;
+const ATTACK: Duration = Duration::from_millis(1);
+const RELEASE: Duration = Duration::from_millis(60);
+
+pub struct DeEsserProcessor {
+    frequency_id: Id,
+    threshold_id: Id,
+    amount_id: Id,
+    filter: StateVariableFilter,
+    filter_states: [FilterState; MAXIMUM_NUMBER_OF_CHANNELS],
+    envelope: EnvelopeFollower,
+    meter: GainReductionMeter,
+}
+
+impl DeEsserProcessor {
+    pub fn new(
+        frequency_id: Id,
+        threshold_id: Id,
+        amount_id: Id,
+        meter: GainReductionMeter,
+    ) -> Self {
+        Self {
+            frequency_id,
+            threshold_id,
+            amount_id,
+            filter: StateVariableFilter::default(),
+            filter_states: [FilterState::default(); MAXIMUM_NUMBER_OF_CHANNELS],
+            envelope: EnvelopeFollower::new(ATTACK, RELEASE),
+            meter,
+        }
+    }
+}
+
+impl DspProcessor for DeEsserProcessor {
+    fn process_audio(
+        &mut self,
+        input_buffer: &dyn AudioBuffer,
+        output_buffer: &mut dyn AudioBuffer,
+        start_time: &Timestamp,
+        parameters: &DspParameterMap,
+    ) {
+        let sample_rate = output_buffer.sample_rate();
+
+        let (frequency, threshold, amount) = match (
+            parameters.get(&self.frequency_id),
+            parameters.get(&self.threshold_id),
+            parameters.get(&self.amount_id),
+        ) {
+            (Some(frequency), Some(threshold), Some(amount)) => (frequency, threshold, amount),
+            _ => return,
+        };
+
+        self.envelope.prepare(sample_rate);
+
+        let num_channels = output_buffer
+            .num_channels()
+            .min(input_buffer.num_channels())
+            .min(MAXIMUM_NUMBER_OF_CHANNELS);
+
+        let mut bands = [0.0; MAXIMUM_NUMBER_OF_CHANNELS];
+
+        for frame in 0..output_buffer.num_frames() {
+            let frame_time = start_time.incremented_by_samples(frame, sample_rate);
+
+            self.filter.update(
+                frequency.get_value_at_time(&frame_time),
+                BAND_Q,
+                sample_rate,
+            );
+
+            let mut peak = 0.0_f64;
+            for (channel, band) in bands.iter_mut().enumerate().take(num_channels) {
+                let input = input_buffer.get_sample(SampleLocation::new(channel, frame)) as f64;
+                *band = self
+                    .filter
+                    .process(&mut self.filter_states[channel], input)
+                    .band;
+                peak = peak.max(band.abs());
+            }
+
+            let envelope = self.envelope.process(peak);
+
+            let over_db =
+                fast_math::gain_to_db(envelope) - threshold.get_value_at_time(&frame_time);
+            let reduction_db = over_db.clamp(0.0, amount.get_value_at_time(&frame_time));
+            let band_gain = fast_math::db_to_gain(-reduction_db);
+
+            self.meter.record(reduction_db);
+
+            for (channel, band) in bands.iter().enumerate().take(num_channels) {
+                let location = SampleLocation::new(channel, frame);
+                let input = input_buffer.get_sample(location) as f64;
+                output_buffer.set_sample(location, (input + (band_gain - 1.0) * band) as f32);
+            }
+        }
+
+        self.meter
+            .advance(start_time, output_buffer.num_frames(), sample_rate);
+    }
+
+    fn tail_time(&self) -> Option<Duration> {
+        Some(RELEASE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{f64::consts::TAU, sync::Arc};
+
+    use atomic_float::AtomicF64;
+
+    use crate::{
+        parameter::realtime_parameter::RealtimeAudioParameter, utility::gain_reduction,
+        OwnedAudioBuffer,
+    };
+
+    use crate::utility::level::Level;
+
+    use super::*;
+
+    const SAMPLE_RATE: usize = 48_000;
+
+    fn parameter(parameters: &mut DspParameterMap, value: f64) -> Id {
+        let id = Id::generate();
+        parameters.insert(
+            id,
+            RealtimeAudioParameter::new(id, Arc::new(AtomicF64::new(value))),
+        );
+        id
+    }
+
+    fn output_peak(processor: &mut DeEsserProcessor, parameters: &DspParameterMap, hz: f64) -> f32 {
+        let mut peak = 0.0_f32;
+
+        for block in 0..20 {
+            let mut input_buffer = OwnedAudioBuffer::new(512, 1, SAMPLE_RATE);
+            for frame in 0..512 {
+                let time = (block * 512 + frame) as f64 / SAMPLE_RATE as f64;
+                let value = 0.5 * (TAU * hz * time).sin();
+                input_buffer.set_sample(SampleLocation::new(0, frame), value as f32);
+            }
+
+            let mut output_buffer = OwnedAudioBuffer::new(512, 1, SAMPLE_RATE);
+            processor.process_audio(
+                &input_buffer,
+                &mut output_buffer,
+                &Timestamp::zero(),
+                parameters,
+            );
+
+            if block >= 10 {
+                for frame in 0..512 {
+                    let value = output_buffer.get_sample(SampleLocation::new(0, frame));
+                    peak = peak.max(value.abs());
+                }
+            }
+        }
+
+        peak
+    }
+
+    #[test]
+    fn reduces_sibilance_band_only() {
+        let mut parameters = DspParameterMap::new();
+        let frequency_id = parameter(&mut parameters, 6000.0);
+        let threshold_id = parameter(&mut parameters, -30.0);
+        let amount_id = parameter(&mut parameters, 12.0);
+
+        let (meter, mut receiver) = gain_reduction::create();
+        let mut processor = DeEsserProcessor::new(frequency_id, threshold_id, amount_id, meter);
+
+        let low_peak = output_peak(&mut processor, &parameters, 300.0);
+        assert!((low_peak - 0.5).abs() < 0.05);
+
+        let sibilant_peak = output_peak(&mut processor, &parameters, 6000.0);
+        assert!(sibilant_peak < 0.5 * Level::from_db(-10.0).as_gain() as f32);
+
+        let readings = receiver.take();
+        assert!(!readings.is_empty());
+        assert!((readings.last().unwrap().reduction_db - 12.0).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,119 @@
+use std::{collections::HashMap, time::Duration};
+
+use crate::{
+    commands::{command_queue::CommandQueue, id::Id},
+    graph::{dsp::Dsp, node::Node},
+    parameter::audio_parameter::AudioParameter,
+};
+
+use super::processor::{
+    DelayMode, DelayProcessor, DelaySettings, DelaySettingsTransmitter, DelayTap, DelayTapTable,
+};
+
+pub struct DelayNode {
+    id: Id,
+    command_queue: CommandQueue,
+    maximum_delay: Duration,
+    settings_transmitter: DelaySettingsTransmitter,
+    settings: DelaySettings,
+    pub delay_time: AudioParameter,
+    pub feedback: AudioParameter,
+    pub mix: AudioParameter,
+}
+
+const DEFAULT_DELAY_TIME: f64 = 0.25;
+const MIN_FEEDBACK: f64 = 0.0;
+const MAX_FEEDBACK: f64 = 0.95;
+const MIN_MIX: f64 = 0.0;
+const MAX_MIX: f64 = 1.0;
+
+impl DelayNode {
+    pub fn new(command_queue: CommandQueue, sample_rate: usize, maximum_delay: Duration) -> Self {
+        assert!(!maximum_delay.is_zero());
+
+        let mut parameters = HashMap::new();
+
+        let id = Id::generate();
+
+        let maximum_delay_seconds = maximum_delay.as_secs_f64();
+        let (delay_time, realtime_delay_time) = AudioParameter::new(
+            id,
+            DEFAULT_DELAY_TIME.min(0.5 * maximum_delay_seconds),
+            0.0,
+            maximum_delay_seconds,
+            command_queue.clone(),
+        );
+        parameters.insert(realtime_delay_time.get_id(), realtime_delay_time);
+
+        let (feedback, realtime_feedback) =
+            AudioParameter::new(id, 0.3, MIN_FEEDBACK, MAX_FEEDBACK, command_queue.clone());
+        parameters.insert(realtime_feedback.get_id(), realtime_feedback);
+
+        let (mix, realtime_mix) =
+            AudioParameter::new(id, 0.5, MIN_MIX, MAX_MIX, command_queue.clone());
+        parameters.insert(realtime_mix.get_id(), realtime_mix);
+
+        let maximum_delay_samples = (maximum_delay_seconds * sample_rate as f64).ceil() as usize;
+        let (settings_transmitter, settings_receiver) = lockfree::channel::spsc::create();
+        let processor = DelayProcessor::new(
+            delay_time.get_id(),
+            feedback.get_id(),
+            mix.get_id(),
+            maximum_delay_samples,
+            settings_receiver,
+        );
+
+        let dsp = Dsp::new(id, Box::new(processor), parameters);
+
+        Dsp::add_to_audio_process(dsp, &command_queue);
+
+        Self {
+            id,
+            command_queue,
+            maximum_delay,
+            settings_transmitter,
+            settings: DelaySettings::default(),
+            delay_time,
+            feedback,
+            mix,
+        }
+    }
+
+    pub fn get_maximum_delay(&self) -> Duration {
+        self.maximum_delay
+    }
+
+    pub fn set_mode(&mut self, mode: DelayMode) {
+        self.settings.mode = mode;
+        let _ = self.settings_transmitter.send(self.settings);
+    }
+
+    pub fn get_mode(&self) -> DelayMode {
+        self.settings.mode
+    }
+
+    pub fn set_taps(&mut self, taps: &[DelayTap]) {
+        self.settings.taps = DelayTapTable::new(taps);
+        let _ = self.settings_transmitter.send(self.settings);
+    }
+
+    pub fn get_taps(&self) -> &[DelayTap] {
+        self.settings.taps.taps()
+    }
+}
+
+impl Node for DelayNode {
+    fn get_id(&self) -> Id {
+        self.id
+    }
+
+    fn get_command_queue(&self) -> CommandQueue {
+        self.command_queue.clone()
+    }
+}
+
+impl Drop for DelayNode {
+    fn drop(&mut self) {
+        Dsp::remove_from_audio_process(self.id, &self.command_queue);
+    }
+}
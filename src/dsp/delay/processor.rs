@@ -0,0 +1,377 @@
+use std::{
+    f64::consts::{FRAC_PI_4, SQRT_2},
+    time::Duration,
+};
+
+use crate::{
+    commands::id::Id,
+    graph::dsp::{DspParameterMap, DspProcessor},
+    realtime::processor::MAXIMUM_NUMBER_OF_CHANNELS,
+    AudioBuffer, SampleLocation, Timestamp,
+};
+
+pub type DelaySettingsReceiver = lockfree::channel::spsc::Receiver<DelaySettings>;
+pub type DelaySettingsTransmitter = lockfree::channel::spsc::Sender<DelaySettings>;
+
+const TAIL_THRESHOLD: f64 = 1e-3;
+pub const MAXIMUM_DELAY_TAPS: usize = 8;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DelayMode {
+    #[default]
+    Standard,
+    PingPong,
+    MultiTap,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DelayTap {
+    pub time: Duration,
+    pub level: f64,
+    pub pan: f64,
+}
+
+impl DelayTap {
+    pub fn new(time: Duration, level: f64, pan: f64) -> Self {
+        Self { time, level, pan }
+    }
+
+    fn channel_gain(&self, channel: usize, num_channels: usize) -> f32 {
+        if num_channels != 2 {
+            return self.level as f32;
+        }
+
+        let angle = (self.pan.clamp(-1.0, 1.0) + 1.0) * FRAC_PI_4;
+        let pan_gain = if channel == 0 {
+            angle.cos()
+        } else {
+            angle.sin()
+        };
+
+        (self.level * pan_gain * SQRT_2) as f32
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DelayTapTable {
+    taps: [DelayTap; MAXIMUM_DELAY_TAPS],
+    num_taps: usize,
+}
+
+impl DelayTapTable {
+    pub fn new(taps: &[DelayTap]) -> Self {
+        let num_taps = taps.len().min(MAXIMUM_DELAY_TAPS);
+        let mut table = [DelayTap::new(Duration::ZERO, 0.0, 0.0); MAXIMUM_DELAY_TAPS];
+        table[..num_taps].copy_from_slice(&taps[..num_taps]);
+
+        Self {
+            taps: table,
+            num_taps,
+        }
+    }
+
+    pub fn taps(&self) -> &[DelayTap] {
+        &self.taps[..self.num_taps]
+    }
+
+    fn longest_time(&self) -> f64 {
+        self.taps()
+            .iter()
+            .map(|tap| tap.time.as_secs_f64())
+            .fold(0.0, f64::max)
+    }
+}
+
+impl Default for DelayTapTable {
+    fn default() -> Self {
+        Self::new(&[])
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct DelaySettings {
+    pub mode: DelayMode,
+    pub taps: DelayTapTable,
+}
+
+pub struct DelayProcessor {
+    delay_time_id: Id,
+    feedback_id: Id,
+    mix_id: Id,
+    lines: Vec<Vec<f32>>,
+    write_position: usize,
+    last_delay_time: f64,
+    last_feedback: f64,
+    settings: DelaySettings,
+    settings_receiver: DelaySettingsReceiver,
+}
+
+impl DelayProcessor {
+    pub fn new(
+        delay_time_id: Id,
+        feedback_id: Id,
+        mix_id: Id,
+        maximum_delay_samples: usize,
+        settings_receiver: DelaySettingsReceiver,
+    ) -> Self {
+        let line_length = maximum_delay_samples.max(1) + 2;
+
+        Self {
+            delay_time_id,
+            feedback_id,
+            mix_id,
+            lines: (0..MAXIMUM_NUMBER_OF_CHANNELS)
+                .map(|_| vec![0.0; line_length])
+                .collect(),
+            write_position: 0,
+            last_delay_time: 0.0,
+            last_feedback: 0.0,
+            settings: DelaySettings::default(),
+            settings_receiver,
+        }
+    }
+
+    fn read_settings(&mut self) {
+        while let Ok(settings) = self.settings_receiver.recv() {
+            self.settings = settings;
+        }
+    }
+
+    fn line_length(&self) -> usize {
+        self.lines[0].len()
+    }
+
+    fn read(&self, channel: usize, delay_samples: f64) -> f32 {
+        let line = &self.lines[channel];
+        let line_length = line.len();
+
+        let read_position = (self.write_position + line_length) as f64
+            - delay_samples.clamp(1.0, (line_length - 2) as f64);
+        let index = read_position.floor() as usize;
+        let fraction = (read_position - index as f64) as f32;
+
+        let before = line[index % line_length];
+        let after = line[(index + 1) % line_length];
+        before + (after - before) * fraction
+    }
+
+    fn read_taps(&self, channel: usize, num_channels: usize, sample_rate: usize) -> f32 {
+        self.settings
+            .taps
+            .taps()
+            .iter()
+            .map(|tap| {
+                let delay_samples = tap.time.as_secs_f64() * sample_rate as f64;
+                self.read(channel, delay_samples) * tap.channel_gain(channel, num_channels)
+            })
+            .sum()
+    }
+}
+
+impl DspProcessor for DelayProcessor {
+    fn process_audio(
+        &mut self,
+        input_buffer: &dyn AudioBuffer,
+        output_buffer: &mut dyn AudioBuffer,
+        start_time: &Timestamp,
+        parameters: &DspParameterMap,
+    ) {
+        self.read_settings();
+
+        let sample_rate = output_buffer.sample_rate();
+
+        let (delay_time, feedback, mix) = match (
+            parameters.get(&self.delay_time_id),
+            parameters.get(&self.feedback_id),
+            parameters.get(&self.mix_id),
+        ) {
+            (Some(delay_time), Some(feedback), Some(mix)) => (delay_time, feedback, mix),
+            _ => return,
+        };
+
+        let num_channels = output_buffer
+            .num_channels()
+            .min(input_buffer.num_channels())
+            .min(MAXIMUM_NUMBER_OF_CHANNELS);
+        let line_length = self.line_length();
+
+        for frame in 0..output_buffer.num_frames() {
+            let frame_time = start_time.incremented_by_samples(frame, sample_rate);
+            self.last_delay_time = delay_time.get_value_at_time(&frame_time);
+            self.last_feedback = feedback.get_value_at_time(&frame_time);
+            let mix = mix.get_value_at_time(&frame_time) as f32;
+
+            let delay_samples = self.last_delay_time * sample_rate as f64;
+
+            let mut delayed = [0.0; MAXIMUM_NUMBER_OF_CHANNELS];
+            for (channel, delayed) in delayed.iter_mut().enumerate().take(num_channels) {
+                *delayed = self.read(channel, delay_samples);
+            }
+
+            for channel in 0..num_channels {
+                let location = SampleLocation::new(channel, frame);
+                let input = input_buffer.get_sample(location);
+
+                let feedback_channel = match self.settings.mode {
+                    DelayMode::PingPong if channel ^ 1 < num_channels => channel ^ 1,
+                    _ => channel,
+                };
+                let wet = match self.settings.mode {
+                    DelayMode::MultiTap => self.read_taps(channel, num_channels, sample_rate),
+                    _ => delayed[channel],
+                };
+
+                self.lines[channel][self.write_position] =
+                    input + delayed[feedback_channel] * self.last_feedback as f32;
+                output_buffer.set_sample(location, input * (1.0 - mix) + wet * mix);
+            }
+
+            self.write_position = (self.write_position + 1) % line_length;
+        }
+    }
+
+    fn tail_time(&self) -> Option<Duration> {
+        let repeats = if self.last_feedback > TAIL_THRESHOLD {
+            TAIL_THRESHOLD.ln() / self.last_feedback.ln()
+        } else {
+            0.0
+        };
+
+        let longest_time = match self.settings.mode {
+            DelayMode::MultiTap => self.settings.taps.longest_time(),
+            _ => self.last_delay_time,
+        };
+
+        Some(Duration::from_secs_f64(
+            longest_time + self.last_delay_time * repeats,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use atomic_float::AtomicF64;
+
+    use crate::{parameter::realtime_parameter::RealtimeAudioParameter, OwnedAudioBuffer};
+
+    use super::*;
+
+    const SAMPLE_RATE: usize = 48_000;
+
+    fn parameter(parameters: &mut DspParameterMap, value: f64) -> Id {
+        let id = Id::generate();
+        parameters.insert(
+            id,
+            RealtimeAudioParameter::new(id, Arc::new(AtomicF64::new(value))),
+        );
+        id
+    }
+
+    fn process_stereo_impulse(mix: f64, feedback: f64, settings: DelaySettings) -> [Vec<f32>; 2] {
+        let mut parameters = DspParameterMap::new();
+        let delay_time_id = parameter(&mut parameters, 0.01);
+        let feedback_id = parameter(&mut parameters, feedback);
+        let mix_id = parameter(&mut parameters, mix);
+
+        let (mut settings_transmitter, settings_receiver) = lockfree::channel::spsc::create();
+        let _ = settings_transmitter.send(settings);
+
+        let mut processor = DelayProcessor::new(
+            delay_time_id,
+            feedback_id,
+            mix_id,
+            SAMPLE_RATE / 50,
+            settings_receiver,
+        );
+
+        let mut output = [Vec::new(), Vec::new()];
+        for block in 0..4 {
+            let mut input_buffer = OwnedAudioBuffer::new(512, 2, SAMPLE_RATE);
+            if block == 0 {
+                input_buffer.set_sample(SampleLocation::new(0, 0), 1.0);
+            }
+
+            let mut output_buffer = OwnedAudioBuffer::new(512, 2, SAMPLE_RATE);
+            processor.process_audio(
+                &input_buffer,
+                &mut output_buffer,
+                &Timestamp::from_samples((block * 512) as f64, SAMPLE_RATE),
+                &parameters,
+            );
+
+            for (channel, output) in output.iter_mut().enumerate() {
+                output
+                    .extend((0..512).map(|frame| {
+                        output_buffer.get_sample(SampleLocation::new(channel, frame))
+                    }));
+            }
+        }
+        output
+    }
+
+    fn process_impulse(mix: f64, feedback: f64) -> Vec<f32> {
+        let [left, _] = process_stereo_impulse(mix, feedback, DelaySettings::default());
+        left
+    }
+
+    fn echoes(output: &[f32]) -> Vec<(usize, f32)> {
+        output
+            .iter()
+            .enumerate()
+            .filter(|(_, value)| value.abs() > 1e-6)
+            .map(|(frame, value)| (frame, *value))
+            .collect()
+    }
+
+    #[test]
+    fn repeats_input_with_feedback_and_mix() {
+        let wet = process_impulse(1.0, 0.5);
+        assert_eq!(
+            echoes(&wet),
+            vec![(480, 1.0), (960, 0.5), (1440, 0.25), (1920, 0.125)]
+        );
+
+        let dry = process_impulse(0.0, 0.5);
+        assert_eq!(dry[0], 1.0);
+        assert!(dry[1..].iter().all(|value| *value == 0.0));
+
+        let blend = process_impulse(0.25, 0.0);
+        assert_eq!(blend[0], 0.75);
+        assert_eq!(blend[480], 0.25);
+        assert!(blend[481..].iter().all(|value| *value == 0.0));
+    }
+
+    #[test]
+    fn ping_pong_alternates_echoes_between_channels() {
+        let settings = DelaySettings {
+            mode: DelayMode::PingPong,
+            ..Default::default()
+        };
+        let [left, right] = process_stereo_impulse(1.0, 0.5, settings);
+
+        assert_eq!(echoes(&left), vec![(480, 1.0), (1440, 0.25)]);
+        assert_eq!(echoes(&right), vec![(960, 0.5), (1920, 0.125)]);
+    }
+
+    #[test]
+    fn multi_tap_reads_each_tap_with_level_and_pan() {
+        let settings = DelaySettings {
+            mode: DelayMode::MultiTap,
+            taps: DelayTapTable::new(&[
+                DelayTap::new(Duration::from_millis(5), 1.0, -1.0),
+                DelayTap::new(Duration::from_millis(15), 0.5, 0.0),
+            ]),
+        };
+        let [left, right] = process_stereo_impulse(1.0, 0.0, settings);
+
+        let left_echoes = echoes(&left);
+        assert_eq!(left_echoes.len(), 2);
+        assert_eq!(left_echoes[0].0, 240);
+        assert!((left_echoes[0].1 - SQRT_2 as f32).abs() < 1e-5);
+        assert_eq!(left_echoes[1].0, 720);
+        assert!((left_echoes[1].1 - 0.5).abs() < 1e-5);
+        assert!(echoes(&right).is_empty());
+    }
+}
@@ -0,0 +1,84 @@
+use std::{collections::HashMap, time::Duration};
+
+use crate::{
+    commands::{command_queue::CommandQueue, id::Id},
+    graph::{dsp::Dsp, node::Node},
+    parameter::audio_parameter::AudioParameter,
+};
+
+use super::processor::{DenoiseEvent, DenoiseEventTransmitter, DenoiseProcessor, LATENCY};
+
+pub struct DenoiseNode {
+    id: Id,
+    command_queue: CommandQueue,
+    event_transmitter: DenoiseEventTransmitter,
+    pub reduction: AudioParameter,
+    pub floor: AudioParameter,
+}
+
+const MIN_REDUCTION: f64 = 0.0;
+const MAX_REDUCTION: f64 = 4.0;
+const MIN_FLOOR: f64 = 0.0;
+const MAX_FLOOR: f64 = 1.0;
+
+impl DenoiseNode {
+    pub fn new(command_queue: CommandQueue) -> Self {
+        let mut parameters = HashMap::new();
+
+        let id = Id::generate();
+
+        let (reduction, realtime_reduction) =
+            AudioParameter::new(id, 1.0, MIN_REDUCTION, MAX_REDUCTION, command_queue.clone());
+        parameters.insert(realtime_reduction.get_id(), realtime_reduction);
+
+        let (floor, realtime_floor) =
+            AudioParameter::new(id, 0.05, MIN_FLOOR, MAX_FLOOR, command_queue.clone());
+        parameters.insert(realtime_floor.get_id(), realtime_floor);
+
+        let (event_transmitter, event_receiver) = lockfree::channel::spsc::create();
+
+        let processor = DenoiseProcessor::new(reduction.get_id(), floor.get_id(), event_receiver);
+
+        let dsp = Dsp::new(id, Box::new(processor), parameters);
+
+        Dsp::add_to_audio_process(dsp, &command_queue);
+
+        Self {
+            id,
+            command_queue,
+            event_transmitter,
+            reduction,
+            floor,
+        }
+    }
+
+    pub fn learn_noise_profile(&mut self, duration: Duration) {
+        let _ = self
+            .event_transmitter
+            .send(DenoiseEvent::LearnNoise(duration));
+    }
+
+    pub fn clear_noise_profile(&mut self) {
+        let _ = self.event_transmitter.send(DenoiseEvent::ClearNoiseProfile);
+    }
+}
+
+impl Node for DenoiseNode {
+    fn get_id(&self) -> Id {
+        self.id
+    }
+
+    fn get_command_queue(&self) -> CommandQueue {
+        self.command_queue.clone()
+    }
+
+    fn latency_in_samples(&self) -> usize {
+        LATENCY
+    }
+}
+
+impl Drop for DenoiseNode {
+    fn drop(&mut self) {
+        Dsp::remove_from_audio_process(self.id, &self.command_queue);
+    }
+}
@@ -0,0 +1,262 @@
+use std::time::Duration;
+
+use crate::{
+    commands::id::Id,
+    graph::dsp::{DspParameterMap, DspProcessor},
+    utility::stft::{self, SpectralProcessor, StftProcessor},
+    AudioBuffer, Timestamp,
+};
+
+pub type DenoiseEventReceiver = lockfree::channel::spsc::Receiver<DenoiseEvent>;
+pub type DenoiseEventTransmitter = lockfree::channel::spsc::Sender<DenoiseEvent>;
+
+pub const FFT_SIZE: usize = 1024;
+const OVERLAP: usize = 4;
+pub const LATENCY: usize = stft::latency_in_samples(FFT_SIZE);
+const NUM_BINS: usize = FFT_SIZE / 2 + 1;
+const MAXIMUM_NUMBER_OF_CHANNELS: usize = 2;
+const GAIN_SMOOTHING: f32 = 0.5;
+
+pub enum DenoiseEvent {
+    LearnNoise(Duration),
+    ClearNoiseProfile,
+}
+
+struct DenoiseSpectrum {
+    reduction: f32,
+    floor: f32,
+    gains: Vec<Vec<f32>>,
+    noise_profile: Vec<f32>,
+    has_noise_profile: bool,
+    learned_sum: Vec<f32>,
+    learned_frames: usize,
+    learning_hops_remaining: usize,
+}
+
+impl DenoiseSpectrum {
+    fn new() -> Self {
+        Self {
+            reduction: 0.0,
+            floor: 1.0,
+            gains: (0..MAXIMUM_NUMBER_OF_CHANNELS)
+                .map(|_| vec![1.0; NUM_BINS])
+                .collect(),
+            noise_profile: vec![0.0; NUM_BINS],
+            has_noise_profile: false,
+            learned_sum: vec![0.0; NUM_BINS],
+            learned_frames: 0,
+            learning_hops_remaining: 0,
+        }
+    }
+
+    fn is_learning(&self) -> bool {
+        self.learning_hops_remaining > 0
+    }
+}
+
+impl SpectralProcessor for DenoiseSpectrum {
+    fn process_spectrum(&mut self, channel: usize, real: &mut [f32], imaginary: &mut [f32]) {
+        let learning = self.is_learning();
+        let gains = &mut self.gains[channel];
+
+        for bin in 0..NUM_BINS {
+            let magnitude = real[bin].hypot(imaginary[bin]);
+
+            if learning {
+                self.learned_sum[bin] += magnitude;
+            }
+
+            let target_gain = if self.has_noise_profile && !learning && magnitude > 0.0 {
+                (1.0 - self.reduction * self.noise_profile[bin] / magnitude).max(self.floor)
+            } else {
+                1.0
+            };
+
+            let gain = GAIN_SMOOTHING * gains[bin] + (1.0 - GAIN_SMOOTHING) * target_gain;
+            gains[bin] = gain;
+
+            real[bin] *= gain;
+            imaginary[bin] *= gain;
+        }
+
+        if learning {
+            self.learned_frames += 1;
+        }
+    }
+
+    fn finish_hop(&mut self) {
+        if !self.is_learning() {
+            return;
+        }
+
+        self.learning_hops_remaining -= 1;
+
+        if self.learning_hops_remaining == 0 && self.learned_frames > 0 {
+            let num_frames = self.learned_frames as f32;
+            for (noise, sum) in self.noise_profile.iter_mut().zip(self.learned_sum.iter()) {
+                *noise = sum / num_frames;
+            }
+            self.has_noise_profile = true;
+        }
+    }
+}
+
+pub struct DenoiseProcessor {
+    reduction_id: Id,
+    floor_id: Id,
+    event_receiver: DenoiseEventReceiver,
+    stft: StftProcessor,
+    spectrum: DenoiseSpectrum,
+}
+
+impl DenoiseProcessor {
+    pub fn new(reduction_id: Id, floor_id: Id, event_receiver: DenoiseEventReceiver) -> Self {
+        Self {
+            reduction_id,
+            floor_id,
+            event_receiver,
+            stft: StftProcessor::new(FFT_SIZE, OVERLAP, MAXIMUM_NUMBER_OF_CHANNELS),
+            spectrum: DenoiseSpectrum::new(),
+        }
+    }
+
+    fn read_events(&mut self, sample_rate: usize) {
+        while let Ok(event) = self.event_receiver.recv() {
+            match event {
+                DenoiseEvent::LearnNoise(duration) => {
+                    let num_samples = duration.as_secs_f64() * sample_rate as f64;
+                    self.spectrum.learning_hops_remaining =
+                        (num_samples / self.stft.hop_size() as f64).ceil().max(1.0) as usize;
+                    self.spectrum.learned_sum.fill(0.0);
+                    self.spectrum.learned_frames = 0;
+                }
+                DenoiseEvent::ClearNoiseProfile => {
+                    self.spectrum.has_noise_profile = false;
+                    self.spectrum.learning_hops_remaining = 0;
+                    self.spectrum.noise_profile.fill(0.0);
+                }
+            }
+        }
+    }
+}
+
+impl DspProcessor for DenoiseProcessor {
+    fn process_audio(
+        &mut self,
+        input_buffer: &dyn AudioBuffer,
+        output_buffer: &mut dyn AudioBuffer,
+        start_time: &Timestamp,
+        parameters: &DspParameterMap,
+    ) {
+        self.read_events(output_buffer.sample_rate());
+
+        let (reduction, floor) = match (
+            parameters.get(&self.reduction_id),
+            parameters.get(&self.floor_id),
+        ) {
+            (Some(reduction), Some(floor)) => (
+                reduction.get_value_at_time(start_time) as f32,
+                floor.get_value_at_time(start_time) as f32,
+            ),
+            _ => return,
+        };
+
+        self.spectrum.reduction = reduction;
+        self.spectrum.floor = floor;
+        self.stft
+            .process(input_buffer, output_buffer, &mut self.spectrum);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{f64::consts::TAU, sync::Arc};
+
+    use atomic_float::AtomicF64;
+
+    use crate::{
+        parameter::realtime_parameter::RealtimeAudioParameter, utility::random::Random,
+        OwnedAudioBuffer, SampleLocation,
+    };
+
+    use super::*;
+
+    const SAMPLE_RATE: usize = 16_000;
+
+    fn rms(samples: &[f32]) -> f32 {
+        (samples.iter().map(|sample| sample * sample).sum::<f32>() / samples.len() as f32).sqrt()
+    }
+
+    fn process(
+        denoise: &mut DenoiseProcessor,
+        parameters: &DspParameterMap,
+        random: &mut Random,
+        num_blocks: usize,
+        tone: f32,
+    ) -> (Vec<f32>, Vec<f32>) {
+        let mut inputs = Vec::new();
+        let mut outputs = Vec::new();
+
+        for _ in 0..num_blocks {
+            let mut input_buffer = OwnedAudioBuffer::new(512, 1, SAMPLE_RATE);
+            for frame in 0..512 {
+                let time = (inputs.len() + frame) as f64 / SAMPLE_RATE as f64;
+                let sine = tone * (TAU * 1000.0 * time).sin() as f32;
+                let noise = 0.1 * random.next_bipolar() as f32;
+                input_buffer.set_sample(SampleLocation::new(0, frame), sine + noise);
+            }
+
+            let mut output_buffer = OwnedAudioBuffer::new(512, 1, SAMPLE_RATE);
+            denoise.process_audio(
+                &input_buffer,
+                &mut output_buffer,
+                &Timestamp::zero(),
+                parameters,
+            );
+
+            for frame in 0..512 {
+                inputs.push(input_buffer.get_sample(SampleLocation::new(0, frame)));
+                outputs.push(output_buffer.get_sample(SampleLocation::new(0, frame)));
+            }
+        }
+
+        (inputs, outputs)
+    }
+
+    #[test]
+    fn learns_noise_profile_and_attenuates_noise() {
+        let reduction_id = Id::generate();
+        let floor_id = Id::generate();
+
+        let mut parameters = DspParameterMap::new();
+        parameters.insert(
+            reduction_id,
+            RealtimeAudioParameter::new(reduction_id, Arc::new(AtomicF64::new(2.0))),
+        );
+        parameters.insert(
+            floor_id,
+            RealtimeAudioParameter::new(floor_id, Arc::new(AtomicF64::new(0.05))),
+        );
+
+        let (mut event_transmitter, event_receiver) = lockfree::channel::spsc::create();
+        let mut denoise = DenoiseProcessor::new(reduction_id, floor_id, event_receiver);
+        let mut random = Random::with_seed(7);
+
+        let (inputs, outputs) = process(&mut denoise, &parameters, &mut random, 8, 0.0);
+        assert!((rms(&outputs[LATENCY..]) - rms(&inputs[..inputs.len() - LATENCY])).abs() < 0.01);
+
+        let _ = event_transmitter.send(DenoiseEvent::LearnNoise(Duration::from_secs(1)));
+        process(&mut denoise, &parameters, &mut random, 40, 0.0);
+
+        let (inputs, outputs) = process(&mut denoise, &parameters, &mut random, 16, 0.0);
+        assert!(rms(&outputs[FFT_SIZE..]) < 0.25 * rms(&inputs));
+
+        let (_, outputs) = process(&mut denoise, &parameters, &mut random, 16, 0.5);
+        let tone_rms = 0.5 / 2.0_f32.sqrt();
+        assert!((rms(&outputs[FFT_SIZE..]) - tone_rms).abs() < 0.1 * tone_rms);
+
+        let _ = event_transmitter.send(DenoiseEvent::ClearNoiseProfile);
+        let (inputs, outputs) = process(&mut denoise, &parameters, &mut random, 16, 0.0);
+        assert!(rms(&outputs[FFT_SIZE..]) > 0.9 * rms(&inputs));
+    }
+}
@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+
+use crate::{
+    commands::{command_queue::CommandQueue, id::Id},
+    graph::{
+        dsp::{Dsp, DspParameterMap},
+        node::Node,
+    },
+    parameter::audio_parameter::AudioParameter,
+};
+
+use super::processor::{DynamicEqBandIds, DynamicEqBandType, DynamicEqProcessor};
+
+const MIN_FREQUENCY: f64 = 20.0;
+const MAX_FREQUENCY: f64 = 20000.0;
+const MIN_Q: f64 = 0.1;
+const MAX_Q: f64 = 10.0;
+const MIN_THRESHOLD: f64 = -80.0;
+const MAX_THRESHOLD: f64 = 0.0;
+const MIN_RATIO: f64 = 1.0;
+const MAX_RATIO: f64 = 20.0;
+const MIN_RANGE: f64 = -24.0;
+const MAX_RANGE: f64 = 24.0;
+
+pub struct DynamicEqBand {
+    band_type: DynamicEqBandType,
+    pub frequency: AudioParameter,
+    pub q: AudioParameter,
+    pub threshold: AudioParameter,
+    pub ratio: AudioParameter,
+    pub range: AudioParameter,
+}
+
+impl DynamicEqBand {
+    fn new(
+        id: Id,
+        band_type: DynamicEqBandType,
+        frequency: f64,
+        command_queue: &CommandQueue,
+        parameters: &mut DspParameterMap,
+    ) -> Self {
+        let mut make_parameter = |value: f64, minimum: f64, maximum: f64| {
+            let (parameter, realtime_parameter) =
+                AudioParameter::new(id, value, minimum, maximum, command_queue.clone());
+            parameters.insert(realtime_parameter.get_id(), realtime_parameter);
+            parameter
+        };
+
+        Self {
+            band_type,
+            frequency: make_parameter(frequency, MIN_FREQUENCY, MAX_FREQUENCY),
+            q: make_parameter(0.7, MIN_Q, MAX_Q),
+            threshold: make_parameter(-24.0, MIN_THRESHOLD, MAX_THRESHOLD),
+            ratio: make_parameter(2.0, MIN_RATIO, MAX_RATIO),
+            range: make_parameter(-6.0, MIN_RANGE, MAX_RANGE),
+        }
+    }
+
+    pub fn get_band_type(&self) -> DynamicEqBandType {
+        self.band_type
+    }
+
+    fn ids(&self) -> DynamicEqBandIds {
+        DynamicEqBandIds {
+            frequency_id: self.frequency.get_id(),
+            q_id: self.q.get_id(),
+            threshold_id: self.threshold.get_id(),
+            ratio_id: self.ratio.get_id(),
+            range_id: self.range.get_id(),
+        }
+    }
+}
+
+pub struct DynamicEqNode {
+    id: Id,
+    command_queue: CommandQueue,
+    pub bands: Vec<DynamicEqBand>,
+}
+
+impl DynamicEqNode {
+    pub fn new(command_queue: CommandQueue, bands: &[(DynamicEqBandType, f64)]) -> Self {
+        let mut parameters = HashMap::new();
+
+        let id = Id::generate();
+
+        let bands: Vec<DynamicEqBand> = bands
+            .iter()
+            .map(|(band_type, frequency)| {
+                DynamicEqBand::new(id, *band_type, *frequency, &command_queue, &mut parameters)
+            })
+            .collect();
+
+        let band_ids: Vec<(DynamicEqBandType, DynamicEqBandIds)> = bands
+            .iter()
+            .map(|band| (band.get_band_type(), band.ids()))
+            .collect();
+
+        let dsp = Dsp::new(id, Box::new(DynamicEqProcessor::new(&band_ids)), parameters);
+
+        Dsp::add_to_audio_process(dsp, &command_queue);
+
+        Self {
+            id,
+            command_queue,
+            bands,
+        }
+    }
+}
+
+impl Node for DynamicEqNode {
+    fn get_id(&self) -> Id {
+        self.id
+    }
+
+    fn get_command_queue(&self) -> CommandQueue {
+        self.command_queue.clone()
+    }
+}
+
+impl Drop for DynamicEqNode {
+    fn drop(&mut self) {
+        Dsp::remove_from_audio_process(self.id, &self.command_queue);
+    }
+}
@@ -0,0 +1,297 @@
+use std::time::Duration;
+
+use crate::{
+    commands::id::Id,
+    graph::dsp::{DspParameterMap, DspProcessor},
+    utility::{
+        envelope_follower::EnvelopeFollower,
+        fast_math,
+        state_variable_filter::{FilterOutputs, FilterState, StateVariableFilter},
+    },
+    AudioBuffer, SampleLocation, Timestamp,
+};
+
+const MAXIMUM_NUMBER_OF_CHANNELS: usize = 2;
+const ATTACK: Duration = Duration::from_millis(5);
+const RELEASE: Duration = Duration::from_millis(100);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DynamicEqBandType {
+    Bell,
+    LowShelf,
+    HighShelf,
+}
+
+impl DynamicEqBandType {
+    fn component(&self, outputs: FilterOutputs) -> f64 {
+        match self {
+            DynamicEqBandType::Bell => outputs.band,
+            DynamicEqBandType::LowShelf => outputs.low,
+            DynamicEqBandType::HighShelf => outputs.high,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct DynamicEqBandIds {
+    pub frequency_id: Id,
+    pub q_id: Id,
+    pub threshold_id: Id,
+    pub ratio_id: Id,
+    pub range_id: Id,
+}
+
+struct BandProcessor {
+    band_type: DynamicEqBandType,
+    ids: DynamicEqBandIds,
+    filter: StateVariableFilter,
+    filter_states: [FilterState; MAXIMUM_NUMBER_OF_CHANNELS],
+    envelope: EnvelopeFollower,
+}
+
+pub struct DynamicEqProcessor {
+    bands: Vec<BandProcessor>,
+}
+
+impl DynamicEqProcessor {
+    pub fn new(bands: &[(DynamicEqBandType, DynamicEqBandIds)]) -> Self {
+        Self {
+            bands: bands
+                .iter()
+                .map(|(band_type, ids)| BandProcessor {
+                    band_type: *band_type,
+                    ids: *ids,
+                    filter: StateVariableFilter::default(),
+                    filter_states: [FilterState::default(); MAXIMUM_NUMBER_OF_CHANNELS],
+                    envelope: EnvelopeFollower::new(ATTACK, RELEASE),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn gain_db(envelope: f64, threshold: f64, ratio: f64, range: f64) -> f64 {
+        let over_db = fast_math::gain_to_db(envelope) - threshold;
+
+        if over_db <= 0.0 {
+            return 0.0;
+        }
+
+        let change_db = (over_db * (1.0 - 1.0 / ratio.max(1.0))).min(range.abs());
+        change_db * range.signum()
+    }
+}
+
+impl BandProcessor {
+    fn process_frame(
+        &mut self,
+        samples: &mut [f64],
+        frame_time: &Timestamp,
+        sample_rate: usize,
+        parameters: &DspParameterMap,
+    ) {
+        let value = |id: &Id| {
+            parameters
+                .get(id)
+                .map(|parameter| parameter.get_value_at_time(frame_time))
+        };
+
+        let (frequency, q, threshold, ratio, range) = match (
+            value(&self.ids.frequency_id),
+            value(&self.ids.q_id),
+            value(&self.ids.threshold_id),
+            value(&self.ids.ratio_id),
+            value(&self.ids.range_id),
+        ) {
+            (Some(frequency), Some(q), Some(threshold), Some(ratio), Some(range)) => {
+                (frequency, q, threshold, ratio, range)
+            }
+            _ => return,
+        };
+
+        self.filter.update(frequency, q, sample_rate);
+
+        let mut components = [0.0; MAXIMUM_NUMBER_OF_CHANNELS];
+        let mut peak = 0.0_f64;
+
+        for (channel, sample) in samples.iter().enumerate() {
+            let outputs = self
+                .filter
+                .process(&mut self.filter_states[channel], *sample);
+            components[channel] = self.band_type.component(outputs);
+            peak = peak.max(components[channel].abs());
+        }
+
+        let envelope = self.envelope.process(peak);
+        let gain = fast_math::db_to_gain(DynamicEqProcessor::gain_db(
+            envelope, threshold, ratio, range,
+        ));
+
+        for (sample, component) in samples.iter_mut().zip(components.iter()) {
+            *sample += (gain - 1.0) * component;
+        }
+    }
+}
+
+impl DspProcessor for DynamicEqProcessor {
+    fn process_audio(
+        &mut self,
+        input_buffer: &dyn AudioBuffer,
+        output_buffer: &mut dyn AudioBuffer,
+        start_time: &Timestamp,
+        parameters: &DspParameterMap,
+    ) {
+        let sample_rate = output_buffer.sample_rate();
+
+        for band in self.bands.iter_mut() {
+            band.envelope.prepare(sample_rate);
+        }
+
+        let num_channels = output_buffer
+            .num_channels()
+            .min(input_buffer.num_channels())
+            .min(MAXIMUM_NUMBER_OF_CHANNELS);
+
+        let mut samples = [0.0; MAXIMUM_NUMBER_OF_CHANNELS];
+
+        for frame in 0..output_buffer.num_frames() {
+            let frame_time = start_time.incremented_by_samples(frame, sample_rate);
+
+            for (channel, sample) in samples.iter_mut().enumerate().take(num_channels) {
+                *sample = input_buffer.get_sample(SampleLocation::new(channel, frame)) as f64;
+            }
+
+            for band in self.bands.iter_mut() {
+                band.process_frame(
+                    &mut samples[..num_channels],
+                    &frame_time,
+                    sample_rate,
+                    parameters,
+                );
+            }
+
+            for (channel, sample) in samples.iter().enumerate().take(num_channels) {
+                output_buffer.set_sample(SampleLocation::new(channel, frame), *sample as f32);
+            }
+        }
+    }
+
+    fn tail_time(&self) -> Option<Duration> {
+        Some(RELEASE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{f64::consts::TAU, sync::Arc};
+
+    use approx::assert_relative_eq;
+    use atomic_float::AtomicF64;
+
+    use crate::{parameter::realtime_parameter::RealtimeAudioParameter, OwnedAudioBuffer};
+
+    use crate::utility::level::Level;
+
+    use super::*;
+
+    const SAMPLE_RATE: usize = 48_000;
+
+    fn band(
+        parameters: &mut DspParameterMap,
+        frequency: f64,
+        threshold: f64,
+        range: f64,
+    ) -> DynamicEqBandIds {
+        let mut parameter = |value: f64| {
+            let id = Id::generate();
+            parameters.insert(
+                id,
+                RealtimeAudioParameter::new(id, Arc::new(AtomicF64::new(value))),
+            );
+            id
+        };
+
+        DynamicEqBandIds {
+            frequency_id: parameter(frequency),
+            q_id: parameter(1.0),
+            threshold_id: parameter(threshold),
+            ratio_id: parameter(4.0),
+            range_id: parameter(range),
+        }
+    }
+
+    fn output_peak(
+        processor: &mut DynamicEqProcessor,
+        parameters: &DspParameterMap,
+        hz: f64,
+        amplitude: f64,
+    ) -> f64 {
+        let mut peak = 0.0_f64;
+
+        for block in 0..20 {
+            let mut input_buffer = OwnedAudioBuffer::new(512, 1, SAMPLE_RATE);
+            for frame in 0..512 {
+                let time = (block * 512 + frame) as f64 / SAMPLE_RATE as f64;
+                let value = amplitude * (TAU * hz * time).sin();
+                input_buffer.set_sample(SampleLocation::new(0, frame), value as f32);
+            }
+
+            let mut output_buffer = OwnedAudioBuffer::new(512, 1, SAMPLE_RATE);
+            processor.process_audio(
+                &input_buffer,
+                &mut output_buffer,
+                &Timestamp::zero(),
+                parameters,
+            );
+
+            if block >= 10 {
+                for frame in 0..512 {
+                    let value = output_buffer.get_sample(SampleLocation::new(0, frame)) as f64;
+                    peak = peak.max(value.abs());
+                }
+            }
+        }
+
+        peak
+    }
+
+    #[test]
+    fn computes_band_gain_from_threshold_ratio_and_range() {
+        let envelope = Level::from_db(-10.0).as_gain();
+        assert_relative_eq!(
+            DynamicEqProcessor::gain_db(envelope, -20.0, 4.0, -24.0),
+            -7.5,
+            epsilon = fast_math::DB_MAX_ERROR
+        );
+        assert_relative_eq!(
+            DynamicEqProcessor::gain_db(envelope, -20.0, 4.0, -6.0),
+            -6.0
+        );
+        assert_relative_eq!(DynamicEqProcessor::gain_db(envelope, -20.0, 4.0, 3.0), 3.0);
+        assert_relative_eq!(DynamicEqProcessor::gain_db(envelope, 0.0, 4.0, -24.0), 0.0);
+    }
+
+    #[test]
+    fn only_loud_signals_in_a_band_are_changed() {
+        let mut parameters = DspParameterMap::new();
+        let cut = band(&mut parameters, 1000.0, -20.0, -24.0);
+        let boost = band(&mut parameters, 8000.0, -30.0, 6.0);
+
+        let mut processor = DynamicEqProcessor::new(&[
+            (DynamicEqBandType::Bell, cut),
+            (DynamicEqBandType::HighShelf, boost),
+        ]);
+
+        let quiet = output_peak(&mut processor, &parameters, 1000.0, 0.01);
+        assert!((quiet - 0.01).abs() < 0.001);
+
+        let loud = output_peak(&mut processor, &parameters, 1000.0, 0.5);
+        let expected = 0.5 * Level::from_db(-10.5).as_gain();
+        assert!((loud - expected).abs() < 0.1 * expected);
+
+        let low = output_peak(&mut processor, &parameters, 100.0, 0.5);
+        assert!((low - 0.5).abs() < 0.03);
+
+        let bright = output_peak(&mut processor, &parameters, 15000.0, 0.5);
+        assert!((bright - 0.5 * Level::from_db(6.0).as_gain()).abs() < 0.05);
+    }
+}
@@ -0,0 +1,145 @@
+pub type Vector3 = [f64; 3];
+
+pub const OPEN_CUTOFF: f64 = 20000.0;
+const OCCLUDED_CUTOFF: f64 = 500.0;
+const OCCLUDED_GAIN: f64 = 0.5;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct EmitterAttributes {
+    pub position: Vector3,
+    pub velocity: Vector3,
+    pub occlusion: f64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Listener {
+    pub position: Vector3,
+    pub right: Vector3,
+}
+
+impl Default for Listener {
+    fn default() -> Self {
+        Self {
+            position: [0.0; 3],
+            right: [1.0, 0.0, 0.0],
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DistanceModel {
+    pub reference_distance: f64,
+    pub max_distance: f64,
+    pub rolloff: f64,
+}
+
+impl Default for DistanceModel {
+    fn default() -> Self {
+        Self {
+            reference_distance: 1.0,
+            max_distance: 100.0,
+            rolloff: 1.0,
+        }
+    }
+}
+
+impl DistanceModel {
+    pub fn gain_at_distance(&self, distance: f64) -> f64 {
+        let reference = self.reference_distance.max(1e-6);
+        let distance = distance.clamp(reference, self.max_distance.max(reference));
+        reference / (reference + self.rolloff * (distance - reference))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EmitterTargets {
+    pub gain: f64,
+    pub pan: f64,
+    pub cutoff: f64,
+}
+
+impl EmitterTargets {
+    pub fn from_attributes(
+        attributes: &EmitterAttributes,
+        listener: &Listener,
+        distance_model: &DistanceModel,
+        lookahead_in_seconds: f64,
+    ) -> Self {
+        let mut offset = [0.0; 3];
+        for (axis, value) in offset.iter_mut().enumerate() {
+            *value = attributes.position[axis] + attributes.velocity[axis] * lookahead_in_seconds
+                - listener.position[axis];
+        }
+
+        let distance = length(&offset);
+        let pan = if distance > 1e-9 {
+            dot(&offset, &listener.right) / (distance * length(&listener.right).max(1e-9))
+        } else {
+            0.0
+        };
+
+        let occlusion = attributes.occlusion.clamp(0.0, 1.0);
+        let occlusion_gain = 1.0 - occlusion * (1.0 - OCCLUDED_GAIN);
+        let cutoff = OPEN_CUTOFF * (OCCLUDED_CUTOFF / OPEN_CUTOFF).powf(occlusion);
+
+        Self {
+            gain: distance_model.gain_at_distance(distance) * occlusion_gain,
+            pan: pan.clamp(-1.0, 1.0),
+            cutoff,
+        }
+    }
+}
+
+fn dot(a: &Vector3, b: &Vector3) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn length(a: &Vector3) -> f64 {
+    dot(a, a).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use super::*;
+
+    #[test]
+    fn attenuates_with_distance_and_pans_to_side() {
+        let attributes = EmitterAttributes {
+            position: [4.0, 0.0, 0.0],
+            ..Default::default()
+        };
+
+        let targets = EmitterTargets::from_attributes(
+            &attributes,
+            &Listener::default(),
+            &DistanceModel::default(),
+            0.0,
+        );
+
+        assert_relative_eq!(targets.gain, 0.25);
+        assert_relative_eq!(targets.pan, 1.0);
+        assert_relative_eq!(targets.cutoff, OPEN_CUTOFF);
+    }
+
+    #[test]
+    fn occlusion_darkens_and_velocity_predicts_position() {
+        let attributes = EmitterAttributes {
+            position: [0.0, 0.0, -2.0],
+            velocity: [-20.0, 0.0, 0.0],
+            occlusion: 1.0,
+        };
+
+        let targets = EmitterTargets::from_attributes(
+            &attributes,
+            &Listener::default(),
+            &DistanceModel::default(),
+            0.1,
+        );
+
+        assert_relative_eq!(targets.cutoff, OCCLUDED_CUTOFF, epsilon = 1e-9);
+        assert!(targets.pan < -0.7);
+        assert!(targets.gain < 0.5 * 0.5);
+    }
+}
@@ -0,0 +1,3 @@
+pub mod attributes;
+pub mod node;
+pub mod processor;
@@ -0,0 +1,118 @@
+use std::{collections::HashMap, sync::atomic::Ordering, time::Duration};
+
+use crate::{
+    commands::{command_queue::CommandQueue, id::Id},
+    graph::{dsp::Dsp, node::Node},
+    parameter::audio_parameter::AudioParameter,
+    Timestamp,
+};
+
+use super::{
+    attributes::{DistanceModel, EmitterAttributes, EmitterTargets, Listener},
+    processor::EmitterProcessor,
+};
+
+pub struct EmitterNode {
+    command_queue: CommandQueue,
+    id: Id,
+    distance_model: DistanceModel,
+    smoothing: Duration,
+    pub gain: AudioParameter,
+    pub pan: AudioParameter,
+    pub cutoff: AudioParameter,
+}
+
+impl Node for EmitterNode {
+    fn get_id(&self) -> Id {
+        self.id
+    }
+
+    fn get_command_queue(&self) -> CommandQueue {
+        self.command_queue.clone()
+    }
+}
+
+const MIN_GAIN: f64 = 0.0;
+const MAX_GAIN: f64 = 2.0;
+const MIN_PAN: f64 = -1.0;
+const MAX_PAN: f64 = 1.0;
+const MIN_CUTOFF: f64 = 20.0;
+const MAX_CUTOFF: f64 = 22000.0;
+
+impl EmitterNode {
+    pub fn new(command_queue: CommandQueue, smoothing: Duration) -> Self {
+        let id = Id::generate();
+
+        let mut parameters = HashMap::new();
+
+        let (gain, realtime_gain) =
+            AudioParameter::new(id, 1.0, MIN_GAIN, MAX_GAIN, command_queue.clone());
+        parameters.insert(realtime_gain.get_id(), realtime_gain);
+
+        let (pan, realtime_pan) =
+            AudioParameter::new(id, 0.0, MIN_PAN, MAX_PAN, command_queue.clone());
+        parameters.insert(realtime_pan.get_id(), realtime_pan);
+
+        let (cutoff, realtime_cutoff) =
+            AudioParameter::new(id, 20000.0, MIN_CUTOFF, MAX_CUTOFF, command_queue.clone());
+        parameters.insert(realtime_cutoff.get_id(), realtime_cutoff);
+
+        let dsp = Dsp::new(
+            id,
+            Box::new(EmitterProcessor::new(
+                gain.get_id(),
+                pan.get_id(),
+                cutoff.get_id(),
+            )),
+            parameters,
+        );
+
+        Dsp::add_to_audio_process(dsp, &command_queue);
+
+        Self {
+            command_queue,
+            id,
+            distance_model: DistanceModel::default(),
+            smoothing,
+            gain,
+            pan,
+            cutoff,
+        }
+    }
+
+    pub fn set_distance_model(&mut self, distance_model: DistanceModel) {
+        self.distance_model = distance_model;
+    }
+
+    pub fn set_attributes(
+        &mut self,
+        attributes: &EmitterAttributes,
+        listener: &Listener,
+        current_time: Timestamp,
+    ) {
+        let targets = EmitterTargets::from_attributes(
+            attributes,
+            listener,
+            &self.distance_model,
+            self.smoothing.as_secs_f64(),
+        );
+
+        let end_time = current_time.incremented_by_seconds(self.smoothing.as_secs_f64());
+
+        for (parameter, target) in [
+            (&mut self.gain, targets.gain),
+            (&mut self.pan, targets.pan),
+            (&mut self.cutoff, targets.cutoff),
+        ] {
+            let current_value = parameter.get_value().load(Ordering::Acquire);
+            parameter.set_value_at_time(current_value, current_time);
+            parameter.linear_ramp_to_value(target, end_time);
+        }
+    }
+}
+
+impl Drop for EmitterNode {
+    fn drop(&mut self) {
+        Dsp::remove_from_audio_process(self.id, &self.command_queue);
+    }
+}
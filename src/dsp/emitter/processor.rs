@@ -0,0 +1,178 @@
+use std::f64::consts::{FRAC_PI_4, PI, SQRT_2};
+
+use crate::{
+    commands::id::Id,
+    graph::dsp::{DspParameterMap, DspProcessor},
+    utility::fast_math,
+    AudioBuffer, SampleLocation, Timestamp,
+};
+
+use super::attributes::OPEN_CUTOFF;
+
+const MAX_CHANNELS: usize = 8;
+
+pub struct EmitterProcessor {
+    gain_id: Id,
+    pan_id: Id,
+    cutoff_id: Id,
+    filter_state: [f32; MAX_CHANNELS],
+}
+
+impl EmitterProcessor {
+    pub fn new(gain_id: Id, pan_id: Id, cutoff_id: Id) -> Self {
+        fast_math::initialise();
+
+        Self {
+            gain_id,
+            pan_id,
+            cutoff_id,
+            filter_state: [0.0; MAX_CHANNELS],
+        }
+    }
+
+    fn filter_coefficient(cutoff: f64, sample_rate: usize) -> f32 {
+        if cutoff >= OPEN_CUTOFF {
+            return 1.0;
+        }
+
+        let nyquist = sample_rate as f64 / 2.0;
+        let cutoff = cutoff.clamp(1.0, nyquist);
+        (1.0 - (-2.0 * PI * cutoff / sample_rate as f64).exp()) as f32
+    }
+
+    fn channel_gain(channel: usize, num_channels: usize, gain: f64, pan: f64) -> f32 {
+        if num_channels != 2 {
+            return gain as f32;
+        }
+
+        let angle = (pan.clamp(-1.0, 1.0) + 1.0) * FRAC_PI_4;
+        let pan_gain = if channel == 0 {
+            fast_math::cos(angle)
+        } else {
+            fast_math::sin(angle)
+        };
+
+        (gain * pan_gain * SQRT_2) as f32
+    }
+}
+
+impl DspProcessor for EmitterProcessor {
+    fn process_audio(
+        &mut self,
+        input_buffer: &dyn AudioBuffer,
+        output_buffer: &mut dyn AudioBuffer,
+        start_time: &Timestamp,
+        parameters: &DspParameterMap,
+    ) {
+        let sample_rate = output_buffer.sample_rate();
+        let num_channels = output_buffer.num_channels().min(MAX_CHANNELS);
+
+        let (gain, pan, cutoff) = match (
+            parameters.get(&self.gain_id),
+            parameters.get(&self.pan_id),
+            parameters.get(&self.cutoff_id),
+        ) {
+            (Some(gain), Some(pan), Some(cutoff)) => (gain, pan, cutoff),
+            _ => return,
+        };
+
+        for frame in 0..output_buffer.num_frames() {
+            let frame_time = start_time.incremented_by_samples(frame, sample_rate);
+            let gain = gain.get_value_at_time(&frame_time);
+            let pan = pan.get_value_at_time(&frame_time);
+            let coefficient =
+                Self::filter_coefficient(cutoff.get_value_at_time(&frame_time), sample_rate);
+
+            for channel in 0..num_channels {
+                let location = SampleLocation::new(channel, frame);
+                let input = input_buffer.get_sample(location);
+
+                let state = &mut self.filter_state[channel];
+                *state += coefficient * (input - *state);
+
+                let output = *state * Self::channel_gain(channel, num_channels, gain, pan);
+                output_buffer.set_sample(location, output);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use approx::assert_relative_eq;
+    use atomic_float::AtomicF64;
+
+    use crate::{parameter::realtime_parameter::RealtimeAudioParameter, OwnedAudioBuffer};
+
+    use super::*;
+
+    fn add_parameter(parameters: &mut DspParameterMap, value: f64) -> Id {
+        let id = Id::generate();
+        parameters.insert(
+            id,
+            RealtimeAudioParameter::new(id, Arc::new(AtomicF64::new(value))),
+        );
+        id
+    }
+
+    fn process(gain: f64, pan: f64, cutoff: f64) -> OwnedAudioBuffer {
+        let mut parameters = DspParameterMap::new();
+        let gain_id = add_parameter(&mut parameters, gain);
+        let pan_id = add_parameter(&mut parameters, pan);
+        let cutoff_id = add_parameter(&mut parameters, cutoff);
+
+        let mut processor = EmitterProcessor::new(gain_id, pan_id, cutoff_id);
+
+        let mut input_buffer = OwnedAudioBuffer::new(100, 2, 1000);
+        input_buffer.fill_with_value(1.0);
+        let mut output_buffer = OwnedAudioBuffer::new(100, 2, 1000);
+
+        processor.process_audio(
+            &input_buffer,
+            &mut output_buffer,
+            &Timestamp::zero(),
+            &parameters,
+        );
+
+        output_buffer
+    }
+
+    #[test]
+    fn centred_open_emitter_passes_signal() {
+        let output = process(1.0, 0.0, 20000.0);
+        assert_relative_eq!(
+            output.get_sample(SampleLocation::new(0, 99)),
+            1.0,
+            epsilon = 1e-4
+        );
+        assert_relative_eq!(
+            output.get_sample(SampleLocation::new(1, 99)),
+            1.0,
+            epsilon = 1e-4
+        );
+    }
+
+    #[test]
+    fn pans_and_filters() {
+        let output = process(0.5, 1.0, 20000.0);
+        assert_relative_eq!(
+            output.get_sample(SampleLocation::new(0, 99)),
+            0.0,
+            epsilon = 1e-4
+        );
+
+        let output = process(1.0, 0.0, 10.0);
+        assert!(output.get_sample(SampleLocation::new(0, 0)) < 0.1);
+    }
+
+    #[test]
+    fn open_emitter_is_unfiltered() {
+        assert_eq!(
+            EmitterProcessor::filter_coefficient(OPEN_CUTOFF, 48_000),
+            1.0
+        );
+        assert!(EmitterProcessor::filter_coefficient(OPEN_CUTOFF - 1.0, 48_000) < 1.0);
+    }
+}
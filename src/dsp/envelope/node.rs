@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+
+use crate::{
+    commands::{command_queue::CommandQueue, id::Id},
+    events::note_event::NoteEventTransmitter,
+    graph::{dsp::Dsp, node::Node},
+    parameter::audio_parameter::AudioParameter,
+    Timestamp,
+};
+
+use super::processor::{EnvelopeEvent, EnvelopeEventTransmitter, EnvelopeProcessor};
+
+const MIN_SEGMENT_TIME: f64 = 0.0;
+const MAX_SEGMENT_TIME: f64 = 30.0;
+const DEFAULT_ATTACK: f64 = 0.01;
+const DEFAULT_DECAY: f64 = 0.1;
+const DEFAULT_SUSTAIN: f64 = 0.7;
+const DEFAULT_RELEASE: f64 = 0.3;
+
+pub struct EnvelopeNode {
+    id: Id,
+    command_queue: CommandQueue,
+    event_transmitter: EnvelopeEventTransmitter,
+    note_transmitter: NoteEventTransmitter,
+    pub attack: AudioParameter,
+    pub decay: AudioParameter,
+    pub sustain: AudioParameter,
+    pub release: AudioParameter,
+}
+
+impl EnvelopeNode {
+    pub fn new(command_queue: CommandQueue) -> Self {
+        let id = Id::generate();
+
+        let (event_transmitter, event_receiver) = lockfree::channel::spsc::create();
+        let (note_transmitter, note_receiver) = lockfree::channel::mpsc::create();
+
+        let mut parameters = HashMap::new();
+
+        let mut segment_parameter = |value: f64, maximum: f64| {
+            let (parameter, realtime_parameter) =
+                AudioParameter::new(id, value, MIN_SEGMENT_TIME, maximum, command_queue.clone());
+            parameters.insert(realtime_parameter.get_id(), realtime_parameter);
+            parameter
+        };
+
+        let attack = segment_parameter(DEFAULT_ATTACK, MAX_SEGMENT_TIME);
+        let decay = segment_parameter(DEFAULT_DECAY, MAX_SEGMENT_TIME);
+        let sustain = segment_parameter(DEFAULT_SUSTAIN, 1.0);
+        let release = segment_parameter(DEFAULT_RELEASE, MAX_SEGMENT_TIME);
+
+        let processor = EnvelopeProcessor::new(
+            attack.get_id(),
+            decay.get_id(),
+            sustain.get_id(),
+            release.get_id(),
+            event_receiver,
+        )
+        .with_note_receiver(note_receiver);
+
+        let dsp = Dsp::new(id, Box::new(processor), parameters);
+
+        Dsp::add_to_audio_process(dsp, &command_queue);
+
+        Self {
+            id,
+            command_queue,
+            event_transmitter,
+            note_transmitter,
+            attack,
+            decay,
+            sustain,
+            release,
+        }
+    }
+
+    pub fn note_on(&mut self, time: Timestamp, velocity: f64) {
+        let _ = self
+            .event_transmitter
+            .send(EnvelopeEvent::note_on(time, velocity));
+    }
+
+    pub fn note_off(&mut self, time: Timestamp) {
+        let _ = self.event_transmitter.send(EnvelopeEvent::note_off(time));
+    }
+
+    pub fn note_input(&self) -> NoteEventTransmitter {
+        self.note_transmitter.clone()
+    }
+}
+
+impl Node for EnvelopeNode {
+    fn get_id(&self) -> Id {
+        self.id
+    }
+
+    fn get_command_queue(&self) -> CommandQueue {
+        self.command_queue.clone()
+    }
+}
+
+impl Drop for EnvelopeNode {
+    fn drop(&mut self) {
+        Dsp::remove_from_audio_process(self.id, &self.command_queue);
+    }
+}
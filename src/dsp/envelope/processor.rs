@@ -0,0 +1,319 @@
+use crate::{
+    commands::id::Id,
+    events::note_event::{NoteEvent, NoteEventReceiver, NoteEventType},
+    graph::dsp::{DspParameterMap, DspProcessor},
+    utility::trace::{self, Category},
+    AudioBuffer, SampleLocation, Timestamp,
+};
+
+pub type EnvelopeEventReceiver = lockfree::channel::spsc::Receiver<EnvelopeEvent>;
+pub type EnvelopeEventTransmitter = lockfree::channel::spsc::Sender<EnvelopeEvent>;
+
+const MAX_PENDING_EVENTS: usize = 32;
+const STAGE_TOLERANCE: f64 = 1e-9;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EnvelopeEventType {
+    NoteOn(f64),
+    NoteOff,
+}
+
+impl EnvelopeEventType {
+    fn name(&self) -> &'static str {
+        match self {
+            EnvelopeEventType::NoteOn(_) => "EnvelopeNoteOn",
+            EnvelopeEventType::NoteOff => "EnvelopeNoteOff",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EnvelopeEvent {
+    time: Timestamp,
+    event_type: EnvelopeEventType,
+}
+
+impl EnvelopeEvent {
+    pub fn note_on(time: Timestamp, velocity: f64) -> Self {
+        Self {
+            time,
+            event_type: EnvelopeEventType::NoteOn(velocity.clamp(0.0, 1.0)),
+        }
+    }
+
+    pub fn note_off(time: Timestamp) -> Self {
+        Self {
+            time,
+            event_type: EnvelopeEventType::NoteOff,
+        }
+    }
+
+    fn from_note_event(event: &NoteEvent) -> Option<Self> {
+        match event.event_type {
+            NoteEventType::NoteOn { velocity, .. } => Some(Self::note_on(event.time, velocity)),
+            NoteEventType::NoteOff { .. } => Some(Self::note_off(event.time)),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Stage {
+    Idle,
+    Attack,
+    Decay,
+    Sustain,
+    Release,
+}
+
+#[derive(Clone, Copy)]
+struct Segments {
+    attack: f64,
+    decay: f64,
+    sustain: f64,
+    release: f64,
+}
+
+pub struct EnvelopeProcessor {
+    attack_id: Id,
+    decay_id: Id,
+    sustain_id: Id,
+    release_id: Id,
+    event_receiver: EnvelopeEventReceiver,
+    note_receiver: Option<NoteEventReceiver>,
+    pending_events: Vec<EnvelopeEvent>,
+    stage: Stage,
+    value: f64,
+    velocity: f64,
+    release_step: f64,
+}
+
+impl EnvelopeProcessor {
+    pub fn new(
+        attack_id: Id,
+        decay_id: Id,
+        sustain_id: Id,
+        release_id: Id,
+        event_receiver: EnvelopeEventReceiver,
+    ) -> Self {
+        Self {
+            attack_id,
+            decay_id,
+            sustain_id,
+            release_id,
+            event_receiver,
+            note_receiver: None,
+            pending_events: Vec::with_capacity(MAX_PENDING_EVENTS),
+            stage: Stage::Idle,
+            value: 0.0,
+            velocity: 0.0,
+            release_step: 0.0,
+        }
+    }
+
+    pub fn with_note_receiver(mut self, note_receiver: NoteEventReceiver) -> Self {
+        self.note_receiver = Some(note_receiver);
+        self
+    }
+
+    fn read_events(&mut self) {
+        let mut sort_required = false;
+
+        while let Ok(event) = self.event_receiver.recv() {
+            if self.pending_events.len() == self.pending_events.capacity() {
+                continue;
+            }
+
+            self.pending_events.push(event);
+            sort_required = true;
+        }
+
+        while let Some(Ok(event)) = self.note_receiver.as_mut().map(|receiver| receiver.recv()) {
+            let event = match EnvelopeEvent::from_note_event(&event) {
+                Some(event) => event,
+                None => continue,
+            };
+
+            if self.pending_events.len() == self.pending_events.capacity() {
+                continue;
+            }
+
+            self.pending_events.push(event);
+            sort_required = true;
+        }
+
+        if sort_required {
+            self.pending_events.sort_by_key(|event| event.time);
+        }
+    }
+
+    fn process_event(&mut self, event: &EnvelopeEvent, segments: &Segments) {
+        trace::instant(event.event_type.name(), Category::Event, None);
+
+        match event.event_type {
+            EnvelopeEventType::NoteOn(velocity) => {
+                self.velocity = velocity;
+                self.stage = Stage::Attack;
+            }
+            EnvelopeEventType::NoteOff => {
+                if self.stage != Stage::Idle {
+                    self.release_step = self.value / segments.release;
+                    self.stage = Stage::Release;
+                }
+            }
+        }
+    }
+
+    fn advance(&mut self, segments: &Segments) -> f64 {
+        let sustain_level = segments.sustain * self.velocity;
+
+        match self.stage {
+            Stage::Idle => self.value = 0.0,
+            Stage::Attack => {
+                self.value += self.velocity / segments.attack;
+
+                if self.value >= self.velocity - STAGE_TOLERANCE {
+                    self.value = self.velocity;
+                    self.stage = Stage::Decay;
+                }
+            }
+            Stage::Decay => {
+                self.value -= (self.velocity - sustain_level) / segments.decay;
+
+                if self.value <= sustain_level + STAGE_TOLERANCE {
+                    self.value = sustain_level;
+                    self.stage = Stage::Sustain;
+                }
+            }
+            Stage::Sustain => self.value = sustain_level,
+            Stage::Release => {
+                self.value -= self.release_step;
+
+                if self.value <= STAGE_TOLERANCE {
+                    self.value = 0.0;
+                    self.stage = Stage::Idle;
+                }
+            }
+        }
+
+        self.value
+    }
+
+    fn segments(
+        &self,
+        parameters: &DspParameterMap,
+        time: &Timestamp,
+        sample_rate: usize,
+    ) -> Option<Segments> {
+        let value = |id: &Id| {
+            parameters
+                .get(id)
+                .map(|parameter| parameter.get_value_at_time(time))
+        };
+        let samples = |seconds: f64| (seconds * sample_rate as f64).max(1.0);
+
+        Some(Segments {
+            attack: samples(value(&self.attack_id)?),
+            decay: samples(value(&self.decay_id)?),
+            sustain: value(&self.sustain_id)?,
+            release: samples(value(&self.release_id)?),
+        })
+    }
+}
+
+impl DspProcessor for EnvelopeProcessor {
+    fn process_audio(
+        &mut self,
+        _input_buffer: &dyn AudioBuffer,
+        output_buffer: &mut dyn AudioBuffer,
+        start_time: &Timestamp,
+        parameters: &DspParameterMap,
+    ) {
+        self.read_events();
+
+        let sample_rate = output_buffer.sample_rate();
+
+        let segments = match self.segments(parameters, start_time, sample_rate) {
+            Some(segments) => segments,
+            None => return,
+        };
+
+        for frame in 0..output_buffer.num_frames() {
+            let frame_time = start_time.incremented_by_samples(frame, sample_rate);
+
+            while let Some(event) = self.pending_events.first().copied() {
+                if event.time > frame_time {
+                    break;
+                }
+
+                self.pending_events.remove(0);
+                self.process_event(&event, &segments);
+            }
+
+            let value = self.advance(&segments) as f32;
+
+            for channel in 0..output_buffer.num_channels() {
+                output_buffer.set_sample(SampleLocation::new(channel, frame), value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use approx::assert_relative_eq;
+    use atomic_float::AtomicF64;
+    use lockfree::channel::spsc;
+
+    use crate::{parameter::realtime_parameter::RealtimeAudioParameter, OwnedAudioBuffer};
+
+    use super::*;
+
+    const SAMPLE_RATE: usize = 1_000;
+
+    fn parameter(parameters: &mut DspParameterMap, value: f64) -> Id {
+        let id = Id::generate();
+        parameters.insert(
+            id,
+            RealtimeAudioParameter::new(id, Arc::new(AtomicF64::new(value))),
+        );
+        id
+    }
+
+    #[test]
+    fn follows_attack_decay_sustain_and_release() {
+        let mut parameters = DspParameterMap::new();
+        let attack_id = parameter(&mut parameters, 0.01);
+        let decay_id = parameter(&mut parameters, 0.01);
+        let sustain_id = parameter(&mut parameters, 0.5);
+        let release_id = parameter(&mut parameters, 0.02);
+
+        let (mut event_transmitter, event_receiver) = spsc::create();
+        let mut processor =
+            EnvelopeProcessor::new(attack_id, decay_id, sustain_id, release_id, event_receiver);
+
+        let _ = event_transmitter.send(EnvelopeEvent::note_off(Timestamp::from_seconds(0.05)));
+        let _ = event_transmitter.send(EnvelopeEvent::note_on(Timestamp::zero(), 1.0));
+
+        let input_buffer = OwnedAudioBuffer::new(100, 1, SAMPLE_RATE);
+        let mut output_buffer = OwnedAudioBuffer::new(100, 1, SAMPLE_RATE);
+        processor.process_audio(
+            &input_buffer,
+            &mut output_buffer,
+            &Timestamp::zero(),
+            &parameters,
+        );
+
+        let sample = |frame| output_buffer.get_sample(SampleLocation::new(0, frame));
+        assert_relative_eq!(sample(0), 0.1, epsilon = 1e-6);
+        assert_relative_eq!(sample(9), 1.0, epsilon = 1e-6);
+        assert_relative_eq!(sample(14), 0.75, epsilon = 1e-6);
+        assert_relative_eq!(sample(19), 0.5, epsilon = 1e-6);
+        assert_relative_eq!(sample(49), 0.5, epsilon = 1e-6);
+        assert_relative_eq!(sample(59), 0.25, epsilon = 1e-6);
+        assert_relative_eq!(sample(69), 0.0, epsilon = 1e-6);
+        assert_relative_eq!(sample(99), 0.0, epsilon = 1e-6);
+    }
+}
@@ -1,2 +1,2 @@
 pub mod node;
-mod processor;
+pub(crate) mod processor;
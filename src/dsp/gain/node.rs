@@ -1,18 +1,19 @@
 use std::collections::HashMap;
 
-use lockfree::prelude::mpsc::Sender;
-
 use crate::{
-    commands::{command::Command, id::Id},
+    commands::{command_queue::CommandQueue, id::Id},
     graph::{dsp::Dsp, node::Node},
-    parameter::audio_parameter::AudioParameter,
+    parameter::{
+        audio_parameter::AudioParameter,
+        preset::{NodePreset, PresetError, PresetNode},
+    },
 };
 
 use super::processor::GainProcessor;
 
 pub struct GainNode {
     id: Id,
-    command_queue: Sender<Command>,
+    command_queue: CommandQueue,
     pub gain: AudioParameter,
 }
 
@@ -20,7 +21,7 @@ const MIN_GAIN: f64 = -2.0;
 const MAX_GAIN: f64 = 2.0;
 
 impl GainNode {
-    pub fn new(command_queue: Sender<Command>) -> Self {
+    pub fn new(command_queue: CommandQueue) -> Self {
         let mut parameters = HashMap::new();
 
         let id = Id::generate();
@@ -45,7 +46,25 @@ impl Node for GainNode {
         self.id
     }
 
-    fn get_command_queue(&self) -> Sender<Command> {
+    fn get_command_queue(&self) -> CommandQueue {
         self.command_queue.clone()
     }
 }
+
+impl PresetNode for GainNode {
+    fn preset_type(&self) -> &'static str {
+        "gain"
+    }
+
+    fn capture_preset(&self, name: &str) -> NodePreset {
+        let mut preset = NodePreset::new(self.preset_type(), name);
+        preset.capture_parameter("gain", &self.gain);
+        preset
+    }
+
+    fn apply_preset(&mut self, preset: &NodePreset) -> Result<(), PresetError> {
+        preset.check_node_type(self.preset_type())?;
+        preset.apply_parameter("gain", &mut self.gain);
+        Ok(())
+    }
+}
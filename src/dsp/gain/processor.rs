@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use crate::{
     commands::id::Id,
     graph::dsp::{DspParameterMap, DspProcessor},
@@ -40,4 +42,8 @@ impl DspProcessor for GainProcessor {
             }
         }
     }
+
+    fn tail_time(&self) -> Option<Duration> {
+        Some(Duration::ZERO)
+    }
 }
@@ -0,0 +1,95 @@
+use std::{collections::HashMap, sync::atomic::Ordering};
+
+use crate::{
+    commands::{command_queue::CommandQueue, id::Id},
+    graph::{dsp::Dsp, node::Node},
+    parameter::audio_parameter::AudioParameter,
+};
+
+use super::processor::{LevelerFreeze, LevelerGain, LevelerProcessor};
+
+pub struct LevelerNode {
+    id: Id,
+    command_queue: CommandQueue,
+    freeze: LevelerFreeze,
+    gain: LevelerGain,
+    pub target: AudioParameter,
+    pub max_gain: AudioParameter,
+}
+
+const MIN_TARGET: f64 = -40.0;
+const MAX_TARGET: f64 = 0.0;
+const MIN_MAX_GAIN: f64 = 0.0;
+const MAX_MAX_GAIN: f64 = 30.0;
+
+impl LevelerNode {
+    pub fn new(command_queue: CommandQueue, target_lufs: f64) -> Self {
+        let mut parameters = HashMap::new();
+
+        let id = Id::generate();
+
+        let (target, realtime_target) = AudioParameter::new(
+            id,
+            target_lufs,
+            MIN_TARGET,
+            MAX_TARGET,
+            command_queue.clone(),
+        );
+        parameters.insert(realtime_target.get_id(), realtime_target);
+
+        let (max_gain, realtime_max_gain) =
+            AudioParameter::new(id, 12.0, MIN_MAX_GAIN, MAX_MAX_GAIN, command_queue.clone());
+        parameters.insert(realtime_max_gain.get_id(), realtime_max_gain);
+
+        let freeze = LevelerFreeze::default();
+        let gain = LevelerGain::default();
+
+        let processor = LevelerProcessor::new(
+            target.get_id(),
+            max_gain.get_id(),
+            freeze.clone(),
+            gain.clone(),
+        );
+
+        let dsp = Dsp::new(id, Box::new(processor), parameters);
+
+        Dsp::add_to_audio_process(dsp, &command_queue);
+
+        Self {
+            id,
+            command_queue,
+            freeze,
+            gain,
+            target,
+            max_gain,
+        }
+    }
+
+    pub fn set_frozen(&mut self, frozen: bool) {
+        self.freeze.store(frozen, Ordering::Release);
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        self.freeze.load(Ordering::Acquire)
+    }
+
+    pub fn get_gain_db(&self) -> f64 {
+        self.gain.load(Ordering::Acquire)
+    }
+}
+
+impl Node for LevelerNode {
+    fn get_id(&self) -> Id {
+        self.id
+    }
+
+    fn get_command_queue(&self) -> CommandQueue {
+        self.command_queue.clone()
+    }
+}
+
+impl Drop for LevelerNode {
+    fn drop(&mut self) {
+        Dsp::remove_from_audio_process(self.id, &self.command_queue);
+    }
+}
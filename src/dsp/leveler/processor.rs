@@ -0,0 +1,203 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use atomic_float::AtomicF64;
+
+use crate::{
+    commands::id::Id,
+    graph::dsp::{DspParameterMap, DspProcessor},
+    utility::{fast_math, loudness::LoudnessMeter},
+    AudioBuffer, SampleLocation, Timestamp,
+};
+
+const MAXIMUM_NUMBER_OF_CHANNELS: usize = 2;
+const GAIN_RATE_DB_PER_SECOND: f64 = 3.0;
+const SILENCE_GATE_LUFS: f64 = -60.0;
+
+pub type LevelerFreeze = Arc<AtomicBool>;
+pub type LevelerGain = Arc<AtomicF64>;
+
+pub struct LevelerProcessor {
+    target_id: Id,
+    max_gain_id: Id,
+    freeze: LevelerFreeze,
+    gain: LevelerGain,
+    meter: Option<LoudnessMeter>,
+    sample_rate: usize,
+    gain_db: f64,
+    target_gain_db: f64,
+}
+
+impl LevelerProcessor {
+    pub fn new(target_id: Id, max_gain_id: Id, freeze: LevelerFreeze, gain: LevelerGain) -> Self {
+        Self {
+            target_id,
+            max_gain_id,
+            freeze,
+            gain,
+            meter: None,
+            sample_rate: 0,
+            gain_db: 0.0,
+            target_gain_db: 0.0,
+        }
+    }
+
+    fn prepare(&mut self, sample_rate: usize) {
+        if self.sample_rate != sample_rate || self.meter.is_none() {
+            self.meter = Some(LoudnessMeter::new(sample_rate));
+            self.sample_rate = sample_rate;
+        }
+    }
+
+    fn update_target_gain(&mut self, momentary: f64, short_term: f64, target: f64, max_gain: f64) {
+        if self.freeze.load(Ordering::Acquire) || momentary < SILENCE_GATE_LUFS {
+            self.target_gain_db = self.gain_db;
+            return;
+        }
+
+        self.target_gain_db = (target - short_term).clamp(-max_gain, max_gain);
+    }
+}
+
+impl DspProcessor for LevelerProcessor {
+    fn process_audio(
+        &mut self,
+        input_buffer: &dyn AudioBuffer,
+        output_buffer: &mut dyn AudioBuffer,
+        start_time: &Timestamp,
+        parameters: &DspParameterMap,
+    ) {
+        let sample_rate = output_buffer.sample_rate();
+        self.prepare(sample_rate);
+
+        let (target, max_gain) = match (
+            parameters.get(&self.target_id),
+            parameters.get(&self.max_gain_id),
+        ) {
+            (Some(target), Some(max_gain)) => (target, max_gain),
+            _ => return,
+        };
+
+        let num_channels = output_buffer
+            .num_channels()
+            .min(input_buffer.num_channels())
+            .min(MAXIMUM_NUMBER_OF_CHANNELS);
+
+        let step_db = GAIN_RATE_DB_PER_SECOND / sample_rate as f64;
+        let mut samples = [0.0; MAXIMUM_NUMBER_OF_CHANNELS];
+
+        for frame in 0..output_buffer.num_frames() {
+            for (channel, sample) in samples.iter_mut().enumerate().take(num_channels) {
+                *sample = input_buffer.get_sample(SampleLocation::new(channel, frame)) as f64;
+            }
+
+            let block_complete = match self.meter.as_mut() {
+                Some(meter) => meter.process_frame(&samples[..num_channels]),
+                None => false,
+            };
+
+            if block_complete {
+                let frame_time = start_time.incremented_by_samples(frame, sample_rate);
+                let (momentary, short_term) = self
+                    .meter
+                    .as_ref()
+                    .map_or((f64::NEG_INFINITY, f64::NEG_INFINITY), |meter| {
+                        (meter.momentary_lufs(), meter.short_term_lufs())
+                    });
+                self.update_target_gain(
+                    momentary,
+                    short_term,
+                    target.get_value_at_time(&frame_time),
+                    max_gain.get_value_at_time(&frame_time),
+                );
+            }
+
+            self.gain_db += (self.target_gain_db - self.gain_db).clamp(-step_db, step_db);
+            let gain = fast_math::db_to_gain(self.gain_db);
+
+            for (channel, sample) in samples.iter().enumerate().take(num_channels) {
+                output_buffer
+                    .set_sample(SampleLocation::new(channel, frame), (*sample * gain) as f32);
+            }
+        }
+
+        self.gain.store(self.gain_db, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::TAU;
+
+    use crate::{parameter::realtime_parameter::RealtimeAudioParameter, OwnedAudioBuffer};
+
+    use crate::utility::level::Level;
+
+    use super::*;
+
+    const SAMPLE_RATE: usize = 16_000;
+
+    fn run(
+        processor: &mut LevelerProcessor,
+        parameters: &DspParameterMap,
+        amplitude: f64,
+        seconds: f64,
+    ) {
+        let num_blocks = (seconds * SAMPLE_RATE as f64 / 512.0) as usize;
+
+        for block in 0..num_blocks {
+            let mut input_buffer = OwnedAudioBuffer::new(512, 1, SAMPLE_RATE);
+            for frame in 0..512 {
+                let time = (block * 512 + frame) as f64 / SAMPLE_RATE as f64;
+                let value = amplitude * (TAU * 997.0 * time).sin();
+                input_buffer.set_sample(SampleLocation::new(0, frame), value as f32);
+            }
+
+            let mut output_buffer = OwnedAudioBuffer::new(512, 1, SAMPLE_RATE);
+            processor.process_audio(
+                &input_buffer,
+                &mut output_buffer,
+                &Timestamp::zero(),
+                parameters,
+            );
+        }
+    }
+
+    #[test]
+    fn levels_towards_target_within_max_gain_and_freezes() {
+        let target_id = Id::generate();
+        let max_gain_id = Id::generate();
+
+        let mut parameters = DspParameterMap::new();
+        parameters.insert(
+            target_id,
+            RealtimeAudioParameter::new(target_id, Arc::new(AtomicF64::new(-20.0))),
+        );
+        parameters.insert(
+            max_gain_id,
+            RealtimeAudioParameter::new(max_gain_id, Arc::new(AtomicF64::new(12.0))),
+        );
+
+        let freeze = LevelerFreeze::default();
+        let gain = LevelerGain::default();
+        let mut processor =
+            LevelerProcessor::new(target_id, max_gain_id, freeze.clone(), gain.clone());
+
+        let quiet = Level::from_db(-26.99).as_gain();
+        run(&mut processor, &parameters, quiet, 10.0);
+        assert!((gain.load(Ordering::Acquire) - 10.0).abs() < 0.2);
+
+        let very_quiet = Level::from_db(-36.99).as_gain();
+        run(&mut processor, &parameters, very_quiet, 12.0);
+        assert!((gain.load(Ordering::Acquire) - 12.0).abs() < 0.2);
+
+        run(&mut processor, &parameters, 0.0, 5.0);
+        assert!((gain.load(Ordering::Acquire) - 12.0).abs() < 0.2);
+
+        freeze.store(true, Ordering::Release);
+        run(&mut processor, &parameters, 1.0, 5.0);
+        assert!((gain.load(Ordering::Acquire) - 12.0).abs() < 0.2);
+    }
+}
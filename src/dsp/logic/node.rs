@@ -0,0 +1,283 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use lockfree::channel::spsc::{self, Receiver};
+
+use crate::{
+    commands::{command_queue::CommandQueue, id::Id},
+    dsp::constant::node::{MAX_SIGNAL_VALUE, MIN_SIGNAL_VALUE},
+    graph::{
+        dsp::{Dsp, DspParameterMap},
+        node::Node,
+    },
+    parameter::{
+        audio_parameter::AudioParameter,
+        preset::{NodePreset, PresetError, PresetNode, PresetValue},
+    },
+    timestamp::Timestamp,
+};
+
+use super::processor::{
+    ComparatorProcessor, Comparison, LogicOperation, LogicProcessor, TriggerProcessor,
+};
+
+const MIN_HYSTERESIS: f64 = 0.0;
+const MAX_HYSTERESIS: f64 = 100_000.0;
+
+fn make_threshold_parameters(
+    id: Id,
+    threshold: f64,
+    command_queue: &CommandQueue,
+    parameters: &mut DspParameterMap,
+) -> (AudioParameter, AudioParameter) {
+    let (threshold, realtime_threshold) = AudioParameter::new(
+        id,
+        threshold,
+        MIN_SIGNAL_VALUE,
+        MAX_SIGNAL_VALUE,
+        command_queue.clone(),
+    );
+    parameters.insert(realtime_threshold.get_id(), realtime_threshold);
+
+    let (hysteresis, realtime_hysteresis) = AudioParameter::new(
+        id,
+        0.0,
+        MIN_HYSTERESIS,
+        MAX_HYSTERESIS,
+        command_queue.clone(),
+    );
+    parameters.insert(realtime_hysteresis.get_id(), realtime_hysteresis);
+
+    (threshold, hysteresis)
+}
+
+pub struct ComparatorNode {
+    id: Id,
+    command_queue: CommandQueue,
+    pub threshold: AudioParameter,
+    pub hysteresis: AudioParameter,
+}
+
+impl ComparatorNode {
+    pub fn new(command_queue: CommandQueue, comparison: Comparison, threshold: f64) -> Self {
+        let mut parameters = HashMap::new();
+
+        let id = Id::generate();
+
+        let (threshold, hysteresis) =
+            make_threshold_parameters(id, threshold, &command_queue, &mut parameters);
+
+        let processor =
+            ComparatorProcessor::new(comparison, threshold.get_id(), hysteresis.get_id());
+
+        let dsp = Dsp::new(id, Box::new(processor), parameters);
+
+        Dsp::add_to_audio_process(dsp, &command_queue);
+
+        Self {
+            id,
+            command_queue,
+            threshold,
+            hysteresis,
+        }
+    }
+}
+
+pub struct LogicNode {
+    id: Id,
+    command_queue: CommandQueue,
+    num_inputs: Arc<AtomicUsize>,
+}
+
+impl LogicNode {
+    pub fn new(command_queue: CommandQueue, operation: LogicOperation, num_inputs: usize) -> Self {
+        let id = Id::generate();
+
+        let num_inputs = Arc::new(AtomicUsize::new(num_inputs));
+
+        let processor = LogicProcessor::new(operation, num_inputs.clone());
+
+        let dsp = Dsp::new(id, Box::new(processor), HashMap::new());
+
+        Dsp::add_to_audio_process(dsp, &command_queue);
+
+        Self {
+            id,
+            command_queue,
+            num_inputs,
+        }
+    }
+
+    pub fn set_num_inputs(&mut self, num_inputs: usize) {
+        self.num_inputs.store(num_inputs, Ordering::Release);
+    }
+
+    pub fn get_num_inputs(&self) -> usize {
+        self.num_inputs.load(Ordering::Acquire)
+    }
+}
+
+pub struct TriggerNode {
+    id: Id,
+    command_queue: CommandQueue,
+    trigger_rx: Receiver<Timestamp>,
+    pub threshold: AudioParameter,
+    pub hysteresis: AudioParameter,
+}
+
+impl TriggerNode {
+    pub fn new(command_queue: CommandQueue, threshold: f64) -> Self {
+        let mut parameters = HashMap::new();
+
+        let id = Id::generate();
+
+        let (threshold, hysteresis) =
+            make_threshold_parameters(id, threshold, &command_queue, &mut parameters);
+
+        let (trigger_tx, trigger_rx) = spsc::create();
+
+        let processor = TriggerProcessor::new(threshold.get_id(), hysteresis.get_id(), trigger_tx);
+
+        let dsp = Dsp::new(id, Box::new(processor), parameters);
+
+        Dsp::add_to_audio_process(dsp, &command_queue);
+
+        Self {
+            id,
+            command_queue,
+            trigger_rx,
+            threshold,
+            hysteresis,
+        }
+    }
+
+    pub fn take_triggers(&mut self) -> Vec<Timestamp> {
+        let mut triggers = Vec::new();
+
+        while let Ok(time) = self.trigger_rx.recv() {
+            triggers.push(time);
+        }
+
+        triggers
+    }
+}
+
+impl Node for ComparatorNode {
+    fn get_id(&self) -> Id {
+        self.id
+    }
+
+    fn get_command_queue(&self) -> CommandQueue {
+        self.command_queue.clone()
+    }
+}
+
+impl Drop for ComparatorNode {
+    fn drop(&mut self) {
+        Dsp::remove_from_audio_process(self.id, &self.command_queue);
+    }
+}
+
+impl Node for LogicNode {
+    fn get_id(&self) -> Id {
+        self.id
+    }
+
+    fn get_command_queue(&self) -> CommandQueue {
+        self.command_queue.clone()
+    }
+}
+
+impl Drop for LogicNode {
+    fn drop(&mut self) {
+        Dsp::remove_from_audio_process(self.id, &self.command_queue);
+    }
+}
+
+impl Node for TriggerNode {
+    fn get_id(&self) -> Id {
+        self.id
+    }
+
+    fn get_command_queue(&self) -> CommandQueue {
+        self.command_queue.clone()
+    }
+}
+
+impl Drop for TriggerNode {
+    fn drop(&mut self) {
+        Dsp::remove_from_audio_process(self.id, &self.command_queue);
+    }
+}
+
+impl PresetNode for ComparatorNode {
+    fn preset_type(&self) -> &'static str {
+        "comparator"
+    }
+
+    fn capture_preset(&self, name: &str) -> NodePreset {
+        let mut preset = NodePreset::new(self.preset_type(), name);
+        preset.capture_parameter("threshold", &self.threshold);
+        preset.capture_parameter("hysteresis", &self.hysteresis);
+        preset
+    }
+
+    fn apply_preset(&mut self, preset: &NodePreset) -> Result<(), PresetError> {
+        preset.check_node_type(self.preset_type())?;
+        preset.apply_parameter("threshold", &mut self.threshold);
+        preset.apply_parameter("hysteresis", &mut self.hysteresis);
+        Ok(())
+    }
+}
+
+impl PresetNode for TriggerNode {
+    fn preset_type(&self) -> &'static str {
+        "trigger"
+    }
+
+    fn capture_preset(&self, name: &str) -> NodePreset {
+        let mut preset = NodePreset::new(self.preset_type(), name);
+        preset.capture_parameter("threshold", &self.threshold);
+        preset.capture_parameter("hysteresis", &self.hysteresis);
+        preset
+    }
+
+    fn apply_preset(&mut self, preset: &NodePreset) -> Result<(), PresetError> {
+        preset.check_node_type(self.preset_type())?;
+        preset.apply_parameter("threshold", &mut self.threshold);
+        preset.apply_parameter("hysteresis", &mut self.hysteresis);
+        Ok(())
+    }
+}
+
+impl PresetNode for LogicNode {
+    fn preset_type(&self) -> &'static str {
+        "logic"
+    }
+
+    fn capture_preset(&self, name: &str) -> NodePreset {
+        NodePreset::new(self.preset_type(), name).with_state(
+            "num_inputs",
+            PresetValue::Number(self.get_num_inputs() as f64),
+        )
+    }
+
+    fn apply_preset(&mut self, preset: &NodePreset) -> Result<(), PresetError> {
+        preset.check_node_type(self.preset_type())?;
+
+        if let Some(value) = preset.get_state("num_inputs") {
+            let num_inputs = value
+                .as_number()
+                .filter(|num_inputs| *num_inputs >= 0.0)
+                .ok_or_else(|| PresetError::InvalidState(String::from("num_inputs")))?;
+            self.set_num_inputs(num_inputs as usize);
+        }
+
+        Ok(())
+    }
+}
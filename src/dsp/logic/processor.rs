@@ -0,0 +1,315 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use lockfree::channel::spsc::Sender;
+
+use crate::{
+    commands::id::Id,
+    graph::dsp::{DspParameterMap, DspProcessor},
+    AudioBuffer, SampleLocation, Timestamp,
+};
+
+const GATE_THRESHOLD: f32 = 0.5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Comparison {
+    Above,
+    Below,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogicOperation {
+    And,
+    Or,
+    Xor,
+    Not,
+}
+
+impl LogicOperation {
+    pub fn apply(&self, num_high: usize, num_inputs: usize) -> bool {
+        match self {
+            LogicOperation::And => num_inputs > 0 && num_high >= num_inputs,
+            LogicOperation::Or => num_high > 0,
+            LogicOperation::Xor => num_high % 2 == 1,
+            LogicOperation::Not => num_high == 0,
+        }
+    }
+}
+
+fn write_gate(output_buffer: &mut dyn AudioBuffer, frame: usize, value: f32) {
+    for channel in 0..output_buffer.num_channels() {
+        output_buffer.set_sample(SampleLocation::new(channel, frame), value);
+    }
+}
+
+fn gate_value(high: bool) -> f32 {
+    if high {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+struct Hysteresis {
+    high: bool,
+}
+
+impl Hysteresis {
+    fn update(&mut self, input: f64, threshold: f64, hysteresis: f64) -> bool {
+        self.high = if self.high {
+            input > threshold - hysteresis
+        } else {
+            input > threshold
+        };
+
+        self.high
+    }
+}
+
+pub struct ComparatorProcessor {
+    comparison: Comparison,
+    state: Hysteresis,
+    threshold_id: Id,
+    hysteresis_id: Id,
+}
+
+impl ComparatorProcessor {
+    pub fn new(comparison: Comparison, threshold_id: Id, hysteresis_id: Id) -> Self {
+        Self {
+            comparison,
+            state: Hysteresis { high: false },
+            threshold_id,
+            hysteresis_id,
+        }
+    }
+}
+
+impl DspProcessor for ComparatorProcessor {
+    fn process_audio(
+        &mut self,
+        input_buffer: &dyn AudioBuffer,
+        output_buffer: &mut dyn AudioBuffer,
+        start_time: &Timestamp,
+        parameters: &DspParameterMap,
+    ) {
+        let sample_rate = output_buffer.sample_rate();
+
+        let (threshold, hysteresis) = match (
+            parameters.get(&self.threshold_id),
+            parameters.get(&self.hysteresis_id),
+        ) {
+            (Some(threshold), Some(hysteresis)) => (threshold, hysteresis),
+            _ => return,
+        };
+
+        for frame in 0..output_buffer.num_frames() {
+            let frame_time = start_time.incremented_by_samples(frame, sample_rate);
+            let input = input_buffer.get_sample(SampleLocation::new(0, frame)) as f64;
+
+            let above = self.state.update(
+                input,
+                threshold.get_value_at_time(&frame_time),
+                hysteresis.get_value_at_time(&frame_time),
+            );
+
+            let high = match self.comparison {
+                Comparison::Above => above,
+                Comparison::Below => !above,
+            };
+
+            write_gate(output_buffer, frame, gate_value(high));
+        }
+    }
+}
+
+pub struct LogicProcessor {
+    operation: LogicOperation,
+    num_inputs: Arc<AtomicUsize>,
+}
+
+impl LogicProcessor {
+    pub fn new(operation: LogicOperation, num_inputs: Arc<AtomicUsize>) -> Self {
+        Self {
+            operation,
+            num_inputs,
+        }
+    }
+}
+
+impl DspProcessor for LogicProcessor {
+    fn process_audio(
+        &mut self,
+        input_buffer: &dyn AudioBuffer,
+        output_buffer: &mut dyn AudioBuffer,
+        _start_time: &Timestamp,
+        _parameters: &DspParameterMap,
+    ) {
+        let num_inputs = self.num_inputs.load(Ordering::Acquire);
+
+        for frame in 0..output_buffer.num_frames() {
+            let sum = input_buffer.get_sample(SampleLocation::new(0, frame));
+            let num_high = (sum + 1.0 - GATE_THRESHOLD).floor().max(0.0) as usize;
+            let high = self.operation.apply(num_high, num_inputs);
+
+            write_gate(output_buffer, frame, gate_value(high));
+        }
+    }
+
+    fn tail_time(&self) -> Option<Duration> {
+        match self.operation {
+            LogicOperation::Not => None,
+            _ => Some(Duration::ZERO),
+        }
+    }
+}
+
+pub struct TriggerProcessor {
+    state: Hysteresis,
+    threshold_id: Id,
+    hysteresis_id: Id,
+    trigger_tx: Sender<Timestamp>,
+}
+
+impl TriggerProcessor {
+    pub fn new(threshold_id: Id, hysteresis_id: Id, trigger_tx: Sender<Timestamp>) -> Self {
+        Self {
+            state: Hysteresis { high: false },
+            threshold_id,
+            hysteresis_id,
+            trigger_tx,
+        }
+    }
+}
+
+impl DspProcessor for TriggerProcessor {
+    fn process_audio(
+        &mut self,
+        input_buffer: &dyn AudioBuffer,
+        output_buffer: &mut dyn AudioBuffer,
+        start_time: &Timestamp,
+        parameters: &DspParameterMap,
+    ) {
+        let sample_rate = output_buffer.sample_rate();
+
+        let (threshold, hysteresis) = match (
+            parameters.get(&self.threshold_id),
+            parameters.get(&self.hysteresis_id),
+        ) {
+            (Some(threshold), Some(hysteresis)) => (threshold, hysteresis),
+            _ => return,
+        };
+
+        for frame in 0..output_buffer.num_frames() {
+            let frame_time = start_time.incremented_by_samples(frame, sample_rate);
+            let input = input_buffer.get_sample(SampleLocation::new(0, frame)) as f64;
+
+            let was_high = self.state.high;
+            let high = self.state.update(
+                input,
+                threshold.get_value_at_time(&frame_time),
+                hysteresis.get_value_at_time(&frame_time),
+            );
+
+            let triggered = high && !was_high;
+            if triggered {
+                let _ = self.trigger_tx.send(frame_time);
+            }
+
+            write_gate(output_buffer, frame, gate_value(triggered));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use atomic_float::AtomicF64;
+    use lockfree::channel::spsc;
+
+    use crate::{parameter::realtime_parameter::RealtimeAudioParameter, OwnedAudioBuffer};
+
+    use super::*;
+
+    fn make_parameters(values: &[(Id, f64)]) -> DspParameterMap {
+        let mut parameters = DspParameterMap::new();
+        for (id, value) in values {
+            parameters.insert(
+                *id,
+                RealtimeAudioParameter::new(*id, Arc::new(AtomicF64::new(*value))),
+            );
+        }
+        parameters
+    }
+
+    fn process(
+        processor: &mut dyn DspProcessor,
+        input: &[f32],
+        parameters: &DspParameterMap,
+    ) -> Vec<f32> {
+        let input_buffer = OwnedAudioBuffer::new_from_data(input.to_vec(), 1, 1000);
+        let mut output_buffer = OwnedAudioBuffer::new(input.len(), 1, 1000);
+        processor.process_audio(
+            &input_buffer,
+            &mut output_buffer,
+            &Timestamp::zero(),
+            parameters,
+        );
+
+        (0..input.len())
+            .map(|frame| output_buffer.get_sample(SampleLocation::new(0, frame)))
+            .collect()
+    }
+
+    #[test]
+    fn comparator_applies_hysteresis() {
+        let threshold_id = Id::generate();
+        let hysteresis_id = Id::generate();
+        let parameters = make_parameters(&[(threshold_id, 0.5), (hysteresis_id, 0.2)]);
+        let mut comparator =
+            ComparatorProcessor::new(Comparison::Above, threshold_id, hysteresis_id);
+
+        let output = process(&mut comparator, &[0.0, 0.6, 0.4, 0.2, 0.4], &parameters);
+
+        assert_eq!(output, vec![0.0, 1.0, 1.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn logic_counts_summed_gates() {
+        let num_inputs = Arc::new(AtomicUsize::new(2));
+        let parameters = DspParameterMap::new();
+        let input = [0.0, 1.0, 2.0];
+
+        let mut and = LogicProcessor::new(LogicOperation::And, num_inputs.clone());
+        let mut or = LogicProcessor::new(LogicOperation::Or, num_inputs.clone());
+        let mut xor = LogicProcessor::new(LogicOperation::Xor, num_inputs.clone());
+        let mut not = LogicProcessor::new(LogicOperation::Not, num_inputs);
+
+        assert_eq!(process(&mut and, &input, &parameters), vec![0.0, 0.0, 1.0]);
+        assert_eq!(process(&mut or, &input, &parameters), vec![0.0, 1.0, 1.0]);
+        assert_eq!(process(&mut xor, &input, &parameters), vec![0.0, 1.0, 0.0]);
+        assert_eq!(process(&mut not, &input, &parameters), vec![1.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn trigger_fires_once_per_crossing() {
+        let threshold_id = Id::generate();
+        let hysteresis_id = Id::generate();
+        let parameters = make_parameters(&[(threshold_id, 0.5), (hysteresis_id, 0.1)]);
+        let (trigger_tx, mut trigger_rx) = spsc::create();
+        let mut trigger = TriggerProcessor::new(threshold_id, hysteresis_id, trigger_tx);
+
+        let output = process(
+            &mut trigger,
+            &[0.0, 0.6, 0.7, 0.45, 0.6, 0.2, 0.8],
+            &parameters,
+        );
+
+        assert_eq!(output, vec![0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0]);
+        assert_eq!(trigger_rx.recv(), Ok(Timestamp::from_samples(1.0, 1000)));
+        assert_eq!(trigger_rx.recv(), Ok(Timestamp::from_samples(6.0, 1000)));
+    }
+}
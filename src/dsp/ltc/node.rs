@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+
+use lockfree::channel::spsc::{self, Receiver};
+
+use crate::{
+    commands::{command_queue::CommandQueue, id::Id},
+    graph::{dsp::Dsp, node::Node},
+    timecode::{FrameRate, Timecode},
+    timestamp::Timestamp,
+};
+
+use super::processor::{LtcGeneratorProcessor, LtcReaderProcessor, LtcReading};
+
+pub struct LtcGeneratorNode {
+    id: Id,
+    command_queue: CommandQueue,
+    start: Timecode,
+}
+
+impl LtcGeneratorNode {
+    pub fn new(command_queue: CommandQueue, start: Timecode) -> Self {
+        let id = Id::generate();
+
+        let processor = LtcGeneratorProcessor::new(start);
+        let dsp = Dsp::new(id, Box::new(processor), HashMap::new());
+
+        Dsp::add_to_audio_process(dsp, &command_queue);
+
+        Self {
+            id,
+            command_queue,
+            start,
+        }
+    }
+
+    pub fn get_start_timecode(&self) -> Timecode {
+        self.start
+    }
+
+    pub fn timecode_at(&self, time: Timestamp) -> Timecode {
+        let frames = time.to_timecode(self.start.rate).frame_count();
+        Timecode::from_frame_count(self.start.frame_count() + frames, self.start.rate)
+    }
+}
+
+pub struct LtcReaderNode {
+    id: Id,
+    command_queue: CommandQueue,
+    rate: FrameRate,
+    reading_rx: Receiver<LtcReading>,
+    last_reading: Option<LtcReading>,
+}
+
+impl LtcReaderNode {
+    pub fn new(command_queue: CommandQueue, rate: FrameRate) -> Self {
+        let id = Id::generate();
+
+        let (reading_tx, reading_rx) = spsc::create();
+
+        let processor = LtcReaderProcessor::new(rate, reading_tx);
+        let dsp = Dsp::new(id, Box::new(processor), HashMap::new());
+
+        Dsp::add_to_audio_process(dsp, &command_queue);
+
+        Self {
+            id,
+            command_queue,
+            rate,
+            reading_rx,
+            last_reading: None,
+        }
+    }
+
+    pub fn get_frame_rate(&self) -> FrameRate {
+        self.rate
+    }
+
+    pub fn take_readings(&mut self) -> Vec<LtcReading> {
+        let mut readings = Vec::new();
+
+        while let Ok(reading) = self.reading_rx.recv() {
+            readings.push(reading);
+        }
+
+        if let Some(reading) = readings.last() {
+            self.last_reading = Some(*reading);
+        }
+
+        readings
+    }
+
+    pub fn get_last_reading(&self) -> Option<LtcReading> {
+        self.last_reading
+    }
+
+    pub fn timecode_at(&self, time: Timestamp) -> Option<Timecode> {
+        let reading = self.last_reading?;
+
+        if time < reading.time {
+            return Some(reading.timecode);
+        }
+
+        let frames = (time - reading.time).to_timecode(self.rate).frame_count();
+        Some(Timecode::from_frame_count(
+            reading.timecode.frame_count() + frames,
+            self.rate,
+        ))
+    }
+}
+
+impl Node for LtcGeneratorNode {
+    fn get_id(&self) -> Id {
+        self.id
+    }
+
+    fn get_command_queue(&self) -> CommandQueue {
+        self.command_queue.clone()
+    }
+}
+
+impl Drop for LtcGeneratorNode {
+    fn drop(&mut self) {
+        Dsp::remove_from_audio_process(self.id, &self.command_queue);
+    }
+}
+
+impl Node for LtcReaderNode {
+    fn get_id(&self) -> Id {
+        self.id
+    }
+
+    fn get_command_queue(&self) -> CommandQueue {
+        self.command_queue.clone()
+    }
+}
+
+impl Drop for LtcReaderNode {
+    fn drop(&mut self) {
+        Dsp::remove_from_audio_process(self.id, &self.command_queue);
+    }
+}
@@ -0,0 +1,269 @@
+use lockfree::channel::spsc::Sender;
+
+use crate::{
+    graph::dsp::{DspParameterMap, DspProcessor},
+    timecode::{FrameRate, Timecode},
+    AudioBuffer, SampleLocation, Timestamp,
+};
+
+const BITS_PER_FRAME: u64 = 80;
+const SYNC_WORD: u128 = 0xBFFC;
+const SYNC_WORD_OFFSET: u32 = 64;
+const FRAME_MASK: u128 = (1 << BITS_PER_FRAME) - 1;
+const DROP_FRAME_BIT: u32 = 10;
+const OUTPUT_LEVEL: f32 = 0.5;
+const TRANSITION_THRESHOLD: f32 = 0.05;
+const PERIOD_SMOOTHING: f64 = 0.1;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LtcReading {
+    pub timecode: Timecode,
+    pub time: Timestamp,
+}
+
+fn encode_frame(timecode: &Timecode) -> u128 {
+    let mut bits = SYNC_WORD << SYNC_WORD_OFFSET;
+
+    for (value, offset) in [
+        (timecode.frames, 0),
+        (timecode.seconds, 16),
+        (timecode.minutes, 32),
+        (timecode.hours, 48),
+    ] {
+        bits |= ((value % 10) as u128) << offset;
+        bits |= ((value / 10) as u128) << (offset + 8);
+    }
+
+    if timecode.rate.is_drop_frame() {
+        bits |= 1 << DROP_FRAME_BIT;
+    }
+
+    bits
+}
+
+fn decode_frame(bits: u128, rate: FrameRate) -> Timecode {
+    let digit = |offset: u32, width: u32| ((bits >> offset) & ((1 << width) - 1)) as u32;
+    let field =
+        |offset: u32, tens_width: u32| digit(offset, 4) + 10 * digit(offset + 8, tens_width);
+
+    Timecode::new(field(48, 2), field(32, 3), field(16, 3), field(0, 2), rate)
+}
+
+pub struct LtcGeneratorProcessor {
+    rate: FrameRate,
+    start_frame: u64,
+    frame_index: Option<u64>,
+    frame_bits: u128,
+    bit_index: Option<u64>,
+    second_half: bool,
+    level: f32,
+}
+
+impl LtcGeneratorProcessor {
+    pub fn new(start: Timecode) -> Self {
+        Self {
+            rate: start.rate,
+            start_frame: start.frame_count(),
+            frame_index: None,
+            frame_bits: 0,
+            bit_index: None,
+            second_half: false,
+            level: OUTPUT_LEVEL,
+        }
+    }
+
+    fn advance_to(&mut self, time: &Timestamp) {
+        let position =
+            time.get_seconds().max(0.0) * self.rate.frames_per_second() * BITS_PER_FRAME as f64;
+        let bit_index = position.floor() as u64;
+
+        if self.bit_index != Some(bit_index) {
+            self.level = -self.level;
+            self.bit_index = Some(bit_index);
+            self.second_half = false;
+
+            let frame_index = bit_index / BITS_PER_FRAME;
+            if self.frame_index != Some(frame_index) {
+                let timecode =
+                    Timecode::from_frame_count(self.start_frame + frame_index, self.rate);
+                self.frame_bits = encode_frame(&timecode);
+                self.frame_index = Some(frame_index);
+            }
+        }
+
+        if position.fract() >= 0.5 && !self.second_half {
+            self.second_half = true;
+
+            if (self.frame_bits >> (bit_index % BITS_PER_FRAME)) & 1 == 1 {
+                self.level = -self.level;
+            }
+        }
+    }
+}
+
+impl DspProcessor for LtcGeneratorProcessor {
+    fn process_audio(
+        &mut self,
+        _input_buffer: &dyn AudioBuffer,
+        output_buffer: &mut dyn AudioBuffer,
+        start_time: &Timestamp,
+        _parameters: &DspParameterMap,
+    ) {
+        let sample_rate = output_buffer.sample_rate();
+
+        for frame in 0..output_buffer.num_frames() {
+            self.advance_to(&start_time.incremented_by_samples(frame, sample_rate));
+
+            for channel in 0..output_buffer.num_channels() {
+                output_buffer.set_sample(SampleLocation::new(channel, frame), self.level);
+            }
+        }
+    }
+}
+
+pub struct LtcReaderProcessor {
+    rate: FrameRate,
+    reading_tx: Sender<LtcReading>,
+    positive: bool,
+    samples_since_transition: usize,
+    bit_period: f64,
+    pending_half: bool,
+    bits: u128,
+    num_bits: u64,
+}
+
+impl LtcReaderProcessor {
+    pub fn new(rate: FrameRate, reading_tx: Sender<LtcReading>) -> Self {
+        Self {
+            rate,
+            reading_tx,
+            positive: false,
+            samples_since_transition: 0,
+            bit_period: 0.0,
+            pending_half: false,
+            bits: 0,
+            num_bits: 0,
+        }
+    }
+
+    fn handle_transition(&mut self, interval: f64, time: &Timestamp) {
+        if interval < 0.75 * self.bit_period {
+            self.bit_period += PERIOD_SMOOTHING * (2.0 * interval - self.bit_period);
+
+            if self.pending_half {
+                self.pending_half = false;
+                self.push_bit(true, time);
+            } else {
+                self.pending_half = true;
+            }
+        } else if interval < 1.5 * self.bit_period {
+            self.bit_period += PERIOD_SMOOTHING * (interval - self.bit_period);
+            self.pending_half = false;
+            self.push_bit(false, time);
+        } else {
+            self.pending_half = false;
+            self.num_bits = 0;
+        }
+    }
+
+    fn push_bit(&mut self, bit: bool, time: &Timestamp) {
+        self.bits = ((self.bits >> 1) | ((bit as u128) << (BITS_PER_FRAME - 1))) & FRAME_MASK;
+        self.num_bits = (self.num_bits + 1).min(BITS_PER_FRAME);
+
+        if self.num_bits < BITS_PER_FRAME || (self.bits >> SYNC_WORD_OFFSET) != SYNC_WORD {
+            return;
+        }
+
+        let reading = LtcReading {
+            timecode: decode_frame(self.bits, self.rate),
+            time: time.incremented_by_seconds(-1.0 / self.rate.frames_per_second()),
+        };
+
+        let _ = self.reading_tx.send(reading);
+        self.num_bits = 0;
+    }
+}
+
+impl DspProcessor for LtcReaderProcessor {
+    fn process_audio(
+        &mut self,
+        input_buffer: &dyn AudioBuffer,
+        output_buffer: &mut dyn AudioBuffer,
+        start_time: &Timestamp,
+        _parameters: &DspParameterMap,
+    ) {
+        let sample_rate = output_buffer.sample_rate();
+
+        if self.bit_period == 0.0 {
+            self.bit_period =
+                sample_rate as f64 / (self.rate.frames_per_second() * BITS_PER_FRAME as f64);
+        }
+
+        for frame in 0..output_buffer.num_frames() {
+            let sample = input_buffer.get_sample(SampleLocation::new(0, frame));
+            self.samples_since_transition += 1;
+
+            let transition = (self.positive && sample < -TRANSITION_THRESHOLD)
+                || (!self.positive && sample > TRANSITION_THRESHOLD);
+
+            if transition {
+                self.positive = !self.positive;
+
+                let interval = self.samples_since_transition as f64;
+                self.samples_since_transition = 0;
+
+                let time = start_time.incremented_by_samples(frame, sample_rate);
+                self.handle_transition(interval, &time);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lockfree::channel::spsc;
+
+    use crate::OwnedAudioBuffer;
+
+    use super::*;
+
+    const SAMPLE_RATE: usize = 48_000;
+
+    #[test]
+    fn reads_generated_timecode() {
+        let rate = FrameRate::Fps2997Drop;
+        let start = Timecode::new(0, 0, 59, 27, rate);
+
+        let mut generator = LtcGeneratorProcessor::new(start);
+        let (reading_tx, mut reading_rx) = spsc::create();
+        let mut reader = LtcReaderProcessor::new(rate, reading_tx);
+        let parameters = DspParameterMap::new();
+
+        for block in 0..20 {
+            let start_time = Timestamp::from_samples((block * 512) as f64, SAMPLE_RATE);
+            let mut ltc = OwnedAudioBuffer::new(512, 1, SAMPLE_RATE);
+            let mut output = OwnedAudioBuffer::new(512, 1, SAMPLE_RATE);
+
+            generator.process_audio(&output, &mut ltc, &start_time, &parameters);
+            reader.process_audio(&ltc, &mut output, &start_time, &parameters);
+        }
+
+        let mut readings = Vec::new();
+        while let Ok(reading) = reading_rx.recv() {
+            readings.push(reading);
+        }
+
+        assert!(readings.len() >= 6);
+        assert_eq!(readings[0].timecode, start);
+        assert_eq!(readings[3].timecode, Timecode::new(0, 1, 0, 2, rate));
+
+        for (index, reading) in readings.iter().enumerate() {
+            let expected = index as f64 / rate.frames_per_second();
+            assert!((reading.time.get_seconds() - expected).abs() < 2.0 / SAMPLE_RATE as f64);
+            assert_eq!(
+                reading.timecode.frame_count(),
+                start.frame_count() + index as u64
+            );
+        }
+    }
+}
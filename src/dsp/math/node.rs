@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+
+use crate::{
+    commands::{command_queue::CommandQueue, id::Id},
+    dsp::constant::node::{MAX_SIGNAL_VALUE, MIN_SIGNAL_VALUE},
+    graph::{dsp::Dsp, node::Node},
+    parameter::audio_parameter::AudioParameter,
+};
+
+use super::processor::{MathOperation, MathProcessor};
+
+pub struct MathNode {
+    id: Id,
+    command_queue: CommandQueue,
+    operation: MathOperation,
+    pub first: AudioParameter,
+    pub second: AudioParameter,
+}
+
+impl MathNode {
+    pub fn new(
+        command_queue: CommandQueue,
+        operation: MathOperation,
+        first_value: f64,
+        second_value: f64,
+    ) -> Self {
+        let mut parameters = HashMap::new();
+
+        let id = Id::generate();
+
+        let (first, realtime_first) = AudioParameter::new(
+            id,
+            first_value,
+            MIN_SIGNAL_VALUE,
+            MAX_SIGNAL_VALUE,
+            command_queue.clone(),
+        );
+        parameters.insert(realtime_first.get_id(), realtime_first);
+
+        let (second, realtime_second) = AudioParameter::new(
+            id,
+            second_value,
+            MIN_SIGNAL_VALUE,
+            MAX_SIGNAL_VALUE,
+            command_queue.clone(),
+        );
+        parameters.insert(realtime_second.get_id(), realtime_second);
+
+        let dsp = Dsp::new(
+            id,
+            Box::new(MathProcessor::new(
+                operation,
+                first.get_id(),
+                second.get_id(),
+            )),
+            parameters,
+        );
+
+        Dsp::add_to_audio_process(dsp, &command_queue);
+        Self {
+            id,
+            command_queue,
+            operation,
+            first,
+            second,
+        }
+    }
+
+    pub fn add(command_queue: CommandQueue, amount: f64) -> Self {
+        Self::new(command_queue, MathOperation::Add, amount, 0.0)
+    }
+
+    pub fn multiply(command_queue: CommandQueue, factor: f64) -> Self {
+        Self::new(command_queue, MathOperation::Multiply, factor, 0.0)
+    }
+
+    pub fn min(command_queue: CommandQueue, limit: f64) -> Self {
+        Self::new(command_queue, MathOperation::Min, limit, 0.0)
+    }
+
+    pub fn max(command_queue: CommandQueue, limit: f64) -> Self {
+        Self::new(command_queue, MathOperation::Max, limit, 0.0)
+    }
+
+    pub fn clamp(command_queue: CommandQueue, lower: f64, upper: f64) -> Self {
+        Self::new(command_queue, MathOperation::Clamp, lower, upper)
+    }
+
+    pub fn scale_offset(command_queue: CommandQueue, scale: f64, offset: f64) -> Self {
+        Self::new(command_queue, MathOperation::ScaleOffset, scale, offset)
+    }
+
+    pub fn get_operation(&self) -> MathOperation {
+        self.operation
+    }
+}
+
+impl Node for MathNode {
+    fn get_id(&self) -> Id {
+        self.id
+    }
+
+    fn get_command_queue(&self) -> CommandQueue {
+        self.command_queue.clone()
+    }
+}
+
+impl Drop for MathNode {
+    fn drop(&mut self) {
+        Dsp::remove_from_audio_process(self.id, &self.command_queue);
+    }
+}
@@ -0,0 +1,103 @@
+use std::time::Duration;
+
+use crate::{
+    commands::id::Id,
+    graph::dsp::{DspParameterMap, DspProcessor},
+    AudioBuffer, SampleLocation, Timestamp,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MathOperation {
+    Add,
+    Multiply,
+    Min,
+    Max,
+    Clamp,
+    ScaleOffset,
+}
+
+impl MathOperation {
+    pub fn apply(&self, input: f64, first: f64, second: f64) -> f64 {
+        match self {
+            MathOperation::Add => input + first,
+            MathOperation::Multiply => input * first,
+            MathOperation::Min => input.min(first),
+            MathOperation::Max => input.max(first),
+            MathOperation::Clamp => input.max(first.min(second)).min(second.max(first)),
+            MathOperation::ScaleOffset => input * first + second,
+        }
+    }
+}
+
+pub struct MathProcessor {
+    operation: MathOperation,
+    first_id: Id,
+    second_id: Id,
+}
+
+impl MathProcessor {
+    pub fn new(operation: MathOperation, first_id: Id, second_id: Id) -> Self {
+        Self {
+            operation,
+            first_id,
+            second_id,
+        }
+    }
+}
+
+impl DspProcessor for MathProcessor {
+    fn process_audio(
+        &mut self,
+        input_buffer: &dyn AudioBuffer,
+        output_buffer: &mut dyn AudioBuffer,
+        start_time: &Timestamp,
+        parameters: &DspParameterMap,
+    ) {
+        let sample_rate = output_buffer.sample_rate();
+
+        let (first, second) = match (
+            parameters.get(&self.first_id),
+            parameters.get(&self.second_id),
+        ) {
+            (Some(first), Some(second)) => (first, second),
+            _ => return,
+        };
+
+        for frame in 0..output_buffer.num_frames() {
+            let frame_time = start_time.incremented_by_samples(frame, sample_rate);
+            let first = first.get_value_at_time(&frame_time);
+            let second = second.get_value_at_time(&frame_time);
+
+            for channel in 0..output_buffer.num_channels() {
+                let location = SampleLocation::new(channel, frame);
+                let input = input_buffer.get_sample(location) as f64;
+                let value = self.operation.apply(input, first, second);
+                output_buffer.set_sample(location, value as f32);
+            }
+        }
+    }
+
+    fn tail_time(&self) -> Option<Duration> {
+        match self.operation {
+            MathOperation::Multiply => Some(Duration::ZERO),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn applies_operations() {
+        assert_eq!(MathOperation::Add.apply(0.5, 0.25, 0.0), 0.75);
+        assert_eq!(MathOperation::Multiply.apply(0.5, 4.0, 0.0), 2.0);
+        assert_eq!(MathOperation::Min.apply(0.5, 0.25, 0.0), 0.25);
+        assert_eq!(MathOperation::Max.apply(0.5, 0.25, 0.0), 0.5);
+        assert_eq!(MathOperation::Clamp.apply(2.0, -1.0, 1.0), 1.0);
+        assert_eq!(MathOperation::Clamp.apply(-2.0, -1.0, 1.0), -1.0);
+        assert_eq!(MathOperation::Clamp.apply(2.0, 1.0, -1.0), 1.0);
+        assert_eq!(MathOperation::ScaleOffset.apply(0.5, 100.0, 440.0), 490.0);
+    }
+}
@@ -0,0 +1,72 @@
+use std::collections::HashMap;
+
+use crate::{
+    commands::{command_queue::CommandQueue, id::Id},
+    events::note_event::NoteEventTransmitter,
+    graph::{dsp::Dsp, node::Node},
+    midi::midi_file::MidiFile,
+};
+
+use super::processor::{
+    MidiFilePlayerDspProcess, MidiFilePlayerEvent, MidiFilePlayerEventTransmitter, MAX_NOTE_OUTPUTS,
+};
+
+pub struct MidiFilePlayerNode {
+    command_queue: CommandQueue,
+    id: Id,
+    event_transmitter: MidiFilePlayerEventTransmitter,
+}
+
+impl Node for MidiFilePlayerNode {
+    fn get_id(&self) -> Id {
+        self.id
+    }
+
+    fn get_command_queue(&self) -> CommandQueue {
+        self.command_queue.clone()
+    }
+}
+
+impl MidiFilePlayerNode {
+    pub fn new(command_queue: CommandQueue, midi_file: &MidiFile) -> Self {
+        let id = Id::generate();
+
+        let (event_transmitter, event_receiver) = lockfree::channel::spsc::create();
+
+        let player_process = MidiFilePlayerDspProcess::new(midi_file, event_receiver);
+
+        let dsp = Dsp::new(id, Box::new(player_process), HashMap::new());
+
+        Dsp::add_to_audio_process(dsp, &command_queue);
+
+        Self {
+            command_queue,
+            id,
+            event_transmitter,
+        }
+    }
+
+    pub fn max_note_outputs(&self) -> usize {
+        MAX_NOTE_OUTPUTS
+    }
+
+    pub fn add_note_output(&mut self, note_input: NoteEventTransmitter) {
+        let _ = self
+            .event_transmitter
+            .send(MidiFilePlayerEvent::AddOutput(note_input));
+    }
+
+    pub fn start(&mut self) {
+        let _ = self.event_transmitter.send(MidiFilePlayerEvent::Start);
+    }
+
+    pub fn stop(&mut self) {
+        let _ = self.event_transmitter.send(MidiFilePlayerEvent::Stop);
+    }
+}
+
+impl Drop for MidiFilePlayerNode {
+    fn drop(&mut self) {
+        Dsp::remove_from_audio_process(self.id, &self.command_queue);
+    }
+}
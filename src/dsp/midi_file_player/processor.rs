@@ -0,0 +1,294 @@
+use crate::{
+    events::note_event::{NoteEvent, NoteEventTransmitter, NoteEventType, NoteId},
+    graph::{
+        dsp::{DspParameterMap, DspProcessor},
+        transport::Transport,
+    },
+    midi::{midi_file::MidiFile, midi_file_player::MidiFilePlayer},
+    utility::realtime_log::{self, LogLevel},
+    AudioBuffer, Timestamp,
+};
+
+pub type MidiFilePlayerEventReceiver = lockfree::channel::spsc::Receiver<MidiFilePlayerEvent>;
+pub type MidiFilePlayerEventTransmitter = lockfree::channel::spsc::Sender<MidiFilePlayerEvent>;
+
+pub const MAX_NOTE_OUTPUTS: usize = 16;
+const MAX_SOUNDING_NOTES: usize = 16 * 128;
+
+pub enum MidiFilePlayerEvent {
+    AddOutput(NoteEventTransmitter),
+    Start,
+    Stop,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct SequencedNote {
+    beat: f64,
+    event: NoteEvent,
+}
+
+pub struct MidiFilePlayerDspProcess {
+    notes: Vec<SequencedNote>,
+    outputs: Vec<NoteEventTransmitter>,
+    sounding: Vec<bool>,
+    transport: Transport,
+    next_beat: Option<f64>,
+    playing: bool,
+    event_receiver: MidiFilePlayerEventReceiver,
+}
+
+impl MidiFilePlayerDspProcess {
+    pub fn new(midi_file: &MidiFile, event_receiver: MidiFilePlayerEventReceiver) -> Self {
+        let mut notes: Vec<SequencedNote> = midi_file
+            .tracks()
+            .iter()
+            .flat_map(|track| track.iter())
+            .filter_map(|event| {
+                MidiFilePlayer::note_event(Timestamp::zero(), &event.message).map(|note| {
+                    SequencedNote {
+                        beat: midi_file.tick_to_beat(event.tick),
+                        event: note,
+                    }
+                })
+            })
+            .collect();
+
+        notes.sort_by(|a, b| {
+            a.beat
+                .total_cmp(&b.beat)
+                .then(Self::is_note_on(&a.event).cmp(&Self::is_note_on(&b.event)))
+        });
+
+        Self {
+            notes,
+            outputs: Vec::with_capacity(MAX_NOTE_OUTPUTS),
+            sounding: vec![false; MAX_SOUNDING_NOTES],
+            transport: Transport::default(),
+            next_beat: None,
+            playing: false,
+            event_receiver,
+        }
+    }
+
+    fn is_note_on(event: &NoteEvent) -> bool {
+        matches!(event.event_type, NoteEventType::NoteOn { .. })
+    }
+
+    fn read_events(&mut self, time: Timestamp) {
+        while let Ok(event) = self.event_receiver.recv() {
+            match event {
+                MidiFilePlayerEvent::AddOutput(output) => {
+                    if self.outputs.len() == self.outputs.capacity() {
+                        realtime_log::log(LogLevel::Warning, "MIDI file player outputs full");
+                        continue;
+                    }
+
+                    self.outputs.push(output);
+                }
+                MidiFilePlayerEvent::Start => self.playing = true,
+                MidiFilePlayerEvent::Stop => {
+                    self.release_sounding_notes(time);
+                    self.playing = false;
+                    self.next_beat = None;
+                }
+            }
+        }
+    }
+
+    fn emit(&mut self, event: NoteEvent) {
+        if let Some(sounding) = self.sounding.get_mut(event.note_id as usize) {
+            *sounding = Self::is_note_on(&event);
+        }
+
+        for output in self.outputs.iter_mut() {
+            let _ = output.send(event);
+        }
+    }
+
+    fn release_sounding_notes(&mut self, time: Timestamp) {
+        for note_id in 0..self.sounding.len() {
+            if self.sounding[note_id] {
+                self.emit(NoteEvent::note_off(time, note_id as NoteId, 0.0));
+            }
+        }
+    }
+
+    fn play_range(&mut self, from_beat: f64, to_beat: f64, time: Timestamp) {
+        let first = self.notes.partition_point(|note| note.beat < from_beat);
+
+        for index in first..self.notes.len() {
+            let note = self.notes[index];
+            if note.beat >= to_beat {
+                break;
+            }
+
+            let offset = self.transport.seconds_between(from_beat, note.beat);
+            self.emit(NoteEvent {
+                time: time.incremented_by_seconds(offset),
+                ..note.event
+            });
+        }
+    }
+}
+
+impl DspProcessor for MidiFilePlayerDspProcess {
+    fn process_audio(
+        &mut self,
+        _input_buffer: &dyn AudioBuffer,
+        output_buffer: &mut dyn AudioBuffer,
+        start_time: &Timestamp,
+        _parameters: &DspParameterMap,
+    ) {
+        let sample_rate = output_buffer.sample_rate();
+        let num_frames = output_buffer.num_frames();
+
+        self.read_events(*start_time);
+
+        if !self.playing {
+            return;
+        }
+
+        let from_beat = self.transport.beat_position;
+        let jump_tolerance = self.transport.beats_per_frame(sample_rate);
+        if self
+            .next_beat
+            .is_some_and(|next_beat| (next_beat - from_beat).abs() > jump_tolerance)
+        {
+            self.release_sounding_notes(*start_time);
+        }
+
+        let to_beat = from_beat + num_frames as f64 * self.transport.beats_per_frame(sample_rate);
+
+        match self.transport.loop_beats {
+            Some((loop_start, loop_end)) if from_beat < loop_end && to_beat > loop_end => {
+                self.play_range(from_beat, loop_end, *start_time);
+
+                let wrap_time = start_time
+                    .incremented_by_seconds(self.transport.seconds_between(from_beat, loop_end));
+                self.release_sounding_notes(wrap_time);
+                self.play_range(loop_start, loop_start + to_beat - loop_end, wrap_time);
+            }
+            _ => self.play_range(from_beat, to_beat, *start_time),
+        }
+
+        self.next_beat = Some(self.transport.beat_at_frame(num_frames, sample_rate));
+    }
+
+    fn set_transport(&mut self, transport: &Transport) {
+        self.transport = *transport;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use crate::{
+        events::note_event::NoteEventReceiver, midi::midi_file::tests::make_test_file,
+        OwnedAudioBuffer,
+    };
+
+    use super::*;
+
+    const SAMPLE_RATE: usize = 1_000;
+
+    fn make_player() -> (MidiFilePlayerDspProcess, NoteEventReceiver) {
+        let midi_file = MidiFile::from_bytes(&make_test_file()).unwrap();
+        let (mut transmitter, receiver) = lockfree::channel::spsc::create();
+        let (note_transmitter, note_receiver) = lockfree::channel::mpsc::create();
+
+        let _ = transmitter.send(MidiFilePlayerEvent::AddOutput(note_transmitter));
+        let _ = transmitter.send(MidiFilePlayerEvent::Start);
+
+        (
+            MidiFilePlayerDspProcess::new(&midi_file, receiver),
+            note_receiver,
+        )
+    }
+
+    fn process(
+        player: &mut MidiFilePlayerDspProcess,
+        transport: Transport,
+        start_frame: usize,
+        num_frames: usize,
+    ) {
+        let input = OwnedAudioBuffer::new(num_frames, 1, SAMPLE_RATE);
+        let mut output = OwnedAudioBuffer::new(num_frames, 1, SAMPLE_RATE);
+
+        player.set_transport(&transport);
+        player.process_audio(
+            &input,
+            &mut output,
+            &Timestamp::from_samples(start_frame as f64, SAMPLE_RATE),
+            &DspParameterMap::new(),
+        );
+    }
+
+    fn received(receiver: &mut NoteEventReceiver) -> Vec<NoteEvent> {
+        std::iter::from_fn(|| receiver.recv().ok()).collect()
+    }
+
+    #[test]
+    fn follows_the_transport_tempo() {
+        let (mut player, mut receiver) = make_player();
+
+        process(&mut player, Transport::new(60.0, 0.0, None), 0, 1_500);
+
+        let events = received(&mut receiver);
+        assert_eq!(events.len(), 3);
+        assert!(matches!(
+            events[1].event_type,
+            NoteEventType::NoteOff { .. }
+        ));
+        assert!(matches!(events[2].event_type, NoteEventType::NoteOn { .. }));
+        assert_relative_eq!(events[2].time.get_seconds(), 1.0, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn repeats_the_loop_and_releases_notes_at_the_seam() {
+        let (mut player, mut receiver) = make_player();
+        let loop_beats = Some((0.0, 1.5));
+
+        process(&mut player, Transport::new(120.0, 0.0, loop_beats), 0, 500);
+        process(
+            &mut player,
+            Transport::new(120.0, 1.0, loop_beats),
+            500,
+            500,
+        );
+
+        let events = received(&mut receiver);
+        let timeline: Vec<(f64, bool)> = events
+            .iter()
+            .map(|event| {
+                (
+                    event.time.get_seconds(),
+                    matches!(event.event_type, NoteEventType::NoteOn { .. }),
+                )
+            })
+            .collect();
+
+        assert_eq!(events.len(), 5);
+        assert_eq!(timeline[3], (0.75, false));
+        assert_eq!(events[3].note_id, 64);
+        assert_eq!(timeline[4], (0.75, true));
+        assert_eq!(events[4].note_id, 60);
+    }
+
+    #[test]
+    fn releases_sounding_notes_when_the_transport_jumps() {
+        let (mut player, mut receiver) = make_player();
+
+        process(&mut player, Transport::new(120.0, 0.0, None), 0, 100);
+        process(&mut player, Transport::new(120.0, 1.5, None), 100, 100);
+
+        let events = received(&mut receiver);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1].note_id, 60);
+        assert!(matches!(
+            events[1].event_type,
+            NoteEventType::NoteOff { .. }
+        ));
+        assert_relative_eq!(events[1].time.get_seconds(), 0.1, epsilon = 1e-9);
+    }
+}
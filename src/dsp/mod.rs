@@ -1,3 +1,26 @@
+pub mod ambience;
+pub mod amp_sim;
+pub mod channel_adapter;
+pub mod clip_player;
+pub mod compressor;
+pub mod constant;
+pub mod convolver;
+pub mod de_esser;
+pub mod delay;
+pub mod denoise;
+pub mod dynamic_eq;
+pub mod emitter;
+pub mod envelope;
 pub mod gain;
+pub mod leveler;
+pub mod logic;
+pub mod ltc;
+pub mod math;
+pub mod midi_file_player;
+pub mod monitor;
+pub mod music;
 pub mod oscillator;
+pub mod random_source;
+pub mod recorder;
 pub mod sampler;
+pub mod subgraph;
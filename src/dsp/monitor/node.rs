@@ -0,0 +1,82 @@
+use std::collections::HashMap;
+
+use lockfree::channel::spsc;
+
+use crate::{
+    commands::{command_queue::CommandQueue, id::Id},
+    graph::{dsp::Dsp, node::Node},
+};
+
+use super::processor::{
+    MonitorCommand, MonitorCommandTransmitter, MonitorControllerProcessor, MonitorOutput,
+    MonitorState,
+};
+
+pub struct MonitorControllerNode {
+    id: Id,
+    command_queue: CommandQueue,
+    command_transmitter: MonitorCommandTransmitter,
+    state: MonitorState,
+}
+
+impl MonitorControllerNode {
+    pub fn new(command_queue: CommandQueue) -> Self {
+        let id = Id::generate();
+
+        let (command_transmitter, command_receiver) = spsc::create();
+
+        let processor = MonitorControllerProcessor::new(command_receiver);
+
+        let dsp = Dsp::new(id, Box::new(processor), HashMap::new());
+
+        Dsp::add_to_audio_process(dsp, &command_queue);
+
+        Self {
+            id,
+            command_queue,
+            command_transmitter,
+            state: MonitorState::default(),
+        }
+    }
+
+    pub fn send(&mut self, command: MonitorCommand) {
+        self.state.apply(command);
+        let _ = self.command_transmitter.send(command);
+    }
+
+    pub fn get_state(&self) -> MonitorState {
+        self.state
+    }
+
+    pub fn select_output(&mut self, output: MonitorOutput) {
+        self.send(MonitorCommand::SelectOutput(output));
+    }
+
+    pub fn toggle_dim(&mut self) {
+        self.send(MonitorCommand::Dim(!self.state.dim));
+    }
+
+    pub fn toggle_mono_sum(&mut self) {
+        self.send(MonitorCommand::MonoSum(!self.state.mono_sum));
+    }
+
+    pub fn toggle_mute(&mut self) {
+        self.send(MonitorCommand::Mute(!self.state.mute));
+    }
+}
+
+impl Node for MonitorControllerNode {
+    fn get_id(&self) -> Id {
+        self.id
+    }
+
+    fn get_command_queue(&self) -> CommandQueue {
+        self.command_queue.clone()
+    }
+}
+
+impl Drop for MonitorControllerNode {
+    fn drop(&mut self) {
+        Dsp::remove_from_audio_process(self.id, &self.command_queue);
+    }
+}
@@ -0,0 +1,271 @@
+use lockfree::channel::spsc::{Receiver, Sender};
+
+use crate::{
+    graph::dsp::{DspParameterMap, DspProcessor},
+    utility::level::Level,
+    AudioBuffer, SampleLocation, Timestamp,
+};
+
+pub type MonitorCommandReceiver = Receiver<MonitorCommand>;
+pub type MonitorCommandTransmitter = Sender<MonitorCommand>;
+
+const RAMP_SECONDS: f64 = 0.01;
+const CHANNELS_PER_OUTPUT: usize = 2;
+pub const DEFAULT_DIM_LEVEL_DB: f64 = -20.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MonitorOutput {
+    Main,
+    Alt,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MonitorCommand {
+    SelectOutput(MonitorOutput),
+    Dim(bool),
+    Talkback(bool),
+    MonoSum(bool),
+    Mute(bool),
+    SetDimLevel(f64),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MonitorState {
+    pub output: MonitorOutput,
+    pub dim: bool,
+    pub talkback: bool,
+    pub mono_sum: bool,
+    pub mute: bool,
+    pub dim_level_db: f64,
+}
+
+impl Default for MonitorState {
+    fn default() -> Self {
+        Self {
+            output: MonitorOutput::Main,
+            dim: false,
+            talkback: false,
+            mono_sum: false,
+            mute: false,
+            dim_level_db: DEFAULT_DIM_LEVEL_DB,
+        }
+    }
+}
+
+impl MonitorState {
+    pub fn apply(&mut self, command: MonitorCommand) {
+        match command {
+            MonitorCommand::SelectOutput(output) => self.output = output,
+            MonitorCommand::Dim(dim) => self.dim = dim,
+            MonitorCommand::Talkback(talkback) => self.talkback = talkback,
+            MonitorCommand::MonoSum(mono_sum) => self.mono_sum = mono_sum,
+            MonitorCommand::Mute(mute) => self.mute = mute,
+            MonitorCommand::SetDimLevel(level_db) => self.dim_level_db = level_db.min(0.0),
+        }
+    }
+
+    fn gain(&self) -> f32 {
+        if self.mute {
+            0.0
+        } else if self.dim || self.talkback {
+            Level::from_db(self.dim_level_db).as_gain() as f32
+        } else {
+            1.0
+        }
+    }
+
+    fn output_gain(&self, output: MonitorOutput) -> f32 {
+        if self.output == output {
+            self.gain()
+        } else {
+            0.0
+        }
+    }
+
+    fn mono_amount(&self) -> f32 {
+        if self.mono_sum {
+            1.0
+        } else {
+            0.0
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Ramp {
+    current: f32,
+    target: f32,
+    step: f32,
+}
+
+impl Ramp {
+    fn new(value: f32) -> Self {
+        Self {
+            current: value,
+            target: value,
+            step: 0.0,
+        }
+    }
+
+    fn set_target(&mut self, target: f32, num_frames: usize) {
+        self.target = target;
+        self.step = (target - self.current) / num_frames.max(1) as f32;
+    }
+
+    fn next(&mut self) -> f32 {
+        if self.current != self.target {
+            let next = self.current + self.step;
+            let overshot = (self.step > 0.0 && next >= self.target)
+                || (self.step < 0.0 && next <= self.target)
+                || self.step == 0.0;
+            self.current = if overshot { self.target } else { next };
+        }
+
+        self.current
+    }
+}
+
+pub struct MonitorControllerProcessor {
+    command_receiver: MonitorCommandReceiver,
+    state: MonitorState,
+    main_gain: Ramp,
+    alt_gain: Ramp,
+    mono_amount: Ramp,
+}
+
+impl MonitorControllerProcessor {
+    pub fn new(command_receiver: MonitorCommandReceiver) -> Self {
+        let state = MonitorState::default();
+
+        Self {
+            command_receiver,
+            main_gain: Ramp::new(state.output_gain(MonitorOutput::Main)),
+            alt_gain: Ramp::new(state.output_gain(MonitorOutput::Alt)),
+            mono_amount: Ramp::new(state.mono_amount()),
+            state,
+        }
+    }
+
+    fn read_commands(&mut self, sample_rate: usize) {
+        let mut changed = false;
+
+        while let Ok(command) = self.command_receiver.recv() {
+            self.state.apply(command);
+            changed = true;
+        }
+
+        if changed {
+            let ramp_frames = (RAMP_SECONDS * sample_rate as f64) as usize;
+            self.main_gain
+                .set_target(self.state.output_gain(MonitorOutput::Main), ramp_frames);
+            self.alt_gain
+                .set_target(self.state.output_gain(MonitorOutput::Alt), ramp_frames);
+            self.mono_amount
+                .set_target(self.state.mono_amount(), ramp_frames);
+        }
+    }
+}
+
+impl DspProcessor for MonitorControllerProcessor {
+    fn process_audio(
+        &mut self,
+        input_buffer: &dyn AudioBuffer,
+        output_buffer: &mut dyn AudioBuffer,
+        _start_time: &Timestamp,
+        _parameters: &DspParameterMap,
+    ) {
+        self.read_commands(output_buffer.sample_rate());
+
+        let num_channels = output_buffer.num_channels();
+        let last_input_channel = input_buffer.num_channels().saturating_sub(1);
+        output_buffer.clear();
+
+        for frame in 0..output_buffer.num_frames() {
+            let left = input_buffer.get_sample(SampleLocation::new(0, frame));
+            let right =
+                input_buffer.get_sample(SampleLocation::new(last_input_channel.min(1), frame));
+
+            let mono_amount = self.mono_amount.next();
+            let mono = 0.5 * (left + right);
+            let pair = [
+                left + mono_amount * (mono - left),
+                right + mono_amount * (mono - right),
+            ];
+
+            let gains = [self.main_gain.next(), self.alt_gain.next()];
+
+            for (output_index, gain) in gains.iter().enumerate() {
+                for (pair_channel, value) in pair.iter().enumerate() {
+                    let channel = output_index * CHANNELS_PER_OUTPUT + pair_channel;
+                    if channel < num_channels {
+                        output_buffer.set_sample(SampleLocation::new(channel, frame), value * gain);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lockfree::channel::spsc;
+
+    use crate::{AudioBufferSlice, OwnedAudioBuffer};
+
+    use super::*;
+
+    const SAMPLE_RATE: usize = 1000;
+    const NUM_FRAMES: usize = 32;
+
+    fn render(processor: &mut MonitorControllerProcessor) -> OwnedAudioBuffer {
+        let mut input = OwnedAudioBuffer::new(NUM_FRAMES, 4, SAMPLE_RATE);
+        for frame in 0..NUM_FRAMES {
+            input.set_sample(SampleLocation::new(0, frame), 1.0);
+            input.set_sample(SampleLocation::new(1, frame), 0.5);
+        }
+
+        let mut output = OwnedAudioBuffer::new(NUM_FRAMES, 4, SAMPLE_RATE);
+        processor.process_audio(
+            &input,
+            &mut AudioBufferSlice::new(&mut output, 0, NUM_FRAMES),
+            &Timestamp::zero(),
+            &DspParameterMap::new(),
+        );
+        output
+    }
+
+    fn last_frame(output: &OwnedAudioBuffer) -> Vec<f32> {
+        (0..4)
+            .map(|channel| output.get_sample(SampleLocation::new(channel, NUM_FRAMES - 1)))
+            .collect()
+    }
+
+    #[test]
+    fn routes_dims_and_sums_to_mono() {
+        let (mut commands, command_receiver) = spsc::create();
+        let mut processor = MonitorControllerProcessor::new(command_receiver);
+
+        assert_eq!(
+            last_frame(&render(&mut processor)),
+            vec![1.0, 0.5, 0.0, 0.0]
+        );
+
+        let _ = commands.send(MonitorCommand::SelectOutput(MonitorOutput::Alt));
+        let _ = commands.send(MonitorCommand::MonoSum(true));
+        let crossfade = render(&mut processor);
+        assert!(crossfade.get_sample(SampleLocation::new(0, 0)) < 1.0);
+        assert!(crossfade.get_sample(SampleLocation::new(0, 0)) > 0.5);
+        assert_eq!(last_frame(&crossfade), vec![0.0, 0.0, 0.75, 0.75]);
+
+        let _ = commands.send(MonitorCommand::SetDimLevel(-6.0));
+        let _ = commands.send(MonitorCommand::Talkback(true));
+        render(&mut processor);
+        let dimmed = Level::from_db(-6.0).as_gain() as f32 * 0.75;
+        let output = last_frame(&render(&mut processor));
+        assert!((output[2] - dimmed).abs() < 1e-6);
+
+        let _ = commands.send(MonitorCommand::Mute(true));
+        render(&mut processor);
+        assert_eq!(last_frame(&render(&mut processor)), vec![0.0; 4]);
+    }
+}
@@ -0,0 +1,3 @@
+pub mod node;
+pub mod processor;
+pub mod track;
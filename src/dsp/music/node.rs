@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+
+use crate::{
+    commands::{command_queue::CommandQueue, id::Id},
+    graph::{dsp::Dsp, node::Node},
+};
+
+use super::{
+    processor::{MusicEvent, MusicEventTransmitter, MusicPlayerProcessor},
+    track::{MusicTrack, TransitionPoint, TransitionType},
+};
+
+pub struct MusicPlayerNode {
+    command_queue: CommandQueue,
+    id: Id,
+    event_transmitter: MusicEventTransmitter,
+}
+
+impl Node for MusicPlayerNode {
+    fn get_id(&self) -> Id {
+        self.id
+    }
+
+    fn get_command_queue(&self) -> CommandQueue {
+        self.command_queue.clone()
+    }
+}
+
+impl MusicPlayerNode {
+    pub fn new(command_queue: CommandQueue) -> Self {
+        let id = Id::generate();
+
+        let (event_transmitter, event_receiver) = lockfree::channel::spsc::create();
+
+        let dsp = Dsp::new(
+            id,
+            Box::new(MusicPlayerProcessor::new(event_receiver)),
+            HashMap::new(),
+        );
+
+        Dsp::add_to_audio_process(dsp, &command_queue);
+
+        Self {
+            command_queue,
+            id,
+            event_transmitter,
+        }
+    }
+
+    pub fn set_track(&mut self, index: usize, track: MusicTrack) {
+        let _ = self
+            .event_transmitter
+            .send(MusicEvent::SetTrack(index, Some(track)));
+    }
+
+    pub fn remove_track(&mut self, index: usize) {
+        let _ = self
+            .event_transmitter
+            .send(MusicEvent::SetTrack(index, None));
+    }
+
+    pub fn play(&mut self, index: usize) {
+        self.transition_to(index, TransitionPoint::Immediate, TransitionType::Cut);
+    }
+
+    pub fn transition_to(
+        &mut self,
+        index: usize,
+        point: TransitionPoint,
+        transition_type: TransitionType,
+    ) {
+        let _ = self.event_transmitter.send(MusicEvent::Transition {
+            track: Some(index),
+            point,
+            transition_type,
+        });
+    }
+
+    pub fn stop(&mut self, point: TransitionPoint, transition_type: TransitionType) {
+        let _ = self.event_transmitter.send(MusicEvent::Transition {
+            track: None,
+            point,
+            transition_type,
+        });
+    }
+}
+
+impl Drop for MusicPlayerNode {
+    fn drop(&mut self) {
+        Dsp::remove_from_audio_process(self.id, &self.command_queue);
+    }
+}
@@ -0,0 +1,413 @@
+use std::f64::consts::FRAC_PI_2;
+
+use crate::{
+    dsp::sampler::processor::SharedSample,
+    graph::dsp::{DspParameterMap, DspProcessor},
+    utility::fast_math,
+    AudioBuffer, SampleLocation, Timestamp,
+};
+
+use super::track::{MusicTrack, TransitionPoint, TransitionType};
+
+pub type MusicEventReceiver = lockfree::channel::spsc::Receiver<MusicEvent>;
+pub type MusicEventTransmitter = lockfree::channel::spsc::Sender<MusicEvent>;
+
+pub const MAX_MUSIC_TRACKS: usize = 32;
+
+pub enum MusicEvent {
+    SetTrack(usize, Option<MusicTrack>),
+    Transition {
+        track: Option<usize>,
+        point: TransitionPoint,
+        transition_type: TransitionType,
+    },
+}
+
+#[derive(Clone, Copy)]
+struct PlayingTrack {
+    index: usize,
+    position: usize,
+}
+
+struct Fade {
+    position: usize,
+    length: usize,
+}
+
+impl Fade {
+    fn amount(&self) -> f64 {
+        (self.position as f64 / self.length as f64).min(1.0) * FRAC_PI_2
+    }
+
+    fn is_finished(&self) -> bool {
+        self.position >= self.length
+    }
+}
+
+struct PendingTransition {
+    at_frame: usize,
+    track: Option<usize>,
+    transition_type: TransitionType,
+}
+
+pub struct MusicPlayerProcessor {
+    tracks: Vec<Option<MusicTrack>>,
+    current: Option<PlayingTrack>,
+    outgoing: Option<PlayingTrack>,
+    fade: Option<Fade>,
+    stinger: Option<(SharedSample, usize)>,
+    pending: Option<PendingTransition>,
+    event_receiver: MusicEventReceiver,
+}
+
+impl MusicPlayerProcessor {
+    pub fn new(event_receiver: MusicEventReceiver) -> Self {
+        fast_math::initialise();
+
+        Self {
+            tracks: vec![None; MAX_MUSIC_TRACKS],
+            current: None,
+            outgoing: None,
+            fade: None,
+            stinger: None,
+            pending: None,
+            event_receiver,
+        }
+    }
+
+    fn track(&self, index: usize) -> Option<&MusicTrack> {
+        self.tracks.get(index).and_then(Option::as_ref)
+    }
+
+    fn read_events(&mut self) {
+        while let Ok(event) = self.event_receiver.recv() {
+            match event {
+                MusicEvent::SetTrack(index, track) => {
+                    if index >= self.tracks.len() {
+                        continue;
+                    }
+
+                    if self.current.map(|playing| playing.index) == Some(index) {
+                        self.current = None;
+                    }
+
+                    if self.outgoing.map(|playing| playing.index) == Some(index) {
+                        self.outgoing = None;
+                        self.fade = None;
+                    }
+
+                    self.tracks[index] = track;
+                }
+                MusicEvent::Transition {
+                    track,
+                    point,
+                    transition_type,
+                } => {
+                    let at_frame = match self.current {
+                        Some(playing) => self
+                            .track(playing.index)
+                            .map(|current| current.next_transition_frame(point, playing.position))
+                            .unwrap_or(0),
+                        None => 0,
+                    };
+
+                    self.pending = Some(PendingTransition {
+                        at_frame,
+                        track,
+                        transition_type,
+                    });
+                }
+            }
+        }
+    }
+
+    fn apply_pending_transition(&mut self) {
+        let is_due = match (&self.pending, self.current) {
+            (Some(pending), Some(playing)) => playing.position >= pending.at_frame,
+            (Some(_), None) => true,
+            (None, _) => false,
+        };
+
+        if !is_due {
+            return;
+        }
+
+        let pending = match self.pending.take() {
+            Some(pending) => pending,
+            None => return,
+        };
+
+        let next = pending
+            .track
+            .filter(|index| self.track(*index).is_some())
+            .map(|index| PlayingTrack { index, position: 0 });
+
+        match pending.transition_type {
+            TransitionType::Cut => {
+                self.outgoing = None;
+                self.fade = None;
+            }
+            TransitionType::Crossfade(duration) => {
+                let sample_rate = self
+                    .current
+                    .and_then(|playing| self.track(playing.index))
+                    .map(|track| track.sample().sample_rate())
+                    .unwrap_or(0);
+                let length = (duration.as_secs_f64() * sample_rate as f64) as usize;
+
+                if length > 0 && self.current.is_some() {
+                    self.outgoing = self.current;
+                    self.fade = Some(Fade {
+                        position: 0,
+                        length,
+                    });
+                } else {
+                    self.outgoing = None;
+                    self.fade = None;
+                }
+            }
+            TransitionType::Stinger(sample) => {
+                self.outgoing = None;
+                self.fade = None;
+                self.stinger = Some((sample, 0));
+            }
+        }
+
+        self.current = next;
+    }
+
+    fn wrap_or_finish(tracks: &[Option<MusicTrack>], playing: &mut Option<PlayingTrack>) {
+        let finished = match playing.as_mut() {
+            Some(current) => match &tracks[current.index] {
+                Some(track) if current.position < track.len() => false,
+                Some(track) if track.is_looping() && !track.is_empty() => {
+                    current.position = 0;
+                    false
+                }
+                _ => true,
+            },
+            None => false,
+        };
+
+        if finished {
+            *playing = None;
+        }
+    }
+
+    fn render_track(
+        tracks: &[Option<MusicTrack>],
+        playing: &mut Option<PlayingTrack>,
+        output_buffer: &mut dyn AudioBuffer,
+        frame: usize,
+        gain: f64,
+    ) {
+        Self::wrap_or_finish(tracks, playing);
+
+        let current = match playing.as_mut() {
+            Some(current) => current,
+            None => return,
+        };
+
+        if let Some(track) = &tracks[current.index] {
+            render_sample(track.sample(), current.position, output_buffer, frame, gain);
+        }
+
+        current.position += 1;
+    }
+
+    fn render_frame(&mut self, output_buffer: &mut dyn AudioBuffer, frame: usize) {
+        let (fade_in, fade_out) = match &self.fade {
+            Some(fade) => (fast_math::sin(fade.amount()), fast_math::cos(fade.amount())),
+            None => (1.0, 0.0),
+        };
+
+        Self::render_track(
+            &self.tracks,
+            &mut self.current,
+            output_buffer,
+            frame,
+            fade_in,
+        );
+
+        if self.fade.is_some() {
+            Self::render_track(
+                &self.tracks,
+                &mut self.outgoing,
+                output_buffer,
+                frame,
+                fade_out,
+            );
+        }
+
+        if let Some(fade) = self.fade.as_mut() {
+            fade.position += 1;
+            if fade.is_finished() {
+                self.fade = None;
+                self.outgoing = None;
+            }
+        }
+
+        if let Some((sample, position)) = self.stinger.as_mut() {
+            if *position < sample.num_frames() {
+                render_sample(sample, *position, output_buffer, frame, 1.0);
+                *position += 1;
+            } else {
+                self.stinger = None;
+            }
+        }
+    }
+}
+
+fn render_sample(
+    sample: &SharedSample,
+    position: usize,
+    output_buffer: &mut dyn AudioBuffer,
+    frame: usize,
+    gain: f64,
+) {
+    let num_channels = sample.num_channels().min(output_buffer.num_channels());
+
+    for channel in 0..num_channels {
+        let value = sample.get_sample(SampleLocation::new(channel, position));
+        output_buffer.add_sample(SampleLocation::new(channel, frame), value * gain as f32);
+    }
+}
+
+impl DspProcessor for MusicPlayerProcessor {
+    fn process_audio(
+        &mut self,
+        _input_buffer: &dyn AudioBuffer,
+        output_buffer: &mut dyn AudioBuffer,
+        _start_time: &Timestamp,
+        _parameters: &DspParameterMap,
+    ) {
+        self.read_events();
+
+        for frame in 0..output_buffer.num_frames() {
+            self.apply_pending_transition();
+            self.render_frame(output_buffer, frame);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use approx::assert_relative_eq;
+
+    use crate::{OwnedAudioBuffer, TempoMap};
+
+    use super::*;
+
+    fn make_track(value: f32) -> Option<MusicTrack> {
+        let mut sample = OwnedAudioBuffer::new(400, 1, 100);
+        sample.fill_with_value(value);
+        Some(MusicTrack::new(Arc::new(sample), TempoMap::new(120.0), 4.0))
+    }
+
+    fn process(player: &mut MusicPlayerProcessor) -> OwnedAudioBuffer {
+        let input_buffer = OwnedAudioBuffer::new(50, 1, 100);
+        let mut output_buffer = OwnedAudioBuffer::new(50, 1, 100);
+        player.process_audio(
+            &input_buffer,
+            &mut output_buffer,
+            &Timestamp::zero(),
+            &DspParameterMap::new(),
+        );
+        output_buffer
+    }
+
+    fn transition(
+        transmitter: &mut MusicEventTransmitter,
+        track: usize,
+        point: TransitionPoint,
+        transition_type: TransitionType,
+    ) {
+        let _ = transmitter.send(MusicEvent::Transition {
+            track: Some(track),
+            point,
+            transition_type,
+        });
+    }
+
+    #[test]
+    fn cuts_on_next_bar() {
+        let (mut event_transmitter, event_receiver) = lockfree::channel::spsc::create();
+        let mut player = MusicPlayerProcessor::new(event_receiver);
+
+        let _ = event_transmitter.send(MusicEvent::SetTrack(0, make_track(1.0)));
+        let _ = event_transmitter.send(MusicEvent::SetTrack(1, make_track(2.0)));
+        transition(
+            &mut event_transmitter,
+            0,
+            TransitionPoint::Immediate,
+            TransitionType::Cut,
+        );
+
+        let output = process(&mut player);
+        assert_eq!(output.get_sample(SampleLocation::new(0, 0)), 1.0);
+
+        transition(
+            &mut event_transmitter,
+            1,
+            TransitionPoint::NextBar,
+            TransitionType::Cut,
+        );
+
+        for _ in 0..3 {
+            let output = process(&mut player);
+            assert_eq!(output.get_sample(SampleLocation::new(0, 49)), 1.0);
+        }
+
+        let output = process(&mut player);
+        assert_eq!(output.get_sample(SampleLocation::new(0, 0)), 2.0);
+    }
+
+    #[test]
+    fn crossfades_and_overlays_stinger() {
+        let (mut event_transmitter, event_receiver) = lockfree::channel::spsc::create();
+        let mut player = MusicPlayerProcessor::new(event_receiver);
+
+        let _ = event_transmitter.send(MusicEvent::SetTrack(0, make_track(1.0)));
+        let _ = event_transmitter.send(MusicEvent::SetTrack(1, make_track(1.0)));
+        transition(
+            &mut event_transmitter,
+            0,
+            TransitionPoint::Immediate,
+            TransitionType::Cut,
+        );
+        process(&mut player);
+
+        transition(
+            &mut event_transmitter,
+            1,
+            TransitionPoint::NextBeat,
+            TransitionType::Crossfade(Duration::from_millis(500)),
+        );
+
+        let output = process(&mut player);
+        let middle = output.get_sample(SampleLocation::new(0, 25));
+        assert_relative_eq!(middle, 2.0_f32.sqrt(), epsilon = 0.05);
+
+        let output = process(&mut player);
+        assert_relative_eq!(
+            output.get_sample(SampleLocation::new(0, 49)),
+            1.0,
+            epsilon = 1e-6
+        );
+
+        let mut stinger = OwnedAudioBuffer::new(10, 1, 100);
+        stinger.fill_with_value(0.5);
+        transition(
+            &mut event_transmitter,
+            0,
+            TransitionPoint::Immediate,
+            TransitionType::Stinger(Arc::new(stinger)),
+        );
+
+        let output = process(&mut player);
+        assert_relative_eq!(output.get_sample(SampleLocation::new(0, 0)), 1.5);
+        assert_relative_eq!(output.get_sample(SampleLocation::new(0, 10)), 1.0);
+    }
+}
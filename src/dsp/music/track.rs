@@ -0,0 +1,136 @@
+use crate::{dsp::sampler::processor::SharedSample, tempo_map::TempoMap};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TransitionPoint {
+    Immediate,
+    NextBeat,
+    NextBar,
+    NextMarker,
+    End,
+}
+
+#[derive(Clone)]
+pub enum TransitionType {
+    Cut,
+    Crossfade(std::time::Duration),
+    Stinger(SharedSample),
+}
+
+#[derive(Clone)]
+pub struct MusicTrack {
+    sample: SharedSample,
+    tempo_map: TempoMap,
+    beats_per_bar: f64,
+    markers: Vec<usize>,
+    looping: bool,
+}
+
+impl MusicTrack {
+    pub fn new(sample: SharedSample, tempo_map: TempoMap, beats_per_bar: f64) -> Self {
+        Self {
+            sample,
+            tempo_map,
+            beats_per_bar: beats_per_bar.max(1.0),
+            markers: Vec::new(),
+            looping: true,
+        }
+    }
+
+    pub fn with_marker(mut self, position_in_seconds: f64) -> Self {
+        let frame = self.seconds_to_frame(position_in_seconds);
+        if let Err(index) = self.markers.binary_search(&frame) {
+            self.markers.insert(index, frame);
+        }
+        self
+    }
+
+    pub fn with_looping(mut self, looping: bool) -> Self {
+        self.looping = looping;
+        self
+    }
+
+    pub fn sample(&self) -> &SharedSample {
+        &self.sample
+    }
+
+    pub fn is_looping(&self) -> bool {
+        self.looping
+    }
+
+    pub fn len(&self) -> usize {
+        self.sample.num_frames()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn next_transition_frame(&self, point: TransitionPoint, position: usize) -> usize {
+        let frame = match point {
+            TransitionPoint::Immediate => position,
+            TransitionPoint::NextBeat => self.next_grid_frame(position, 1.0),
+            TransitionPoint::NextBar => self.next_grid_frame(position, self.beats_per_bar),
+            TransitionPoint::NextMarker => self
+                .markers
+                .iter()
+                .copied()
+                .find(|marker| *marker >= position)
+                .unwrap_or_else(|| self.len()),
+            TransitionPoint::End => self.len(),
+        };
+
+        frame.min(self.len())
+    }
+
+    fn next_grid_frame(&self, position: usize, grid_in_beats: f64) -> usize {
+        let seconds = position as f64 / self.sample.sample_rate() as f64;
+        let beat = self.tempo_map.beat_at_seconds(seconds);
+        let boundary = (beat / grid_in_beats - 1e-9).ceil() * grid_in_beats;
+        self.seconds_to_frame(self.tempo_map.seconds_at_beat(boundary))
+    }
+
+    fn seconds_to_frame(&self, seconds: f64) -> usize {
+        (seconds.max(0.0) * self.sample.sample_rate() as f64).round() as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::OwnedAudioBuffer;
+
+    use super::*;
+
+    #[test]
+    fn finds_musical_transition_points() {
+        let sample = Arc::new(OwnedAudioBuffer::new(1000, 1, 100));
+        let track = MusicTrack::new(sample, TempoMap::new(120.0), 4.0).with_marker(3.3);
+
+        assert_eq!(
+            track.next_transition_frame(TransitionPoint::Immediate, 12),
+            12
+        );
+        assert_eq!(
+            track.next_transition_frame(TransitionPoint::NextBeat, 12),
+            50
+        );
+        assert_eq!(
+            track.next_transition_frame(TransitionPoint::NextBeat, 50),
+            50
+        );
+        assert_eq!(
+            track.next_transition_frame(TransitionPoint::NextBar, 12),
+            200
+        );
+        assert_eq!(
+            track.next_transition_frame(TransitionPoint::NextMarker, 12),
+            330
+        );
+        assert_eq!(
+            track.next_transition_frame(TransitionPoint::NextMarker, 400),
+            1000
+        );
+        assert_eq!(track.next_transition_frame(TransitionPoint::End, 12), 1000);
+    }
+}
@@ -1,2 +1,4 @@
 pub mod node;
 mod processor;
+pub mod waveform;
+pub mod wavetable;
@@ -1,18 +1,27 @@
-use std::collections::HashMap;
-
-use lockfree::channel::mpsc::Sender;
+use std::{collections::HashMap, sync::Arc};
 
 use crate::{
-    commands::{command::Command, id::Id},
+    commands::{command_queue::CommandQueue, id::Id},
     graph::{dsp::Dsp, node::Node},
-    parameter::audio_parameter::AudioParameter,
+    parameter::{
+        audio_parameter::AudioParameter,
+        preset::{NodePreset, PresetError, PresetNode},
+    },
 };
 
-use super::processor::OscillatorDspProcess;
+use super::{
+    processor::{OscillatorDspProcess, OscillatorEvent, OscillatorEventTransmitter},
+    waveform::Waveform,
+    wavetable::{sine_wavetable, SharedWavetable},
+};
 
 pub struct OscillatorNode {
-    command_queue: Sender<Command>,
+    command_queue: CommandQueue,
     id: Id,
+    event_transmitter: OscillatorEventTransmitter,
+    wavetable: SharedWavetable,
+    waveform: Waveform,
+    retired_wavetables: Vec<SharedWavetable>,
     pub frequency: AudioParameter,
     pub gain: AudioParameter,
 }
@@ -22,7 +31,7 @@ impl Node for OscillatorNode {
         self.id
     }
 
-    fn get_command_queue(&self) -> Sender<Command> {
+    fn get_command_queue(&self) -> CommandQueue {
         self.command_queue.clone()
     }
 }
@@ -33,7 +42,28 @@ const MIN_FREQUENCY: f64 = 20.0;
 const MAX_FREQUENCY: f64 = 20000.0;
 
 impl OscillatorNode {
-    pub fn new(command_queue: Sender<Command>, frequency: f64) -> Self {
+    pub fn new(command_queue: CommandQueue, frequency: f64) -> Self {
+        Self::with_wavetable(command_queue, frequency, sine_wavetable())
+    }
+
+    pub fn with_waveform(command_queue: CommandQueue, frequency: f64, waveform: Waveform) -> Self {
+        Self::create(command_queue, frequency, sine_wavetable(), waveform)
+    }
+
+    pub fn with_wavetable(
+        command_queue: CommandQueue,
+        frequency: f64,
+        wavetable: SharedWavetable,
+    ) -> Self {
+        Self::create(command_queue, frequency, wavetable, Waveform::Wavetable)
+    }
+
+    fn create(
+        command_queue: CommandQueue,
+        frequency: f64,
+        wavetable: SharedWavetable,
+        waveform: Waveform,
+    ) -> Self {
         let id = Id::generate();
 
         let mut parameters = HashMap::new();
@@ -50,21 +80,63 @@ impl OscillatorNode {
             AudioParameter::new(id, 1.0, MIN_GAIN, MAX_GAIN, command_queue.clone());
         parameters.insert(realtime_gain.get_id(), realtime_gain);
 
-        let dsp = Dsp::new(
-            id,
-            Box::new(OscillatorDspProcess::new(frequency.get_id(), gain.get_id())),
-            parameters,
+        let (event_transmitter, event_receiver) = lockfree::channel::spsc::create();
+
+        let processor = OscillatorDspProcess::new(
+            frequency.get_id(),
+            gain.get_id(),
+            wavetable.clone(),
+            waveform,
+            event_receiver,
         );
 
+        let dsp = Dsp::new(id, Box::new(processor), parameters);
+
         Dsp::add_to_audio_process(dsp, &command_queue);
 
         Self {
             command_queue,
             id,
+            event_transmitter,
+            wavetable,
+            waveform,
+            retired_wavetables: Vec::new(),
             frequency,
             gain,
         }
     }
+
+    pub fn set_wavetable(&mut self, wavetable: SharedWavetable) {
+        let previous = std::mem::replace(&mut self.wavetable, wavetable.clone());
+        self.retired_wavetables.push(previous);
+
+        let _ = self
+            .event_transmitter
+            .send(OscillatorEvent::SetWavetable(wavetable));
+
+        self.collect_garbage();
+    }
+
+    pub fn get_wavetable(&self) -> SharedWavetable {
+        self.wavetable.clone()
+    }
+
+    pub fn set_waveform(&mut self, waveform: Waveform) {
+        self.waveform = waveform;
+
+        let _ = self
+            .event_transmitter
+            .send(OscillatorEvent::SetWaveform(waveform));
+    }
+
+    pub fn get_waveform(&self) -> Waveform {
+        self.waveform
+    }
+
+    pub fn collect_garbage(&mut self) {
+        self.retired_wavetables
+            .retain(|wavetable| Arc::strong_count(wavetable) > 1);
+    }
 }
 
 impl Drop for OscillatorNode {
@@ -72,3 +144,23 @@ impl Drop for OscillatorNode {
         Dsp::remove_from_audio_process(self.id, &self.command_queue);
     }
 }
+
+impl PresetNode for OscillatorNode {
+    fn preset_type(&self) -> &'static str {
+        "oscillator"
+    }
+
+    fn capture_preset(&self, name: &str) -> NodePreset {
+        let mut preset = NodePreset::new(self.preset_type(), name);
+        preset.capture_parameter("frequency", &self.frequency);
+        preset.capture_parameter("gain", &self.gain);
+        preset
+    }
+
+    fn apply_preset(&mut self, preset: &NodePreset) -> Result<(), PresetError> {
+        preset.check_node_type(self.preset_type())?;
+        preset.apply_parameter("frequency", &mut self.frequency);
+        preset.apply_parameter("gain", &mut self.gain);
+        Ok(())
+    }
+}
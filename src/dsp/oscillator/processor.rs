@@ -1,69 +1,137 @@
 use crate::{
     commands::id::Id,
-    graph::dsp::{DspParameterMap, DspProcessor},
+    graph::{
+        dsp::{DspParameterMap, DspProcessor},
+        render_quality::RenderQuality,
+    },
+    parameter::realtime_parameter::RealtimeAudioParameter,
+    utility::interpolation::{interpolate, interpolate_cubic},
     AudioBuffer, SampleLocation, Timestamp,
 };
 
+use super::{
+    waveform::{band_limited_value, Waveform},
+    wavetable::SharedWavetable,
+};
+
+pub type OscillatorEventReceiver = lockfree::channel::spsc::Receiver<OscillatorEvent>;
+pub type OscillatorEventTransmitter = lockfree::channel::spsc::Sender<OscillatorEvent>;
+
+const LANES: usize = 8;
+
+pub enum OscillatorEvent {
+    SetWavetable(SharedWavetable),
+    SetWaveform(Waveform),
+}
+
 pub struct OscillatorDspProcess {
     phase: f64,
     frequency_id: Id,
     gain_id: Id,
-}
-
-lazy_static! {
-    static ref SINE_WAVE_TABLE: Vec<f64> = {
-        let length = 8192;
-        let mut values = Vec::with_capacity(length);
-
-        for frame in 0..length {
-            let time = frame as f64 / length as f64;
-            let value = (std::f64::consts::TAU * time).sin();
-            values.push(value);
-        }
-
-        values
-    };
+    wavetable: SharedWavetable,
+    waveform: Waveform,
+    event_receiver: OscillatorEventReceiver,
+    render_quality: RenderQuality,
 }
 
 impl OscillatorDspProcess {
-    pub fn new(frequency_id: Id, gain_id: Id) -> Self {
-        // ensure table is initialised off the realtime thread
-        let _ = SINE_WAVE_TABLE[0];
-
+    pub fn new(
+        frequency_id: Id,
+        gain_id: Id,
+        wavetable: SharedWavetable,
+        waveform: Waveform,
+        event_receiver: OscillatorEventReceiver,
+    ) -> Self {
         Self {
             phase: 0.0,
             frequency_id,
             gain_id,
+            wavetable,
+            waveform,
+            event_receiver,
+            render_quality: RenderQuality::default(),
         }
     }
 
-    fn increment_phase(&mut self, frequency: f64, sample_rate: usize) {
-        self.phase += frequency / (sample_rate as f64);
-        while self.phase > 1.0 {
-            self.phase -= 1.0;
+    fn read_events(&mut self) {
+        while let Ok(event) = self.event_receiver.recv() {
+            match event {
+                OscillatorEvent::SetWavetable(wavetable) => self.wavetable = wavetable,
+                OscillatorEvent::SetWaveform(waveform) => self.waveform = waveform,
+            }
         }
     }
 
-    fn get_value(&self) -> f64 {
-        let offset = self.phase * SINE_WAVE_TABLE.len() as f64;
+    fn render_lanes(
+        &mut self,
+        increments: &[f64; LANES],
+        gains: &[f64; LANES],
+        count: usize,
+    ) -> [f64; LANES] {
+        let table = self.wavetable.values_with_guard_point();
+        let length = self.wavetable.len();
 
-        let offset_before = offset.floor() as usize;
-        let offset_after = offset.ceil() as usize;
+        let mut phases = [0.0; LANES];
+        let mut phase = self.phase;
+        for (lane_phase, increment) in phases.iter_mut().zip(increments.iter()) {
+            phase += increment;
+            *lane_phase = phase;
+        }
 
-        let value_before = SINE_WAVE_TABLE[offset_before];
-        let value_after = if offset_after < SINE_WAVE_TABLE.len() {
-            SINE_WAVE_TABLE[offset_after]
-        } else {
-            0.0
-        };
+        let mut values = [0.0; LANES];
+        for lane in 0..LANES {
+            let phase = phases[lane] - phases[lane].floor();
+            phases[lane] = phase;
+
+            if self.waveform != Waveform::Wavetable {
+                values[lane] =
+                    gains[lane] * band_limited_value(self.waveform, phase, increments[lane]);
+                continue;
+            }
 
-        let weighting = offset - offset.floor();
-        interpolate(value_before, value_after, weighting)
+            let offset = phase * length as f64;
+            let index = (offset as usize).min(length - 1);
+            let weighting = offset - index as f64;
+
+            let value_before = table[index];
+            let value_after = table[index + 1];
+            let value = match self.render_quality {
+                RenderQuality::Draft if weighting < 0.5 => value_before,
+                RenderQuality::Draft => value_after,
+                RenderQuality::Normal => interpolate(value_before, value_after, weighting),
+                RenderQuality::Best => interpolate_cubic(
+                    table[(index + length - 1) % length],
+                    value_before,
+                    value_after,
+                    table[(index + 2) % length],
+                    weighting,
+                ),
+            };
+            values[lane] = gains[lane] * value;
+        }
+
+        self.phase = phases[count - 1];
+        values
     }
 }
 
-fn interpolate(a: f64, b: f64, amount_of_b: f64) -> f64 {
-    (1.0 - amount_of_b) * a + amount_of_b * b
+fn fill_lanes(
+    parameter: &RealtimeAudioParameter,
+    start_time: &Timestamp,
+    first_frame: usize,
+    count: usize,
+    sample_rate: usize,
+    values: &mut [f64; LANES],
+) {
+    if !parameter.has_pending_changes() {
+        values.fill(parameter.get_value_at_time(start_time));
+        return;
+    }
+
+    for (lane, value) in values.iter_mut().enumerate().take(count) {
+        let frame_time = start_time.incremented_by_samples(first_frame + lane, sample_rate);
+        *value = parameter.get_value_at_time(&frame_time);
+    }
 }
 
 impl DspProcessor for OscillatorDspProcess {
@@ -74,6 +142,8 @@ impl DspProcessor for OscillatorDspProcess {
         start_time: &Timestamp,
         parameters: &DspParameterMap,
     ) {
+        self.read_events();
+
         let sample_rate = output_buffer.sample_rate();
 
         let frequency = match parameters.get(&self.frequency_id) {
@@ -89,17 +159,225 @@ impl DspProcessor for OscillatorDspProcess {
         let num_frames = output_buffer.num_frames();
         let num_channels = output_buffer.num_channels();
 
-        for frame in 0..num_frames {
-            let frame_time = start_time.incremented_by_samples(frame, sample_rate);
-            let frequency = frequency.get_value_at_time(&frame_time);
-            let gain = gain.get_value_at_time(&frame_time);
+        let mut increments = [0.0; LANES];
+        let mut gains = [0.0; LANES];
+
+        for first_frame in (0..num_frames).step_by(LANES) {
+            let count = LANES.min(num_frames - first_frame);
+
+            fill_lanes(
+                frequency,
+                start_time,
+                first_frame,
+                count,
+                sample_rate,
+                &mut increments,
+            );
+            for increment in increments.iter_mut() {
+                *increment /= sample_rate as f64;
+            }
+
+            fill_lanes(
+                gain,
+                start_time,
+                first_frame,
+                count,
+                sample_rate,
+                &mut gains,
+            );
+
+            let values = self.render_lanes(&increments, &gains, count);
+
+            for (lane, value) in values.iter().enumerate().take(count) {
+                for channel in 0..num_channels {
+                    output_buffer.set_sample(
+                        SampleLocation::new(channel, first_frame + lane),
+                        *value as f32,
+                    );
+                }
+            }
+        }
+    }
+
+    fn set_render_quality(&mut self, quality: RenderQuality) {
+        self.render_quality = quality;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{f64::consts::TAU, sync::Arc};
+
+    use atomic_float::AtomicF64;
+
+    use crate::{
+        dsp::oscillator::wavetable::{sine_wavetable, Wavetable},
+        parameter::ParameterChange,
+        OwnedAudioBuffer,
+    };
+
+    use super::*;
+
+    const SAMPLE_RATE: usize = 48_000;
+
+    #[test]
+    fn vectorised_rendering_matches_scalar_phase_accumulation() {
+        let frequency_id = Id::generate();
+        let gain_id = Id::generate();
+
+        let mut parameters = DspParameterMap::new();
+        parameters.insert(
+            frequency_id,
+            RealtimeAudioParameter::new(frequency_id, Arc::new(AtomicF64::new(440.0))),
+        );
+        parameters.insert(
+            gain_id,
+            RealtimeAudioParameter::new(gain_id, Arc::new(AtomicF64::new(0.5))),
+        );
+
+        let (_, event_receiver) = lockfree::channel::spsc::create();
+        let mut oscillator = OscillatorDspProcess::new(
+            frequency_id,
+            gain_id,
+            sine_wavetable(),
+            Waveform::Wavetable,
+            event_receiver,
+        );
+        let mut expected_phase = 0.0;
+
+        for block in 0..4 {
+            if block == 2 {
+                parameters
+                    .get_mut(&frequency_id)
+                    .unwrap()
+                    .add_parameter_change(ParameterChange::linear(
+                        880.0,
+                        Timestamp::from_samples(3.0 * 509.0, SAMPLE_RATE),
+                    ));
+            }
+
+            let start_time = Timestamp::from_samples(block as f64 * 509.0, SAMPLE_RATE);
+            let mut output_buffer = OwnedAudioBuffer::new(509, 2, SAMPLE_RATE);
+            oscillator.process_audio(
+                &OwnedAudioBuffer::new(509, 2, SAMPLE_RATE),
+                &mut output_buffer,
+                &start_time,
+                &parameters,
+            );
 
-            self.increment_phase(frequency, sample_rate);
-            let value = gain * self.get_value();
+            let frequency = parameters.get(&frequency_id).unwrap();
+            for frame in 0..509 {
+                let frame_time = start_time.incremented_by_samples(frame, SAMPLE_RATE);
+                expected_phase += frequency.get_value_at_time(&frame_time) / SAMPLE_RATE as f64;
+                let expected = 0.5 * (TAU * expected_phase).sin();
 
-            for channel in 0..num_channels {
-                output_buffer.set_sample(SampleLocation::new(channel, frame), value as f32);
+                for channel in 0..2 {
+                    let actual = output_buffer.get_sample(SampleLocation::new(channel, frame));
+                    assert!((actual as f64 - expected).abs() < 1e-5);
+                }
             }
         }
     }
+
+    #[test]
+    fn switches_to_shared_wavetable() {
+        let frequency_id = Id::generate();
+        let gain_id = Id::generate();
+
+        let mut parameters = DspParameterMap::new();
+        parameters.insert(
+            frequency_id,
+            RealtimeAudioParameter::new(frequency_id, Arc::new(AtomicF64::new(1000.0))),
+        );
+        parameters.insert(
+            gain_id,
+            RealtimeAudioParameter::new(gain_id, Arc::new(AtomicF64::new(1.0))),
+        );
+
+        let (mut event_transmitter, event_receiver) = lockfree::channel::spsc::create();
+        let mut oscillator = OscillatorDspProcess::new(
+            frequency_id,
+            gain_id,
+            sine_wavetable(),
+            Waveform::Wavetable,
+            event_receiver,
+        );
+
+        let square = Arc::new(Wavetable::from_fn(
+            64,
+            |phase| {
+                if phase < 0.5 {
+                    1.0
+                } else {
+                    -1.0
+                }
+            },
+        ));
+        let _ = event_transmitter.send(OscillatorEvent::SetWavetable(square.clone()));
+
+        let mut output_buffer = OwnedAudioBuffer::new(48, 1, SAMPLE_RATE);
+        oscillator.process_audio(
+            &OwnedAudioBuffer::new(48, 1, SAMPLE_RATE),
+            &mut output_buffer,
+            &Timestamp::zero(),
+            &parameters,
+        );
+
+        for frame in 0..48 {
+            let phase = (frame + 1) as f64 * 1000.0 / SAMPLE_RATE as f64;
+            let expected = square.value_at_phase(phase);
+            let actual = output_buffer.get_sample(SampleLocation::new(0, frame));
+            assert!((actual as f64 - expected).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn interpolation_follows_render_quality() {
+        let frequency_id = Id::generate();
+        let gain_id = Id::generate();
+
+        let mut parameters = DspParameterMap::new();
+        parameters.insert(
+            frequency_id,
+            RealtimeAudioParameter::new(frequency_id, Arc::new(AtomicF64::new(1234.0))),
+        );
+        parameters.insert(
+            gain_id,
+            RealtimeAudioParameter::new(gain_id, Arc::new(AtomicF64::new(1.0))),
+        );
+
+        let maximum_error = |quality: RenderQuality| {
+            let (_, event_receiver) = lockfree::channel::spsc::create();
+            let mut oscillator = OscillatorDspProcess::new(
+                frequency_id,
+                gain_id,
+                Arc::new(Wavetable::sine(64)),
+                Waveform::Wavetable,
+                event_receiver,
+            );
+            oscillator.set_render_quality(quality);
+
+            let mut output_buffer = OwnedAudioBuffer::new(480, 1, SAMPLE_RATE);
+            oscillator.process_audio(
+                &OwnedAudioBuffer::new(480, 1, SAMPLE_RATE),
+                &mut output_buffer,
+                &Timestamp::zero(),
+                &parameters,
+            );
+
+            (0..480)
+                .map(|frame| {
+                    let phase = (frame + 1) as f64 * 1234.0 / SAMPLE_RATE as f64;
+                    let actual = output_buffer.get_sample(SampleLocation::new(0, frame));
+                    (actual as f64 - (TAU * phase).sin()).abs()
+                })
+                .fold(0.0, f64::max)
+        };
+
+        let draft = maximum_error(RenderQuality::Draft);
+        let normal = maximum_error(RenderQuality::Normal);
+        let best = maximum_error(RenderQuality::Best);
+        assert!(best < 0.1 * normal);
+        assert!(normal < 0.1 * draft);
+    }
 }
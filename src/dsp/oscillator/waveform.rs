@@ -0,0 +1,130 @@
+const MAX_INCREMENT: f64 = 0.5;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Waveform {
+    #[default]
+    Wavetable,
+    Saw,
+    Square,
+    Triangle,
+}
+
+fn poly_blep(phase: f64, increment: f64) -> f64 {
+    if phase < increment {
+        let position = phase / increment;
+        2.0 * position - position * position - 1.0
+    } else if phase > 1.0 - increment {
+        let position = (phase - 1.0) / increment;
+        position * position + 2.0 * position + 1.0
+    } else {
+        0.0
+    }
+}
+
+fn poly_blamp(phase: f64, increment: f64) -> f64 {
+    if phase < increment {
+        let position = phase / increment - 1.0;
+        -position * position * position / 3.0
+    } else if phase > 1.0 - increment {
+        let position = (phase - 1.0) / increment + 1.0;
+        position * position * position / 3.0
+    } else {
+        0.0
+    }
+}
+
+fn wrap(phase: f64) -> f64 {
+    phase - phase.floor()
+}
+
+pub fn band_limited_value(waveform: Waveform, phase: f64, increment: f64) -> f64 {
+    let increment = increment.abs().min(MAX_INCREMENT);
+
+    if increment == 0.0 {
+        return naive_value(waveform, phase);
+    }
+
+    match waveform {
+        Waveform::Wavetable => 0.0,
+        Waveform::Saw => naive_value(waveform, phase) - poly_blep(phase, increment),
+        Waveform::Square => {
+            naive_value(waveform, phase) + poly_blep(phase, increment)
+                - poly_blep(wrap(phase + 0.5), increment)
+        }
+        Waveform::Triangle => {
+            naive_value(waveform, phase)
+                + 4.0
+                    * increment
+                    * (poly_blamp(wrap(phase + 0.25), increment)
+                        - poly_blamp(wrap(phase + 0.75), increment))
+        }
+    }
+}
+
+fn naive_value(waveform: Waveform, phase: f64) -> f64 {
+    match waveform {
+        Waveform::Wavetable => 0.0,
+        Waveform::Saw => 2.0 * phase - 1.0,
+        Waveform::Square if phase < 0.5 => 1.0,
+        Waveform::Square => -1.0,
+        Waveform::Triangle => 1.0 - 4.0 * (wrap(phase + 0.25) - 0.5).abs(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::TAU;
+
+    use super::*;
+
+    fn harmonic_magnitude(signal: &[f64], cycles: f64) -> f64 {
+        let (real, imaginary) =
+            signal
+                .iter()
+                .enumerate()
+                .fold((0.0, 0.0), |(real, imaginary), (index, value)| {
+                    let angle = TAU * cycles * index as f64 / signal.len() as f64;
+                    (real + value * angle.cos(), imaginary + value * angle.sin())
+                });
+
+        2.0 * (real * real + imaginary * imaginary).sqrt() / signal.len() as f64
+    }
+
+    #[test]
+    fn reduces_aliasing_of_high_frequency_waveforms() {
+        let sample_rate = 48_000.0;
+        let frequency = 4_700.0;
+        let num_frames = 4_800;
+        let increment = frequency / sample_rate;
+        let fundamental = num_frames as f64 * increment;
+        let alias = (sample_rate - 7.0 * frequency).abs() * num_frames as f64 / sample_rate;
+
+        for waveform in [Waveform::Saw, Waveform::Square, Waveform::Triangle] {
+            let render = |band_limited: bool| -> Vec<f64> {
+                (0..num_frames)
+                    .map(|frame| {
+                        let phase = wrap(frame as f64 * increment);
+                        if band_limited {
+                            band_limited_value(waveform, phase, increment)
+                        } else {
+                            naive_value(waveform, phase)
+                        }
+                    })
+                    .collect()
+            };
+
+            let naive = render(false);
+            let band_limited = render(true);
+
+            assert!(
+                (harmonic_magnitude(&band_limited, fundamental)
+                    - harmonic_magnitude(&naive, fundamental))
+                .abs()
+                    < 0.1
+            );
+            assert!(
+                harmonic_magnitude(&band_limited, alias) < 0.5 * harmonic_magnitude(&naive, alias)
+            );
+        }
+    }
+}
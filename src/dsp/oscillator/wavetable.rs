@@ -0,0 +1,210 @@
+use std::{collections::HashMap, f64::consts::TAU, sync::Arc, thread};
+
+use lockfree::channel::mpsc::{self, Receiver, Sender};
+
+pub type SharedWavetable = Arc<Wavetable>;
+
+const DEFAULT_LENGTH: usize = 8192;
+
+lazy_static! {
+    static ref SINE_WAVETABLE: SharedWavetable = Arc::new(Wavetable::sine(DEFAULT_LENGTH));
+}
+
+pub fn sine_wavetable() -> SharedWavetable {
+    SINE_WAVETABLE.clone()
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Wavetable {
+    values: Vec<f64>,
+}
+
+impl Wavetable {
+    pub fn from_values(values: &[f64]) -> Self {
+        assert!(!values.is_empty());
+
+        let mut table = Vec::with_capacity(values.len() + 1);
+        table.extend_from_slice(values);
+        table.push(values[0]);
+
+        Self { values: table }
+    }
+
+    pub fn from_fn(length: usize, function: impl Fn(f64) -> f64) -> Self {
+        let values: Vec<f64> = (0..length)
+            .map(|index| function(index as f64 / length as f64))
+            .collect();
+
+        Self::from_values(&values)
+    }
+
+    pub fn from_harmonics(length: usize, amplitudes: &[f64]) -> Self {
+        Self::from_fn(length, |phase| {
+            amplitudes
+                .iter()
+                .enumerate()
+                .map(|(index, amplitude)| amplitude * (TAU * (index + 1) as f64 * phase).sin())
+                .sum()
+        })
+    }
+
+    pub fn sine(length: usize) -> Self {
+        Self::from_harmonics(length, &[1.0])
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len() - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn values_with_guard_point(&self) -> &[f64] {
+        &self.values
+    }
+
+    pub fn value_at_phase(&self, phase: f64) -> f64 {
+        let offset = (phase - phase.floor()) * self.len() as f64;
+        let index = (offset as usize).min(self.len() - 1);
+        let weighting = offset - index as f64;
+
+        let a = self.values[index];
+        let b = self.values[index + 1];
+        a + weighting * (b - a)
+    }
+}
+
+pub struct WavetableRegistry {
+    tables: HashMap<String, SharedWavetable>,
+    retired: Vec<SharedWavetable>,
+    build_tx: Sender<(String, Wavetable)>,
+    build_rx: Receiver<(String, Wavetable)>,
+}
+
+impl Default for WavetableRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WavetableRegistry {
+    pub fn new() -> Self {
+        let (build_tx, build_rx) = mpsc::create();
+
+        Self {
+            tables: HashMap::new(),
+            retired: Vec::new(),
+            build_tx,
+            build_rx,
+        }
+    }
+
+    pub fn insert(&mut self, name: &str, wavetable: Wavetable) -> SharedWavetable {
+        let shared = Arc::new(wavetable);
+
+        if let Some(previous) = self.tables.insert(String::from(name), shared.clone()) {
+            self.retired.push(previous);
+        }
+
+        shared
+    }
+
+    pub fn build_async<F>(&mut self, name: &str, build: F)
+    where
+        F: FnOnce() -> Wavetable + Send + 'static,
+    {
+        let name = String::from(name);
+        let build_tx = self.build_tx.clone();
+
+        thread::spawn(move || {
+            let _ = build_tx.send((name, build()));
+        });
+    }
+
+    pub fn update(&mut self) -> Vec<String> {
+        let mut built = Vec::new();
+
+        while let Ok((name, wavetable)) = self.build_rx.recv() {
+            self.insert(&name, wavetable);
+            built.push(name);
+        }
+
+        built
+    }
+
+    pub fn get(&self, name: &str) -> Option<SharedWavetable> {
+        self.tables.get(name).cloned()
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.tables.contains_key(name)
+    }
+
+    pub fn remove(&mut self, name: &str) -> bool {
+        match self.tables.remove(name) {
+            Some(wavetable) => {
+                self.retired.push(wavetable);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn num_retired(&self) -> usize {
+        self.retired.len()
+    }
+
+    pub fn collect_garbage(&mut self) -> usize {
+        let before = self.retired.len();
+        self.retired
+            .retain(|wavetable| Arc::strong_count(wavetable) > 1);
+        before - self.retired.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use approx::assert_relative_eq;
+
+    use super::*;
+
+    #[test]
+    fn interpolates_with_wrapping_guard_point() {
+        let wavetable = Wavetable::from_values(&[0.0, 1.0, 0.0, -1.0]);
+        assert_eq!(wavetable.len(), 4);
+        assert_relative_eq!(wavetable.value_at_phase(0.125), 0.5);
+        assert_relative_eq!(wavetable.value_at_phase(0.875), -0.5);
+        assert_relative_eq!(wavetable.value_at_phase(1.25), 1.0);
+    }
+
+    #[test]
+    fn shares_built_tables_and_retires_unused_ones() {
+        let mut registry = WavetableRegistry::new();
+        registry.build_async("organ", || {
+            Wavetable::from_harmonics(2048, &[1.0, 0.5, 0.25])
+        });
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !registry.contains("organ") && Instant::now() < deadline {
+            registry.update();
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        let first = registry.get("organ").unwrap();
+        let second = registry.get("organ").unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(first.len(), 2048);
+
+        assert!(registry.remove("organ"));
+        assert!(registry.get("organ").is_none());
+        assert_eq!(registry.collect_garbage(), 0);
+
+        drop(first);
+        drop(second);
+        assert_eq!(registry.collect_garbage(), 1);
+        assert_eq!(registry.num_retired(), 0);
+    }
+}
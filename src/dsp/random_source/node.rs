@@ -0,0 +1,118 @@
+use std::{
+    collections::HashMap,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    commands::{command_queue::CommandQueue, id::Id},
+    graph::{dsp::Dsp, node::Node},
+    parameter::{
+        audio_parameter::AudioParameter,
+        preset::{NodePreset, PresetError, PresetNode},
+    },
+};
+
+use super::processor::{RandomDistribution, RandomInterpolation, RandomSourceProcessor};
+
+pub struct RandomSourceNode {
+    id: Id,
+    command_queue: CommandQueue,
+    pub rate: AudioParameter,
+    pub depth: AudioParameter,
+}
+
+const MIN_RATE: f64 = 0.0;
+const MAX_RATE: f64 = 1000.0;
+const MIN_DEPTH: f64 = 0.0;
+const MAX_DEPTH: f64 = 100_000.0;
+
+impl RandomSourceNode {
+    pub fn new(
+        command_queue: CommandQueue,
+        rate: f64,
+        distribution: RandomDistribution,
+        interpolation: RandomInterpolation,
+    ) -> Self {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|time| time.as_nanos() as u64)
+            .unwrap_or(1);
+
+        Self::with_seed(command_queue, rate, distribution, interpolation, seed)
+    }
+
+    pub fn with_seed(
+        command_queue: CommandQueue,
+        rate: f64,
+        distribution: RandomDistribution,
+        interpolation: RandomInterpolation,
+        seed: u64,
+    ) -> Self {
+        let mut parameters = HashMap::new();
+
+        let id = Id::generate();
+
+        let (rate, realtime_rate) =
+            AudioParameter::new(id, rate, MIN_RATE, MAX_RATE, command_queue.clone());
+        parameters.insert(realtime_rate.get_id(), realtime_rate);
+
+        let (depth, realtime_depth) =
+            AudioParameter::new(id, 1.0, MIN_DEPTH, MAX_DEPTH, command_queue.clone());
+        parameters.insert(realtime_depth.get_id(), realtime_depth);
+
+        let processor = RandomSourceProcessor::new(
+            distribution,
+            interpolation,
+            seed,
+            rate.get_id(),
+            depth.get_id(),
+        );
+
+        let dsp = Dsp::new(id, Box::new(processor), parameters);
+
+        Dsp::add_to_audio_process(dsp, &command_queue);
+
+        Self {
+            id,
+            command_queue,
+            rate,
+            depth,
+        }
+    }
+}
+
+impl Node for RandomSourceNode {
+    fn get_id(&self) -> Id {
+        self.id
+    }
+
+    fn get_command_queue(&self) -> CommandQueue {
+        self.command_queue.clone()
+    }
+}
+
+impl Drop for RandomSourceNode {
+    fn drop(&mut self) {
+        Dsp::remove_from_audio_process(self.id, &self.command_queue);
+    }
+}
+
+impl PresetNode for RandomSourceNode {
+    fn preset_type(&self) -> &'static str {
+        "random_source"
+    }
+
+    fn capture_preset(&self, name: &str) -> NodePreset {
+        let mut preset = NodePreset::new(self.preset_type(), name);
+        preset.capture_parameter("rate", &self.rate);
+        preset.capture_parameter("depth", &self.depth);
+        preset
+    }
+
+    fn apply_preset(&mut self, preset: &NodePreset) -> Result<(), PresetError> {
+        preset.check_node_type(self.preset_type())?;
+        preset.apply_parameter("rate", &mut self.rate);
+        preset.apply_parameter("depth", &mut self.depth);
+        Ok(())
+    }
+}
@@ -0,0 +1,189 @@
+use crate::{
+    commands::id::Id,
+    graph::dsp::{DspParameterMap, DspProcessor},
+    utility::random::Random,
+    AudioBuffer, SampleLocation, Timestamp,
+};
+
+const GAUSSIAN_RANGE_IN_DEVIATIONS: f64 = 3.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RandomDistribution {
+    Uniform,
+    Gaussian,
+    Binary,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RandomInterpolation {
+    Hold,
+    Linear,
+    Cosine,
+}
+
+pub struct RandomSourceProcessor {
+    random: Random,
+    distribution: RandomDistribution,
+    interpolation: RandomInterpolation,
+    phase: f64,
+    previous_value: f64,
+    next_value: f64,
+    rate_id: Id,
+    depth_id: Id,
+}
+
+impl RandomSourceProcessor {
+    pub fn new(
+        distribution: RandomDistribution,
+        interpolation: RandomInterpolation,
+        seed: u64,
+        rate_id: Id,
+        depth_id: Id,
+    ) -> Self {
+        let mut random = Random::with_seed(seed);
+        let previous_value = Self::draw(&mut random, distribution);
+        let next_value = Self::draw(&mut random, distribution);
+
+        Self {
+            random,
+            distribution,
+            interpolation,
+            phase: 0.0,
+            previous_value,
+            next_value,
+            rate_id,
+            depth_id,
+        }
+    }
+
+    fn draw(random: &mut Random, distribution: RandomDistribution) -> f64 {
+        match distribution {
+            RandomDistribution::Uniform => random.next_bipolar(),
+            RandomDistribution::Gaussian => {
+                let radius = (-2.0 * (1.0 - random.next_unipolar()).ln()).sqrt();
+                let angle = std::f64::consts::TAU * random.next_unipolar();
+                (radius * angle.cos() / GAUSSIAN_RANGE_IN_DEVIATIONS).clamp(-1.0, 1.0)
+            }
+            RandomDistribution::Binary => {
+                if random.next_unipolar() < 0.5 {
+                    -1.0
+                } else {
+                    1.0
+                }
+            }
+        }
+    }
+
+    fn advance(&mut self, rate: f64, sample_rate: usize) {
+        self.phase += rate.max(0.0) / sample_rate as f64;
+
+        while self.phase >= 1.0 {
+            self.phase -= 1.0;
+            self.previous_value = self.next_value;
+            self.next_value = Self::draw(&mut self.random, self.distribution);
+        }
+    }
+
+    fn current_value(&self) -> f64 {
+        let amount = match self.interpolation {
+            RandomInterpolation::Hold => 0.0,
+            RandomInterpolation::Linear => self.phase,
+            RandomInterpolation::Cosine => 0.5 - 0.5 * (std::f64::consts::PI * self.phase).cos(),
+        };
+
+        self.previous_value + (self.next_value - self.previous_value) * amount
+    }
+}
+
+impl DspProcessor for RandomSourceProcessor {
+    fn process_audio(
+        &mut self,
+        _input_buffer: &dyn AudioBuffer,
+        output_buffer: &mut dyn AudioBuffer,
+        start_time: &Timestamp,
+        parameters: &DspParameterMap,
+    ) {
+        let sample_rate = output_buffer.sample_rate();
+
+        let (rate, depth) = match (
+            parameters.get(&self.rate_id),
+            parameters.get(&self.depth_id),
+        ) {
+            (Some(rate), Some(depth)) => (rate, depth),
+            _ => return,
+        };
+
+        for frame in 0..output_buffer.num_frames() {
+            let frame_time = start_time.incremented_by_samples(frame, sample_rate);
+            let value = self.current_value() * depth.get_value_at_time(&frame_time);
+
+            for channel in 0..output_buffer.num_channels() {
+                output_buffer.set_sample(SampleLocation::new(channel, frame), value as f32);
+            }
+
+            self.advance(rate.get_value_at_time(&frame_time), sample_rate);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use atomic_float::AtomicF64;
+
+    use crate::{parameter::realtime_parameter::RealtimeAudioParameter, OwnedAudioBuffer};
+
+    use super::*;
+
+    fn render(
+        distribution: RandomDistribution,
+        interpolation: RandomInterpolation,
+        seed: u64,
+    ) -> Vec<f32> {
+        let rate_id = Id::generate();
+        let depth_id = Id::generate();
+
+        let mut parameters = DspParameterMap::new();
+        for (id, value) in [(rate_id, 10.0), (depth_id, 1.0)] {
+            parameters.insert(
+                id,
+                RealtimeAudioParameter::new(id, std::sync::Arc::new(AtomicF64::new(value))),
+            );
+        }
+
+        let mut processor =
+            RandomSourceProcessor::new(distribution, interpolation, seed, rate_id, depth_id);
+
+        let input_buffer = OwnedAudioBuffer::new(1000, 1, 1000);
+        let mut output_buffer = OwnedAudioBuffer::new(1000, 1, 1000);
+        processor.process_audio(
+            &input_buffer,
+            &mut output_buffer,
+            &Timestamp::zero(),
+            &parameters,
+        );
+
+        (0..1000)
+            .map(|frame| output_buffer.get_sample(SampleLocation::new(0, frame)))
+            .collect()
+    }
+
+    #[test]
+    fn is_deterministic_when_seeded() {
+        let first = render(RandomDistribution::Gaussian, RandomInterpolation::Cosine, 7);
+        let second = render(RandomDistribution::Gaussian, RandomInterpolation::Cosine, 7);
+        let other = render(RandomDistribution::Gaussian, RandomInterpolation::Cosine, 8);
+
+        assert_eq!(first, second);
+        assert_ne!(first, other);
+        assert!(first.iter().all(|value| (-1.0..=1.0).contains(value)));
+    }
+
+    #[test]
+    fn hold_changes_value_at_rate() {
+        let values = render(RandomDistribution::Binary, RandomInterpolation::Hold, 3);
+
+        assert!(values.iter().all(|value| value.abs() == 1.0));
+        assert!(values[..95].iter().all(|value| *value == values[0]));
+        assert!(values[105..195].iter().all(|value| *value == values[105]));
+    }
+}
@@ -0,0 +1,193 @@
+use std::{collections::HashMap, time::Duration};
+
+use lockfree::channel::spsc::{self, Receiver};
+
+use crate::{
+    commands::{command_queue::CommandQueue, id::Id},
+    graph::{dsp::Dsp, node::Node},
+    AudioBuffer, OwnedAudioBuffer, SampleLocation, Timestamp,
+};
+
+use super::processor::{
+    PunchSession, RecorderEvent, RecorderEventTransmitter, RecorderNotification, RecorderProcessor,
+};
+
+pub struct Recording {
+    pub start_time: Timestamp,
+    pub take: usize,
+    pub audio: OwnedAudioBuffer,
+}
+
+pub struct RecorderNode {
+    id: Id,
+    command_queue: CommandQueue,
+    event_transmitter: RecorderEventTransmitter,
+    notification_receiver: Receiver<RecorderNotification>,
+    sample_rate: usize,
+    num_channels: usize,
+    latency_compensation: usize,
+    armed: bool,
+}
+
+impl RecorderNode {
+    pub fn new(command_queue: CommandQueue, sample_rate: usize, num_channels: usize) -> Self {
+        let id = Id::generate();
+
+        let (event_transmitter, event_receiver) = spsc::create();
+        let (notification_transmitter, notification_receiver) = spsc::create();
+
+        let processor = RecorderProcessor::new(event_receiver, notification_transmitter);
+
+        let dsp = Dsp::new(id, Box::new(processor), HashMap::new());
+
+        Dsp::add_to_audio_process(dsp, &command_queue);
+
+        Self {
+            id,
+            command_queue,
+            event_transmitter,
+            notification_receiver,
+            sample_rate,
+            num_channels,
+            latency_compensation: 0,
+            armed: false,
+        }
+    }
+
+    pub fn arm(&mut self) {
+        self.set_armed(true);
+    }
+
+    pub fn disarm(&mut self) {
+        self.set_armed(false);
+    }
+
+    fn set_armed(&mut self, armed: bool) {
+        self.armed = armed;
+        let _ = self.event_transmitter.send(RecorderEvent::SetArmed(armed));
+    }
+
+    pub fn is_armed(&self) -> bool {
+        self.armed
+    }
+
+    fn frames_between(&self, start_time: Timestamp, end_time: Timestamp) -> usize {
+        (end_time - start_time)
+            .get_samples(self.sample_rate)
+            .round()
+            .max(0.0) as usize
+    }
+
+    fn punch(
+        &mut self,
+        punch_in: Timestamp,
+        punch_out: Option<Timestamp>,
+        take_length: Option<usize>,
+        num_takes: usize,
+        buffer_length: usize,
+    ) {
+        let buffers = (0..num_takes)
+            .map(|_| {
+                Some(OwnedAudioBuffer::new(
+                    buffer_length,
+                    self.num_channels,
+                    self.sample_rate,
+                ))
+            })
+            .collect();
+
+        let _ = self
+            .event_transmitter
+            .send(RecorderEvent::PunchIn(PunchSession {
+                punch_in,
+                punch_out,
+                take_length,
+                buffers,
+            }));
+    }
+
+    pub fn punch_in_at(&mut self, punch_in: Timestamp, max_duration: Duration) {
+        let buffer_length = (max_duration.as_secs_f64() * self.sample_rate as f64).ceil();
+        self.punch(punch_in, None, None, 1, buffer_length as usize);
+    }
+
+    pub fn punch_out_at(&mut self, punch_out: Timestamp) {
+        let _ = self
+            .event_transmitter
+            .send(RecorderEvent::PunchOut(punch_out));
+    }
+
+    pub fn record(&mut self, start_time: Timestamp, stop_time: Timestamp) {
+        let num_frames = self.frames_between(start_time, stop_time);
+        self.punch(start_time, Some(stop_time), None, 1, num_frames);
+    }
+
+    pub fn record_loop(&mut self, loop_start: Timestamp, loop_end: Timestamp, num_takes: usize) {
+        let loop_length = self.frames_between(loop_start, loop_end);
+        if loop_length == 0 {
+            return;
+        }
+
+        self.punch(loop_start, None, Some(loop_length), num_takes, loop_length);
+    }
+
+    pub fn set_latency_compensation(&mut self, latency_in_samples: usize) {
+        self.latency_compensation = latency_in_samples;
+        let _ = self
+            .event_transmitter
+            .send(RecorderEvent::SetLatencyCompensation(latency_in_samples));
+    }
+
+    pub fn get_latency_compensation(&self) -> usize {
+        self.latency_compensation
+    }
+
+    pub fn take_recordings(&mut self) -> Vec<Recording> {
+        let mut recordings = Vec::new();
+
+        while let Ok(notification) = self.notification_receiver.recv() {
+            let finished = match notification {
+                RecorderNotification::Take(finished) if finished.num_frames > 0 => finished,
+                RecorderNotification::Take(_) => continue,
+                RecorderNotification::Release(buffers) => {
+                    drop(buffers);
+                    continue;
+                }
+            };
+
+            let mut audio =
+                OwnedAudioBuffer::new(finished.num_frames, self.num_channels, self.sample_rate);
+
+            for frame in 0..finished.num_frames {
+                for channel in 0..self.num_channels {
+                    let location = SampleLocation::new(channel, frame);
+                    audio.set_sample(location, finished.buffer.get_sample(location));
+                }
+            }
+
+            recordings.push(Recording {
+                start_time: finished.start_time,
+                take: finished.take,
+                audio,
+            });
+        }
+
+        recordings
+    }
+}
+
+impl Node for RecorderNode {
+    fn get_id(&self) -> Id {
+        self.id
+    }
+
+    fn get_command_queue(&self) -> CommandQueue {
+        self.command_queue.clone()
+    }
+}
+
+impl Drop for RecorderNode {
+    fn drop(&mut self) {
+        Dsp::remove_from_audio_process(self.id, &self.command_queue);
+    }
+}
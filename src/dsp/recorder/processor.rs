@@ -0,0 +1,418 @@
+use lockfree::channel::spsc::{Receiver, Sender};
+
+use crate::{
+    graph::dsp::{DspParameterMap, DspProcessor},
+    utility::realtime_log::{self, LogLevel},
+    AudioBuffer, OwnedAudioBuffer, SampleLocation, Timestamp,
+};
+
+pub type RecorderEventReceiver = Receiver<RecorderEvent>;
+pub type RecorderEventTransmitter = Sender<RecorderEvent>;
+
+const MAXIMUM_NUMBER_OF_SESSIONS: usize = 16;
+
+pub struct PunchSession {
+    pub punch_in: Timestamp,
+    pub punch_out: Option<Timestamp>,
+    pub take_length: Option<usize>,
+    pub buffers: Vec<Option<OwnedAudioBuffer>>,
+}
+
+pub enum RecorderEvent {
+    SetArmed(bool),
+    PunchIn(PunchSession),
+    PunchOut(Timestamp),
+    SetLatencyCompensation(usize),
+}
+
+pub struct FinishedTake {
+    pub start_time: Timestamp,
+    pub take: usize,
+    pub buffer: OwnedAudioBuffer,
+    pub num_frames: usize,
+}
+
+pub enum RecorderNotification {
+    Take(FinishedTake),
+    Release(Vec<Option<OwnedAudioBuffer>>),
+}
+
+struct ScheduledSession {
+    punch_in_frame: i64,
+    punch_out_frame: Option<i64>,
+    session: PunchSession,
+}
+
+struct ActiveTake {
+    take: usize,
+    start_frame: i64,
+    buffer: OwnedAudioBuffer,
+    num_frames: usize,
+}
+
+pub struct RecorderProcessor {
+    event_receiver: RecorderEventReceiver,
+    notification_transmitter: Sender<RecorderNotification>,
+    latency_compensation: usize,
+    armed: bool,
+    sessions: Vec<ScheduledSession>,
+    active: Option<ActiveTake>,
+}
+
+fn frame_at_time(time: &Timestamp, sample_rate: usize) -> i64 {
+    time.get_samples(sample_rate).round() as i64
+}
+
+impl RecorderProcessor {
+    pub fn new(
+        event_receiver: RecorderEventReceiver,
+        notification_transmitter: Sender<RecorderNotification>,
+    ) -> Self {
+        Self {
+            event_receiver,
+            notification_transmitter,
+            latency_compensation: 0,
+            armed: false,
+            sessions: Vec::with_capacity(MAXIMUM_NUMBER_OF_SESSIONS),
+            active: None,
+        }
+    }
+
+    fn read_events(&mut self, sample_rate: usize) {
+        while let Ok(event) = self.event_receiver.recv() {
+            match event {
+                RecorderEvent::SetArmed(armed) => self.armed = armed,
+                RecorderEvent::PunchIn(session) => self.schedule(session, sample_rate),
+                RecorderEvent::PunchOut(time) => {
+                    if let Some(scheduled) = self.sessions.last_mut() {
+                        scheduled.punch_out_frame = Some(frame_at_time(&time, sample_rate));
+                    }
+                }
+                RecorderEvent::SetLatencyCompensation(latency) => {
+                    self.latency_compensation = latency
+                }
+            }
+        }
+    }
+
+    fn schedule(&mut self, session: PunchSession, sample_rate: usize) {
+        if self.sessions.len() == MAXIMUM_NUMBER_OF_SESSIONS {
+            realtime_log::log(LogLevel::Warning, "Too many scheduled punch sessions");
+            let _ = self
+                .notification_transmitter
+                .send(RecorderNotification::Release(session.buffers));
+            return;
+        }
+
+        let scheduled = ScheduledSession {
+            punch_in_frame: frame_at_time(&session.punch_in, sample_rate),
+            punch_out_frame: session
+                .punch_out
+                .map(|time| frame_at_time(&time, sample_rate)),
+            session,
+        };
+
+        let index = self
+            .sessions
+            .iter()
+            .position(|existing| existing.punch_in_frame > scheduled.punch_in_frame)
+            .unwrap_or(self.sessions.len());
+
+        self.sessions.insert(index, scheduled);
+    }
+
+    fn finish_take(&mut self, sample_rate: usize) {
+        if let Some(take) = self.active.take() {
+            let _ = self
+                .notification_transmitter
+                .send(RecorderNotification::Take(FinishedTake {
+                    start_time: Timestamp::from_samples(take.start_frame as f64, sample_rate),
+                    take: take.take,
+                    buffer: take.buffer,
+                    num_frames: take.num_frames,
+                }));
+        }
+    }
+
+    fn end_session(&mut self, sample_rate: usize) {
+        self.finish_take(sample_rate);
+
+        if !self.sessions.is_empty() {
+            let scheduled = self.sessions.remove(0);
+            let _ = self
+                .notification_transmitter
+                .send(RecorderNotification::Release(scheduled.session.buffers));
+        }
+    }
+
+    fn advance_sessions(&mut self, frame: i64, sample_rate: usize) {
+        while let Some(current) = self.sessions.first() {
+            let punched_out = current
+                .punch_out_frame
+                .is_some_and(|punch_out| frame >= punch_out);
+            let superseded = self
+                .sessions
+                .get(1)
+                .is_some_and(|next| frame >= next.punch_in_frame);
+
+            if !punched_out && !superseded {
+                return;
+            }
+
+            self.end_session(sample_rate);
+        }
+    }
+
+    fn record_frame(
+        &mut self,
+        input_buffer: &dyn AudioBuffer,
+        frame: usize,
+        aligned_frame: i64,
+        sample_rate: usize,
+    ) {
+        let current = match self.sessions.first_mut() {
+            Some(current) if aligned_frame >= current.punch_in_frame => current,
+            _ => return,
+        };
+
+        let elapsed = aligned_frame - current.punch_in_frame;
+        let (take, position) = match current.session.take_length {
+            Some(take_length) => {
+                let take_length = take_length.max(1) as i64;
+                ((elapsed / take_length) as usize, elapsed % take_length)
+            }
+            None => (0, elapsed),
+        };
+
+        if take >= current.session.buffers.len() {
+            return self.end_session(sample_rate);
+        }
+
+        if self.active.as_ref().map(|active| active.take) != Some(take) {
+            let start_frame = aligned_frame - position;
+            let buffer = current.session.buffers[take].take();
+
+            self.finish_take(sample_rate);
+            self.active = buffer.map(|buffer| ActiveTake {
+                take,
+                start_frame,
+                buffer,
+                num_frames: 0,
+            });
+        }
+
+        let active = match self.active.as_mut() {
+            Some(active) if (position as usize) < active.buffer.num_frames() => active,
+            _ => return,
+        };
+
+        let num_channels = active
+            .buffer
+            .num_channels()
+            .min(input_buffer.num_channels());
+
+        for channel in 0..num_channels {
+            active.buffer.set_sample(
+                SampleLocation::new(channel, position as usize),
+                input_buffer.get_sample(SampleLocation::new(channel, frame)),
+            );
+        }
+
+        active.num_frames = active.num_frames.max(position as usize + 1);
+    }
+}
+
+impl DspProcessor for RecorderProcessor {
+    fn process_audio(
+        &mut self,
+        input_buffer: &dyn AudioBuffer,
+        output_buffer: &mut dyn AudioBuffer,
+        start_time: &Timestamp,
+        _parameters: &DspParameterMap,
+    ) {
+        let sample_rate = output_buffer.sample_rate();
+        self.read_events(sample_rate);
+
+        for frame in 0..output_buffer.num_frames() {
+            for channel in 0..output_buffer.num_channels() {
+                let location = SampleLocation::new(channel, frame);
+                output_buffer.set_sample(location, input_buffer.get_sample(location));
+            }
+        }
+
+        if !self.armed {
+            return self.finish_take(sample_rate);
+        }
+
+        let block_frame = frame_at_time(start_time, sample_rate) - self.latency_compensation as i64;
+
+        for frame in 0..output_buffer.num_frames() {
+            let aligned_frame = block_frame + frame as i64;
+            self.advance_sessions(aligned_frame, sample_rate);
+            self.record_frame(input_buffer, frame, aligned_frame, sample_rate);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lockfree::channel::spsc;
+
+    use crate::AudioBufferSlice;
+
+    use super::*;
+
+    const SAMPLE_RATE: usize = 1000;
+    const BLOCK_SIZE: usize = 16;
+
+    struct Harness {
+        processor: RecorderProcessor,
+        events: RecorderEventTransmitter,
+        notifications: Receiver<RecorderNotification>,
+        elapsed_frames: usize,
+    }
+
+    impl Harness {
+        fn new() -> Self {
+            let (events, event_receiver) = spsc::create();
+            let (notification_transmitter, notifications) = spsc::create();
+
+            Self {
+                processor: RecorderProcessor::new(event_receiver, notification_transmitter),
+                events,
+                notifications,
+                elapsed_frames: 0,
+            }
+        }
+
+        fn send(&mut self, event: RecorderEvent) {
+            let _ = self.events.send(event);
+        }
+
+        fn punch_in(&mut self, start: usize, stop: usize, take_length: Option<usize>) {
+            let num_takes = take_length.map_or(1, |length| (stop - start) / length);
+            let buffer_length = take_length.unwrap_or(stop - start);
+
+            self.send(RecorderEvent::PunchIn(PunchSession {
+                punch_in: Timestamp::from_samples(start as f64, SAMPLE_RATE),
+                punch_out: Some(Timestamp::from_samples(stop as f64, SAMPLE_RATE)),
+                take_length,
+                buffers: (0..num_takes)
+                    .map(|_| Some(OwnedAudioBuffer::new(buffer_length, 1, SAMPLE_RATE)))
+                    .collect(),
+            }));
+        }
+
+        fn render(&mut self, num_blocks: usize) {
+            let mut output = OwnedAudioBuffer::new(BLOCK_SIZE, 1, SAMPLE_RATE);
+
+            for _ in 0..num_blocks {
+                let data = (self.elapsed_frames..self.elapsed_frames + BLOCK_SIZE)
+                    .map(|frame| frame as f32)
+                    .collect();
+                let input = OwnedAudioBuffer::new_from_data(data, 1, SAMPLE_RATE);
+
+                self.processor.process_audio(
+                    &input,
+                    &mut AudioBufferSlice::new(&mut output, 0, BLOCK_SIZE),
+                    &Timestamp::from_samples(self.elapsed_frames as f64, SAMPLE_RATE),
+                    &DspParameterMap::new(),
+                );
+                assert_eq!(
+                    output.get_sample(SampleLocation::new(0, 3)),
+                    input.get_sample(SampleLocation::new(0, 3))
+                );
+
+                self.elapsed_frames += BLOCK_SIZE;
+            }
+        }
+
+        fn takes(&mut self) -> Vec<FinishedTake> {
+            let mut takes = Vec::new();
+            while let Ok(notification) = self.notifications.recv() {
+                if let RecorderNotification::Take(take) = notification {
+                    takes.push(take);
+                }
+            }
+            takes
+        }
+    }
+
+    fn first_sample(take: &FinishedTake) -> f32 {
+        take.buffer.get_sample(SampleLocation::new(0, 0))
+    }
+
+    #[test]
+    fn records_with_latency_compensation() {
+        let mut harness = Harness::new();
+        harness.send(RecorderEvent::SetArmed(true));
+        harness.send(RecorderEvent::SetLatencyCompensation(5));
+        harness.punch_in(10, 30, None);
+        harness.render(4);
+
+        let takes = harness.takes();
+        assert_eq!(takes.len(), 1);
+        assert_eq!(takes[0].num_frames, 20);
+        assert_eq!(
+            takes[0].start_time,
+            Timestamp::from_samples(10.0, SAMPLE_RATE)
+        );
+        for frame in 0..takes[0].num_frames {
+            assert_eq!(
+                takes[0].buffer.get_sample(SampleLocation::new(0, frame)),
+                (frame + 15) as f32
+            );
+        }
+    }
+
+    #[test]
+    fn ignores_punches_while_disarmed() {
+        let mut harness = Harness::new();
+        harness.punch_in(0, 20, None);
+        harness.render(4);
+        assert!(harness.takes().is_empty());
+
+        harness.send(RecorderEvent::SetArmed(true));
+        harness.punch_in(70, 90, None);
+        harness.render(4);
+
+        let takes = harness.takes();
+        assert_eq!(takes.len(), 1);
+        assert_eq!(first_sample(&takes[0]), 70.0);
+    }
+
+    #[test]
+    fn records_a_take_per_loop_pass() {
+        let mut harness = Harness::new();
+        harness.send(RecorderEvent::SetArmed(true));
+        harness.punch_in(8, 38, Some(10));
+        harness.render(4);
+
+        let takes = harness.takes();
+        assert_eq!(takes.len(), 3);
+        for (index, take) in takes.iter().enumerate() {
+            assert_eq!(take.take, index);
+            assert_eq!(take.num_frames, 10);
+            assert_eq!(first_sample(take), (8 + 10 * index) as f32);
+        }
+    }
+
+    #[test]
+    fn punches_out_early_and_chains_sessions() {
+        let mut harness = Harness::new();
+        harness.send(RecorderEvent::SetArmed(true));
+        harness.punch_in(0, 40, None);
+        harness.punch_in(20, 40, None);
+        harness.send(RecorderEvent::PunchOut(Timestamp::from_samples(
+            25.0,
+            SAMPLE_RATE,
+        )));
+        harness.render(3);
+
+        let takes = harness.takes();
+        assert_eq!(takes.len(), 2);
+        assert_eq!(takes[0].num_frames, 20);
+        assert_eq!(first_sample(&takes[1]), 20.0);
+        assert_eq!(takes[1].num_frames, 5);
+    }
+}
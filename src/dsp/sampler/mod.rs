@@ -1,4 +1,11 @@
 mod fade;
 pub mod node;
-mod processor;
-mod voice;
+mod note_voice;
+pub mod one_shot;
+pub mod one_shot_node;
+pub mod playlist;
+pub mod playlist_node;
+pub mod processor;
+mod scrub;
+pub(crate) mod voice;
+pub mod voice_limiter;
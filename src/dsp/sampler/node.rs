@@ -1,44 +1,72 @@
-use std::collections::HashMap;
-
-use lockfree::channel::mpsc::Sender;
+use std::{collections::HashMap, sync::Arc};
 
 use crate::{
-    commands::{command::Command, id::Id},
+    commands::{command_queue::CommandQueue, id::Id},
+    events::note_event::{NoteEvent, NoteEventTransmitter},
     graph::{dsp::Dsp, node::Node},
+    parameter::audio_parameter::AudioParameter,
     OwnedAudioBuffer, Timestamp,
 };
 
-use super::processor::{EventTransmitter, SamplerDspProcess, SamplerEvent};
+use super::processor::{EventTransmitter, SamplerDspProcess, SamplerEvent, SharedSample};
 
 pub struct SamplerNode {
-    command_queue: Sender<Command>,
+    command_queue: CommandQueue,
     id: Id,
     event_transmitter: EventTransmitter,
+    note_transmitter: NoteEventTransmitter,
+    pub scrub_position: AudioParameter,
+    pub playback_rate: AudioParameter,
 }
 
+const MIN_PLAYBACK_RATE: f64 = 0.25;
+const MAX_PLAYBACK_RATE: f64 = 4.0;
+
 impl Node for SamplerNode {
     fn get_id(&self) -> Id {
         self.id
     }
 
-    fn get_command_queue(&self) -> Sender<Command> {
+    fn get_command_queue(&self) -> CommandQueue {
         self.command_queue.clone()
     }
 }
 
 impl SamplerNode {
-    pub fn new(
-        command_queue: Sender<Command>,
+    pub fn new(command_queue: CommandQueue, sample_rate: usize, sample: OwnedAudioBuffer) -> Self {
+        Self::with_shared_sample(command_queue, sample_rate, Arc::new(sample))
+    }
+
+    pub fn with_shared_sample(
+        command_queue: CommandQueue,
         sample_rate: usize,
-        sample: OwnedAudioBuffer,
+        sample: SharedSample,
     ) -> Self {
         let id = Id::generate();
 
         let (event_transmitter, event_receiver) = lockfree::channel::spsc::create();
+        let (note_transmitter, note_receiver) = lockfree::channel::mpsc::create();
 
-        let parameters = HashMap::new();
+        let mut parameters = HashMap::new();
 
-        let sampler_process = SamplerDspProcess::new(sample_rate, sample, event_receiver);
+        let duration = sample.num_frames().max(1) as f64 / sample_rate as f64;
+        let (scrub_position, realtime_scrub_position) =
+            AudioParameter::new(id, 0.0, 0.0, duration, command_queue.clone());
+        parameters.insert(realtime_scrub_position.get_id(), realtime_scrub_position);
+
+        let (playback_rate, realtime_playback_rate) = AudioParameter::new(
+            id,
+            1.0,
+            MIN_PLAYBACK_RATE,
+            MAX_PLAYBACK_RATE,
+            command_queue.clone(),
+        );
+        parameters.insert(realtime_playback_rate.get_id(), realtime_playback_rate);
+
+        let sampler_process = SamplerDspProcess::new(sample_rate, sample, event_receiver)
+            .with_scrub_position(scrub_position.get_id())
+            .with_playback_rate(playback_rate.get_id())
+            .with_note_receiver(note_receiver);
 
         let dsp = Dsp::new(id, Box::new(sampler_process), parameters);
 
@@ -48,6 +76,9 @@ impl SamplerNode {
             command_queue,
             id,
             event_transmitter,
+            note_transmitter,
+            scrub_position,
+            playback_rate,
         }
     }
 
@@ -82,6 +113,26 @@ impl SamplerNode {
     pub fn cancel_loop(&mut self) {
         let _ = self.event_transmitter.send(SamplerEvent::cancel_loop());
     }
+
+    pub fn send_note_event(&mut self, event: NoteEvent) {
+        let _ = self.event_transmitter.send(SamplerEvent::note(event));
+    }
+
+    pub fn note_input(&self) -> NoteEventTransmitter {
+        self.note_transmitter.clone()
+    }
+
+    pub fn set_root_pitch(&mut self, pitch: f64) {
+        let _ = self.event_transmitter.send(SamplerEvent::root_pitch(pitch));
+    }
+
+    pub fn start_scrubbing(&mut self) {
+        let _ = self.event_transmitter.send(SamplerEvent::scrub(true));
+    }
+
+    pub fn stop_scrubbing(&mut self) {
+        let _ = self.event_transmitter.send(SamplerEvent::scrub(false));
+    }
 }
 
 impl Drop for SamplerNode {
@@ -0,0 +1,122 @@
+use std::f64::consts::TAU;
+
+use crate::{
+    events::note_event::{NoteEventType, NoteExpression, NoteId},
+    realtime::processor::MAXIMUM_NUMBER_OF_CHANNELS,
+    AudioBuffer, SampleLocation,
+};
+
+use super::{fade::Fade, voice::read_interpolated};
+
+const TIMBRE_MINIMUM_CUTOFF: f64 = 20.0;
+const TIMBRE_MAXIMUM_CUTOFF: f64 = 20_000.0;
+const NEUTRAL_TIMBRE: f64 = 0.5;
+const PRESSURE_DEPTH: f64 = 1.0;
+
+#[derive(Default)]
+pub struct NoteVoice {
+    note_id: Option<NoteId>,
+    expression: Option<NoteExpression>,
+    position: f64,
+    release_position: Option<usize>,
+    age: usize,
+    filter_state: [f32; MAXIMUM_NUMBER_OF_CHANNELS],
+}
+
+impl NoteVoice {
+    pub fn is_active(&self) -> bool {
+        self.note_id.is_some()
+    }
+
+    pub fn get_age(&self) -> usize {
+        self.age
+    }
+
+    pub fn plays(&self, note_id: NoteId) -> bool {
+        self.note_id == Some(note_id)
+    }
+
+    pub fn start(&mut self, note_id: NoteId, pitch: f64, velocity: f64, age: usize) {
+        *self = Self {
+            note_id: Some(note_id),
+            expression: Some(NoteExpression::new(pitch, velocity)),
+            age,
+            ..Self::default()
+        };
+    }
+
+    pub fn apply(&mut self, event_type: &NoteEventType) {
+        match event_type {
+            NoteEventType::NoteOff { .. } => {
+                self.release_position.get_or_insert(0);
+            }
+            event_type => {
+                if let Some(expression) = self.expression.as_mut() {
+                    expression.apply(event_type);
+                }
+            }
+        }
+    }
+
+    fn timbre_coefficient(timbre: f64, sample_rate: usize) -> f32 {
+        if timbre >= NEUTRAL_TIMBRE {
+            return 1.0;
+        }
+
+        let cutoff = TIMBRE_MINIMUM_CUTOFF
+            * (TIMBRE_MAXIMUM_CUTOFF / TIMBRE_MINIMUM_CUTOFF).powf(timbre / NEUTRAL_TIMBRE);
+        (1.0 - (-TAU * cutoff / sample_rate as f64).exp()) as f32
+    }
+
+    pub fn render(
+        &mut self,
+        output: &mut dyn AudioBuffer,
+        sample: &dyn AudioBuffer,
+        fade: &Fade,
+        root_frequency: f64,
+        playback_rate: f64,
+    ) {
+        let expression = match self.expression {
+            Some(expression) if self.is_active() => expression,
+            _ => return,
+        };
+
+        let rate = expression.frequency() / root_frequency * playback_rate;
+        let gain = (expression.velocity * (1.0 + PRESSURE_DEPTH * expression.pressure)) as f32;
+        let coefficient = Self::timbre_coefficient(expression.timbre, output.sample_rate());
+        let num_channels = sample
+            .num_channels()
+            .min(output.num_channels())
+            .min(MAXIMUM_NUMBER_OF_CHANNELS);
+
+        for frame in 0..output.num_frames() {
+            if self.position >= sample.num_frames() as f64 {
+                self.note_id = None;
+                return;
+            }
+
+            let envelope = match self.release_position {
+                Some(position) if position >= fade.len() => {
+                    self.note_id = None;
+                    return;
+                }
+                Some(position) => fade.fade_out_value(position),
+                None => 1.0,
+            };
+
+            for channel in 0..num_channels {
+                let input = read_interpolated(sample, channel, self.position);
+                let state = &mut self.filter_state[channel];
+                *state += coefficient * (input - *state);
+
+                let location = SampleLocation::new(channel, frame);
+                output.add_sample(location, *state * gain * envelope);
+            }
+
+            self.position += rate;
+            if let Some(position) = self.release_position.as_mut() {
+                *position += 1;
+            }
+        }
+    }
+}
@@ -0,0 +1,551 @@
+use std::{f64::consts::FRAC_PI_4, sync::atomic::Ordering, time::Duration};
+
+use crate::{
+    events::event_batch::{EventBatchReceiver, TimedEvent},
+    graph::dsp::{DspParameterMap, DspProcessor},
+    realtime::garbage_collector::GarbageCollectionCommand,
+    utility::realtime_log::{self, LogLevel},
+    AudioBuffer, SampleLocation, Timestamp,
+};
+
+use super::{
+    processor::SharedSample,
+    voice_limiter::{find_least_important, find_voice_to_cull, VoiceBudget, VoiceImportance},
+};
+
+pub type OneShotEventReceiver = lockfree::channel::spsc::Receiver<OneShotEvent>;
+pub type OneShotEventTransmitter = lockfree::channel::spsc::Sender<OneShotEvent>;
+
+pub const MAX_ONE_SHOT_VOICES: usize = 256;
+pub const DEFAULT_MAX_VOICES: usize = 64;
+const MAX_RETIRED_SAMPLES: usize = 3 * MAX_ONE_SHOT_VOICES;
+const MIN_PITCH: f64 = 0.01;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OneShotOptions {
+    pub gain: f64,
+    pub pan: f64,
+    pub pitch: f64,
+    pub delay: Duration,
+    pub priority: i32,
+}
+
+impl Default for OneShotOptions {
+    fn default() -> Self {
+        Self {
+            gain: 1.0,
+            pan: 0.0,
+            pitch: 1.0,
+            delay: Duration::ZERO,
+            priority: 0,
+        }
+    }
+}
+
+pub struct OneShotEvent {
+    sample: SharedSample,
+    options: OneShotOptions,
+    start_time: Timestamp,
+}
+
+impl OneShotEvent {
+    pub fn new(sample: SharedSample, options: OneShotOptions, start_time: Timestamp) -> Self {
+        Self {
+            sample,
+            options,
+            start_time,
+        }
+    }
+}
+
+impl TimedEvent for OneShotEvent {
+    fn event_time(&self) -> Timestamp {
+        self.start_time
+    }
+}
+
+struct OneShotVoice {
+    sample: SharedSample,
+    start_time: Timestamp,
+    channel_gains: [f32; 2],
+    increment: f64,
+    position: f64,
+    importance: VoiceImportance,
+}
+
+impl OneShotVoice {
+    fn new(event: OneShotEvent, sample_rate: usize, started_order: u64) -> Self {
+        let angle = (event.options.pan.clamp(-1.0, 1.0) + 1.0) * FRAC_PI_4;
+        let gain = event.options.gain;
+        let rate_ratio = event.sample.sample_rate() as f64 / sample_rate as f64;
+
+        Self {
+            channel_gains: [(gain * angle.cos()) as f32, (gain * angle.sin()) as f32],
+            increment: event.options.pitch.max(MIN_PITCH) * rate_ratio,
+            start_time: event.start_time,
+            sample: event.sample,
+            position: 0.0,
+            importance: VoiceImportance {
+                priority: event.options.priority,
+                loudness: gain.abs(),
+                started_order,
+            },
+        }
+    }
+
+    fn is_finished(&self) -> bool {
+        self.position >= (self.sample.num_frames() as f64 - 1.0).max(0.0)
+    }
+
+    fn read(&self, channel: usize) -> f32 {
+        let channel = channel.min(self.sample.num_channels() - 1);
+        let index = self.position as usize;
+        let fraction = (self.position - index as f64) as f32;
+        let next_index = (index + 1).min(self.sample.num_frames() - 1);
+
+        let a = self.sample.get_sample(SampleLocation::new(channel, index));
+        let b = self
+            .sample
+            .get_sample(SampleLocation::new(channel, next_index));
+        a + (b - a) * fraction
+    }
+
+    fn render(&mut self, output_buffer: &mut dyn AudioBuffer, start_time: &Timestamp) {
+        let sample_rate = output_buffer.sample_rate();
+        let num_output_channels = output_buffer.num_channels();
+
+        for frame in 0..output_buffer.num_frames() {
+            if self.is_finished() {
+                return;
+            }
+
+            if start_time.incremented_by_samples(frame, sample_rate) < self.start_time {
+                continue;
+            }
+
+            for channel in 0..num_output_channels {
+                let gain = if num_output_channels == 2 {
+                    self.channel_gains[channel]
+                } else {
+                    self.channel_gains[0].max(self.channel_gains[1])
+                };
+
+                output_buffer.add_sample(
+                    SampleLocation::new(channel, frame),
+                    self.read(channel) * gain,
+                );
+            }
+
+            self.position += self.increment;
+        }
+    }
+}
+
+pub struct OneShotPoolProcessor {
+    voices: Vec<OneShotVoice>,
+    retired_samples: Vec<SharedSample>,
+    due_events: Vec<OneShotEvent>,
+    started_count: u64,
+    voice_budget: VoiceBudget,
+    event_receiver: OneShotEventReceiver,
+    batch_receiver: EventBatchReceiver<OneShotEvent>,
+    stopped: bool,
+}
+
+impl OneShotPoolProcessor {
+    pub fn new(
+        event_receiver: OneShotEventReceiver,
+        batch_receiver: EventBatchReceiver<OneShotEvent>,
+        voice_budget: VoiceBudget,
+    ) -> Self {
+        Self {
+            voices: Vec::with_capacity(MAX_ONE_SHOT_VOICES),
+            retired_samples: Vec::with_capacity(MAX_RETIRED_SAMPLES),
+            due_events: Vec::with_capacity(MAX_RETIRED_SAMPLES - MAX_ONE_SHOT_VOICES),
+            started_count: 0,
+            voice_budget,
+            event_receiver,
+            batch_receiver,
+            stopped: false,
+        }
+    }
+
+    fn max_voices(&self) -> usize {
+        self.voice_budget
+            .load(Ordering::Acquire)
+            .min(MAX_ONE_SHOT_VOICES)
+    }
+
+    fn retire(retired_samples: &mut Vec<SharedSample>, sample: SharedSample) {
+        if retired_samples.len() == retired_samples.capacity() {
+            realtime_log::log(LogLevel::Warning, "One-shot retired samples full");
+            return;
+        }
+
+        retired_samples.push(sample);
+    }
+
+    fn event_headroom(&self) -> usize {
+        (MAX_RETIRED_SAMPLES - MAX_ONE_SHOT_VOICES).saturating_sub(self.retired_samples.len())
+    }
+
+    fn start_voice(&mut self, event: OneShotEvent, sample_rate: usize) {
+        if self.stopped || event.sample.num_frames() == 0 || event.sample.num_channels() == 0 {
+            Self::retire(&mut self.retired_samples, event.sample);
+            return;
+        }
+
+        self.started_count += 1;
+        let voice = OneShotVoice::new(event, sample_rate, self.started_count);
+
+        if self.voices.len() >= self.max_voices() {
+            let importances = self.voices.iter().map(|voice| voice.importance);
+
+            match find_voice_to_cull(importances, &voice.importance) {
+                Some(index) => {
+                    let culled = self.voices.swap_remove(index);
+                    Self::retire(&mut self.retired_samples, culled.sample);
+                }
+                None => {
+                    Self::retire(&mut self.retired_samples, voice.sample);
+                    return;
+                }
+            }
+        }
+
+        self.voices.push(voice);
+    }
+
+    fn read_events(&mut self, sample_rate: usize, end_time: Timestamp) {
+        while self.event_headroom() > 0 {
+            match self.event_receiver.recv() {
+                Ok(event) => self.start_voice(event, sample_rate),
+                Err(_) => break,
+            }
+        }
+
+        let mut due_events = std::mem::take(&mut self.due_events);
+        self.batch_receiver
+            .drain_until(end_time, self.event_headroom(), |event| {
+                due_events.push(event)
+            });
+
+        for event in due_events.drain(..) {
+            self.start_voice(event, sample_rate);
+        }
+        self.due_events = due_events;
+    }
+
+    fn cull_voices_over_budget(&mut self) {
+        while self.voices.len() > self.max_voices() {
+            let importances = self.voices.iter().map(|voice| voice.importance);
+
+            match find_least_important(importances) {
+                Some(index) => {
+                    let culled = self.voices.swap_remove(index);
+                    Self::retire(&mut self.retired_samples, culled.sample);
+                }
+                None => return,
+            }
+        }
+    }
+
+    fn retire_voices_where(&mut self, mut should_retire: impl FnMut(&OneShotVoice) -> bool) {
+        let mut index = 0;
+        while index < self.voices.len() {
+            if should_retire(&self.voices[index]) {
+                let voice = self.voices.swap_remove(index);
+                Self::retire(&mut self.retired_samples, voice.sample);
+            } else {
+                index += 1;
+            }
+        }
+    }
+}
+
+impl DspProcessor for OneShotPoolProcessor {
+    fn process_audio(
+        &mut self,
+        _input_buffer: &dyn AudioBuffer,
+        output_buffer: &mut dyn AudioBuffer,
+        start_time: &Timestamp,
+        _parameters: &DspParameterMap,
+    ) {
+        let sample_rate = output_buffer.sample_rate();
+        let end_time = start_time.incremented_by_samples(output_buffer.num_frames(), sample_rate);
+
+        self.cull_voices_over_budget();
+        self.read_events(sample_rate, end_time);
+
+        for voice in self.voices.iter_mut() {
+            voice.render(output_buffer, start_time);
+        }
+
+        self.retire_voices_where(|voice| voice.is_finished());
+    }
+
+    fn stop(&mut self, stop_time: &Timestamp, allow_tail: bool) {
+        self.stopped = true;
+
+        if allow_tail {
+            let stop_time = *stop_time;
+            self.retire_voices_where(|voice| voice.start_time >= stop_time);
+        } else {
+            self.retire_voices_where(|_| true);
+        }
+    }
+
+    fn drain_garbage(&mut self, dispose: &mut dyn FnMut(GarbageCollectionCommand)) {
+        for sample in self.retired_samples.drain(..) {
+            dispose(GarbageCollectionCommand::DisposeSample(sample));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{atomic::AtomicUsize, Arc};
+
+    use approx::assert_relative_eq;
+
+    use crate::{
+        events::event_batch::{create_event_batch_channel, DEFAULT_BATCH_CHUNK_SIZE},
+        OwnedAudioBuffer,
+    };
+
+    use super::*;
+
+    fn make_budget(max_voices: usize) -> VoiceBudget {
+        VoiceBudget::new(AtomicUsize::new(max_voices))
+    }
+
+    fn make_pool(budget: VoiceBudget) -> (OneShotEventTransmitter, OneShotPoolProcessor) {
+        let (event_transmitter, event_receiver) = lockfree::channel::spsc::create();
+        let (_, batch_receiver) = create_event_batch_channel(DEFAULT_BATCH_CHUNK_SIZE);
+        (
+            event_transmitter,
+            OneShotPoolProcessor::new(event_receiver, batch_receiver, budget),
+        )
+    }
+
+    fn make_sample(num_frames: usize) -> SharedSample {
+        let mut sample = OwnedAudioBuffer::new(num_frames, 1, 100);
+        sample.fill_with_value(1.0);
+        Arc::new(sample)
+    }
+
+    fn process(pool: &mut OneShotPoolProcessor, start_frame: usize) -> OwnedAudioBuffer {
+        let input_buffer = OwnedAudioBuffer::new(50, 2, 100);
+        let mut output_buffer = OwnedAudioBuffer::new(50, 2, 100);
+        pool.process_audio(
+            &input_buffer,
+            &mut output_buffer,
+            &Timestamp::from_samples(start_frame as f64, 100),
+            &DspParameterMap::new(),
+        );
+        output_buffer
+    }
+
+    #[test]
+    fn plays_delayed_panned_one_shot_and_reclaims_voice() {
+        let (mut event_transmitter, mut pool) = make_pool(make_budget(MAX_ONE_SHOT_VOICES));
+
+        let options = OneShotOptions {
+            pan: 1.0,
+            ..Default::default()
+        };
+        let _ = event_transmitter.send(OneShotEvent::new(
+            make_sample(20),
+            options,
+            Timestamp::from_samples(10.0, 100),
+        ));
+
+        let output = process(&mut pool, 0);
+        assert_eq!(output.get_sample(SampleLocation::new(1, 9)), 0.0);
+        assert_relative_eq!(output.get_sample(SampleLocation::new(1, 10)), 1.0);
+        assert_relative_eq!(
+            output.get_sample(SampleLocation::new(0, 10)),
+            0.0,
+            epsilon = 1e-6
+        );
+        assert_eq!(pool.voices.len(), 0);
+    }
+
+    #[test]
+    fn pitch_changes_playback_length() {
+        let (mut event_transmitter, mut pool) = make_pool(make_budget(MAX_ONE_SHOT_VOICES));
+
+        let options = OneShotOptions {
+            pitch: 2.0,
+            ..Default::default()
+        };
+        let _ = event_transmitter.send(OneShotEvent::new(
+            make_sample(41),
+            options,
+            Timestamp::zero(),
+        ));
+
+        let output = process(&mut pool, 0);
+        assert!(output.get_sample(SampleLocation::new(0, 19)) > 0.0);
+        assert_eq!(output.get_sample(SampleLocation::new(0, 20)), 0.0);
+    }
+
+    #[test]
+    fn steals_oldest_voice_when_pool_is_full() {
+        let (mut event_transmitter, mut pool) = make_pool(make_budget(MAX_ONE_SHOT_VOICES));
+
+        for _ in 0..MAX_ONE_SHOT_VOICES + 4 {
+            let _ = event_transmitter.send(OneShotEvent::new(
+                make_sample(1000),
+                OneShotOptions::default(),
+                Timestamp::zero(),
+            ));
+        }
+
+        process(&mut pool, 0);
+        assert_eq!(pool.voices.len(), MAX_ONE_SHOT_VOICES);
+    }
+
+    #[test]
+    fn keeps_higher_priority_voices_within_budget() {
+        let budget = make_budget(2);
+        let (mut event_transmitter, mut pool) = make_pool(budget.clone());
+
+        for priority in [1, 3, 0, 2] {
+            let options = OneShotOptions {
+                priority,
+                ..Default::default()
+            };
+            let _ = event_transmitter.send(OneShotEvent::new(
+                make_sample(1000),
+                options,
+                Timestamp::zero(),
+            ));
+        }
+
+        process(&mut pool, 0);
+
+        let mut priorities: Vec<i32> = pool
+            .voices
+            .iter()
+            .map(|voice| voice.importance.priority)
+            .collect();
+        priorities.sort();
+        assert_eq!(priorities, vec![2, 3]);
+
+        budget.store(1, Ordering::Release);
+        process(&mut pool, 50);
+        assert_eq!(pool.voices.len(), 1);
+        assert_eq!(pool.voices[0].importance.priority, 3);
+    }
+
+    #[test]
+    fn starts_batched_one_shots_when_due() {
+        let (_event_transmitter, event_receiver) = lockfree::channel::spsc::create();
+        let (mut batch_sender, batch_receiver) = create_event_batch_channel(2);
+        let mut pool = OneShotPoolProcessor::new(
+            event_receiver,
+            batch_receiver,
+            make_budget(MAX_ONE_SHOT_VOICES),
+        );
+
+        batch_sender.send_batch((0..5).map(|index| {
+            OneShotEvent::new(
+                make_sample(10),
+                OneShotOptions::default(),
+                Timestamp::from_samples(index as f64 * 30.0, 100),
+            )
+        }));
+
+        let output = process(&mut pool, 0);
+        assert_relative_eq!(
+            output.get_sample(SampleLocation::new(0, 30)),
+            FRAC_PI_4.cos() as f32
+        );
+        assert_eq!(pool.voices.len(), 0);
+
+        let output = process(&mut pool, 50);
+        assert!(output.get_sample(SampleLocation::new(0, 10)) > 0.0);
+        assert_eq!(output.get_sample(SampleLocation::new(0, 20)), 0.0);
+        assert!(output.get_sample(SampleLocation::new(0, 40)) > 0.0);
+    }
+
+    #[test]
+    fn stopping_rejects_new_one_shots_and_keeps_tails() {
+        let (mut event_transmitter, mut pool) = make_pool(make_budget(MAX_ONE_SHOT_VOICES));
+
+        for start_frame in [0.0, 30.0] {
+            let _ = event_transmitter.send(OneShotEvent::new(
+                make_sample(200),
+                OneShotOptions::default(),
+                Timestamp::from_samples(start_frame, 100),
+            ));
+        }
+        process(&mut pool, 0);
+        assert_eq!(pool.voices.len(), 2);
+
+        pool.stop(&Timestamp::from_samples(20.0, 100), true);
+        assert_eq!(pool.voices.len(), 1);
+
+        let _ = event_transmitter.send(OneShotEvent::new(
+            make_sample(200),
+            OneShotOptions::default(),
+            Timestamp::from_samples(50.0, 100),
+        ));
+        let output = process(&mut pool, 50);
+        assert!(output.get_sample(SampleLocation::new(0, 10)) > 0.0);
+        assert_eq!(pool.voices.len(), 1);
+
+        pool.stop(&Timestamp::from_samples(100.0, 100), false);
+        assert!(pool.voices.is_empty());
+    }
+
+    #[test]
+    fn zero_pitch_still_finishes() {
+        let (mut event_transmitter, mut pool) = make_pool(make_budget(MAX_ONE_SHOT_VOICES));
+
+        let options = OneShotOptions {
+            pitch: 0.0,
+            ..Default::default()
+        };
+        let _ = event_transmitter.send(OneShotEvent::new(
+            make_sample(2),
+            options,
+            Timestamp::zero(),
+        ));
+
+        for block in 0..3 {
+            process(&mut pool, block * 50);
+        }
+        assert!(pool.voices.is_empty());
+    }
+
+    #[test]
+    fn hands_finished_and_culled_samples_to_the_garbage_collector() {
+        let (mut event_transmitter, mut pool) = make_pool(make_budget(1));
+        let sample = make_sample(10);
+
+        for priority in [0, 1] {
+            let options = OneShotOptions {
+                priority,
+                ..Default::default()
+            };
+            let _ = event_transmitter.send(OneShotEvent::new(
+                sample.clone(),
+                options,
+                Timestamp::zero(),
+            ));
+        }
+
+        process(&mut pool, 0);
+        assert!(pool.voices.is_empty());
+        assert_eq!(Arc::strong_count(&sample), 3);
+
+        let mut disposed = Vec::new();
+        pool.drain_garbage(&mut |command| disposed.push(command));
+
+        assert_eq!(disposed.len(), 2);
+        drop(disposed);
+        assert_eq!(Arc::strong_count(&sample), 1);
+    }
+}
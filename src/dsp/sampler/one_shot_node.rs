@@ -0,0 +1,87 @@
+use std::collections::HashMap;
+
+use crate::{
+    commands::{command::Command, command_queue::CommandQueue, id::Id},
+    events::event_batch::{create_event_batch_channel, EventBatchSender, DEFAULT_BATCH_CHUNK_SIZE},
+    graph::{dsp::Dsp, node::Node},
+    Timestamp,
+};
+
+use super::{
+    one_shot::{OneShotEvent, OneShotEventTransmitter, OneShotOptions, OneShotPoolProcessor},
+    processor::SharedSample,
+    voice_limiter::VoiceBudget,
+};
+
+pub struct OneShotPoolNode {
+    command_queue: CommandQueue,
+    id: Id,
+    event_transmitter: OneShotEventTransmitter,
+    batch_sender: EventBatchSender<OneShotEvent>,
+}
+
+impl Node for OneShotPoolNode {
+    fn get_id(&self) -> Id {
+        self.id
+    }
+
+    fn get_command_queue(&self) -> CommandQueue {
+        self.command_queue.clone()
+    }
+}
+
+impl OneShotPoolNode {
+    pub fn new(command_queue: CommandQueue, voice_budget: VoiceBudget) -> Self {
+        let id = Id::generate();
+
+        let (event_transmitter, event_receiver) = lockfree::channel::spsc::create();
+        let (batch_sender, batch_receiver) = create_event_batch_channel(DEFAULT_BATCH_CHUNK_SIZE);
+
+        let dsp = Dsp::new(
+            id,
+            Box::new(OneShotPoolProcessor::new(
+                event_receiver,
+                batch_receiver,
+                voice_budget,
+            )),
+            HashMap::new(),
+        );
+
+        let _ = command_queue.send_validated(Command::AddDsp(Box::new(dsp)));
+
+        Self {
+            command_queue,
+            id,
+            event_transmitter,
+            batch_sender,
+        }
+    }
+
+    pub fn play_at_time(
+        &mut self,
+        sample: SharedSample,
+        options: OneShotOptions,
+        start_time: Timestamp,
+    ) {
+        let _ = self
+            .event_transmitter
+            .send(OneShotEvent::new(sample, options, start_time));
+    }
+
+    pub fn play_batch(
+        &mut self,
+        one_shots: impl IntoIterator<Item = (SharedSample, OneShotOptions, Timestamp)>,
+    ) {
+        self.batch_sender.send_batch(
+            one_shots.into_iter().map(|(sample, options, start_time)| {
+                OneShotEvent::new(sample, options, start_time)
+            }),
+        );
+    }
+}
+
+impl Drop for OneShotPoolNode {
+    fn drop(&mut self) {
+        Dsp::remove_from_audio_process(self.id, &self.command_queue);
+    }
+}
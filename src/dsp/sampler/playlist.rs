@@ -0,0 +1,398 @@
+use std::time::Duration;
+
+use crate::{
+    graph::dsp::{DspParameterMap, DspProcessor},
+    AudioBuffer, SampleLocation, Timestamp,
+};
+
+use super::{fade::Fade, processor::SharedSample};
+
+pub type PlaylistEventReceiver = lockfree::channel::spsc::Receiver<PlaylistEvent>;
+pub type PlaylistEventTransmitter = lockfree::channel::spsc::Sender<PlaylistEvent>;
+
+const MAX_PLAYLIST_ITEMS: usize = 256;
+const MAX_PENDING_EVENTS: usize = 16;
+
+#[derive(Clone)]
+pub struct PlaylistItem {
+    sample: SharedSample,
+    start_frame: usize,
+    end_frame: usize,
+}
+
+impl PlaylistItem {
+    pub fn new(sample: SharedSample) -> Self {
+        let end_frame = sample.num_frames();
+
+        Self {
+            sample,
+            start_frame: 0,
+            end_frame,
+        }
+    }
+
+    pub fn with_region(mut self, start: Timestamp, end: Timestamp) -> Self {
+        let sample_rate = self.sample.sample_rate();
+        let num_frames = self.sample.num_frames();
+
+        self.end_frame = (end.get_samples(sample_rate).round() as usize).min(num_frames);
+        self.start_frame = (start.get_samples(sample_rate).round() as usize).min(self.end_frame);
+        self
+    }
+
+    fn len(&self) -> usize {
+        self.end_frame - self.start_frame
+    }
+}
+
+pub enum PlaylistEventType {
+    Enqueue(PlaylistItem),
+    Start,
+    Stop,
+    Clear,
+}
+
+pub struct PlaylistEvent {
+    time: Timestamp,
+    event_type: PlaylistEventType,
+}
+
+impl PlaylistEvent {
+    pub fn enqueue(item: PlaylistItem) -> Self {
+        Self {
+            time: Timestamp::zero(),
+            event_type: PlaylistEventType::Enqueue(item),
+        }
+    }
+
+    pub fn start(start_at_time: Timestamp) -> Self {
+        Self {
+            time: start_at_time,
+            event_type: PlaylistEventType::Start,
+        }
+    }
+
+    pub fn stop(stop_at_time: Timestamp) -> Self {
+        Self {
+            time: stop_at_time,
+            event_type: PlaylistEventType::Stop,
+        }
+    }
+
+    pub fn clear() -> Self {
+        Self {
+            time: Timestamp::zero(),
+            event_type: PlaylistEventType::Clear,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Envelope {
+    Full,
+    FadingIn(usize),
+    FadingOut(usize),
+}
+
+#[derive(Clone, Copy)]
+struct PlaylistVoice {
+    item: usize,
+    position: usize,
+    envelope: Envelope,
+}
+
+impl PlaylistVoice {
+    fn new(item: usize, envelope: Envelope) -> Self {
+        Self {
+            item,
+            position: 0,
+            envelope,
+        }
+    }
+
+    fn gain(&self, crossfade: &Fade) -> f32 {
+        match self.envelope {
+            Envelope::Full => 1.0,
+            Envelope::FadingIn(position) => crossfade.fade_in_value(position),
+            Envelope::FadingOut(position) => crossfade.fade_out_value(position),
+        }
+    }
+
+    fn advance(&mut self, crossfade: &Fade) -> bool {
+        self.position += 1;
+
+        match self.envelope {
+            Envelope::Full => true,
+            Envelope::FadingIn(position) => {
+                self.envelope = if position + 1 < crossfade.len() {
+                    Envelope::FadingIn(position + 1)
+                } else {
+                    Envelope::Full
+                };
+                true
+            }
+            Envelope::FadingOut(position) => {
+                self.envelope = Envelope::FadingOut(position + 1);
+                position + 1 < crossfade.len()
+            }
+        }
+    }
+}
+
+pub struct PlaylistDspProcess {
+    items: Vec<PlaylistItem>,
+    crossfade: Fade,
+    active: Option<PlaylistVoice>,
+    outgoing: Option<PlaylistVoice>,
+    event_receiver: PlaylistEventReceiver,
+    pending_events: Vec<PlaylistEvent>,
+}
+
+impl PlaylistDspProcess {
+    pub fn new(
+        sample_rate: usize,
+        crossfade_length: Duration,
+        event_receiver: PlaylistEventReceiver,
+    ) -> Self {
+        Self {
+            items: Vec::with_capacity(MAX_PLAYLIST_ITEMS),
+            crossfade: Fade::new(crossfade_length, sample_rate),
+            active: None,
+            outgoing: None,
+            event_receiver,
+            pending_events: Vec::with_capacity(MAX_PENDING_EVENTS),
+        }
+    }
+
+    fn read_events(&mut self) {
+        let mut sort_required = false;
+
+        while let Ok(event) = self.event_receiver.recv() {
+            if self.pending_events.len() < self.pending_events.capacity() {
+                self.pending_events.push(event);
+                sort_required = true;
+            }
+        }
+
+        if sort_required {
+            self.pending_events.sort_by_key(|event| event.time);
+        }
+    }
+
+    fn process_events_until(&mut self, time: &Timestamp) {
+        while self
+            .pending_events
+            .first()
+            .map(|event| event.time <= *time)
+            .unwrap_or(false)
+        {
+            let event = self.pending_events.remove(0);
+            self.process_event(event);
+        }
+    }
+
+    fn process_event(&mut self, event: PlaylistEvent) {
+        match event.event_type {
+            PlaylistEventType::Enqueue(item) => {
+                if self.items.len() < self.items.capacity() {
+                    self.items.push(item);
+                }
+            }
+            PlaylistEventType::Start => {
+                self.outgoing = None;
+                self.active = self
+                    .first_playable_item(0)
+                    .map(|item| PlaylistVoice::new(item, Envelope::Full));
+            }
+            PlaylistEventType::Stop => self.stop(),
+            PlaylistEventType::Clear => {
+                self.active = None;
+                self.outgoing = None;
+                self.items.clear();
+            }
+        }
+    }
+
+    fn stop(&mut self) {
+        self.outgoing = match self.active.take() {
+            Some(voice) if self.crossfade.len() > 0 => Some(PlaylistVoice {
+                envelope: Envelope::FadingOut(0),
+                ..voice
+            }),
+            _ => None,
+        };
+    }
+
+    fn first_playable_item(&self, from_item: usize) -> Option<usize> {
+        (from_item..self.items.len()).find(|index| self.items[*index].len() > 0)
+    }
+
+    fn update_transitions(&mut self) {
+        let active = match self.active {
+            Some(active) => active,
+            None => return,
+        };
+
+        let remaining = self.items[active.item]
+            .len()
+            .saturating_sub(active.position);
+        let next_item = self.first_playable_item(active.item + 1);
+        let crossfade_length = self.crossfade.len();
+
+        match next_item {
+            Some(next_item)
+                if crossfade_length > 0
+                    && remaining <= crossfade_length
+                    && self.outgoing.is_none() =>
+            {
+                self.outgoing = Some(PlaylistVoice {
+                    envelope: Envelope::FadingOut(crossfade_length - remaining),
+                    ..active
+                });
+                self.active = Some(PlaylistVoice::new(
+                    next_item,
+                    Envelope::FadingIn(crossfade_length - remaining),
+                ));
+            }
+            Some(next_item) if remaining == 0 => {
+                self.active = Some(PlaylistVoice::new(next_item, Envelope::Full));
+            }
+            None if remaining == 0 => self.active = None,
+            _ => (),
+        }
+    }
+
+    fn render_voice(
+        items: &[PlaylistItem],
+        voice: &PlaylistVoice,
+        crossfade: &Fade,
+        output_buffer: &mut dyn AudioBuffer,
+        frame: usize,
+    ) {
+        let item = &items[voice.item];
+        if voice.position >= item.len() {
+            return;
+        }
+
+        let gain = voice.gain(crossfade);
+        let num_channels = item.sample.num_channels().min(output_buffer.num_channels());
+
+        for channel in 0..num_channels {
+            let value = item.sample.get_sample(SampleLocation::new(
+                channel,
+                item.start_frame + voice.position,
+            ));
+            output_buffer.add_sample(SampleLocation::new(channel, frame), gain * value);
+        }
+    }
+}
+
+impl DspProcessor for PlaylistDspProcess {
+    fn process_audio(
+        &mut self,
+        _input_buffer: &dyn AudioBuffer,
+        output_buffer: &mut dyn AudioBuffer,
+        start_time: &Timestamp,
+        _parameters: &DspParameterMap,
+    ) {
+        let sample_rate = output_buffer.sample_rate();
+
+        self.read_events();
+
+        for frame in 0..output_buffer.num_frames() {
+            self.process_events_until(&start_time.incremented_by_samples(frame, sample_rate));
+            self.update_transitions();
+
+            if let Some(voice) = self.outgoing.as_ref() {
+                Self::render_voice(&self.items, voice, &self.crossfade, output_buffer, frame);
+            }
+
+            if let Some(voice) = self.active.as_ref() {
+                Self::render_voice(&self.items, voice, &self.crossfade, output_buffer, frame);
+            }
+
+            if let Some(voice) = self.outgoing.as_mut() {
+                if !voice.advance(&self.crossfade) {
+                    self.outgoing = None;
+                }
+            }
+
+            if let Some(voice) = self.active.as_mut() {
+                voice.advance(&self.crossfade);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use approx::assert_relative_eq;
+
+    use crate::OwnedAudioBuffer;
+
+    use super::*;
+
+    fn make_item(num_frames: usize, value: f32) -> PlaylistItem {
+        let mut sample = OwnedAudioBuffer::new(num_frames, 1, 1000);
+        sample.fill_with_value(value);
+        PlaylistItem::new(Arc::new(sample))
+    }
+
+    fn process(playlist: &mut PlaylistDspProcess, num_frames: usize) -> OwnedAudioBuffer {
+        let input_buffer = OwnedAudioBuffer::new(num_frames, 1, 1000);
+        let mut output_buffer = OwnedAudioBuffer::new(num_frames, 1, 1000);
+        playlist.process_audio(
+            &input_buffer,
+            &mut output_buffer,
+            &Timestamp::zero(),
+            &DspParameterMap::new(),
+        );
+        output_buffer
+    }
+
+    #[test]
+    fn plays_items_back_to_back() {
+        let (mut event_transmitter, event_receiver) = lockfree::channel::spsc::create();
+        let mut playlist = PlaylistDspProcess::new(1000, Duration::ZERO, event_receiver);
+
+        let _ = event_transmitter.send(PlaylistEvent::enqueue(make_item(10, 1.0)));
+        let _ = event_transmitter.send(PlaylistEvent::enqueue(make_item(20, 2.0).with_region(
+            Timestamp::from_seconds(0.005),
+            Timestamp::from_seconds(0.015),
+        )));
+        let _ = event_transmitter.send(PlaylistEvent::start(Timestamp::from_seconds(0.005)));
+
+        let output = process(&mut playlist, 40);
+
+        assert_eq!(output.get_sample(SampleLocation::new(0, 4)), 0.0);
+        assert_eq!(output.get_sample(SampleLocation::new(0, 5)), 1.0);
+        assert_eq!(output.get_sample(SampleLocation::new(0, 14)), 1.0);
+        assert_eq!(output.get_sample(SampleLocation::new(0, 15)), 2.0);
+        assert_eq!(output.get_sample(SampleLocation::new(0, 24)), 2.0);
+        assert_eq!(output.get_sample(SampleLocation::new(0, 25)), 0.0);
+    }
+
+    #[test]
+    fn crossfades_between_items() {
+        let (mut event_transmitter, event_receiver) = lockfree::channel::spsc::create();
+        let mut playlist = PlaylistDspProcess::new(1000, Duration::from_millis(10), event_receiver);
+
+        let _ = event_transmitter.send(PlaylistEvent::enqueue(make_item(30, 1.0)));
+        let _ = event_transmitter.send(PlaylistEvent::enqueue(make_item(30, 1.0)));
+        let _ = event_transmitter.send(PlaylistEvent::start(Timestamp::zero()));
+
+        let output = process(&mut playlist, 60);
+
+        for frame in 0..50 {
+            assert_relative_eq!(
+                output.get_sample(SampleLocation::new(0, frame)),
+                1.0,
+                epsilon = 1e-3
+            );
+        }
+
+        assert_eq!(output.get_sample(SampleLocation::new(0, 50)), 0.0);
+    }
+}
@@ -0,0 +1,81 @@
+use std::{collections::HashMap, time::Duration};
+
+use crate::{
+    commands::{command_queue::CommandQueue, id::Id},
+    graph::{dsp::Dsp, node::Node},
+    Timestamp,
+};
+
+use super::playlist::{PlaylistDspProcess, PlaylistEvent, PlaylistEventTransmitter, PlaylistItem};
+
+pub struct PlaylistNode {
+    command_queue: CommandQueue,
+    id: Id,
+    event_transmitter: PlaylistEventTransmitter,
+}
+
+impl Node for PlaylistNode {
+    fn get_id(&self) -> Id {
+        self.id
+    }
+
+    fn get_command_queue(&self) -> CommandQueue {
+        self.command_queue.clone()
+    }
+}
+
+impl PlaylistNode {
+    pub fn new(command_queue: CommandQueue, sample_rate: usize, crossfade: Duration) -> Self {
+        let id = Id::generate();
+
+        let (event_transmitter, event_receiver) = lockfree::channel::spsc::create();
+
+        let playlist_process = PlaylistDspProcess::new(sample_rate, crossfade, event_receiver);
+
+        let dsp = Dsp::new(id, Box::new(playlist_process), HashMap::new());
+
+        Dsp::add_to_audio_process(dsp, &command_queue);
+
+        Self {
+            command_queue,
+            id,
+            event_transmitter,
+        }
+    }
+
+    pub fn enqueue(&mut self, item: PlaylistItem) {
+        let _ = self.event_transmitter.send(PlaylistEvent::enqueue(item));
+    }
+
+    pub fn start_now(&mut self) {
+        let _ = self
+            .event_transmitter
+            .send(PlaylistEvent::start(Timestamp::zero()));
+    }
+
+    pub fn start_at_time(&mut self, start_time: Timestamp) {
+        let _ = self
+            .event_transmitter
+            .send(PlaylistEvent::start(start_time));
+    }
+
+    pub fn stop_now(&mut self) {
+        let _ = self
+            .event_transmitter
+            .send(PlaylistEvent::stop(Timestamp::zero()));
+    }
+
+    pub fn stop_at_time(&mut self, stop_time: Timestamp) {
+        let _ = self.event_transmitter.send(PlaylistEvent::stop(stop_time));
+    }
+
+    pub fn clear(&mut self) {
+        let _ = self.event_transmitter.send(PlaylistEvent::clear());
+    }
+}
+
+impl Drop for PlaylistNode {
+    fn drop(&mut self) {
+        Dsp::remove_from_audio_process(self.id, &self.command_queue);
+    }
+}
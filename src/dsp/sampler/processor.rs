@@ -1,12 +1,19 @@
-use std::time::Duration;
+use std::{sync::Arc, time::Duration};
 
 use crate::{
+    commands::id::Id,
+    events::note_event::{NoteEvent, NoteEventReceiver, NoteEventType, NoteExpression, NoteId},
     graph::dsp::{DspParameterMap, DspProcessor},
-    AudioBuffer, AudioBufferSlice, OwnedAudioBuffer, Timestamp,
+    utility::{
+        realtime_log::{self, LogLevel},
+        trace::{self, Category},
+    },
+    AudioBuffer, AudioBufferSlice, Timestamp,
 };
 
-use super::{fade::Fade, voice::Voice};
+use super::{fade::Fade, note_voice::NoteVoice, scrub::Scrubber, voice::Voice};
 
+pub type SharedSample = Arc<dyn AudioBuffer + Send + Sync>;
 pub type EventReceiver = lockfree::channel::spsc::Receiver<SamplerEvent>;
 pub type EventTransmitter = lockfree::channel::spsc::Sender<SamplerEvent>;
 
@@ -14,8 +21,9 @@ pub struct SamplerDspProcess {
     fade: Fade,
     voices: Vec<Voice>,
     active_voice: Option<usize>,
-    buffer: OwnedAudioBuffer,
+    buffer: SharedSample,
     event_receiver: EventReceiver,
+    note_receiver: Option<NoteEventReceiver>,
     pending_events: Vec<SamplerEvent>,
     sample_rate: usize,
 
@@ -24,11 +32,21 @@ pub struct SamplerDspProcess {
     position: Timestamp,
     start_position_in_sample: Timestamp,
     completed_loops: usize,
+
+    scrubber: Scrubber,
+    scrub_position_id: Option<Id>,
+    playback_rate_id: Option<Id>,
+
+    note_voices: Vec<NoteVoice>,
+    root_frequency: f64,
+    notes_started: usize,
 }
 
 const NUM_VOICES: usize = 2;
 const FADE_LENGTH: Duration = Duration::from_millis(50);
-const MAX_PENDING_EVENTS: usize = 10;
+const MAX_PENDING_EVENTS: usize = 256;
+const MAX_NOTE_VOICES: usize = 16;
+const DEFAULT_ROOT_PITCH: f64 = 60.0;
 
 pub enum SampleEventType {
     Start(Timestamp),
@@ -36,6 +54,25 @@ pub enum SampleEventType {
 
     EnableLoop(Timestamp, Timestamp),
     CancelLoop,
+
+    Scrub(bool),
+
+    Note(NoteId, NoteEventType),
+    RootPitch(f64),
+}
+
+impl SampleEventType {
+    fn name(&self) -> &'static str {
+        match self {
+            SampleEventType::Start(_) => "SamplerStart",
+            SampleEventType::Stop => "SamplerStop",
+            SampleEventType::EnableLoop(_, _) => "SamplerEnableLoop",
+            SampleEventType::CancelLoop => "SamplerCancelLoop",
+            SampleEventType::Scrub(_) => "SamplerScrub",
+            SampleEventType::Note(..) => "SamplerNote",
+            SampleEventType::RootPitch(_) => "SamplerRootPitch",
+        }
+    }
 }
 
 pub struct SamplerEvent {
@@ -85,6 +122,27 @@ impl SamplerEvent {
             event_type: SampleEventType::CancelLoop,
         }
     }
+
+    pub fn scrub(enabled: bool) -> Self {
+        Self {
+            time: Timestamp::zero(),
+            event_type: SampleEventType::Scrub(enabled),
+        }
+    }
+
+    pub fn note(event: NoteEvent) -> Self {
+        Self {
+            time: event.time,
+            event_type: SampleEventType::Note(event.note_id, event.event_type),
+        }
+    }
+
+    pub fn root_pitch(pitch: f64) -> Self {
+        Self {
+            time: Timestamp::zero(),
+            event_type: SampleEventType::RootPitch(pitch),
+        }
+    }
 }
 
 impl DspProcessor for SamplerDspProcess {
@@ -93,7 +151,7 @@ impl DspProcessor for SamplerDspProcess {
         _input_buffer: &dyn AudioBuffer,
         output_buffer: &mut dyn AudioBuffer,
         start_time: &Timestamp,
-        _parameters: &DspParameterMap,
+        parameters: &DspParameterMap,
     ) {
         debug_assert_eq!(self.sample_rate, output_buffer.sample_rate());
 
@@ -109,11 +167,20 @@ impl DspProcessor for SamplerDspProcess {
             debug_assert!(end_frame <= output_buffer.num_frames());
             let num_frames = end_frame - position;
 
-            self.process_sample(&mut AudioBufferSlice::new(
-                output_buffer,
-                position,
-                num_frames,
-            ));
+            let playback_rate = self
+                .playback_rate_id
+                .and_then(|id| parameters.get(&id))
+                .map_or(1.0, |parameter| parameter.get_value_at_time(&current_time));
+
+            let mut output_slice = AudioBufferSlice::new(output_buffer, position, num_frames);
+            self.process_sample(&mut output_slice, playback_rate);
+            self.scrubber.render(
+                &mut output_slice,
+                self.buffer.as_ref(),
+                &current_time,
+                self.scrub_position_id.and_then(|id| parameters.get(&id)),
+            );
+            self.process_note_voices(&mut output_slice, playback_rate);
 
             position += num_frames;
             current_time = current_time.incremented_by_samples(num_frames, self.sample_rate);
@@ -126,26 +193,44 @@ impl DspProcessor for SamplerDspProcess {
 }
 
 impl SamplerDspProcess {
-    pub fn new(
-        sample_rate: usize,
-        buffer: OwnedAudioBuffer,
-        event_receiver: EventReceiver,
-    ) -> Self {
+    pub fn new(sample_rate: usize, buffer: SharedSample, event_receiver: EventReceiver) -> Self {
         Self {
             fade: Fade::new(FADE_LENGTH, sample_rate),
             voices: (0..NUM_VOICES).map(|_| Voice::default()).collect(),
             active_voice: None,
             buffer,
             event_receiver,
+            note_receiver: None,
             pending_events: Vec::with_capacity(MAX_PENDING_EVENTS),
             loop_points: None,
             position: Timestamp::zero(),
             start_position_in_sample: Timestamp::zero(),
             completed_loops: 0,
+            scrubber: Scrubber::new(sample_rate),
+            scrub_position_id: None,
+            playback_rate_id: None,
+            note_voices: (0..MAX_NOTE_VOICES).map(|_| NoteVoice::default()).collect(),
+            root_frequency: NoteExpression::new(DEFAULT_ROOT_PITCH, 1.0).frequency(),
+            notes_started: 0,
             sample_rate,
         }
     }
 
+    pub fn with_scrub_position(mut self, scrub_position_id: Id) -> Self {
+        self.scrub_position_id = Some(scrub_position_id);
+        self
+    }
+
+    pub fn with_playback_rate(mut self, playback_rate_id: Id) -> Self {
+        self.playback_rate_id = Some(playback_rate_id);
+        self
+    }
+
+    pub fn with_note_receiver(mut self, note_receiver: NoteEventReceiver) -> Self {
+        self.note_receiver = Some(note_receiver);
+        self
+    }
+
     fn next_loop_position(&self) -> Timestamp {
         let (loop_start, loop_end) = match self.loop_points {
             Some(loop_points) => loop_points,
@@ -163,20 +248,17 @@ impl SamplerDspProcess {
         Timestamp::from_samples(sample_position, self.sample_rate)
     }
 
-    fn get_render_interval(&self, num_samples_remaining_in_frame: usize) -> Timestamp {
-        let end_of_frame = self
-            .position
-            .incremented_by_samples(num_samples_remaining_in_frame, self.sample_rate);
-
-        let end_of_sample = self.next_loop_position();
-
-        let end_point = if end_of_frame < end_of_sample {
-            end_of_frame
-        } else {
-            end_of_sample
-        };
+    fn get_render_interval(
+        &self,
+        num_samples_remaining_in_frame: usize,
+        playback_rate: f64,
+    ) -> usize {
+        let remaining_in_sample = (self.next_loop_position() - self.position)
+            .get_samples(self.sample_rate)
+            .max(0.0);
 
-        end_point - self.position
+        let render_interval = (remaining_in_sample / playback_rate).round() as usize;
+        std::cmp::min(render_interval, num_samples_remaining_in_frame)
     }
 
     fn finish_sample(&mut self) {
@@ -189,17 +271,14 @@ impl SamplerDspProcess {
         self.loop_points.is_some()
     }
 
-    fn process_sample(&mut self, output_buffer: &mut dyn AudioBuffer) {
+    fn process_sample(&mut self, output_buffer: &mut dyn AudioBuffer, playback_rate: f64) {
         let mut frame_position = 0;
 
         while frame_position < output_buffer.num_frames() {
             let num_samples_remaining_in_frame = output_buffer.num_frames() - frame_position;
 
-            let render_interval = self.get_render_interval(num_samples_remaining_in_frame);
-            let render_interval = render_interval.get_samples(self.sample_rate).round() as usize;
-
             let num_frames_to_render =
-                std::cmp::min(render_interval, num_samples_remaining_in_frame);
+                self.get_render_interval(num_samples_remaining_in_frame, playback_rate);
 
             if num_frames_to_render == 0 && self.is_looping() {
                 self.finish_sample();
@@ -210,26 +289,27 @@ impl SamplerDspProcess {
                 break;
             }
 
-            self.process_voices(&mut AudioBufferSlice::new(
-                output_buffer,
-                frame_position,
-                num_frames_to_render,
-            ));
+            self.process_voices(
+                &mut AudioBufferSlice::new(output_buffer, frame_position, num_frames_to_render),
+                playback_rate,
+            );
 
             frame_position += num_frames_to_render;
 
-            self.position = self
-                .position
-                .incremented_by_samples(num_frames_to_render, self.sample_rate);
+            self.position = Timestamp::from_samples(
+                self.position.get_samples(self.sample_rate)
+                    + num_frames_to_render as f64 * playback_rate,
+                self.sample_rate,
+            );
         }
     }
 
-    fn process_voices(&mut self, output_buffer: &mut dyn AudioBuffer) {
+    fn process_voices(&mut self, output_buffer: &mut dyn AudioBuffer, playback_rate: f64) {
         let fade = &self.fade;
-        let sample = &self.buffer;
+        let sample = self.buffer.as_ref();
         self.voices
             .iter_mut()
-            .for_each(|voice| voice.render(output_buffer, sample, fade));
+            .for_each(|voice| voice.render(output_buffer, sample, fade, playback_rate));
     }
 
     fn next_event_position(
@@ -264,6 +344,8 @@ impl SamplerDspProcess {
     }
 
     fn process_event(&mut self, event: &SamplerEvent) {
+        trace::instant(event.event_type.name(), Category::Event, None);
+
         match event.event_type {
             SampleEventType::Start(position_in_sample) => {
                 self.start(position_in_sample);
@@ -273,7 +355,43 @@ impl SamplerDspProcess {
                 self.set_loop_points(loop_start, loop_end)
             }
             SampleEventType::CancelLoop => self.clear_loop_points(),
+            SampleEventType::Scrub(enabled) => self.set_scrubbing(enabled),
+            SampleEventType::Note(note_id, event_type) => self.apply_note(note_id, &event_type),
+            SampleEventType::RootPitch(pitch) => {
+                self.root_frequency = NoteExpression::new(pitch, 1.0).frequency()
+            }
+        }
+    }
+
+    fn apply_note(&mut self, note_id: NoteId, event_type: &NoteEventType) {
+        if let NoteEventType::NoteOn { pitch, velocity } = *event_type {
+            let voice = match self.note_voices.iter().position(|voice| !voice.is_active()) {
+                Some(index) => &mut self.note_voices[index],
+                None => self
+                    .note_voices
+                    .iter_mut()
+                    .min_by_key(|voice| voice.get_age())
+                    .unwrap(),
+            };
+
+            voice.start(note_id, pitch, velocity, self.notes_started);
+            self.notes_started += 1;
+            return;
         }
+
+        self.note_voices
+            .iter_mut()
+            .filter(|voice| voice.plays(note_id))
+            .for_each(|voice| voice.apply(event_type));
+    }
+
+    fn process_note_voices(&mut self, output_buffer: &mut dyn AudioBuffer, playback_rate: f64) {
+        let fade = &self.fade;
+        let sample = self.buffer.as_ref();
+        let root_frequency = self.root_frequency;
+        self.note_voices.iter_mut().for_each(|voice| {
+            voice.render(output_buffer, sample, fade, root_frequency, playback_rate)
+        });
     }
 
     fn set_loop_points(&mut self, loop_start: Timestamp, loop_end: Timestamp) {
@@ -284,20 +402,36 @@ impl SamplerDspProcess {
         self.loop_points = None
     }
 
-    fn read_events(&mut self) {
-        let mut sort_required = false;
+    fn set_scrubbing(&mut self, enabled: bool) {
+        if enabled {
+            self.stop();
+        }
 
+        self.scrubber.set_enabled(enabled);
+    }
+
+    fn read_events(&mut self) {
         while let Ok(event) = self.event_receiver.recv() {
-            self.pending_events.push(event);
-            sort_required = true;
+            self.queue_event(event);
         }
 
-        if sort_required {
-            self.pending_events
-                .sort_by(|a, b| a.time.partial_cmp(&b.time).unwrap())
+        while let Some(Ok(event)) = self.note_receiver.as_mut().map(|receiver| receiver.recv()) {
+            self.queue_event(SamplerEvent::note(event));
         }
     }
 
+    fn queue_event(&mut self, event: SamplerEvent) {
+        if self.pending_events.len() == self.pending_events.capacity() {
+            realtime_log::log(LogLevel::Warning, "Sampler event queue full");
+            return;
+        }
+
+        let index = self
+            .pending_events
+            .partition_point(|pending| pending.time <= event.time);
+        self.pending_events.insert(index, event);
+    }
+
     fn assign_voice(&mut self, start_position: Timestamp) {
         let sample_position = start_position.get_samples(self.sample_rate).round() as usize;
 
@@ -352,7 +486,11 @@ impl SamplerDspProcess {
 
 #[cfg(test)]
 mod tests {
-    use crate::{OwnedAudioBuffer, SampleLocation};
+    use atomic_float::AtomicF64;
+
+    use crate::{
+        parameter::realtime_parameter::RealtimeAudioParameter, OwnedAudioBuffer, SampleLocation,
+    };
 
     use super::*;
 
@@ -403,7 +541,7 @@ mod tests {
 
         let sample = create_sample_with_value(num_frames, num_channels, sample_rate, 1.0);
         let (mut event_transmitter, event_receiver) = lockfree::channel::spsc::create();
-        let mut sampler = SamplerDspProcess::new(sample_rate, sample, event_receiver);
+        let mut sampler = SamplerDspProcess::new(sample_rate, Arc::new(sample), event_receiver);
 
         let _ = event_transmitter.send(SamplerEvent::start(
             Timestamp::zero(),
@@ -425,7 +563,7 @@ mod tests {
 
         let sample = create_sample_with_value(num_frames, num_channels, sample_rate, 1.0);
         let (mut event_transmitter, event_receiver) = lockfree::channel::spsc::create();
-        let mut sampler = SamplerDspProcess::new(sample_rate, sample, event_receiver);
+        let mut sampler = SamplerDspProcess::new(sample_rate, Arc::new(sample), event_receiver);
 
         let _ = event_transmitter.send(SamplerEvent::start_now());
         let _ = event_transmitter.send(SamplerEvent::stop(Timestamp::from_samples(
@@ -448,7 +586,7 @@ mod tests {
 
         let sample = create_sample_with_value(num_frames, num_channels, sample_rate, 1.0);
         let (mut event_transmitter, event_receiver) = lockfree::channel::spsc::create();
-        let mut sampler = SamplerDspProcess::new(sample_rate, sample, event_receiver);
+        let mut sampler = SamplerDspProcess::new(sample_rate, Arc::new(sample), event_receiver);
 
         let _ = event_transmitter.send(SamplerEvent::start(Timestamp::zero(), Timestamp::zero()));
 
@@ -477,7 +615,7 @@ mod tests {
 
         let sample = create_sample_with_value(num_frames, num_channels, sample_rate, 1.0);
         let (mut event_transmitter, event_receiver) = lockfree::channel::spsc::create();
-        let mut sampler = SamplerDspProcess::new(sample_rate, sample, event_receiver);
+        let mut sampler = SamplerDspProcess::new(sample_rate, Arc::new(sample), event_receiver);
 
         let start_time_in_samples = 1500;
 
@@ -499,7 +637,7 @@ mod tests {
 
         let sample = create_sample_with_value(num_frames, num_channels, sample_rate, 1.0);
         let (mut event_transmitter, event_receiver) = lockfree::channel::spsc::create();
-        let mut sampler = SamplerDspProcess::new(sample_rate, sample, event_receiver);
+        let mut sampler = SamplerDspProcess::new(sample_rate, Arc::new(sample), event_receiver);
 
         let stop_time_in_samples = 2000;
 
@@ -525,7 +663,7 @@ mod tests {
         sample.set_sample(SampleLocation::new(0, 4999), 0.4999);
 
         let (mut event_transmitter, event_receiver) = lockfree::channel::spsc::create();
-        let mut sampler = SamplerDspProcess::new(sample_rate, sample, event_receiver);
+        let mut sampler = SamplerDspProcess::new(sample_rate, Arc::new(sample), event_receiver);
 
         let _ = event_transmitter.send(SamplerEvent::start_now());
 
@@ -550,7 +688,7 @@ mod tests {
         sample.set_sample(SampleLocation::new(0, 9999), 0.123);
 
         let (mut event_transmitter, event_receiver) = lockfree::channel::spsc::create();
-        let mut sampler = SamplerDspProcess::new(sample_rate, sample, event_receiver);
+        let mut sampler = SamplerDspProcess::new(sample_rate, Arc::new(sample), event_receiver);
 
         let _ = event_transmitter.send(SamplerEvent::start_now());
 
@@ -579,7 +717,7 @@ mod tests {
         let sample = create_sample_with_value(num_frames, num_channels, sample_rate, 1.0);
 
         let (mut event_transmitter, event_receiver) = lockfree::channel::spsc::create();
-        let mut sampler = SamplerDspProcess::new(sample_rate, sample, event_receiver);
+        let mut sampler = SamplerDspProcess::new(sample_rate, Arc::new(sample), event_receiver);
 
         let _ = event_transmitter.send(SamplerEvent::start(
             Timestamp::zero(),
@@ -596,4 +734,122 @@ mod tests {
         let _ = process_sampler(&mut sampler, 1, num_channels, sample_rate);
         assert_eq!(100, sampler.completed_loops);
     }
+
+    #[test]
+    fn repitches_with_cubic_interpolation() {
+        let num_frames = 1_000;
+        let sample_rate = 48_000;
+
+        let mut sample = OwnedAudioBuffer::new(num_frames, 1, sample_rate);
+        for frame in 0..num_frames {
+            sample.set_sample(
+                SampleLocation::new(0, frame),
+                frame as f32 / num_frames as f32,
+            );
+        }
+
+        let (mut event_transmitter, event_receiver) = lockfree::channel::spsc::create();
+        let playback_rate_id = Id::generate();
+        let mut sampler = SamplerDspProcess::new(sample_rate, Arc::new(sample), event_receiver)
+            .with_playback_rate(playback_rate_id);
+
+        let mut parameters = DspParameterMap::new();
+        parameters.insert(
+            playback_rate_id,
+            RealtimeAudioParameter::new(playback_rate_id, Arc::new(AtomicF64::new(1.5))),
+        );
+
+        let _ = event_transmitter.send(SamplerEvent::start_now());
+
+        let mut output = OwnedAudioBuffer::new(num_frames, 1, sample_rate);
+        let input = OwnedAudioBuffer::new(num_frames, 1, sample_rate);
+        sampler.process_audio(&input, &mut output, &Timestamp::zero(), &parameters);
+
+        approx::assert_relative_eq!(
+            0.1515,
+            output.get_sample(SampleLocation::new(0, 101)),
+            epsilon = 1e-5
+        );
+        approx::assert_relative_eq!(
+            0.4995,
+            output.get_sample(SampleLocation::new(0, 333)),
+            epsilon = 1e-5
+        );
+        expect_sample(0.0, &output, 700, 0);
+    }
+
+    fn ramp(num_frames: usize, sample_rate: usize) -> OwnedAudioBuffer {
+        let mut sample = OwnedAudioBuffer::new(num_frames, 1, sample_rate);
+        for frame in 0..num_frames {
+            sample.set_sample(
+                SampleLocation::new(0, frame),
+                frame as f32 / num_frames as f32,
+            );
+        }
+        sample
+    }
+
+    #[test]
+    fn plays_notes_with_per_voice_expression() {
+        let num_frames = 20_000;
+        let sample_rate = 44_100;
+        let at = |frame: usize| Timestamp::from_samples(frame as f64, sample_rate);
+        let value = |position: usize| position as f32 / num_frames as f32;
+
+        let (mut event_transmitter, event_receiver) = lockfree::channel::spsc::create();
+        let mut sampler = SamplerDspProcess::new(
+            sample_rate,
+            Arc::new(ramp(num_frames, sample_rate)),
+            event_receiver,
+        );
+
+        for event in [
+            NoteEvent::note_on(at(0), 0, 72.0, 1.0),
+            NoteEvent::note_on(at(0), 1, 60.0, 1.0),
+            NoteEvent::pitch_bend(at(1_000), 1, 12.0),
+            NoteEvent::pressure(at(2_000), 0, 0.5),
+            NoteEvent::note_off(at(2_500), 1, 0.0),
+        ] {
+            let _ = event_transmitter.send(SamplerEvent::note(event));
+        }
+
+        let output = process_sampler(&mut sampler, 6_000, 1, sample_rate);
+
+        expect_sample(value(1_000) + value(500), &output, 500, 0);
+        expect_sample(value(3_000) + value(2_000), &output, 1_500, 0);
+        expect_sample(1.5 * value(4_400) + value(3_400), &output, 2_200, 0);
+
+        let released = 2_500 + sampler.fade.len() + 10;
+        expect_sample(1.5 * value(2 * released), &output, released, 0);
+    }
+
+    #[test]
+    fn timbre_darkens_each_voice() {
+        let sample_rate = 44_100;
+        let mut sample = OwnedAudioBuffer::new(10_000, 1, sample_rate);
+        for frame in 0..10_000 {
+            let value = if frame % 2 == 0 { 1.0 } else { -1.0 };
+            sample.set_sample(SampleLocation::new(0, frame), value);
+        }
+
+        let (mut event_transmitter, event_receiver) = lockfree::channel::spsc::create();
+        let mut sampler = SamplerDspProcess::new(sample_rate, Arc::new(sample), event_receiver);
+
+        let _ = event_transmitter.send(SamplerEvent::note(NoteEvent::note_on(
+            Timestamp::zero(),
+            0,
+            60.0,
+            1.0,
+        )));
+        let _ = event_transmitter.send(SamplerEvent::note(NoteEvent::timbre(
+            Timestamp::from_samples(1_000.0, sample_rate),
+            0,
+            0.0,
+        )));
+
+        let output = process_sampler(&mut sampler, 4_000, 1, sample_rate);
+
+        expect_sample(1.0, &output, 500, 0);
+        assert!(output.get_sample(SampleLocation::new(0, 3_500)).abs() < 0.01);
+    }
 }
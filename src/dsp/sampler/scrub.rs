@@ -0,0 +1,154 @@
+use std::{f64::consts::TAU, time::Duration};
+
+use crate::{
+    parameter::realtime_parameter::RealtimeAudioParameter, AudioBuffer, SampleLocation, Timestamp,
+};
+
+const GRAIN_LENGTH: Duration = Duration::from_millis(40);
+const NUM_GRAINS: usize = 2;
+
+#[derive(Clone, Copy, Default)]
+struct Grain {
+    start: usize,
+    progress: usize,
+    active: bool,
+}
+
+pub struct Scrubber {
+    window: Vec<f32>,
+    grains: [Grain; NUM_GRAINS],
+    frames_until_next_grain: usize,
+    enabled: bool,
+}
+
+impl Scrubber {
+    pub fn new(sample_rate: usize) -> Self {
+        let grain_length = ((GRAIN_LENGTH.as_secs_f64() * sample_rate as f64) as usize).max(2);
+        let grain_length = grain_length + grain_length % 2;
+
+        Self {
+            window: (0..grain_length)
+                .map(|index| (0.5 - 0.5 * (TAU * index as f64 / grain_length as f64).cos()) as f32)
+                .collect(),
+            grains: [Grain::default(); NUM_GRAINS],
+            frames_until_next_grain: 0,
+            enabled: false,
+        }
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        if enabled && !self.enabled {
+            self.frames_until_next_grain = 0;
+        }
+
+        self.enabled = enabled;
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.enabled || self.grains.iter().any(|grain| grain.active)
+    }
+
+    fn hop_size(&self) -> usize {
+        self.window.len() / NUM_GRAINS
+    }
+
+    pub fn render(
+        &mut self,
+        output: &mut dyn AudioBuffer,
+        sample: &dyn AudioBuffer,
+        start_time: &Timestamp,
+        position: Option<&RealtimeAudioParameter>,
+    ) {
+        if !self.is_active() {
+            return;
+        }
+
+        let sample_rate = output.sample_rate();
+        let num_channels = output.num_channels().min(sample.num_channels());
+
+        for frame in 0..output.num_frames() {
+            if self.enabled && self.frames_until_next_grain == 0 {
+                let frame_time = start_time.incremented_by_samples(frame, sample_rate);
+                let position_in_sample = position
+                    .map(|position| position.get_value_at_time(&frame_time))
+                    .unwrap_or(0.0);
+                self.start_grain(position_in_sample, sample_rate);
+                self.frames_until_next_grain = self.hop_size();
+            }
+
+            self.frames_until_next_grain = self.frames_until_next_grain.saturating_sub(1);
+
+            for grain in self.grains.iter_mut().filter(|grain| grain.active) {
+                let sample_frame = grain.start + grain.progress;
+
+                if sample_frame < sample.num_frames() {
+                    let gain = self.window[grain.progress];
+                    for channel in 0..num_channels {
+                        let value = sample.get_sample(SampleLocation::new(channel, sample_frame));
+                        output.add_sample(SampleLocation::new(channel, frame), value * gain);
+                    }
+                }
+
+                grain.progress += 1;
+                grain.active = grain.progress < self.window.len();
+            }
+        }
+    }
+
+    fn start_grain(&mut self, position_in_sample: f64, sample_rate: usize) {
+        let centre = (position_in_sample.max(0.0) * sample_rate as f64).round() as usize;
+        let start = centre.saturating_sub(self.window.len() / 2);
+
+        if let Some(grain) = self.grains.iter_mut().find(|grain| !grain.active) {
+            *grain = Grain {
+                start,
+                progress: 0,
+                active: true,
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use atomic_float::AtomicF64;
+
+    use crate::{commands::id::Id, OwnedAudioBuffer};
+
+    use super::*;
+
+    const SAMPLE_RATE: usize = 48_000;
+
+    #[test]
+    fn overlapping_grains_follow_position() {
+        let mut sample = OwnedAudioBuffer::new(SAMPLE_RATE, 1, SAMPLE_RATE);
+        for frame in 0..SAMPLE_RATE {
+            sample.set_sample(SampleLocation::new(0, frame), frame as f32);
+        }
+
+        let position = RealtimeAudioParameter::new(Id::generate(), Arc::new(AtomicF64::new(0.5)));
+        let mut scrubber = Scrubber::new(SAMPLE_RATE);
+        let grain_length = scrubber.window.len();
+        scrubber.set_enabled(true);
+
+        let mut output = OwnedAudioBuffer::new(4 * grain_length, 1, SAMPLE_RATE);
+        scrubber.render(&mut output, &sample, &Timestamp::zero(), Some(&position));
+
+        let frame = 2 * grain_length + grain_length / 4;
+        let expected_first = (SAMPLE_RATE / 2 - grain_length / 2 + grain_length / 4) as f32;
+        let expected_second = expected_first + (grain_length / 2) as f32;
+        let window = |index: usize| scrubber.window[index];
+        let expected = expected_first * window(grain_length / 4)
+            + expected_second * window(grain_length / 4 + grain_length / 2);
+        assert!((output.get_sample(SampleLocation::new(0, frame)) - expected).abs() < 1e-2);
+        assert!((window(grain_length / 4) + window(3 * grain_length / 4) - 1.0).abs() < 1e-6);
+
+        scrubber.set_enabled(false);
+        let mut output = OwnedAudioBuffer::new(2 * grain_length, 1, SAMPLE_RATE);
+        scrubber.render(&mut output, &sample, &Timestamp::zero(), Some(&position));
+        assert!(!scrubber.is_active());
+        assert_eq!(output.get_sample(SampleLocation::new(0, grain_length)), 0.0);
+    }
+}
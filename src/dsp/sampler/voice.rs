@@ -1,26 +1,21 @@
-use crate::{AudioBuffer, SampleLocation};
+use crate::{utility::interpolation::interpolate_cubic, AudioBuffer, SampleLocation};
 
 use super::fade::Fade;
 
 use std::cmp::min;
 
-#[derive(PartialEq)]
+#[derive(Default, PartialEq)]
 enum Phase {
+    #[default]
     Stopped,
     FadingIn(usize),
     Playing,
     FadingOut(usize),
 }
 
-impl Default for Phase {
-    fn default() -> Self {
-        Self::Stopped
-    }
-}
-
 #[derive(Default)]
 pub struct Voice {
-    position: usize,
+    position: f64,
     phase: Phase,
 }
 
@@ -30,7 +25,7 @@ impl Voice {
     }
 
     pub fn start_from_position(&mut self, position: usize) {
-        self.position = position;
+        self.position = position as f64;
         self.phase = match position {
             0 => Phase::Playing,
             _ => Phase::FadingIn(0),
@@ -38,7 +33,7 @@ impl Voice {
     }
 
     pub fn get_position(&self) -> usize {
-        self.position
+        self.position.round() as usize
     }
 
     pub fn stop(&mut self) {
@@ -50,7 +45,13 @@ impl Voice {
         }
     }
 
-    pub fn render(&mut self, output: &mut dyn AudioBuffer, sample: &dyn AudioBuffer, fade: &Fade) {
+    pub fn render(
+        &mut self,
+        output: &mut dyn AudioBuffer,
+        sample: &dyn AudioBuffer,
+        fade: &Fade,
+        playback_rate: f64,
+    ) {
         if self.is_stopped() {
             return;
         }
@@ -61,16 +62,10 @@ impl Voice {
             match self.phase {
                 Phase::Stopped => break,
                 Phase::FadingIn(fade_position) => {
-                    let num_frames = self.render_fade(
-                        output,
-                        destination_offset,
-                        sample,
-                        fade,
-                        fade_position,
-                        true,
-                    );
-
-                    self.position += num_frames;
+                    let num_frames =
+                        self.render_fade(output, destination_offset, sample, fade, playback_rate);
+
+                    self.position += num_frames as f64 * playback_rate;
                     destination_offset += num_frames;
 
                     let fade_position = fade_position + num_frames;
@@ -82,22 +77,17 @@ impl Voice {
                     }
                 }
                 Phase::Playing => {
-                    self.render_playing(output, destination_offset, sample);
+                    let num_frames = output.num_frames() - destination_offset;
+                    self.render_playing(output, destination_offset, sample, playback_rate);
 
-                    self.position += output.num_frames() - destination_offset;
+                    self.position += num_frames as f64 * playback_rate;
                     destination_offset = output.num_frames();
                 }
                 Phase::FadingOut(fade_position) => {
-                    let num_frames = self.render_fade(
-                        output,
-                        destination_offset,
-                        sample,
-                        fade,
-                        fade_position,
-                        false,
-                    );
-
-                    self.position += num_frames;
+                    let num_frames =
+                        self.render_fade(output, destination_offset, sample, fade, playback_rate);
+
+                    self.position += num_frames as f64 * playback_rate;
                     destination_offset += num_frames;
 
                     let fade_position = fade_position + num_frames;
@@ -116,19 +106,40 @@ impl Voice {
         output: &mut dyn AudioBuffer,
         destination_offset: usize,
         source: &dyn AudioBuffer,
+        playback_rate: f64,
     ) {
         let num_channels = min(source.num_channels(), output.num_channels());
 
-        if self.position >= source.num_frames() {
+        if self.position >= source.num_frames() as f64 {
+            return;
+        }
+
+        if playback_rate != 1.0 || self.position.fract() != 0.0 {
+            let num_frames = output.num_frames() - destination_offset;
+            for frame in 0..num_frames {
+                let position = self.position + frame as f64 * playback_rate;
+                if position >= source.num_frames() as f64 {
+                    break;
+                }
+
+                for channel in 0..num_channels {
+                    let dest_location = SampleLocation::new(channel, destination_offset + frame);
+                    let sample = output.get_sample(dest_location)
+                        + read_interpolated(source, channel, position);
+                    output.set_sample(dest_location, sample);
+                }
+            }
+
             return;
         }
 
+        let position = self.position as usize;
         let num_frames = std::cmp::min(
             output.num_frames() - destination_offset,
-            source.num_frames() - self.position,
+            source.num_frames() - position,
         );
 
-        let source_location = SampleLocation::new(0, self.position);
+        let source_location = SampleLocation::new(0, position);
         let destination_location = SampleLocation::new(0, destination_offset);
 
         output.add_from(
@@ -146,9 +157,14 @@ impl Voice {
         destination_offset: usize,
         source: &dyn AudioBuffer,
         fade: &Fade,
-        fade_position: usize,
-        fade_in: bool,
+        playback_rate: f64,
     ) -> usize {
+        let (fade_position, fade_in) = match self.phase {
+            Phase::FadingIn(fade_position) => (fade_position, true),
+            Phase::FadingOut(fade_position) => (fade_position, false),
+            _ => return 0,
+        };
+
         let num_channels = min(source.num_channels(), output.num_channels());
 
         let num_frames = std::cmp::min(
@@ -157,7 +173,8 @@ impl Voice {
         );
 
         for frame in 0..num_frames {
-            if frame + self.position >= source.num_frames() {
+            let position = self.position + frame as f64 * playback_rate;
+            if position >= source.num_frames() as f64 {
                 break;
             }
 
@@ -168,18 +185,13 @@ impl Voice {
             };
 
             for channel in 0..num_channels {
-                let source_location = SampleLocation {
-                    channel,
-                    frame: self.position + frame,
-                };
-
                 let dest_location = SampleLocation {
                     channel,
                     frame: destination_offset + frame,
                 };
 
                 let sample = output.get_sample(dest_location)
-                    + fade_value * source.get_sample(source_location);
+                    + fade_value * read_interpolated(source, channel, position);
                 output.set_sample(dest_location, sample);
             }
         }
@@ -187,3 +199,19 @@ impl Voice {
         num_frames
     }
 }
+
+pub(crate) fn read_interpolated(source: &dyn AudioBuffer, channel: usize, position: f64) -> f32 {
+    let index = position as usize;
+    let last_frame = source.num_frames() - 1;
+    let read = |frame: usize| {
+        source.get_sample(SampleLocation::new(channel, frame.min(last_frame))) as f64
+    };
+
+    interpolate_cubic(
+        read(index.saturating_sub(1)),
+        read(index),
+        read(index + 1),
+        read(index + 2),
+        position - index as f64,
+    ) as f32
+}
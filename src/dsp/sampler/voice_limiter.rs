@@ -0,0 +1,94 @@
+use std::{
+    cmp::Ordering,
+    sync::{atomic::AtomicUsize, Arc},
+};
+
+pub type VoiceBudget = Arc<AtomicUsize>;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct VoiceImportance {
+    pub priority: i32,
+    pub loudness: f64,
+    pub started_order: u64,
+}
+
+impl VoiceImportance {
+    fn compare(&self, other: &Self) -> Ordering {
+        self.priority
+            .cmp(&other.priority)
+            .then(
+                self.loudness
+                    .partial_cmp(&other.loudness)
+                    .unwrap_or(Ordering::Equal),
+            )
+            .then(self.started_order.cmp(&other.started_order))
+    }
+}
+
+pub fn find_least_important(voices: impl Iterator<Item = VoiceImportance>) -> Option<usize> {
+    voices
+        .enumerate()
+        .min_by(|(_, a), (_, b)| a.compare(b))
+        .map(|(index, _)| index)
+}
+
+pub fn find_voice_to_cull(
+    voices: impl Iterator<Item = VoiceImportance>,
+    incoming: &VoiceImportance,
+) -> Option<usize> {
+    let mut least_important: Option<(usize, VoiceImportance)> = None;
+
+    for (index, voice) in voices.enumerate() {
+        let is_less_important = least_important
+            .map(|(_, least)| voice.compare(&least) == Ordering::Less)
+            .unwrap_or(true);
+
+        if is_less_important {
+            least_important = Some((index, voice));
+        }
+    }
+
+    least_important
+        .filter(|(_, voice)| voice.compare(incoming) == Ordering::Less)
+        .map(|(index, _)| index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn voice(priority: i32, loudness: f64, started_order: u64) -> VoiceImportance {
+        VoiceImportance {
+            priority,
+            loudness,
+            started_order,
+        }
+    }
+
+    #[test]
+    fn culls_lowest_priority_then_quietest_then_oldest() {
+        let voices = [voice(1, 0.1, 0), voice(0, 1.0, 1), voice(0, 0.5, 2)];
+        assert_eq!(find_least_important(voices.into_iter()), Some(2));
+
+        let voices = [voice(0, 0.5, 3), voice(0, 0.5, 2)];
+        assert_eq!(find_least_important(voices.into_iter()), Some(1));
+    }
+
+    #[test]
+    fn rejects_incoming_voice_less_important_than_all_playing() {
+        let voices = [voice(2, 0.5, 0), voice(1, 0.5, 1)];
+
+        assert_eq!(
+            find_voice_to_cull(voices.into_iter(), &voice(0, 1.0, 2)),
+            None
+        );
+        assert_eq!(
+            find_voice_to_cull(voices.into_iter(), &voice(1, 0.5, 2)),
+            Some(1)
+        );
+        assert_eq!(
+            find_voice_to_cull(voices.into_iter(), &voice(1, 0.1, 2)),
+            None
+        );
+    }
+}
@@ -0,0 +1,188 @@
+use std::collections::HashMap;
+
+use crate::{
+    commands::{command_queue::CommandQueue, id::Id},
+    graph::{
+        decimation::Decimated,
+        dsp::{Dsp, DspProcessor},
+        node::Node,
+        oversampling::Oversampled,
+    },
+};
+
+use super::processor::{SubgraphProcessor, SubgraphRate};
+
+pub struct SubgraphNode {
+    id: Id,
+    command_queue: CommandQueue,
+    subgraph_queue: CommandQueue,
+    rate: SubgraphRate,
+    sample_rate: usize,
+    latency_in_samples: usize,
+}
+
+impl SubgraphNode {
+    pub fn new(command_queue: CommandQueue, sample_rate: usize, rate: SubgraphRate) -> Self {
+        let id = Id::generate();
+
+        let (subgraph_queue, subgraph_rx) = command_queue.create_subgraph();
+        let processor = SubgraphProcessor::new(subgraph_rx, rate, sample_rate);
+
+        let (processor, latency_in_samples): (Box<dyn DspProcessor + Send + Sync>, usize) =
+            match rate {
+                SubgraphRate::Oversampled(factor) => {
+                    let processor = Oversampled::new(processor, factor);
+                    let latency = processor.latency_in_samples();
+                    (Box::new(processor), latency)
+                }
+                SubgraphRate::Reduced(divisor) => {
+                    let processor = Decimated::new(processor, divisor);
+                    let latency = processor.latency_in_samples();
+                    (Box::new(processor), latency)
+                }
+            };
+
+        let dsp = Dsp::new(id, processor, HashMap::new());
+
+        Dsp::add_to_audio_process(dsp, &command_queue);
+
+        Self {
+            id,
+            command_queue,
+            subgraph_queue,
+            rate,
+            sample_rate,
+            latency_in_samples,
+        }
+    }
+
+    pub fn get_subgraph_queue(&self) -> CommandQueue {
+        self.subgraph_queue.clone()
+    }
+
+    pub fn get_subgraph_sample_rate(&self) -> usize {
+        self.rate.sample_rate(self.sample_rate)
+    }
+}
+
+impl Node for SubgraphNode {
+    fn get_id(&self) -> Id {
+        self.id
+    }
+
+    fn get_command_queue(&self) -> CommandQueue {
+        self.command_queue.clone()
+    }
+
+    fn latency_in_samples(&self) -> usize {
+        self.latency_in_samples
+    }
+}
+
+impl Drop for SubgraphNode {
+    fn drop(&mut self) {
+        Dsp::remove_from_audio_process(self.id, &self.command_queue);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        commands::journal::JournalEntry,
+        graph::endpoint::{Endpoint, EndpointType},
+        AudioBuffer, ConstantSource, Context, Gain, OversamplingFactor, OwnedAudioBuffer,
+        SampleLocation, Timestamp,
+    };
+
+    fn last_sample(buffer: &OwnedAudioBuffer, channel: usize) -> f32 {
+        buffer.get_sample(SampleLocation::new(channel, buffer.num_frames() - 1))
+    }
+
+    #[test]
+    fn renders_child_nodes_at_reduced_rate() {
+        let mut context = Context::new(48000);
+        let mut audio_process = context.get_audio_process();
+        let mut output_buffer = OwnedAudioBuffer::new(512, 2, 48000);
+
+        let subgraph = SubgraphNode::new(
+            context.get_command_queue(),
+            context.get_sample_rate(),
+            SubgraphRate::Reduced(4),
+        );
+        assert_eq!(subgraph.get_subgraph_sample_rate(), 12000);
+        subgraph.connect_to_output().unwrap();
+
+        let constant = ConstantSource::new(subgraph.get_subgraph_queue(), 0.5);
+        constant.connect_to_output().unwrap();
+        context.start();
+
+        audio_process.process(&mut output_buffer);
+        audio_process.process(&mut output_buffer);
+
+        assert!((last_sample(&output_buffer, 0) - 0.5).abs() < 1e-4);
+        assert!((last_sample(&output_buffer, 1) - 0.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn routes_subgraph_input_through_oversampled_children() {
+        let mut context = Context::new(48000);
+        let mut audio_process = context.get_audio_process();
+        let mut input_buffer = OwnedAudioBuffer::new(512, 2, 48000);
+        input_buffer.fill_with_value(0.25);
+        let mut output_buffer = OwnedAudioBuffer::new(512, 2, 48000);
+
+        let subgraph = SubgraphNode::new(
+            context.get_command_queue(),
+            context.get_sample_rate(),
+            SubgraphRate::Oversampled(OversamplingFactor::TwoTimes),
+        );
+        assert_eq!(subgraph.get_subgraph_sample_rate(), 96000);
+        assert!(subgraph.latency_in_samples() > 0);
+        subgraph.connect_to_output().unwrap();
+        context.connect_input_to(subgraph.get_id()).unwrap();
+
+        let gain = Gain::new(subgraph.get_subgraph_queue());
+        gain.connect_from_input().unwrap();
+        gain.connect_to_output().unwrap();
+        context.start();
+
+        audio_process.process_with_input(&input_buffer, &mut output_buffer);
+        audio_process.process_with_input(&input_buffer, &mut output_buffer);
+
+        assert!((last_sample(&output_buffer, 0) - 0.25).abs() < 1e-3);
+        assert!((last_sample(&output_buffer, 1) - 0.25).abs() < 1e-3);
+    }
+
+    #[test]
+    fn applies_scheduled_commands_inside_the_block() {
+        let mut context = Context::new(48000);
+        let mut audio_process = context.get_audio_process();
+        let mut output_buffer = OwnedAudioBuffer::new(512, 2, 48000);
+
+        let subgraph = SubgraphNode::new(
+            context.get_command_queue(),
+            context.get_sample_rate(),
+            SubgraphRate::Oversampled(OversamplingFactor::TwoTimes),
+        );
+        subgraph.connect_to_output().unwrap();
+
+        let subgraph_queue = subgraph.get_subgraph_queue();
+        let constant = ConstantSource::new(subgraph_queue.clone(), 0.5);
+        subgraph_queue
+            .try_apply_at(
+                Timestamp::from_seconds(256.0 / 48000.0),
+                JournalEntry::ConnectToOutput {
+                    previous: None,
+                    endpoint: Some(Endpoint::new(constant.get_id(), EndpointType::Output)),
+                },
+            )
+            .unwrap();
+        context.start();
+
+        audio_process.process(&mut output_buffer);
+
+        assert_eq!(output_buffer.get_sample(SampleLocation::new(0, 255)), 0.0);
+        assert!((last_sample(&output_buffer, 0) - 0.5).abs() < 1e-3);
+    }
+}
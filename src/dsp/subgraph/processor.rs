@@ -0,0 +1,207 @@
+use lockfree::channel::mpsc::Receiver;
+
+use crate::{
+    buffer::{
+        audio_buffer_slice::AudioBufferSlice,
+        immutable_audio_buffer_slice::ImmutableAudioBufferSlice,
+    },
+    commands::command::Command,
+    graph::{
+        dsp::{Dsp, DspParameterMap, DspProcessor},
+        oversampling::OversamplingFactor,
+        render_quality::RenderQuality,
+        transport::Transport,
+    },
+    midi::midi_file_player::ScheduledMidiMessage,
+    realtime::{
+        connection_schedule::ConnectionAction,
+        dsp_graph::DspGraph,
+        processor::{
+            MAXIMUM_NUMBER_OF_CHANNELS, MAXIMUM_NUMBER_OF_FRAMES, MAXIMUM_SCHEDULED_COMMANDS,
+        },
+    },
+    utility::realtime_log::{self, LogLevel},
+    AudioBuffer, Timestamp,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SubgraphRate {
+    Oversampled(OversamplingFactor),
+    Reduced(usize),
+}
+
+impl SubgraphRate {
+    pub fn sample_rate(&self, engine_sample_rate: usize) -> usize {
+        match self {
+            SubgraphRate::Oversampled(factor) => engine_sample_rate * factor.multiplier(),
+            SubgraphRate::Reduced(divisor) => engine_sample_rate / divisor,
+        }
+    }
+
+    fn maximum_number_of_frames(&self) -> usize {
+        match self {
+            SubgraphRate::Oversampled(factor) => MAXIMUM_NUMBER_OF_FRAMES * factor.multiplier(),
+            SubgraphRate::Reduced(divisor) => MAXIMUM_NUMBER_OF_FRAMES / divisor + 1,
+        }
+    }
+}
+
+pub struct SubgraphProcessor {
+    command_rx: Receiver<Command>,
+    scheduled_commands: Vec<(Timestamp, Box<Command>)>,
+    graph: DspGraph,
+}
+
+impl SubgraphProcessor {
+    pub fn new(command_rx: Receiver<Command>, rate: SubgraphRate, sample_rate: usize) -> Self {
+        Self {
+            command_rx,
+            scheduled_commands: Vec::with_capacity(MAXIMUM_SCHEDULED_COMMANDS),
+            graph: DspGraph::new(
+                rate.maximum_number_of_frames(),
+                MAXIMUM_NUMBER_OF_CHANNELS,
+                rate.sample_rate(sample_rate),
+            ),
+        }
+    }
+
+    fn process_commands(&mut self) {
+        while let Ok(command) = self.command_rx.recv() {
+            self.apply_command(command);
+        }
+    }
+
+    fn frames_until(time: &Timestamp, block_time: &Timestamp, sample_rate: usize) -> usize {
+        if *time <= *block_time {
+            0
+        } else {
+            Dsp::frame_at_time(&(*time - *block_time), sample_rate)
+        }
+    }
+
+    fn schedule_command(&mut self, time: Timestamp, command: Box<Command>) {
+        if self.scheduled_commands.len() == self.scheduled_commands.capacity() {
+            realtime_log::log(LogLevel::Warning, "Subgraph command schedule full");
+            self.graph.dispose_command(command);
+            return;
+        }
+
+        let index = self
+            .scheduled_commands
+            .iter()
+            .position(|(scheduled_time, _)| *scheduled_time > time)
+            .unwrap_or(self.scheduled_commands.len());
+
+        self.scheduled_commands.insert(index, (time, command));
+    }
+
+    fn apply_scheduled_commands(&mut self, block_time: &Timestamp, sample_rate: usize) {
+        while self
+            .scheduled_commands
+            .first()
+            .is_some_and(|(time, _)| Self::frames_until(time, block_time, sample_rate) == 0)
+        {
+            let (_, mut command) = self.scheduled_commands.remove(0);
+            let scheduled = std::mem::replace(&mut *command, Command::Start);
+            self.graph.dispose_command(command);
+            self.apply_command(scheduled);
+        }
+    }
+
+    fn apply_command(&mut self, command: Command) {
+        match command {
+            Command::AddDsp(dsp) => self.graph.add_dsp(dsp),
+            Command::RemoveDsp(id) => self.graph.remove_dsp(id),
+            Command::DetachDsp(id) => self.graph.detach_dsp(id),
+            Command::ReattachDsp(id) => self.graph.reattach_dsp(id),
+            Command::StopDsp(id, time, allow_tail) => self.graph.stop_dsp(id, time, allow_tail),
+            Command::SetDspPriority(id, priority) => self.graph.set_dsp_priority(id, priority),
+
+            Command::ParameterValueChange(change_request) => {
+                self.graph.request_parameter_change(change_request)
+            }
+            Command::ParameterValueChanges(change_requests) => {
+                self.graph.request_parameter_changes(change_requests)
+            }
+            Command::ParameterTempoSync(request) => self.graph.set_parameter_tempo_sync(request),
+            Command::SetTempoMap(tempo_map) => self.graph.set_tempo_map(tempo_map),
+            Command::SetTransportLoop(loop_beats) => self.graph.set_transport_loop(loop_beats),
+            Command::SetRenderQuality(quality) => self.graph.set_render_quality(quality),
+
+            Command::AddConnection(connection) => self.graph.add_connection(connection),
+            Command::RemoveConnection(connection) => self.graph.remove_connection(connection),
+            Command::ScheduleConnection(connection, time) => {
+                self.graph
+                    .schedule_connection(connection, time, ConnectionAction::Connect)
+            }
+            Command::ScheduleDisconnection(connection, time) => {
+                self.graph
+                    .schedule_connection(connection, time, ConnectionAction::Disconnect)
+            }
+            Command::SetConnectionFadeTime(fade_time) => {
+                self.graph.set_connection_fade_time(fade_time)
+            }
+            Command::ConnectToOutput(output_endpoint) => {
+                self.graph.connect_to_output(output_endpoint)
+            }
+            Command::DisconnectFromOutput => self.graph.disconnect_from_output(),
+            Command::ConnectInput(id) => self.graph.connect_input(id),
+            Command::DisconnectInput(id) => self.graph.disconnect_input(id),
+            Command::ConnectOutputMix(id) => self.graph.connect_output_mix(id),
+            Command::DisconnectOutputMix(id) => self.graph.disconnect_output_mix(id),
+            Command::At(time, command) => self.schedule_command(time, command),
+
+            command => self.graph.dispose_command(Box::new(command)),
+        }
+    }
+}
+
+impl DspProcessor for SubgraphProcessor {
+    fn process_audio(
+        &mut self,
+        input_buffer: &dyn AudioBuffer,
+        output_buffer: &mut dyn AudioBuffer,
+        start_time: &Timestamp,
+        _parameters: &DspParameterMap,
+    ) {
+        self.process_commands();
+
+        let num_frames = output_buffer.num_frames();
+        let sample_rate = output_buffer.sample_rate();
+        let mut offset = 0;
+
+        while offset < num_frames {
+            let block_time = start_time.incremented_by_samples(offset, sample_rate);
+            self.apply_scheduled_commands(&block_time, sample_rate);
+
+            let mut block_frames = num_frames - offset;
+            if let Some((time, _)) = self.scheduled_commands.first() {
+                block_frames = block_frames.min(Self::frames_until(time, &block_time, sample_rate));
+            }
+
+            let input_slice = (offset < input_buffer.num_frames())
+                .then(|| ImmutableAudioBufferSlice::new(input_buffer, offset));
+            let mut output_slice = AudioBufferSlice::new(output_buffer, offset, block_frames);
+            self.graph.process_with_input(
+                input_slice.as_ref().map(|slice| slice as &dyn AudioBuffer),
+                &mut output_slice,
+                &block_time,
+            );
+
+            offset += block_frames;
+        }
+    }
+
+    fn drain_midi_output(&mut self, output: &mut dyn FnMut(ScheduledMidiMessage)) {
+        self.graph.drain_midi_output(output);
+    }
+
+    fn set_render_quality(&mut self, quality: RenderQuality) {
+        self.graph.set_render_quality(quality);
+    }
+
+    fn set_transport(&mut self, transport: &Transport) {
+        self.graph.set_host_tempo(transport.beats_per_minute);
+        self.graph.set_transport_loop(transport.loop_beats);
+    }
+}
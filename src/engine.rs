@@ -0,0 +1,384 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use atomic_float::AtomicF32;
+use lockfree::channel::{
+    mpsc::{self, Receiver, Sender},
+    spsc,
+};
+
+use crate::{
+    audio_process::AudioProcess,
+    buffer::{
+        audio_buffer::AudioBuffer, audio_buffer_slice::AudioBufferSlice,
+        immutable_audio_buffer_slice::ImmutableAudioBufferSlice,
+        owned_audio_buffer::OwnedAudioBuffer, sample_location::SampleLocation,
+    },
+    commands::id::Id,
+    context::Context,
+    host_transport::HostTransport,
+    utility::realtime_log::{self, LogLevel},
+};
+
+const MAXIMUM_NUMBER_OF_FRAMES: usize = 512;
+const MAXIMUM_NUMBER_OF_CONTEXTS: usize = 32;
+
+struct ContextControl {
+    gain: Arc<AtomicF32>,
+    suspended: Arc<AtomicBool>,
+}
+
+struct ContextEntry {
+    context: Context,
+    control: ContextControl,
+}
+
+struct ContextChannel {
+    id: Id,
+    process: Box<dyn AudioProcess + Send>,
+    gain: Arc<AtomicF32>,
+    suspended: Arc<AtomicBool>,
+    current_gain: f32,
+}
+
+enum EngineCommand {
+    AddContext(ContextChannel),
+    RemoveContext(Id),
+}
+
+pub struct Engine {
+    sample_rate: usize,
+    num_channels: usize,
+    contexts: HashMap<Id, ContextEntry>,
+    command_tx: Sender<EngineCommand>,
+    disposal_rx: spsc::Receiver<ContextChannel>,
+    engine_process: Option<EngineProcess>,
+}
+
+impl Engine {
+    pub fn new(sample_rate: usize, num_channels: usize) -> Self {
+        let (command_tx, command_rx) = mpsc::create();
+        let (disposal_tx, disposal_rx) = spsc::create();
+
+        Self {
+            sample_rate,
+            num_channels,
+            contexts: HashMap::new(),
+            command_tx,
+            disposal_rx,
+            engine_process: Some(EngineProcess::new(
+                sample_rate,
+                num_channels,
+                command_rx,
+                disposal_tx,
+            )),
+        }
+    }
+
+    pub fn get_sample_rate(&self) -> usize {
+        self.sample_rate
+    }
+
+    pub fn get_num_channels(&self) -> usize {
+        self.num_channels
+    }
+
+    pub fn get_audio_process(&mut self) -> Box<dyn AudioProcess + Send> {
+        let engine_process = self.engine_process.take();
+        assert!(engine_process.is_some());
+        Box::new(engine_process.unwrap())
+    }
+
+    pub fn create_context(&mut self) -> Id {
+        let mut context = Context::new(self.sample_rate);
+
+        let control = ContextControl {
+            gain: Arc::new(AtomicF32::new(1.0)),
+            suspended: Arc::new(AtomicBool::new(false)),
+        };
+
+        let id = context.get_id();
+
+        let channel = ContextChannel {
+            id,
+            process: context.get_audio_process(),
+            gain: control.gain.clone(),
+            suspended: control.suspended.clone(),
+            current_gain: 1.0,
+        };
+
+        let _ = self.command_tx.send(EngineCommand::AddContext(channel));
+
+        self.contexts.insert(id, ContextEntry { context, control });
+
+        id
+    }
+
+    pub fn remove_context(&mut self, id: Id) -> Option<Context> {
+        let entry = self.contexts.remove(&id)?;
+        let _ = self.command_tx.send(EngineCommand::RemoveContext(id));
+        Some(entry.context)
+    }
+
+    pub fn get_context(&self, id: Id) -> Option<&Context> {
+        self.contexts.get(&id).map(|entry| &entry.context)
+    }
+
+    pub fn get_context_mut(&mut self, id: Id) -> Option<&mut Context> {
+        self.contexts.get_mut(&id).map(|entry| &mut entry.context)
+    }
+
+    pub fn get_context_ids(&self) -> Vec<Id> {
+        self.contexts.keys().copied().collect()
+    }
+
+    pub fn set_context_gain(&mut self, id: Id, gain: f32) {
+        if let Some(entry) = self.contexts.get(&id) {
+            entry.control.gain.store(gain.max(0.0), Ordering::Release);
+        }
+    }
+
+    pub fn get_context_gain(&self, id: Id) -> Option<f32> {
+        self.contexts
+            .get(&id)
+            .map(|entry| entry.control.gain.load(Ordering::Acquire))
+    }
+
+    pub fn suspend_context(&mut self, id: Id) {
+        if let Some(entry) = self.contexts.get(&id) {
+            entry.control.suspended.store(true, Ordering::Release);
+        }
+    }
+
+    pub fn resume_context(&mut self, id: Id) {
+        if let Some(entry) = self.contexts.get(&id) {
+            entry.control.suspended.store(false, Ordering::Release);
+        }
+    }
+
+    pub fn is_context_suspended(&self, id: Id) -> bool {
+        self.contexts
+            .get(&id)
+            .map(|entry| entry.control.suspended.load(Ordering::Acquire))
+            .unwrap_or(false)
+    }
+
+    pub fn process_notifications(&mut self) {
+        while let Ok(channel) = self.disposal_rx.recv() {
+            drop(channel);
+        }
+
+        for entry in self.contexts.values_mut() {
+            entry.context.process_notifications();
+        }
+    }
+}
+
+struct EngineProcess {
+    command_rx: Receiver<EngineCommand>,
+    disposal_tx: spsc::Sender<ContextChannel>,
+    channels: Vec<ContextChannel>,
+    mix_buffer: OwnedAudioBuffer,
+}
+
+impl EngineProcess {
+    fn new(
+        sample_rate: usize,
+        num_channels: usize,
+        command_rx: Receiver<EngineCommand>,
+        disposal_tx: spsc::Sender<ContextChannel>,
+    ) -> Self {
+        Self {
+            command_rx,
+            disposal_tx,
+            channels: Vec::with_capacity(MAXIMUM_NUMBER_OF_CONTEXTS),
+            mix_buffer: OwnedAudioBuffer::new(MAXIMUM_NUMBER_OF_FRAMES, num_channels, sample_rate),
+        }
+    }
+
+    fn process_commands(&mut self) {
+        while let Ok(command) = self.command_rx.recv() {
+            match command {
+                EngineCommand::AddContext(channel) => {
+                    if self.channels.len() < self.channels.capacity() {
+                        self.channels.push(channel);
+                    } else {
+                        realtime_log::log(LogLevel::Error, "Engine context capacity reached");
+                        self.dispose(channel);
+                    }
+                }
+                EngineCommand::RemoveContext(id) => {
+                    if let Some(index) = self.channels.iter().position(|channel| channel.id == id) {
+                        let channel = self.channels.swap_remove(index);
+                        self.dispose(channel);
+                    }
+                }
+            }
+        }
+    }
+
+    fn dispose(&mut self, channel: ContextChannel) {
+        if self.disposal_tx.send(channel).is_err() {
+            realtime_log::log(LogLevel::Error, "Engine disposal queue unavailable");
+        }
+    }
+
+    fn mix_block(
+        &mut self,
+        input_buffer: Option<&dyn AudioBuffer>,
+        output_buffer: &mut dyn AudioBuffer,
+        offset: usize,
+        num_frames: usize,
+    ) {
+        let num_channels = output_buffer
+            .num_channels()
+            .min(self.mix_buffer.num_channels());
+
+        let input_slice = input_buffer
+            .filter(|input_buffer| offset < input_buffer.num_frames())
+            .map(|input_buffer| ImmutableAudioBufferSlice::new(input_buffer, offset));
+
+        for channel in self.channels.iter_mut() {
+            if channel.suspended.load(Ordering::Acquire) {
+                continue;
+            }
+
+            let mut mix_slice = AudioBufferSlice::new(&mut self.mix_buffer, 0, num_frames);
+            match &input_slice {
+                Some(input_slice) => channel
+                    .process
+                    .process_with_input(input_slice, &mut mix_slice),
+                None => channel.process.process(&mut mix_slice),
+            }
+
+            let target_gain = channel.gain.load(Ordering::Acquire);
+            let gain_increment = (target_gain - channel.current_gain) / num_frames as f32;
+
+            for frame in 0..num_frames {
+                let gain = channel.current_gain + gain_increment * (frame + 1) as f32;
+
+                for channel_index in 0..num_channels {
+                    let value = self
+                        .mix_buffer
+                        .get_sample(SampleLocation::new(channel_index, frame));
+
+                    output_buffer.add_sample(
+                        SampleLocation::new(channel_index, offset + frame),
+                        value * gain,
+                    );
+                }
+            }
+
+            channel.current_gain = target_gain;
+        }
+    }
+}
+
+impl AudioProcess for EngineProcess {
+    fn process(&mut self, output_buffer: &mut dyn AudioBuffer) {
+        self.process_block(None, output_buffer);
+    }
+
+    fn process_with_input(
+        &mut self,
+        input_buffer: &dyn AudioBuffer,
+        output_buffer: &mut dyn AudioBuffer,
+    ) {
+        self.process_block(Some(input_buffer), output_buffer);
+    }
+
+    fn set_host_transport(&mut self, transport: &HostTransport) {
+        for channel in self.channels.iter_mut() {
+            channel.process.set_host_transport(transport);
+        }
+    }
+}
+
+impl EngineProcess {
+    fn process_block(
+        &mut self,
+        input_buffer: Option<&dyn AudioBuffer>,
+        output_buffer: &mut dyn AudioBuffer,
+    ) {
+        output_buffer.clear();
+
+        self.process_commands();
+
+        let mut offset = 0;
+
+        while offset < output_buffer.num_frames() {
+            let num_frames = std::cmp::min(
+                output_buffer.num_frames() - offset,
+                MAXIMUM_NUMBER_OF_FRAMES,
+            );
+
+            self.mix_block(input_buffer, output_buffer, offset, num_frames);
+
+            offset += num_frames;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsp::oscillator::node::OscillatorNode;
+    use crate::graph::node::Node;
+
+    fn process(engine_process: &mut dyn AudioProcess, num_frames: usize) -> OwnedAudioBuffer {
+        let mut buffer = OwnedAudioBuffer::new(num_frames, 2, 48000);
+        engine_process.process(&mut buffer);
+        buffer
+    }
+
+    fn peak(buffer: &OwnedAudioBuffer) -> f32 {
+        (0..buffer.num_frames())
+            .map(|frame| buffer.get_sample(SampleLocation::new(0, frame)).abs())
+            .fold(0.0, f32::max)
+    }
+
+    #[test]
+    fn mixes_contexts_with_gain_and_suspension() {
+        let mut engine = Engine::new(48000, 2);
+        let mut engine_process = engine.get_audio_process();
+
+        let first = engine.create_context();
+        let second = engine.create_context();
+
+        let mut oscillators = Vec::new();
+        for id in [first, second] {
+            let context = engine.get_context_mut(id).unwrap();
+            let oscillator = OscillatorNode::new(context.get_command_queue(), 440.0);
+            oscillator.connect_to_output().unwrap();
+            context.start();
+            oscillators.push(oscillator);
+        }
+
+        process(engine_process.as_mut(), 512);
+        let both = peak(&process(engine_process.as_mut(), 512));
+
+        engine.suspend_context(second);
+        assert!(engine.is_context_suspended(second));
+        let single = peak(&process(engine_process.as_mut(), 512));
+        assert!(single < both * 0.75);
+
+        engine.set_context_gain(first, 0.0);
+        process(engine_process.as_mut(), 512);
+        let silent = peak(&process(engine_process.as_mut(), 512));
+        assert_eq!(silent, 0.0);
+
+        engine.resume_context(second);
+        let resumed = peak(&process(engine_process.as_mut(), 512));
+        assert!(resumed > 0.0);
+
+        assert!(engine.remove_context(second).is_some());
+        process(engine_process.as_mut(), 512);
+        engine.process_notifications();
+        assert_eq!(engine.get_context_ids(), vec![first]);
+    }
+}
@@ -0,0 +1,192 @@
+use std::collections::VecDeque;
+
+use lockfree::channel::spsc::{self, Receiver, Sender};
+
+use crate::{
+    timestamp::Timestamp,
+    utility::realtime_log::{self, LogLevel},
+};
+
+pub const DEFAULT_BATCH_CHUNK_SIZE: usize = 256;
+const MAX_ACTIVE_CHUNKS: usize = 256;
+
+pub trait TimedEvent {
+    fn event_time(&self) -> Timestamp;
+}
+
+type EventChunk<T> = VecDeque<T>;
+
+pub struct EventBatchSender<T> {
+    chunk_size: usize,
+    chunk_tx: Sender<EventChunk<T>>,
+    recycle_rx: Receiver<EventChunk<T>>,
+}
+
+pub struct EventBatchReceiver<T> {
+    chunk_rx: Receiver<EventChunk<T>>,
+    recycle_tx: Sender<EventChunk<T>>,
+    active_chunks: Vec<EventChunk<T>>,
+}
+
+pub fn create_event_batch_channel<T>(
+    chunk_size: usize,
+) -> (EventBatchSender<T>, EventBatchReceiver<T>) {
+    let (chunk_tx, chunk_rx) = spsc::create();
+    let (recycle_tx, recycle_rx) = spsc::create();
+
+    (
+        EventBatchSender {
+            chunk_size: chunk_size.max(1),
+            chunk_tx,
+            recycle_rx,
+        },
+        EventBatchReceiver {
+            chunk_rx,
+            recycle_tx,
+            active_chunks: Vec::with_capacity(MAX_ACTIVE_CHUNKS),
+        },
+    )
+}
+
+impl<T: TimedEvent> EventBatchSender<T> {
+    pub fn send_batch(&mut self, events: impl IntoIterator<Item = T>) {
+        let mut events: Vec<T> = events.into_iter().collect();
+        events.sort_by_key(|event| event.event_time());
+
+        let mut chunk = self.take_chunk();
+
+        for event in events {
+            if chunk.len() == self.chunk_size {
+                self.send_chunk(chunk);
+                chunk = self.take_chunk();
+            }
+
+            chunk.push_back(event);
+        }
+
+        if !chunk.is_empty() {
+            self.send_chunk(chunk);
+        }
+    }
+
+    fn take_chunk(&mut self) -> EventChunk<T> {
+        self.recycle_rx
+            .recv()
+            .unwrap_or_else(|_| VecDeque::with_capacity(self.chunk_size))
+    }
+
+    fn send_chunk(&mut self, chunk: EventChunk<T>) {
+        let _ = self.chunk_tx.send(chunk);
+    }
+}
+
+impl<T: TimedEvent> EventBatchReceiver<T> {
+    pub fn drain_until(
+        &mut self,
+        end_time: Timestamp,
+        max_events: usize,
+        mut output: impl FnMut(T),
+    ) {
+        self.receive_chunks();
+
+        let mut remaining = max_events;
+        while remaining > 0 {
+            let index = match self.earliest_chunk_before(end_time) {
+                Some(index) => index,
+                None => return,
+            };
+
+            if let Some(event) = self.active_chunks[index].pop_front() {
+                output(event);
+                remaining -= 1;
+            }
+
+            if self.active_chunks[index].is_empty() {
+                let chunk = self.active_chunks.swap_remove(index);
+                let _ = self.recycle_tx.send(chunk);
+            }
+        }
+    }
+
+    fn receive_chunks(&mut self) {
+        while self.active_chunks.len() < self.active_chunks.capacity() {
+            match self.chunk_rx.recv() {
+                Ok(chunk) => self.active_chunks.push(chunk),
+                Err(_) => return,
+            }
+        }
+
+        realtime_log::log(LogLevel::Warning, "Event batch backlog full");
+    }
+
+    fn earliest_chunk_before(&self, end_time: Timestamp) -> Option<usize> {
+        self.active_chunks
+            .iter()
+            .enumerate()
+            .filter_map(|(index, chunk)| chunk.front().map(|event| (index, event.event_time())))
+            .filter(|(_, time)| *time < end_time)
+            .min_by_key(|(_, time)| *time)
+            .map(|(index, _)| index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Event(f64);
+
+    impl TimedEvent for Event {
+        fn event_time(&self) -> Timestamp {
+            Timestamp::from_seconds(self.0)
+        }
+    }
+
+    fn drain(receiver: &mut EventBatchReceiver<Event>, end: f64) -> Vec<f64> {
+        let mut times = Vec::new();
+        receiver.drain_until(Timestamp::from_seconds(end), usize::MAX, |event| {
+            times.push(event.0)
+        });
+        times
+    }
+
+    #[test]
+    fn delivers_due_events_in_order_across_batches() {
+        let (mut sender, mut receiver) = create_event_batch_channel(2);
+
+        sender.send_batch([3.0, 1.0, 5.0, 2.0].map(Event));
+        sender.send_batch([1.5, 4.0].map(Event));
+
+        assert_eq!(drain(&mut receiver, 2.5), vec![1.0, 1.5, 2.0]);
+        assert_eq!(drain(&mut receiver, 4.5), vec![3.0, 4.0]);
+        assert!(!receiver.active_chunks.is_empty());
+        assert_eq!(drain(&mut receiver, 10.0), vec![5.0]);
+        assert!(receiver.active_chunks.is_empty());
+    }
+
+    #[test]
+    fn limited_drain_leaves_the_rest_for_later() {
+        let (mut sender, mut receiver) = create_event_batch_channel(2);
+
+        sender.send_batch([1.0, 2.0, 3.0].map(Event));
+
+        let mut times = Vec::new();
+        receiver.drain_until(Timestamp::from_seconds(10.0), 2, |event| {
+            times.push(event.0)
+        });
+        assert_eq!(times, vec![1.0, 2.0]);
+        assert_eq!(drain(&mut receiver, 10.0), vec![3.0]);
+    }
+
+    #[test]
+    fn recycles_chunks() {
+        let (mut sender, mut receiver) = create_event_batch_channel(4);
+
+        sender.send_batch([1.0, 2.0].map(Event));
+        drain(&mut receiver, 10.0);
+
+        let chunk = sender.take_chunk();
+        assert!(chunk.is_empty());
+        assert!(chunk.capacity() >= 4);
+    }
+}
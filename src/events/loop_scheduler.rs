@@ -0,0 +1,213 @@
+use std::cmp::Ordering;
+
+use crate::timestamp::Timestamp;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct LoopRegion {
+    start: f64,
+    end: f64,
+}
+
+impl LoopRegion {
+    fn length(&self) -> f64 {
+        self.end - self.start
+    }
+}
+
+pub struct LoopScheduler {
+    origin: Timestamp,
+    loop_region: Option<LoopRegion>,
+    scheduled_until: f64,
+}
+
+impl LoopScheduler {
+    pub fn new(origin: Timestamp) -> Self {
+        Self {
+            origin,
+            loop_region: None,
+            scheduled_until: 0.0,
+        }
+    }
+
+    pub fn set_loop(&mut self, start: Timestamp, end: Timestamp) {
+        let (start, end) = (start.get_seconds(), end.get_seconds());
+
+        self.loop_region = if end > start {
+            Some(LoopRegion { start, end })
+        } else {
+            None
+        };
+    }
+
+    pub fn clear_loop(&mut self) {
+        self.loop_region = None;
+    }
+
+    pub fn is_looping(&self) -> bool {
+        self.loop_region.is_some()
+    }
+
+    pub fn get_origin(&self) -> Timestamp {
+        self.origin
+    }
+
+    pub fn song_position_at(&self, time: Timestamp) -> Timestamp {
+        let elapsed = (time.get_seconds() - self.origin.get_seconds()).max(0.0);
+
+        let position = match self.loop_region {
+            Some(region) if elapsed >= region.end => {
+                region.start + (elapsed - region.end) % region.length()
+            }
+            _ => elapsed,
+        };
+
+        Timestamp::from_seconds(position)
+    }
+
+    pub fn schedule_until<T>(
+        &mut self,
+        horizon: Timestamp,
+        events: impl IntoIterator<Item = (Timestamp, T)>,
+        mut sink: impl FnMut(Timestamp, T),
+    ) where
+        T: Clone,
+    {
+        let window_start = self.scheduled_until;
+        let window_end = horizon.get_seconds() - self.origin.get_seconds();
+
+        if window_end <= window_start {
+            return;
+        }
+
+        let mut occurrences = Vec::new();
+
+        for (song_time, event) in events {
+            let song_time = song_time.get_seconds();
+
+            self.for_each_occurrence(song_time, window_start, window_end, |elapsed| {
+                occurrences.push((elapsed, song_time, event.clone()));
+            });
+        }
+
+        occurrences.sort_by(|a, b| {
+            a.0.partial_cmp(&b.0)
+                .unwrap_or(Ordering::Equal)
+                .then(b.1.partial_cmp(&a.1).unwrap_or(Ordering::Equal))
+        });
+
+        for (elapsed, _, event) in occurrences {
+            sink(self.origin.incremented_by_seconds(elapsed), event);
+        }
+
+        self.scheduled_until = window_end;
+    }
+
+    fn for_each_occurrence(
+        &self,
+        song_time: f64,
+        window_start: f64,
+        window_end: f64,
+        mut output: impl FnMut(f64),
+    ) {
+        let in_window = |elapsed: f64| elapsed >= window_start && elapsed < window_end;
+
+        let region = match self.loop_region {
+            Some(region) if song_time >= region.start => region,
+            _ => {
+                let is_before_loop = self
+                    .loop_region
+                    .map(|region| song_time < region.start)
+                    .unwrap_or(true);
+
+                if is_before_loop && in_window(song_time) {
+                    output(song_time);
+                }
+                return;
+            }
+        };
+
+        if song_time > region.end {
+            return;
+        }
+
+        let length = region.length();
+        let first_pass = ((window_start - song_time) / length).ceil().max(0.0);
+
+        let mut elapsed = song_time + first_pass * length;
+        while elapsed < window_end {
+            if in_window(elapsed) {
+                output(elapsed);
+            }
+            elapsed += length;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use super::*;
+
+    fn schedule(scheduler: &mut LoopScheduler, horizon: f64, events: &[f64]) -> Vec<(f64, f64)> {
+        let mut output = Vec::new();
+        scheduler.schedule_until(
+            Timestamp::from_seconds(horizon),
+            events
+                .iter()
+                .map(|time| (Timestamp::from_seconds(*time), *time)),
+            |time, event| output.push((time.get_seconds(), event)),
+        );
+        output
+    }
+
+    #[test]
+    fn repeats_events_inside_loop_on_every_pass() {
+        let mut scheduler = LoopScheduler::new(Timestamp::from_seconds(10.0));
+        scheduler.set_loop(Timestamp::from_seconds(1.0), Timestamp::from_seconds(3.0));
+
+        let events = [0.5, 1.5, 4.0];
+
+        let output = schedule(&mut scheduler, 14.0, &events);
+        let times: Vec<f64> = output.iter().map(|(time, _)| *time).collect();
+        assert_eq!(times.len(), 3);
+        assert_relative_eq!(times[0], 10.5, epsilon = 1e-6);
+        assert_relative_eq!(times[1], 11.5, epsilon = 1e-6);
+        assert_relative_eq!(times[2], 13.5, epsilon = 1e-6);
+
+        let output = schedule(&mut scheduler, 16.0, &events);
+        assert_eq!(output.len(), 1);
+        assert_relative_eq!(output[0].0, 15.5, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn orders_loop_end_events_before_next_pass() {
+        let mut scheduler = LoopScheduler::new(Timestamp::zero());
+        scheduler.set_loop(Timestamp::from_seconds(1.0), Timestamp::from_seconds(2.0));
+
+        let output = schedule(&mut scheduler, 2.5, &[1.0, 2.0]);
+        let events: Vec<f64> = output.iter().map(|(_, event)| *event).collect();
+        assert_eq!(events, vec![1.0, 2.0, 1.0]);
+    }
+
+    #[test]
+    fn maps_time_to_song_position() {
+        let mut scheduler = LoopScheduler::new(Timestamp::from_seconds(5.0));
+        assert_relative_eq!(
+            scheduler
+                .song_position_at(Timestamp::from_seconds(9.0))
+                .get_seconds(),
+            4.0,
+            epsilon = 1e-6
+        );
+
+        scheduler.set_loop(Timestamp::from_seconds(1.0), Timestamp::from_seconds(3.0));
+        assert_relative_eq!(
+            scheduler
+                .song_position_at(Timestamp::from_seconds(9.0))
+                .get_seconds(),
+            2.0,
+            epsilon = 1e-6
+        );
+    }
+}
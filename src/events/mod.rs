@@ -0,0 +1,4 @@
+pub mod event_batch;
+pub mod loop_scheduler;
+pub mod note_event;
+pub mod transform;
@@ -0,0 +1,132 @@
+use crate::timestamp::Timestamp;
+
+pub type NoteId = u32;
+pub type NoteEventReceiver = lockfree::channel::mpsc::Receiver<NoteEvent>;
+pub type NoteEventTransmitter = lockfree::channel::mpsc::Sender<NoteEvent>;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NoteEventType {
+    NoteOn { pitch: f64, velocity: f64 },
+    NoteOff { velocity: f64 },
+    PitchBend { semitones: f64 },
+    Pressure { amount: f64 },
+    Timbre { amount: f64 },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NoteEvent {
+    pub time: Timestamp,
+    pub note_id: NoteId,
+    pub event_type: NoteEventType,
+}
+
+impl NoteEvent {
+    pub fn note_on(time: Timestamp, note_id: NoteId, pitch: f64, velocity: f64) -> Self {
+        Self {
+            time,
+            note_id,
+            event_type: NoteEventType::NoteOn { pitch, velocity },
+        }
+    }
+
+    pub fn note_off(time: Timestamp, note_id: NoteId, velocity: f64) -> Self {
+        Self {
+            time,
+            note_id,
+            event_type: NoteEventType::NoteOff { velocity },
+        }
+    }
+
+    pub fn pitch_bend(time: Timestamp, note_id: NoteId, semitones: f64) -> Self {
+        Self {
+            time,
+            note_id,
+            event_type: NoteEventType::PitchBend { semitones },
+        }
+    }
+
+    pub fn pressure(time: Timestamp, note_id: NoteId, amount: f64) -> Self {
+        Self {
+            time,
+            note_id,
+            event_type: NoteEventType::Pressure { amount },
+        }
+    }
+
+    pub fn timbre(time: Timestamp, note_id: NoteId, amount: f64) -> Self {
+        Self {
+            time,
+            note_id,
+            event_type: NoteEventType::Timbre { amount },
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NoteExpression {
+    pub pitch: f64,
+    pub velocity: f64,
+    pub pitch_bend: f64,
+    pub pressure: f64,
+    pub timbre: f64,
+}
+
+impl NoteExpression {
+    pub fn new(pitch: f64, velocity: f64) -> Self {
+        Self {
+            pitch,
+            velocity,
+            pitch_bend: 0.0,
+            pressure: 0.0,
+            timbre: 0.5,
+        }
+    }
+
+    pub fn apply(&mut self, event_type: &NoteEventType) {
+        match *event_type {
+            NoteEventType::NoteOn { pitch, velocity } => *self = Self::new(pitch, velocity),
+            NoteEventType::NoteOff { .. } => (),
+            NoteEventType::PitchBend { semitones } => self.pitch_bend = semitones,
+            NoteEventType::Pressure { amount } => self.pressure = amount,
+            NoteEventType::Timbre { amount } => self.timbre = amount,
+        }
+    }
+
+    pub fn frequency(&self) -> f64 {
+        440.0 * 2.0_f64.powf((self.pitch + self.pitch_bend - 69.0) / 12.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use super::*;
+
+    #[test]
+    fn expression_tracks_per_note_controls() {
+        let mut expression = NoteExpression::new(69.0, 0.8);
+        assert_relative_eq!(expression.frequency(), 440.0);
+
+        expression.apply(&NoteEventType::PitchBend { semitones: 12.0 });
+        expression.apply(&NoteEventType::Pressure { amount: 0.3 });
+        expression.apply(&NoteEventType::Timbre { amount: 0.9 });
+
+        assert_relative_eq!(expression.frequency(), 880.0);
+        assert_relative_eq!(expression.pressure, 0.3);
+        assert_relative_eq!(expression.timbre, 0.9);
+    }
+
+    #[test]
+    fn note_on_resets_expression() {
+        let mut expression = NoteExpression::new(60.0, 0.5);
+        expression.apply(&NoteEventType::PitchBend { semitones: 2.0 });
+        expression.apply(&NoteEventType::NoteOn {
+            pitch: 64.0,
+            velocity: 1.0,
+        });
+
+        assert_relative_eq!(expression.pitch_bend, 0.0);
+        assert_relative_eq!(expression.pitch, 64.0);
+    }
+}
@@ -0,0 +1,231 @@
+use std::collections::HashMap;
+
+use crate::{tempo_map::TempoMap, timestamp::Timestamp, utility::random::Random};
+
+use super::note_event::{NoteEvent, NoteEventType, NoteId};
+
+pub trait NoteEventTransform {
+    fn transform(&mut self, event: NoteEvent, output: &mut dyn FnMut(NoteEvent));
+}
+
+#[derive(Default)]
+struct NoteOffsets {
+    offsets: HashMap<NoteId, f64>,
+}
+
+impl NoteOffsets {
+    fn apply(
+        &mut self,
+        mut event: NoteEvent,
+        note_on_offset: impl FnOnce(&mut NoteEvent) -> f64,
+    ) -> NoteEvent {
+        let offset = match event.event_type {
+            NoteEventType::NoteOn { .. } => {
+                let offset = note_on_offset(&mut event);
+                self.offsets.insert(event.note_id, offset);
+                offset
+            }
+            NoteEventType::NoteOff { .. } => self.offsets.remove(&event.note_id).unwrap_or(0.0),
+            _ => self.offsets.get(&event.note_id).copied().unwrap_or(0.0),
+        };
+
+        event.time = Timestamp::from_seconds((event.time.get_seconds() + offset).max(0.0));
+        event
+    }
+}
+
+pub struct Quantize {
+    tempo_map: TempoMap,
+    grid: f64,
+    strength: f64,
+    swing: f64,
+    offsets: NoteOffsets,
+}
+
+impl Quantize {
+    pub fn new(tempo_map: TempoMap, grid_in_beats: f64) -> Self {
+        assert!(grid_in_beats > 0.0);
+
+        Self {
+            tempo_map,
+            grid: grid_in_beats,
+            strength: 1.0,
+            swing: 0.0,
+            offsets: NoteOffsets::default(),
+        }
+    }
+
+    pub fn with_strength(mut self, strength: f64) -> Self {
+        self.strength = strength.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn with_swing(mut self, swing: f64) -> Self {
+        self.swing = swing.clamp(0.0, 1.0);
+        self
+    }
+
+    fn offset_for(&self, time: Timestamp) -> f64 {
+        let seconds = time.get_seconds();
+        let beat = self.tempo_map.beat_at_seconds(seconds);
+
+        let step = (beat / self.grid).round();
+        let mut target = step * self.grid;
+        if step as i64 % 2 != 0 {
+            target += self.swing * self.grid * 0.5;
+        }
+
+        let quantized_beat = beat + (target - beat) * self.strength;
+        self.tempo_map.seconds_at_beat(quantized_beat) - seconds
+    }
+}
+
+impl NoteEventTransform for Quantize {
+    fn transform(&mut self, event: NoteEvent, output: &mut dyn FnMut(NoteEvent)) {
+        let offset = self.offset_for(event.time);
+        output(self.offsets.apply(event, |_| offset));
+    }
+}
+
+pub struct Humanize {
+    timing: f64,
+    velocity: f64,
+    random: Random,
+    offsets: NoteOffsets,
+}
+
+impl Humanize {
+    pub fn new(timing_in_seconds: f64, velocity: f64) -> Self {
+        Self {
+            timing: timing_in_seconds.abs(),
+            velocity: velocity.abs(),
+            random: Random::default(),
+            offsets: NoteOffsets::default(),
+        }
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.random = Random::with_seed(seed);
+        self
+    }
+}
+
+impl NoteEventTransform for Humanize {
+    fn transform(&mut self, event: NoteEvent, output: &mut dyn FnMut(NoteEvent)) {
+        let random = &mut self.random;
+        let timing = self.timing;
+        let velocity_amount = self.velocity;
+
+        output(self.offsets.apply(event, |event| {
+            if let NoteEventType::NoteOn { velocity, .. } = &mut event.event_type {
+                *velocity = (*velocity + velocity_amount * random.next_bipolar()).clamp(0.0, 1.0);
+            }
+
+            timing * random.next_bipolar()
+        }));
+    }
+}
+
+#[derive(Default)]
+pub struct NoteEventTransformChain {
+    transforms: Vec<Box<dyn NoteEventTransform + Send>>,
+}
+
+impl NoteEventTransformChain {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, transform: impl NoteEventTransform + Send + 'static) -> Self {
+        self.transforms.push(Box::new(transform));
+        self
+    }
+
+    pub fn process(&mut self, event: NoteEvent, output: &mut dyn FnMut(NoteEvent)) {
+        Self::process_from(&mut self.transforms, event, output);
+    }
+
+    fn process_from(
+        transforms: &mut [Box<dyn NoteEventTransform + Send>],
+        event: NoteEvent,
+        output: &mut dyn FnMut(NoteEvent),
+    ) {
+        match transforms.split_first_mut() {
+            Some((first, rest)) => {
+                first.transform(event, &mut |event| Self::process_from(rest, event, output))
+            }
+            None => output(event),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use super::*;
+
+    fn collect(transform: &mut dyn NoteEventTransform, event: NoteEvent) -> NoteEvent {
+        let mut result = None;
+        transform.transform(event, &mut |event| result = Some(event));
+        result.unwrap()
+    }
+
+    #[test]
+    fn quantize_moves_notes_to_grid_and_keeps_length() {
+        let mut quantize = Quantize::new(TempoMap::new(120.0), 0.5);
+
+        let note_on = collect(
+            &mut quantize,
+            NoteEvent::note_on(Timestamp::from_seconds(0.27), 1, 60.0, 1.0),
+        );
+        assert_relative_eq!(note_on.time.get_seconds(), 0.25, epsilon = 1e-6);
+
+        let note_off = collect(
+            &mut quantize,
+            NoteEvent::note_off(Timestamp::from_seconds(0.77), 1, 0.0),
+        );
+        assert_relative_eq!(note_off.time.get_seconds(), 0.75, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn quantize_strength_and_swing() {
+        let mut quantize = Quantize::new(TempoMap::new(120.0), 0.5).with_strength(0.5);
+        let event = collect(
+            &mut quantize,
+            NoteEvent::note_on(Timestamp::from_seconds(0.03), 1, 60.0, 1.0),
+        );
+        assert_relative_eq!(event.time.get_seconds(), 0.015, epsilon = 1e-6);
+
+        let mut quantize = Quantize::new(TempoMap::new(120.0), 0.5).with_swing(0.5);
+        let event = collect(
+            &mut quantize,
+            NoteEvent::note_on(Timestamp::from_seconds(0.25), 2, 60.0, 1.0),
+        );
+        assert_relative_eq!(event.time.get_seconds(), 0.3125, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn humanize_stays_within_bounds() {
+        let mut chain = NoteEventTransformChain::new()
+            .with(Quantize::new(TempoMap::new(120.0), 0.25))
+            .with(Humanize::new(0.01, 0.1).with_seed(7));
+
+        for note_id in 0..100 {
+            let mut events = Vec::new();
+            chain.process(
+                NoteEvent::note_on(Timestamp::from_seconds(1.0), note_id, 60.0, 0.5),
+                &mut |event| events.push(event),
+            );
+
+            assert_eq!(events.len(), 1);
+            assert!((events[0].time.get_seconds() - 1.0).abs() <= 0.01);
+            match events[0].event_type {
+                NoteEventType::NoteOn { velocity, .. } => {
+                    assert!((velocity - 0.5).abs() <= 0.1)
+                }
+                _ => panic!("Expected note on"),
+            }
+        }
+    }
+}
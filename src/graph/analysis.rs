@@ -0,0 +1,417 @@
+use std::{collections::HashMap, fmt, mem, time::Duration};
+
+use crate::{commands::id::Id, realtime::profiler::NodeProfile};
+
+use super::{
+    node::Node,
+    validation::{self, GraphSnapshot},
+};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct NodeEstimate {
+    pub latency_in_samples: usize,
+    pub cost: Duration,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum AnalysisWarning {
+    ExceedsRealtimeBudget {
+        estimated: Duration,
+        budget: Duration,
+    },
+    BufferPoolExhausted {
+        required: usize,
+        available: usize,
+    },
+    MisalignedInputs {
+        node: Id,
+        shortest: usize,
+        longest: usize,
+    },
+    Cycle(Vec<Id>),
+}
+
+impl fmt::Display for AnalysisWarning {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AnalysisWarning::ExceedsRealtimeBudget { estimated, budget } => write!(
+                formatter,
+                "Estimated processing time {:?} exceeds the block budget of {:?}",
+                estimated, budget
+            ),
+            AnalysisWarning::BufferPoolExhausted {
+                required,
+                available,
+            } => write!(
+                formatter,
+                "Graph needs {} buffers but the pool holds {}",
+                required, available
+            ),
+            AnalysisWarning::MisalignedInputs {
+                node,
+                shortest,
+                longest,
+            } => write!(
+                formatter,
+                "Inputs to {} arrive between {} and {} samples late",
+                validation::label(*node),
+                shortest,
+                longest
+            ),
+            AnalysisWarning::Cycle(nodes) => {
+                let labels: Vec<String> = nodes.iter().map(|id| validation::label(*id)).collect();
+                write!(formatter, "Cycle through {}", labels.join(", "))
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct GraphAnalysis {
+    pub num_nodes: usize,
+    pub num_channels: usize,
+    pub buffers_required: usize,
+    pub buffer_memory_in_bytes: usize,
+    pub estimated_cost: Duration,
+    pub block_budget: Duration,
+    pub node_latencies: HashMap<Id, usize>,
+    pub output_latency_in_samples: Option<usize>,
+    pub unestimated_nodes: Vec<Id>,
+    pub warnings: Vec<AnalysisWarning>,
+}
+
+impl GraphAnalysis {
+    pub fn load(&self) -> f64 {
+        if self.block_budget.is_zero() {
+            return 0.0;
+        }
+
+        self.estimated_cost.as_secs_f64() / self.block_budget.as_secs_f64()
+    }
+
+    pub fn is_realtime_safe(&self) -> bool {
+        self.warnings.iter().all(|warning| {
+            !matches!(
+                warning,
+                AnalysisWarning::ExceedsRealtimeBudget { .. }
+                    | AnalysisWarning::BufferPoolExhausted { .. }
+            )
+        })
+    }
+}
+
+pub struct GraphAnalyzer {
+    sample_rate: usize,
+    block_size: usize,
+    num_channels: usize,
+    buffer_pool_capacity: usize,
+    snapshot: GraphSnapshot,
+    estimates: HashMap<Id, NodeEstimate>,
+}
+
+impl GraphAnalyzer {
+    pub fn new(
+        sample_rate: usize,
+        block_size: usize,
+        num_channels: usize,
+        buffer_pool_capacity: usize,
+    ) -> Self {
+        Self {
+            sample_rate,
+            block_size,
+            num_channels,
+            buffer_pool_capacity,
+            snapshot: GraphSnapshot::default(),
+            estimates: HashMap::new(),
+        }
+    }
+
+    pub fn with_snapshot(mut self, snapshot: GraphSnapshot) -> Self {
+        self.snapshot = snapshot;
+        self
+    }
+
+    pub fn with_node(mut self, id: Id) -> Self {
+        if !self.snapshot.nodes.contains(&id) {
+            self.snapshot.nodes.push(id);
+        }
+        self
+    }
+
+    pub fn with_connection(mut self, source: Id, destination: Id) -> Self {
+        if !self.snapshot.connections.contains(&(source, destination)) {
+            self.snapshot.connections.push((source, destination));
+        }
+        self
+    }
+
+    pub fn without_connection(mut self, source: Id, destination: Id) -> Self {
+        self.snapshot
+            .connections
+            .retain(|connection| *connection != (source, destination));
+        self
+    }
+
+    pub fn with_output(mut self, id: Id) -> Self {
+        self.snapshot.output = Some(id);
+        self
+    }
+
+    pub fn with_estimate(mut self, id: Id, estimate: NodeEstimate) -> Self {
+        self.estimates.insert(id, estimate);
+        self
+    }
+
+    pub fn with_node_latency(mut self, node: &dyn Node) -> Self {
+        self.estimates
+            .entry(node.get_id())
+            .or_default()
+            .latency_in_samples = node.latency_in_samples();
+        self
+    }
+
+    pub fn with_profiles(mut self, profiles: &HashMap<Id, NodeProfile>) -> Self {
+        for profile in profiles.values() {
+            self.estimates.entry(profile.dsp_id).or_default().cost = profile.mean;
+        }
+        self
+    }
+
+    fn sorted_nodes(&self) -> (Vec<Id>, Vec<Id>) {
+        let mut num_inputs: HashMap<Id, usize> =
+            self.snapshot.nodes.iter().map(|id| (*id, 0)).collect();
+
+        for (_, destination) in self.snapshot.connections.iter() {
+            if let Some(count) = num_inputs.get_mut(destination) {
+                *count += 1;
+            }
+        }
+
+        let mut ready: Vec<Id> = self
+            .snapshot
+            .nodes
+            .iter()
+            .filter(|id| num_inputs[id] == 0)
+            .copied()
+            .collect();
+        let mut order = Vec::with_capacity(self.snapshot.nodes.len());
+
+        while let Some(id) = ready.pop() {
+            order.push(id);
+
+            for (source, destination) in self.snapshot.connections.iter() {
+                if *source != id {
+                    continue;
+                }
+
+                if let Some(count) = num_inputs.get_mut(destination) {
+                    *count -= 1;
+                    if *count == 0 {
+                        ready.push(*destination);
+                    }
+                }
+            }
+        }
+
+        let cyclic = self
+            .snapshot
+            .nodes
+            .iter()
+            .filter(|id| !order.contains(id))
+            .copied()
+            .collect();
+
+        (order, cyclic)
+    }
+
+    pub fn analyze(&self) -> GraphAnalysis {
+        let mut warnings = Vec::new();
+        let (order, cyclic) = self.sorted_nodes();
+
+        let mut node_latencies: HashMap<Id, usize> = HashMap::new();
+        for id in order.iter() {
+            let input_latencies: Vec<usize> = self
+                .snapshot
+                .connections
+                .iter()
+                .filter(|(_, destination)| destination == id)
+                .filter_map(|(source, _)| node_latencies.get(source).copied())
+                .collect();
+
+            let shortest = input_latencies.iter().copied().min().unwrap_or(0);
+            let longest = input_latencies.iter().copied().max().unwrap_or(0);
+            if shortest != longest {
+                warnings.push(AnalysisWarning::MisalignedInputs {
+                    node: *id,
+                    shortest,
+                    longest,
+                });
+            }
+
+            let own_latency = self
+                .estimates
+                .get(id)
+                .map_or(0, |estimate| estimate.latency_in_samples);
+            node_latencies.insert(*id, longest + own_latency);
+        }
+
+        if !cyclic.is_empty() {
+            warnings.push(AnalysisWarning::Cycle(cyclic));
+        }
+
+        let num_nodes = self.snapshot.nodes.len();
+        let buffers_required = if num_nodes == 0 { 0 } else { num_nodes + 1 };
+        if buffers_required > self.buffer_pool_capacity {
+            warnings.push(AnalysisWarning::BufferPoolExhausted {
+                required: buffers_required,
+                available: self.buffer_pool_capacity,
+            });
+        }
+
+        let estimated_cost = self
+            .snapshot
+            .nodes
+            .iter()
+            .filter_map(|id| self.estimates.get(id))
+            .map(|estimate| estimate.cost)
+            .sum();
+        let block_budget =
+            Duration::from_secs_f64(self.block_size as f64 / self.sample_rate.max(1) as f64);
+        if estimated_cost > block_budget {
+            warnings.push(AnalysisWarning::ExceedsRealtimeBudget {
+                estimated: estimated_cost,
+                budget: block_budget,
+            });
+        }
+
+        let unestimated_nodes = self
+            .snapshot
+            .nodes
+            .iter()
+            .filter(|id| {
+                self.estimates
+                    .get(id)
+                    .is_none_or(|estimate| estimate.cost.is_zero())
+            })
+            .copied()
+            .collect();
+
+        GraphAnalysis {
+            num_nodes,
+            num_channels: self.num_channels,
+            buffers_required,
+            buffer_memory_in_bytes: buffers_required
+                * self.block_size
+                * self.num_channels
+                * mem::size_of::<f32>(),
+            estimated_cost,
+            block_budget,
+            output_latency_in_samples: self
+                .snapshot
+                .output
+                .and_then(|id| node_latencies.get(&id).copied()),
+            node_latencies,
+            unestimated_nodes,
+            warnings,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn analyzer() -> GraphAnalyzer {
+        GraphAnalyzer::new(48_000, 480, 2, 8)
+    }
+
+    #[test]
+    fn propagates_latency_and_flags_misaligned_inputs() {
+        let [dry, wet, mix] = [Id::generate(), Id::generate(), Id::generate()];
+
+        let analysis = analyzer()
+            .with_node(dry)
+            .with_node(wet)
+            .with_node(mix)
+            .with_connection(dry, mix)
+            .with_connection(wet, mix)
+            .with_output(mix)
+            .with_estimate(
+                wet,
+                NodeEstimate {
+                    latency_in_samples: 64,
+                    cost: Duration::from_micros(100),
+                },
+            )
+            .analyze();
+
+        assert_eq!(analysis.output_latency_in_samples, Some(64));
+        assert_eq!(analysis.buffers_required, 4);
+        assert_eq!(analysis.buffer_memory_in_bytes, 4 * 480 * 2 * 4);
+        assert_eq!(analysis.unestimated_nodes.len(), 2);
+        assert_eq!(
+            analysis.warnings,
+            vec![AnalysisWarning::MisalignedInputs {
+                node: mix,
+                shortest: 0,
+                longest: 64
+            }]
+        );
+        assert!(analysis.is_realtime_safe());
+    }
+
+    #[test]
+    fn warns_when_patch_exceeds_budget() {
+        let nodes: Vec<Id> = (0..8).map(|_| Id::generate()).collect();
+
+        let analyzer = nodes.iter().fold(analyzer(), |analyzer, id| {
+            analyzer.with_node(*id).with_estimate(
+                *id,
+                NodeEstimate {
+                    latency_in_samples: 0,
+                    cost: Duration::from_millis(2),
+                },
+            )
+        });
+
+        let analysis = analyzer.analyze();
+        assert!((analysis.load() - 1.6).abs() < 1e-9);
+        assert!(!analysis.is_realtime_safe());
+        assert!(analysis
+            .warnings
+            .contains(&AnalysisWarning::BufferPoolExhausted {
+                required: 9,
+                available: 8
+            }));
+        assert!(analysis
+            .warnings
+            .contains(&AnalysisWarning::ExceedsRealtimeBudget {
+                estimated: Duration::from_millis(16),
+                budget: Duration::from_millis(10),
+            }));
+    }
+
+    #[test]
+    fn reports_cycles_in_proposed_connections() {
+        let [a, b] = [Id::generate(), Id::generate()];
+
+        let analysis = analyzer()
+            .with_node(a)
+            .with_node(b)
+            .with_connection(a, b)
+            .with_connection(b, a)
+            .analyze();
+        assert!(
+            matches!(&analysis.warnings[..], [AnalysisWarning::Cycle(nodes)] if nodes.len() == 2)
+        );
+
+        let analysis = analyzer()
+            .with_node(a)
+            .with_node(b)
+            .with_connection(a, b)
+            .with_connection(b, a)
+            .without_connection(b, a)
+            .analyze();
+        assert!(analysis.warnings.is_empty());
+    }
+}
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use crate::{
     buffer::{audio_buffer::AudioBuffer, owned_audio_buffer::OwnedAudioBuffer},
@@ -7,6 +7,7 @@ use crate::{
 
 pub struct BufferPool {
     assigned_buffers: HashMap<Endpoint, OwnedAudioBuffer>,
+    silent_endpoints: HashSet<Endpoint>,
     free_buffers: Vec<OwnedAudioBuffer>,
     num_buffers: usize,
 }
@@ -20,6 +21,7 @@ impl BufferPool {
     ) -> Self {
         Self {
             assigned_buffers: HashMap::with_capacity(num_buffers),
+            silent_endpoints: HashSet::with_capacity(num_buffers),
             free_buffers: (0..num_buffers)
                 .map(|_| OwnedAudioBuffer::new(num_frames, num_channels, sample_rate))
                 .collect(),
@@ -35,12 +37,31 @@ impl BufferPool {
         self.assigned_buffers.remove(&for_endpoint)
     }
 
+    pub fn is_silent(&self, endpoint: Endpoint) -> bool {
+        self.silent_endpoints.contains(&endpoint)
+    }
+
     pub fn return_buffer(&mut self, mut buffer: OwnedAudioBuffer) {
         buffer.clear();
         self.free_buffers.push(buffer)
     }
 
-    pub fn return_buffer_with_assignment(&mut self, buffer: OwnedAudioBuffer, endpoint: Endpoint) {
+    pub fn return_silent_buffer(&mut self, buffer: OwnedAudioBuffer) {
+        self.free_buffers.push(buffer)
+    }
+
+    pub fn return_buffer_with_assignment(
+        &mut self,
+        buffer: OwnedAudioBuffer,
+        endpoint: Endpoint,
+        is_silent: bool,
+    ) {
+        if is_silent {
+            self.silent_endpoints.insert(endpoint);
+        } else {
+            self.silent_endpoints.remove(&endpoint);
+        }
+
         self.assigned_buffers.insert(endpoint, buffer);
     }
 
@@ -48,7 +69,12 @@ impl BufferPool {
         while !self.assigned_buffers.is_empty() {
             let endpoint = *self.assigned_buffers.keys().next().unwrap();
             let buffer = self.assigned_buffers.remove(&endpoint).unwrap();
-            self.return_buffer(buffer);
+
+            if self.silent_endpoints.remove(&endpoint) {
+                self.return_silent_buffer(buffer);
+            } else {
+                self.return_buffer(buffer);
+            }
         }
     }
 
@@ -56,3 +82,30 @@ impl BufferPool {
         self.free_buffers.len() == self.num_buffers
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{commands::id::Id, graph::endpoint::EndpointType};
+
+    use super::*;
+
+    #[test]
+    fn tracks_silence_of_assigned_buffers() {
+        let mut pool = BufferPool::with_capacity(2, 16, 2, 44100);
+        let silent_endpoint = Endpoint::new(Id::generate(), EndpointType::Output);
+        let audible_endpoint = Endpoint::new(Id::generate(), EndpointType::Output);
+
+        let buffer = pool.get_unassigned_buffer().unwrap();
+        pool.return_buffer_with_assignment(buffer, silent_endpoint, true);
+        let buffer = pool.get_unassigned_buffer().unwrap();
+        pool.return_buffer_with_assignment(buffer, audible_endpoint, false);
+
+        assert!(pool.is_silent(silent_endpoint));
+        assert!(!pool.is_silent(audible_endpoint));
+
+        pool.clear_assignments();
+
+        assert!(!pool.is_silent(silent_endpoint));
+        assert!(pool.all_buffers_are_available());
+    }
+}
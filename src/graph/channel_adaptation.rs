@@ -0,0 +1,149 @@
+use super::channel_matrix::{ChannelMatrix, MAXIMUM_MATRIX_CHANNELS};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DownmixLaw {
+    Sum,
+    #[default]
+    Average,
+    EqualPower,
+    LeftOnly,
+    RightOnly,
+}
+
+impl DownmixLaw {
+    pub fn coefficient(&self, source_channel: usize, num_folded_channels: usize) -> f32 {
+        let num_folded_channels = num_folded_channels.max(1) as f32;
+        match self {
+            DownmixLaw::Sum => 1.0,
+            DownmixLaw::Average => 1.0 / num_folded_channels,
+            DownmixLaw::EqualPower => 1.0 / num_folded_channels.sqrt(),
+            DownmixLaw::LeftOnly => (source_channel == 0) as usize as f32,
+            DownmixLaw::RightOnly => (source_channel == 1) as usize as f32,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UpmixLaw {
+    #[default]
+    Duplicate,
+    EqualPower,
+    LeftOnly,
+}
+
+impl UpmixLaw {
+    pub fn coefficient(&self, destination_channel: usize, num_destination_channels: usize) -> f32 {
+        match self {
+            UpmixLaw::Duplicate => 1.0,
+            UpmixLaw::EqualPower => 1.0 / (num_destination_channels.max(1) as f32).sqrt(),
+            UpmixLaw::LeftOnly => (destination_channel == 0) as usize as f32,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ChannelAdaptation {
+    pub downmix: DownmixLaw,
+    pub upmix: UpmixLaw,
+}
+
+impl ChannelAdaptation {
+    pub fn new(downmix: DownmixLaw, upmix: UpmixLaw) -> Self {
+        Self { downmix, upmix }
+    }
+
+    pub fn matrix(
+        &self,
+        num_source_channels: usize,
+        num_destination_channels: usize,
+    ) -> Option<ChannelMatrix> {
+        let num_source_channels = num_source_channels.clamp(1, MAXIMUM_MATRIX_CHANNELS);
+        let num_destination_channels = num_destination_channels.clamp(1, MAXIMUM_MATRIX_CHANNELS);
+
+        if num_source_channels == num_destination_channels {
+            return None;
+        }
+
+        let mut matrix = ChannelMatrix::silent();
+
+        if num_source_channels == 1 {
+            for destination in 0..num_destination_channels {
+                matrix.set_gain(
+                    0,
+                    destination,
+                    self.upmix
+                        .coefficient(destination, num_destination_channels),
+                );
+            }
+        } else if num_source_channels > num_destination_channels {
+            for source in 0..num_source_channels {
+                let destination = source % num_destination_channels;
+                let num_folded_channels = (destination..num_source_channels)
+                    .step_by(num_destination_channels)
+                    .count();
+                let law_channel = if num_destination_channels == 1 {
+                    source
+                } else {
+                    source / num_destination_channels
+                };
+                matrix.set_gain(
+                    source,
+                    destination,
+                    self.downmix.coefficient(law_channel, num_folded_channels),
+                );
+            }
+        } else {
+            for channel in 0..num_source_channels {
+                matrix.set_gain(channel, channel, 1.0);
+            }
+        }
+
+        Some(matrix)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_downmix_matrices_for_each_law() {
+        let gains = |law: DownmixLaw| {
+            let matrix = ChannelAdaptation::new(law, UpmixLaw::default())
+                .matrix(2, 1)
+                .unwrap();
+            [matrix.get_gain(0, 0), matrix.get_gain(1, 0)]
+        };
+
+        assert_eq!(gains(DownmixLaw::Sum), [1.0, 1.0]);
+        assert_eq!(gains(DownmixLaw::Average), [0.5, 0.5]);
+        assert_eq!(gains(DownmixLaw::EqualPower), [0.5_f32.sqrt(); 2]);
+        assert_eq!(gains(DownmixLaw::LeftOnly), [1.0, 0.0]);
+        assert_eq!(gains(DownmixLaw::RightOnly), [0.0, 1.0]);
+
+        let matrix = ChannelAdaptation::default().matrix(4, 2).unwrap();
+        assert_eq!(matrix.get_gain(2, 0), 0.5);
+        assert_eq!(matrix.get_gain(3, 1), 0.5);
+        assert_eq!(matrix.get_gain(1, 0), 0.0);
+    }
+
+    #[test]
+    fn builds_upmix_matrices_for_each_law() {
+        let gains = |law: UpmixLaw| {
+            let matrix = ChannelAdaptation::new(DownmixLaw::default(), law)
+                .matrix(1, 2)
+                .unwrap();
+            [matrix.get_gain(0, 0), matrix.get_gain(0, 1)]
+        };
+
+        assert_eq!(gains(UpmixLaw::Duplicate), [1.0, 1.0]);
+        assert_eq!(gains(UpmixLaw::EqualPower), [0.5_f32.sqrt(); 2]);
+        assert_eq!(gains(UpmixLaw::LeftOnly), [1.0, 0.0]);
+
+        let matrix = ChannelAdaptation::default().matrix(2, 4).unwrap();
+        assert_eq!(matrix.get_gain(1, 1), 1.0);
+        assert_eq!(matrix.get_gain(1, 3), 0.0);
+
+        assert!(ChannelAdaptation::default().matrix(2, 2).is_none());
+    }
+}
@@ -0,0 +1,77 @@
+pub const MAXIMUM_MATRIX_CHANNELS: usize = 8;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ChannelMatrix {
+    gains: [[f32; MAXIMUM_MATRIX_CHANNELS]; MAXIMUM_MATRIX_CHANNELS],
+}
+
+impl Default for ChannelMatrix {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl ChannelMatrix {
+    pub fn silent() -> Self {
+        Self {
+            gains: [[0.0; MAXIMUM_MATRIX_CHANNELS]; MAXIMUM_MATRIX_CHANNELS],
+        }
+    }
+
+    pub fn identity() -> Self {
+        let mut matrix = Self::silent();
+        for channel in 0..MAXIMUM_MATRIX_CHANNELS {
+            matrix.gains[channel][channel] = 1.0;
+        }
+        matrix
+    }
+
+    pub fn swap_stereo() -> Self {
+        Self::identity()
+            .with_gain(0, 0, 0.0)
+            .with_gain(1, 1, 0.0)
+            .with_gain(1, 0, 1.0)
+            .with_gain(0, 1, 1.0)
+    }
+
+    pub fn mono_sum() -> Self {
+        Self::identity()
+            .with_gain(0, 0, 0.5)
+            .with_gain(1, 1, 0.5)
+            .with_gain(1, 0, 0.5)
+            .with_gain(0, 1, 0.5)
+    }
+
+    pub fn with_gain(
+        mut self,
+        source_channel: usize,
+        destination_channel: usize,
+        gain: f32,
+    ) -> Self {
+        self.set_gain(source_channel, destination_channel, gain);
+        self
+    }
+
+    pub fn set_gain(&mut self, source_channel: usize, destination_channel: usize, gain: f32) {
+        if source_channel < MAXIMUM_MATRIX_CHANNELS && destination_channel < MAXIMUM_MATRIX_CHANNELS
+        {
+            self.gains[destination_channel][source_channel] = gain;
+        }
+    }
+
+    pub fn get_gain(&self, source_channel: usize, destination_channel: usize) -> f32 {
+        if source_channel < MAXIMUM_MATRIX_CHANNELS && destination_channel < MAXIMUM_MATRIX_CHANNELS
+        {
+            self.gains[destination_channel][source_channel]
+        } else {
+            0.0
+        }
+    }
+
+    pub fn destination_gains(&self, destination_channel: usize) -> &[f32] {
+        match self.gains.get(destination_channel) {
+            Some(gains) => gains,
+            None => &[],
+        }
+    }
+}
@@ -1,11 +1,17 @@
 use crate::commands::id::Id;
 
-use super::endpoint::{Endpoint, EndpointType};
+use super::{
+    channel_matrix::ChannelMatrix,
+    endpoint::{Endpoint, EndpointType},
+};
 
 #[derive(Clone, PartialEq)]
 pub struct Connection {
     pub source: Endpoint,
     pub destination: Endpoint,
+    pub gain: f32,
+    pub channel_matrix: Option<ChannelMatrix>,
+    pub feedback: bool,
 }
 
 impl Connection {
@@ -13,6 +19,55 @@ impl Connection {
         Self {
             source: Endpoint::new(source_id, EndpointType::Output),
             destination: Endpoint::new(destination_id, EndpointType::Input),
+            gain: 1.0,
+            channel_matrix: None,
+            feedback: false,
         }
     }
+
+    pub fn feedback(source_id: Id, destination_id: Id) -> Self {
+        Self {
+            feedback: true,
+            ..Self::new(source_id, destination_id)
+        }
+    }
+
+    pub fn to_parameter(source_id: Id, destination_id: Id, parameter_id: Id) -> Self {
+        Self {
+            destination: Endpoint::new(destination_id, EndpointType::Parameter(parameter_id)),
+            ..Self::new(source_id, destination_id)
+        }
+    }
+
+    pub fn to_sidechain(source_id: Id, destination_id: Id) -> Self {
+        Self {
+            destination: Endpoint::new(destination_id, EndpointType::Sidechain),
+            ..Self::new(source_id, destination_id)
+        }
+    }
+
+    pub fn is_sidechain(&self) -> bool {
+        self.destination.endpoint_type == EndpointType::Sidechain
+    }
+
+    pub fn get_destination_parameter(&self) -> Option<Id> {
+        match self.destination.endpoint_type {
+            EndpointType::Parameter(parameter_id) => Some(parameter_id),
+            _ => None,
+        }
+    }
+
+    pub fn with_gain(mut self, gain: f32) -> Self {
+        self.gain = gain;
+        self
+    }
+
+    pub fn with_channel_matrix(mut self, channel_matrix: ChannelMatrix) -> Self {
+        self.channel_matrix = Some(channel_matrix);
+        self
+    }
+
+    pub fn is_unity(&self) -> bool {
+        self.gain == 1.0 && self.channel_matrix.is_none()
+    }
 }
@@ -0,0 +1,354 @@
+use std::time::Duration;
+
+use crate::{
+    buffer::{
+        audio_buffer::AudioBuffer, borrowed_audio_buffer::BorrowedAudioBuffer,
+        sample_location::SampleLocation,
+    },
+    midi::midi_file_player::ScheduledMidiMessage,
+    realtime::garbage_collector::GarbageCollectionCommand,
+    realtime::processor::{MAXIMUM_NUMBER_OF_CHANNELS, MAXIMUM_NUMBER_OF_FRAMES},
+    timestamp::Timestamp,
+};
+
+use super::{
+    dsp::{DspParameterMap, DspProcessor},
+    oversampling::{half_band_taps, StageState, NUM_TAPS, STAGE_LATENCY},
+    render_quality::RenderQuality,
+    transport::Transport,
+};
+
+pub struct Decimated<P: DspProcessor> {
+    processor: P,
+    factor: usize,
+    phase: usize,
+    taps: [f32; NUM_TAPS],
+    down_stages: Vec<[StageState; MAXIMUM_NUMBER_OF_CHANNELS]>,
+    up_stages: Vec<[StageState; MAXIMUM_NUMBER_OF_CHANNELS]>,
+    pending: Vec<[f32; MAXIMUM_NUMBER_OF_CHANNELS]>,
+    upsampled: Vec<f32>,
+    scratch: Vec<f32>,
+    decimated: Vec<f32>,
+    processed: Vec<f32>,
+}
+
+impl<P: DspProcessor> Decimated<P> {
+    pub fn new(processor: P, factor: usize) -> Self {
+        assert!(factor >= 2 && factor.is_power_of_two());
+
+        let num_stages = factor.trailing_zeros() as usize;
+        let capacity = (MAXIMUM_NUMBER_OF_FRAMES / factor + 1) * MAXIMUM_NUMBER_OF_CHANNELS;
+
+        Self {
+            processor,
+            factor,
+            phase: 0,
+            taps: half_band_taps(),
+            down_stages: vec![Default::default(); num_stages],
+            up_stages: vec![Default::default(); num_stages],
+            pending: vec![[0.0; MAXIMUM_NUMBER_OF_CHANNELS]; num_stages],
+            upsampled: vec![0.0; factor * MAXIMUM_NUMBER_OF_CHANNELS],
+            scratch: vec![0.0; factor],
+            decimated: vec![0.0; capacity],
+            processed: vec![0.0; capacity],
+        }
+    }
+
+    pub fn latency_in_samples(&self) -> usize {
+        self.factor + 2 * STAGE_LATENCY * (self.factor - 1)
+    }
+
+    pub fn processor(&self) -> &P {
+        &self.processor
+    }
+
+    fn downsample_frame(
+        &mut self,
+        samples: &mut [f32; MAXIMUM_NUMBER_OF_CHANNELS],
+        num_channels: usize,
+    ) -> bool {
+        for (stage, (states, pending)) in self
+            .down_stages
+            .iter_mut()
+            .zip(self.pending.iter_mut())
+            .enumerate()
+        {
+            if !self.phase.is_multiple_of(2 << stage) {
+                *pending = *samples;
+                return false;
+            }
+
+            for channel in 0..num_channels {
+                samples[channel] =
+                    states[channel].downsample(&self.taps, [pending[channel], samples[channel]]);
+            }
+        }
+
+        true
+    }
+
+    fn upsample_frame(&mut self, processed_frame: usize, num_channels: usize) {
+        for channel in 0..num_channels {
+            let mut num_samples = 1;
+            self.scratch[0] = self.processed[processed_frame * num_channels + channel];
+
+            for states in self.up_stages.iter_mut().rev() {
+                for index in 0..num_samples {
+                    let samples = states[channel].upsample(&self.taps, self.scratch[index]);
+                    self.upsampled[2 * index * num_channels + channel] = samples[0];
+                    self.upsampled[(2 * index + 1) * num_channels + channel] = samples[1];
+                }
+
+                num_samples *= 2;
+                for index in 0..num_samples {
+                    self.scratch[index] = self.upsampled[index * num_channels + channel];
+                }
+            }
+        }
+    }
+
+    fn process_chunk(
+        &mut self,
+        input_buffer: &dyn AudioBuffer,
+        output_buffer: &mut dyn AudioBuffer,
+        offset: usize,
+        num_frames: usize,
+        start_time: &Timestamp,
+        parameters: &DspParameterMap,
+    ) {
+        let num_channels = output_buffer.num_channels().min(MAXIMUM_NUMBER_OF_CHANNELS);
+        let num_input_channels = input_buffer.num_channels().min(num_channels);
+
+        let start_phase = self.phase;
+        let mut num_decimated_frames = 0;
+
+        for frame in 0..num_frames {
+            let mut samples = [0.0; MAXIMUM_NUMBER_OF_CHANNELS];
+            for (channel, sample) in samples.iter_mut().enumerate().take(num_input_channels) {
+                *sample = input_buffer.get_sample(SampleLocation::new(channel, offset + frame));
+            }
+
+            self.phase += 1;
+
+            if self.downsample_frame(&mut samples, num_channels) {
+                self.phase = 0;
+
+                let decimated_frame = num_decimated_frames * num_channels;
+                self.decimated[decimated_frame..decimated_frame + num_channels]
+                    .copy_from_slice(&samples[..num_channels]);
+
+                num_decimated_frames += 1;
+            }
+        }
+
+        if num_decimated_frames > 0 {
+            let num_samples = num_decimated_frames * num_channels;
+            let decimated_rate = output_buffer.sample_rate() / self.factor;
+
+            let input = BorrowedAudioBuffer::new(
+                &mut self.decimated[..num_samples],
+                num_channels,
+                decimated_rate,
+            );
+            let mut output = BorrowedAudioBuffer::new(
+                &mut self.processed[..num_samples],
+                num_channels,
+                decimated_rate,
+            );
+            output.clear();
+            self.processor
+                .process_audio(&input, &mut output, start_time, parameters);
+        }
+
+        let mut phase = start_phase;
+        let mut processed_frame = 0;
+
+        for frame in 0..num_frames {
+            for channel in 0..num_channels {
+                output_buffer.set_sample(
+                    SampleLocation::new(channel, offset + frame),
+                    self.upsampled[phase * num_channels + channel],
+                );
+            }
+
+            phase += 1;
+
+            if phase == self.factor {
+                phase = 0;
+                self.upsample_frame(processed_frame, num_channels);
+                processed_frame += 1;
+            }
+        }
+    }
+}
+
+impl<P: DspProcessor> DspProcessor for Decimated<P> {
+    fn process_audio(
+        &mut self,
+        input_buffer: &dyn AudioBuffer,
+        output_buffer: &mut dyn AudioBuffer,
+        start_time: &Timestamp,
+        parameters: &DspParameterMap,
+    ) {
+        let num_frames = output_buffer.num_frames();
+        let sample_rate = output_buffer.sample_rate();
+        let mut offset = 0;
+
+        while offset < num_frames {
+            let chunk_size = (num_frames - offset).min(MAXIMUM_NUMBER_OF_FRAMES);
+            let chunk_time = start_time.incremented_by_samples(offset, sample_rate);
+            self.process_chunk(
+                input_buffer,
+                output_buffer,
+                offset,
+                chunk_size,
+                &chunk_time,
+                parameters,
+            );
+            offset += chunk_size;
+        }
+    }
+
+    fn drain_midi_output(&mut self, output: &mut dyn FnMut(ScheduledMidiMessage)) {
+        self.processor.drain_midi_output(output);
+    }
+
+    fn drain_garbage(&mut self, dispose: &mut dyn FnMut(GarbageCollectionCommand)) {
+        self.processor.drain_garbage(dispose);
+    }
+
+    fn tail_time(&self) -> Option<Duration> {
+        self.processor.tail_time()
+    }
+
+    fn stop(&mut self, stop_time: &Timestamp, allow_tail: bool) {
+        self.processor.stop(stop_time, allow_tail);
+    }
+
+    fn set_render_quality(&mut self, quality: RenderQuality) {
+        self.processor.set_render_quality(quality);
+    }
+
+    fn set_transport(&mut self, transport: &Transport) {
+        self.processor.set_transport(transport);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::TAU;
+
+    use crate::OwnedAudioBuffer;
+
+    use super::*;
+
+    const SAMPLE_RATE: usize = 48_000;
+
+    struct FrameCounter {
+        sample_rate: usize,
+        num_frames: usize,
+    }
+
+    impl DspProcessor for FrameCounter {
+        fn process_audio(
+            &mut self,
+            input_buffer: &dyn AudioBuffer,
+            output_buffer: &mut dyn AudioBuffer,
+            _start_time: &Timestamp,
+            _parameters: &DspParameterMap,
+        ) {
+            self.sample_rate = output_buffer.sample_rate();
+            self.num_frames += output_buffer.num_frames();
+            output_buffer.add_from(
+                input_buffer,
+                SampleLocation::new(0, 0),
+                SampleLocation::new(0, 0),
+                output_buffer.num_channels(),
+                output_buffer.num_frames(),
+            );
+        }
+    }
+
+    fn process(decimated: &mut Decimated<FrameCounter>, input: &[f32]) -> Vec<f32> {
+        let mut output = Vec::new();
+
+        for num_frames in [100, 37, 512, 3, 60].into_iter().cycle() {
+            let start = output.len();
+            if start == input.len() {
+                break;
+            }
+
+            let num_frames = num_frames.min(input.len() - start);
+            let mut input_buffer = OwnedAudioBuffer::new(num_frames, 2, SAMPLE_RATE);
+            for frame in 0..num_frames {
+                input_buffer.set_sample(SampleLocation::new(0, frame), input[start + frame]);
+                input_buffer.set_sample(SampleLocation::new(1, frame), -input[start + frame]);
+            }
+
+            let mut output_buffer = OwnedAudioBuffer::new(num_frames, 2, SAMPLE_RATE);
+            decimated.process_audio(
+                &input_buffer,
+                &mut output_buffer,
+                &Timestamp::zero(),
+                &DspParameterMap::new(),
+            );
+
+            for frame in 0..num_frames {
+                let left = output_buffer.get_sample(SampleLocation::new(0, frame));
+                let right = output_buffer.get_sample(SampleLocation::new(1, frame));
+                assert_eq!(left, -right);
+                output.push(left);
+            }
+        }
+
+        output
+    }
+
+    fn sine(frame: usize, frequency: f64) -> f32 {
+        (TAU * frequency * frame as f64 / SAMPLE_RATE as f64).sin() as f32
+    }
+
+    #[test]
+    fn runs_processor_at_reduced_rate_with_reported_latency() {
+        for factor in [2, 4, 8] {
+            let processor = FrameCounter {
+                sample_rate: 0,
+                num_frames: 0,
+            };
+            let mut decimated = Decimated::new(processor, factor);
+            let latency = decimated.latency_in_samples();
+
+            let input: Vec<f32> = (0..4000).map(|frame| sine(frame, 200.0)).collect();
+            let output = process(&mut decimated, &input);
+
+            assert_eq!(decimated.processor().sample_rate, SAMPLE_RATE / factor);
+            assert_eq!(decimated.processor().num_frames, output.len() / factor);
+            for (frame, sample) in output.iter().enumerate().skip(2 * latency) {
+                assert!(
+                    (sample - sine(frame - latency, 200.0)).abs() < 0.01,
+                    "factor {} frame {}",
+                    factor,
+                    frame
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn filters_content_above_the_reduced_nyquist_frequency() {
+        let factor = 4;
+        let processor = FrameCounter {
+            sample_rate: 0,
+            num_frames: 0,
+        };
+        let mut decimated = Decimated::new(processor, factor);
+
+        let input: Vec<f32> = (0..4000).map(|frame| sine(frame, 9000.0)).collect();
+        let output = process(&mut decimated, &input);
+
+        let peak = output[1000..]
+            .iter()
+            .fold(0.0_f32, |peak, sample| peak.max(sample.abs()));
+        assert!(peak < 0.01, "peak {}", peak);
+    }
+}
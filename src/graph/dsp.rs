@@ -1,23 +1,53 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, time::Duration};
 
 use crate::{
-    buffer::audio_buffer::AudioBuffer,
+    buffer::{
+        audio_buffer::AudioBuffer, audio_buffer_slice::AudioBufferSlice,
+        immutable_audio_buffer_slice::ImmutableAudioBufferSlice, sample_location::SampleLocation,
+    },
     commands::{
-        command::{Command, ParameterChangeRequest},
+        command::{Command, ParameterChangeRequest, ParameterTempoSyncRequest},
+        command_queue::CommandQueue,
         id::Id,
+        journal::JournalEntry,
     },
+    graph::{realtime_budget::Degradation, render_quality::RenderQuality, transport::Transport},
+    midi::midi_file_player::ScheduledMidiMessage,
     parameter::realtime_parameter::RealtimeAudioParameter,
+    realtime::garbage_collector::GarbageCollectionCommand,
     timestamp::Timestamp,
 };
 
-use lockfree::channel::mpsc::Sender;
+const FRAME_ROUNDING_TOLERANCE: f64 = 1e-3;
+const SILENCE_THRESHOLD: f32 = 1e-6;
 
 pub type DspParameterMap = HashMap<Id, RealtimeAudioParameter>;
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum StopState {
+    Pending,
+    Stopped,
+    Idle,
+}
+
+#[derive(Clone, Copy)]
+struct DspStop {
+    time: Timestamp,
+    allow_tail: bool,
+    state: StopState,
+}
+
 pub struct Dsp {
     id: Id,
     processor: Box<dyn DspProcessor + Send + Sync>,
     parameters: DspParameterMap,
+    detached: bool,
+    quarantined: bool,
+    silent_input_frames: usize,
+    sleeping: bool,
+    stop: Option<DspStop>,
+    priority: i32,
+    degradation: Option<Degradation>,
 }
 
 pub trait DspProcessor {
@@ -28,6 +58,31 @@ pub trait DspProcessor {
         start_time: &Timestamp,
         parameters: &DspParameterMap,
     );
+
+    fn process_audio_with_sidechain(
+        &mut self,
+        input_buffer: &dyn AudioBuffer,
+        _sidechain_buffer: &dyn AudioBuffer,
+        output_buffer: &mut dyn AudioBuffer,
+        start_time: &Timestamp,
+        parameters: &DspParameterMap,
+    ) {
+        self.process_audio(input_buffer, output_buffer, start_time, parameters);
+    }
+
+    fn drain_midi_output(&mut self, _output: &mut dyn FnMut(ScheduledMidiMessage)) {}
+
+    fn drain_garbage(&mut self, _dispose: &mut dyn FnMut(GarbageCollectionCommand)) {}
+
+    fn tail_time(&self) -> Option<Duration> {
+        None
+    }
+
+    fn stop(&mut self, _stop_time: &Timestamp, _allow_tail: bool) {}
+
+    fn set_render_quality(&mut self, _quality: RenderQuality) {}
+
+    fn set_transport(&mut self, _transport: &Transport) {}
 }
 
 impl Dsp {
@@ -40,33 +95,402 @@ impl Dsp {
             id,
             processor,
             parameters,
+            detached: false,
+            quarantined: false,
+            silent_input_frames: 0,
+            sleeping: false,
+            stop: None,
+            priority: 0,
+            degradation: None,
         }
     }
 
-    pub fn add_to_audio_process(dsp: Self, command_queue: &Sender<Command>) {
-        let _ = command_queue.send(Command::AddDsp(Box::new(dsp)));
+    pub fn add_to_audio_process(dsp: Self, command_queue: &CommandQueue) {
+        let id = dsp.get_id();
+        command_queue.send_recorded(Command::AddDsp(Box::new(dsp)));
+        command_queue.journal(JournalEntry::AddDsp(id));
     }
 
-    pub fn remove_from_audio_process(id: Id, command_queue: &Sender<Command>) {
-        let _ = command_queue.send(Command::RemoveDsp(id));
+    pub fn remove_from_audio_process(id: Id, command_queue: &CommandQueue) {
+        command_queue.send_recorded(Command::RemoveDsp(id));
     }
 
     pub fn get_id(&self) -> Id {
         self.id
     }
 
+    pub fn is_detached(&self) -> bool {
+        self.detached
+    }
+
+    pub fn set_detached(&mut self, detached: bool) {
+        self.detached = detached;
+    }
+
+    pub fn is_quarantined(&self) -> bool {
+        self.quarantined
+    }
+
+    pub fn quarantine(&mut self) {
+        self.quarantined = true;
+    }
+
+    pub fn get_priority(&self) -> i32 {
+        self.priority
+    }
+
+    pub fn set_priority(&mut self, priority: i32) {
+        self.priority = priority;
+    }
+
+    pub fn is_degraded(&self) -> bool {
+        self.degradation.is_some()
+    }
+
+    pub fn is_skipped(&self) -> bool {
+        self.degradation == Some(Degradation::Skip)
+    }
+
+    pub fn degrade(&mut self, degradation: Degradation) {
+        if degradation == Degradation::Draft {
+            self.processor.set_render_quality(RenderQuality::Draft);
+        }
+        self.degradation = Some(degradation);
+    }
+
+    pub fn restore(&mut self, quality: RenderQuality) {
+        if self.degradation.take() == Some(Degradation::Draft) {
+            self.processor.set_render_quality(quality);
+        }
+    }
+
+    pub fn is_sleeping(&self) -> bool {
+        self.sleeping
+    }
+
+    pub fn stop_at(&mut self, time: Timestamp, allow_tail: bool) {
+        if self
+            .stop
+            .is_some_and(|stop| stop.state != StopState::Pending)
+        {
+            return;
+        }
+
+        self.stop = Some(DspStop {
+            time,
+            allow_tail,
+            state: StopState::Pending,
+        });
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.stop.is_some_and(|stop| stop.state == StopState::Idle)
+    }
+
+    fn stop_frame(
+        &self,
+        start_time: &Timestamp,
+        num_frames: usize,
+        sample_rate: usize,
+    ) -> Option<usize> {
+        let stop = self.stop?;
+
+        if stop.state != StopState::Pending {
+            return Some(0);
+        }
+
+        if stop.time <= *start_time {
+            return Some(0);
+        }
+
+        let frame = Self::frame_at_time(&(stop.time - *start_time), sample_rate);
+        (frame < num_frames).then_some(frame)
+    }
+
+    pub fn silence_input_after_stop(
+        &self,
+        input_buffer: &mut dyn AudioBuffer,
+        start_time: &Timestamp,
+        num_frames: usize,
+    ) {
+        if let Some(frame) = self.stop_frame(start_time, num_frames, input_buffer.sample_rate()) {
+            AudioBufferSlice::new(input_buffer, frame, num_frames - frame).clear();
+        }
+    }
+
+    pub fn update_idle_state(
+        &mut self,
+        output_buffer: &dyn AudioBuffer,
+        num_frames: usize,
+    ) -> bool {
+        match self.stop.as_mut() {
+            Some(stop)
+                if stop.state == StopState::Stopped
+                    && (!stop.allow_tail || Self::is_silent(output_buffer, num_frames)) =>
+            {
+                stop.state = StopState::Idle;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn update_sleep_state(
+        &mut self,
+        input_buffer: &dyn AudioBuffer,
+        input_is_silent: bool,
+        num_frames: usize,
+    ) -> bool {
+        let tail_time = match self.processor.tail_time() {
+            Some(tail_time) => tail_time,
+            None => return false,
+        };
+
+        if !input_is_silent && !Self::is_silent(input_buffer, num_frames) {
+            self.silent_input_frames = 0;
+            self.sleeping = false;
+            return false;
+        }
+
+        let tail_frames =
+            (tail_time.as_secs_f64() * input_buffer.sample_rate() as f64).ceil() as usize;
+
+        self.sleeping = self.silent_input_frames >= tail_frames;
+        self.silent_input_frames = self.silent_input_frames.saturating_add(num_frames);
+        self.sleeping
+    }
+
+    fn is_silent(buffer: &dyn AudioBuffer, num_frames: usize) -> bool {
+        let num_frames = num_frames.min(buffer.num_frames());
+
+        (0..num_frames).all(|frame| {
+            (0..buffer.num_channels()).all(|channel| {
+                buffer.get_sample(SampleLocation::new(channel, frame)).abs() < SILENCE_THRESHOLD
+            })
+        })
+    }
+
     pub fn process_audio(
         &mut self,
         input_buffer: &dyn AudioBuffer,
         output_buffer: &mut dyn AudioBuffer,
         start_time: &Timestamp,
     ) {
-        for (_, parameter) in self.parameters.iter_mut() {
-            parameter.set_current_time(*start_time);
+        self.process_audio_with_sidechain(input_buffer, None, output_buffer, start_time);
+    }
+
+    pub fn process_audio_with_sidechain(
+        &mut self,
+        input_buffer: &dyn AudioBuffer,
+        sidechain_buffer: Option<&dyn AudioBuffer>,
+        output_buffer: &mut dyn AudioBuffer,
+        start_time: &Timestamp,
+    ) {
+        let sample_rate = output_buffer.sample_rate();
+        let num_frames = output_buffer.num_frames();
+
+        let (stop_frame, stop) = match self
+            .stop
+            .filter(|stop| stop.state == StopState::Pending)
+            .and_then(|stop| Some((self.stop_frame(start_time, num_frames, sample_rate)?, stop)))
+        {
+            Some(pending) => pending,
+            None => {
+                if !self
+                    .stop
+                    .is_some_and(|stop| stop.state != StopState::Pending && !stop.allow_tail)
+                {
+                    self.process_segments(
+                        input_buffer,
+                        sidechain_buffer,
+                        output_buffer,
+                        start_time,
+                    );
+                }
+                return;
+            }
+        };
+
+        if stop_frame > 0 {
+            self.process_segments(
+                input_buffer,
+                sidechain_buffer,
+                &mut AudioBufferSlice::new(output_buffer, 0, stop_frame),
+                start_time,
+            );
+        }
+
+        self.processor.stop(&stop.time, stop.allow_tail);
+        self.stop = Some(DspStop {
+            state: StopState::Stopped,
+            ..stop
+        });
+
+        if stop.allow_tail {
+            self.process_segments(
+                &ImmutableAudioBufferSlice::new(input_buffer, stop_frame),
+                sidechain_buffer
+                    .map(|sidechain| ImmutableAudioBufferSlice::new(sidechain, stop_frame))
+                    .as_ref()
+                    .map(|sidechain| sidechain as &dyn AudioBuffer),
+                &mut AudioBufferSlice::new(output_buffer, stop_frame, num_frames - stop_frame),
+                &start_time.incremented_by_samples(stop_frame, sample_rate),
+            );
+        }
+    }
+
+    fn process_segments(
+        &mut self,
+        input_buffer: &dyn AudioBuffer,
+        sidechain_buffer: Option<&dyn AudioBuffer>,
+        output_buffer: &mut dyn AudioBuffer,
+        start_time: &Timestamp,
+    ) {
+        let sample_rate = output_buffer.sample_rate();
+        let num_frames = output_buffer.num_frames();
+        let end_time = start_time.incremented_by_samples(num_frames, sample_rate);
+
+        let mut offset = 0;
+        let mut parameter_time = *start_time;
+
+        while offset < num_frames {
+            for (_, parameter) in self.parameters.iter_mut() {
+                parameter.set_current_time(parameter_time);
+            }
+
+            let next_change = self.next_immediate_parameter_change(&parameter_time, &end_time);
+
+            let segment_end = next_change
+                .map(|time| Self::frame_at_time(&(time - *start_time), sample_rate))
+                .filter(|frame| *frame > offset && *frame < num_frames)
+                .unwrap_or(num_frames);
+
+            if offset == 0 && segment_end == num_frames {
+                self.process_segment(input_buffer, sidechain_buffer, output_buffer, start_time);
+                return;
+            }
+
+            self.process_segment(
+                &ImmutableAudioBufferSlice::new(input_buffer, offset),
+                sidechain_buffer
+                    .map(|sidechain| ImmutableAudioBufferSlice::new(sidechain, offset))
+                    .as_ref()
+                    .map(|sidechain| sidechain as &dyn AudioBuffer),
+                &mut AudioBufferSlice::new(output_buffer, offset, segment_end - offset),
+                &start_time.incremented_by_samples(offset, sample_rate),
+            );
+
+            offset = segment_end;
+            parameter_time = std::cmp::max(
+                start_time.incremented_by_samples(offset, sample_rate),
+                next_change.unwrap_or(end_time),
+            );
+        }
+    }
+
+    fn process_segment(
+        &mut self,
+        input_buffer: &dyn AudioBuffer,
+        sidechain_buffer: Option<&dyn AudioBuffer>,
+        output_buffer: &mut dyn AudioBuffer,
+        start_time: &Timestamp,
+    ) {
+        match sidechain_buffer {
+            Some(sidechain_buffer) => self.processor.process_audio_with_sidechain(
+                input_buffer,
+                sidechain_buffer,
+                output_buffer,
+                start_time,
+                &self.parameters,
+            ),
+            None => self.processor.process_audio(
+                input_buffer,
+                output_buffer,
+                start_time,
+                &self.parameters,
+            ),
+        }
+    }
+
+    pub fn frame_at_time(offset: &Timestamp, sample_rate: usize) -> usize {
+        (offset.get_samples(sample_rate) - FRAME_ROUNDING_TOLERANCE).ceil() as usize
+    }
+
+    fn next_immediate_parameter_change(
+        &self,
+        start_time: &Timestamp,
+        end_time: &Timestamp,
+    ) -> Option<Timestamp> {
+        self.parameters
+            .values()
+            .filter_map(|parameter| {
+                parameter.get_next_immediate_change_between(start_time, end_time)
+            })
+            .min()
+    }
+
+    pub fn drain_midi_output(&mut self, output: &mut dyn FnMut(ScheduledMidiMessage)) {
+        self.processor.drain_midi_output(output);
+    }
+
+    pub fn drain_garbage(&mut self, dispose: &mut dyn FnMut(GarbageCollectionCommand)) {
+        self.processor.drain_garbage(dispose);
+    }
+
+    pub fn set_parameter_modulation(
+        &mut self,
+        parameter_id: Id,
+        offset: f64,
+        minimum: f64,
+        maximum: f64,
+    ) {
+        if let Some(parameter) = self.parameters.get_mut(&parameter_id) {
+            parameter.set_modulation(offset, minimum, maximum);
+        }
+    }
+
+    pub fn clear_audio_modulation(&mut self) {
+        for parameter in self.parameters.values_mut() {
+            parameter.clear_audio_modulation();
         }
+    }
 
-        self.processor
-            .process_audio(input_buffer, output_buffer, start_time, &self.parameters);
+    pub fn add_audio_modulation(
+        &mut self,
+        parameter_id: Id,
+        start_time: Timestamp,
+        sample_rate: usize,
+        num_frames: usize,
+        modulation_at_frame: impl Fn(usize) -> f64,
+    ) {
+        if let Some(parameter) = self.parameters.get_mut(&parameter_id) {
+            parameter.add_audio_modulation(
+                start_time,
+                sample_rate,
+                num_frames,
+                modulation_at_frame,
+            );
+        }
+    }
+
+    pub fn set_parameter_tempo_sync(&mut self, request: ParameterTempoSyncRequest) {
+        if let Some(parameter) = self.parameters.get_mut(&request.parameter_id) {
+            parameter.set_tempo_sync(request.sync, request.minimum, request.maximum);
+        }
+    }
+
+    pub fn set_transport(&mut self, transport: &Transport) {
+        for parameter in self.parameters.values_mut() {
+            parameter.set_tempo(transport.beats_per_minute);
+        }
+
+        self.processor.set_transport(transport);
+    }
+
+    pub fn set_render_quality(&mut self, quality: RenderQuality) {
+        if self.degradation != Some(Degradation::Draft) {
+            self.processor.set_render_quality(quality);
+        }
     }
 
     pub fn request_parameter_change(&mut self, parameter_change: ParameterChangeRequest) {
@@ -77,4 +501,164 @@ impl Dsp {
 }
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use atomic_float::AtomicF64;
+
+    use crate::{
+        buffer::{owned_audio_buffer::OwnedAudioBuffer, sample_location::SampleLocation},
+        parameter::{ParameterChange, ParameterValue},
+    };
+
+    use super::*;
+
+    struct BlockRateProcessor {
+        parameter_id: Id,
+    }
+
+    impl DspProcessor for BlockRateProcessor {
+        fn process_audio(
+            &mut self,
+            _input_buffer: &dyn AudioBuffer,
+            output_buffer: &mut dyn AudioBuffer,
+            _start_time: &Timestamp,
+            parameters: &DspParameterMap,
+        ) {
+            let value = parameters.get(&self.parameter_id).unwrap().get_value();
+
+            for frame in 0..output_buffer.num_frames() {
+                output_buffer.set_sample(SampleLocation::new(0, frame), value as f32);
+            }
+        }
+    }
+
+    #[test]
+    fn applies_immediate_parameter_changes_mid_block() {
+        let sample_rate = 1000;
+        let parameter_id = Id::generate();
+
+        let mut parameters = DspParameterMap::new();
+        parameters.insert(
+            parameter_id,
+            RealtimeAudioParameter::new(parameter_id, ParameterValue::new(AtomicF64::new(0.0))),
+        );
+
+        let mut dsp = Dsp::new(
+            Id::generate(),
+            Box::new(BlockRateProcessor { parameter_id }),
+            parameters,
+        );
+
+        dsp.request_parameter_change(ParameterChangeRequest {
+            dsp_id: dsp.get_id(),
+            parameter_id,
+            change: ParameterChange::immediate(1.0, Timestamp::from_samples(40.0, sample_rate)),
+        });
+
+        let input_buffer = OwnedAudioBuffer::new(100, 1, sample_rate);
+        let mut output_buffer = OwnedAudioBuffer::new(100, 1, sample_rate);
+        dsp.process_audio(&input_buffer, &mut output_buffer, &Timestamp::zero());
+
+        assert_eq!(output_buffer.get_sample(SampleLocation::new(0, 39)), 0.0);
+        assert_eq!(output_buffer.get_sample(SampleLocation::new(0, 40)), 1.0);
+        assert_eq!(output_buffer.get_sample(SampleLocation::new(0, 99)), 1.0);
+    }
+
+    struct ReleaseProcessor {
+        remaining_release_frames: Option<usize>,
+    }
+
+    impl DspProcessor for ReleaseProcessor {
+        fn process_audio(
+            &mut self,
+            _input_buffer: &dyn AudioBuffer,
+            output_buffer: &mut dyn AudioBuffer,
+            _start_time: &Timestamp,
+            _parameters: &DspParameterMap,
+        ) {
+            for frame in 0..output_buffer.num_frames() {
+                let value = match self.remaining_release_frames.as_mut() {
+                    None => 1.0,
+                    Some(0) => 0.0,
+                    Some(remaining) => {
+                        *remaining -= 1;
+                        0.5
+                    }
+                };
+
+                output_buffer.set_sample(SampleLocation::new(0, frame), value);
+            }
+        }
+
+        fn stop(&mut self, _stop_time: &Timestamp, allow_tail: bool) {
+            self.remaining_release_frames = Some(if allow_tail { 80 } else { 0 });
+        }
+    }
+
+    fn process_block(dsp: &mut Dsp, block: usize) -> OwnedAudioBuffer {
+        let input_buffer = OwnedAudioBuffer::new(100, 1, 1000);
+        let mut output_buffer = OwnedAudioBuffer::new(100, 1, 1000);
+        dsp.process_audio(
+            &input_buffer,
+            &mut output_buffer,
+            &Timestamp::from_samples(block as f64 * 100.0, 1000),
+        );
+        output_buffer
+    }
+
+    #[test]
+    fn stops_sample_accurately_with_and_without_tail() {
+        for allow_tail in [false, true] {
+            let mut dsp = Dsp::new(
+                Id::generate(),
+                Box::new(ReleaseProcessor {
+                    remaining_release_frames: None,
+                }),
+                DspParameterMap::new(),
+            );
+            dsp.stop_at(Timestamp::from_samples(40.0, 1000), allow_tail);
+
+            let output = process_block(&mut dsp, 0);
+            assert_eq!(output.get_sample(SampleLocation::new(0, 39)), 1.0);
+            let tail = if allow_tail { 0.5 } else { 0.0 };
+            assert_eq!(output.get_sample(SampleLocation::new(0, 40)), tail);
+            assert_eq!(dsp.update_idle_state(&output, 100), !allow_tail);
+
+            if allow_tail {
+                let output = process_block(&mut dsp, 1);
+                assert_eq!(output.get_sample(SampleLocation::new(0, 19)), 0.5);
+                assert_eq!(output.get_sample(SampleLocation::new(0, 20)), 0.0);
+                assert!(!dsp.update_idle_state(&output, 100));
+
+                let output = process_block(&mut dsp, 2);
+                assert!(dsp.update_idle_state(&output, 100));
+            }
+
+            assert!(dsp.is_idle());
+        }
+    }
+
+    #[test]
+    fn silences_input_after_stop_time() {
+        let mut dsp = Dsp::new(
+            Id::generate(),
+            Box::new(ReleaseProcessor {
+                remaining_release_frames: None,
+            }),
+            DspParameterMap::new(),
+        );
+        dsp.stop_at(Timestamp::from_samples(140.0, 1000), true);
+
+        let mut input_buffer = OwnedAudioBuffer::new(100, 1, 1000);
+        input_buffer.fill_with_value(1.0);
+        dsp.silence_input_after_stop(&mut input_buffer, &Timestamp::zero(), 100);
+        assert_eq!(input_buffer.get_sample(SampleLocation::new(0, 99)), 1.0);
+
+        dsp.silence_input_after_stop(
+            &mut input_buffer,
+            &Timestamp::from_samples(100.0, 1000),
+            100,
+        );
+        assert_eq!(input_buffer.get_sample(SampleLocation::new(0, 39)), 1.0);
+        assert_eq!(input_buffer.get_sample(SampleLocation::new(0, 40)), 0.0);
+    }
+}
@@ -3,7 +3,9 @@ use crate::commands::id::Id;
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub enum EndpointType {
     Input,
+    Sidechain,
     Output,
+    Parameter(Id),
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
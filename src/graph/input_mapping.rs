@@ -0,0 +1,266 @@
+use crate::{
+    buffer::{audio_buffer::AudioBuffer, sample_location::SampleLocation},
+    realtime::processor::MAXIMUM_NUMBER_OF_CHANNELS,
+    utility::level::Level,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct InputChannel {
+    hardware_channel: usize,
+    trim_db: f64,
+    phase_invert: bool,
+    gain: f32,
+}
+
+impl InputChannel {
+    pub fn new(hardware_channel: usize) -> Self {
+        Self {
+            hardware_channel,
+            trim_db: 0.0,
+            phase_invert: false,
+            gain: 1.0,
+        }
+    }
+
+    pub fn with_trim(mut self, trim_db: f64) -> Self {
+        self.set_trim(trim_db);
+        self
+    }
+
+    pub fn with_phase_invert(mut self, phase_invert: bool) -> Self {
+        self.set_phase_invert(phase_invert);
+        self
+    }
+
+    pub fn get_hardware_channel(&self) -> usize {
+        self.hardware_channel
+    }
+
+    pub fn get_trim(&self) -> f64 {
+        self.trim_db
+    }
+
+    pub fn is_phase_inverted(&self) -> bool {
+        self.phase_invert
+    }
+
+    fn set_trim(&mut self, trim_db: f64) {
+        self.trim_db = trim_db;
+        self.update_gain();
+    }
+
+    fn set_phase_invert(&mut self, phase_invert: bool) {
+        self.phase_invert = phase_invert;
+        self.update_gain();
+    }
+
+    fn update_gain(&mut self) {
+        let polarity = if self.phase_invert { -1.0 } else { 1.0 };
+        self.gain = polarity * Level::from_db(self.trim_db).as_gain() as f32;
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct InputMapping {
+    channels: [InputChannel; MAXIMUM_NUMBER_OF_CHANNELS],
+    num_channels: usize,
+    stereo_linked: bool,
+}
+
+impl InputMapping {
+    pub fn mono(hardware_channel: usize) -> Self {
+        Self {
+            channels: [InputChannel::new(hardware_channel); MAXIMUM_NUMBER_OF_CHANNELS],
+            num_channels: 1,
+            stereo_linked: false,
+        }
+    }
+
+    pub fn stereo(left_channel: usize, right_channel: usize) -> Self {
+        Self {
+            channels: [
+                InputChannel::new(left_channel),
+                InputChannel::new(right_channel),
+            ],
+            num_channels: 2,
+            stereo_linked: false,
+        }
+    }
+
+    pub fn with_channel(mut self, graph_channel: usize, channel: InputChannel) -> Self {
+        self.set_channel(graph_channel, channel);
+        self
+    }
+
+    pub fn with_stereo_link(mut self, linked: bool) -> Self {
+        self.set_stereo_linked(linked);
+        self
+    }
+
+    pub fn num_channels(&self) -> usize {
+        self.num_channels
+    }
+
+    pub fn get_channel(&self, graph_channel: usize) -> Option<&InputChannel> {
+        self.channels[..self.num_channels].get(graph_channel)
+    }
+
+    pub fn set_channel(&mut self, graph_channel: usize, channel: InputChannel) {
+        if graph_channel >= self.num_channels {
+            return;
+        }
+
+        self.channels[graph_channel] = channel;
+        self.set_trim(graph_channel, channel.trim_db);
+        self.set_phase_invert(graph_channel, channel.phase_invert);
+    }
+
+    pub fn set_trim(&mut self, graph_channel: usize, trim_db: f64) {
+        for channel in self.linked_channels(graph_channel) {
+            self.channels[channel].set_trim(trim_db);
+        }
+    }
+
+    pub fn set_phase_invert(&mut self, graph_channel: usize, phase_invert: bool) {
+        for channel in self.linked_channels(graph_channel) {
+            self.channels[channel].set_phase_invert(phase_invert);
+        }
+    }
+
+    pub fn is_stereo_linked(&self) -> bool {
+        self.stereo_linked
+    }
+
+    pub fn set_stereo_linked(&mut self, linked: bool) {
+        self.stereo_linked = linked && self.num_channels == 2;
+
+        if self.stereo_linked {
+            let left = self.channels[0];
+            self.set_trim(0, left.trim_db);
+            self.set_phase_invert(0, left.phase_invert);
+        }
+    }
+
+    pub fn get_sample(&self, input: &dyn AudioBuffer, sample_location: SampleLocation) -> f32 {
+        let channel = &self.channels[sample_location.channel];
+        if channel.hardware_channel >= input.num_channels() {
+            return 0.0;
+        }
+
+        channel.gain
+            * input.get_sample(SampleLocation::new(
+                channel.hardware_channel,
+                sample_location.frame,
+            ))
+    }
+
+    fn linked_channels(&self, graph_channel: usize) -> std::ops::Range<usize> {
+        if self.stereo_linked {
+            0..2
+        } else if graph_channel < self.num_channels {
+            graph_channel..graph_channel + 1
+        } else {
+            0..0
+        }
+    }
+}
+
+pub struct MappedInput<'a> {
+    input: &'a dyn AudioBuffer,
+    mapping: &'a InputMapping,
+}
+
+impl<'a> MappedInput<'a> {
+    pub fn new(input: &'a dyn AudioBuffer, mapping: &'a InputMapping) -> Self {
+        Self { input, mapping }
+    }
+}
+
+impl<'a> AudioBuffer for MappedInput<'a> {
+    fn num_channels(&self) -> usize {
+        self.mapping.num_channels()
+    }
+
+    fn num_frames(&self) -> usize {
+        self.input.num_frames()
+    }
+
+    fn sample_rate(&self) -> usize {
+        self.input.sample_rate()
+    }
+
+    fn clear(&mut self) {
+        debug_assert!(false)
+    }
+
+    fn set_sample(&mut self, _sample_location: SampleLocation, _value: f32) {
+        debug_assert!(false)
+    }
+
+    fn add_sample(&mut self, _sample_location: SampleLocation, _value: f32) {
+        debug_assert!(false)
+    }
+
+    fn get_sample(&self, sample_location: SampleLocation) -> f32 {
+        self.mapping.get_sample(self.input, sample_location)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::OwnedAudioBuffer;
+
+    fn input() -> OwnedAudioBuffer {
+        let mut buffer = OwnedAudioBuffer::new(4, 4, 44100);
+        for channel in 0..4 {
+            for frame in 0..4 {
+                buffer.set_sample(
+                    SampleLocation::new(channel, frame),
+                    0.1 * (channel + 1) as f32,
+                );
+            }
+        }
+        buffer
+    }
+
+    #[test]
+    fn routes_hardware_channels_with_trim_and_polarity() {
+        let input = input();
+        let mapping = InputMapping::stereo(3, 1)
+            .with_channel(0, InputChannel::new(3).with_trim(6.0))
+            .with_channel(1, InputChannel::new(1).with_phase_invert(true));
+        let mapped = MappedInput::new(&input, &mapping);
+
+        let left = mapped.get_sample(SampleLocation::new(0, 2));
+        let right = mapped.get_sample(SampleLocation::new(1, 2));
+        assert!((left - 0.4 * Level::from_db(6.0).as_gain() as f32).abs() < 1e-6);
+        assert_eq!(right, -0.2);
+    }
+
+    #[test]
+    fn silences_channels_missing_from_the_hardware_input() {
+        let input = input();
+        let mapping = InputMapping::mono(7);
+        let mapped = MappedInput::new(&input, &mapping);
+
+        assert_eq!(mapped.num_channels(), 1);
+        assert_eq!(mapped.get_sample(SampleLocation::new(0, 0)), 0.0);
+    }
+
+    #[test]
+    fn linked_stereo_pair_shares_trim_and_polarity() {
+        let mut mapping = InputMapping::stereo(0, 1)
+            .with_channel(0, InputChannel::new(0).with_trim(-3.0))
+            .with_stereo_link(true);
+        assert_eq!(mapping.get_channel(1).unwrap().get_trim(), -3.0);
+
+        mapping.set_phase_invert(1, true);
+        assert!(mapping.get_channel(0).unwrap().is_phase_inverted());
+        assert_eq!(mapping.get_channel(1).unwrap().get_hardware_channel(), 1);
+
+        mapping.set_stereo_linked(false);
+        mapping.set_trim(0, 0.0);
+        assert_eq!(mapping.get_channel(1).unwrap().get_trim(), -3.0);
+    }
+}
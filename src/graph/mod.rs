@@ -1,5 +1,17 @@
+pub mod analysis;
 pub mod buffer_pool;
+pub mod channel_adaptation;
+pub mod channel_matrix;
 pub mod connection;
+pub mod decimation;
 pub mod dsp;
 pub mod endpoint;
+pub mod input_mapping;
+pub mod monitoring;
 pub mod node;
+pub mod node_handle;
+pub mod oversampling;
+pub mod realtime_budget;
+pub mod render_quality;
+pub mod transport;
+pub mod validation;
@@ -0,0 +1,242 @@
+use crate::{
+    commands::{command::Command, command_queue::CommandQueue, id::Id},
+    dsp::recorder::node::RecorderNode,
+};
+
+use super::{connection::Connection, node::Node, validation::GraphError};
+
+struct ChainNode {
+    id: Id,
+    latency_in_samples: usize,
+}
+
+pub struct MonitoringPath {
+    command_queue: CommandQueue,
+    source_id: Id,
+    monitor_chain: Vec<ChainNode>,
+    record_chain: Vec<ChainNode>,
+}
+
+fn chain_node(node: &dyn Node) -> ChainNode {
+    ChainNode {
+        id: node.get_id(),
+        latency_in_samples: node.latency_in_samples(),
+    }
+}
+
+fn chain_latency(chain: &[ChainNode]) -> usize {
+    chain.iter().map(|node| node.latency_in_samples).sum()
+}
+
+fn chain_ids(source_id: Id, chain: &[ChainNode], destination_id: Id) -> Vec<Id> {
+    let mut ids = Vec::with_capacity(chain.len() + 2);
+    ids.push(source_id);
+    ids.extend(chain.iter().map(|node| node.id));
+    ids.push(destination_id);
+    ids
+}
+
+impl MonitoringPath {
+    pub fn new(source: &dyn Node) -> Self {
+        Self {
+            command_queue: source.get_command_queue(),
+            source_id: source.get_id(),
+            monitor_chain: Vec::new(),
+            record_chain: Vec::new(),
+        }
+    }
+
+    pub fn with_monitor_node(mut self, node: &dyn Node) -> Self {
+        self.monitor_chain.push(chain_node(node));
+        self
+    }
+
+    pub fn with_record_node(mut self, node: &dyn Node) -> Self {
+        self.record_chain.push(chain_node(node));
+        self
+    }
+
+    pub fn monitoring_latency_in_samples(&self) -> usize {
+        chain_latency(&self.monitor_chain)
+    }
+
+    pub fn recording_latency_in_samples(&self) -> usize {
+        chain_latency(&self.record_chain)
+    }
+
+    pub fn connect(
+        &self,
+        monitor_destination_id: Id,
+        recorder: &mut RecorderNode,
+    ) -> Result<(), GraphError> {
+        let monitor_ids = chain_ids(self.source_id, &self.monitor_chain, monitor_destination_id);
+        let record_ids = chain_ids(self.source_id, &self.record_chain, recorder.get_id());
+
+        let commands: Vec<Command> = monitor_ids
+            .windows(2)
+            .chain(record_ids.windows(2))
+            .map(|pair| Command::AddConnection(Connection::new(pair[0], pair[1])))
+            .collect();
+
+        for command in commands.iter() {
+            self.command_queue.validate(command)?;
+        }
+
+        recorder.set_latency_compensation(self.recording_latency_in_samples());
+        self.send_all(commands)
+    }
+
+    pub fn disconnect(
+        &self,
+        monitor_destination_id: Id,
+        recorder: &RecorderNode,
+    ) -> Result<(), GraphError> {
+        let monitor_ids = chain_ids(self.source_id, &self.monitor_chain, monitor_destination_id);
+        let record_ids = chain_ids(self.source_id, &self.record_chain, recorder.get_id());
+
+        let commands = monitor_ids
+            .windows(2)
+            .chain(record_ids.windows(2))
+            .map(|pair| Command::RemoveConnection(Connection::new(pair[0], pair[1])))
+            .collect();
+
+        self.send_all(commands)
+    }
+
+    fn send_all(&self, commands: Vec<Command>) -> Result<(), GraphError> {
+        for command in commands {
+            self.command_queue.send_validated(command)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{
+        graph::dsp::{Dsp, DspParameterMap, DspProcessor},
+        AudioBuffer, AudioBufferSlice, Context, Gain, OwnedAudioBuffer, SampleLocation, Sampler,
+        Timestamp,
+    };
+
+    use super::*;
+
+    const SAMPLE_RATE: usize = 48_000;
+    const LATENCY: usize = 64;
+    const IMPULSE_FRAME: usize = 100;
+
+    struct DelayProcessor {
+        delay_line: Vec<f32>,
+        position: usize,
+    }
+
+    impl DspProcessor for DelayProcessor {
+        fn process_audio(
+            &mut self,
+            input_buffer: &dyn AudioBuffer,
+            output_buffer: &mut dyn AudioBuffer,
+            _start_time: &Timestamp,
+            _parameters: &DspParameterMap,
+        ) {
+            for frame in 0..output_buffer.num_frames() {
+                let location = SampleLocation::new(0, frame);
+                output_buffer.set_sample(location, self.delay_line[self.position]);
+                self.delay_line[self.position] = input_buffer.get_sample(location);
+                self.position = (self.position + 1) % LATENCY;
+            }
+        }
+    }
+
+    struct LookaheadNode {
+        id: Id,
+        command_queue: CommandQueue,
+    }
+
+    impl LookaheadNode {
+        fn new(command_queue: CommandQueue) -> Self {
+            let id = Id::generate();
+            let processor = DelayProcessor {
+                delay_line: vec![0.0; LATENCY],
+                position: 0,
+            };
+            Dsp::add_to_audio_process(
+                Dsp::new(id, Box::new(processor), HashMap::new()),
+                &command_queue,
+            );
+
+            Self { id, command_queue }
+        }
+    }
+
+    impl Node for LookaheadNode {
+        fn get_id(&self) -> Id {
+            self.id
+        }
+
+        fn get_command_queue(&self) -> CommandQueue {
+            self.command_queue.clone()
+        }
+
+        fn latency_in_samples(&self) -> usize {
+            LATENCY
+        }
+    }
+
+    fn impulse(num_frames: usize) -> OwnedAudioBuffer {
+        let mut buffer = OwnedAudioBuffer::new(num_frames, 1, SAMPLE_RATE);
+        buffer.set_sample(SampleLocation::new(0, IMPULSE_FRAME), 1.0);
+        buffer
+    }
+
+    fn impulse_frame(buffer: &OwnedAudioBuffer) -> Option<usize> {
+        (0..buffer.num_frames())
+            .find(|frame| buffer.get_sample(SampleLocation::new(0, *frame)) > 0.5)
+    }
+
+    #[test]
+    fn monitors_without_latency_and_aligns_recording() {
+        let mut context = Context::new(SAMPLE_RATE);
+        let mut audio_process = context.get_audio_process();
+
+        let mut input = Sampler::new(context.get_command_queue(), SAMPLE_RATE, impulse(1024));
+        let monitor_effect = Gain::new(context.get_command_queue());
+        let lookahead = LookaheadNode::new(context.get_command_queue());
+        let master = Gain::new(context.get_command_queue());
+        let mut recorder = RecorderNode::new(context.get_command_queue(), SAMPLE_RATE, 1);
+        master.connect_to_output().unwrap();
+
+        let path = MonitoringPath::new(&input)
+            .with_monitor_node(&monitor_effect)
+            .with_record_node(&lookahead);
+        assert_eq!(path.monitoring_latency_in_samples(), 0);
+        assert_eq!(path.recording_latency_in_samples(), LATENCY);
+
+        path.connect(master.get_id(), &mut recorder).unwrap();
+        assert_eq!(recorder.get_latency_compensation(), LATENCY);
+
+        recorder.arm();
+        recorder.record(
+            Timestamp::zero(),
+            Timestamp::from_samples(512.0, SAMPLE_RATE),
+        );
+        input.start_now();
+        context.start();
+
+        let mut output = OwnedAudioBuffer::new(1024, 2, SAMPLE_RATE);
+        for block in 0..8 {
+            audio_process.process(&mut AudioBufferSlice::new(&mut output, block * 128, 128));
+        }
+
+        assert_eq!(impulse_frame(&output), Some(IMPULSE_FRAME));
+
+        let recordings = recorder.take_recordings();
+        assert_eq!(recordings.len(), 1);
+        assert_eq!(recordings[0].audio.num_frames(), 512);
+        assert_eq!(impulse_frame(&recordings[0].audio), Some(IMPULSE_FRAME));
+
+        path.disconnect(master.get_id(), &recorder).unwrap();
+    }
+}
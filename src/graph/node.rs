@@ -1,37 +1,112 @@
-use crate::commands::{command::Command, id::Id};
-use lockfree::channel::mpsc::Sender;
+use crate::{
+    commands::{command::Command, command_queue::CommandQueue, id::Id, journal::JournalEntry},
+    parameter::audio_parameter::AudioParameter,
+    timestamp::Timestamp,
+};
 
 use super::{
+    channel_matrix::ChannelMatrix,
     connection::Connection,
     endpoint::{Endpoint, EndpointType},
+    validation::GraphError,
 };
 
 pub trait Node {
     fn get_id(&self) -> Id;
 
-    fn get_command_queue(&self) -> Sender<Command>;
+    fn get_command_queue(&self) -> CommandQueue;
+
+    fn latency_in_samples(&self) -> usize {
+        0
+    }
+
+    fn set_tag(&self, tag: &str) {
+        self.get_command_queue().set_tag(self.get_id(), tag);
+    }
+
+    fn clear_tag(&self) {
+        self.get_command_queue().clear_tag(self.get_id());
+    }
+
+    fn get_tag(&self) -> Option<String> {
+        self.get_command_queue().tag(self.get_id())
+    }
+
+    fn set_channel_count(&self, num_channels: usize) {
+        self.get_command_queue()
+            .set_channel_counts(self.get_id(), num_channels, num_channels);
+    }
+
+    fn clear_channel_count(&self) {
+        self.get_command_queue().clear_channel_counts(self.get_id());
+    }
+
+    fn get_channel_count(&self) -> Option<usize> {
+        self.get_command_queue().output_channel_count(self.get_id())
+    }
+
+    fn connect_to_output(&self) -> Result<(), GraphError> {
+        let command_queue = self.get_command_queue();
+        command_queue.try_apply(JournalEntry::ConnectToOutput {
+            previous: command_queue.output_endpoint(),
+            endpoint: Some(Endpoint::new(self.get_id(), EndpointType::Output)),
+        })
+    }
+
+    fn connect_from_input(&self) -> Result<(), GraphError> {
+        self.send_validated(Command::ConnectInput(self.get_id()))
+    }
+
+    fn disconnect_from_input(&self) -> Result<(), GraphError> {
+        self.send_validated(Command::DisconnectInput(self.get_id()))
+    }
+
+    #[deprecated(note = "add the node to a Context and use Context::connect_nodes")]
+    fn connect_to(&self, id: Id) -> Result<(), GraphError> {
+        self.get_command_queue()
+            .connect(Connection::new(self.get_id(), id))
+    }
+
+    fn connect_to_with(
+        &self,
+        id: Id,
+        gain: f32,
+        channel_matrix: Option<ChannelMatrix>,
+    ) -> Result<(), GraphError> {
+        let mut connection = Connection::new(self.get_id(), id).with_gain(gain);
+        connection.channel_matrix = channel_matrix;
+        self.get_command_queue().connect(connection)
+    }
+
+    fn connect_to_parameter(
+        &self,
+        parameter: &AudioParameter,
+        gain: f32,
+    ) -> Result<(), GraphError> {
+        let connection =
+            Connection::to_parameter(self.get_id(), parameter.get_dsp_id(), parameter.get_id());
+        self.get_command_queue().connect(connection.with_gain(gain))
+    }
+
+    fn connect_to_sidechain(&self, id: Id) -> Result<(), GraphError> {
+        self.get_command_queue()
+            .connect(Connection::to_sidechain(self.get_id(), id))
+    }
+
+    #[deprecated(note = "add the node to a Context and use Context::disconnect_nodes")]
+    fn disconnect_from(&self, id: Id) -> Result<(), GraphError> {
+        self.get_command_queue().disconnect(self.get_id(), id)
+    }
 
-    fn connect_to_output(&self) {
-        let _ = self
-            .get_command_queue()
-            .send(Command::ConnectToOutput(Endpoint::new(
-                self.get_id(),
-                EndpointType::Output,
-            )));
+    fn set_priority(&self, priority: i32) -> Result<(), GraphError> {
+        self.send_validated(Command::SetDspPriority(self.get_id(), priority))
     }
 
-    fn connect_to(&self, id: Id) {
-        let _ = self
-            .get_command_queue()
-            .send(Command::AddConnection(Connection::new(self.get_id(), id)));
+    fn stop_at(&self, time: Timestamp, allow_tail: bool) -> Result<(), GraphError> {
+        self.send_validated(Command::StopDsp(self.get_id(), time, allow_tail))
     }
 
-    fn disconnect_from(&self, id: Id) {
-        let _ = self
-            .get_command_queue()
-            .send(Command::RemoveConnection(Connection::new(
-                self.get_id(),
-                id,
-            )));
+    fn send_validated(&self, command: Command) -> Result<(), GraphError> {
+        self.get_command_queue().send_validated(command)
     }
 }
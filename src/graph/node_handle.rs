@@ -0,0 +1,50 @@
+use std::{fmt, marker::PhantomData};
+
+use crate::commands::id::Id;
+
+pub struct NodeHandle<T> {
+    id: Id,
+    context_id: Id,
+    _node: PhantomData<fn() -> T>,
+}
+
+impl<T> NodeHandle<T> {
+    pub(crate) fn new(id: Id, context_id: Id) -> Self {
+        Self {
+            id,
+            context_id,
+            _node: PhantomData,
+        }
+    }
+
+    pub fn get_id(&self) -> Id {
+        self.id
+    }
+
+    pub fn get_context_id(&self) -> Id {
+        self.context_id
+    }
+}
+
+impl<T> Clone for NodeHandle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for NodeHandle<T> {}
+
+impl<T> PartialEq for NodeHandle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id && self.context_id == other.context_id
+    }
+}
+
+impl<T> fmt::Debug for NodeHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NodeHandle")
+            .field("id", &self.id)
+            .field("context_id", &self.context_id)
+            .finish()
+    }
+}
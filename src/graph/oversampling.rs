@@ -0,0 +1,447 @@
+use std::{f64::consts::PI, time::Duration};
+
+use crate::{
+    buffer::{
+        audio_buffer::AudioBuffer, borrowed_audio_buffer::BorrowedAudioBuffer,
+        sample_location::SampleLocation,
+    },
+    midi::midi_file_player::ScheduledMidiMessage,
+    realtime::garbage_collector::GarbageCollectionCommand,
+    realtime::processor::{MAXIMUM_NUMBER_OF_CHANNELS, MAXIMUM_NUMBER_OF_FRAMES},
+    timestamp::Timestamp,
+};
+
+use super::{
+    dsp::{DspParameterMap, DspProcessor},
+    render_quality::RenderQuality,
+    transport::Transport,
+};
+
+const FILTER_LENGTH: usize = 33;
+pub(super) const NUM_TAPS: usize = FILTER_LENGTH + 1;
+const PHASE_LENGTH: usize = NUM_TAPS / 2;
+pub(super) const STAGE_LATENCY: usize = (FILTER_LENGTH - 1) / 2;
+const CUTOFF: f64 = 0.225;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OversamplingFactor {
+    TwoTimes,
+    FourTimes,
+}
+
+impl OversamplingFactor {
+    pub fn multiplier(&self) -> usize {
+        1 << self.num_stages()
+    }
+
+    pub fn latency_in_samples(&self) -> usize {
+        (0..self.num_stages())
+            .map(|stage| STAGE_LATENCY >> stage)
+            .sum()
+    }
+
+    fn num_stages(&self) -> usize {
+        match self {
+            OversamplingFactor::TwoTimes => 1,
+            OversamplingFactor::FourTimes => 2,
+        }
+    }
+}
+
+pub(super) fn half_band_taps() -> [f32; NUM_TAPS] {
+    let mut taps = [0.0; NUM_TAPS];
+    let centre = STAGE_LATENCY as f64;
+
+    for (index, tap) in taps.iter_mut().take(FILTER_LENGTH).enumerate() {
+        let offset = index as f64 - centre;
+        let sinc = if offset == 0.0 {
+            2.0 * CUTOFF
+        } else {
+            (2.0 * PI * CUTOFF * offset).sin() / (PI * offset)
+        };
+        let phase = 2.0 * PI * index as f64 / (FILTER_LENGTH - 1) as f64;
+        let window = 0.42 - 0.5 * phase.cos() + 0.08 * (2.0 * phase).cos();
+        *tap = (sinc * window) as f32;
+    }
+
+    let sum: f32 = taps.iter().sum();
+    for tap in taps.iter_mut() {
+        *tap /= sum;
+    }
+
+    taps
+}
+
+#[derive(Clone, Copy)]
+pub(super) struct StageState {
+    low_rate_history: [f32; PHASE_LENGTH],
+    high_rate_history: [f32; NUM_TAPS],
+}
+
+impl Default for StageState {
+    fn default() -> Self {
+        Self {
+            low_rate_history: [0.0; PHASE_LENGTH],
+            high_rate_history: [0.0; NUM_TAPS],
+        }
+    }
+}
+
+impl StageState {
+    pub(super) fn upsample(&mut self, taps: &[f32; NUM_TAPS], input: f32) -> [f32; 2] {
+        self.low_rate_history.copy_within(..PHASE_LENGTH - 1, 1);
+        self.low_rate_history[0] = input;
+
+        let mut output = [0.0; 2];
+        for (phase, value) in output.iter_mut().enumerate() {
+            *value = 2.0
+                * self
+                    .low_rate_history
+                    .iter()
+                    .enumerate()
+                    .map(|(index, sample)| taps[2 * index + phase] * sample)
+                    .sum::<f32>();
+        }
+        output
+    }
+
+    pub(super) fn downsample(&mut self, taps: &[f32; NUM_TAPS], input: [f32; 2]) -> f32 {
+        self.push_high_rate(input[0]);
+        let output = taps
+            .iter()
+            .zip(self.high_rate_history.iter())
+            .map(|(tap, sample)| tap * sample)
+            .sum();
+        self.push_high_rate(input[1]);
+        output
+    }
+
+    fn push_high_rate(&mut self, sample: f32) {
+        self.high_rate_history.copy_within(..NUM_TAPS - 1, 1);
+        self.high_rate_history[0] = sample;
+    }
+}
+
+pub struct Oversampled<P: DspProcessor> {
+    processor: P,
+    factor: OversamplingFactor,
+    taps: [f32; NUM_TAPS],
+    up_stages: Vec<[StageState; MAXIMUM_NUMBER_OF_CHANNELS]>,
+    down_stages: Vec<[StageState; MAXIMUM_NUMBER_OF_CHANNELS]>,
+    upsampled: Vec<Vec<f32>>,
+    downsampled: Vec<Vec<f32>>,
+}
+
+impl<P: DspProcessor> Oversampled<P> {
+    pub fn new(processor: P, factor: OversamplingFactor) -> Self {
+        let num_levels = factor.num_stages() + 1;
+        let level_capacity =
+            |level: usize| (MAXIMUM_NUMBER_OF_FRAMES << level) * MAXIMUM_NUMBER_OF_CHANNELS;
+
+        Self {
+            processor,
+            factor,
+            taps: half_band_taps(),
+            up_stages: vec![Default::default(); factor.num_stages()],
+            down_stages: vec![Default::default(); factor.num_stages()],
+            upsampled: (0..num_levels)
+                .map(|level| vec![0.0; level_capacity(level)])
+                .collect(),
+            downsampled: (0..num_levels)
+                .map(|level| vec![0.0; level_capacity(level)])
+                .collect(),
+        }
+    }
+
+    pub fn latency_in_samples(&self) -> usize {
+        self.factor.latency_in_samples()
+    }
+
+    pub fn processor(&self) -> &P {
+        &self.processor
+    }
+
+    fn upsample_level(&mut self, level: usize, num_frames: usize, num_channels: usize) {
+        let (lower, higher) = self.upsampled.split_at_mut(level + 1);
+        let input = &lower[level];
+        let output = &mut higher[0];
+        let stages = &mut self.up_stages[level];
+        let num_input_frames = num_frames << level;
+
+        for frame in 0..num_input_frames {
+            for (channel, stage) in stages.iter_mut().enumerate().take(num_channels) {
+                let samples = stage.upsample(&self.taps, input[frame * num_channels + channel]);
+                output[2 * frame * num_channels + channel] = samples[0];
+                output[(2 * frame + 1) * num_channels + channel] = samples[1];
+            }
+        }
+    }
+
+    fn downsample_level(&mut self, level: usize, num_frames: usize, num_channels: usize) {
+        let (lower, higher) = self.downsampled.split_at_mut(level + 1);
+        let input = &higher[0];
+        let output = &mut lower[level];
+        let stages = &mut self.down_stages[level];
+        let num_output_frames = num_frames << level;
+
+        for frame in 0..num_output_frames {
+            for (channel, stage) in stages.iter_mut().enumerate().take(num_channels) {
+                let samples = [
+                    input[2 * frame * num_channels + channel],
+                    input[(2 * frame + 1) * num_channels + channel],
+                ];
+                output[frame * num_channels + channel] = stage.downsample(&self.taps, samples);
+            }
+        }
+    }
+
+    fn process_chunk(
+        &mut self,
+        input_buffer: &dyn AudioBuffer,
+        output_buffer: &mut dyn AudioBuffer,
+        offset: usize,
+        num_frames: usize,
+        start_time: &Timestamp,
+        parameters: &DspParameterMap,
+    ) {
+        let num_channels = output_buffer.num_channels().min(MAXIMUM_NUMBER_OF_CHANNELS);
+        let num_input_channels = input_buffer.num_channels().min(num_channels);
+        let num_stages = self.factor.num_stages();
+        let sample_rate = output_buffer.sample_rate();
+
+        for frame in 0..num_frames {
+            for channel in 0..num_channels {
+                self.upsampled[0][frame * num_channels + channel] = if channel < num_input_channels
+                {
+                    input_buffer.get_sample(SampleLocation::new(channel, offset + frame))
+                } else {
+                    0.0
+                };
+            }
+        }
+
+        for level in 0..num_stages {
+            self.upsample_level(level, num_frames, num_channels);
+        }
+
+        let num_samples = (num_frames << num_stages) * num_channels;
+        let oversampled_rate = sample_rate * self.factor.multiplier();
+        {
+            let input = BorrowedAudioBuffer::new(
+                &mut self.upsampled[num_stages][..num_samples],
+                num_channels,
+                oversampled_rate,
+            );
+            let mut output = BorrowedAudioBuffer::new(
+                &mut self.downsampled[num_stages][..num_samples],
+                num_channels,
+                oversampled_rate,
+            );
+            output.clear();
+            self.processor
+                .process_audio(&input, &mut output, start_time, parameters);
+        }
+
+        for level in (0..num_stages).rev() {
+            self.downsample_level(level, num_frames, num_channels);
+        }
+
+        for frame in 0..num_frames {
+            for channel in 0..num_channels {
+                output_buffer.set_sample(
+                    SampleLocation::new(channel, offset + frame),
+                    self.downsampled[0][frame * num_channels + channel],
+                );
+            }
+        }
+    }
+}
+
+impl<P: DspProcessor> DspProcessor for Oversampled<P> {
+    fn process_audio(
+        &mut self,
+        input_buffer: &dyn AudioBuffer,
+        output_buffer: &mut dyn AudioBuffer,
+        start_time: &Timestamp,
+        parameters: &DspParameterMap,
+    ) {
+        let num_frames = output_buffer.num_frames();
+        let sample_rate = output_buffer.sample_rate();
+        let mut offset = 0;
+
+        while offset < num_frames {
+            let chunk_size = (num_frames - offset).min(MAXIMUM_NUMBER_OF_FRAMES);
+            let chunk_time = start_time.incremented_by_samples(offset, sample_rate);
+            self.process_chunk(
+                input_buffer,
+                output_buffer,
+                offset,
+                chunk_size,
+                &chunk_time,
+                parameters,
+            );
+            offset += chunk_size;
+        }
+    }
+
+    fn drain_midi_output(&mut self, output: &mut dyn FnMut(ScheduledMidiMessage)) {
+        self.processor.drain_midi_output(output);
+    }
+
+    fn drain_garbage(&mut self, dispose: &mut dyn FnMut(GarbageCollectionCommand)) {
+        self.processor.drain_garbage(dispose);
+    }
+
+    fn tail_time(&self) -> Option<Duration> {
+        self.processor.tail_time()
+    }
+
+    fn stop(&mut self, stop_time: &Timestamp, allow_tail: bool) {
+        self.processor.stop(stop_time, allow_tail);
+    }
+
+    fn set_render_quality(&mut self, quality: RenderQuality) {
+        self.processor.set_render_quality(quality);
+    }
+
+    fn set_transport(&mut self, transport: &Transport) {
+        self.processor.set_transport(transport);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::TAU;
+
+    use crate::{utility::fft::Fft, OwnedAudioBuffer};
+
+    use super::*;
+
+    const SAMPLE_RATE: usize = 48_000;
+
+    struct Passthrough {
+        sample_rate: usize,
+    }
+
+    impl DspProcessor for Passthrough {
+        fn process_audio(
+            &mut self,
+            input_buffer: &dyn AudioBuffer,
+            output_buffer: &mut dyn AudioBuffer,
+            _start_time: &Timestamp,
+            _parameters: &DspParameterMap,
+        ) {
+            self.sample_rate = output_buffer.sample_rate();
+            output_buffer.add_from(
+                input_buffer,
+                SampleLocation::new(0, 0),
+                SampleLocation::new(0, 0),
+                output_buffer.num_channels(),
+                output_buffer.num_frames(),
+            );
+        }
+    }
+
+    struct HardClipper;
+
+    impl DspProcessor for HardClipper {
+        fn process_audio(
+            &mut self,
+            input_buffer: &dyn AudioBuffer,
+            output_buffer: &mut dyn AudioBuffer,
+            _start_time: &Timestamp,
+            _parameters: &DspParameterMap,
+        ) {
+            for frame in 0..output_buffer.num_frames() {
+                for channel in 0..output_buffer.num_channels() {
+                    let location = SampleLocation::new(channel, frame);
+                    let sample = 4.0 * input_buffer.get_sample(location);
+                    output_buffer.set_sample(location, sample.clamp(-1.0, 1.0));
+                }
+            }
+        }
+    }
+
+    fn sine(frame: usize, frequency: f64) -> f32 {
+        (TAU * frequency * frame as f64 / SAMPLE_RATE as f64).sin() as f32
+    }
+
+    fn process(processor: &mut dyn DspProcessor, num_frames: usize, frequency: f64) -> Vec<f32> {
+        let mut input_buffer = OwnedAudioBuffer::new(num_frames, 2, SAMPLE_RATE);
+        for frame in 0..num_frames {
+            for channel in 0..2 {
+                input_buffer
+                    .set_sample(SampleLocation::new(channel, frame), sine(frame, frequency));
+            }
+        }
+
+        let mut output_buffer = OwnedAudioBuffer::new(num_frames, 2, SAMPLE_RATE);
+        processor.process_audio(
+            &input_buffer,
+            &mut output_buffer,
+            &Timestamp::zero(),
+            &DspParameterMap::new(),
+        );
+
+        (0..num_frames)
+            .map(|frame| output_buffer.get_sample(SampleLocation::new(1, frame)))
+            .collect()
+    }
+
+    fn energy_outside(samples: &[f32], bins: &[usize]) -> f32 {
+        let size = samples.len();
+        let mut real = samples.to_vec();
+        let mut imaginary = vec![0.0; size];
+        Fft::new(size).forward(&mut real, &mut imaginary);
+
+        (0..size / 2)
+            .filter(|bin| !bins.iter().any(|harmonic| harmonic.abs_diff(*bin) <= 2))
+            .map(|bin| real[bin] * real[bin] + imaginary[bin] * imaginary[bin])
+            .sum::<f32>()
+            / (0..size / 2)
+                .map(|bin| real[bin] * real[bin] + imaginary[bin] * imaginary[bin])
+                .sum::<f32>()
+    }
+
+    #[test]
+    fn passes_signal_with_reported_latency() {
+        for factor in [OversamplingFactor::TwoTimes, OversamplingFactor::FourTimes] {
+            let mut oversampled = Oversampled::new(Passthrough { sample_rate: 0 }, factor);
+            let latency = oversampled.latency_in_samples();
+            let output = process(&mut oversampled, 1200, 1000.0);
+
+            assert_eq!(
+                oversampled.processor().sample_rate,
+                SAMPLE_RATE * factor.multiplier()
+            );
+            for (frame, sample) in output.iter().enumerate().skip(latency + 64) {
+                assert!((sample - sine(frame - latency, 1000.0)).abs() < 0.01);
+            }
+        }
+
+        assert_eq!(OversamplingFactor::TwoTimes.latency_in_samples(), 16);
+        assert_eq!(OversamplingFactor::FourTimes.latency_in_samples(), 24);
+    }
+
+    #[test]
+    fn reduces_aliasing_from_nonlinear_processing() {
+        let size = 4096;
+        let fundamental_bin = 331;
+        let frequency = fundamental_bin as f64 * SAMPLE_RATE as f64 / size as f64;
+        let harmonics: Vec<usize> = (1..size / 2 / fundamental_bin + 1)
+            .map(|harmonic| harmonic * fundamental_bin)
+            .collect();
+
+        let skip = 256;
+        let naive = process(&mut HardClipper, size + skip, frequency);
+        let oversampled = process(
+            &mut Oversampled::new(HardClipper, OversamplingFactor::FourTimes),
+            size + skip,
+            frequency,
+        );
+
+        let naive_aliasing = energy_outside(&naive[skip..], &harmonics);
+        let oversampled_aliasing = energy_outside(&oversampled[skip..], &harmonics);
+        assert!(oversampled_aliasing < 0.1 * naive_aliasing);
+    }
+}
@@ -0,0 +1,22 @@
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Degradation {
+    Draft,
+    Skip,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RealtimeBudget {
+    pub max_load: f64,
+    pub degradation: Degradation,
+    pub recovery_blocks: usize,
+}
+
+impl Default for RealtimeBudget {
+    fn default() -> Self {
+        Self {
+            max_load: 0.8,
+            degradation: Degradation::Draft,
+            recovery_blocks: 32,
+        }
+    }
+}
@@ -0,0 +1,27 @@
+use super::oversampling::OversamplingFactor;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RenderQuality {
+    Draft,
+    #[default]
+    Normal,
+    Best,
+}
+
+impl RenderQuality {
+    pub fn oversampling_factor(&self) -> Option<OversamplingFactor> {
+        match self {
+            RenderQuality::Draft => None,
+            RenderQuality::Normal => Some(OversamplingFactor::TwoTimes),
+            RenderQuality::Best => Some(OversamplingFactor::FourTimes),
+        }
+    }
+
+    pub fn table_length(&self, normal_length: usize) -> usize {
+        match self {
+            RenderQuality::Draft => (normal_length / 4).max(1),
+            RenderQuality::Normal => normal_length,
+            RenderQuality::Best => normal_length * 4,
+        }
+    }
+}
@@ -0,0 +1,72 @@
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transport {
+    pub beats_per_minute: f64,
+    pub beat_position: f64,
+    pub loop_beats: Option<(f64, f64)>,
+}
+
+impl Default for Transport {
+    fn default() -> Self {
+        Self {
+            beats_per_minute: 120.0,
+            beat_position: 0.0,
+            loop_beats: None,
+        }
+    }
+}
+
+impl Transport {
+    pub fn new(beats_per_minute: f64, beat: f64, loop_beats: Option<(f64, f64)>) -> Self {
+        let loop_beats = loop_beats.filter(|(start, end)| end > start);
+
+        Self {
+            beats_per_minute,
+            beat_position: Self::wrap(beat, loop_beats),
+            loop_beats,
+        }
+    }
+
+    pub fn beats_per_frame(&self, sample_rate: usize) -> f64 {
+        self.beats_per_minute / (60.0 * sample_rate as f64)
+    }
+
+    pub fn beat_at_frame(&self, frame: usize, sample_rate: usize) -> f64 {
+        Self::wrap(
+            self.beat_position + frame as f64 * self.beats_per_frame(sample_rate),
+            self.loop_beats,
+        )
+    }
+
+    pub fn seconds_between(&self, from_beat: f64, to_beat: f64) -> f64 {
+        let beats = match self.loop_beats {
+            Some((start, end)) if to_beat < from_beat => (end - from_beat) + (to_beat - start),
+            _ => to_beat - from_beat,
+        };
+
+        beats.max(0.0) * 60.0 / self.beats_per_minute
+    }
+
+    fn wrap(beat: f64, loop_beats: Option<(f64, f64)>) -> f64 {
+        match loop_beats {
+            Some((start, end)) if beat >= end => start + (beat - end) % (end - start),
+            _ => beat,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use super::*;
+
+    #[test]
+    fn wraps_positions_inside_the_loop() {
+        let transport = Transport::new(120.0, 9.0, Some((4.0, 8.0)));
+        assert_relative_eq!(transport.beat_position, 5.0);
+
+        assert_relative_eq!(transport.beat_at_frame(12_000, 48_000), 5.5);
+        assert_relative_eq!(transport.beat_at_frame(72_000, 48_000), 4.0);
+        assert_relative_eq!(transport.seconds_between(7.0, 4.5), 0.75);
+    }
+}
@@ -0,0 +1,714 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    sync::{Arc, Mutex, MutexGuard, Weak},
+};
+
+use crate::{
+    commands::{command::Command, id::Id},
+    timestamp::Timestamp,
+};
+
+use super::{
+    connection::Connection,
+    endpoint::{Endpoint, EndpointType},
+};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum GraphError {
+    UnknownNode(Id),
+    RemovedNode(Id),
+    DuplicateConnection { source: Id, destination: Id },
+    MissingConnection { source: Id, destination: Id },
+    SelfConnection(Id),
+    Cycle { source: Id, destination: Id },
+    OutputAsDestination(Id),
+    InputAsSource(Id),
+    ForeignContext(Id),
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct GraphSnapshot {
+    pub nodes: Vec<Id>,
+    pub connections: Vec<(Id, Id)>,
+    pub output: Option<Id>,
+}
+
+#[derive(Clone)]
+enum GraphChange {
+    AddNode(Id),
+    RemoveNode(Id),
+    Detach(Id),
+    Reattach(Id),
+    Connect(Connection),
+    Disconnect(Connection),
+    ConnectOutput(Endpoint),
+    DisconnectOutput,
+}
+
+fn graph_change(command: &Command) -> Option<(Option<Timestamp>, GraphChange)> {
+    let change = match command {
+        Command::AddDsp(dsp) => GraphChange::AddNode(dsp.get_id()),
+        Command::RemoveDsp(id) => GraphChange::RemoveNode(*id),
+        Command::DetachDsp(id) => GraphChange::Detach(*id),
+        Command::ReattachDsp(id) => GraphChange::Reattach(*id),
+        Command::AddConnection(connection) => GraphChange::Connect(connection.clone()),
+        Command::RemoveConnection(connection) => GraphChange::Disconnect(connection.clone()),
+        Command::ScheduleConnection(connection, time) => {
+            return Some((Some(*time), GraphChange::Connect(connection.clone())))
+        }
+        Command::ScheduleDisconnection(connection, time) => {
+            return Some((Some(*time), GraphChange::Disconnect(connection.clone())))
+        }
+        Command::ConnectToOutput(endpoint) => GraphChange::ConnectOutput(*endpoint),
+        Command::DisconnectFromOutput => GraphChange::DisconnectOutput,
+        Command::At(time, command) => {
+            return graph_change(command).map(|(_, change)| (Some(*time), change))
+        }
+        _ => return None,
+    };
+
+    Some((None, change))
+}
+
+#[derive(Clone, Default)]
+pub struct GraphState {
+    nodes: HashSet<Id>,
+    detached: HashSet<Id>,
+    connections: HashMap<(Id, Id), Connection>,
+    tags: HashMap<Id, String>,
+    channel_counts: HashMap<Id, (usize, usize)>,
+    output: Option<Endpoint>,
+    scheduled: Vec<(Timestamp, GraphChange)>,
+    time: Timestamp,
+}
+
+lazy_static! {
+    static ref GRAPH_STATES: Mutex<Vec<Weak<Mutex<GraphState>>>> = Mutex::new(Vec::new());
+}
+
+pub fn create_graph_state() -> Arc<Mutex<GraphState>> {
+    let state = Arc::new(Mutex::new(GraphState::default()));
+
+    let mut states = lock(&GRAPH_STATES);
+    states.retain(|state| state.strong_count() > 0);
+    states.push(Arc::downgrade(&state));
+
+    state
+}
+
+pub fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+impl GraphState {
+    fn check_node(&self, id: Id) -> Result<(), GraphError> {
+        if !self.nodes.contains(&id) {
+            return Err(GraphError::UnknownNode(id));
+        }
+
+        if self.detached.contains(&id) {
+            return Err(GraphError::RemovedNode(id));
+        }
+
+        Ok(())
+    }
+
+    fn check_endpoints(connection: &Connection) -> Result<(), GraphError> {
+        if connection.source.endpoint_type != EndpointType::Output {
+            return Err(GraphError::InputAsSource(connection.source.dsp_id));
+        }
+
+        if connection.destination.endpoint_type == EndpointType::Output {
+            return Err(GraphError::OutputAsDestination(
+                connection.destination.dsp_id,
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub fn label(&self, id: Id) -> String {
+        match self.tags.get(&id) {
+            Some(tag) => format!("{} ({:?})", tag, id),
+            None => format!("{:?}", id),
+        }
+    }
+
+    fn upstream_connections(&self, root: Id) -> Vec<(Id, Id)> {
+        let mut connections = Vec::new();
+        let mut visited = HashSet::from([root]);
+        let mut pending = vec![root];
+
+        while let Some(destination) = pending.pop() {
+            let mut sources: Vec<Id> = self
+                .connections
+                .keys()
+                .filter(|(_, connected)| *connected == destination)
+                .map(|(source, _)| *source)
+                .collect();
+            sources.sort_by_key(|source| self.label(*source));
+
+            for source in sources {
+                connections.push((source, destination));
+
+                if visited.insert(source) {
+                    pending.push(source);
+                }
+            }
+        }
+
+        connections
+    }
+
+    fn reaches(&self, from: Id, to: Id) -> bool {
+        let mut visited = HashSet::from([from]);
+        let mut pending = vec![from];
+
+        while let Some(source) = pending.pop() {
+            if source == to {
+                return true;
+            }
+
+            for ((_, destination), _) in self
+                .connections
+                .iter()
+                .filter(|((connected, _), connection)| *connected == source && !connection.feedback)
+            {
+                if visited.insert(*destination) {
+                    pending.push(*destination);
+                }
+            }
+        }
+
+        false
+    }
+
+    pub fn validate(&self, command: &Command) -> Result<(), GraphError> {
+        match graph_change(command) {
+            Some((Some(time), _)) if !self.scheduled.is_empty() => {
+                self.projected_to(time).validate_now(command)
+            }
+            _ => self.validate_now(command),
+        }
+    }
+
+    fn projected_to(&self, time: Timestamp) -> GraphState {
+        let mut projected = self.clone();
+        projected.advance_to(time);
+        projected
+    }
+
+    fn validate_now(&self, command: &Command) -> Result<(), GraphError> {
+        match command {
+            Command::AddConnection(connection) | Command::ScheduleConnection(connection, _) => {
+                Self::check_endpoints(connection)?;
+
+                let (source, destination) = key(connection);
+                self.check_node(source)?;
+                self.check_node(destination)?;
+
+                if source == destination {
+                    return Err(GraphError::SelfConnection(source));
+                }
+
+                if self.connections.contains_key(&(source, destination)) {
+                    return Err(GraphError::DuplicateConnection {
+                        source,
+                        destination,
+                    });
+                }
+
+                if !connection.feedback && self.reaches(destination, source) {
+                    return Err(GraphError::Cycle {
+                        source,
+                        destination,
+                    });
+                }
+
+                Ok(())
+            }
+            Command::RemoveConnection(connection)
+            | Command::ScheduleDisconnection(connection, _) => {
+                Self::check_endpoints(connection)?;
+
+                let (source, destination) = key(connection);
+                if !self.connections.contains_key(&(source, destination)) {
+                    return Err(GraphError::MissingConnection {
+                        source,
+                        destination,
+                    });
+                }
+
+                Ok(())
+            }
+            Command::ConnectToOutput(endpoint) => validate_output_endpoint(self, endpoint),
+            Command::DetachDsp(id)
+            | Command::ReattachDsp(id)
+            | Command::RemoveDsp(id)
+            | Command::StopDsp(id, ..)
+            | Command::SetDspPriority(id, _)
+            | Command::ConnectInput(id)
+            | Command::DisconnectInput(id)
+            | Command::ConnectOutputMix(id)
+            | Command::DisconnectOutputMix(id) => {
+                if self.nodes.contains(id) {
+                    Ok(())
+                } else {
+                    Err(GraphError::UnknownNode(*id))
+                }
+            }
+            Command::At(_, command) => self.validate_now(command),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn record(&mut self, command: &Command) {
+        match graph_change(command) {
+            Some((Some(time), change)) if time > self.time => {
+                let index = self
+                    .scheduled
+                    .partition_point(|(scheduled_time, _)| *scheduled_time <= time);
+                self.scheduled.insert(index, (time, change));
+            }
+            Some((_, change)) => self.apply(change),
+            None => (),
+        }
+    }
+
+    pub fn validate_and_record(&mut self, command: &Command) -> Result<(), GraphError> {
+        self.validate(command)?;
+        self.record(command);
+        Ok(())
+    }
+
+    pub fn advance_to(&mut self, time: Timestamp) {
+        self.time = std::cmp::max(self.time, time);
+
+        let num_due = self
+            .scheduled
+            .partition_point(|(scheduled_time, _)| *scheduled_time <= self.time);
+
+        for (_, change) in self.scheduled.drain(..num_due).collect::<Vec<_>>() {
+            self.apply(change);
+        }
+    }
+
+    fn apply(&mut self, change: GraphChange) {
+        match change {
+            GraphChange::AddNode(id) => {
+                self.nodes.insert(id);
+            }
+            GraphChange::RemoveNode(id) => {
+                self.nodes.remove(&id);
+                self.detached.remove(&id);
+                self.tags.remove(&id);
+                self.channel_counts.remove(&id);
+                self.connections
+                    .retain(|(source, destination), _| *source != id && *destination != id);
+                if self.output.is_some_and(|endpoint| endpoint.dsp_id == id) {
+                    self.output = None;
+                }
+            }
+            GraphChange::Detach(id) => {
+                self.detached.insert(id);
+            }
+            GraphChange::Reattach(id) => {
+                self.detached.remove(&id);
+            }
+            GraphChange::Connect(connection) => {
+                self.connections.insert(key(&connection), connection);
+            }
+            GraphChange::Disconnect(connection) => {
+                self.connections.remove(&key(&connection));
+            }
+            GraphChange::ConnectOutput(endpoint) => self.output = Some(endpoint),
+            GraphChange::DisconnectOutput => self.output = None,
+        }
+    }
+
+    pub fn contains(&self, id: Id) -> bool {
+        self.nodes.contains(&id)
+    }
+
+    pub fn output(&self) -> Option<Endpoint> {
+        self.output
+    }
+
+    pub fn is_connected(&self, source: Id, destination: Id) -> bool {
+        self.connections.contains_key(&(source, destination))
+    }
+
+    pub fn connection(&self, source: Id, destination: Id) -> Option<Connection> {
+        self.connections.get(&(source, destination)).cloned()
+    }
+
+    pub fn snapshot_upstream(&self, root: Id) -> GraphSnapshot {
+        let connections: Vec<(Id, Id)> = self
+            .upstream_connections(root)
+            .into_iter()
+            .filter(|key| !self.connections[key].feedback)
+            .collect();
+
+        let mut nodes = vec![root];
+        for (source, _) in connections.iter() {
+            if !nodes.contains(source) {
+                nodes.push(*source);
+            }
+        }
+
+        GraphSnapshot {
+            nodes,
+            connections,
+            output: Some(root),
+        }
+    }
+
+    pub fn set_tag(&mut self, id: Id, tag: &str) {
+        if self.nodes.contains(&id) {
+            self.tags.insert(id, String::from(tag));
+        }
+    }
+
+    pub fn clear_tag(&mut self, id: Id) {
+        self.tags.remove(&id);
+    }
+
+    pub fn tag(&self, id: Id) -> Option<String> {
+        self.tags.get(&id).cloned()
+    }
+
+    pub fn set_channel_counts(
+        &mut self,
+        id: Id,
+        num_input_channels: usize,
+        num_output_channels: usize,
+    ) {
+        if self.nodes.contains(&id) {
+            self.channel_counts
+                .insert(id, (num_input_channels, num_output_channels));
+        }
+    }
+
+    pub fn clear_channel_counts(&mut self, id: Id) {
+        self.channel_counts.remove(&id);
+    }
+
+    pub fn input_channel_count(&self, id: Id) -> Option<usize> {
+        self.channel_counts.get(&id).map(|counts| counts.0)
+    }
+
+    pub fn output_channel_count(&self, id: Id) -> Option<usize> {
+        self.channel_counts.get(&id).map(|counts| counts.1)
+    }
+
+    pub fn describe_upstream(&self, root: Id) -> String {
+        let mut lines = vec![self.label(root)];
+
+        for (source, destination) in self.upstream_connections(root) {
+            lines.push(format!(
+                "{} -> {}",
+                self.label(source),
+                self.label(destination)
+            ));
+        }
+
+        lines.join("\n")
+    }
+}
+
+fn validate_output_endpoint(state: &GraphState, endpoint: &Endpoint) -> Result<(), GraphError> {
+    if endpoint.endpoint_type != EndpointType::Output {
+        return Err(GraphError::InputAsSource(endpoint.dsp_id));
+    }
+
+    state.check_node(endpoint.dsp_id)
+}
+
+fn key(connection: &Connection) -> (Id, Id) {
+    (connection.source.dsp_id, connection.destination.dsp_id)
+}
+
+pub fn label(id: Id) -> String {
+    let states = lock(&GRAPH_STATES);
+
+    states
+        .iter()
+        .filter_map(Weak::upgrade)
+        .find_map(|state| {
+            let state = lock(&state);
+            state.contains(id).then(|| state.label(id))
+        })
+        .unwrap_or_else(|| format!("{:?}", id))
+}
+
+impl fmt::Display for GraphError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GraphError::UnknownNode(id) => write!(formatter, "Unknown node {}", label(*id)),
+            GraphError::RemovedNode(id) => write!(formatter, "Node {} was removed", label(*id)),
+            GraphError::DuplicateConnection {
+                source,
+                destination,
+            } => write!(
+                formatter,
+                "{} is already connected to {}",
+                label(*source),
+                label(*destination)
+            ),
+            GraphError::MissingConnection {
+                source,
+                destination,
+            } => write!(
+                formatter,
+                "{} is not connected to {}",
+                label(*source),
+                label(*destination)
+            ),
+            GraphError::SelfConnection(id) => {
+                write!(formatter, "{} cannot connect to itself", label(*id))
+            }
+            GraphError::Cycle {
+                source,
+                destination,
+            } => write!(
+                formatter,
+                "Connecting {} to {} would create a cycle; use a feedback connection",
+                label(*source),
+                label(*destination)
+            ),
+            GraphError::OutputAsDestination(id) => {
+                write!(formatter, "Output of {} used as a destination", label(*id))
+            }
+            GraphError::InputAsSource(id) => {
+                write!(formatter, "Input of {} used as a source", label(*id))
+            }
+            GraphError::ForeignContext(id) => {
+                write!(formatter, "{} belongs to another context", label(*id))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::graph::dsp::{Dsp, DspParameterMap, DspProcessor};
+    use crate::{AudioBuffer, Timestamp};
+
+    use super::*;
+
+    struct Silence;
+
+    impl DspProcessor for Silence {
+        fn process_audio(
+            &mut self,
+            _input_buffer: &dyn AudioBuffer,
+            _output_buffer: &mut dyn AudioBuffer,
+            _start_time: &Timestamp,
+            _parameters: &DspParameterMap,
+        ) {
+        }
+    }
+
+    fn add_node(state: &mut GraphState) -> Id {
+        let id = Id::generate();
+        state.record(&Command::AddDsp(Box::new(Dsp::new(
+            id,
+            Box::new(Silence),
+            HashMap::new(),
+        ))));
+        id
+    }
+
+    #[test]
+    fn rejects_invalid_connections() {
+        let mut state = GraphState::default();
+        let source = add_node(&mut state);
+        let destination = add_node(&mut state);
+        let unknown = Id::generate();
+
+        let connection = Command::AddConnection(Connection::new(source, destination));
+        assert_eq!(state.validate_and_record(&connection), Ok(()));
+        assert!(state.is_connected(source, destination));
+
+        assert_eq!(
+            state.validate_and_record(&connection),
+            Err(GraphError::DuplicateConnection {
+                source,
+                destination
+            })
+        );
+
+        assert_eq!(
+            state.validate(&Command::AddConnection(Connection::new(source, unknown))),
+            Err(GraphError::UnknownNode(unknown))
+        );
+
+        assert_eq!(
+            state.validate(&Command::AddConnection(Connection::new(source, source))),
+            Err(GraphError::SelfConnection(source))
+        );
+
+        let mut reversed = Connection::new(destination, source);
+        reversed.destination = Endpoint::new(source, EndpointType::Output);
+        assert_eq!(
+            state.validate(&Command::AddConnection(reversed)),
+            Err(GraphError::OutputAsDestination(source))
+        );
+    }
+
+    #[test]
+    fn rejects_cycles_unless_connection_is_feedback() {
+        let mut state = GraphState::default();
+        let first = add_node(&mut state);
+        let second = add_node(&mut state);
+        let third = add_node(&mut state);
+
+        state.record(&Command::AddConnection(Connection::new(first, second)));
+        state.record(&Command::AddConnection(Connection::new(second, third)));
+
+        assert_eq!(
+            state.validate(&Command::AddConnection(Connection::new(third, first))),
+            Err(GraphError::Cycle {
+                source: third,
+                destination: first
+            })
+        );
+
+        let feedback = Command::AddConnection(Connection::feedback(third, first));
+        assert_eq!(state.validate_and_record(&feedback), Ok(()));
+        assert_eq!(
+            state.snapshot_upstream(third).connections,
+            vec![(second, third), (first, second)]
+        );
+    }
+
+    #[test]
+    fn rejects_connections_to_removed_nodes() {
+        let mut state = GraphState::default();
+        let source = add_node(&mut state);
+        let destination = add_node(&mut state);
+
+        state.record(&Command::DetachDsp(destination));
+        assert_eq!(
+            state.validate(&Command::AddConnection(Connection::new(
+                source,
+                destination
+            ))),
+            Err(GraphError::RemovedNode(destination))
+        );
+
+        state.record(&Command::RemoveDsp(destination));
+        assert_eq!(
+            state.validate(&Command::AddConnection(Connection::new(
+                source,
+                destination
+            ))),
+            Err(GraphError::UnknownNode(destination))
+        );
+
+        assert_eq!(
+            state.validate(&Command::RemoveConnection(Connection::new(
+                source,
+                destination
+            ))),
+            Err(GraphError::MissingConnection {
+                source,
+                destination
+            })
+        );
+    }
+
+    #[test]
+    fn labels_nodes_with_tags() {
+        let shared_state = create_graph_state();
+        let mut state = lock(&shared_state);
+        let synth = add_node(&mut state);
+        let reverb = add_node(&mut state);
+        let master = add_node(&mut state);
+        state.set_tag(synth, "Synth");
+        state.set_tag(master, "Master");
+
+        for (source, destination) in [(synth, master), (reverb, master), (synth, reverb)] {
+            state.record(&Command::AddConnection(Connection::new(
+                source,
+                destination,
+            )));
+        }
+
+        let description = state.describe_upstream(master);
+        let lines: Vec<&str> = description.lines().collect();
+        assert_eq!(lines[0], format!("Master ({:?})", master));
+        assert!(lines.contains(&format!("Synth ({:?}) -> Master ({:?})", synth, master).as_str()));
+        assert!(lines.contains(&format!("{:?} -> Master ({:?})", reverb, master).as_str()));
+        assert!(lines.contains(&format!("Synth ({:?}) -> {:?}", synth, reverb).as_str()));
+
+        drop(state);
+        assert_eq!(
+            GraphError::SelfConnection(synth).to_string(),
+            format!("Synth ({:?}) cannot connect to itself", synth)
+        );
+
+        let mut state = lock(&shared_state);
+        state.record(&Command::RemoveDsp(synth));
+        assert_eq!(state.tag(synth), None);
+        state.set_tag(synth, "Removed");
+        assert_eq!(state.tag(synth), None);
+    }
+
+    #[test]
+    fn applies_scheduled_changes_when_time_is_reached() {
+        let mut state = GraphState::default();
+        let source = add_node(&mut state);
+        let destination = add_node(&mut state);
+        let connection = Connection::new(source, destination);
+
+        let connect_time = Timestamp::from_seconds(1.0);
+        let disconnect_time = Timestamp::from_seconds(2.0);
+        state.record(&Command::ScheduleConnection(
+            connection.clone(),
+            connect_time,
+        ));
+        assert!(!state.is_connected(source, destination));
+
+        let disconnect = Command::ScheduleDisconnection(connection.clone(), disconnect_time);
+        assert_eq!(state.validate_and_record(&disconnect), Ok(()));
+
+        state.advance_to(connect_time);
+        assert!(state.is_connected(source, destination));
+
+        state.record(&Command::At(
+            Timestamp::from_seconds(3.0),
+            Box::new(Command::DetachDsp(source)),
+        ));
+        state.advance_to(disconnect_time);
+        assert!(!state.is_connected(source, destination));
+        assert_eq!(state.validate(&Command::DetachDsp(source)), Ok(()));
+
+        state.advance_to(Timestamp::from_seconds(3.0));
+        assert_eq!(
+            state.validate(&Command::AddConnection(connection)),
+            Err(GraphError::RemovedNode(source))
+        );
+    }
+
+    #[test]
+    fn keeps_separate_graph_states_apart() {
+        let mut first = GraphState::default();
+        let mut second = GraphState::default();
+        let source = add_node(&mut first);
+        let destination = add_node(&mut second);
+
+        assert_eq!(
+            first.validate(&Command::AddConnection(Connection::new(
+                source,
+                destination
+            ))),
+            Err(GraphError::UnknownNode(destination))
+        );
+    }
+}
@@ -0,0 +1,82 @@
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TimeSignature {
+    pub numerator: u32,
+    pub denominator: u32,
+}
+
+impl Default for TimeSignature {
+    fn default() -> Self {
+        Self::new(4, 4)
+    }
+}
+
+impl TimeSignature {
+    pub fn new(numerator: u32, denominator: u32) -> Self {
+        assert!(numerator > 0 && denominator > 0);
+        Self {
+            numerator,
+            denominator,
+        }
+    }
+
+    pub fn beats_per_bar(&self) -> f64 {
+        self.numerator as f64 * 4.0 / self.denominator as f64
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HostTransport {
+    pub playing: bool,
+    pub beats_per_minute: f64,
+    pub time_signature: TimeSignature,
+    pub beat_position: f64,
+    pub loop_beats: Option<(f64, f64)>,
+}
+
+impl Default for HostTransport {
+    fn default() -> Self {
+        Self {
+            playing: false,
+            beats_per_minute: 120.0,
+            time_signature: TimeSignature::default(),
+            beat_position: 0.0,
+            loop_beats: None,
+        }
+    }
+}
+
+impl HostTransport {
+    pub fn sample_position(&self, sample_rate: usize) -> usize {
+        let seconds = self.beat_position.max(0.0) * 60.0 / self.beats_per_minute;
+        (seconds * sample_rate as f64).round() as usize
+    }
+
+    pub fn bar_position(&self) -> f64 {
+        self.beat_position / self.time_signature.beats_per_bar()
+    }
+
+    pub fn is_looping(&self) -> bool {
+        self.loop_beats.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use super::*;
+
+    #[test]
+    fn maps_musical_position_to_samples() {
+        let transport = HostTransport {
+            beats_per_minute: 90.0,
+            time_signature: TimeSignature::new(6, 8),
+            beat_position: 6.0,
+            ..Default::default()
+        };
+
+        assert_eq!(transport.sample_position(48_000), 192_000);
+        assert_relative_eq!(transport.time_signature.beats_per_bar(), 3.0);
+        assert_relative_eq!(transport.bar_position(), 2.0);
+    }
+}
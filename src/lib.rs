@@ -1,34 +1,207 @@
 mod audio_process;
+#[cfg(feature = "cpal")]
+pub mod backends;
+mod batch;
 mod buffer;
 mod commands;
 mod context;
 mod dsp;
+mod engine;
+mod events;
 mod graph;
+mod host_transport;
+mod midi;
 mod parameter;
+mod preview_player;
+mod project;
 mod realtime;
+mod tempo_map;
+mod timecode;
 mod timestamp;
 mod utility;
 
 pub type Level = utility::level::Level;
 pub type Context = context::Context;
+pub type ContextOptions = context::ContextOptions;
+pub type CommandQueue = commands::command_queue::CommandQueue;
+pub type Engine = engine::Engine;
 pub type Timestamp = timestamp::Timestamp;
+pub type Timecode = timecode::Timecode;
+pub type FrameRate = timecode::FrameRate;
+pub type TempoMap = tempo_map::TempoMap;
+pub type HostTransport = host_transport::HostTransport;
+pub type TimeSignature = host_transport::TimeSignature;
+pub type NoteDivision = parameter::tempo_sync::NoteDivision;
+pub type ParameterUnit = parameter::tempo_sync::ParameterUnit;
+pub type NodeProfile = realtime::profiler::NodeProfile;
+pub type NodeHandle<T> = graph::node_handle::NodeHandle<T>;
+pub type GraphError = graph::validation::GraphError;
+pub type Connection = graph::connection::Connection;
+pub type ChannelMatrix = graph::channel_matrix::ChannelMatrix;
+pub type ChannelAdaptation = graph::channel_adaptation::ChannelAdaptation;
+pub type DownmixLaw = graph::channel_adaptation::DownmixLaw;
+pub type UpmixLaw = graph::channel_adaptation::UpmixLaw;
+pub type InputMapping = graph::input_mapping::InputMapping;
+pub type InputChannel = graph::input_mapping::InputChannel;
+pub type MonitoringPath = graph::monitoring::MonitoringPath;
+pub type GraphAnalyzer = graph::analysis::GraphAnalyzer;
+pub type GraphAnalysis = graph::analysis::GraphAnalysis;
+pub type AnalysisWarning = graph::analysis::AnalysisWarning;
+pub type NodeEstimate = graph::analysis::NodeEstimate;
+pub type RenderQuality = graph::render_quality::RenderQuality;
+pub type RealtimeBudget = graph::realtime_budget::RealtimeBudget;
+pub type Degradation = graph::realtime_budget::Degradation;
+pub type Oversampled<P> = graph::oversampling::Oversampled<P>;
+pub type OversamplingFactor = graph::oversampling::OversamplingFactor;
+pub type Decimated<P> = graph::decimation::Decimated<P>;
+pub type AudioFileError = utility::audio_file::AudioFileError;
+pub type ExportFormat = utility::audio_file::ExportFormat;
+pub type BatchProgress = batch::BatchProgress;
+pub type BatchError = batch::BatchError;
+pub type LogRecord = utility::realtime_log::LogRecord;
+pub type LogLevel = utility::realtime_log::LogLevel;
+pub type GainReduction = utility::gain_reduction::GainReduction;
+pub type LoudnessMeter = utility::loudness::LoudnessMeter;
+pub type LoudnessTarget = utility::loudness::LoudnessTarget;
 
+pub type AmbiencePlayer = dsp::ambience::node::AmbiencePlayerNode;
+pub type AmpSim = dsp::amp_sim::node::AmpSimNode;
+pub type ChannelAdapter = dsp::channel_adapter::node::ChannelAdapterNode;
+pub type ClipPlayer = dsp::clip_player::node::ClipPlayerNode;
+pub type Compressor = dsp::compressor::node::CompressorNode;
+pub type LaunchQuantization = dsp::clip_player::processor::LaunchQuantization;
+pub type ConstantSource = dsp::constant::node::ConstantSourceNode;
+pub type Convolver = dsp::convolver::node::ConvolverNode;
+pub type DeEsser = dsp::de_esser::node::DeEsserNode;
+pub type Delay = dsp::delay::node::DelayNode;
+pub type DelayMode = dsp::delay::processor::DelayMode;
+pub type DelayTap = dsp::delay::processor::DelayTap;
+pub type Denoise = dsp::denoise::node::DenoiseNode;
+pub type DynamicEq = dsp::dynamic_eq::node::DynamicEqNode;
+pub type DynamicEqBand = dsp::dynamic_eq::node::DynamicEqBand;
+pub type DynamicEqBandType = dsp::dynamic_eq::processor::DynamicEqBandType;
+pub type Emitter = dsp::emitter::node::EmitterNode;
+pub type Envelope = dsp::envelope::node::EnvelopeNode;
+pub type EmitterAttributes = dsp::emitter::attributes::EmitterAttributes;
+pub type Listener = dsp::emitter::attributes::Listener;
+pub type DistanceModel = dsp::emitter::attributes::DistanceModel;
 pub type Gain = dsp::gain::node::GainNode;
+pub type Leveler = dsp::leveler::node::LevelerNode;
+pub type Comparator = dsp::logic::node::ComparatorNode;
+pub type Comparison = dsp::logic::processor::Comparison;
+pub type LogicNode = dsp::logic::node::LogicNode;
+pub type LogicOperation = dsp::logic::processor::LogicOperation;
+pub type Trigger = dsp::logic::node::TriggerNode;
+pub type LtcGenerator = dsp::ltc::node::LtcGeneratorNode;
+pub type LtcReader = dsp::ltc::node::LtcReaderNode;
+pub type LtcReading = dsp::ltc::processor::LtcReading;
+pub type MathNode = dsp::math::node::MathNode;
+pub type MathOperation = dsp::math::processor::MathOperation;
+pub type MonitorController = dsp::monitor::node::MonitorControllerNode;
+pub type MonitorCommand = dsp::monitor::processor::MonitorCommand;
+pub type MonitorOutput = dsp::monitor::processor::MonitorOutput;
+pub type MonitorState = dsp::monitor::processor::MonitorState;
+pub type MusicPlayer = dsp::music::node::MusicPlayerNode;
+pub type MusicTrack = dsp::music::track::MusicTrack;
+pub type TransitionPoint = dsp::music::track::TransitionPoint;
+pub type TransitionType = dsp::music::track::TransitionType;
 pub type Oscillator = dsp::oscillator::node::OscillatorNode;
+pub type Waveform = dsp::oscillator::waveform::Waveform;
+pub type Wavetable = dsp::oscillator::wavetable::Wavetable;
+pub type SharedWavetable = dsp::oscillator::wavetable::SharedWavetable;
+pub type WavetableRegistry = dsp::oscillator::wavetable::WavetableRegistry;
+pub type RandomSource = dsp::random_source::node::RandomSourceNode;
+pub type RandomDistribution = dsp::random_source::processor::RandomDistribution;
+pub type RandomInterpolation = dsp::random_source::processor::RandomInterpolation;
+pub type Recorder = dsp::recorder::node::RecorderNode;
+pub type Recording = dsp::recorder::node::Recording;
 pub type Sampler = dsp::sampler::node::SamplerNode;
+pub type Playlist = dsp::sampler::playlist_node::PlaylistNode;
+pub type PlaylistItem = dsp::sampler::playlist::PlaylistItem;
+pub type OneShotOptions = dsp::sampler::one_shot::OneShotOptions;
+pub type Subgraph = dsp::subgraph::node::SubgraphNode;
+pub type SubgraphRate = dsp::subgraph::processor::SubgraphRate;
 
 pub type AudioBufferSlice<'a> = buffer::audio_buffer_slice::AudioBufferSlice<'a>;
 pub type OwnedAudioBuffer = buffer::owned_audio_buffer::OwnedAudioBuffer;
+pub type MappedAudioBuffer = buffer::mapped_audio_buffer::MappedAudioBuffer;
 pub type BorrowedAudioBuffer<'a> = buffer::borrowed_audio_buffer::BorrowedAudioBuffer<'a>;
-pub type ImmutableAudioBufferSlice<'a> = buffer::immutable_audio_buffer_slice::ImmutableAudioBufferSlice<'a>;
+pub type ImmutableAudioBufferSlice<'a> =
+    buffer::immutable_audio_buffer_slice::ImmutableAudioBufferSlice<'a>;
 
 pub type SampleLocation = buffer::sample_location::SampleLocation;
+pub type SampleStore = buffer::sample_store::SampleStore;
+pub type SoundBank = utility::sound_bank::SoundBank;
+pub type BankPreset = utility::sound_bank::BankPreset;
+pub type SampleHandle = utility::sample_loader::SampleHandle;
+pub type SampleLoadStatus = utility::sample_loader::SampleLoadStatus;
+
+pub type NoteId = events::note_event::NoteId;
+pub type NoteEvent = events::note_event::NoteEvent;
+pub type NoteEventType = events::note_event::NoteEventType;
+pub type NoteExpression = events::note_event::NoteExpression;
+pub type NoteEventTransmitter = events::note_event::NoteEventTransmitter;
+pub type Quantize = events::transform::Quantize;
+pub type Humanize = events::transform::Humanize;
+pub type LoopScheduler = events::loop_scheduler::LoopScheduler;
+pub type NoteEventTransformChain = events::transform::NoteEventTransformChain;
+
+pub type MidiMessage = midi::message::MidiMessage;
+pub type MpeDecoder = midi::mpe::MpeDecoder;
+pub type MidiFile = midi::midi_file::MidiFile;
+pub type MidiFileError = midi::midi_file::MidiFileError;
+pub type MidiFilePlayer = midi::midi_file_player::MidiFilePlayer;
+pub type MidiFilePlayerNode = dsp::midi_file_player::node::MidiFilePlayerNode;
+pub type MidiOutputQueue = midi::output::MidiOutputQueue;
+pub type ScheduledMidiMessage = midi::midi_file_player::ScheduledMidiMessage;
+pub type MidiSource = midi::mapping::MidiSource;
+pub type MidiMapping = midi::mapping::MidiMapping;
+pub type MidiMapper = midi::mapping::MidiMapper;
+pub type MappingCurve = midi::mapping::MappingCurve;
 
 pub type AudioParameter = parameter::audio_parameter::AudioParameter;
+pub type ParameterSnapshot = parameter::snapshot::ParameterSnapshot;
+pub type SnapshotBank = parameter::snapshot::SnapshotBank;
+pub type MixState = parameter::mix_state::MixState;
+pub type ModulationMatrix = parameter::modulation::ModulationMatrix;
+pub type ModulationSource = parameter::modulation::ModulationSource;
+pub type ModulationCurve = parameter::modulation::ModulationCurve;
+pub type LfoShape = parameter::modulation::LfoShape;
+pub type NodePreset = parameter::preset::NodePreset;
+pub type PresetValue = parameter::preset::PresetValue;
+pub type PresetError = parameter::preset::PresetError;
+#[cfg(feature = "presets")]
+pub type PresetFormat = parameter::preset::PresetFormat;
+pub type AutomationLane = parameter::automation::AutomationLane;
+pub type AutomationPoint = parameter::automation::AutomationPoint;
+pub type AutomationCurve = parameter::automation::AutomationCurve;
+pub type AutomationRecorder = parameter::automation_recorder::AutomationRecorder;
+pub type ParameterGesture = parameter::gesture::ParameterGesture;
+pub type GesturePhase = parameter::gesture::GesturePhase;
+
+pub type Project = project::model::Project;
+pub type ProjectTrack = project::model::ProjectTrack;
+pub type ProjectClip = project::model::ProjectClip;
+pub type TrackAutomation = project::model::TrackAutomation;
+pub type AutomationTarget = project::model::AutomationTarget;
+pub type ProjectError = project::model::ProjectError;
+pub type RenderTail = project::renderer::RenderTail;
 
 pub use audio_process::AudioProcess;
+pub use batch::{process_files, process_files_normalized};
 pub use buffer::audio_buffer::AudioBuffer;
+pub use events::transform::NoteEventTransform;
 pub use graph::node::Node;
+pub use midi::output::MidiOutputPort;
+pub use parameter::preset::PresetNode;
+pub use project::renderer::{
+    export_project_stems, render_project, render_project_normalized, render_project_with_tail,
+};
+#[cfg(feature = "fuzzing")]
+pub use realtime::fuzzing;
+pub use utility::audio_file::write_audio_file;
+pub use utility::fast_math;
+pub use utility::loudness::{integrated_lufs, normalize_loudness, true_peak_db};
 
 #[macro_use]
 extern crate lazy_static;
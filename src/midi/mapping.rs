@@ -0,0 +1,278 @@
+use std::collections::HashMap;
+
+use crate::{
+    commands::{
+        command::{Command, ParameterChangeRequest},
+        command_queue::CommandQueue,
+        id::Id,
+    },
+    parameter::{audio_parameter::AudioParameter, ParameterChange},
+    timestamp::Timestamp,
+};
+
+use super::message::{MidiMessage, PITCH_BEND_MAXIMUM};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MidiSource {
+    ControlChange { channel: u8, controller: u8 },
+    PitchBend { channel: u8 },
+    ChannelPressure { channel: u8 },
+    PolyphonicAftertouch { channel: u8 },
+}
+
+impl MidiSource {
+    pub fn from_message(message: &MidiMessage) -> Option<(Self, f64)> {
+        match *message {
+            MidiMessage::ControlChange {
+                channel,
+                controller,
+                value,
+            } => Some((
+                MidiSource::ControlChange {
+                    channel,
+                    controller,
+                },
+                value as f64 / 127.0,
+            )),
+            MidiMessage::PitchBend { channel, value } => Some((
+                MidiSource::PitchBend { channel },
+                value as f64 / PITCH_BEND_MAXIMUM as f64,
+            )),
+            MidiMessage::ChannelPressure { channel, pressure } => Some((
+                MidiSource::ChannelPressure { channel },
+                pressure as f64 / 127.0,
+            )),
+            MidiMessage::PolyphonicAftertouch {
+                channel, pressure, ..
+            } => Some((
+                MidiSource::PolyphonicAftertouch { channel },
+                pressure as f64 / 127.0,
+            )),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MappingCurve {
+    Linear,
+    Power(f64),
+    Exponential,
+}
+
+impl MappingCurve {
+    fn apply(&self, normalized: f64, minimum: f64, maximum: f64) -> f64 {
+        match *self {
+            MappingCurve::Linear => minimum + normalized * (maximum - minimum),
+            MappingCurve::Power(exponent) => {
+                minimum + normalized.powf(exponent) * (maximum - minimum)
+            }
+            MappingCurve::Exponential => {
+                if minimum > 0.0 && maximum > 0.0 {
+                    minimum * (maximum / minimum).powf(normalized)
+                } else {
+                    MappingCurve::Linear.apply(normalized, minimum, maximum)
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MidiMapping {
+    dsp_id: Id,
+    parameter_id: Id,
+    parameter_minimum: f64,
+    parameter_maximum: f64,
+    minimum_value: f64,
+    maximum_value: f64,
+    curve: MappingCurve,
+}
+
+impl MidiMapping {
+    pub fn new(parameter: &AudioParameter) -> Self {
+        Self {
+            dsp_id: parameter.get_dsp_id(),
+            parameter_id: parameter.get_id(),
+            parameter_minimum: parameter.get_minimum_value(),
+            parameter_maximum: parameter.get_maximum_value(),
+            minimum_value: parameter.get_minimum_value(),
+            maximum_value: parameter.get_maximum_value(),
+            curve: MappingCurve::Linear,
+        }
+    }
+
+    pub fn with_range(mut self, minimum_value: f64, maximum_value: f64) -> Self {
+        self.minimum_value = minimum_value;
+        self.maximum_value = maximum_value;
+        self
+    }
+
+    pub fn with_curve(mut self, curve: MappingCurve) -> Self {
+        self.curve = curve;
+        self
+    }
+
+    pub fn map(&self, normalized: f64) -> f64 {
+        self.curve
+            .apply(
+                normalized.clamp(0.0, 1.0),
+                self.minimum_value,
+                self.maximum_value,
+            )
+            .clamp(self.parameter_minimum, self.parameter_maximum)
+    }
+}
+
+pub struct MidiMapper {
+    command_queue: CommandQueue,
+    mappings: HashMap<MidiSource, Vec<MidiMapping>>,
+    learn_target: Option<MidiMapping>,
+}
+
+impl MidiMapper {
+    pub fn new(command_queue: CommandQueue) -> Self {
+        Self {
+            command_queue,
+            mappings: HashMap::new(),
+            learn_target: None,
+        }
+    }
+
+    pub fn map(&mut self, source: MidiSource, mapping: MidiMapping) {
+        self.mappings.entry(source).or_default().push(mapping);
+    }
+
+    pub fn unmap(&mut self, source: MidiSource) {
+        self.mappings.remove(&source);
+    }
+
+    pub fn unmap_parameter(&mut self, parameter_id: Id) {
+        self.mappings
+            .values_mut()
+            .for_each(|mappings| mappings.retain(|mapping| mapping.parameter_id != parameter_id));
+
+        self.mappings.retain(|_, mappings| !mappings.is_empty());
+    }
+
+    pub fn learn(&mut self, mapping: MidiMapping) {
+        self.learn_target = Some(mapping);
+    }
+
+    pub fn cancel_learn(&mut self) {
+        self.learn_target = None;
+    }
+
+    pub fn is_learning(&self) -> bool {
+        self.learn_target.is_some()
+    }
+
+    pub fn handle_message(&mut self, message: &MidiMessage, time: Timestamp) -> bool {
+        let (source, normalized) = match MidiSource::from_message(message) {
+            Some(source) => source,
+            None => return false,
+        };
+
+        if let Some(learn_target) = self.learn_target.take() {
+            self.map(source, learn_target);
+        }
+
+        let mappings = match self.mappings.get(&source) {
+            Some(mappings) => mappings,
+            None => return false,
+        };
+
+        for mapping in mappings.iter() {
+            let _ =
+                self.command_queue
+                    .send(Command::ParameterValueChange(ParameterChangeRequest {
+                        dsp_id: mapping.dsp_id,
+                        parameter_id: mapping.parameter_id,
+                        change: ParameterChange::immediate(mapping.map(normalized), time),
+                    }));
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands::command_queue::CommandQueue;
+    use approx::assert_relative_eq;
+
+    use super::*;
+
+    fn make_parameter(command_queue: CommandQueue) -> AudioParameter {
+        let (parameter, _) =
+            AudioParameter::new(Id::generate(), 20.0, 20.0, 20000.0, command_queue);
+        parameter
+    }
+
+    #[test]
+    fn maps_linear_range() {
+        let (command_tx, _) = CommandQueue::create();
+        let parameter = make_parameter(command_tx);
+        let mapping = MidiMapping::new(&parameter).with_range(100.0, 200.0);
+        assert_relative_eq!(mapping.map(0.0), 100.0);
+        assert_relative_eq!(mapping.map(0.5), 150.0);
+        assert_relative_eq!(mapping.map(1.0), 200.0);
+    }
+
+    #[test]
+    fn maps_exponential_range() {
+        let (command_tx, _) = CommandQueue::create();
+        let parameter = make_parameter(command_tx);
+        let mapping = MidiMapping::new(&parameter)
+            .with_range(100.0, 10000.0)
+            .with_curve(MappingCurve::Exponential);
+        assert_relative_eq!(mapping.map(0.5), 1000.0, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn clamps_to_parameter_range() {
+        let (command_tx, _) = CommandQueue::create();
+        let parameter = make_parameter(command_tx);
+        let mapping = MidiMapping::new(&parameter).with_range(0.0, 100000.0);
+        assert_relative_eq!(mapping.map(0.0), 20.0);
+        assert_relative_eq!(mapping.map(1.0), 20000.0);
+    }
+
+    #[test]
+    fn learns_from_next_message() {
+        let (command_tx, mut command_rx) = CommandQueue::create();
+        let parameter = make_parameter(command_tx.clone());
+        let mut mapper = MidiMapper::new(command_tx);
+
+        mapper.learn(MidiMapping::new(&parameter));
+        assert!(mapper.is_learning());
+
+        let message = MidiMessage::ControlChange {
+            channel: 0,
+            controller: 74,
+            value: 127,
+        };
+
+        assert!(mapper.handle_message(&message, Timestamp::from_seconds(1.0)));
+        assert!(!mapper.is_learning());
+
+        match command_rx.recv() {
+            Ok(Command::ParameterValueChange(request)) => {
+                assert_eq!(request.parameter_id, parameter.get_id());
+                assert!(
+                    request.change
+                        == ParameterChange::immediate(20000.0, Timestamp::from_seconds(1.0))
+                );
+            }
+            _ => panic!("Expected a parameter change"),
+        }
+
+        let unmapped = MidiMessage::ControlChange {
+            channel: 0,
+            controller: 1,
+            value: 127,
+        };
+
+        assert!(!mapper.handle_message(&unmapped, Timestamp::zero()));
+    }
+}
@@ -0,0 +1,199 @@
+pub const PITCH_BEND_MAXIMUM: u16 = 16383;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MidiMessage {
+    NoteOff {
+        channel: u8,
+        note: u8,
+        velocity: u8,
+    },
+    NoteOn {
+        channel: u8,
+        note: u8,
+        velocity: u8,
+    },
+    PolyphonicAftertouch {
+        channel: u8,
+        note: u8,
+        pressure: u8,
+    },
+    ControlChange {
+        channel: u8,
+        controller: u8,
+        value: u8,
+    },
+    ProgramChange {
+        channel: u8,
+        program: u8,
+    },
+    ChannelPressure {
+        channel: u8,
+        pressure: u8,
+    },
+    PitchBend {
+        channel: u8,
+        value: u16,
+    },
+}
+
+impl MidiMessage {
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let status = *bytes.first()?;
+        let channel = status & 0x0F;
+        let data = |index: usize| bytes.get(index).map(|value| value & 0x7F);
+
+        match status & 0xF0 {
+            0x80 => Some(MidiMessage::NoteOff {
+                channel,
+                note: data(1)?,
+                velocity: data(2)?,
+            }),
+            0x90 => {
+                let note = data(1)?;
+                let velocity = data(2)?;
+                if velocity == 0 {
+                    Some(MidiMessage::NoteOff {
+                        channel,
+                        note,
+                        velocity,
+                    })
+                } else {
+                    Some(MidiMessage::NoteOn {
+                        channel,
+                        note,
+                        velocity,
+                    })
+                }
+            }
+            0xA0 => Some(MidiMessage::PolyphonicAftertouch {
+                channel,
+                note: data(1)?,
+                pressure: data(2)?,
+            }),
+            0xB0 => Some(MidiMessage::ControlChange {
+                channel,
+                controller: data(1)?,
+                value: data(2)?,
+            }),
+            0xC0 => Some(MidiMessage::ProgramChange {
+                channel,
+                program: data(1)?,
+            }),
+            0xD0 => Some(MidiMessage::ChannelPressure {
+                channel,
+                pressure: data(1)?,
+            }),
+            0xE0 => Some(MidiMessage::PitchBend {
+                channel,
+                value: (data(2)? as u16) << 7 | data(1)? as u16,
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn to_bytes(self) -> ([u8; 3], usize) {
+        match self {
+            MidiMessage::NoteOff {
+                channel,
+                note,
+                velocity,
+            } => ([0x80 | channel, note, velocity], 3),
+            MidiMessage::NoteOn {
+                channel,
+                note,
+                velocity,
+            } => ([0x90 | channel, note, velocity], 3),
+            MidiMessage::PolyphonicAftertouch {
+                channel,
+                note,
+                pressure,
+            } => ([0xA0 | channel, note, pressure], 3),
+            MidiMessage::ControlChange {
+                channel,
+                controller,
+                value,
+            } => ([0xB0 | channel, controller, value], 3),
+            MidiMessage::ProgramChange { channel, program } => ([0xC0 | channel, program, 0], 2),
+            MidiMessage::ChannelPressure { channel, pressure } => {
+                ([0xD0 | channel, pressure, 0], 2)
+            }
+            MidiMessage::PitchBend { channel, value } => (
+                [
+                    0xE0 | channel,
+                    (value & 0x7F) as u8,
+                    (value >> 7 & 0x7F) as u8,
+                ],
+                3,
+            ),
+        }
+    }
+
+    pub fn channel(&self) -> u8 {
+        match *self {
+            MidiMessage::NoteOff { channel, .. }
+            | MidiMessage::NoteOn { channel, .. }
+            | MidiMessage::PolyphonicAftertouch { channel, .. }
+            | MidiMessage::ControlChange { channel, .. }
+            | MidiMessage::ProgramChange { channel, .. }
+            | MidiMessage::ChannelPressure { channel, .. }
+            | MidiMessage::PitchBend { channel, .. } => channel,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_control_change() {
+        assert_eq!(
+            MidiMessage::from_bytes(&[0xB3, 74, 100]),
+            Some(MidiMessage::ControlChange {
+                channel: 3,
+                controller: 74,
+                value: 100
+            })
+        );
+    }
+
+    #[test]
+    fn note_on_with_zero_velocity_is_note_off() {
+        assert_eq!(
+            MidiMessage::from_bytes(&[0x90, 60, 0]),
+            Some(MidiMessage::NoteOff {
+                channel: 0,
+                note: 60,
+                velocity: 0
+            })
+        );
+    }
+
+    #[test]
+    fn parses_pitch_bend() {
+        assert_eq!(
+            MidiMessage::from_bytes(&[0xE1, 0x00, 0x40]),
+            Some(MidiMessage::PitchBend {
+                channel: 1,
+                value: 8192
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_truncated_messages() {
+        assert_eq!(MidiMessage::from_bytes(&[0x90, 60]), None);
+        assert_eq!(MidiMessage::from_bytes(&[]), None);
+    }
+
+    #[test]
+    fn round_trips_through_bytes() {
+        let message = MidiMessage::PitchBend {
+            channel: 5,
+            value: 12345,
+        };
+
+        let (bytes, length) = message.to_bytes();
+        assert_eq!(MidiMessage::from_bytes(&bytes[..length]), Some(message));
+    }
+}
@@ -0,0 +1,305 @@
+use crate::tempo_map::TempoMap;
+
+use super::message::MidiMessage;
+
+const META_EVENT: u8 = 0xFF;
+const META_SET_TEMPO: u8 = 0x51;
+const META_END_OF_TRACK: u8 = 0x2F;
+const SYSEX_START: u8 = 0xF0;
+const SYSEX_ESCAPE: u8 = 0xF7;
+
+#[derive(Debug, PartialEq)]
+pub enum MidiFileError {
+    Io(String),
+    InvalidHeader,
+    UnsupportedTimeDivision,
+    UnexpectedEndOfData,
+    InvalidEvent(u8),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MidiFileEvent {
+    pub tick: u64,
+    pub message: MidiMessage,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MidiFile {
+    format: u16,
+    ticks_per_quarter_note: u16,
+    tracks: Vec<Vec<MidiFileEvent>>,
+    tempo_changes: Vec<(u64, u32)>,
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.position >= self.data.len()
+    }
+
+    fn read_u8(&mut self) -> Result<u8, MidiFileError> {
+        let value = *self
+            .data
+            .get(self.position)
+            .ok_or(MidiFileError::UnexpectedEndOfData)?;
+        self.position += 1;
+        Ok(value)
+    }
+
+    fn peek_u8(&self) -> Result<u8, MidiFileError> {
+        self.data
+            .get(self.position)
+            .copied()
+            .ok_or(MidiFileError::UnexpectedEndOfData)
+    }
+
+    fn read_bytes(&mut self, length: usize) -> Result<&'a [u8], MidiFileError> {
+        let end = self.position + length;
+        if end > self.data.len() {
+            return Err(MidiFileError::UnexpectedEndOfData);
+        }
+
+        let bytes = &self.data[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn read_u16(&mut self) -> Result<u16, MidiFileError> {
+        let bytes = self.read_bytes(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn read_u32(&mut self) -> Result<u32, MidiFileError> {
+        let bytes = self.read_bytes(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn read_variable_length(&mut self) -> Result<u32, MidiFileError> {
+        let mut value = 0_u32;
+
+        for _ in 0..4 {
+            let byte = self.read_u8()?;
+            value = (value << 7) | (byte & 0x7F) as u32;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+
+        Err(MidiFileError::InvalidEvent(0x80))
+    }
+}
+
+impl MidiFile {
+    pub fn from_file(path: &str) -> Result<Self, MidiFileError> {
+        let data = std::fs::read(path).map_err(|error| MidiFileError::Io(error.to_string()))?;
+        Self::from_bytes(&data)
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Result<Self, MidiFileError> {
+        let mut reader = Reader::new(data);
+
+        if reader.read_bytes(4)? != b"MThd" || reader.read_u32()? < 6 {
+            return Err(MidiFileError::InvalidHeader);
+        }
+
+        let format = reader.read_u16()?;
+        let num_tracks = reader.read_u16()?;
+        let division = reader.read_u16()?;
+
+        if division & 0x8000 != 0 {
+            return Err(MidiFileError::UnsupportedTimeDivision);
+        }
+
+        let mut midi_file = Self {
+            format,
+            ticks_per_quarter_note: division,
+            tracks: Vec::with_capacity(num_tracks as usize),
+            tempo_changes: Vec::new(),
+        };
+
+        while !reader.is_empty() && midi_file.tracks.len() < num_tracks as usize {
+            let chunk_type = reader.read_bytes(4)?;
+            let length = reader.read_u32()? as usize;
+            let chunk = reader.read_bytes(length)?;
+
+            if chunk_type == b"MTrk" {
+                let track = midi_file.read_track(chunk)?;
+                midi_file.tracks.push(track);
+            }
+        }
+
+        midi_file.tempo_changes.sort_by_key(|(tick, _)| *tick);
+
+        Ok(midi_file)
+    }
+
+    fn read_track(&mut self, data: &[u8]) -> Result<Vec<MidiFileEvent>, MidiFileError> {
+        let mut reader = Reader::new(data);
+        let mut events = Vec::new();
+        let mut tick = 0_u64;
+        let mut running_status: Option<u8> = None;
+
+        while !reader.is_empty() {
+            tick += reader.read_variable_length()? as u64;
+
+            let status = match reader.peek_u8()? {
+                byte if byte & 0x80 != 0 => {
+                    reader.read_u8()?;
+                    byte
+                }
+                byte => running_status.ok_or(MidiFileError::InvalidEvent(byte))?,
+            };
+
+            match status {
+                META_EVENT => {
+                    let meta_type = reader.read_u8()?;
+                    let length = reader.read_variable_length()? as usize;
+                    let meta_data = reader.read_bytes(length)?;
+
+                    match meta_type {
+                        META_SET_TEMPO if length == 3 => {
+                            let microseconds_per_quarter_note = (meta_data[0] as u32) << 16
+                                | (meta_data[1] as u32) << 8
+                                | meta_data[2] as u32;
+                            self.tempo_changes
+                                .push((tick, microseconds_per_quarter_note));
+                        }
+                        META_END_OF_TRACK => break,
+                        _ => (),
+                    }
+                }
+                SYSEX_START | SYSEX_ESCAPE => {
+                    let length = reader.read_variable_length()? as usize;
+                    reader.read_bytes(length)?;
+                }
+                _ => {
+                    running_status = Some(status);
+
+                    let num_data_bytes = match status & 0xF0 {
+                        0xC0 | 0xD0 => 1,
+                        0x80..=0xE0 => 2,
+                        _ => return Err(MidiFileError::InvalidEvent(status)),
+                    };
+
+                    let mut bytes = [status, 0, 0];
+                    for byte in bytes.iter_mut().skip(1).take(num_data_bytes) {
+                        *byte = reader.read_u8()?;
+                    }
+
+                    if let Some(message) = MidiMessage::from_bytes(&bytes[..num_data_bytes + 1]) {
+                        events.push(MidiFileEvent { tick, message });
+                    }
+                }
+            }
+        }
+
+        Ok(events)
+    }
+
+    pub fn format(&self) -> u16 {
+        self.format
+    }
+
+    pub fn ticks_per_quarter_note(&self) -> u16 {
+        self.ticks_per_quarter_note
+    }
+
+    pub fn tracks(&self) -> &[Vec<MidiFileEvent>] {
+        &self.tracks
+    }
+
+    pub fn tick_to_beat(&self, tick: u64) -> f64 {
+        tick as f64 / self.ticks_per_quarter_note as f64
+    }
+
+    pub fn tempo_map(&self) -> TempoMap {
+        let mut tempo_map = TempoMap::default();
+
+        for (tick, microseconds_per_quarter_note) in self.tempo_changes.iter() {
+            if *microseconds_per_quarter_note > 0 {
+                tempo_map.add_tempo_change(
+                    self.tick_to_beat(*tick),
+                    60_000_000.0 / *microseconds_per_quarter_note as f64,
+                );
+            }
+        }
+
+        tempo_map
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use approx::assert_relative_eq;
+
+    use super::*;
+
+    pub fn make_test_file() -> Vec<u8> {
+        let track: Vec<u8> = vec![
+            0x00, 0xFF, 0x51, 0x03, 0x07, 0xA1, 0x20, // 120 bpm
+            0x00, 0x90, 60, 100, // note on
+            0x83, 0x60, 0x80, 60, 0, // note off after 480 ticks
+            0x00, 0xFF, 0x51, 0x03, 0x0F, 0x42, 0x40, // 60 bpm
+            0x00, 0x90, 64, 100, // note on
+            0x83, 0x60, 64, 0, // running status note off after 480 ticks
+            0x00, 0xFF, 0x2F, 0x00,
+        ];
+
+        let mut data = Vec::new();
+        data.extend_from_slice(b"MThd");
+        data.extend_from_slice(&6_u32.to_be_bytes());
+        data.extend_from_slice(&0_u16.to_be_bytes());
+        data.extend_from_slice(&1_u16.to_be_bytes());
+        data.extend_from_slice(&480_u16.to_be_bytes());
+        data.extend_from_slice(b"MTrk");
+        data.extend_from_slice(&(track.len() as u32).to_be_bytes());
+        data.extend_from_slice(&track);
+        data
+    }
+
+    #[test]
+    fn parses_events_and_running_status() {
+        let midi_file = MidiFile::from_bytes(&make_test_file()).unwrap();
+
+        assert_eq!(midi_file.ticks_per_quarter_note(), 480);
+        assert_eq!(midi_file.tracks().len(), 1);
+
+        let track = &midi_file.tracks()[0];
+        assert_eq!(track.len(), 4);
+        assert_eq!(track[1].tick, 480);
+        assert_eq!(
+            track[3].message,
+            MidiMessage::NoteOff {
+                channel: 0,
+                note: 64,
+                velocity: 0
+            }
+        );
+    }
+
+    #[test]
+    fn builds_tempo_map_from_meta_events() {
+        let midi_file = MidiFile::from_bytes(&make_test_file()).unwrap();
+        let tempo_map = midi_file.tempo_map();
+
+        assert_relative_eq!(tempo_map.tempo_at_beat(0.0), 120.0);
+        assert_relative_eq!(tempo_map.tempo_at_beat(1.0), 60.0);
+        assert_relative_eq!(tempo_map.seconds_at_beat(2.0), 1.5);
+    }
+
+    #[test]
+    fn rejects_invalid_header() {
+        assert_eq!(
+            MidiFile::from_bytes(b"RIFF0000"),
+            Err(MidiFileError::InvalidHeader)
+        );
+    }
+}
@@ -0,0 +1,206 @@
+use crate::{
+    events::{
+        loop_scheduler::LoopScheduler,
+        note_event::{NoteEvent, NoteId},
+    },
+    timestamp::Timestamp,
+};
+
+use super::{message::MidiMessage, midi_file::MidiFile};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScheduledMidiMessage {
+    pub time: Timestamp,
+    pub message: MidiMessage,
+}
+
+pub struct MidiFilePlayer {
+    messages: Vec<ScheduledMidiMessage>,
+}
+
+impl MidiFilePlayer {
+    pub fn new(midi_file: &MidiFile) -> Self {
+        let tempo_map = midi_file.tempo_map();
+
+        let mut messages: Vec<ScheduledMidiMessage> = midi_file
+            .tracks()
+            .iter()
+            .flat_map(|track| track.iter())
+            .map(|event| ScheduledMidiMessage {
+                time: Timestamp::from_seconds(
+                    tempo_map.seconds_at_beat(midi_file.tick_to_beat(event.tick)),
+                ),
+                message: event.message,
+            })
+            .collect();
+
+        messages.sort_by_key(|message| message.time);
+
+        Self { messages }
+    }
+
+    pub fn messages(&self) -> &[ScheduledMidiMessage] {
+        &self.messages
+    }
+
+    pub fn duration(&self) -> Timestamp {
+        self.messages
+            .last()
+            .map(|message| message.time)
+            .unwrap_or_else(Timestamp::zero)
+    }
+
+    pub fn schedule(
+        &self,
+        start_time: Timestamp,
+        from_position: Timestamp,
+        mut sink: impl FnMut(Timestamp, &MidiMessage),
+    ) {
+        for scheduled in self
+            .messages
+            .iter()
+            .filter(|scheduled| scheduled.time >= from_position)
+        {
+            let offset = scheduled.time - from_position;
+            sink(
+                start_time.incremented_by_seconds(offset.get_seconds()),
+                &scheduled.message,
+            );
+        }
+    }
+
+    pub fn schedule_note_events(
+        &self,
+        start_time: Timestamp,
+        from_position: Timestamp,
+        mut sink: impl FnMut(NoteEvent),
+    ) {
+        self.schedule(start_time, from_position, |time, message| {
+            if let Some(event) = Self::note_event(time, message) {
+                sink(event);
+            }
+        });
+    }
+
+    pub fn schedule_note_events_looped(
+        &self,
+        scheduler: &mut LoopScheduler,
+        horizon: Timestamp,
+        mut sink: impl FnMut(NoteEvent),
+    ) {
+        scheduler.schedule_until(
+            horizon,
+            self.messages
+                .iter()
+                .map(|scheduled| (scheduled.time, scheduled.message)),
+            |time, message| {
+                if let Some(event) = Self::note_event(time, &message) {
+                    sink(event);
+                }
+            },
+        );
+    }
+
+    pub(crate) fn note_event(time: Timestamp, message: &MidiMessage) -> Option<NoteEvent> {
+        match *message {
+            MidiMessage::NoteOn {
+                channel,
+                note,
+                velocity,
+            } => Some(NoteEvent::note_on(
+                time,
+                Self::note_id(channel, note),
+                note as f64,
+                velocity as f64 / 127.0,
+            )),
+            MidiMessage::NoteOff {
+                channel,
+                note,
+                velocity,
+            } => Some(NoteEvent::note_off(
+                time,
+                Self::note_id(channel, note),
+                velocity as f64 / 127.0,
+            )),
+            _ => None,
+        }
+    }
+
+    fn note_id(channel: u8, note: u8) -> NoteId {
+        channel as NoteId * 128 + note as NoteId
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use crate::{events::note_event::NoteEventType, midi::midi_file::tests::make_test_file};
+
+    use super::*;
+
+    #[test]
+    fn schedules_events_against_tempo_changes() {
+        let midi_file = MidiFile::from_bytes(&make_test_file()).unwrap();
+        let player = MidiFilePlayer::new(&midi_file);
+
+        let times: Vec<f64> = player
+            .messages()
+            .iter()
+            .map(|message| message.time.get_seconds())
+            .collect();
+
+        assert_eq!(times.len(), 4);
+        assert_relative_eq!(times[0], 0.0);
+        assert_relative_eq!(times[1], 0.5, epsilon = 1e-6);
+        assert_relative_eq!(times[2], 0.5, epsilon = 1e-6);
+        assert_relative_eq!(times[3], 1.5, epsilon = 1e-6);
+        assert_relative_eq!(player.duration().get_seconds(), 1.5, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn schedules_note_events_relative_to_start() {
+        let midi_file = MidiFile::from_bytes(&make_test_file()).unwrap();
+        let player = MidiFilePlayer::new(&midi_file);
+
+        let mut events = Vec::new();
+        player.schedule_note_events(
+            Timestamp::from_seconds(10.0),
+            Timestamp::from_seconds(0.5),
+            |event| events.push(event),
+        );
+
+        assert_eq!(events.len(), 3);
+        assert_relative_eq!(events[0].time.get_seconds(), 10.0, epsilon = 1e-6);
+        assert!(matches!(
+            events[2].event_type,
+            NoteEventType::NoteOff { .. }
+        ));
+        assert_relative_eq!(events[2].time.get_seconds(), 11.0, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn repeats_note_events_on_each_loop_pass() {
+        let midi_file = MidiFile::from_bytes(&make_test_file()).unwrap();
+        let player = MidiFilePlayer::new(&midi_file);
+
+        let mut scheduler = LoopScheduler::new(Timestamp::from_seconds(10.0));
+        scheduler.set_loop(Timestamp::zero(), Timestamp::from_seconds(1.5));
+
+        let mut events = Vec::new();
+        player.schedule_note_events_looped(
+            &mut scheduler,
+            Timestamp::from_seconds(13.0),
+            |event| events.push(event),
+        );
+
+        assert_eq!(events.len(), 7);
+        assert!(matches!(
+            events[3].event_type,
+            NoteEventType::NoteOff { .. }
+        ));
+        assert!(matches!(events[4].event_type, NoteEventType::NoteOn { .. }));
+        assert_relative_eq!(events[3].time.get_seconds(), 11.5, epsilon = 1e-6);
+        assert_relative_eq!(events[4].time.get_seconds(), 11.5, epsilon = 1e-6);
+    }
+}
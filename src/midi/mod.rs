@@ -0,0 +1,6 @@
+pub mod mapping;
+pub mod message;
+pub mod midi_file;
+pub mod midi_file_player;
+pub mod mpe;
+pub mod output;
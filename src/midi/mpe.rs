@@ -0,0 +1,325 @@
+use crate::{
+    events::note_event::{NoteEvent, NoteId},
+    timestamp::Timestamp,
+};
+
+use super::message::{MidiMessage, PITCH_BEND_MAXIMUM};
+
+const NUM_CHANNELS: usize = 16;
+const TIMBRE_CONTROLLER: u8 = 74;
+const DEFAULT_MEMBER_PITCH_BEND_RANGE: f64 = 48.0;
+const DEFAULT_MASTER_PITCH_BEND_RANGE: f64 = 2.0;
+
+#[derive(Clone, Default)]
+struct ChannelState {
+    notes: Vec<(u8, NoteId)>,
+    pitch_bend: f64,
+}
+
+pub struct MpeDecoder {
+    master_channel: u8,
+    num_member_channels: u8,
+    member_pitch_bend_range: f64,
+    master_pitch_bend_range: f64,
+    master_pitch_bend: f64,
+    channels: Vec<ChannelState>,
+    next_note_id: NoteId,
+}
+
+impl Default for MpeDecoder {
+    fn default() -> Self {
+        Self::lower_zone(15)
+    }
+}
+
+impl MpeDecoder {
+    pub fn lower_zone(num_member_channels: u8) -> Self {
+        Self::new(0, num_member_channels)
+    }
+
+    pub fn upper_zone(num_member_channels: u8) -> Self {
+        Self::new(15, num_member_channels)
+    }
+
+    fn new(master_channel: u8, num_member_channels: u8) -> Self {
+        Self {
+            master_channel,
+            num_member_channels: num_member_channels.min(15),
+            member_pitch_bend_range: DEFAULT_MEMBER_PITCH_BEND_RANGE,
+            master_pitch_bend_range: DEFAULT_MASTER_PITCH_BEND_RANGE,
+            master_pitch_bend: 0.0,
+            channels: vec![ChannelState::default(); NUM_CHANNELS],
+            next_note_id: 0,
+        }
+    }
+
+    pub fn with_pitch_bend_ranges(mut self, member_semitones: f64, master_semitones: f64) -> Self {
+        self.member_pitch_bend_range = member_semitones;
+        self.master_pitch_bend_range = master_semitones;
+        self
+    }
+
+    fn is_member_channel(&self, channel: u8) -> bool {
+        let num_members = self.num_member_channels;
+        if self.master_channel == 0 {
+            (1..=num_members).contains(&channel)
+        } else {
+            (15 - num_members..15).contains(&channel)
+        }
+    }
+
+    fn bend_in_semitones(value: u16, range: f64) -> f64 {
+        let centred = value as f64 - (PITCH_BEND_MAXIMUM as f64 + 1.0) / 2.0;
+        range * centred / ((PITCH_BEND_MAXIMUM as f64 + 1.0) / 2.0)
+    }
+
+    pub fn decode(
+        &mut self,
+        message: &MidiMessage,
+        time: Timestamp,
+        mut output: impl FnMut(NoteEvent),
+    ) {
+        let channel = message.channel();
+
+        if channel == self.master_channel {
+            if let MidiMessage::PitchBend { value, .. } = *message {
+                self.master_pitch_bend =
+                    Self::bend_in_semitones(value, self.master_pitch_bend_range);
+
+                let master_pitch_bend = self.master_pitch_bend;
+                for state in self.channels.iter() {
+                    for (_, note_id) in state.notes.iter() {
+                        output(NoteEvent::pitch_bend(
+                            time,
+                            *note_id,
+                            state.pitch_bend + master_pitch_bend,
+                        ));
+                    }
+                }
+            }
+
+            return;
+        }
+
+        if !self.is_member_channel(channel) {
+            return;
+        }
+
+        let master_pitch_bend = self.master_pitch_bend;
+        let member_pitch_bend_range = self.member_pitch_bend_range;
+        let next_note_id = &mut self.next_note_id;
+        let state = &mut self.channels[channel as usize];
+
+        match *message {
+            MidiMessage::NoteOn { note, velocity, .. } => {
+                let note_id = *next_note_id;
+                *next_note_id = next_note_id.wrapping_add(1);
+                state.notes.push((note, note_id));
+
+                output(NoteEvent::note_on(
+                    time,
+                    note_id,
+                    note as f64,
+                    velocity as f64 / 127.0,
+                ));
+
+                let pitch_bend = state.pitch_bend + master_pitch_bend;
+                if pitch_bend != 0.0 {
+                    output(NoteEvent::pitch_bend(time, note_id, pitch_bend));
+                }
+            }
+            MidiMessage::NoteOff { note, velocity, .. } => {
+                if let Some(index) = state.notes.iter().position(|(n, _)| *n == note) {
+                    let (_, note_id) = state.notes.remove(index);
+                    output(NoteEvent::note_off(time, note_id, velocity as f64 / 127.0));
+                }
+            }
+            MidiMessage::PitchBend { value, .. } => {
+                state.pitch_bend = Self::bend_in_semitones(value, member_pitch_bend_range);
+                for (_, note_id) in state.notes.iter() {
+                    output(NoteEvent::pitch_bend(
+                        time,
+                        *note_id,
+                        state.pitch_bend + master_pitch_bend,
+                    ));
+                }
+            }
+            MidiMessage::ChannelPressure { pressure, .. } => {
+                for (_, note_id) in state.notes.iter() {
+                    output(NoteEvent::pressure(time, *note_id, pressure as f64 / 127.0));
+                }
+            }
+            MidiMessage::PolyphonicAftertouch { note, pressure, .. } => {
+                for (_, note_id) in state.notes.iter().filter(|(n, _)| *n == note) {
+                    output(NoteEvent::pressure(time, *note_id, pressure as f64 / 127.0));
+                }
+            }
+            MidiMessage::ControlChange {
+                controller, value, ..
+            } if controller == TIMBRE_CONTROLLER => {
+                for (_, note_id) in state.notes.iter() {
+                    output(NoteEvent::timbre(time, *note_id, value as f64 / 127.0));
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use crate::events::note_event::NoteEventType;
+
+    use super::*;
+
+    fn decode(decoder: &mut MpeDecoder, message: MidiMessage) -> Vec<NoteEvent> {
+        let mut events = Vec::new();
+        decoder.decode(&message, Timestamp::zero(), |event| events.push(event));
+        events
+    }
+
+    #[test]
+    fn pitch_bend_only_affects_note_on_same_channel() {
+        let mut decoder = MpeDecoder::default();
+
+        let first = decode(
+            &mut decoder,
+            MidiMessage::NoteOn {
+                channel: 1,
+                note: 60,
+                velocity: 127,
+            },
+        )[0];
+
+        let _ = decode(
+            &mut decoder,
+            MidiMessage::NoteOn {
+                channel: 2,
+                note: 64,
+                velocity: 127,
+            },
+        );
+
+        let events = decode(
+            &mut decoder,
+            MidiMessage::PitchBend {
+                channel: 1,
+                value: PITCH_BEND_MAXIMUM,
+            },
+        );
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].note_id, first.note_id);
+        match events[0].event_type {
+            NoteEventType::PitchBend { semitones } => {
+                assert_relative_eq!(semitones, 48.0, epsilon = 0.01)
+            }
+            _ => panic!("Expected pitch bend"),
+        }
+    }
+
+    #[test]
+    fn timbre_and_pressure_are_per_note() {
+        let mut decoder = MpeDecoder::default();
+
+        let _ = decode(
+            &mut decoder,
+            MidiMessage::NoteOn {
+                channel: 3,
+                note: 60,
+                velocity: 100,
+            },
+        );
+
+        let events = decode(
+            &mut decoder,
+            MidiMessage::ControlChange {
+                channel: 3,
+                controller: TIMBRE_CONTROLLER,
+                value: 127,
+            },
+        );
+        assert_eq!(events[0].event_type, NoteEventType::Timbre { amount: 1.0 });
+
+        let events = decode(
+            &mut decoder,
+            MidiMessage::ChannelPressure {
+                channel: 3,
+                pressure: 0,
+            },
+        );
+        assert_eq!(
+            events[0].event_type,
+            NoteEventType::Pressure { amount: 0.0 }
+        );
+    }
+
+    #[test]
+    fn master_pitch_bend_applies_to_all_notes() {
+        let mut decoder = MpeDecoder::default();
+
+        for channel in 1..4 {
+            let _ = decode(
+                &mut decoder,
+                MidiMessage::NoteOn {
+                    channel,
+                    note: 60,
+                    velocity: 100,
+                },
+            );
+        }
+
+        let events = decode(
+            &mut decoder,
+            MidiMessage::PitchBend {
+                channel: 0,
+                value: 0,
+            },
+        );
+
+        assert_eq!(events.len(), 3);
+        for event in events {
+            assert_eq!(
+                event.event_type,
+                NoteEventType::PitchBend { semitones: -2.0 }
+            );
+        }
+    }
+
+    #[test]
+    fn note_off_releases_matching_note() {
+        let mut decoder = MpeDecoder::default();
+
+        let note_on = decode(
+            &mut decoder,
+            MidiMessage::NoteOn {
+                channel: 1,
+                note: 60,
+                velocity: 100,
+            },
+        )[0];
+
+        let events = decode(
+            &mut decoder,
+            MidiMessage::NoteOff {
+                channel: 1,
+                note: 60,
+                velocity: 0,
+            },
+        );
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].note_id, note_on.note_id);
+
+        let events = decode(
+            &mut decoder,
+            MidiMessage::PitchBend {
+                channel: 1,
+                value: 0,
+            },
+        );
+        assert!(events.is_empty());
+    }
+}
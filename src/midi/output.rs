@@ -0,0 +1,66 @@
+use crate::timestamp::Timestamp;
+
+use super::{message::MidiMessage, midi_file_player::ScheduledMidiMessage};
+
+pub trait MidiOutputPort {
+    fn send(&mut self, time: Timestamp, message: &MidiMessage);
+}
+
+pub struct MidiOutputQueue {
+    messages: Vec<ScheduledMidiMessage>,
+}
+
+impl MidiOutputQueue {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            messages: Vec::with_capacity(capacity),
+        }
+    }
+
+    pub fn push(&mut self, time: Timestamp, message: MidiMessage) -> bool {
+        if self.messages.len() == self.messages.capacity() {
+            return false;
+        }
+
+        self.messages.push(ScheduledMidiMessage { time, message });
+        true
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    pub fn drain(&mut self, output: &mut dyn FnMut(ScheduledMidiMessage)) {
+        for message in self.messages.drain(..) {
+            output(message);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note_on(note: u8) -> MidiMessage {
+        MidiMessage::NoteOn {
+            channel: 0,
+            note,
+            velocity: 100,
+        }
+    }
+
+    #[test]
+    fn drops_messages_when_full() {
+        let mut queue = MidiOutputQueue::with_capacity(2);
+
+        assert!(queue.push(Timestamp::zero(), note_on(60)));
+        assert!(queue.push(Timestamp::zero(), note_on(61)));
+        assert!(!queue.push(Timestamp::zero(), note_on(62)));
+
+        let mut drained = Vec::new();
+        queue.drain(&mut |message| drained.push(message.message));
+
+        assert_eq!(drained, vec![note_on(60), note_on(61)]);
+        assert!(queue.is_empty());
+    }
+}
@@ -1,15 +1,21 @@
-use lockfree::channel::mpsc::Sender;
+use std::time::Duration;
 
 use crate::{
     commands::{
-        command::{Command, ParameterChangeRequest},
+        command::{Command, ParameterChangeRequest, ParameterTempoSyncRequest},
+        command_queue::CommandQueue,
         id::Id,
     },
     timestamp::Timestamp,
 };
 use atomic_float::AtomicF64;
 
-use super::{realtime_parameter::RealtimeAudioParameter, ParameterChange};
+use super::{
+    gesture::{GesturePhase, ParameterGesture},
+    realtime_parameter::RealtimeAudioParameter,
+    tempo_sync::{NoteDivision, ParameterUnit, TempoSync},
+    ParameterChange,
+};
 use super::{ParameterValue, ValueChangeMethod};
 
 pub struct AudioParameter {
@@ -18,7 +24,7 @@ pub struct AudioParameter {
     value: ParameterValue,
     minimum_value: f64,
     maximum_value: f64,
-    command_queue: Sender<Command>,
+    command_queue: CommandQueue,
 }
 
 impl AudioParameter {
@@ -27,14 +33,15 @@ impl AudioParameter {
         initial_value: f64,
         minimum_value: f64,
         maximum_value: f64,
-        command_queue: Sender<Command>,
+        command_queue: CommandQueue,
     ) -> (Self, RealtimeAudioParameter) {
         assert!((minimum_value..maximum_value).contains(&initial_value));
         assert!(minimum_value < maximum_value);
 
         let parameter_id = Id::generate();
         let param_value = ParameterValue::new(AtomicF64::new(initial_value));
-        let realtime_audio_param = RealtimeAudioParameter::new(parameter_id, param_value.clone());
+        let realtime_audio_param = RealtimeAudioParameter::new(parameter_id, param_value.clone())
+            .with_range(minimum_value, maximum_value);
 
         (
             Self {
@@ -53,6 +60,18 @@ impl AudioParameter {
         self.parameter_id
     }
 
+    pub fn get_dsp_id(&self) -> Id {
+        self.dsp_id
+    }
+
+    pub fn get_minimum_value(&self) -> f64 {
+        self.minimum_value
+    }
+
+    pub fn get_maximum_value(&self) -> f64 {
+        self.maximum_value
+    }
+
     pub fn get_value(&self) -> ParameterValue {
         self.value.clone()
     }
@@ -64,11 +83,46 @@ impl AudioParameter {
             .send(Command::ParameterValueChange(ParameterChangeRequest {
                 dsp_id: self.dsp_id,
                 parameter_id: self.parameter_id,
-                change: ParameterChange {
-                    value,
-                    end_time: at_time,
-                    method: ValueChangeMethod::Immediate,
-                },
+                change: ParameterChange::immediate(value, at_time),
+            }));
+    }
+
+    pub fn begin_gesture(&mut self, at_time: Timestamp) {
+        self.send_gesture(GesturePhase::Begin, at_time);
+    }
+
+    pub fn end_gesture(&mut self, at_time: Timestamp) {
+        self.send_gesture(GesturePhase::End, at_time);
+    }
+
+    fn send_gesture(&mut self, phase: GesturePhase, time: Timestamp) {
+        let _ = self
+            .command_queue
+            .send(Command::ParameterGesture(ParameterGesture {
+                dsp_id: self.dsp_id,
+                parameter_id: self.parameter_id,
+                phase,
+                time,
+            }));
+    }
+
+    pub fn sync_to_tempo(&mut self, division: NoteDivision, unit: ParameterUnit) {
+        self.send_tempo_sync(Some(TempoSync::new(division, unit)));
+    }
+
+    pub fn clear_tempo_sync(&mut self) {
+        self.send_tempo_sync(None);
+    }
+
+    fn send_tempo_sync(&mut self, sync: Option<TempoSync>) {
+        let _ = self
+            .command_queue
+            .send(Command::ParameterTempoSync(ParameterTempoSyncRequest {
+                dsp_id: self.dsp_id,
+                parameter_id: self.parameter_id,
+                sync,
+                minimum: self.minimum_value,
+                maximum: self.maximum_value,
             }));
     }
 
@@ -86,6 +140,35 @@ impl AudioParameter {
                 },
             }));
     }
+
+    pub fn exponential_ramp_to_value_at_time(&mut self, mut value: f64, end_time: Timestamp) {
+        value = value.clamp(self.minimum_value, self.maximum_value);
+        self.send_change(ParameterChange::exponential(value, end_time));
+    }
+
+    pub fn set_target_at_time(
+        &mut self,
+        mut target: f64,
+        start_time: Timestamp,
+        time_constant: Duration,
+    ) {
+        target = target.clamp(self.minimum_value, self.maximum_value);
+        self.send_change(ParameterChange::target(
+            target,
+            start_time,
+            time_constant.as_secs_f64(),
+        ));
+    }
+
+    fn send_change(&mut self, change: ParameterChange) {
+        let _ = self
+            .command_queue
+            .send(Command::ParameterValueChange(ParameterChangeRequest {
+                dsp_id: self.dsp_id,
+                parameter_id: self.parameter_id,
+                change,
+            }));
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,217 @@
+use crate::{events::loop_scheduler::LoopScheduler, tempo_map::TempoMap, timestamp::Timestamp};
+
+use super::audio_parameter::AudioParameter;
+
+const BEAT_TOLERANCE: f64 = 1e-9;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AutomationCurve {
+    Step,
+    Linear,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AutomationPoint {
+    pub beat: f64,
+    pub value: f64,
+    pub curve: AutomationCurve,
+}
+
+impl AutomationPoint {
+    pub fn new(beat: f64, value: f64, curve: AutomationCurve) -> Self {
+        Self { beat, value, curve }
+    }
+}
+
+pub struct AutomationLane {
+    points: Vec<AutomationPoint>,
+    tempo_map: TempoMap,
+    scheduler: LoopScheduler,
+    loop_beats: Option<(f64, f64)>,
+}
+
+impl AutomationLane {
+    pub fn new(tempo_map: TempoMap, origin: Timestamp) -> Self {
+        Self {
+            points: Vec::new(),
+            tempo_map,
+            scheduler: LoopScheduler::new(origin),
+            loop_beats: None,
+        }
+    }
+
+    pub fn points(&self) -> &[AutomationPoint] {
+        &self.points
+    }
+
+    pub fn add_point(&mut self, point: AutomationPoint) {
+        self.remove_point(point.beat);
+
+        let index = self
+            .points
+            .iter()
+            .position(|existing| existing.beat > point.beat)
+            .unwrap_or(self.points.len());
+
+        self.points.insert(index, point);
+    }
+
+    pub fn move_point(&mut self, from_beat: f64, to_beat: f64, value: f64) -> bool {
+        match self.find_point(from_beat) {
+            Some(index) => {
+                let curve = self.points.remove(index).curve;
+                self.add_point(AutomationPoint::new(to_beat.max(0.0), value, curve));
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn remove_point(&mut self, beat: f64) -> bool {
+        match self.find_point(beat) {
+            Some(index) => {
+                self.points.remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn find_point(&self, beat: f64) -> Option<usize> {
+        self.points
+            .iter()
+            .position(|point| (point.beat - beat).abs() < BEAT_TOLERANCE)
+    }
+
+    pub fn value_at_beat(&self, beat: f64) -> Option<f64> {
+        let next_index = self.points.iter().position(|point| point.beat > beat);
+
+        match next_index {
+            Some(0) => self.points.first().map(|point| point.value),
+            Some(index) => {
+                let previous = self.points[index - 1];
+                let next = self.points[index];
+
+                match next.curve {
+                    AutomationCurve::Step => Some(previous.value),
+                    AutomationCurve::Linear => {
+                        let amount = (beat - previous.beat) / (next.beat - previous.beat);
+                        Some(previous.value + (next.value - previous.value) * amount)
+                    }
+                }
+            }
+            None => self.points.last().map(|point| point.value),
+        }
+    }
+
+    pub fn set_loop(&mut self, start_beat: f64, end_beat: f64) {
+        self.loop_beats = Some((start_beat, end_beat));
+        self.scheduler.set_loop(
+            Timestamp::from_seconds(self.tempo_map.seconds_at_beat(start_beat)),
+            Timestamp::from_seconds(self.tempo_map.seconds_at_beat(end_beat)),
+        );
+    }
+
+    pub fn clear_loop(&mut self) {
+        self.loop_beats = None;
+        self.scheduler.clear_loop();
+    }
+
+    pub fn beat_at_time(&self, time: Timestamp) -> f64 {
+        let position = self.scheduler.song_position_at(time);
+        self.tempo_map.beat_at_seconds(position.get_seconds())
+    }
+
+    pub fn stream_to(&mut self, parameter: &mut AudioParameter, horizon: Timestamp) {
+        let mut events: Vec<(Timestamp, AutomationPoint)> = self
+            .points
+            .iter()
+            .map(|point| (self.song_time_at_beat(point.beat), *point))
+            .collect();
+
+        if let Some((start_beat, _)) = self.loop_beats {
+            if let Some(value) = self.value_at_beat(start_beat) {
+                events.push((
+                    self.song_time_at_beat(start_beat),
+                    AutomationPoint::new(start_beat, value, AutomationCurve::Step),
+                ));
+            }
+        }
+
+        self.scheduler
+            .schedule_until(horizon, events, |time, point| match point.curve {
+                AutomationCurve::Step => parameter.set_value_at_time(point.value, time),
+                AutomationCurve::Linear => parameter.linear_ramp_to_value(point.value, time),
+            });
+    }
+
+    fn song_time_at_beat(&self, beat: f64) -> Timestamp {
+        Timestamp::from_seconds(self.tempo_map.seconds_at_beat(beat))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands::command_queue::CommandQueue;
+    use approx::assert_relative_eq;
+
+    use crate::commands::{command::Command, id::Id};
+
+    use super::*;
+
+    fn make_lane() -> AutomationLane {
+        let mut lane = AutomationLane::new(TempoMap::new(120.0), Timestamp::zero());
+        lane.add_point(AutomationPoint::new(0.0, 0.0, AutomationCurve::Step));
+        lane.add_point(AutomationPoint::new(4.0, 1.0, AutomationCurve::Linear));
+        lane.add_point(AutomationPoint::new(2.0, 0.5, AutomationCurve::Step));
+        lane
+    }
+
+    #[test]
+    fn edits_points_in_musical_time() {
+        let mut lane = make_lane();
+
+        let beats: Vec<f64> = lane.points().iter().map(|point| point.beat).collect();
+        assert_eq!(beats, vec![0.0, 2.0, 4.0]);
+        assert_eq!(lane.value_at_beat(1.0), Some(0.0));
+        assert_relative_eq!(lane.value_at_beat(3.0).unwrap(), 0.75);
+
+        assert!(lane.move_point(2.0, 3.0, 0.0));
+        assert_relative_eq!(lane.value_at_beat(3.5).unwrap(), 0.5);
+
+        assert!(lane.remove_point(4.0));
+        assert!(!lane.remove_point(4.0));
+        assert_eq!(lane.value_at_beat(10.0), Some(0.0));
+    }
+
+    #[test]
+    fn streams_points_to_parameter_on_each_loop_pass() {
+        let (command_tx, mut command_rx) = CommandQueue::create();
+        let (mut parameter, _) = AudioParameter::new(Id::generate(), 0.0, 0.0, 2.0, command_tx);
+
+        let mut lane = make_lane();
+        lane.set_loop(0.0, 4.0);
+        lane.stream_to(&mut parameter, Timestamp::from_seconds(3.0));
+
+        let mut changes = Vec::new();
+        while let Ok(Command::ParameterValueChange(request)) = command_rx.recv() {
+            changes.push((
+                request.change.get_end_time().get_seconds(),
+                request.change.value,
+            ));
+        }
+
+        assert_eq!(
+            changes,
+            vec![
+                (0.0, 0.0),
+                (0.0, 0.0),
+                (1.0, 0.5),
+                (2.0, 1.0),
+                (2.0, 0.0),
+                (2.0, 0.0)
+            ]
+        );
+        assert_relative_eq!(lane.beat_at_time(Timestamp::from_seconds(2.5)), 1.0);
+    }
+}
@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+
+use crate::{commands::id::Id, tempo_map::TempoMap, timestamp::Timestamp};
+
+use super::{
+    automation::{AutomationCurve, AutomationLane, AutomationPoint},
+    gesture::{GesturePhase, ParameterGesture},
+    ParameterChange, ValueChangeMethod,
+};
+
+pub struct AutomationRecorder {
+    tempo_map: TempoMap,
+    origin: Timestamp,
+    lanes: HashMap<Id, AutomationLane>,
+    gestures: HashMap<Id, usize>,
+}
+
+impl AutomationRecorder {
+    pub fn new(tempo_map: TempoMap, origin: Timestamp) -> Self {
+        Self {
+            tempo_map,
+            origin,
+            lanes: HashMap::new(),
+            gestures: HashMap::new(),
+        }
+    }
+
+    pub fn handle_gesture(&mut self, gesture: ParameterGesture) {
+        match gesture.phase {
+            GesturePhase::Begin => {
+                self.gestures.insert(gesture.parameter_id, 0);
+            }
+            GesturePhase::End => {
+                self.gestures.remove(&gesture.parameter_id);
+            }
+        }
+    }
+
+    pub fn handle_change(&mut self, parameter_id: Id, change: ParameterChange) {
+        let curve = match self.gestures.get_mut(&parameter_id) {
+            Some(num_points) => {
+                *num_points += 1;
+                if *num_points == 1 {
+                    AutomationCurve::Step
+                } else {
+                    AutomationCurve::Linear
+                }
+            }
+            None => match change.method {
+                ValueChangeMethod::Immediate => AutomationCurve::Step,
+                ValueChangeMethod::Linear
+                | ValueChangeMethod::Exponential
+                | ValueChangeMethod::Target { .. } => AutomationCurve::Linear,
+            },
+        };
+
+        let lane = self
+            .lanes
+            .entry(parameter_id)
+            .or_insert_with(|| AutomationLane::new(self.tempo_map.clone(), self.origin));
+
+        let beat = lane.beat_at_time(change.end_time);
+        lane.add_point(AutomationPoint::new(beat, change.value, curve));
+    }
+
+    pub fn is_in_gesture(&self, parameter_id: Id) -> bool {
+        self.gestures.contains_key(&parameter_id)
+    }
+
+    pub fn lane(&self, parameter_id: Id) -> Option<&AutomationLane> {
+        self.lanes.get(&parameter_id)
+    }
+
+    pub fn into_lanes(self) -> HashMap<Id, AutomationLane> {
+        self.lanes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gesture(parameter_id: Id, phase: GesturePhase, seconds: f64) -> ParameterGesture {
+        ParameterGesture {
+            dsp_id: Id::generate(),
+            parameter_id,
+            phase,
+            time: Timestamp::from_seconds(seconds),
+        }
+    }
+
+    #[test]
+    fn distinguishes_gestures_from_discrete_sets() {
+        let parameter_id = Id::generate();
+        let mut recorder = AutomationRecorder::new(TempoMap::new(120.0), Timestamp::zero());
+
+        recorder.handle_change(
+            parameter_id,
+            ParameterChange::immediate(0.2, Timestamp::zero()),
+        );
+
+        recorder.handle_gesture(gesture(parameter_id, GesturePhase::Begin, 1.0));
+        assert!(recorder.is_in_gesture(parameter_id));
+        for (seconds, value) in [(1.0, 0.4), (1.5, 0.6), (2.0, 0.8)] {
+            recorder.handle_change(
+                parameter_id,
+                ParameterChange::immediate(value, Timestamp::from_seconds(seconds)),
+            );
+        }
+        recorder.handle_gesture(gesture(parameter_id, GesturePhase::End, 2.0));
+        assert!(!recorder.is_in_gesture(parameter_id));
+
+        recorder.handle_change(
+            parameter_id,
+            ParameterChange::immediate(0.1, Timestamp::from_seconds(3.0)),
+        );
+
+        let points: Vec<(f64, f64, AutomationCurve)> = recorder
+            .lane(parameter_id)
+            .unwrap()
+            .points()
+            .iter()
+            .map(|point| (point.beat, point.value, point.curve))
+            .collect();
+
+        assert_eq!(
+            points,
+            vec![
+                (0.0, 0.2, AutomationCurve::Step),
+                (2.0, 0.4, AutomationCurve::Step),
+                (3.0, 0.6, AutomationCurve::Linear),
+                (4.0, 0.8, AutomationCurve::Linear),
+                (6.0, 0.1, AutomationCurve::Step),
+            ]
+        );
+    }
+}
@@ -0,0 +1,15 @@
+use crate::{commands::id::Id, timestamp::Timestamp};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GesturePhase {
+    Begin,
+    End,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ParameterGesture {
+    pub dsp_id: Id,
+    pub parameter_id: Id,
+    pub phase: GesturePhase,
+    pub time: Timestamp,
+}
@@ -0,0 +1,197 @@
+use std::{collections::HashMap, sync::atomic::Ordering, time::Duration};
+
+use crate::{
+    commands::{command::ParameterChangeRequest, id::Id},
+    timestamp::Timestamp,
+};
+
+use super::{audio_parameter::AudioParameter, snapshot::ParameterSnapshot, ParameterChange};
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MixState {
+    snapshot: ParameterSnapshot,
+    transitions: HashMap<Id, Duration>,
+    default_transition: Duration,
+}
+
+impl MixState {
+    pub fn new(default_transition: Duration) -> Self {
+        Self {
+            default_transition,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_value(mut self, parameter: &AudioParameter, value: f64) -> Self {
+        self.snapshot.set_value(parameter.get_id(), value);
+        self
+    }
+
+    pub fn with_transition(
+        mut self,
+        parameter: &AudioParameter,
+        value: f64,
+        transition: Duration,
+    ) -> Self {
+        self.snapshot.set_value(parameter.get_id(), value);
+        self.transitions.insert(parameter.get_id(), transition);
+        self
+    }
+
+    pub fn get_value(&self, parameter_id: Id) -> Option<f64> {
+        self.snapshot.get_value(parameter_id)
+    }
+
+    pub fn get_transition(&self, parameter_id: Id) -> Duration {
+        self.transitions
+            .get(&parameter_id)
+            .copied()
+            .unwrap_or(self.default_transition)
+    }
+
+    pub fn transition_changes(
+        &self,
+        parameters: &[&AudioParameter],
+        start_time: Timestamp,
+    ) -> Vec<ParameterChangeRequest> {
+        let mut changes = Vec::with_capacity(parameters.len() * 2);
+
+        for parameter in parameters {
+            let target = match self.get_value(parameter.get_id()) {
+                Some(value) => {
+                    value.clamp(parameter.get_minimum_value(), parameter.get_maximum_value())
+                }
+                None => continue,
+            };
+
+            let transition = self.get_transition(parameter.get_id());
+
+            let mut push = |change| {
+                changes.push(ParameterChangeRequest {
+                    dsp_id: parameter.get_dsp_id(),
+                    parameter_id: parameter.get_id(),
+                    change,
+                })
+            };
+
+            if transition.is_zero() {
+                push(ParameterChange::immediate(target, start_time));
+                continue;
+            }
+
+            let current = parameter.get_value().load(Ordering::Acquire);
+            push(ParameterChange::immediate(current, start_time));
+            push(ParameterChange::linear(
+                target,
+                start_time.incremented_by_seconds(transition.as_secs_f64()),
+            ));
+        }
+
+        changes
+    }
+}
+
+#[derive(Default)]
+pub struct MixStateBank {
+    states: HashMap<String, MixState>,
+    current: Option<String>,
+}
+
+impl MixStateBank {
+    pub fn insert(&mut self, name: &str, state: MixState) {
+        self.states.insert(String::from(name), state);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&MixState> {
+        self.states.get(name)
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<MixState> {
+        if self.current.as_deref() == Some(name) {
+            self.current = None;
+        }
+
+        self.states.remove(name)
+    }
+
+    pub fn current(&self) -> Option<&str> {
+        self.current.as_deref()
+    }
+
+    pub fn transition_to(
+        &mut self,
+        name: &str,
+        parameters: &[&AudioParameter],
+        start_time: Timestamp,
+    ) -> Option<Vec<ParameterChangeRequest>> {
+        let changes = self.get(name)?.transition_changes(parameters, start_time);
+        self.current = Some(String::from(name));
+        Some(changes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands::command_queue::CommandQueue;
+
+    use crate::parameter::ValueChangeMethod;
+
+    use super::*;
+
+    #[test]
+    fn uses_per_parameter_transition_times() {
+        let (command_tx, _command_rx) = CommandQueue::create();
+        let dsp_id = Id::generate();
+
+        let (gain, _) = AudioParameter::new(dsp_id, 1.0, 0.0, 2.0, command_tx.clone());
+        let (cutoff, _) = AudioParameter::new(dsp_id, 1000.0, 20.0, 20000.0, command_tx);
+
+        let underwater = MixState::new(Duration::from_secs(1))
+            .with_value(&gain, 0.5)
+            .with_transition(&cutoff, 400.0, Duration::from_secs(3));
+
+        let changes =
+            underwater.transition_changes(&[&gain, &cutoff], Timestamp::from_seconds(1.0));
+
+        assert_eq!(changes.len(), 4);
+
+        let ramp_end = |parameter_id: Id| {
+            changes
+                .iter()
+                .find(|request| {
+                    request.parameter_id == parameter_id
+                        && request.change.method == ValueChangeMethod::Linear
+                })
+                .map(|request| request.change.end_time)
+        };
+
+        assert_eq!(ramp_end(gain.get_id()), Some(Timestamp::from_seconds(2.0)));
+        assert_eq!(
+            ramp_end(cutoff.get_id()),
+            Some(Timestamp::from_seconds(4.0))
+        );
+    }
+
+    #[test]
+    fn tracks_current_state() {
+        let (command_tx, _command_rx) = CommandQueue::create();
+        let (gain, _) = AudioParameter::new(Id::generate(), 1.0, 0.0, 2.0, command_tx);
+
+        let mut bank = MixStateBank::default();
+        bank.insert(
+            "pause",
+            MixState::new(Duration::ZERO).with_value(&gain, 0.0),
+        );
+
+        assert!(bank
+            .transition_to("missing", &[&gain], Timestamp::zero())
+            .is_none());
+        assert_eq!(bank.current(), None);
+
+        let changes = bank
+            .transition_to("pause", &[&gain], Timestamp::zero())
+            .unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(bank.current(), Some("pause"));
+    }
+}
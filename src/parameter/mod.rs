@@ -6,18 +6,66 @@ use crate::Timestamp;
 
 pub type ParameterValue = Arc<AtomicF64>;
 
-#[derive(Clone, Copy, PartialEq, PartialOrd)]
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
 pub enum ValueChangeMethod {
     Immediate,
     Linear,
+    Exponential,
+    Target { time_constant: f64 },
 }
 
-#[derive(Clone, Copy, PartialEq, PartialOrd)]
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
 pub struct ParameterChange {
     value: f64,
     end_time: Timestamp,
     method: ValueChangeMethod,
 }
 
+impl ParameterChange {
+    pub fn immediate(value: f64, at_time: Timestamp) -> Self {
+        Self {
+            value,
+            end_time: at_time,
+            method: ValueChangeMethod::Immediate,
+        }
+    }
+
+    pub fn get_end_time(&self) -> Timestamp {
+        self.end_time
+    }
+
+    pub fn linear(value: f64, end_time: Timestamp) -> Self {
+        Self {
+            value,
+            end_time,
+            method: ValueChangeMethod::Linear,
+        }
+    }
+
+    pub fn exponential(value: f64, end_time: Timestamp) -> Self {
+        Self {
+            value,
+            end_time,
+            method: ValueChangeMethod::Exponential,
+        }
+    }
+
+    pub fn target(value: f64, start_time: Timestamp, time_constant: f64) -> Self {
+        Self {
+            value,
+            end_time: start_time,
+            method: ValueChangeMethod::Target { time_constant },
+        }
+    }
+}
+
 pub(crate) mod audio_parameter;
+pub(crate) mod automation;
+pub(crate) mod automation_recorder;
+pub(crate) mod gesture;
+pub(crate) mod mix_state;
+pub(crate) mod modulation;
+pub(crate) mod preset;
 pub(crate) mod realtime_parameter;
+pub(crate) mod snapshot;
+pub(crate) mod tempo_sync;
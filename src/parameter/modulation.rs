@@ -0,0 +1,330 @@
+use std::{collections::HashMap, time::Duration};
+
+use crate::{
+    commands::{command::Command, command_queue::CommandQueue, id::Id},
+    midi::{mapping::MidiSource, message::MidiMessage},
+};
+
+use super::{audio_parameter::AudioParameter, tempo_sync::NoteDivision};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LfoShape {
+    Sine,
+    Triangle,
+    Saw,
+    Square,
+}
+
+impl LfoShape {
+    pub fn value_at_phase(&self, phase: f64) -> f64 {
+        match self {
+            LfoShape::Sine => (std::f64::consts::TAU * phase).sin(),
+            LfoShape::Triangle => 1.0 - 4.0 * (phase - 0.5).abs(),
+            LfoShape::Saw => 2.0 * phase - 1.0,
+            LfoShape::Square => {
+                if phase < 0.5 {
+                    1.0
+                } else {
+                    -1.0
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ModulationSource {
+    Lfo {
+        frequency: f64,
+        shape: LfoShape,
+    },
+    SyncedLfo {
+        division: NoteDivision,
+        shape: LfoShape,
+    },
+    Envelope {
+        attack: Duration,
+        decay: Duration,
+        sustain: f64,
+        release: Duration,
+    },
+    Midi(MidiSource),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ModulationCurve {
+    Linear,
+    Exponential,
+    Logarithmic,
+}
+
+impl ModulationCurve {
+    pub fn apply(&self, value: f64) -> f64 {
+        match self {
+            ModulationCurve::Linear => value,
+            ModulationCurve::Exponential => value * value.abs(),
+            ModulationCurve::Logarithmic => value.signum() * value.abs().sqrt(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ModulationDestination {
+    pub dsp_id: Id,
+    pub parameter_id: Id,
+    pub minimum: f64,
+    pub maximum: f64,
+}
+
+impl ModulationDestination {
+    pub fn new(parameter: &AudioParameter) -> Self {
+        Self {
+            dsp_id: parameter.get_dsp_id(),
+            parameter_id: parameter.get_id(),
+            minimum: parameter.get_minimum_value(),
+            maximum: parameter.get_maximum_value(),
+        }
+    }
+}
+
+pub enum ModulationCommand {
+    AddSource(Id, ModulationSource),
+    UpdateSource(Id, ModulationSource),
+    RemoveSource(Id),
+    SetGate(Id, bool),
+    SetSourceValue(Id, f64),
+    AddSlot {
+        slot_id: Id,
+        source_id: Id,
+        destination: ModulationDestination,
+        depth: f64,
+        curve: ModulationCurve,
+    },
+    SetDepth(Id, f64),
+    SetCurve(Id, ModulationCurve),
+    RemoveSlot(Id),
+    SetCrossfadeTime(Duration),
+}
+
+pub struct ModulationMatrix {
+    command_queue: CommandQueue,
+    sources: HashMap<Id, ModulationSource>,
+    slots: HashMap<Id, Id>,
+}
+
+impl ModulationMatrix {
+    pub fn new(command_queue: CommandQueue) -> Self {
+        Self {
+            command_queue,
+            sources: HashMap::new(),
+            slots: HashMap::new(),
+        }
+    }
+
+    pub fn add_source(&mut self, source: ModulationSource) -> Id {
+        let id = Id::generate();
+        self.sources.insert(id, source);
+        self.send(ModulationCommand::AddSource(id, source));
+        id
+    }
+
+    pub fn update_source(&mut self, source_id: Id, source: ModulationSource) -> bool {
+        match self.sources.get_mut(&source_id) {
+            Some(existing) => {
+                *existing = source;
+                self.send(ModulationCommand::UpdateSource(source_id, source));
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn remove_source(&mut self, source_id: Id) -> bool {
+        if self.sources.remove(&source_id).is_none() {
+            return false;
+        }
+
+        self.slots
+            .retain(|_, slot_source| *slot_source != source_id);
+        self.send(ModulationCommand::RemoveSource(source_id));
+        true
+    }
+
+    pub fn get_source(&self, source_id: Id) -> Option<&ModulationSource> {
+        self.sources.get(&source_id)
+    }
+
+    pub fn connect(
+        &mut self,
+        source_id: Id,
+        parameter: &AudioParameter,
+        depth: f64,
+        curve: ModulationCurve,
+    ) -> Option<Id> {
+        if !self.sources.contains_key(&source_id) {
+            return None;
+        }
+
+        let slot_id = Id::generate();
+        self.slots.insert(slot_id, source_id);
+        self.send(ModulationCommand::AddSlot {
+            slot_id,
+            source_id,
+            destination: ModulationDestination::new(parameter),
+            depth: depth.clamp(-1.0, 1.0),
+            curve,
+        });
+
+        Some(slot_id)
+    }
+
+    pub fn set_depth(&mut self, slot_id: Id, depth: f64) -> bool {
+        self.send_for_slot(
+            slot_id,
+            ModulationCommand::SetDepth(slot_id, depth.clamp(-1.0, 1.0)),
+        )
+    }
+
+    pub fn set_curve(&mut self, slot_id: Id, curve: ModulationCurve) -> bool {
+        self.send_for_slot(slot_id, ModulationCommand::SetCurve(slot_id, curve))
+    }
+
+    pub fn disconnect(&mut self, slot_id: Id) -> bool {
+        let sent = self.send_for_slot(slot_id, ModulationCommand::RemoveSlot(slot_id));
+        self.slots.remove(&slot_id);
+        sent
+    }
+
+    pub fn num_slots(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn set_gate(&mut self, source_id: Id, open: bool) {
+        if let Some(ModulationSource::Envelope { .. }) = self.sources.get(&source_id) {
+            self.send(ModulationCommand::SetGate(source_id, open));
+        }
+    }
+
+    pub fn set_crossfade_time(&mut self, crossfade_time: Duration) {
+        self.send(ModulationCommand::SetCrossfadeTime(crossfade_time));
+    }
+
+    pub fn handle_midi(&mut self, message: &MidiMessage) {
+        let envelope_gate = match message {
+            MidiMessage::NoteOn { .. } => Some(true),
+            MidiMessage::NoteOff { .. } => Some(false),
+            _ => None,
+        };
+
+        let controller = MidiSource::from_message(message);
+
+        let mut commands = Vec::new();
+
+        for (id, source) in self.sources.iter() {
+            match (source, envelope_gate, controller) {
+                (ModulationSource::Envelope { .. }, Some(open), _) => {
+                    commands.push(ModulationCommand::SetGate(*id, open))
+                }
+                (ModulationSource::Midi(midi_source), _, Some((message_source, value)))
+                    if *midi_source == message_source =>
+                {
+                    commands.push(ModulationCommand::SetSourceValue(*id, value))
+                }
+                _ => (),
+            }
+        }
+
+        for command in commands {
+            self.send(command);
+        }
+    }
+
+    fn send_for_slot(&mut self, slot_id: Id, command: ModulationCommand) -> bool {
+        if !self.slots.contains_key(&slot_id) {
+            return false;
+        }
+
+        self.send(command);
+        true
+    }
+
+    fn send(&mut self, command: ModulationCommand) {
+        let _ = self.command_queue.send(Command::Modulation(command));
+    }
+}
+
+impl Drop for ModulationMatrix {
+    fn drop(&mut self) {
+        let source_ids: Vec<Id> = self.sources.keys().copied().collect();
+
+        for source_id in source_ids {
+            self.send(ModulationCommand::RemoveSource(source_id));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands::command_queue::CommandQueue;
+    use approx::assert_relative_eq;
+
+    use super::*;
+
+    #[test]
+    fn curves_preserve_sign_and_end_points() {
+        for curve in [
+            ModulationCurve::Linear,
+            ModulationCurve::Exponential,
+            ModulationCurve::Logarithmic,
+        ] {
+            assert_relative_eq!(curve.apply(1.0), 1.0);
+            assert_relative_eq!(curve.apply(-1.0), -1.0);
+            assert_relative_eq!(curve.apply(0.0), 0.0);
+        }
+
+        assert_relative_eq!(ModulationCurve::Exponential.apply(0.5), 0.25);
+        assert_relative_eq!(ModulationCurve::Logarithmic.apply(-0.25), -0.5);
+    }
+
+    #[test]
+    fn routes_midi_to_matching_sources() {
+        let (command_tx, mut command_rx) = CommandQueue::create();
+        let mut matrix = ModulationMatrix::new(command_tx.clone());
+        let (cutoff, _) = AudioParameter::new(Id::generate(), 1000.0, 20.0, 20000.0, command_tx);
+
+        let wheel = matrix.add_source(ModulationSource::Midi(MidiSource::ControlChange {
+            channel: 0,
+            controller: 1,
+        }));
+        assert!(matrix
+            .connect(wheel, &cutoff, 0.5, ModulationCurve::Linear)
+            .is_some());
+        assert!(matrix
+            .connect(Id::generate(), &cutoff, 0.5, ModulationCurve::Linear)
+            .is_none());
+        while command_rx.recv().is_ok() {}
+
+        matrix.handle_midi(&MidiMessage::ControlChange {
+            channel: 0,
+            controller: 7,
+            value: 127,
+        });
+        assert!(command_rx.recv().is_err());
+
+        matrix.handle_midi(&MidiMessage::ControlChange {
+            channel: 0,
+            controller: 1,
+            value: 127,
+        });
+        match command_rx.recv() {
+            Ok(Command::Modulation(ModulationCommand::SetSourceValue(id, value))) => {
+                assert_eq!(id, wheel);
+                assert_relative_eq!(value, 1.0);
+            }
+            _ => panic!("Expected a source value change"),
+        }
+
+        assert!(matrix.remove_source(wheel));
+        assert_eq!(matrix.num_slots(), 0);
+    }
+}
@@ -0,0 +1,255 @@
+use std::sync::atomic::Ordering;
+#[cfg(feature = "presets")]
+use std::{fs, path::Path};
+
+use crate::timestamp::Timestamp;
+#[cfg(feature = "presets")]
+use crate::utility::preset_format;
+
+use super::audio_parameter::AudioParameter;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum PresetValue {
+    Number(f64),
+    Text(String),
+    Boolean(bool),
+}
+
+impl PresetValue {
+    pub fn as_number(&self) -> Option<f64> {
+        match self {
+            PresetValue::Number(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            PresetValue::Text(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_boolean(&self) -> Option<bool> {
+        match self {
+            PresetValue::Boolean(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum PresetError {
+    Io(String),
+    Parse { line: usize, message: String },
+    UnsupportedFormat,
+    WrongNodeType { expected: String, found: String },
+    InvalidState(String),
+}
+
+#[cfg(feature = "presets")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PresetFormat {
+    Toml,
+    Json,
+}
+
+#[cfg(feature = "presets")]
+impl PresetFormat {
+    pub fn from_path(path: &str) -> Option<Self> {
+        match Path::new(path).extension()?.to_str()? {
+            "toml" => Some(PresetFormat::Toml),
+            "json" => Some(PresetFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NodePreset {
+    node_type: String,
+    name: String,
+    parameters: Vec<(String, f64)>,
+    state: Vec<(String, PresetValue)>,
+}
+
+impl NodePreset {
+    pub fn new(node_type: &str, name: &str) -> Self {
+        Self {
+            node_type: String::from(node_type),
+            name: String::from(name),
+            ..Default::default()
+        }
+    }
+
+    pub fn get_node_type(&self) -> &str {
+        &self.node_type
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn set_name(&mut self, name: &str) {
+        self.name = String::from(name);
+    }
+
+    pub fn with_parameter(mut self, parameter_name: &str, value: f64) -> Self {
+        self.set_parameter(parameter_name, value);
+        self
+    }
+
+    pub fn with_state(mut self, key: &str, value: PresetValue) -> Self {
+        self.set_state(key, value);
+        self
+    }
+
+    pub fn set_parameter(&mut self, parameter_name: &str, value: f64) {
+        match self
+            .parameters
+            .iter_mut()
+            .find(|(name, _)| name == parameter_name)
+        {
+            Some((_, existing)) => *existing = value,
+            None => self.parameters.push((String::from(parameter_name), value)),
+        }
+    }
+
+    pub fn get_parameter(&self, parameter_name: &str) -> Option<f64> {
+        self.parameters
+            .iter()
+            .find(|(name, _)| name == parameter_name)
+            .map(|(_, value)| *value)
+    }
+
+    pub fn parameters(&self) -> &[(String, f64)] {
+        &self.parameters
+    }
+
+    pub fn set_state(&mut self, key: &str, value: PresetValue) {
+        match self.state.iter_mut().find(|(name, _)| name == key) {
+            Some((_, existing)) => *existing = value,
+            None => self.state.push((String::from(key), value)),
+        }
+    }
+
+    pub fn get_state(&self, key: &str) -> Option<&PresetValue> {
+        self.state
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value)
+    }
+
+    pub fn state(&self) -> &[(String, PresetValue)] {
+        &self.state
+    }
+
+    pub fn capture_parameter(&mut self, parameter_name: &str, parameter: &AudioParameter) {
+        self.set_parameter(
+            parameter_name,
+            parameter.get_value().load(Ordering::Acquire),
+        );
+    }
+
+    pub fn apply_parameter(&self, parameter_name: &str, parameter: &mut AudioParameter) {
+        if let Some(value) = self.get_parameter(parameter_name) {
+            parameter.set_value_at_time(value, Timestamp::zero());
+        }
+    }
+
+    pub fn check_node_type(&self, expected: &str) -> Result<(), PresetError> {
+        if self.node_type != expected {
+            return Err(PresetError::WrongNodeType {
+                expected: String::from(expected),
+                found: self.node_type.clone(),
+            });
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "presets")]
+    pub fn to_text(&self, format: PresetFormat) -> String {
+        match format {
+            PresetFormat::Toml => preset_format::write_toml(self),
+            PresetFormat::Json => preset_format::write_json(self),
+        }
+    }
+
+    #[cfg(feature = "presets")]
+    pub fn from_text(text: &str, format: PresetFormat) -> Result<Self, PresetError> {
+        match format {
+            PresetFormat::Toml => preset_format::parse_toml(text),
+            PresetFormat::Json => preset_format::parse_json(text),
+        }
+    }
+
+    #[cfg(feature = "presets")]
+    pub fn write(&self, path: &str) -> Result<(), PresetError> {
+        let format = PresetFormat::from_path(path).ok_or(PresetError::UnsupportedFormat)?;
+        fs::write(path, self.to_text(format)).map_err(|error| PresetError::Io(error.to_string()))
+    }
+
+    #[cfg(feature = "presets")]
+    pub fn read(path: &str) -> Result<Self, PresetError> {
+        let format = PresetFormat::from_path(path).ok_or(PresetError::UnsupportedFormat)?;
+        let text = fs::read_to_string(path).map_err(|error| PresetError::Io(error.to_string()))?;
+        Self::from_text(&text, format)
+    }
+}
+
+pub trait PresetNode {
+    fn preset_type(&self) -> &'static str;
+
+    fn capture_preset(&self, name: &str) -> NodePreset;
+
+    fn apply_preset(&mut self, preset: &NodePreset) -> Result<(), PresetError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands::command_queue::CommandQueue;
+
+    use crate::{commands::command::Command, Gain, LogicNode, LogicOperation};
+
+    use super::*;
+
+    #[test]
+    fn applies_presets_through_the_command_queue() {
+        let (command_tx, mut command_rx) = CommandQueue::create();
+
+        let mut gain = Gain::new(command_tx.clone());
+        let preset = NodePreset::new("gain", "Quiet").with_parameter("gain", 0.25);
+        assert_eq!(gain.apply_preset(&preset), Ok(()));
+
+        let mut applied = None;
+        while let Ok(command) = command_rx.recv() {
+            if let Command::ParameterValueChange(request) = command {
+                applied = Some(request.parameter_id);
+            }
+        }
+        assert_eq!(applied, Some(gain.gain.get_id()));
+
+        let mut logic = LogicNode::new(command_tx, LogicOperation::And, 2);
+        assert_eq!(
+            logic.apply_preset(&preset),
+            Err(PresetError::WrongNodeType {
+                expected: String::from("logic"),
+                found: String::from("gain"),
+            })
+        );
+
+        let three_inputs =
+            NodePreset::new("logic", "Three").with_state("num_inputs", PresetValue::Number(3.0));
+        assert_eq!(logic.apply_preset(&three_inputs), Ok(()));
+        assert_eq!(logic.get_num_inputs(), 3);
+        assert_eq!(logic.capture_preset("Three"), three_inputs);
+
+        let invalid = NodePreset::new("logic", "Invalid")
+            .with_state("num_inputs", PresetValue::Text(String::from("three")));
+        assert_eq!(
+            logic.apply_preset(&invalid),
+            Err(PresetError::InvalidState(String::from("num_inputs")))
+        );
+    }
+}
@@ -1,6 +1,6 @@
-use crate::{commands::id::Id, Timestamp};
+use crate::{commands::id::Id, realtime::processor::MAXIMUM_NUMBER_OF_FRAMES, Timestamp};
 
-use super::{ParameterChange, ParameterValue, ValueChangeMethod};
+use super::{tempo_sync::TempoSync, ParameterChange, ParameterValue, ValueChangeMethod};
 
 use std::sync::atomic::Ordering;
 
@@ -9,13 +9,19 @@ pub struct RealtimeAudioParameter {
     value: ParameterValue,
     parameter_changes: Vec<ParameterChange>,
     last_value: f64,
-    last_change: Timestamp,
+    last_change: ParameterChange,
+    modulation: f64,
+    modulation_range: (f64, f64),
+    tempo_sync: Option<(TempoSync, f64, f64)>,
+    tempo_synced_value: Option<f64>,
+    audio_modulation: Vec<f64>,
+    audio_modulation_start: Timestamp,
+    audio_modulation_sample_rate: usize,
 }
 
 impl RealtimeAudioParameter {
     pub fn new(parameter_id: Id, value: ParameterValue) -> Self {
-        let mut parameter_changes = Vec::new();
-        parameter_changes.reserve(16);
+        let parameter_changes = Vec::with_capacity(16);
 
         let initial_value = value.load(Ordering::Acquire);
 
@@ -23,11 +29,23 @@ impl RealtimeAudioParameter {
             parameter_id,
             value,
             parameter_changes,
-            last_change: Timestamp::default(),
+            last_change: ParameterChange::immediate(initial_value, Timestamp::default()),
             last_value: initial_value,
+            modulation: 0.0,
+            modulation_range: (f64::MIN, f64::MAX),
+            tempo_sync: None,
+            tempo_synced_value: None,
+            audio_modulation: Vec::with_capacity(MAXIMUM_NUMBER_OF_FRAMES),
+            audio_modulation_start: Timestamp::default(),
+            audio_modulation_sample_rate: 0,
         }
     }
 
+    pub fn with_range(mut self, minimum: f64, maximum: f64) -> Self {
+        self.modulation_range = (minimum, maximum);
+        self
+    }
+
     pub fn get_id(&self) -> Id {
         self.parameter_id
     }
@@ -39,8 +57,9 @@ impl RealtimeAudioParameter {
     pub fn set_current_time(&mut self, time: Timestamp) {
         for param_change in self.parameter_changes.iter() {
             if param_change.end_time <= time {
-                self.last_change = param_change.end_time;
-                self.last_value = param_change.value;
+                self.last_value =
+                    Self::start_value(self.last_change, self.last_value, *param_change);
+                self.last_change = *param_change;
             }
         }
 
@@ -50,59 +69,165 @@ impl RealtimeAudioParameter {
             .retain(|param_change| param_change.end_time > time);
     }
 
+    pub fn get_next_immediate_change_between(
+        &self,
+        start_time: &Timestamp,
+        end_time: &Timestamp,
+    ) -> Option<Timestamp> {
+        self.parameter_changes
+            .iter()
+            .filter(|change| change.method == ValueChangeMethod::Immediate)
+            .map(|change| change.end_time)
+            .find(|time| time > start_time && time < end_time)
+    }
+
+    pub fn has_pending_changes(&self) -> bool {
+        !self.parameter_changes.is_empty()
+            || !self.audio_modulation.is_empty()
+            || matches!(self.last_change.method, ValueChangeMethod::Target { .. })
+    }
+
+    pub fn set_modulation(&mut self, offset: f64, minimum: f64, maximum: f64) {
+        self.modulation = offset;
+        self.modulation_range = (minimum, maximum);
+    }
+
+    pub fn clear_audio_modulation(&mut self) {
+        self.audio_modulation.clear();
+    }
+
+    pub fn add_audio_modulation(
+        &mut self,
+        start_time: Timestamp,
+        sample_rate: usize,
+        num_frames: usize,
+        modulation_at_frame: impl Fn(usize) -> f64,
+    ) {
+        if self.audio_modulation.is_empty() {
+            let num_frames = num_frames.min(self.audio_modulation.capacity());
+            self.audio_modulation.resize(num_frames, 0.0);
+            self.audio_modulation_start = start_time;
+            self.audio_modulation_sample_rate = sample_rate;
+        }
+
+        for (frame, value) in self.audio_modulation.iter_mut().enumerate() {
+            *value += modulation_at_frame(frame);
+        }
+    }
+
+    fn audio_modulation_at_time(&self, time: &Timestamp) -> f64 {
+        if self.audio_modulation.is_empty() || *time < self.audio_modulation_start {
+            return 0.0;
+        }
+
+        let frame = (*time - self.audio_modulation_start)
+            .get_samples(self.audio_modulation_sample_rate)
+            .round() as usize;
+
+        self.audio_modulation.get(frame).copied().unwrap_or(0.0)
+    }
+
+    pub fn set_tempo_sync(&mut self, sync: Option<TempoSync>, minimum: f64, maximum: f64) {
+        self.tempo_sync = sync.map(|sync| (sync, minimum, maximum));
+
+        if self.tempo_sync.is_none() {
+            self.tempo_synced_value = None;
+        }
+    }
+
+    pub fn set_tempo(&mut self, beats_per_minute: f64) {
+        if let Some((sync, minimum, maximum)) = self.tempo_sync {
+            self.tempo_synced_value = Some(
+                sync.value_at_tempo(beats_per_minute)
+                    .clamp(minimum, maximum),
+            );
+        }
+    }
+
     pub fn get_value_at_time(&self, time: &Timestamp) -> f64 {
-        let (previous_change, next_change) = self.get_next_parameter_change_after(time);
+        let value = self.get_unmodulated_value_at_time(time);
 
-        if let Some(next_change) = next_change {
-            return Self::get_next_value(previous_change, next_change, time);
+        if self.modulation == 0.0 && self.audio_modulation.is_empty() {
+            return value;
         }
 
-        previous_change.value
+        let (minimum, maximum) = self.modulation_range;
+        (value + self.modulation + self.audio_modulation_at_time(time)).clamp(minimum, maximum)
     }
 
-    fn get_next_parameter_change_after(
-        &self,
-        time: &Timestamp,
-    ) -> (ParameterChange, Option<ParameterChange>) {
-        let mut previous_change = ParameterChange {
-            value: self.last_value,
-            end_time: self.last_change,
-            method: ValueChangeMethod::Immediate,
-        };
+    fn get_unmodulated_value_at_time(&self, time: &Timestamp) -> f64 {
+        if let Some(value) = self.tempo_synced_value {
+            return value;
+        }
 
-        let mut next_change: Option<ParameterChange> = None;
+        let mut previous_change = self.last_change;
+        let mut previous_value = self.last_value;
 
         for change in self.parameter_changes.iter() {
-            if change.end_time <= *time {
-                previous_change = *change;
-            } else {
-                next_change = Some(*change);
-                break;
+            if change.end_time > *time {
+                return Self::get_next_value(previous_change, previous_value, Some(*change), time);
             }
+
+            previous_value = Self::start_value(previous_change, previous_value, *change);
+            previous_change = *change;
         }
 
-        (previous_change, next_change)
+        Self::get_next_value(previous_change, previous_value, None, time)
+    }
+
+    fn start_value(
+        previous_change: ParameterChange,
+        previous_value: f64,
+        change: ParameterChange,
+    ) -> f64 {
+        match change.method {
+            ValueChangeMethod::Target { .. } => Self::get_next_value(
+                previous_change,
+                previous_value,
+                Some(change),
+                &change.end_time,
+            ),
+            _ => change.value,
+        }
     }
 
     fn get_next_value(
         previous_change: ParameterChange,
-        next_change: ParameterChange,
+        previous_value: f64,
+        next_change: Option<ParameterChange>,
         time: &Timestamp,
     ) -> f64 {
-        match next_change.method {
-            ValueChangeMethod::Immediate => {
-                if next_change.end_time <= *time {
-                    next_change.value
-                } else {
-                    previous_change.value
-                }
+        let start_time = previous_change.end_time.get_seconds();
+
+        match next_change {
+            Some(next_change) if next_change.method == ValueChangeMethod::Linear => {
+                let amount = (time.get_seconds() - start_time)
+                    / (next_change.end_time.get_seconds() - start_time);
+                previous_value + (next_change.value - previous_value) * amount
             }
-            ValueChangeMethod::Linear => {
-                let a = (next_change.value - previous_change.value)
-                    / (next_change.end_time.get_seconds() - previous_change.end_time.get_seconds());
-                let b = previous_change.value - a * previous_change.end_time.get_seconds();
-                a * time.get_seconds() + b
+            Some(next_change) if next_change.method == ValueChangeMethod::Exponential => {
+                let amount = (time.get_seconds() - start_time)
+                    / (next_change.end_time.get_seconds() - start_time);
+
+                if previous_value * next_change.value <= 0.0 {
+                    return previous_value + (next_change.value - previous_value) * amount;
+                }
+
+                previous_value * (next_change.value / previous_value).powf(amount)
             }
+            _ => match previous_change.method {
+                ValueChangeMethod::Target { time_constant } => {
+                    if time_constant <= 0.0 {
+                        return previous_change.value;
+                    }
+
+                    let elapsed = (time.get_seconds() - start_time).max(0.0);
+                    previous_change.value
+                        + (previous_value - previous_change.value)
+                            * (-elapsed / time_constant).exp()
+                }
+                _ => previous_value,
+            },
         }
     }
 
@@ -111,10 +236,23 @@ impl RealtimeAudioParameter {
     }
 
     pub fn add_parameter_change(&mut self, parameter_change: ParameterChange) {
-        self.parameter_changes.push(parameter_change);
+        if let Some(existing) = self.parameter_changes.iter_mut().find(|change| {
+            change.end_time == parameter_change.end_time
+                && std::mem::discriminant(&change.method)
+                    == std::mem::discriminant(&parameter_change.method)
+        }) {
+            *existing = parameter_change;
+            return;
+        }
 
-        self.parameter_changes
-            .sort_by(|a, b| a.end_time.partial_cmp(&b.end_time).unwrap());
+        let index = self
+            .parameter_changes
+            .partition_point(|change| change.end_time <= parameter_change.end_time);
+        self.parameter_changes.insert(index, parameter_change);
+    }
+
+    pub fn num_pending_changes(&self) -> usize {
+        self.parameter_changes.len()
     }
 }
 
@@ -123,6 +261,7 @@ mod tests {
     use super::*;
     use approx::assert_relative_eq;
     use atomic_float::AtomicF64;
+    use proptest::prelude::*;
 
     #[test]
     fn immediate_parameter_changes() {
@@ -187,4 +326,281 @@ mod tests {
         assert_relative_eq!(param.get_value_at_time(&Timestamp::from_seconds(2.5)), 2.5);
         assert_relative_eq!(param.get_value_at_time(&Timestamp::from_seconds(3.0)), 3.0);
     }
+
+    #[test]
+    fn exponential_ramps_start_from_previous_value() {
+        let mut param = parameter_with_changes(
+            100.0,
+            &[
+                ParameterChange::linear(200.0, Timestamp::from_seconds(1.0)),
+                ParameterChange::exponential(800.0, Timestamp::from_seconds(3.0)),
+                ParameterChange::exponential(-1.0, Timestamp::from_seconds(4.0)),
+            ],
+        );
+
+        assert_relative_eq!(
+            param.get_value_at_time(&Timestamp::from_seconds(0.5)),
+            150.0
+        );
+        assert_relative_eq!(
+            param.get_value_at_time(&Timestamp::from_seconds(2.0)),
+            400.0
+        );
+        assert_relative_eq!(
+            param.get_value_at_time(&Timestamp::from_seconds(3.0)),
+            800.0
+        );
+        assert_relative_eq!(
+            param.get_value_at_time(&Timestamp::from_seconds(3.5)),
+            399.5
+        );
+
+        param.set_current_time(Timestamp::from_seconds(2.0));
+        assert_relative_eq!(param.get_value(), 400.0);
+        assert_relative_eq!(
+            param.get_value_at_time(&Timestamp::from_seconds(2.5)),
+            565.685424949238
+        );
+    }
+
+    #[test]
+    fn target_approach_continues_until_next_change() {
+        let time_constant = 0.5;
+        let mut param = parameter_with_changes(
+            1.0,
+            &[
+                ParameterChange::target(0.0, Timestamp::from_seconds(1.0), time_constant),
+                ParameterChange::immediate(0.5, Timestamp::from_seconds(3.0)),
+                ParameterChange::target(1.0, Timestamp::from_seconds(4.0), time_constant),
+                ParameterChange::linear(0.0, Timestamp::from_seconds(5.0)),
+            ],
+        );
+
+        let expected = (-0.5 / time_constant).exp();
+        assert_relative_eq!(param.get_value_at_time(&Timestamp::from_seconds(1.0)), 1.0);
+        assert_relative_eq!(
+            param.get_value_at_time(&Timestamp::from_seconds(1.5)),
+            expected
+        );
+        assert_relative_eq!(param.get_value_at_time(&Timestamp::from_seconds(3.0)), 0.5);
+        assert_relative_eq!(param.get_value_at_time(&Timestamp::from_seconds(4.5)), 0.25);
+
+        param.set_current_time(Timestamp::from_seconds(1.5));
+        assert!(param.has_pending_changes());
+        assert_relative_eq!(param.get_value(), expected);
+        assert_relative_eq!(
+            param.get_value_at_time(&Timestamp::from_seconds(2.0)),
+            expected * expected
+        );
+
+        param.set_current_time(Timestamp::from_seconds(6.0));
+        assert!(!param.has_pending_changes());
+        assert_relative_eq!(param.get_value(), 0.0);
+    }
+
+    #[test]
+    fn tempo_synced_value_tracks_tempo() {
+        use crate::parameter::tempo_sync::{NoteDivision, ParameterUnit};
+
+        let id = Id::generate();
+        let value = ParameterValue::new(AtomicF64::new(0.25));
+        let mut param = RealtimeAudioParameter::new(id, value);
+
+        let sync = TempoSync::new(NoteDivision::new(1, 8).dotted(), ParameterUnit::Seconds);
+        param.set_tempo_sync(Some(sync), 0.0, 0.5);
+
+        param.set_tempo(120.0);
+        assert_relative_eq!(param.get_value_at_time(&Timestamp::zero()), 0.375);
+
+        param.set_tempo(60.0);
+        assert_relative_eq!(param.get_value_at_time(&Timestamp::zero()), 0.5);
+
+        param.set_tempo_sync(None, 0.0, 0.5);
+        assert_relative_eq!(param.get_value_at_time(&Timestamp::zero()), 0.25);
+    }
+
+    #[test]
+    fn coalesces_changes_at_the_same_time() {
+        let id = Id::generate();
+        let value = ParameterValue::new(AtomicF64::new(0.0));
+        let mut param = RealtimeAudioParameter::new(id, value);
+
+        for step in 0..100 {
+            param.add_parameter_change(ParameterChange::immediate(
+                step as f64,
+                Timestamp::from_seconds(1.0),
+            ));
+        }
+        param.add_parameter_change(ParameterChange::linear(5.0, Timestamp::from_seconds(1.0)));
+        param.add_parameter_change(ParameterChange::immediate(
+            7.0,
+            Timestamp::from_seconds(0.5),
+        ));
+
+        assert_eq!(param.num_pending_changes(), 3);
+        assert_relative_eq!(param.get_value_at_time(&Timestamp::from_seconds(0.5)), 7.0);
+        assert_relative_eq!(param.get_value_at_time(&Timestamp::from_seconds(1.0)), 5.0);
+
+        param.set_current_time(Timestamp::from_seconds(1.0));
+        assert_eq!(param.num_pending_changes(), 0);
+        assert_relative_eq!(param.get_value(), 5.0);
+    }
+
+    #[test]
+    fn finds_immediate_changes_inside_interval() {
+        let id = Id::generate();
+        let value = ParameterValue::new(AtomicF64::new(0.0));
+        let mut param = RealtimeAudioParameter::new(id, value);
+
+        param.add_parameter_change(ParameterChange::immediate(
+            1.0,
+            Timestamp::from_seconds(1.5),
+        ));
+
+        assert_eq!(
+            param.get_next_immediate_change_between(
+                &Timestamp::from_seconds(1.0),
+                &Timestamp::from_seconds(2.0)
+            ),
+            Some(Timestamp::from_seconds(1.5))
+        );
+        assert_eq!(
+            param.get_next_immediate_change_between(
+                &Timestamp::from_seconds(1.5),
+                &Timestamp::from_seconds(2.0)
+            ),
+            None
+        );
+    }
+
+    const SAMPLE_RATE: usize = 48_000;
+
+    fn parameter_with_changes(
+        initial_value: f64,
+        changes: &[ParameterChange],
+    ) -> RealtimeAudioParameter {
+        let value = ParameterValue::new(AtomicF64::new(initial_value));
+        let mut param = RealtimeAudioParameter::new(Id::generate(), value);
+
+        for change in changes {
+            param.add_parameter_change(*change);
+        }
+
+        param
+    }
+
+    fn change_strategy() -> impl Strategy<Value = Vec<ParameterChange>> {
+        prop::collection::btree_map(1u32..48_000, (-100.0f64..100.0, any::<bool>()), 1..16)
+            .prop_map(|changes| {
+                changes
+                    .into_iter()
+                    .map(|(frame, (value, is_linear))| {
+                        let time = Timestamp::from_samples(frame as f64, SAMPLE_RATE);
+                        if is_linear {
+                            ParameterChange::linear(value, time)
+                        } else {
+                            ParameterChange::immediate(value, time)
+                        }
+                    })
+                    .collect()
+            })
+    }
+
+    fn time_at(frame: u32) -> Timestamp {
+        Timestamp::from_samples(frame as f64, SAMPLE_RATE)
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn linear_ramps_are_monotonic_and_bounded(
+            start in -100.0f64..100.0,
+            end in -100.0f64..100.0,
+            ramp_frames in 1u32..96_000,
+            step in 1u32..512,
+        ) {
+            let param = parameter_with_changes(
+                start,
+                &[ParameterChange::linear(end, time_at(ramp_frames))],
+            );
+
+            let tolerance = 1e-9 * (1.0 + start.abs().max(end.abs()));
+            let direction = (end - start).signum();
+            let mut previous = param.get_value_at_time(&Timestamp::zero());
+            prop_assert!((previous - start).abs() <= tolerance);
+
+            for frame in (step..ramp_frames + 2 * step).step_by(step as usize) {
+                let value = param.get_value_at_time(&time_at(frame));
+
+                prop_assert!(value >= start.min(end) - tolerance);
+                prop_assert!(value <= start.max(end) + tolerance);
+                prop_assert!((value - previous) * direction >= -tolerance);
+
+                previous = value;
+            }
+
+            prop_assert!((param.get_value_at_time(&time_at(ramp_frames)) - end).abs() <= tolerance);
+        }
+
+        #[test]
+        fn values_stay_within_the_targets(
+            initial_value in -100.0f64..100.0,
+            changes in change_strategy(),
+            frames in prop::collection::vec(0u32..50_000, 1..32),
+        ) {
+            let param = parameter_with_changes(initial_value, &changes);
+
+            let targets = changes.iter().map(|change| change.value);
+            let minimum = targets.clone().fold(initial_value, f64::min);
+            let maximum = targets.fold(initial_value, f64::max);
+
+            for frame in frames {
+                let value = param.get_value_at_time(&time_at(frame));
+                prop_assert!(value >= minimum - 1e-9 && value <= maximum + 1e-9);
+            }
+        }
+
+        #[test]
+        fn insertion_order_does_not_change_values(
+            initial_value in -100.0f64..100.0,
+            (changes, shuffled) in change_strategy()
+                .prop_flat_map(|changes| (Just(changes.clone()), Just(changes).prop_shuffle())),
+        ) {
+            let in_order = parameter_with_changes(initial_value, &changes);
+            let out_of_order = parameter_with_changes(initial_value, &shuffled);
+
+            prop_assert_eq!(in_order.num_pending_changes(), out_of_order.num_pending_changes());
+
+            for frame in (0..50_000).step_by(97) {
+                let time = time_at(frame);
+                prop_assert_eq!(
+                    in_order.get_value_at_time(&time),
+                    out_of_order.get_value_at_time(&time)
+                );
+            }
+        }
+
+        #[test]
+        fn advancing_time_does_not_change_values(
+            initial_value in -100.0f64..100.0,
+            changes in change_strategy(),
+            block_size in 1u32..2048,
+        ) {
+            let reference = parameter_with_changes(initial_value, &changes);
+            let mut advanced = parameter_with_changes(initial_value, &changes);
+
+            for block_start in (0..50_000).step_by(block_size as usize) {
+                advanced.set_current_time(time_at(block_start));
+
+                for frame in block_start..(block_start + block_size).min(50_000) {
+                    let time = time_at(frame);
+                    prop_assert_eq!(
+                        reference.get_value_at_time(&time),
+                        advanced.get_value_at_time(&time)
+                    );
+                }
+            }
+        }
+    }
 }
@@ -0,0 +1,197 @@
+use std::{collections::HashMap, sync::atomic::Ordering};
+
+use crate::{commands::id::Id, timestamp::Timestamp};
+
+use super::audio_parameter::AudioParameter;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ParameterSnapshot {
+    values: HashMap<Id, f64>,
+}
+
+impl ParameterSnapshot {
+    pub fn capture(parameters: &[&AudioParameter]) -> Self {
+        Self {
+            values: parameters
+                .iter()
+                .map(|parameter| {
+                    (
+                        parameter.get_id(),
+                        parameter.get_value().load(Ordering::Acquire),
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    pub fn get_value(&self, parameter_id: Id) -> Option<f64> {
+        self.values.get(&parameter_id).copied()
+    }
+
+    pub fn set_value(&mut self, parameter_id: Id, value: f64) {
+        self.values.insert(parameter_id, value);
+    }
+
+    pub fn interpolate(&self, other: &Self, amount_of_other: f64) -> Self {
+        let amount_of_other = amount_of_other.clamp(0.0, 1.0);
+
+        Self {
+            values: self
+                .values
+                .iter()
+                .map(|(id, value)| match other.values.get(id) {
+                    Some(other_value) => (
+                        *id,
+                        (1.0 - amount_of_other) * value + amount_of_other * other_value,
+                    ),
+                    None => (*id, *value),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn apply(&self, parameters: &mut [&mut AudioParameter], at_time: Timestamp) {
+        for parameter in parameters.iter_mut() {
+            if let Some(value) = self.get_value(parameter.get_id()) {
+                parameter.set_value_at_time(value, at_time);
+            }
+        }
+    }
+
+    pub fn ramp_to(&self, parameters: &mut [&mut AudioParameter], end_time: Timestamp) {
+        for parameter in parameters.iter_mut() {
+            if let Some(value) = self.get_value(parameter.get_id()) {
+                parameter.linear_ramp_to_value(value, end_time);
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct SnapshotBank {
+    snapshots: HashMap<String, ParameterSnapshot>,
+}
+
+impl SnapshotBank {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn store(&mut self, name: &str, snapshot: ParameterSnapshot) {
+        self.snapshots.insert(String::from(name), snapshot);
+    }
+
+    pub fn capture(&mut self, name: &str, parameters: &[&AudioParameter]) {
+        self.store(name, ParameterSnapshot::capture(parameters));
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ParameterSnapshot> {
+        self.snapshots.get(name)
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<ParameterSnapshot> {
+        self.snapshots.remove(name)
+    }
+
+    pub fn morph(
+        &self,
+        from: &str,
+        to: &str,
+        parameters: &mut [&mut AudioParameter],
+        start_time: Timestamp,
+        duration: Timestamp,
+    ) -> bool {
+        let (from, to) = match (self.get(from), self.get(to)) {
+            (Some(from), Some(to)) => (from, to),
+            _ => return false,
+        };
+
+        let end_time = start_time.incremented_by_seconds(duration.get_seconds());
+
+        from.apply(parameters, start_time);
+        to.ramp_to(parameters, end_time);
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands::command_queue::CommandQueue;
+    use approx::assert_relative_eq;
+
+    use crate::{
+        commands::command::Command,
+        parameter::{ParameterChange, ValueChangeMethod},
+    };
+
+    use super::*;
+
+    #[test]
+    fn interpolates_between_snapshots() {
+        let id = Id::generate();
+
+        let mut a = ParameterSnapshot::default();
+        a.set_value(id, 0.0);
+
+        let mut b = ParameterSnapshot::default();
+        b.set_value(id, 10.0);
+
+        assert_relative_eq!(a.interpolate(&b, 0.25).get_value(id).unwrap(), 2.5);
+        assert_relative_eq!(a.interpolate(&b, 2.0).get_value(id).unwrap(), 10.0);
+    }
+
+    #[test]
+    fn morph_ramps_all_parameters_over_the_same_interval() {
+        let (command_tx, mut command_rx) = CommandQueue::create();
+        let dsp_id = Id::generate();
+
+        let (mut first, _) = AudioParameter::new(dsp_id, 0.0, -1.0, 1.0, command_tx.clone());
+        let (mut second, _) = AudioParameter::new(dsp_id, 0.5, -1.0, 1.0, command_tx);
+
+        let mut bank = SnapshotBank::new();
+        bank.capture("a", &[&first, &second]);
+
+        let mut b = ParameterSnapshot::default();
+        b.set_value(first.get_id(), 1.0);
+        b.set_value(second.get_id(), -1.0);
+        bank.store("b", b);
+
+        assert!(bank.morph(
+            "a",
+            "b",
+            &mut [&mut first, &mut second],
+            Timestamp::from_seconds(1.0),
+            Timestamp::from_seconds(2.0),
+        ));
+
+        let mut changes = Vec::new();
+        while let Ok(Command::ParameterValueChange(request)) = command_rx.recv() {
+            changes.push(request.change);
+        }
+
+        assert_eq!(changes.len(), 4);
+
+        let ramps: Vec<&ParameterChange> = changes
+            .iter()
+            .filter(|change| change.method == ValueChangeMethod::Linear)
+            .collect();
+
+        assert_eq!(ramps.len(), 2);
+        assert!(ramps
+            .iter()
+            .all(|ramp| ramp.end_time == Timestamp::from_seconds(3.0)));
+    }
+
+    #[test]
+    fn morph_fails_for_unknown_snapshot() {
+        let bank = SnapshotBank::new();
+        assert!(!bank.morph(
+            "a",
+            "b",
+            &mut [],
+            Timestamp::zero(),
+            Timestamp::from_seconds(1.0)
+        ));
+    }
+}
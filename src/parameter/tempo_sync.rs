@@ -0,0 +1,106 @@
+const BEATS_PER_WHOLE_NOTE: f64 = 4.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NoteDivision {
+    beats: f64,
+}
+
+impl NoteDivision {
+    pub fn new(numerator: u32, denominator: u32) -> Self {
+        assert!(numerator > 0 && denominator > 0);
+
+        Self {
+            beats: BEATS_PER_WHOLE_NOTE * numerator as f64 / denominator as f64,
+        }
+    }
+
+    pub fn from_beats(beats: f64) -> Self {
+        assert!(beats > 0.0);
+        Self { beats }
+    }
+
+    pub fn dotted(self) -> Self {
+        Self {
+            beats: self.beats * 1.5,
+        }
+    }
+
+    pub fn triplet(self) -> Self {
+        Self {
+            beats: self.beats * 2.0 / 3.0,
+        }
+    }
+
+    pub fn get_beats(&self) -> f64 {
+        self.beats
+    }
+
+    pub fn seconds_at_tempo(&self, beats_per_minute: f64) -> f64 {
+        self.beats * 60.0 / beats_per_minute
+    }
+
+    pub fn frequency_at_tempo(&self, beats_per_minute: f64) -> f64 {
+        1.0 / self.seconds_at_tempo(beats_per_minute)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParameterUnit {
+    Seconds,
+    Milliseconds,
+    Hertz,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TempoSync {
+    pub division: NoteDivision,
+    pub unit: ParameterUnit,
+}
+
+impl TempoSync {
+    pub fn new(division: NoteDivision, unit: ParameterUnit) -> Self {
+        Self { division, unit }
+    }
+
+    pub fn value_at_tempo(&self, beats_per_minute: f64) -> f64 {
+        match self.unit {
+            ParameterUnit::Seconds => self.division.seconds_at_tempo(beats_per_minute),
+            ParameterUnit::Milliseconds => {
+                1000.0 * self.division.seconds_at_tempo(beats_per_minute)
+            }
+            ParameterUnit::Hertz => self.division.frequency_at_tempo(beats_per_minute),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use super::*;
+
+    #[test]
+    fn converts_note_divisions_to_parameter_units() {
+        let quarter = NoteDivision::new(1, 4);
+        assert_relative_eq!(quarter.get_beats(), 1.0);
+        assert_relative_eq!(NoteDivision::new(1, 8).dotted().get_beats(), 0.75);
+        assert_relative_eq!(NoteDivision::new(1, 4).triplet().get_beats(), 2.0 / 3.0);
+
+        assert_relative_eq!(
+            TempoSync::new(quarter, ParameterUnit::Seconds).value_at_tempo(120.0),
+            0.5
+        );
+        assert_relative_eq!(
+            TempoSync::new(
+                NoteDivision::new(1, 8).dotted(),
+                ParameterUnit::Milliseconds
+            )
+            .value_at_tempo(120.0),
+            375.0
+        );
+        assert_relative_eq!(
+            TempoSync::new(NoteDivision::new(1, 1), ParameterUnit::Hertz).value_at_tempo(60.0),
+            0.25
+        );
+    }
+}
@@ -0,0 +1,200 @@
+use std::{collections::HashMap, sync::Arc};
+
+use atomic_float::AtomicF64;
+
+use crate::{
+    commands::{command::Command, command_queue::CommandQueue, id::Id},
+    dsp::{
+        gain::processor::GainProcessor,
+        sampler::processor::{EventTransmitter, SamplerDspProcess, SamplerEvent},
+    },
+    graph::{connection::Connection, dsp::Dsp},
+    parameter::{realtime_parameter::RealtimeAudioParameter, ParameterValue},
+    timestamp::Timestamp,
+    AudioBuffer, OwnedAudioBuffer,
+};
+
+const CLEANUP_MARGIN_IN_SECONDS: f64 = 0.1;
+
+struct Preview {
+    sampler_id: Id,
+    gain_id: Id,
+    _event_transmitter: EventTransmitter,
+    end_time: Timestamp,
+}
+
+#[derive(Default)]
+pub struct PreviewPlayer {
+    preview: Option<Preview>,
+}
+
+impl PreviewPlayer {
+    pub fn play(
+        &mut self,
+        command_queue: &CommandQueue,
+        sample_rate: usize,
+        sample: OwnedAudioBuffer,
+        gain: f64,
+        current_time: Timestamp,
+    ) {
+        let length_in_seconds = sample.num_frames() as f64 / sample_rate as f64;
+
+        let sampler_id = Id::generate();
+        let gain_id = Id::generate();
+        let gain_parameter_id = Id::generate();
+
+        let (mut event_transmitter, event_receiver) = lockfree::channel::spsc::create();
+        let sampler = SamplerDspProcess::new(sample_rate, Arc::new(sample), event_receiver);
+        let _ = event_transmitter.send(SamplerEvent::start_now());
+
+        let mut parameters = HashMap::new();
+        parameters.insert(
+            gain_parameter_id,
+            RealtimeAudioParameter::new(
+                gain_parameter_id,
+                ParameterValue::new(AtomicF64::new(gain)),
+            ),
+        );
+
+        let commands = [
+            Command::AddDsp(Box::new(Dsp::new(
+                sampler_id,
+                Box::new(sampler),
+                HashMap::new(),
+            ))),
+            Command::AddDsp(Box::new(Dsp::new(
+                gain_id,
+                Box::new(GainProcessor::new(gain_parameter_id)),
+                parameters,
+            ))),
+            Command::AddConnection(Connection::new(sampler_id, gain_id)),
+            Command::ConnectOutputMix(gain_id),
+        ];
+
+        for command in commands {
+            let _ = command_queue.send_validated(command);
+        }
+
+        self.preview = Some(Preview {
+            sampler_id,
+            gain_id,
+            _event_transmitter: event_transmitter,
+            end_time: current_time
+                .incremented_by_seconds(length_in_seconds + CLEANUP_MARGIN_IN_SECONDS),
+        });
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.preview.is_some()
+    }
+
+    pub fn is_finished(&self, current_time: Timestamp) -> bool {
+        self.preview
+            .as_ref()
+            .map(|preview| preview.end_time <= current_time)
+            .unwrap_or(false)
+    }
+
+    pub fn stop(&mut self, command_queue: &CommandQueue) {
+        if let Some(preview) = self.preview.take() {
+            let commands = [
+                Command::DisconnectOutputMix(preview.gain_id),
+                Command::RemoveConnection(Connection::new(preview.sampler_id, preview.gain_id)),
+                Command::RemoveDsp(preview.sampler_id),
+                Command::RemoveDsp(preview.gain_id),
+            ];
+
+            for command in commands {
+                let _ = command_queue.send_validated(command);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        buffer::sample_location::SampleLocation, AudioBuffer, ConstantSource, Context,
+        OwnedAudioBuffer, Timestamp,
+    };
+
+    use super::PreviewPlayer;
+
+    #[test]
+    fn plays_sample_then_cleans_up() {
+        let sample_rate = 1000;
+        let mut context = Context::new(sample_rate);
+        let mut audio_process = context.get_audio_process();
+
+        let mut sample = crate::OwnedAudioBuffer::new(100, 1, sample_rate);
+        sample.fill_with_value(0.5);
+
+        context.start();
+        context.preview_sample(sample, 1.0);
+        assert!(context.is_previewing());
+
+        let mut output_buffer = crate::OwnedAudioBuffer::new(100, 1, sample_rate);
+        audio_process.process(&mut output_buffer);
+        assert!(output_buffer.get_sample(SampleLocation::new(0, 90)) > 0.0);
+
+        for _ in 0..10 {
+            audio_process.process(&mut output_buffer);
+        }
+
+        context.process_notifications();
+        assert!(!context.is_previewing());
+
+        audio_process.process(&mut output_buffer);
+        assert_eq!(output_buffer.get_sample(SampleLocation::new(0, 50)), 0.0);
+    }
+
+    #[test]
+    fn mixes_into_the_output_stage_without_touching_the_journal() {
+        let sample_rate = 1000;
+        let mut context = Context::new(sample_rate);
+        let mut audio_process = context.get_audio_process();
+
+        let mut sample = OwnedAudioBuffer::new(100, 1, sample_rate);
+        sample.fill_with_value(0.5);
+
+        let source = context
+            .add_node(ConstantSource::new(context.get_command_queue(), 0.0))
+            .unwrap();
+        context.connect_node_to_output(&source).unwrap();
+        context.clear_journal();
+        context.start();
+
+        context.preview_sample(sample, 1.0);
+        assert!(!context.can_undo());
+
+        let mut output_buffer = OwnedAudioBuffer::new(100, 1, sample_rate);
+        audio_process.process(&mut output_buffer);
+        assert_eq!(output_buffer.get_sample(SampleLocation::new(0, 50)), 0.5);
+
+        context.stop_preview();
+        assert!(!context.can_undo());
+    }
+
+    #[test]
+    fn stop_removes_both_dsps() {
+        let context = Context::new(1000);
+        let command_queue = context.get_command_queue();
+        let mut player = PreviewPlayer::default();
+
+        player.play(
+            &command_queue,
+            1000,
+            OwnedAudioBuffer::new(100, 1, 1000),
+            1.0,
+            Timestamp::zero(),
+        );
+        let preview = player.preview.as_ref().unwrap();
+        let (sampler_id, gain_id) = (preview.sampler_id, preview.gain_id);
+        assert!(command_queue.is_connected(sampler_id, gain_id));
+
+        player.stop(&command_queue);
+        assert!(!command_queue.contains(sampler_id));
+        assert!(!command_queue.contains(gain_id));
+        assert!(!command_queue.is_connected(sampler_id, gain_id));
+    }
+}
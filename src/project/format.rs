@@ -0,0 +1,470 @@
+use std::{fmt::Write, iter::Peekable, str::Chars};
+
+use crate::parameter::{
+    automation::{AutomationCurve, AutomationPoint},
+    preset::PresetValue,
+};
+
+use super::model::{
+    AutomationTarget, Project, ProjectClip, ProjectError, ProjectTrack, TrackAutomation,
+};
+
+#[derive(Clone, Copy, PartialEq)]
+enum Section {
+    Root,
+    TempoChange,
+    Track,
+    Clip,
+    Automation,
+    AutomationPoint,
+}
+
+fn parse_error(line: usize, message: &str) -> ProjectError {
+    ProjectError::Parse {
+        line,
+        message: String::from(message),
+    }
+}
+
+fn quote(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+
+    for character in text.chars() {
+        match character {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            character => quoted.push(character),
+        }
+    }
+
+    quoted.push('"');
+    quoted
+}
+
+fn format_value(value: &PresetValue) -> String {
+    match value {
+        PresetValue::Number(number) => format!("{:?}", number),
+        PresetValue::Text(text) => quote(text),
+        PresetValue::Boolean(boolean) => boolean.to_string(),
+    }
+}
+
+struct Reader<'a> {
+    characters: Peekable<Chars<'a>>,
+    line: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(text: &'a str) -> Self {
+        Self {
+            characters: text.chars().peekable(),
+            line: 1,
+        }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.characters.peek().copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let character = self.characters.next();
+        if character == Some('\n') {
+            self.line += 1;
+        }
+        character
+    }
+
+    fn error(&self, message: &str) -> ProjectError {
+        parse_error(self.line, message)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.next();
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), ProjectError> {
+        self.skip_whitespace();
+        match self.next() {
+            Some(character) if character == expected => Ok(()),
+            _ => Err(self.error(&format!("Expected '{}'", expected))),
+        }
+    }
+
+    fn read_string(&mut self) -> Result<String, ProjectError> {
+        self.expect('"')?;
+        let mut text = String::new();
+
+        loop {
+            match self.next() {
+                Some('"') => return Ok(text),
+                Some('\\') => match self.next() {
+                    Some('"') => text.push('"'),
+                    Some('\\') => text.push('\\'),
+                    Some('/') => text.push('/'),
+                    Some('n') => text.push('\n'),
+                    Some('t') => text.push('\t'),
+                    _ => return Err(self.error("Unsupported escape sequence")),
+                },
+                Some('\n') | None => return Err(self.error("Unterminated string")),
+                Some(character) => text.push(character),
+            }
+        }
+    }
+
+    fn read_word(&mut self) -> String {
+        let mut word = String::new();
+
+        while let Some(character) = self.peek() {
+            if character.is_ascii_alphanumeric() || "+-._".contains(character) {
+                word.push(character);
+                self.next();
+            } else {
+                break;
+            }
+        }
+
+        word
+    }
+
+    fn read_value(&mut self) -> Result<PresetValue, ProjectError> {
+        self.skip_whitespace();
+
+        if self.peek() == Some('"') {
+            return Ok(PresetValue::Text(self.read_string()?));
+        }
+
+        match self.read_word().as_str() {
+            "true" => Ok(PresetValue::Boolean(true)),
+            "false" => Ok(PresetValue::Boolean(false)),
+            word => word
+                .replace('_', "")
+                .parse::<f64>()
+                .map(PresetValue::Number)
+                .map_err(|_| self.error("Expected a number, string or boolean")),
+        }
+    }
+}
+
+fn parse_key_value(line: &str, line_number: usize) -> Result<(String, PresetValue), ProjectError> {
+    let mut reader = Reader::new(line);
+    reader.line = line_number;
+
+    let key = if reader.peek() == Some('"') {
+        reader.read_string()?
+    } else {
+        reader.read_word()
+    };
+
+    if key.is_empty() {
+        return Err(parse_error(line_number, "Expected a key"));
+    }
+
+    reader.expect('=')?;
+    let value = reader.read_value()?;
+
+    reader.skip_whitespace();
+    if reader.peek().is_some_and(|character| character != '#') {
+        return Err(parse_error(line_number, "Unexpected trailing characters"));
+    }
+
+    Ok((key, value))
+}
+
+fn target_name(target: AutomationTarget) -> &'static str {
+    match target {
+        AutomationTarget::Gain => "gain",
+        AutomationTarget::Pan => "pan",
+    }
+}
+
+fn curve_name(curve: AutomationCurve) -> &'static str {
+    match curve {
+        AutomationCurve::Step => "step",
+        AutomationCurve::Linear => "linear",
+    }
+}
+
+pub fn write_project(project: &Project) -> String {
+    let mut text = String::new();
+
+    let _ = writeln!(text, "name = {}", quote(&project.name));
+    let _ = writeln!(text, "sample_rate = {}", project.sample_rate);
+    let _ = writeln!(text, "tempo = {:?}", project.tempo);
+    if let Some(length_beats) = project.length_beats {
+        let _ = writeln!(text, "length_beats = {:?}", length_beats);
+    }
+
+    for (beat, tempo) in project.tempo_changes.iter() {
+        let _ = writeln!(text, "\n[[tempo_change]]");
+        let _ = writeln!(text, "beat = {:?}\ntempo = {:?}", beat, tempo);
+    }
+
+    for track in project.tracks.iter() {
+        let _ = writeln!(text, "\n[[track]]");
+        let _ = writeln!(text, "name = {}", quote(&track.name));
+        let _ = writeln!(text, "gain_db = {:?}", track.gain_db);
+        let _ = writeln!(text, "pan = {:?}", track.pan);
+        let _ = writeln!(text, "muted = {}", track.muted);
+        let _ = writeln!(text, "soloed = {}", track.soloed);
+        let _ = writeln!(text, "solo_safe = {}", track.solo_safe);
+        if let Some(output) = &track.output {
+            let _ = writeln!(text, "output = {}", quote(output));
+        }
+
+        for clip in track.clips.iter() {
+            let _ = writeln!(text, "\n[[track.clip]]");
+            let _ = writeln!(text, "file = {}", quote(&clip.file));
+            let _ = writeln!(text, "start_beat = {:?}", clip.start_beat);
+            let _ = writeln!(text, "gain_db = {:?}", clip.gain_db);
+        }
+
+        for automation in track.automation.iter() {
+            let _ = writeln!(text, "\n[[track.automation]]");
+            let _ = writeln!(text, "target = {}", quote(target_name(automation.target)));
+
+            for point in automation.points.iter() {
+                let _ = writeln!(text, "\n[[track.automation.point]]");
+                let _ = writeln!(text, "beat = {:?}", point.beat);
+                let _ = writeln!(text, "value = {:?}", point.value);
+                let _ = writeln!(text, "curve = {}", quote(curve_name(point.curve)));
+            }
+        }
+    }
+
+    text
+}
+
+fn current_track(project: &mut Project, line: usize) -> Result<&mut ProjectTrack, ProjectError> {
+    project
+        .tracks
+        .last_mut()
+        .ok_or_else(|| parse_error(line, "Section must follow a [[track]]"))
+}
+
+fn current_automation(
+    project: &mut Project,
+    line: usize,
+) -> Result<&mut TrackAutomation, ProjectError> {
+    current_track(project, line)?
+        .automation
+        .last_mut()
+        .ok_or_else(|| parse_error(line, "Section must follow a [[track.automation]]"))
+}
+
+fn begin_section(
+    project: &mut Project,
+    section: &str,
+    line: usize,
+) -> Result<Section, ProjectError> {
+    match section {
+        "[[tempo_change]]" => {
+            project.tempo_changes.push((0.0, project.tempo));
+            Ok(Section::TempoChange)
+        }
+        "[[track]]" => {
+            project.tracks.push(ProjectTrack::new(""));
+            Ok(Section::Track)
+        }
+        "[[track.clip]]" => {
+            current_track(project, line)?
+                .clips
+                .push(ProjectClip::new("", 0.0));
+            Ok(Section::Clip)
+        }
+        "[[track.automation]]" => {
+            current_track(project, line)?
+                .automation
+                .push(TrackAutomation::new(AutomationTarget::Gain));
+            Ok(Section::Automation)
+        }
+        "[[track.automation.point]]" => {
+            current_automation(project, line)?
+                .points
+                .push(AutomationPoint::new(0.0, 0.0, AutomationCurve::Linear));
+            Ok(Section::AutomationPoint)
+        }
+        _ => Err(parse_error(line, "Unknown section")),
+    }
+}
+
+fn assign(
+    project: &mut Project,
+    section: Section,
+    key: &str,
+    value: PresetValue,
+    line: usize,
+) -> Result<(), ProjectError> {
+    let unexpected = || parse_error(line, &format!("Unexpected key or value for '{}'", key));
+
+    match (section, key, value) {
+        (Section::Root, "name", PresetValue::Text(name)) => project.name = name,
+        (Section::Root, "sample_rate", PresetValue::Number(rate)) if rate >= 0.0 => {
+            project.sample_rate = rate as usize
+        }
+        (Section::Root, "tempo", PresetValue::Number(tempo)) => project.tempo = tempo,
+        (Section::Root, "length_beats", PresetValue::Number(beats)) => {
+            project.length_beats = Some(beats)
+        }
+        (Section::TempoChange, key, PresetValue::Number(number)) => {
+            let change = project.tempo_changes.last_mut().ok_or_else(unexpected)?;
+            match key {
+                "beat" => change.0 = number,
+                "tempo" => change.1 = number,
+                _ => return Err(unexpected()),
+            }
+        }
+        (Section::Track, key, value) => {
+            let track = current_track(project, line)?;
+            match (key, value) {
+                ("name", PresetValue::Text(name)) => track.name = name,
+                ("gain_db", PresetValue::Number(gain_db)) => track.gain_db = gain_db,
+                ("pan", PresetValue::Number(pan)) => track.pan = pan,
+                ("muted", PresetValue::Boolean(muted)) => track.muted = muted,
+                ("soloed", PresetValue::Boolean(soloed)) => track.soloed = soloed,
+                ("solo_safe", PresetValue::Boolean(solo_safe)) => track.solo_safe = solo_safe,
+                ("output", PresetValue::Text(output)) => track.output = Some(output),
+                _ => return Err(unexpected()),
+            }
+        }
+        (Section::Clip, key, value) => {
+            let clip = current_track(project, line)?
+                .clips
+                .last_mut()
+                .ok_or_else(unexpected)?;
+            match (key, value) {
+                ("file", PresetValue::Text(file)) => clip.file = file,
+                ("start_beat", PresetValue::Number(beat)) => clip.start_beat = beat,
+                ("gain_db", PresetValue::Number(gain_db)) => clip.gain_db = gain_db,
+                _ => return Err(unexpected()),
+            }
+        }
+        (Section::Automation, "target", PresetValue::Text(target)) => {
+            current_automation(project, line)?.target = match target.as_str() {
+                "gain" => AutomationTarget::Gain,
+                "pan" => AutomationTarget::Pan,
+                _ => return Err(parse_error(line, "Automation target must be gain or pan")),
+            }
+        }
+        (Section::AutomationPoint, key, value) => {
+            let point = current_automation(project, line)?
+                .points
+                .last_mut()
+                .ok_or_else(unexpected)?;
+            match (key, value) {
+                ("beat", PresetValue::Number(beat)) => point.beat = beat,
+                ("value", PresetValue::Number(number)) => point.value = number,
+                ("curve", PresetValue::Text(curve)) => {
+                    point.curve = match curve.as_str() {
+                        "step" => AutomationCurve::Step,
+                        "linear" => AutomationCurve::Linear,
+                        _ => return Err(parse_error(line, "Curve must be step or linear")),
+                    }
+                }
+                _ => return Err(unexpected()),
+            }
+        }
+        (_, _, value) => {
+            return Err(parse_error(
+                line,
+                &format!("Unexpected key '{}' = {}", key, format_value(&value)),
+            ))
+        }
+    }
+
+    Ok(())
+}
+
+pub fn parse_project(text: &str) -> Result<Project, ProjectError> {
+    let mut project = Project::default();
+    let mut section = Section::Root;
+
+    for (index, line) in text.lines().enumerate() {
+        let line_number = index + 1;
+        let trimmed = line.trim();
+
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        if trimmed.starts_with('[') {
+            section = begin_section(&mut project, trimmed, line_number)?;
+            continue;
+        }
+
+        let (key, value) = parse_key_value(trimmed, line_number)?;
+        assign(&mut project, section, &key, value, line_number)?;
+    }
+
+    Ok(project)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project() -> Project {
+        let mut project = Project::new("Demo \"Mix\"", 44_100, 96.0)
+            .with_track(
+                ProjectTrack::new("Drums")
+                    .with_output("Music")
+                    .with_clip(ProjectClip::new("stems/drums.wav", 0.0))
+                    .with_clip(ProjectClip::new("stems/fill.wav", 15.5))
+                    .with_automation(
+                        TrackAutomation::new(AutomationTarget::Pan)
+                            .with_point(AutomationPoint::new(0.0, -0.5, AutomationCurve::Step))
+                            .with_point(AutomationPoint::new(8.0, 0.5, AutomationCurve::Linear)),
+                    ),
+            )
+            .with_track(ProjectTrack::new("Music"));
+        project.tempo_changes.push((16.0, 128.0));
+        project.length_beats = Some(32.0);
+        project.tracks[0].muted = true;
+        project.tracks[0].soloed = true;
+        project.tracks[1].solo_safe = true;
+        project.tracks[0].clips[1].gain_db = -3.0;
+        project.tracks[1].gain_db = -6.0;
+        project
+    }
+
+    #[test]
+    fn round_trips_projects() {
+        let project = project();
+        let parsed = parse_project(&write_project(&project)).unwrap();
+        assert_eq!(parsed, project);
+    }
+
+    #[test]
+    fn parses_hand_written_projects() {
+        let text = "name = \"Sketch\"\ntempo = 100\n\n[[track]]\nname = \"Keys\"\n\n[[track.clip]]\nfile = \"keys.wav\" # first take\nstart_beat = 4\n";
+        let project = parse_project(text).unwrap();
+
+        assert_eq!(project.sample_rate, 48_000);
+        assert_eq!(project.tempo, 100.0);
+        assert_eq!(project.tracks[0].name, "Keys");
+        assert_eq!(
+            project.tracks[0].clips,
+            vec![ProjectClip::new("keys.wav", 4.0)]
+        );
+    }
+
+    #[test]
+    fn reports_errors_with_line_numbers() {
+        assert!(matches!(
+            parse_project("name = \"Sketch\"\n[[track.clip]]\n"),
+            Err(ProjectError::Parse { line: 2, .. })
+        ));
+        assert!(matches!(
+            parse_project("[[track]]\nname = \"Keys\"\nmuted = 1\n"),
+            Err(ProjectError::Parse { line: 3, .. })
+        ));
+        assert!(matches!(
+            parse_project("[[track]]\n[[track.automation]]\ntarget = \"pitch\"\n"),
+            Err(ProjectError::Parse { line: 3, .. })
+        ));
+    }
+}
@@ -0,0 +1,3 @@
+pub(crate) mod format;
+pub(crate) mod model;
+pub(crate) mod renderer;
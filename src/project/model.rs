@@ -0,0 +1,338 @@
+use std::fs;
+
+use crate::{
+    parameter::automation::AutomationPoint, tempo_map::TempoMap,
+    utility::audio_file::AudioFileError,
+};
+
+use super::format;
+
+#[derive(Clone, Debug)]
+pub enum ProjectError {
+    Io(String),
+    Parse { line: usize, message: String },
+    InvalidProject(String),
+    AudioFile(String, AudioFileError),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AutomationTarget {
+    Gain,
+    Pan,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TrackAutomation {
+    pub target: AutomationTarget,
+    pub points: Vec<AutomationPoint>,
+}
+
+impl TrackAutomation {
+    pub fn new(target: AutomationTarget) -> Self {
+        Self {
+            target,
+            points: Vec::new(),
+        }
+    }
+
+    pub fn with_point(mut self, point: AutomationPoint) -> Self {
+        self.points.push(point);
+        self
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProjectClip {
+    pub file: String,
+    pub start_beat: f64,
+    pub gain_db: f64,
+}
+
+impl ProjectClip {
+    pub fn new(file: &str, start_beat: f64) -> Self {
+        Self {
+            file: String::from(file),
+            start_beat,
+            gain_db: 0.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProjectTrack {
+    pub name: String,
+    pub gain_db: f64,
+    pub pan: f64,
+    pub muted: bool,
+    pub soloed: bool,
+    pub solo_safe: bool,
+    pub output: Option<String>,
+    pub clips: Vec<ProjectClip>,
+    pub automation: Vec<TrackAutomation>,
+}
+
+impl ProjectTrack {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: String::from(name),
+            gain_db: 0.0,
+            pan: 0.0,
+            muted: false,
+            soloed: false,
+            solo_safe: false,
+            output: None,
+            clips: Vec::new(),
+            automation: Vec::new(),
+        }
+    }
+
+    pub fn with_output(mut self, output: &str) -> Self {
+        self.output = Some(String::from(output));
+        self
+    }
+
+    pub fn with_clip(mut self, clip: ProjectClip) -> Self {
+        self.clips.push(clip);
+        self
+    }
+
+    pub fn with_automation(mut self, automation: TrackAutomation) -> Self {
+        self.automation.push(automation);
+        self
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Project {
+    pub name: String,
+    pub sample_rate: usize,
+    pub tempo: f64,
+    pub tempo_changes: Vec<(f64, f64)>,
+    pub length_beats: Option<f64>,
+    pub tracks: Vec<ProjectTrack>,
+}
+
+impl Default for Project {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            sample_rate: 48_000,
+            tempo: 120.0,
+            tempo_changes: Vec::new(),
+            length_beats: None,
+            tracks: Vec::new(),
+        }
+    }
+}
+
+impl Project {
+    pub fn new(name: &str, sample_rate: usize, tempo: f64) -> Self {
+        Self {
+            name: String::from(name),
+            sample_rate,
+            tempo,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_track(mut self, track: ProjectTrack) -> Self {
+        self.tracks.push(track);
+        self
+    }
+
+    pub fn load(path: &str) -> Result<Self, ProjectError> {
+        let text = fs::read_to_string(path).map_err(|error| ProjectError::Io(error.to_string()))?;
+        Self::from_toml(&text)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), ProjectError> {
+        fs::write(path, self.to_toml()).map_err(|error| ProjectError::Io(error.to_string()))
+    }
+
+    pub fn from_toml(text: &str) -> Result<Self, ProjectError> {
+        let project = format::parse_project(text)?;
+        project.validate()?;
+        Ok(project)
+    }
+
+    pub fn to_toml(&self) -> String {
+        format::write_project(self)
+    }
+
+    pub fn tempo_map(&self) -> TempoMap {
+        let mut tempo_map = TempoMap::new(self.tempo);
+        for (beat, beats_per_minute) in self.tempo_changes.iter() {
+            tempo_map.add_tempo_change(*beat, *beats_per_minute);
+        }
+        tempo_map
+    }
+
+    pub fn find_track(&self, name: &str) -> Option<&ProjectTrack> {
+        self.tracks.iter().find(|track| track.name == name)
+    }
+
+    fn routes_through(&self, track: &ProjectTrack, name: &str) -> bool {
+        let mut current = track;
+        for _ in 0..self.tracks.len() {
+            if current.name == name {
+                return true;
+            }
+
+            match current
+                .output
+                .as_deref()
+                .and_then(|output| self.find_track(output))
+            {
+                Some(next) => current = next,
+                None => break,
+            }
+        }
+
+        false
+    }
+
+    pub fn is_audible(&self, track: &ProjectTrack) -> bool {
+        if track.muted {
+            return false;
+        }
+
+        if !self.tracks.iter().any(|other| other.soloed) {
+            return true;
+        }
+
+        track.solo_safe
+            || self.tracks.iter().any(|other| {
+                (other.soloed && self.routes_through(track, &other.name))
+                    || ((other.soloed || other.solo_safe)
+                        && self.routes_through(other, &track.name))
+            })
+    }
+
+    pub fn validate(&self) -> Result<(), ProjectError> {
+        let invalid = |message: String| Err(ProjectError::InvalidProject(message));
+
+        if self.sample_rate == 0 {
+            return invalid(String::from("Sample rate must be positive"));
+        }
+
+        if self.tempo <= 0.0 || self.tempo_changes.iter().any(|(_, tempo)| *tempo <= 0.0) {
+            return invalid(String::from("Tempo must be positive"));
+        }
+
+        for (index, track) in self.tracks.iter().enumerate() {
+            if track.name.is_empty() {
+                return invalid(format!("Track {} has no name", index + 1));
+            }
+
+            if self.tracks[..index]
+                .iter()
+                .any(|other| other.name == track.name)
+            {
+                return invalid(format!("Duplicate track name '{}'", track.name));
+            }
+
+            if track.clips.iter().any(|clip| clip.start_beat < 0.0) {
+                return invalid(format!("Clip before the start of '{}'", track.name));
+            }
+
+            if let Some(output) = &track.output {
+                if self.find_track(output).is_none() {
+                    return invalid(format!(
+                        "Track '{}' routes to unknown track '{}'",
+                        track.name, output
+                    ));
+                }
+            }
+
+            let mut current = track;
+            for _ in 0..self.tracks.len() {
+                match current
+                    .output
+                    .as_deref()
+                    .and_then(|output| self.find_track(output))
+                {
+                    Some(next) => current = next,
+                    None => break,
+                }
+            }
+
+            if current.output.is_some() {
+                return invalid(format!("Routing loop through '{}'", track.name));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn invalid_message(project: &Project) -> String {
+        match project.validate() {
+            Err(ProjectError::InvalidProject(message)) => message,
+            result => panic!("Expected an invalid project, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn validates_routing() {
+        let project = Project::new("Mix", 48_000, 120.0)
+            .with_track(ProjectTrack::new("Drums").with_output("Music"))
+            .with_track(ProjectTrack::new("Music"));
+        assert!(project.validate().is_ok());
+
+        let project = Project::new("Mix", 48_000, 120.0)
+            .with_track(ProjectTrack::new("Drums").with_output("Bus"));
+        assert!(invalid_message(&project).contains("unknown track"));
+
+        let project = Project::new("Mix", 48_000, 120.0)
+            .with_track(ProjectTrack::new("A").with_output("B"))
+            .with_track(ProjectTrack::new("B").with_output("A"));
+        assert!(invalid_message(&project).contains("Routing loop"));
+
+        let project = Project::new("Mix", 48_000, 120.0)
+            .with_track(ProjectTrack::new("A"))
+            .with_track(ProjectTrack::new("A"));
+        assert!(invalid_message(&project).contains("Duplicate"));
+    }
+
+    #[test]
+    fn resolves_solo_in_place_with_solo_safe_returns() {
+        let mut project = Project::new("Mix", 48_000, 120.0)
+            .with_track(ProjectTrack::new("Kick").with_output("Drums"))
+            .with_track(ProjectTrack::new("Snare").with_output("Drums"))
+            .with_track(ProjectTrack::new("Drums").with_output("Music"))
+            .with_track(ProjectTrack::new("Reverb").with_output("Music"))
+            .with_track(ProjectTrack::new("Keys").with_output("Music"))
+            .with_track(ProjectTrack::new("Music"));
+
+        fn audible(project: &Project) -> Vec<&str> {
+            project
+                .tracks
+                .iter()
+                .filter(|track| project.is_audible(track))
+                .map(|track| track.name.as_str())
+                .collect()
+        }
+
+        assert_eq!(audible(&project).len(), 6);
+
+        project.tracks[1].soloed = true;
+        assert_eq!(audible(&project), vec!["Snare", "Drums", "Music"]);
+
+        project.tracks[3].solo_safe = true;
+        assert_eq!(audible(&project), vec!["Snare", "Drums", "Reverb", "Music"]);
+
+        project.tracks[1].soloed = false;
+        project.tracks[2].soloed = true;
+        assert_eq!(
+            audible(&project),
+            vec!["Kick", "Snare", "Drums", "Reverb", "Music"]
+        );
+
+        project.tracks[0].muted = true;
+        assert_eq!(audible(&project), vec!["Snare", "Drums", "Reverb", "Music"]);
+    }
+}
@@ -0,0 +1,636 @@
+use std::{collections::HashMap, path::Path, sync::Arc, time::Duration};
+
+use crate::{
+    buffer::{
+        audio_buffer::AudioBuffer, audio_buffer_slice::AudioBufferSlice,
+        owned_audio_buffer::OwnedAudioBuffer, sample_location::SampleLocation,
+    },
+    context::Context,
+    dsp::{
+        emitter::{attributes::OPEN_CUTOFF, node::EmitterNode},
+        gain::node::GainNode,
+        recorder::node::RecorderNode,
+        sampler::{node::SamplerNode, processor::SharedSample},
+    },
+    graph::{connection::Connection, node::Node, render_quality::RenderQuality},
+    parameter::automation::AutomationLane,
+    tempo_map::TempoMap,
+    timestamp::Timestamp,
+    utility::{
+        audio_file::{read_audio_file, write_audio_file, ExportFormat},
+        level::Level,
+        loudness::{normalize_loudness, LoudnessTarget},
+    },
+};
+
+use super::model::{AutomationTarget, Project, ProjectError, ProjectTrack};
+
+const NUM_CHANNELS: usize = 2;
+const BLOCK_SIZE: usize = 512;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum RenderTail {
+    #[default]
+    Truncate,
+    UntilSilent {
+        threshold_db: f64,
+        hold: Duration,
+        max_tail: Duration,
+    },
+}
+
+impl RenderTail {
+    fn max_tail(&self) -> Duration {
+        match self {
+            RenderTail::Truncate => Duration::ZERO,
+            RenderTail::UntilSilent { max_tail, .. } => *max_tail,
+        }
+    }
+}
+
+struct TrackStrip {
+    strip: EmitterNode,
+    _clips: Vec<(SamplerNode, GainNode)>,
+}
+
+struct ClipSource {
+    sample: SharedSample,
+    start_seconds: f64,
+    gain_db: f64,
+}
+
+fn graph_error(error: impl std::fmt::Debug) -> ProjectError {
+    ProjectError::InvalidProject(format!("Failed to build the render graph: {:?}", error))
+}
+
+fn resolve(base_dir: &Path, file: &str) -> String {
+    base_dir.join(file).to_string_lossy().into_owned()
+}
+
+impl Project {
+    fn load_clips(
+        &self,
+        base_dir: &Path,
+        tempo_map: &TempoMap,
+    ) -> Result<Vec<Vec<ClipSource>>, ProjectError> {
+        let mut cache: HashMap<String, SharedSample> = HashMap::new();
+
+        self.tracks
+            .iter()
+            .map(|track| {
+                track
+                    .clips
+                    .iter()
+                    .map(|clip| {
+                        let path = resolve(base_dir, &clip.file);
+
+                        let sample = match cache.get(&path) {
+                            Some(sample) => sample.clone(),
+                            None => {
+                                let buffer = read_audio_file(&path).map_err(|error| {
+                                    ProjectError::AudioFile(path.clone(), error)
+                                })?;
+
+                                if buffer.sample_rate() != self.sample_rate {
+                                    return Err(ProjectError::InvalidProject(format!(
+                                        "'{}' is {} Hz but the project is {} Hz",
+                                        clip.file,
+                                        buffer.sample_rate(),
+                                        self.sample_rate
+                                    )));
+                                }
+
+                                let sample: SharedSample = Arc::new(buffer);
+                                cache.insert(path, sample.clone());
+                                sample
+                            }
+                        };
+
+                        Ok(ClipSource {
+                            sample,
+                            start_seconds: tempo_map.seconds_at_beat(clip.start_beat),
+                            gain_db: clip.gain_db,
+                        })
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn length_in_seconds(&self, tempo_map: &TempoMap, clips: &[Vec<ClipSource>]) -> f64 {
+        match self.length_beats {
+            Some(length_beats) => tempo_map.seconds_at_beat(length_beats),
+            None => clips
+                .iter()
+                .flatten()
+                .map(|clip| clip.start_seconds + clip.sample.length_in_seconds())
+                .fold(0.0, f64::max),
+        }
+    }
+
+    pub fn render(&self, base_dir: &Path) -> Result<OwnedAudioBuffer, ProjectError> {
+        self.render_with_tail(base_dir, RenderTail::Truncate)
+    }
+
+    pub fn render_normalized(
+        &self,
+        base_dir: &Path,
+        tail: RenderTail,
+        target: &LoudnessTarget,
+    ) -> Result<OwnedAudioBuffer, ProjectError> {
+        let mut output = self.render_with_tail(base_dir, tail)?;
+        normalize_loudness(&mut output, target);
+        Ok(output)
+    }
+
+    pub fn render_with_tail(
+        &self,
+        base_dir: &Path,
+        tail: RenderTail,
+    ) -> Result<OwnedAudioBuffer, ProjectError> {
+        let (output, _) = self.render_with_stems(base_dir, tail, &[])?;
+        Ok(output)
+    }
+
+    pub fn render_with_stems(
+        &self,
+        base_dir: &Path,
+        tail: RenderTail,
+        stems: &[&str],
+    ) -> Result<(OwnedAudioBuffer, Vec<OwnedAudioBuffer>), ProjectError> {
+        self.validate()?;
+
+        let tempo_map = self.tempo_map();
+        let clips = self.load_clips(base_dir, &tempo_map)?;
+
+        let length_in_seconds = self.length_in_seconds(&tempo_map, &clips);
+        let length_in_frames = (length_in_seconds * self.sample_rate as f64).ceil() as usize;
+        let max_tail_in_frames =
+            (tail.max_tail().as_secs_f64() * self.sample_rate as f64).ceil() as usize;
+        let end = Timestamp::from_seconds(length_in_seconds + tail.max_tail().as_secs_f64());
+
+        let mut context = Context::new(self.sample_rate);
+        context.set_tempo_map(&tempo_map);
+        context.set_render_quality(RenderQuality::Best);
+        let mut audio_process = context.get_audio_process();
+
+        let master = GainNode::new(context.get_command_queue());
+        master.connect_to_output().map_err(graph_error)?;
+
+        let strips: Vec<TrackStrip> = self
+            .tracks
+            .iter()
+            .zip(clips)
+            .map(|(track, clips)| {
+                let strip = create_strip(&context, track, !self.is_audible(track), &tempo_map, end);
+                let clips = clips
+                    .into_iter()
+                    .map(|clip| create_clip(&context, clip, &strip))
+                    .collect::<Result<_, _>>()?;
+
+                Ok(TrackStrip {
+                    strip,
+                    _clips: clips,
+                })
+            })
+            .collect::<Result<_, ProjectError>>()?;
+
+        for (track, strip) in self.tracks.iter().zip(strips.iter()) {
+            let destination = match &track.output {
+                Some(output) => {
+                    let index = self
+                        .tracks
+                        .iter()
+                        .position(|other| &other.name == output)
+                        .ok_or_else(|| graph_error(output))?;
+                    strips[index].strip.get_id()
+                }
+                None => master.get_id(),
+            };
+
+            context
+                .get_command_queue()
+                .connect(Connection::new(strip.strip.get_id(), destination))
+                .map_err(graph_error)?;
+        }
+
+        let max_length_in_frames = length_in_frames + max_tail_in_frames;
+
+        let mut taps = stems
+            .iter()
+            .map(|name| {
+                let index = self
+                    .tracks
+                    .iter()
+                    .position(|track| &track.name == name)
+                    .ok_or_else(|| {
+                        ProjectError::InvalidProject(format!("Unknown stem track '{}'", name))
+                    })?;
+
+                let mut recorder =
+                    RecorderNode::new(context.get_command_queue(), self.sample_rate, NUM_CHANNELS);
+                context
+                    .get_command_queue()
+                    .connect(Connection::new(
+                        strips[index].strip.get_id(),
+                        recorder.get_id(),
+                    ))
+                    .map_err(graph_error)?;
+
+                recorder.arm();
+                recorder.punch_in_at(
+                    Timestamp::zero(),
+                    Duration::from_secs_f64(max_length_in_frames as f64 / self.sample_rate as f64),
+                );
+                Ok(recorder)
+            })
+            .collect::<Result<Vec<_>, ProjectError>>()?;
+
+        context.start();
+
+        let mut output =
+            OwnedAudioBuffer::new(max_length_in_frames, NUM_CHANNELS, self.sample_rate);
+
+        let mut position = 0;
+        let mut render_block = |output: &mut OwnedAudioBuffer, position: usize| {
+            let num_frames = BLOCK_SIZE.min(max_length_in_frames - position);
+            let mut block = AudioBufferSlice::new(output, position, num_frames);
+            audio_process.process(&mut block);
+
+            context.process_notifications();
+            num_frames
+        };
+
+        while position < length_in_frames {
+            position += render_block(&mut output, position);
+        }
+
+        let mut silent_since = length_in_frames;
+
+        if let RenderTail::UntilSilent {
+            threshold_db, hold, ..
+        } = tail
+        {
+            let threshold = Level::from_db(threshold_db).as_gain() as f32;
+            let hold_in_frames = (hold.as_secs_f64() * self.sample_rate as f64).ceil() as usize;
+
+            while position < max_length_in_frames && position - silent_since < hold_in_frames {
+                let num_frames = render_block(&mut output, position);
+
+                for frame in position..position + num_frames {
+                    if (0..NUM_CHANNELS).any(|channel| {
+                        output.get_sample(SampleLocation::new(channel, frame)).abs() >= threshold
+                    }) {
+                        silent_since = frame + 1;
+                    }
+                }
+
+                position += num_frames;
+            }
+
+            if position - silent_since < hold_in_frames {
+                silent_since = position;
+            }
+        }
+
+        if !taps.is_empty() {
+            taps.iter_mut().for_each(RecorderNode::disarm);
+            audio_process.process(&mut OwnedAudioBuffer::new(
+                1,
+                NUM_CHANNELS,
+                self.sample_rate,
+            ));
+        }
+
+        context.stop();
+
+        let num_frames = silent_since.max(length_in_frames);
+        output.truncate(num_frames);
+
+        let stems = taps
+            .iter_mut()
+            .map(|recorder| {
+                let mut stem = OwnedAudioBuffer::new(num_frames, NUM_CHANNELS, self.sample_rate);
+                if let Some(recording) = recorder.take_recordings().into_iter().next() {
+                    let location = SampleLocation::new(0, 0);
+                    stem.add_from(
+                        &recording.audio,
+                        location,
+                        location,
+                        NUM_CHANNELS,
+                        num_frames.min(recording.audio.num_frames()),
+                    );
+                }
+                stem
+            })
+            .collect();
+
+        Ok((output, stems))
+    }
+}
+
+fn create_strip(
+    context: &Context,
+    track: &ProjectTrack,
+    muted: bool,
+    tempo_map: &TempoMap,
+    end: Timestamp,
+) -> EmitterNode {
+    let mut strip = EmitterNode::new(context.get_command_queue(), Duration::ZERO);
+
+    let gain = if muted {
+        0.0
+    } else {
+        Level::from_db(track.gain_db).as_gain()
+    };
+    strip.gain.set_value_at_time(gain, Timestamp::zero());
+    strip.pan.set_value_at_time(track.pan, Timestamp::zero());
+    strip
+        .cutoff
+        .set_value_at_time(OPEN_CUTOFF, Timestamp::zero());
+
+    for automation in track.automation.iter() {
+        if muted && automation.target == AutomationTarget::Gain {
+            continue;
+        }
+
+        let mut lane = AutomationLane::new(tempo_map.clone(), Timestamp::zero());
+        for point in automation.points.iter() {
+            lane.add_point(*point);
+        }
+
+        let parameter = match automation.target {
+            AutomationTarget::Gain => &mut strip.gain,
+            AutomationTarget::Pan => &mut strip.pan,
+        };
+        lane.stream_to(parameter, end.incremented_by_seconds(1.0));
+    }
+
+    strip
+}
+
+fn create_clip(
+    context: &Context,
+    clip: ClipSource,
+    strip: &EmitterNode,
+) -> Result<(SamplerNode, GainNode), ProjectError> {
+    let mut sampler = SamplerNode::with_shared_sample(
+        context.get_command_queue(),
+        context.get_sample_rate(),
+        clip.sample,
+    );
+
+    let mut gain = GainNode::new(context.get_command_queue());
+    gain.gain
+        .set_value_at_time(Level::from_db(clip.gain_db).as_gain(), Timestamp::zero());
+
+    let command_queue = context.get_command_queue();
+    command_queue
+        .connect(Connection::new(sampler.get_id(), gain.get_id()))
+        .and_then(|_| command_queue.connect(Connection::new(gain.get_id(), strip.get_id())))
+        .map_err(graph_error)?;
+    sampler.start_from_position_at_time(
+        Timestamp::from_seconds(clip.start_seconds),
+        Timestamp::zero(),
+    );
+
+    Ok((sampler, gain))
+}
+
+pub fn render_project(path: &str) -> Result<OwnedAudioBuffer, ProjectError> {
+    render_project_with_tail(path, RenderTail::Truncate)
+}
+
+pub fn render_project_with_tail(
+    path: &str,
+    tail: RenderTail,
+) -> Result<OwnedAudioBuffer, ProjectError> {
+    let project = Project::load(path)?;
+    let base_dir = Path::new(path).parent().unwrap_or_else(|| Path::new(""));
+    project.render_with_tail(base_dir, tail)
+}
+
+pub fn render_project_normalized(
+    path: &str,
+    tail: RenderTail,
+    target: &LoudnessTarget,
+) -> Result<OwnedAudioBuffer, ProjectError> {
+    let project = Project::load(path)?;
+    let base_dir = Path::new(path).parent().unwrap_or_else(|| Path::new(""));
+    project.render_normalized(base_dir, tail, target)
+}
+
+pub fn export_project_stems(
+    path: &str,
+    tail: RenderTail,
+    stems: &[(&str, &str)],
+    format: ExportFormat,
+) -> Result<(), ProjectError> {
+    let project = Project::load(path)?;
+    let base_dir = Path::new(path).parent().unwrap_or_else(|| Path::new(""));
+
+    let track_names: Vec<&str> = stems.iter().map(|(track_name, _)| *track_name).collect();
+    let (_, buffers) = project.render_with_stems(base_dir, tail, &track_names)?;
+
+    for ((_, output_path), buffer) in stems.iter().zip(buffers.iter()) {
+        write_audio_file(output_path, buffer, format)
+            .map_err(|error| ProjectError::AudioFile(output_path.to_string(), error))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        parameter::automation::{AutomationCurve, AutomationPoint},
+        project::model::{ProjectClip, TrackAutomation},
+    };
+
+    use super::*;
+
+    const SAMPLE_RATE: usize = 48_000;
+
+    fn write_test_file(path: &Path, num_frames: usize, value: f32) {
+        let specification = hound::WavSpec {
+            channels: 1,
+            sample_rate: SAMPLE_RATE as u32,
+            bits_per_sample: 32,
+            sample_format: hound::SampleFormat::Float,
+        };
+
+        let mut writer = hound::WavWriter::create(path, specification).unwrap();
+        for _ in 0..num_frames {
+            writer.write_sample(value).unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+
+    fn test_directory(name: &str) -> std::path::PathBuf {
+        let directory =
+            std::env::temp_dir().join(format!("project-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        directory
+    }
+
+    fn left(buffer: &OwnedAudioBuffer, seconds: f64) -> f32 {
+        buffer.get_sample(SampleLocation::new(
+            0,
+            (seconds * SAMPLE_RATE as f64) as usize,
+        ))
+    }
+
+    #[test]
+    fn renders_clips_at_their_beats() {
+        let directory = test_directory("clips");
+        write_test_file(&directory.join("one.wav"), SAMPLE_RATE / 2, 0.5);
+
+        let project = Project::new("Clips", SAMPLE_RATE, 120.0)
+            .with_track(
+                ProjectTrack::new("Lead")
+                    .with_output("Bus")
+                    .with_clip(ProjectClip::new("one.wav", 1.0)),
+            )
+            .with_track(ProjectTrack::new("Bus"));
+        let path = directory.join("clips.toml");
+        project.save(path.to_str().unwrap()).unwrap();
+
+        let output = render_project(path.to_str().unwrap()).unwrap();
+        assert_eq!(output.num_frames(), SAMPLE_RATE);
+        assert_eq!(left(&output, 0.25), 0.0);
+        assert!((left(&output, 0.75) - 0.5).abs() < 1e-6);
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn applies_mute_and_automation() {
+        let directory = test_directory("automation");
+        write_test_file(&directory.join("tone.wav"), SAMPLE_RATE, 1.0);
+
+        let mut project = Project::new("Automation", SAMPLE_RATE, 60.0)
+            .with_track(
+                ProjectTrack::new("Faded")
+                    .with_clip(ProjectClip::new("tone.wav", 0.0))
+                    .with_automation(
+                        TrackAutomation::new(AutomationTarget::Gain)
+                            .with_point(AutomationPoint::new(0.0, 1.0, AutomationCurve::Step))
+                            .with_point(AutomationPoint::new(0.5, 0.0, AutomationCurve::Step)),
+                    ),
+            )
+            .with_track(ProjectTrack::new("Muted").with_clip(ProjectClip::new("tone.wav", 0.0)));
+        project.tracks[1].muted = true;
+
+        let output = project.render(&directory).unwrap();
+        assert!((left(&output, 0.25) - 1.0).abs() < 1e-6);
+        assert_eq!(left(&output, 0.75), 0.0);
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn reports_missing_and_mismatched_files() {
+        let directory = test_directory("errors");
+        write_test_file(&directory.join("tone.wav"), 16, 1.0);
+
+        let project = Project::new("Missing", SAMPLE_RATE, 120.0)
+            .with_track(ProjectTrack::new("Lead").with_clip(ProjectClip::new("missing.wav", 0.0)));
+        assert!(matches!(
+            project.render(&directory),
+            Err(ProjectError::AudioFile(..))
+        ));
+
+        let project = Project::new("Mismatched", 44_100, 120.0)
+            .with_track(ProjectTrack::new("Lead").with_clip(ProjectClip::new("tone.wav", 0.0)));
+        assert!(matches!(
+            project.render(&directory),
+            Err(ProjectError::InvalidProject(..))
+        ));
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn renders_until_silent_within_max_tail() {
+        let directory = test_directory("tail");
+        write_test_file(&directory.join("tone.wav"), SAMPLE_RATE, 0.5);
+
+        let mut project = Project::new("Tail", SAMPLE_RATE, 120.0)
+            .with_track(ProjectTrack::new("Lead").with_clip(ProjectClip::new("tone.wav", 0.0)));
+        project.length_beats = Some(1.0);
+
+        let until_silent = |max_tail: Duration| RenderTail::UntilSilent {
+            threshold_db: -60.0,
+            hold: Duration::from_millis(100),
+            max_tail,
+        };
+
+        let output = project.render(&directory).unwrap();
+        assert_eq!(output.num_frames(), SAMPLE_RATE / 2);
+
+        let output = project
+            .render_with_tail(&directory, until_silent(Duration::from_secs(2)))
+            .unwrap();
+        assert!(output.num_frames().abs_diff(SAMPLE_RATE) <= 2);
+        assert!((left(&output, 0.9) - 0.5).abs() < 1e-6);
+
+        let output = project
+            .render_with_tail(&directory, until_silent(Duration::from_millis(200)))
+            .unwrap();
+        assert_eq!(output.num_frames(), SAMPLE_RATE * 7 / 10);
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn exports_each_stem_from_a_single_render() {
+        let directory = test_directory("stems");
+        write_test_file(&directory.join("low.wav"), SAMPLE_RATE / 2, 0.25);
+        write_test_file(&directory.join("high.wav"), SAMPLE_RATE / 2, 0.5);
+
+        let project = Project::new("Stems", SAMPLE_RATE, 120.0)
+            .with_track(
+                ProjectTrack::new("Bass")
+                    .with_output("Band")
+                    .with_clip(ProjectClip::new("low.wav", 0.0)),
+            )
+            .with_track(
+                ProjectTrack::new("Lead")
+                    .with_output("Band")
+                    .with_clip(ProjectClip::new("high.wav", 1.0)),
+            )
+            .with_track(ProjectTrack::new("Band"));
+        let path = directory.join("stems.toml");
+        project.save(path.to_str().unwrap()).unwrap();
+
+        let (mix, stems) = project
+            .render_with_stems(&directory, RenderTail::Truncate, &["Bass", "Band"])
+            .unwrap();
+        assert_eq!(stems.len(), 2);
+        assert!(stems
+            .iter()
+            .all(|stem| stem.num_frames() == mix.num_frames()));
+        assert!((left(&stems[0], 0.25) - 0.25).abs() < 1e-6);
+        assert_eq!(left(&stems[0], 0.75), 0.0);
+        assert!((left(&stems[1], 0.75) - 0.5).abs() < 1e-6);
+        assert!((left(&mix, 0.75) - left(&stems[1], 0.75)).abs() < 1e-6);
+
+        assert!(matches!(
+            project.render_with_stems(&directory, RenderTail::Truncate, &["Drums"]),
+            Err(ProjectError::InvalidProject(..))
+        ));
+
+        let bass_path = directory.join("bass.wav");
+        export_project_stems(
+            path.to_str().unwrap(),
+            RenderTail::Truncate,
+            &[("Bass", bass_path.to_str().unwrap())],
+            ExportFormat::Wav,
+        )
+        .unwrap();
+        let bass = read_audio_file(bass_path.to_str().unwrap()).unwrap();
+        assert!((left(&bass, 0.25) - 0.25).abs() < 1e-6);
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -0,0 +1,113 @@
+use std::time::Duration;
+
+use crate::graph::realtime_budget::{Degradation, RealtimeBudget};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BudgetAction {
+    None,
+    Degrade(Degradation),
+    Restore,
+}
+
+#[derive(Default)]
+pub struct BudgetMonitor {
+    budget: Option<RealtimeBudget>,
+    degraded: bool,
+    blocks_until_restore: usize,
+}
+
+impl BudgetMonitor {
+    pub fn is_enabled(&self) -> bool {
+        self.budget.is_some()
+    }
+
+    pub fn set_budget(&mut self, budget: Option<RealtimeBudget>) -> BudgetAction {
+        self.budget = budget;
+
+        if budget.is_none() && self.degraded {
+            self.degraded = false;
+            return BudgetAction::Restore;
+        }
+
+        BudgetAction::None
+    }
+
+    pub fn record_block(
+        &mut self,
+        elapsed: Duration,
+        num_frames: usize,
+        sample_rate: usize,
+    ) -> BudgetAction {
+        let budget = match self.budget {
+            Some(budget) => budget,
+            None => return BudgetAction::None,
+        };
+
+        let deadline = num_frames as f64 / sample_rate as f64 * budget.max_load;
+
+        if elapsed.as_secs_f64() > deadline {
+            self.degraded = true;
+            self.blocks_until_restore = budget.recovery_blocks;
+            return BudgetAction::Degrade(budget.degradation);
+        }
+
+        if !self.degraded {
+            return BudgetAction::None;
+        }
+
+        self.blocks_until_restore = self.blocks_until_restore.saturating_sub(1);
+        if self.blocks_until_restore > 0 {
+            return BudgetAction::None;
+        }
+
+        self.degraded = false;
+        BudgetAction::Restore
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn degrades_on_overrun_and_restores_after_recovery_blocks() {
+        let mut monitor = BudgetMonitor::default();
+        let overrun = Duration::from_millis(9);
+        let on_time = Duration::from_millis(1);
+
+        assert_eq!(
+            monitor.record_block(overrun, 480, 48_000),
+            BudgetAction::None
+        );
+
+        monitor.set_budget(Some(RealtimeBudget {
+            max_load: 0.8,
+            degradation: Degradation::Skip,
+            recovery_blocks: 2,
+        }));
+
+        assert_eq!(
+            monitor.record_block(on_time, 480, 48_000),
+            BudgetAction::None
+        );
+        assert_eq!(
+            monitor.record_block(overrun, 480, 48_000),
+            BudgetAction::Degrade(Degradation::Skip)
+        );
+        assert_eq!(
+            monitor.record_block(on_time, 480, 48_000),
+            BudgetAction::None
+        );
+        assert_eq!(
+            monitor.record_block(on_time, 480, 48_000),
+            BudgetAction::Restore
+        );
+        assert_eq!(
+            monitor.record_block(on_time, 480, 48_000),
+            BudgetAction::None
+        );
+
+        monitor.record_block(overrun, 480, 48_000);
+        assert_eq!(monitor.set_budget(None), BudgetAction::Restore);
+    }
+}
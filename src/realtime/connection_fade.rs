@@ -0,0 +1,67 @@
+use crate::{commands::id::Id, graph::connection::Connection};
+
+pub struct ConnectionFade {
+    connection: Connection,
+    remaining_frames: usize,
+    total_frames: usize,
+}
+
+impl ConnectionFade {
+    pub fn new(connection: Connection, num_frames: usize) -> Self {
+        Self {
+            connection,
+            remaining_frames: num_frames,
+            total_frames: num_frames.max(1),
+        }
+    }
+
+    pub fn get_connection(&self) -> &Connection {
+        &self.connection
+    }
+
+    pub fn matches(&self, source_id: Id, destination_id: Id) -> bool {
+        self.connection.source.dsp_id == source_id
+            && self.connection.destination.dsp_id == destination_id
+    }
+
+    pub fn involves(&self, dsp_id: Id) -> bool {
+        self.connection.source.dsp_id == dsp_id || self.connection.destination.dsp_id == dsp_id
+    }
+
+    pub fn gain_at_frame(&self, frame: usize) -> f32 {
+        let remaining = self.remaining_frames.saturating_sub(frame);
+        remaining as f32 / self.total_frames as f32
+    }
+
+    pub fn advance(&mut self, num_frames: usize) {
+        self.remaining_frames = self.remaining_frames.saturating_sub(num_frames);
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.remaining_frames == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use super::*;
+
+    #[test]
+    fn fades_to_silence() {
+        let connection = Connection::new(Id::generate(), Id::generate());
+        let mut fade = ConnectionFade::new(connection, 100);
+
+        assert_relative_eq!(fade.gain_at_frame(0), 1.0);
+        assert_relative_eq!(fade.gain_at_frame(50), 0.5);
+
+        fade.advance(60);
+        assert!(!fade.is_finished());
+        assert_relative_eq!(fade.gain_at_frame(0), 0.4);
+        assert_relative_eq!(fade.gain_at_frame(40), 0.0);
+
+        fade.advance(40);
+        assert!(fade.is_finished());
+    }
+}
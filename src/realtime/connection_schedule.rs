@@ -0,0 +1,73 @@
+use crate::{commands::id::Id, graph::connection::Connection, timestamp::Timestamp};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConnectionAction {
+    Connect,
+    Disconnect,
+}
+
+pub struct ScheduledConnection {
+    pub connection: Connection,
+    pub time: Timestamp,
+    pub action: ConnectionAction,
+}
+
+pub struct ConnectionGate {
+    connection: Connection,
+    frame: usize,
+    action: ConnectionAction,
+}
+
+impl ConnectionGate {
+    pub fn new(connection: Connection, frame: usize, action: ConnectionAction) -> Self {
+        Self {
+            connection,
+            frame,
+            action,
+        }
+    }
+
+    pub fn get_connection(&self) -> &Connection {
+        &self.connection
+    }
+
+    pub fn get_action(&self) -> ConnectionAction {
+        self.action
+    }
+
+    pub fn matches(&self, source_id: Id, destination_id: Id) -> bool {
+        self.connection.source.dsp_id == source_id
+            && self.connection.destination.dsp_id == destination_id
+    }
+
+    pub fn gain_at_frame(&self, frame: usize) -> f32 {
+        let is_open = match self.action {
+            ConnectionAction::Connect => frame >= self.frame,
+            ConnectionAction::Disconnect => frame < self.frame,
+        };
+
+        if is_open {
+            1.0
+        } else {
+            0.0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opens_and_closes_at_frame() {
+        let connection = Connection::new(Id::generate(), Id::generate());
+
+        let opening = ConnectionGate::new(connection.clone(), 10, ConnectionAction::Connect);
+        assert_eq!(opening.gain_at_frame(9), 0.0);
+        assert_eq!(opening.gain_at_frame(10), 1.0);
+
+        let closing = ConnectionGate::new(connection, 10, ConnectionAction::Disconnect);
+        assert_eq!(closing.gain_at_frame(9), 1.0);
+        assert_eq!(closing.gain_at_frame(10), 0.0);
+    }
+}
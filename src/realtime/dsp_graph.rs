@@ -1,35 +1,80 @@
+use std::{
+    panic::{self, AssertUnwindSafe},
+    time::{Duration, Instant},
+};
+
 use lockfree::channel::{spsc, spsc::Sender};
 
 use crate::{
     buffer::{
         audio_buffer::AudioBuffer, audio_buffer_slice::AudioBufferSlice,
-        sample_location::SampleLocation,
+        owned_audio_buffer::OwnedAudioBuffer, sample_location::SampleLocation,
+    },
+    commands::{
+        command::{Command, ParameterChangeRequest, ParameterTempoSyncRequest},
+        id::Id,
     },
-    commands::{command::ParameterChangeRequest, id::Id},
     graph::{
         buffer_pool::BufferPool,
         connection::Connection,
         dsp::Dsp,
         endpoint::{Endpoint, EndpointType},
+        realtime_budget::Degradation,
+        render_quality::RenderQuality,
+        transport::Transport,
     },
+    midi::{midi_file_player::ScheduledMidiMessage, output::MidiOutputQueue},
+    tempo_map::TempoMap,
     timestamp::Timestamp,
+    utility::{
+        realtime_log::{self, LogLevel},
+        trace::{self, Category},
+    },
 };
 
 use super::{
+    connection_fade::ConnectionFade,
+    connection_schedule::{ConnectionAction, ConnectionGate, ScheduledConnection},
+    feedback_buffer::FeedbackBuffer,
     garbage_collector::{run_garbage_collector, GarbageCollectionCommand},
     graph::{Direction, Graph},
+    profiler::{NodeProfile, Profiler},
     topological_sort::TopologicalSort,
 };
 
+pub const BUFFER_POOL_CAPACITY: usize = 128;
+const MAXIMUM_PARAMETER_CONNECTIONS_PER_NODE: usize = 64;
+const MAXIMUM_FEEDBACK_CONNECTIONS: usize = 64;
+
+type ParameterSource = (Id, Id, f32);
+
 pub struct DspGraph {
     graph: Graph<Box<Dsp>, Connection>,
     topological_sort: TopologicalSort,
     output_endpoint: Option<Endpoint>,
+    input_destinations: Vec<Id>,
+    output_mix_sources: Vec<Id>,
     garbase_collection_tx: Sender<GarbageCollectionCommand>,
     graph_needs_sort: bool,
     buffer_pool: BufferPool,
     maximum_number_of_channels: usize,
     maximum_number_of_frames: usize,
+    profiler: Profiler,
+    midi_output: MidiOutputQueue,
+    sample_rate: usize,
+    connection_fade_frames: usize,
+    connection_fades: Vec<ConnectionFade>,
+    scheduled_connections: Vec<ScheduledConnection>,
+    connection_gates: Vec<ConnectionGate>,
+    quarantined: Vec<Id>,
+    idle: Vec<Id>,
+    tempo_map: Box<TempoMap>,
+    loop_beats: Option<(f64, f64)>,
+    render_quality: RenderQuality,
+    parameter_sources: Vec<ParameterSource>,
+    feedback_buffers: Vec<FeedbackBuffer>,
+    free_feedback_buffers: Vec<OwnedAudioBuffer>,
+    degradation_changes: Vec<(Id, bool)>,
 }
 
 impl DspGraph {
@@ -46,35 +91,138 @@ impl DspGraph {
             topological_sort: TopologicalSort::with_capacity(512),
             graph_needs_sort: false,
             output_endpoint: None,
+            input_destinations: Vec::with_capacity(64),
+            output_mix_sources: Vec::with_capacity(64),
             garbase_collection_tx,
             buffer_pool: BufferPool::with_capacity(
-                128,
+                BUFFER_POOL_CAPACITY,
                 maximum_number_of_frames,
                 maximum_number_of_channels,
                 sample_rate,
             ),
             maximum_number_of_channels,
             maximum_number_of_frames,
+            profiler: Profiler::with_capacity(512),
+            midi_output: MidiOutputQueue::with_capacity(1024),
+            sample_rate,
+            connection_fade_frames: 0,
+            connection_fades: Vec::with_capacity(512),
+            scheduled_connections: Vec::with_capacity(512),
+            connection_gates: Vec::with_capacity(512),
+            quarantined: Vec::with_capacity(64),
+            idle: Vec::with_capacity(64),
+            tempo_map: Box::default(),
+            loop_beats: None,
+            render_quality: RenderQuality::default(),
+            parameter_sources: Vec::with_capacity(MAXIMUM_PARAMETER_CONNECTIONS_PER_NODE),
+            feedback_buffers: Vec::with_capacity(MAXIMUM_FEEDBACK_CONNECTIONS),
+            free_feedback_buffers: (0..MAXIMUM_FEEDBACK_CONNECTIONS)
+                .map(|_| {
+                    OwnedAudioBuffer::new(
+                        maximum_number_of_frames,
+                        maximum_number_of_channels,
+                        sample_rate,
+                    )
+                })
+                .collect(),
+            degradation_changes: Vec::with_capacity(512),
         }
     }
 
+    #[cfg(test)]
     pub fn process(&mut self, output_buffer: &mut dyn AudioBuffer, start_time: &Timestamp) {
+        self.process_with_input(None, output_buffer, start_time);
+    }
+
+    pub fn process_with_input(
+        &mut self,
+        input_buffer: Option<&dyn AudioBuffer>,
+        output_buffer: &mut dyn AudioBuffer,
+        start_time: &Timestamp,
+    ) {
         let num_channels = std::cmp::min(
             output_buffer.num_channels(),
             self.maximum_number_of_channels,
         );
         let num_frames = std::cmp::min(output_buffer.num_frames(), self.maximum_number_of_frames);
 
+        self.apply_scheduled_connections(start_time, num_frames);
         self.sort_graph();
-        self.process_dsps(num_frames, num_channels, start_time);
+        self.process_dsps(
+            input_buffer,
+            num_frames,
+            num_channels,
+            start_time,
+            &self.transport_at(start_time),
+        );
         self.write_to_output(output_buffer, num_channels, num_frames);
+        self.capture_feedback(num_channels, num_frames);
+        self.advance_connection_fades(num_frames);
+        self.close_connection_gates();
 
         self.buffer_pool.clear_assignments();
         assert!(self.buffer_pool.all_buffers_are_available())
     }
 
-    pub fn add_dsp(&mut self, dsp: Box<Dsp>) {
+    #[cfg(any(test, feature = "fuzzing"))]
+    pub fn storage_capacities(&self) -> Vec<usize> {
+        let (num_nodes, num_edges, node_order) = self.graph.capacities();
+        let (dependency_count, order, ready_to_process) = self.topological_sort.capacities();
+
+        vec![
+            num_nodes,
+            num_edges,
+            node_order,
+            dependency_count,
+            order,
+            ready_to_process,
+            self.connection_fades.capacity(),
+            self.scheduled_connections.capacity(),
+            self.connection_gates.capacity(),
+            self.quarantined.capacity(),
+            self.idle.capacity(),
+            self.parameter_sources.capacity(),
+            self.feedback_buffers.capacity(),
+            self.free_feedback_buffers.capacity(),
+            self.degradation_changes.capacity(),
+        ]
+    }
+
+    #[cfg(any(test, feature = "fuzzing"))]
+    pub fn check_invariants(&self) {
+        self.graph.check_invariants();
+        assert!(self.buffer_pool.all_buffers_are_available());
+
+        if !self.graph_needs_sort {
+            let order = self.topological_sort.get_sorted_graph();
+            assert_eq!(order.len(), self.graph.num_nodes());
+
+            for (index, id) in order.iter().enumerate() {
+                assert!(self.graph.contains_node(*id));
+                assert!(order[..index].iter().all(|earlier_id| self
+                    .graph
+                    .get_edge_data(*id, *earlier_id)
+                    .is_none_or(|connection| connection.feedback)));
+            }
+        }
+
+        for fade in self.connection_fades.iter() {
+            let connection = fade.get_connection();
+            assert!(self.graph.contains_node(connection.source.dsp_id));
+            assert!(self.graph.contains_node(connection.destination.dsp_id));
+        }
+
+        assert_eq!(
+            self.feedback_buffers.len() + self.free_feedback_buffers.len(),
+            MAXIMUM_FEEDBACK_CONNECTIONS
+        );
+    }
+
+    pub fn add_dsp(&mut self, mut dsp: Box<Dsp>) {
         let id = dsp.get_id();
+        if self.render_quality != RenderQuality::default() {
+            dsp.set_render_quality(self.render_quality);
+        }
         self.graph.add_node_with_id(id, dsp);
         self.mark_graph_needs_sort();
     }
@@ -85,30 +233,231 @@ impl DspGraph {
 
     fn sort_graph(&mut self) {
         if self.graph_needs_sort {
-            self.topological_sort.sort(&self.graph);
+            self.topological_sort
+                .sort(&self.graph, |connection| connection.feedback);
             self.graph_needs_sort = false;
         }
     }
 
     pub fn remove_dsp(&mut self, id: Id) {
         if let Some(dsp) = self.graph.remove_node(id) {
-            let _ = self
-                .garbase_collection_tx
-                .send(GarbageCollectionCommand::DisposeDsp(dsp));
+            self.dispose(GarbageCollectionCommand::DisposeDsp(dsp));
+        }
+
+        self.profiler.remove(id);
+        self.input_destinations
+            .retain(|destination| *destination != id);
+        self.output_mix_sources.retain(|source| *source != id);
+        self.connection_fades.retain(|fade| !fade.involves(id));
+
+        while let Some(index) = self
+            .feedback_buffers
+            .iter()
+            .position(|feedback| feedback.involves(id))
+        {
+            self.release_feedback_buffer(index);
         }
 
         self.mark_graph_needs_sort();
     }
 
+    pub fn set_profiling_enabled(&mut self, enabled: bool) {
+        self.profiler.set_enabled(enabled);
+    }
+
+    pub fn is_profiling_enabled(&self) -> bool {
+        self.profiler.is_enabled()
+    }
+
+    pub fn drain_quarantined(&mut self, mut report: impl FnMut(Id)) {
+        for id in self.quarantined.drain(..) {
+            report(id);
+        }
+    }
+
+    pub fn set_dsp_priority(&mut self, id: Id, priority: i32) {
+        if let Some(dsp) = self.graph.get_node_mut(id) {
+            dsp.set_priority(priority);
+        }
+    }
+
+    pub fn degrade_next_tier(&mut self, degradation: Degradation) -> bool {
+        let tier = match self
+            .graph
+            .nodes_mut()
+            .filter(|dsp| !dsp.is_degraded() && dsp.get_priority() < 0)
+            .map(|dsp| dsp.get_priority())
+            .min()
+        {
+            Some(tier) => tier,
+            None => return false,
+        };
+
+        for dsp in self.graph.nodes_mut() {
+            if !dsp.is_degraded() && dsp.get_priority() == tier {
+                dsp.degrade(degradation);
+
+                if self.degradation_changes.len() < self.degradation_changes.capacity() {
+                    self.degradation_changes.push((dsp.get_id(), true));
+                }
+            }
+        }
+
+        true
+    }
+
+    pub fn restore_degraded(&mut self) {
+        for dsp in self.graph.nodes_mut() {
+            if dsp.is_degraded() {
+                dsp.restore(self.render_quality);
+
+                if self.degradation_changes.len() < self.degradation_changes.capacity() {
+                    self.degradation_changes.push((dsp.get_id(), false));
+                }
+            }
+        }
+    }
+
+    pub fn drain_degradation_changes(&mut self, mut report: impl FnMut(Id, bool)) {
+        for (id, degraded) in self.degradation_changes.drain(..) {
+            report(id, degraded);
+        }
+    }
+
+    pub fn stop_dsp(&mut self, id: Id, time: Timestamp, allow_tail: bool) {
+        if let Some(dsp) = self.graph.get_node_mut(id) {
+            dsp.stop_at(time, allow_tail);
+        }
+    }
+
+    pub fn drain_idle(&mut self, mut report: impl FnMut(Id)) {
+        for id in self.idle.drain(..) {
+            report(id);
+        }
+    }
+
+    pub fn drain_profiling_report(&mut self, report: impl FnMut(NodeProfile)) {
+        self.profiler.drain_report(report);
+    }
+
+    pub fn drain_midi_output(&mut self, mut output: impl FnMut(ScheduledMidiMessage)) {
+        self.midi_output.drain(&mut output);
+    }
+
+    pub fn detach_dsp(&mut self, id: Id) {
+        if let Some(dsp) = self.graph.get_node_mut(id) {
+            dsp.set_detached(true);
+        }
+    }
+
+    pub fn reattach_dsp(&mut self, id: Id) {
+        if let Some(dsp) = self.graph.get_node_mut(id) {
+            dsp.set_detached(false);
+        }
+    }
+
     pub fn request_parameter_change(&mut self, change_request: ParameterChangeRequest) {
         if let Some(dsp) = self.graph.get_node_mut(change_request.dsp_id) {
             dsp.request_parameter_change(change_request);
         }
     }
 
+    pub fn set_parameter_modulation(
+        &mut self,
+        dsp_id: Id,
+        parameter_id: Id,
+        offset: f64,
+        minimum: f64,
+        maximum: f64,
+    ) {
+        if let Some(dsp) = self.graph.get_node_mut(dsp_id) {
+            dsp.set_parameter_modulation(parameter_id, offset, minimum, maximum);
+        }
+    }
+
+    pub fn set_parameter_tempo_sync(&mut self, request: ParameterTempoSyncRequest) {
+        if let Some(dsp) = self.graph.get_node_mut(request.dsp_id) {
+            dsp.set_parameter_tempo_sync(request);
+        }
+    }
+
+    pub fn set_tempo_map(&mut self, tempo_map: Box<TempoMap>) {
+        let previous = std::mem::replace(&mut self.tempo_map, tempo_map);
+        self.dispose(GarbageCollectionCommand::DisposeTempoMap(previous));
+    }
+
+    pub fn set_host_tempo(&mut self, beats_per_minute: f64) {
+        if !self.tempo_map.is_constant_tempo(beats_per_minute) {
+            self.tempo_map.set_constant_tempo(beats_per_minute);
+        }
+    }
+
+    pub fn set_render_quality(&mut self, quality: RenderQuality) {
+        self.render_quality = quality;
+        for dsp in self.graph.nodes_mut() {
+            dsp.set_render_quality(quality);
+        }
+    }
+
+    pub fn set_transport_loop(&mut self, loop_beats: Option<(f64, f64)>) {
+        self.loop_beats = loop_beats;
+    }
+
+    pub fn tempo_at(&self, time: &Timestamp) -> f64 {
+        self.tempo_map
+            .tempo_at_beat(self.tempo_map.beat_at_seconds(time.get_seconds()))
+    }
+
+    pub fn transport_at(&self, time: &Timestamp) -> Transport {
+        let beat = self.tempo_map.beat_at_seconds(time.get_seconds());
+        Transport::new(self.tempo_map.tempo_at_beat(beat), beat, self.loop_beats)
+    }
+
+    pub fn request_parameter_changes(&mut self, mut change_requests: Vec<ParameterChangeRequest>) {
+        for change_request in change_requests.drain(..) {
+            self.request_parameter_change(change_request);
+        }
+
+        self.dispose(GarbageCollectionCommand::DisposeParameterChanges(
+            change_requests,
+        ));
+    }
+
+    pub fn dispose_command(&mut self, command: Box<Command>) {
+        self.dispose(GarbageCollectionCommand::DisposeCommand(command));
+    }
+
+    fn dispose(&mut self, command: GarbageCollectionCommand) {
+        if self.garbase_collection_tx.send(command).is_err() {
+            realtime_log::log(LogLevel::Error, "Garbage collector unavailable");
+        }
+    }
+
     pub fn add_connection(&mut self, connection: Connection) {
         // TODO: Remove conflicting connections
 
+        if let Some(index) = self.find_connection_fade(&connection) {
+            self.connection_fades.swap_remove(index);
+
+            self.update_feedback_buffer(&connection);
+
+            if let Some(edge_data) = self
+                .graph
+                .get_edge_data_mut(connection.source.dsp_id, connection.destination.dsp_id)
+            {
+                *edge_data = connection;
+            }
+            return;
+        }
+
+        if !self.graph.contains_node(connection.source.dsp_id)
+            || !self.graph.contains_node(connection.destination.dsp_id)
+        {
+            return;
+        }
+
+        self.update_feedback_buffer(&connection);
+
         self.graph.add_edge(
             connection.source.dsp_id,
             connection.destination.dsp_id,
@@ -118,10 +467,181 @@ impl DspGraph {
         self.mark_graph_needs_sort();
     }
 
+    fn find_feedback_buffer(&self, source_id: Id, destination_id: Id) -> Option<usize> {
+        self.feedback_buffers
+            .iter()
+            .position(|feedback| feedback.matches(source_id, destination_id))
+    }
+
+    fn update_feedback_buffer(&mut self, connection: &Connection) {
+        let source_id = connection.source.dsp_id;
+        let destination_id = connection.destination.dsp_id;
+
+        match (
+            connection.feedback,
+            self.find_feedback_buffer(source_id, destination_id),
+        ) {
+            (true, None) => match self.free_feedback_buffers.pop() {
+                Some(buffer) => self.feedback_buffers.push(FeedbackBuffer::new(
+                    source_id,
+                    destination_id,
+                    buffer,
+                )),
+                None => realtime_log::log(LogLevel::Warning, "Too many feedback connections"),
+            },
+            (false, Some(index)) => self.release_feedback_buffer(index),
+            _ => (),
+        }
+    }
+
+    fn release_feedback_buffer(&mut self, index: usize) {
+        let feedback = self.feedback_buffers.swap_remove(index);
+        self.free_feedback_buffers.push(feedback.into_buffer());
+    }
+
+    fn capture_feedback(&mut self, num_channels: usize, num_frames: usize) {
+        for feedback in self.feedback_buffers.iter_mut() {
+            let endpoint = Endpoint::new(feedback.get_source_id(), EndpointType::Output);
+            let is_silent = self.buffer_pool.is_silent(endpoint);
+
+            match self.buffer_pool.get_assigned_buffer(endpoint) {
+                Some(buffer) => {
+                    feedback.capture(
+                        (!is_silent).then_some(&buffer as &dyn AudioBuffer),
+                        num_channels,
+                        num_frames,
+                    );
+                    self.buffer_pool
+                        .return_buffer_with_assignment(buffer, endpoint, is_silent);
+                }
+                None => feedback.capture(None, num_channels, num_frames),
+            }
+        }
+    }
+
+    pub fn schedule_connection(
+        &mut self,
+        connection: Connection,
+        time: Timestamp,
+        action: ConnectionAction,
+    ) {
+        if self.scheduled_connections.len() == self.scheduled_connections.capacity() {
+            realtime_log::log(LogLevel::Warning, "Connection schedule full");
+            return;
+        }
+
+        self.scheduled_connections.push(ScheduledConnection {
+            connection,
+            time,
+            action,
+        });
+    }
+
+    fn apply_scheduled_connections(&mut self, start_time: &Timestamp, num_frames: usize) {
+        let end_time = start_time.incremented_by_samples(num_frames, self.sample_rate);
+
+        let mut index = 0;
+
+        while index < self.scheduled_connections.len() {
+            if self.scheduled_connections[index].time >= end_time {
+                index += 1;
+                continue;
+            }
+
+            let scheduled = self.scheduled_connections.swap_remove(index);
+
+            let frame = if scheduled.time > *start_time {
+                Dsp::frame_at_time(&(scheduled.time - *start_time), self.sample_rate)
+            } else {
+                0
+            };
+
+            match scheduled.action {
+                ConnectionAction::Connect => self.add_connection(scheduled.connection.clone()),
+                ConnectionAction::Disconnect if frame == 0 => {
+                    self.remove_connection(scheduled.connection);
+                    continue;
+                }
+                ConnectionAction::Disconnect => (),
+            }
+
+            if frame > 0 && self.connection_gates.len() < self.connection_gates.capacity() {
+                self.connection_gates.push(ConnectionGate::new(
+                    scheduled.connection,
+                    frame,
+                    scheduled.action,
+                ));
+            }
+        }
+    }
+
+    fn close_connection_gates(&mut self) {
+        while let Some(gate) = self.connection_gates.pop() {
+            if gate.get_action() == ConnectionAction::Disconnect {
+                self.remove_edge(gate.get_connection().clone());
+            }
+        }
+    }
+
+    pub fn set_connection_fade_time(&mut self, fade_time: Duration) {
+        self.connection_fade_frames = (fade_time.as_secs_f64() * self.sample_rate as f64) as usize;
+    }
+
+    fn find_connection_fade(&self, connection: &Connection) -> Option<usize> {
+        self.connection_fades
+            .iter()
+            .position(|fade| fade.matches(connection.source.dsp_id, connection.destination.dsp_id))
+    }
+
     pub fn remove_connection(&mut self, connection: Connection) {
+        let can_fade = self.connection_fade_frames > 0
+            && self.connection_fades.len() < self.connection_fades.capacity()
+            && self
+                .graph
+                .is_connected_to(connection.source.dsp_id, connection.destination.dsp_id);
+
+        if can_fade {
+            if self.find_connection_fade(&connection).is_none() {
+                self.connection_fades
+                    .push(ConnectionFade::new(connection, self.connection_fade_frames));
+            }
+            return;
+        }
+
+        if self.connection_fade_frames > 0
+            && self.connection_fades.len() == self.connection_fades.capacity()
+        {
+            realtime_log::log(LogLevel::Info, "Connection fade capacity reached");
+        }
+
+        self.remove_edge(connection);
+    }
+
+    fn advance_connection_fades(&mut self, num_frames: usize) {
+        let mut index = 0;
+
+        while index < self.connection_fades.len() {
+            self.connection_fades[index].advance(num_frames);
+
+            if self.connection_fades[index].is_finished() {
+                let fade = self.connection_fades.swap_remove(index);
+                self.remove_edge(fade.get_connection().clone());
+            } else {
+                index += 1;
+            }
+        }
+    }
+
+    fn remove_edge(&mut self, connection: Connection) {
         self.graph
             .remove_edge(connection.source.dsp_id, connection.destination.dsp_id);
 
+        if let Some(index) =
+            self.find_feedback_buffer(connection.source.dsp_id, connection.destination.dsp_id)
+        {
+            self.release_feedback_buffer(index);
+        }
+
         self.mark_graph_needs_sort();
     }
 
@@ -129,13 +649,56 @@ impl DspGraph {
         self.output_endpoint = Some(output_endpoint);
     }
 
+    pub fn disconnect_from_output(&mut self) {
+        self.output_endpoint = None;
+    }
+
+    pub fn connect_input(&mut self, id: Id) {
+        if self.input_destinations.contains(&id) {
+            return;
+        }
+
+        if self.input_destinations.len() == self.input_destinations.capacity() {
+            realtime_log::log(LogLevel::Warning, "Too many input connections");
+            return;
+        }
+
+        self.input_destinations.push(id);
+    }
+
+    pub fn disconnect_input(&mut self, id: Id) {
+        self.input_destinations
+            .retain(|destination| *destination != id);
+    }
+
+    pub fn connect_output_mix(&mut self, id: Id) {
+        if self.output_mix_sources.contains(&id) {
+            return;
+        }
+
+        if self.output_mix_sources.len() == self.output_mix_sources.capacity() {
+            realtime_log::log(LogLevel::Warning, "Too many output mix sources");
+            return;
+        }
+
+        self.output_mix_sources.push(id);
+    }
+
+    pub fn disconnect_output_mix(&mut self, id: Id) {
+        self.output_mix_sources.retain(|source| *source != id);
+    }
+
     fn mix_in_endpoint(
         buffer_pool: &mut BufferPool,
         endpoint: Endpoint,
         output_buffer: &mut dyn AudioBuffer,
         num_channels: usize,
         num_frames: usize,
-    ) {
+    ) -> bool {
+        if buffer_pool.is_silent(endpoint) {
+            return false;
+        }
+
         if let Some(buffer) = buffer_pool.get_assigned_buffer(endpoint) {
             let sample_location = SampleLocation::new(0, 0);
             output_buffer.add_from(
@@ -146,7 +709,73 @@ impl DspGraph {
                 num_frames,
             );
 
-            buffer_pool.return_buffer_with_assignment(buffer, endpoint);
+            buffer_pool.return_buffer_with_assignment(buffer, endpoint, false);
+            return true;
+        }
+
+        false
+    }
+
+    fn mix_in_connection(
+        buffer_pool: &mut BufferPool,
+        endpoint: Endpoint,
+        connection: &Connection,
+        gain_at_frame: impl Fn(usize) -> f32,
+        output_buffer: &mut dyn AudioBuffer,
+        num_channels: usize,
+        num_frames: usize,
+    ) -> bool {
+        if buffer_pool.is_silent(endpoint) {
+            return false;
+        }
+
+        if let Some(buffer) = buffer_pool.get_assigned_buffer(endpoint) {
+            Self::mix_in_buffer(
+                &buffer,
+                connection,
+                gain_at_frame,
+                output_buffer,
+                num_channels,
+                num_frames,
+            );
+
+            buffer_pool.return_buffer_with_assignment(buffer, endpoint, false);
+            return true;
+        }
+
+        false
+    }
+
+    fn mix_in_buffer(
+        buffer: &dyn AudioBuffer,
+        connection: &Connection,
+        gain_at_frame: impl Fn(usize) -> f32,
+        output_buffer: &mut dyn AudioBuffer,
+        num_channels: usize,
+        num_frames: usize,
+    ) {
+        for frame in 0..num_frames {
+            let gain = gain_at_frame(frame) * connection.gain;
+
+            for channel in 0..num_channels {
+                let location = SampleLocation::new(channel, frame);
+
+                let sample = match &connection.channel_matrix {
+                    Some(channel_matrix) => channel_matrix
+                        .destination_gains(channel)
+                        .iter()
+                        .take(num_channels)
+                        .enumerate()
+                        .map(|(source_channel, source_gain)| {
+                            buffer.get_sample(SampleLocation::new(source_channel, frame))
+                                * source_gain
+                        })
+                        .sum(),
+                    None => buffer.get_sample(location),
+                };
+
+                output_buffer.add_sample(location, sample * gain);
+            }
         }
     }
 
@@ -157,7 +786,7 @@ impl DspGraph {
         num_frames: usize,
     ) {
         if let Some(output_endpoint) = self.output_endpoint {
-            Self::mix_in_endpoint(
+            let _ = Self::mix_in_endpoint(
                 &mut self.buffer_pool,
                 output_endpoint,
                 output_buffer,
@@ -165,44 +794,191 @@ impl DspGraph {
                 num_frames,
             );
         }
+
+        for source in self.output_mix_sources.iter() {
+            let _ = Self::mix_in_endpoint(
+                &mut self.buffer_pool,
+                Endpoint::new(*source, EndpointType::Output),
+                output_buffer,
+                num_channels,
+                num_frames,
+            );
+        }
     }
 
-    fn process_dsps(&mut self, num_frames: usize, num_channels: usize, start_time: &Timestamp) {
+    fn process_dsps(
+        &mut self,
+        input_buffer: Option<&dyn AudioBuffer>,
+        num_frames: usize,
+        num_channels: usize,
+        start_time: &Timestamp,
+        transport: &Transport,
+    ) {
         for dsp_id in self.topological_sort.get_sorted_graph() {
+            if let Some(dsp) = self.graph.get_node_mut(*dsp_id) {
+                dsp.set_transport(transport);
+            }
+
+            let live_input = input_buffer.filter(|_| self.input_destinations.contains(dsp_id));
+
             Self::process_dsp(
                 &mut self.buffer_pool,
                 &mut self.graph,
+                &mut self.profiler,
+                &self.connection_fades,
+                &self.connection_gates,
+                &self.feedback_buffers,
+                &mut self.quarantined,
+                &mut self.idle,
+                &mut self.parameter_sources,
+                live_input,
                 *dsp_id,
                 num_frames,
                 num_channels,
                 start_time,
             );
+
+            if let Some(dsp) = self.graph.get_node_mut(*dsp_id) {
+                let midi_output = &mut self.midi_output;
+                dsp.drain_midi_output(&mut |scheduled| {
+                    if !midi_output.push(scheduled.time, scheduled.message) {
+                        realtime_log::log(LogLevel::Warning, "MIDI output queue full");
+                    }
+                });
+
+                let garbage_collection_tx = &mut self.garbase_collection_tx;
+                dsp.drain_garbage(&mut |command| {
+                    if garbage_collection_tx.send(command).is_err() {
+                        realtime_log::log(LogLevel::Error, "Garbage collector unavailable");
+                    }
+                });
+            }
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn copy_output_from_dependencies(
         buffer_pool: &mut BufferPool,
         graph: &Graph<Box<Dsp>, Connection>,
+        connection_fades: &[ConnectionFade],
+        connection_gates: &[ConnectionGate],
+        feedback_buffers: &[FeedbackBuffer],
+        parameter_sources: &mut Vec<ParameterSource>,
+        live_input: Option<&dyn AudioBuffer>,
         dsp_id: Id,
         destination_buffer: &mut dyn AudioBuffer,
+        mut sidechain_buffer: Option<&mut OwnedAudioBuffer>,
         num_channels: usize,
         num_frames: usize,
-    ) {
+    ) -> bool {
+        let mut mixed_audio = false;
+        parameter_sources.clear();
+
+        if let Some(live_input) = live_input {
+            let sample_location = SampleLocation::new(0, 0);
+            destination_buffer.add_from(
+                live_input,
+                sample_location,
+                sample_location,
+                num_channels.min(live_input.num_channels()),
+                num_frames.min(live_input.num_frames()),
+            );
+            mixed_audio = true;
+        }
+
         for connected_node_id in graph.node_iter(dsp_id, Direction::Incoming) {
             let endpoint = Endpoint::new(connected_node_id, EndpointType::Output);
-            Self::mix_in_endpoint(
-                buffer_pool,
-                endpoint,
-                destination_buffer,
-                num_channels,
-                num_frames,
-            );
+
+            let connection = match graph.get_edge_data(connected_node_id, dsp_id) {
+                Some(connection) => connection,
+                None => continue,
+            };
+
+            if let Some(parameter_id) = connection.get_destination_parameter() {
+                if parameter_sources.len() < parameter_sources.capacity() {
+                    parameter_sources.push((connected_node_id, parameter_id, connection.gain));
+                }
+                continue;
+            }
+
+            let fade = connection_fades
+                .iter()
+                .find(|fade| fade.matches(connected_node_id, dsp_id));
+
+            let gate = connection_gates
+                .iter()
+                .find(|gate| gate.matches(connected_node_id, dsp_id));
+
+            let gain_at_frame = |frame| match (fade, gate) {
+                (Some(fade), _) => fade.gain_at_frame(frame),
+                (None, Some(gate)) => gate.gain_at_frame(frame),
+                (None, None) => 1.0,
+            };
+
+            let (target_buffer, mixes_input): (&mut dyn AudioBuffer, bool) =
+                match (connection.is_sidechain(), sidechain_buffer.as_deref_mut()) {
+                    (false, _) => (&mut *destination_buffer, true),
+                    (true, Some(sidechain_buffer)) => (sidechain_buffer, false),
+                    (true, None) => continue,
+                };
+
+            if connection.feedback {
+                if let Some(buffer) = feedback_buffers
+                    .iter()
+                    .find(|feedback| feedback.matches(connected_node_id, dsp_id))
+                    .and_then(|feedback| feedback.get_buffer())
+                {
+                    Self::mix_in_buffer(
+                        &buffer,
+                        connection,
+                        gain_at_frame,
+                        target_buffer,
+                        num_channels,
+                        num_frames,
+                    );
+                    mixed_audio |= mixes_input;
+                }
+                continue;
+            }
+
+            let mixed = if fade.is_none() && gate.is_none() && connection.is_unity() {
+                Self::mix_in_endpoint(
+                    buffer_pool,
+                    endpoint,
+                    target_buffer,
+                    num_channels,
+                    num_frames,
+                )
+            } else {
+                Self::mix_in_connection(
+                    buffer_pool,
+                    endpoint,
+                    connection,
+                    gain_at_frame,
+                    target_buffer,
+                    num_channels,
+                    num_frames,
+                )
+            };
+
+            mixed_audio |= mixed && mixes_input;
         }
+
+        mixed_audio
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn process_dsp(
         buffer_pool: &mut BufferPool,
         graph: &mut Graph<Box<Dsp>, Connection>,
+        profiler: &mut Profiler,
+        connection_fades: &[ConnectionFade],
+        connection_gates: &[ConnectionGate],
+        feedback_buffers: &[FeedbackBuffer],
+        quarantined: &mut Vec<Id>,
+        idle: &mut Vec<Id>,
+        parameter_sources: &mut Vec<ParameterSource>,
+        live_input: Option<&dyn AudioBuffer>,
         dsp_id: Id,
         num_frames: usize,
         num_channels: usize,
@@ -210,41 +986,216 @@ impl DspGraph {
     ) {
         let output_endpoint = Endpoint::new(dsp_id, EndpointType::Output);
 
-        let mut node_input_buffer = buffer_pool.get_unassigned_buffer().unwrap();
-        let mut node_output_buffer = buffer_pool.get_unassigned_buffer().unwrap();
+        let (mut node_input_buffer, mut node_output_buffer) = match (
+            buffer_pool.get_unassigned_buffer(),
+            buffer_pool.get_unassigned_buffer(),
+        ) {
+            (Some(input_buffer), Some(output_buffer)) => (input_buffer, output_buffer),
+            (input_buffer, output_buffer) => {
+                realtime_log::log(LogLevel::Error, "Buffer pool exhausted");
+                input_buffer
+                    .into_iter()
+                    .chain(output_buffer)
+                    .for_each(|buffer| buffer_pool.return_buffer(buffer));
+                return;
+            }
+        };
+
+        let mut sidechain_buffer = Self::has_sidechain_input(graph, dsp_id)
+            .then(|| buffer_pool.get_unassigned_buffer())
+            .flatten();
 
         let mut node_output_buffer_slice =
             AudioBufferSlice::new(&mut node_output_buffer, 0, num_frames);
 
-        Self::copy_output_from_dependencies(
+        let input_is_silent = !Self::copy_output_from_dependencies(
             buffer_pool,
             graph,
+            connection_fades,
+            connection_gates,
+            feedback_buffers,
+            parameter_sources,
+            live_input,
             dsp_id,
             &mut node_input_buffer,
+            sidechain_buffer.as_mut(),
             num_channels,
             num_frames,
         );
 
-        if let Some(dsp) = graph.get_node_mut(dsp_id) {
-            dsp.process_audio(
-                &node_input_buffer,
-                &mut node_output_buffer_slice,
+        let mut output_is_silent = true;
+
+        if let Some(dsp) = graph.get_node_mut(dsp_id).filter(|dsp| {
+            !dsp.is_detached() && !dsp.is_quarantined() && !dsp.is_idle() && !dsp.is_skipped()
+        }) {
+            dsp.silence_input_after_stop(&mut node_input_buffer, start_time, num_frames);
+
+            Self::apply_parameter_modulation(
+                buffer_pool,
+                dsp,
+                connection_fades,
+                connection_gates,
+                parameter_sources,
                 start_time,
+                num_frames,
+                node_input_buffer.sample_rate(),
             );
-        };
 
-        buffer_pool.return_buffer(node_input_buffer);
-        buffer_pool.return_buffer_with_assignment(node_output_buffer, output_endpoint);
+            if dsp.update_sleep_state(&node_input_buffer, input_is_silent, num_frames) {
+                return Self::return_buffers(
+                    buffer_pool,
+                    (node_input_buffer, input_is_silent),
+                    (node_output_buffer, true),
+                    sidechain_buffer,
+                    output_endpoint,
+                );
+            }
+
+            let _span = trace::span("process_audio", Category::Node, Some(dsp_id));
+            let process_start = profiler.is_enabled().then(Instant::now);
+
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                dsp.process_audio_with_sidechain(
+                    &node_input_buffer,
+                    sidechain_buffer
+                        .as_ref()
+                        .map(|buffer| buffer as &dyn AudioBuffer),
+                    &mut node_output_buffer_slice,
+                    start_time,
+                )
+            }));
+
+            output_is_silent = result.is_err();
+
+            if result.is_err() {
+                realtime_log::log(LogLevel::Error, "DSP panicked and was quarantined");
+                dsp.quarantine();
+                node_output_buffer_slice.clear();
+
+                if quarantined.len() < quarantined.capacity() {
+                    quarantined.push(dsp_id);
+                }
+            }
+
+            if let Some(process_start) = process_start {
+                profiler.record(dsp_id, process_start.elapsed());
+            }
+
+            if dsp.update_idle_state(&node_output_buffer_slice, num_frames)
+                && idle.len() < idle.capacity()
+            {
+                idle.push(dsp_id);
+            }
+        };
+
+        Self::return_buffers(
+            buffer_pool,
+            (node_input_buffer, input_is_silent),
+            (node_output_buffer, output_is_silent),
+            sidechain_buffer,
+            output_endpoint,
+        );
+    }
+
+    fn has_sidechain_input(graph: &Graph<Box<Dsp>, Connection>, dsp_id: Id) -> bool {
+        graph
+            .node_iter(dsp_id, Direction::Incoming)
+            .filter_map(|source_id| graph.get_edge_data(source_id, dsp_id))
+            .any(|connection| connection.is_sidechain())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn apply_parameter_modulation(
+        buffer_pool: &mut BufferPool,
+        dsp: &mut Dsp,
+        connection_fades: &[ConnectionFade],
+        connection_gates: &[ConnectionGate],
+        parameter_sources: &mut Vec<ParameterSource>,
+        start_time: &Timestamp,
+        num_frames: usize,
+        sample_rate: usize,
+    ) {
+        dsp.clear_audio_modulation();
+
+        let dsp_id = dsp.get_id();
+
+        for (source_id, parameter_id, gain) in parameter_sources.drain(..) {
+            let endpoint = Endpoint::new(source_id, EndpointType::Output);
+
+            if buffer_pool.is_silent(endpoint) {
+                continue;
+            }
+
+            let buffer = match buffer_pool.get_assigned_buffer(endpoint) {
+                Some(buffer) => buffer,
+                None => continue,
+            };
+
+            let fade = connection_fades
+                .iter()
+                .find(|fade| fade.matches(source_id, dsp_id));
+
+            let gate = connection_gates
+                .iter()
+                .find(|gate| gate.matches(source_id, dsp_id));
+
+            dsp.add_audio_modulation(
+                parameter_id,
+                *start_time,
+                sample_rate,
+                num_frames,
+                |frame| {
+                    let connection_gain = match (fade, gate) {
+                        (Some(fade), _) => fade.gain_at_frame(frame),
+                        (None, Some(gate)) => gate.gain_at_frame(frame),
+                        (None, None) => 1.0,
+                    };
+
+                    let sample = buffer.get_sample(SampleLocation::new(0, frame));
+                    (sample * gain * connection_gain) as f64
+                },
+            );
+
+            buffer_pool.return_buffer_with_assignment(buffer, endpoint, false);
+        }
+    }
+
+    fn return_buffers(
+        buffer_pool: &mut BufferPool,
+        (input_buffer, input_is_silent): (OwnedAudioBuffer, bool),
+        (output_buffer, output_is_silent): (OwnedAudioBuffer, bool),
+        sidechain_buffer: Option<OwnedAudioBuffer>,
+        output_endpoint: Endpoint,
+    ) {
+        if let Some(sidechain_buffer) = sidechain_buffer {
+            buffer_pool.return_buffer(sidechain_buffer);
+        }
+
+        if input_is_silent {
+            buffer_pool.return_silent_buffer(input_buffer);
+        } else {
+            buffer_pool.return_buffer(input_buffer);
+        }
+
+        buffer_pool.return_buffer_with_assignment(output_buffer, output_endpoint, output_is_silent);
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
     use approx::{assert_relative_eq, assert_relative_ne};
 
     use crate::{
-        buffer::owned_audio_buffer::OwnedAudioBuffer,
-        graph::dsp::{DspParameterMap, DspProcessor},
+        graph::{
+            channel_matrix::ChannelMatrix,
+            dsp::{DspParameterMap, DspProcessor},
+        },
+        midi::message::MidiMessage,
     };
 
     use super::*;
@@ -283,6 +1234,163 @@ mod tests {
         }
     }
 
+    struct MidiEmitter {
+        midi_output: MidiOutputQueue,
+    }
+
+    impl DspProcessor for MidiEmitter {
+        fn process_audio(
+            &mut self,
+            _input_buffer: &dyn AudioBuffer,
+            _output_buffer: &mut dyn AudioBuffer,
+            start_time: &Timestamp,
+            _parameters: &DspParameterMap,
+        ) {
+            self.midi_output.push(
+                *start_time,
+                MidiMessage::NoteOn {
+                    channel: 0,
+                    note: 60,
+                    velocity: 100,
+                },
+            );
+        }
+
+        fn drain_midi_output(&mut self, output: &mut dyn FnMut(ScheduledMidiMessage)) {
+            self.midi_output.drain(output);
+        }
+    }
+
+    struct FillProcessor;
+
+    impl DspProcessor for FillProcessor {
+        fn process_audio(
+            &mut self,
+            _input_buffer: &dyn AudioBuffer,
+            output_buffer: &mut dyn AudioBuffer,
+            _start_time: &Timestamp,
+            _parameters: &DspParameterMap,
+        ) {
+            output_buffer.fill_with_value(1.0);
+        }
+    }
+
+    struct SidechainSubtractor;
+
+    impl DspProcessor for SidechainSubtractor {
+        fn process_audio(
+            &mut self,
+            input_buffer: &dyn AudioBuffer,
+            output_buffer: &mut dyn AudioBuffer,
+            _start_time: &Timestamp,
+            _parameters: &DspParameterMap,
+        ) {
+            output_buffer.fill_with_value(-1.0);
+            output_buffer.add_from(
+                input_buffer,
+                SampleLocation::new(0, 0),
+                SampleLocation::new(0, 0),
+                output_buffer.num_channels(),
+                output_buffer.num_frames(),
+            );
+        }
+
+        fn process_audio_with_sidechain(
+            &mut self,
+            input_buffer: &dyn AudioBuffer,
+            sidechain_buffer: &dyn AudioBuffer,
+            output_buffer: &mut dyn AudioBuffer,
+            _start_time: &Timestamp,
+            _parameters: &DspParameterMap,
+        ) {
+            for frame in 0..output_buffer.num_frames() {
+                for channel in 0..output_buffer.num_channels() {
+                    let location = SampleLocation::new(channel, frame);
+                    output_buffer.set_sample(
+                        location,
+                        input_buffer.get_sample(location) - sidechain_buffer.get_sample(location),
+                    );
+                }
+            }
+        }
+    }
+
+    struct PanickingProcessor;
+
+    impl DspProcessor for PanickingProcessor {
+        fn process_audio(
+            &mut self,
+            _input_buffer: &dyn AudioBuffer,
+            output_buffer: &mut dyn AudioBuffer,
+            _start_time: &Timestamp,
+            _parameters: &DspParameterMap,
+        ) {
+            output_buffer.fill_with_value(1.0);
+            panic!("faulty processor");
+        }
+    }
+
+    struct TailProcessor {
+        process_count: Arc<AtomicUsize>,
+    }
+
+    impl DspProcessor for TailProcessor {
+        fn process_audio(
+            &mut self,
+            _input_buffer: &dyn AudioBuffer,
+            _output_buffer: &mut dyn AudioBuffer,
+            _start_time: &Timestamp,
+            _parameters: &DspParameterMap,
+        ) {
+            self.process_count.fetch_add(1, Ordering::Relaxed);
+        }
+
+        fn tail_time(&self) -> Option<Duration> {
+            Some(Duration::from_millis(200))
+        }
+    }
+
+    struct RampProcessor;
+
+    impl DspProcessor for RampProcessor {
+        fn process_audio(
+            &mut self,
+            _input_buffer: &dyn AudioBuffer,
+            output_buffer: &mut dyn AudioBuffer,
+            _start_time: &Timestamp,
+            _parameters: &DspParameterMap,
+        ) {
+            for frame in 0..output_buffer.num_frames() {
+                output_buffer.set_sample(SampleLocation::new(0, frame), frame as f32);
+            }
+        }
+    }
+
+    struct ParameterReader {
+        parameter_id: Id,
+    }
+
+    impl DspProcessor for ParameterReader {
+        fn process_audio(
+            &mut self,
+            _input_buffer: &dyn AudioBuffer,
+            output_buffer: &mut dyn AudioBuffer,
+            start_time: &Timestamp,
+            parameters: &DspParameterMap,
+        ) {
+            let parameter = parameters.get(&self.parameter_id).unwrap();
+            let sample_rate = output_buffer.sample_rate();
+
+            for frame in 0..output_buffer.num_frames() {
+                let time = start_time.incremented_by_samples(frame, sample_rate);
+                output_buffer.set_sample(
+                    SampleLocation::new(0, frame),
+                    parameter.get_value_at_time(&time) as f32,
+                );
+            }
+        }
+    }
+
     fn make_dsp(value_to_write: f32, location_to_write: SampleLocation) -> Box<Dsp> {
         let processor = Box::new(Processor::new(value_to_write, location_to_write));
         let parameters = DspParameterMap::new();
@@ -316,6 +1424,66 @@ mod tests {
         assert_relative_eq!(audio_buffer.get_sample(location), value);
     }
 
+    #[test]
+    fn quarantines_panicking_dsp() {
+        let dsp = Box::new(Dsp::new(
+            Id::generate(),
+            Box::new(PanickingProcessor),
+            DspParameterMap::new(),
+        ));
+        let dsp_id = dsp.get_id();
+
+        let mut graph = DspGraph::new(128, 2, 44100);
+        graph.add_dsp(dsp);
+        graph.connect_to_output(Endpoint::new(dsp_id, EndpointType::Output));
+
+        let mut audio_buffer = OwnedAudioBuffer::new(128, 2, 44100);
+
+        for _ in 0..2 {
+            graph.process(&mut audio_buffer, &Timestamp::default());
+            assert_eq!(audio_buffer.get_sample(SampleLocation::new(0, 0)), 0.0);
+        }
+
+        let mut quarantined = Vec::new();
+        graph.drain_quarantined(|id| quarantined.push(id));
+        assert_eq!(quarantined, vec![dsp_id]);
+        assert!(graph.graph.get_node_mut(dsp_id).unwrap().is_quarantined());
+    }
+
+    #[test]
+    fn sleeps_after_silent_input_exceeds_tail_time() {
+        let process_count = Arc::new(AtomicUsize::new(0));
+        let tail_dsp = Box::new(Dsp::new(
+            Id::generate(),
+            Box::new(TailProcessor {
+                process_count: process_count.clone(),
+            }),
+            DspParameterMap::new(),
+        ));
+        let tail_id = tail_dsp.get_id();
+        let source = make_dsp(0.5, SampleLocation::new(0, 0));
+        let source_id = source.get_id();
+
+        let mut graph = DspGraph::new(128, 2, 1000);
+        graph.add_dsp(tail_dsp);
+        graph.add_dsp(source);
+        graph.connect_to_output(Endpoint::new(tail_id, EndpointType::Output));
+
+        let mut audio_buffer = OwnedAudioBuffer::new(128, 2, 1000);
+        for _ in 0..4 {
+            graph.process(&mut audio_buffer, &Timestamp::default());
+        }
+
+        assert_eq!(process_count.load(Ordering::Relaxed), 2);
+        assert!(graph.graph.get_node_mut(tail_id).unwrap().is_sleeping());
+
+        graph.add_connection(Connection::new(source_id, tail_id));
+        graph.process(&mut audio_buffer, &Timestamp::default());
+
+        assert_eq!(process_count.load(Ordering::Relaxed), 3);
+        assert!(!graph.graph.get_node_mut(tail_id).unwrap().is_sleeping());
+    }
+
     #[test]
     fn renders_chain() {
         let value_1 = 0.123;
@@ -350,6 +1518,299 @@ mod tests {
         assert_relative_eq!(audio_buffer.get_sample(location_2), value_2);
     }
 
+    #[test]
+    fn detached_dsp_is_silent_until_reattached() {
+        let value = 0.456;
+        let location = SampleLocation::new(0, 27);
+
+        let dsp = make_dsp(value, location);
+        let dsp_id = dsp.get_id();
+        let sample_rate = 44100;
+
+        let mut graph = DspGraph::new(128, 2, sample_rate);
+        graph.add_dsp(dsp);
+        graph.connect_to_output(Endpoint::new(dsp_id, EndpointType::Output));
+        graph.detach_dsp(dsp_id);
+
+        let mut audio_buffer = OwnedAudioBuffer::new(128, 2, sample_rate);
+        graph.process(&mut audio_buffer, &Timestamp::default());
+        assert_relative_eq!(audio_buffer.get_sample(location), 0.0);
+
+        graph.reattach_dsp(dsp_id);
+        graph.process(&mut audio_buffer, &Timestamp::default());
+        assert_relative_eq!(audio_buffer.get_sample(location), value);
+    }
+
+    #[test]
+    fn removing_connection_stops_mixing_source() {
+        let value_1 = 0.123;
+        let location_1 = SampleLocation::new(0, 27);
+
+        let dsp_1 = make_dsp(value_1, location_1);
+        let dsp_2 = make_dsp(0.0, SampleLocation::new(1, 0));
+
+        let dsp_id_1 = dsp_1.get_id();
+        let dsp_id_2 = dsp_2.get_id();
+
+        let mut graph = DspGraph::new(128, 2, 44100);
+        graph.add_dsp(dsp_1);
+        graph.add_dsp(dsp_2);
+        graph.connect_to_output(Endpoint::new(dsp_id_2, EndpointType::Output));
+        graph.add_connection(Connection::new(dsp_id_1, dsp_id_2));
+        graph.remove_connection(Connection::new(dsp_id_1, dsp_id_2));
+
+        let mut audio_buffer = OwnedAudioBuffer::new(128, 2, 44100);
+        graph.process(&mut audio_buffer, &Timestamp::default());
+        assert_relative_eq!(audio_buffer.get_sample(location_1), 0.0);
+    }
+
+    #[test]
+    fn applies_connection_gain_and_channel_matrix() {
+        let location = SampleLocation::new(0, 27);
+
+        let dsp_1 = make_dsp(0.5, location);
+        let dsp_2 = make_dsp(0.0, SampleLocation::new(1, 0));
+
+        let dsp_id_1 = dsp_1.get_id();
+        let dsp_id_2 = dsp_2.get_id();
+
+        let mut graph = DspGraph::new(128, 2, 44100);
+        graph.add_dsp(dsp_1);
+        graph.add_dsp(dsp_2);
+        graph.connect_to_output(Endpoint::new(dsp_id_2, EndpointType::Output));
+        graph.add_connection(
+            Connection::new(dsp_id_1, dsp_id_2)
+                .with_gain(0.5)
+                .with_channel_matrix(ChannelMatrix::swap_stereo()),
+        );
+
+        let mut audio_buffer = OwnedAudioBuffer::new(128, 2, 44100);
+        graph.process(&mut audio_buffer, &Timestamp::default());
+        assert_relative_eq!(audio_buffer.get_sample(location), 0.0);
+        assert_relative_eq!(audio_buffer.get_sample(SampleLocation::new(1, 27)), 0.25);
+    }
+
+    #[test]
+    fn feedback_connection_delays_source_by_maximum_block_length() {
+        let location = SampleLocation::new(1, 20);
+
+        let dsp_1 = make_dsp(1.0, SampleLocation::new(0, 10));
+        let dsp_2 = make_dsp(0.25, location);
+
+        let dsp_id_1 = dsp_1.get_id();
+        let dsp_id_2 = dsp_2.get_id();
+
+        let mut graph = DspGraph::new(128, 2, 44100);
+        graph.add_dsp(dsp_1);
+        graph.add_dsp(dsp_2);
+        graph.connect_to_output(Endpoint::new(dsp_id_1, EndpointType::Output));
+        graph.add_connection(Connection::new(dsp_id_1, dsp_id_2));
+        graph.add_connection(Connection::feedback(dsp_id_2, dsp_id_1).with_gain(0.5));
+
+        let mut audio_buffer = OwnedAudioBuffer::new(128, 2, 44100);
+        graph.process(&mut audio_buffer, &Timestamp::default());
+        graph.check_invariants();
+        assert_relative_eq!(audio_buffer.get_sample(location), 0.0);
+
+        audio_buffer.clear();
+        graph.process(&mut audio_buffer, &Timestamp::default());
+        assert_relative_eq!(audio_buffer.get_sample(location), 0.125);
+
+        graph.remove_connection(Connection::feedback(dsp_id_2, dsp_id_1));
+        audio_buffer.clear();
+        graph.process(&mut audio_buffer, &Timestamp::default());
+        graph.check_invariants();
+        assert_relative_eq!(audio_buffer.get_sample(location), 0.0);
+    }
+
+    #[test]
+    fn sidechain_connection_is_mixed_separately_from_input() {
+        let input_location = SampleLocation::new(0, 10);
+        let sidechain_location = SampleLocation::new(1, 20);
+
+        let input = make_dsp(0.5, input_location);
+        let sidechain = make_dsp(0.25, sidechain_location);
+        let subtractor = Box::new(Dsp::new(
+            Id::generate(),
+            Box::new(SidechainSubtractor),
+            DspParameterMap::new(),
+        ));
+
+        let input_id = input.get_id();
+        let sidechain_id = sidechain.get_id();
+        let subtractor_id = subtractor.get_id();
+
+        let mut graph = DspGraph::new(128, 2, 44100);
+        graph.add_dsp(input);
+        graph.add_dsp(sidechain);
+        graph.add_dsp(subtractor);
+        graph.connect_to_output(Endpoint::new(subtractor_id, EndpointType::Output));
+        graph.add_connection(Connection::new(input_id, subtractor_id));
+        graph.add_connection(Connection::to_sidechain(sidechain_id, subtractor_id));
+
+        let mut audio_buffer = OwnedAudioBuffer::new(128, 2, 44100);
+        graph.process(&mut audio_buffer, &Timestamp::default());
+        graph.check_invariants();
+        assert_relative_eq!(audio_buffer.get_sample(input_location), 0.5);
+        assert_relative_eq!(audio_buffer.get_sample(sidechain_location), -0.25);
+
+        graph.remove_connection(Connection::to_sidechain(sidechain_id, subtractor_id));
+        audio_buffer.clear();
+        graph.process(&mut audio_buffer, &Timestamp::default());
+        assert_relative_eq!(audio_buffer.get_sample(input_location), -0.5);
+    }
+
+    #[test]
+    fn degrades_lowest_priority_tier_first_and_restores() {
+        let location_1 = SampleLocation::new(0, 10);
+        let location_2 = SampleLocation::new(0, 20);
+
+        let dsp_1 = make_dsp(0.1, location_1);
+        let dsp_2 = make_dsp(0.2, location_2);
+        let dsp_3 = make_dsp(0.3, SampleLocation::new(0, 30));
+
+        let dsp_id_1 = dsp_1.get_id();
+        let dsp_id_2 = dsp_2.get_id();
+        let dsp_id_3 = dsp_3.get_id();
+
+        let mut graph = DspGraph::new(128, 2, 44100);
+        graph.add_dsp(dsp_1);
+        graph.add_dsp(dsp_2);
+        graph.add_dsp(dsp_3);
+        graph.connect_to_output(Endpoint::new(dsp_id_3, EndpointType::Output));
+        graph.add_connection(Connection::new(dsp_id_1, dsp_id_3));
+        graph.add_connection(Connection::new(dsp_id_2, dsp_id_3));
+        graph.set_dsp_priority(dsp_id_1, -2);
+        graph.set_dsp_priority(dsp_id_2, -1);
+
+        let mut audio_buffer = OwnedAudioBuffer::new(128, 2, 44100);
+        let mut render = |graph: &mut DspGraph| {
+            audio_buffer.clear();
+            graph.process(&mut audio_buffer, &Timestamp::default());
+            (
+                audio_buffer.get_sample(location_1),
+                audio_buffer.get_sample(location_2),
+            )
+        };
+
+        assert!(graph.degrade_next_tier(Degradation::Skip));
+        assert_eq!(render(&mut graph), (0.0, 0.2));
+
+        assert!(graph.degrade_next_tier(Degradation::Skip));
+        assert!(!graph.degrade_next_tier(Degradation::Skip));
+        assert_eq!(render(&mut graph), (0.0, 0.0));
+
+        graph.restore_degraded();
+        assert_eq!(render(&mut graph), (0.1, 0.2));
+
+        let mut changes = Vec::new();
+        graph.drain_degradation_changes(|id, degraded| changes.push((id, degraded)));
+        assert_eq!(changes.len(), 4);
+        assert_eq!(changes[0], (dsp_id_1, true));
+        assert!(changes[2..].iter().all(|(_, degraded)| !degraded));
+    }
+
+    #[test]
+    fn removing_connection_with_fade_ramps_out_source() {
+        let sample_rate = 1000;
+        let location = SampleLocation::new(0, 50);
+
+        let dsp_1 = make_dsp(1.0, location);
+        let dsp_2 = make_dsp(0.0, SampleLocation::new(1, 0));
+
+        let dsp_id_1 = dsp_1.get_id();
+        let dsp_id_2 = dsp_2.get_id();
+
+        let mut graph = DspGraph::new(128, 2, sample_rate);
+        graph.set_connection_fade_time(Duration::from_millis(100));
+        graph.add_dsp(dsp_1);
+        graph.add_dsp(dsp_2);
+        graph.connect_to_output(Endpoint::new(dsp_id_2, EndpointType::Output));
+        graph.add_connection(Connection::new(dsp_id_1, dsp_id_2));
+        graph.remove_connection(Connection::new(dsp_id_1, dsp_id_2));
+
+        let mut audio_buffer = OwnedAudioBuffer::new(128, 2, sample_rate);
+        graph.process(&mut audio_buffer, &Timestamp::default());
+        assert_relative_eq!(audio_buffer.get_sample(location), 0.5);
+
+        audio_buffer.clear();
+        graph.process(&mut audio_buffer, &Timestamp::default());
+        assert_relative_eq!(audio_buffer.get_sample(location), 0.0);
+    }
+
+    #[test]
+    fn applies_scheduled_connections_sample_accurately() {
+        let sample_rate = 1000;
+
+        let source = Box::new(Dsp::new(
+            Id::generate(),
+            Box::new(FillProcessor),
+            DspParameterMap::new(),
+        ));
+        let destination = make_dsp(0.0, SampleLocation::new(1, 0));
+
+        let source_id = source.get_id();
+        let destination_id = destination.get_id();
+        let connection = Connection::new(source_id, destination_id);
+
+        let mut graph = DspGraph::new(128, 2, sample_rate);
+        graph.add_dsp(source);
+        graph.add_dsp(destination);
+        graph.connect_to_output(Endpoint::new(destination_id, EndpointType::Output));
+
+        graph.schedule_connection(
+            connection.clone(),
+            Timestamp::from_samples(32.0, sample_rate),
+            ConnectionAction::Connect,
+        );
+        graph.schedule_connection(
+            connection,
+            Timestamp::from_samples(160.0, sample_rate),
+            ConnectionAction::Disconnect,
+        );
+
+        let mut audio_buffer = OwnedAudioBuffer::new(128, 2, sample_rate);
+        graph.process(&mut audio_buffer, &Timestamp::zero());
+        assert_relative_eq!(audio_buffer.get_sample(SampleLocation::new(0, 31)), 0.0);
+        assert_relative_eq!(audio_buffer.get_sample(SampleLocation::new(0, 32)), 1.0);
+
+        audio_buffer.clear();
+        graph.process(
+            &mut audio_buffer,
+            &Timestamp::from_samples(128.0, sample_rate),
+        );
+        assert_relative_eq!(audio_buffer.get_sample(SampleLocation::new(0, 31)), 1.0);
+        assert_relative_eq!(audio_buffer.get_sample(SampleLocation::new(0, 32)), 0.0);
+        assert!(!graph.graph.is_connected_to(source_id, destination_id));
+    }
+
+    #[test]
+    fn collects_midi_output_from_processors() {
+        let mut graph = DspGraph::new(512, 2, 44100);
+        let processor = Box::new(MidiEmitter {
+            midi_output: MidiOutputQueue::with_capacity(4),
+        });
+        graph.add_dsp(Box::new(Dsp::new(
+            Id::generate(),
+            processor,
+            DspParameterMap::new(),
+        )));
+
+        let mut output_buffer = OwnedAudioBuffer::new(512, 2, 44100);
+        let start_time = Timestamp::from_seconds(1.0);
+        graph.process(&mut output_buffer, &start_time);
+
+        let mut messages = Vec::new();
+        graph.drain_midi_output(|scheduled| messages.push(scheduled));
+
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].time, start_time);
+
+        messages.clear();
+        graph.drain_midi_output(|scheduled| messages.push(scheduled));
+        assert!(messages.is_empty());
+    }
+
     #[test]
     fn doesnt_write_too_many_channels() {
         let dsp = make_dsp(0.0, SampleLocation::new(0, 0));
@@ -387,4 +1848,66 @@ mod tests {
         let mut audio_buffer = OwnedAudioBuffer::new(maximum_number_of_frames * 2, 2, 44100);
         graph.process(&mut audio_buffer, &Timestamp::default());
     }
+
+    #[test]
+    fn modulates_parameters_at_audio_rate() {
+        use crate::parameter::{realtime_parameter::RealtimeAudioParameter, ParameterValue};
+        use atomic_float::AtomicF64;
+
+        let sample_rate = 44100;
+        let num_frames = 128;
+        let mut graph = DspGraph::new(num_frames, 2, sample_rate);
+
+        let source = Box::new(Dsp::new(
+            Id::generate(),
+            Box::new(RampProcessor),
+            DspParameterMap::new(),
+        ));
+        let source_id = source.get_id();
+
+        let parameter_id = Id::generate();
+        let value = ParameterValue::new(AtomicF64::new(10.0));
+        let mut parameters = DspParameterMap::new();
+        parameters.insert(
+            parameter_id,
+            RealtimeAudioParameter::new(parameter_id, value).with_range(0.0, 50.0),
+        );
+        let destination = Box::new(Dsp::new(
+            Id::generate(),
+            Box::new(ParameterReader { parameter_id }),
+            parameters,
+        ));
+        let destination_id = destination.get_id();
+
+        graph.add_dsp(source);
+        graph.add_dsp(destination);
+        graph.add_connection(
+            Connection::to_parameter(source_id, destination_id, parameter_id).with_gain(0.5),
+        );
+        graph.connect_to_output(Endpoint::new(destination_id, EndpointType::Output));
+
+        let mut audio_buffer = OwnedAudioBuffer::new(num_frames, 2, sample_rate);
+        graph.process(&mut audio_buffer, &Timestamp::default());
+
+        for frame in 0..num_frames {
+            let expected = (10.0 + frame as f32 * 0.5).min(50.0);
+            assert_relative_eq!(
+                audio_buffer.get_sample(SampleLocation::new(0, frame)),
+                expected
+            );
+        }
+
+        graph.remove_connection(Connection::to_parameter(
+            source_id,
+            destination_id,
+            parameter_id,
+        ));
+        audio_buffer.clear();
+        graph.process(
+            &mut audio_buffer,
+            &Timestamp::from_samples(128.0, sample_rate),
+        );
+
+        assert_relative_eq!(audio_buffer.get_sample(SampleLocation::new(0, 64)), 10.0);
+    }
 }
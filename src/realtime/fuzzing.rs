@@ -0,0 +1,349 @@
+use std::{sync::Arc, time::Duration};
+
+use atomic_float::AtomicF64;
+use lockfree::channel::{
+    mpsc::{self, Sender},
+    spsc::{self, Receiver},
+};
+
+use crate::{
+    audio_process::AudioProcess,
+    buffer::{
+        audio_buffer::AudioBuffer, audio_buffer_slice::AudioBufferSlice,
+        owned_audio_buffer::OwnedAudioBuffer, sample_location::SampleLocation,
+    },
+    commands::{
+        command::{Command, ParameterChangeRequest},
+        id::Id,
+        notification::Notification,
+    },
+    dsp::{constant::processor::ConstantSourceProcessor, gain::processor::GainProcessor},
+    graph::{
+        connection::Connection,
+        dsp::{Dsp, DspParameterMap, DspProcessor},
+        endpoint::{Endpoint, EndpointType},
+    },
+    parameter::{realtime_parameter::RealtimeAudioParameter, ParameterChange},
+    tempo_map::TempoMap,
+    timestamp::Timestamp,
+};
+
+use super::processor::Processor;
+
+const SAMPLE_RATE: usize = 48_000;
+const MAXIMUM_RENDER_FRAMES: usize = 1024;
+const MAXIMUM_NUMBER_OF_DSPS: usize = 32;
+const MAXIMUM_NUMBER_OF_CONNECTIONS: usize = 128;
+const MAXIMUM_SAMPLE_MAGNITUDE: f32 = 1e6;
+
+struct ByteReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+
+    fn next_u8(&mut self) -> Option<u8> {
+        let value = self.data.get(self.position).copied();
+        self.position += 1;
+        value
+    }
+
+    fn next_index(&mut self, len: usize) -> Option<usize> {
+        if len == 0 {
+            return None;
+        }
+
+        self.next_u8().map(|value| value as usize % len)
+    }
+
+    fn next_unipolar(&mut self) -> Option<f64> {
+        self.next_u8().map(|value| value as f64 / u8::MAX as f64)
+    }
+}
+
+struct FuzzDsp {
+    id: Id,
+    parameter_id: Id,
+}
+
+pub struct CommandSequenceRunner {
+    processor: Processor,
+    command_tx: Sender<Command>,
+    notification_rx: Receiver<Notification>,
+    dsps: Vec<FuzzDsp>,
+    connections: Vec<(Id, Id)>,
+    output_buffer: OwnedAudioBuffer,
+    elapsed_frames: usize,
+    initial_capacities: Vec<usize>,
+}
+
+impl Default for CommandSequenceRunner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CommandSequenceRunner {
+    pub fn new() -> Self {
+        let (command_tx, command_rx) = mpsc::create();
+        let (notification_tx, notification_rx) = spsc::create();
+        let processor = Processor::new(SAMPLE_RATE, command_rx, notification_tx);
+        let initial_capacities = processor.graph().storage_capacities();
+
+        Self {
+            processor,
+            command_tx,
+            notification_rx,
+            dsps: Vec::new(),
+            connections: Vec::new(),
+            output_buffer: OwnedAudioBuffer::new(MAXIMUM_RENDER_FRAMES, 2, SAMPLE_RATE),
+            elapsed_frames: 0,
+            initial_capacities,
+        }
+    }
+
+    pub fn run(&mut self, data: &[u8]) {
+        let mut reader = ByteReader::new(data);
+
+        while let Some(operation) = reader.next_u8() {
+            if self.apply(operation, &mut reader).is_none() {
+                break;
+            }
+        }
+
+        self.render(MAXIMUM_RENDER_FRAMES);
+    }
+
+    fn apply(&mut self, operation: u8, reader: &mut ByteReader) -> Option<()> {
+        match operation % 20 {
+            0 => self.send(Command::Start),
+            1 => self.send(Command::Stop),
+            2 => self.send(Command::Suspend),
+            3 => self.send(Command::Resume),
+            4 => self.send(Command::EnableProfiling),
+            5 => self.send(Command::DisableProfiling),
+            6 => self.add_dsp(reader.next_u8()?),
+            7 => {
+                let index = reader.next_index(self.dsps.len())?;
+                let dsp = self.dsps.remove(index);
+                self.connections
+                    .retain(|(source, destination)| *source != dsp.id && *destination != dsp.id);
+                self.send(Command::RemoveDsp(dsp.id));
+            }
+            8 => {
+                let index = reader.next_index(self.dsps.len())?;
+                self.send(Command::DetachDsp(self.dsps[index].id));
+            }
+            9 => {
+                let index = reader.next_index(self.dsps.len())?;
+                self.send(Command::ReattachDsp(self.dsps[index].id));
+            }
+            10 => {
+                let request = self.parameter_change(reader)?;
+                self.send(Command::ParameterValueChange(request));
+            }
+            11 => {
+                let num_changes = reader.next_u8()? as usize % 8;
+                let mut requests = Vec::with_capacity(num_changes);
+                for _ in 0..num_changes {
+                    requests.push(self.parameter_change(reader)?);
+                }
+                self.send(Command::ParameterValueChanges(requests));
+            }
+            12 => {
+                let connection = self.forward_connection(reader)?;
+                if self.connections.len() < MAXIMUM_NUMBER_OF_CONNECTIONS {
+                    self.connections
+                        .push((connection.source.dsp_id, connection.destination.dsp_id));
+                    self.send(Command::AddConnection(connection));
+                }
+            }
+            13 => {
+                let index = reader.next_index(self.connections.len())?;
+                let (source, destination) = self.connections.swap_remove(index);
+                self.send(Command::RemoveConnection(Connection::new(
+                    source,
+                    destination,
+                )));
+            }
+            14 => {
+                let connection = self.forward_connection(reader)?;
+                let time = self.future_time(reader)?;
+                self.send(Command::ScheduleConnection(connection, time));
+            }
+            15 => {
+                let index = reader.next_index(self.connections.len())?;
+                let (source, destination) = self.connections[index];
+                let time = self.future_time(reader)?;
+                self.send(Command::ScheduleDisconnection(
+                    Connection::new(source, destination),
+                    time,
+                ));
+            }
+            16 => {
+                let milliseconds = reader.next_u8()? as u64;
+                self.send(Command::SetConnectionFadeTime(Duration::from_millis(
+                    milliseconds,
+                )));
+            }
+            17 => {
+                let index = reader.next_index(self.dsps.len())?;
+                self.send(Command::ConnectToOutput(Endpoint::new(
+                    self.dsps[index].id,
+                    EndpointType::Output,
+                )));
+            }
+            18 => self.send(Command::DisconnectFromOutput),
+            _ => {
+                if reader.next_u8()? % 4 == 0 {
+                    let beats_per_minute = 40.0 + 200.0 * reader.next_unipolar()?;
+                    self.send(Command::SetTempoMap(Box::new(TempoMap::new(
+                        beats_per_minute,
+                    ))));
+                }
+
+                let num_frames = 1 + reader.next_u8()? as usize * 4;
+                self.render(num_frames);
+            }
+        }
+
+        Some(())
+    }
+
+    fn send(&mut self, command: Command) {
+        if self.command_tx.send(command).is_err() {
+            panic!("Processor command channel closed");
+        }
+    }
+
+    fn add_dsp(&mut self, kind: u8) {
+        if self.dsps.len() == MAXIMUM_NUMBER_OF_DSPS {
+            return;
+        }
+
+        let id = Id::generate();
+        let parameter_id = Id::generate();
+
+        let mut parameters = DspParameterMap::new();
+        parameters.insert(
+            parameter_id,
+            RealtimeAudioParameter::new(parameter_id, Arc::new(AtomicF64::new(0.5))),
+        );
+
+        let processor: Box<dyn DspProcessor + Send + Sync> = match kind % 2 {
+            0 => Box::new(ConstantSourceProcessor::new(parameter_id)),
+            _ => Box::new(GainProcessor::new(parameter_id)),
+        };
+
+        self.dsps.push(FuzzDsp { id, parameter_id });
+        self.send(Command::AddDsp(Box::new(Dsp::new(
+            id, processor, parameters,
+        ))));
+    }
+
+    fn parameter_change(&self, reader: &mut ByteReader) -> Option<ParameterChangeRequest> {
+        let dsp = &self.dsps[reader.next_index(self.dsps.len())?];
+        let value = 2.0 * reader.next_unipolar()? - 1.0;
+        let time = self.future_time(reader)?;
+
+        let change = match reader.next_u8()? % 2 {
+            0 => ParameterChange::immediate(value, time),
+            _ => ParameterChange::linear(value, time),
+        };
+
+        Some(ParameterChangeRequest {
+            dsp_id: dsp.id,
+            parameter_id: dsp.parameter_id,
+            change,
+        })
+    }
+
+    fn forward_connection(&self, reader: &mut ByteReader) -> Option<Connection> {
+        if self.dsps.len() < 2 {
+            return None;
+        }
+
+        let first = reader.next_index(self.dsps.len())?;
+        let second = reader.next_index(self.dsps.len())?;
+
+        if first == second {
+            return None;
+        }
+
+        let (source, destination) = (first.min(second), first.max(second));
+        Some(Connection::new(
+            self.dsps[source].id,
+            self.dsps[destination].id,
+        ))
+    }
+
+    fn future_time(&self, reader: &mut ByteReader) -> Option<Timestamp> {
+        let offset = reader.next_u8()? as usize * 64;
+        Some(Timestamp::from_samples(
+            (self.elapsed_frames + offset) as f64,
+            SAMPLE_RATE,
+        ))
+    }
+
+    fn render(&mut self, num_frames: usize) {
+        let num_frames = num_frames.min(MAXIMUM_RENDER_FRAMES);
+        let mut buffer = AudioBufferSlice::new(&mut self.output_buffer, 0, num_frames);
+        self.processor.process(&mut buffer);
+        self.elapsed_frames += num_frames;
+
+        for frame in 0..num_frames {
+            for channel in 0..buffer.num_channels() {
+                let sample = buffer.get_sample(SampleLocation::new(channel, frame));
+                assert!(sample.is_finite() && sample.abs() < MAXIMUM_SAMPLE_MAGNITUDE);
+            }
+        }
+
+        while self.notification_rx.recv().is_ok() {}
+
+        self.processor.graph().check_invariants();
+        assert_eq!(
+            self.processor.graph().storage_capacities(),
+            self.initial_capacities
+        );
+    }
+}
+
+pub fn run_command_sequence(data: &[u8]) {
+    CommandSequenceRunner::new().run(data);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utility::random::Random;
+
+    use super::*;
+
+    #[test]
+    fn survives_random_command_sequences() {
+        let mut random = Random::with_seed(2232);
+
+        for _ in 0..64 {
+            let data: Vec<u8> = (0..512).map(|_| random.next_u64() as u8).collect();
+            run_command_sequence(&data);
+        }
+    }
+
+    #[test]
+    fn renders_a_built_graph() {
+        let mut runner = CommandSequenceRunner::new();
+        runner.run(&[6, 0, 6, 1, 12, 0, 1, 17, 1, 0, 19, 1, 255]);
+
+        assert_eq!(runner.dsps.len(), 2);
+        assert_eq!(runner.connections.len(), 1);
+        assert_eq!(
+            runner
+                .output_buffer
+                .get_sample(SampleLocation::new(1, MAXIMUM_RENDER_FRAMES - 1)),
+            0.25
+        );
+    }
+}
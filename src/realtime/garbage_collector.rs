@@ -2,10 +2,20 @@ use std::{thread, time};
 
 use lockfree::channel::{spsc::Receiver, RecvErr};
 
-use crate::graph::dsp::Dsp;
+use crate::{
+    commands::command::{Command, ParameterChangeRequest},
+    dsp::sampler::processor::SharedSample,
+    graph::dsp::Dsp,
+    tempo_map::TempoMap,
+};
 
+#[allow(clippy::enum_variant_names)]
 pub enum GarbageCollectionCommand {
     DisposeDsp(Box<Dsp>),
+    DisposeParameterChanges(Vec<ParameterChangeRequest>),
+    DisposeTempoMap(Box<TempoMap>),
+    DisposeCommand(Box<Command>),
+    DisposeSample(SharedSample),
 }
 
 pub fn run_garbage_collector(mut receive_channel: Receiver<GarbageCollectionCommand>) {
@@ -23,5 +33,9 @@ fn handle_garabage_collection_event(command: GarbageCollectionCommand) {
         GarbageCollectionCommand::DisposeDsp(dsp) => {
             println!("Destroying DSP with ID: {:?}", dsp.get_id())
         }
+        GarbageCollectionCommand::DisposeParameterChanges(changes) => drop(changes),
+        GarbageCollectionCommand::DisposeTempoMap(tempo_map) => drop(tempo_map),
+        GarbageCollectionCommand::DisposeCommand(command) => drop(command),
+        GarbageCollectionCommand::DisposeSample(sample) => drop(sample),
     }
 }
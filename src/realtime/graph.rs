@@ -1,4 +1,4 @@
-use std::collections::{hash_map::Keys, HashMap};
+use std::collections::HashMap;
 
 use crate::commands::id::Id;
 
@@ -16,6 +16,7 @@ pub enum Direction {
 pub struct Graph<NodeData, EdgeData> {
     nodes: NodeMap<NodeData>,
     edges: EdgeMap<EdgeData>,
+    node_order: Vec<Id>,
 }
 
 impl<NodeData, EdgeData> Graph<NodeData, EdgeData> {
@@ -23,6 +24,7 @@ impl<NodeData, EdgeData> Graph<NodeData, EdgeData> {
         Self {
             nodes: NodeMap::with_capacity(number_of_nodes),
             edges: EdgeMap::with_capacity(number_of_edges),
+            node_order: Vec::with_capacity(number_of_nodes),
         }
     }
 
@@ -84,17 +86,73 @@ impl<NodeData, EdgeData> Graph<NodeData, EdgeData> {
         edge_id
     }
 
-    pub fn remove_edge(&mut self, from_node_id: Id, to_node_id: Id) {
-        // TODO: Fixup connections
-
+    pub fn remove_edge(&mut self, from_node_id: Id, to_node_id: Id) -> Option<EdgeData> {
         let id = self
             .edges
             .iter()
             .find(|(_, edge)| edge.from_node_id == from_node_id && edge.to_node_id == to_node_id)
             .map(|(id, _)| *id);
 
-        if let Some(id) = id {
-            self.edges.remove(&id);
+        match id {
+            Some(id) => self.remove_edge_with_id(id),
+            None => None,
+        }
+    }
+
+    fn find_edge_id(&self, from_node_id: Id, to_node_id: Id) -> Option<Id> {
+        let first = self.nodes.get(&from_node_id)?.outgoing?;
+
+        std::iter::once(first)
+            .chain(EdgeIterator::new(first, Direction::Outgoing, &self.edges))
+            .find(|id| {
+                self.edges
+                    .get(id)
+                    .is_some_and(|edge| edge.to_node_id == to_node_id)
+            })
+    }
+
+    pub fn get_edge_data(&self, from_node_id: Id, to_node_id: Id) -> Option<&EdgeData> {
+        let id = self.find_edge_id(from_node_id, to_node_id)?;
+        self.edges.get(&id).map(|edge| &edge.edge_data)
+    }
+
+    pub fn get_edge_data_mut(&mut self, from_node_id: Id, to_node_id: Id) -> Option<&mut EdgeData> {
+        let id = self.find_edge_id(from_node_id, to_node_id)?;
+        self.edges.get_mut(&id).map(|edge| &mut edge.edge_data)
+    }
+
+    fn remove_edge_with_id(&mut self, id: Id) -> Option<EdgeData> {
+        let edge = self.edges.remove(&id)?;
+
+        self.unlink_edge(id, edge.from_node_id, edge.next_out, Direction::Outgoing);
+        self.unlink_edge(id, edge.to_node_id, edge.next_in, Direction::Incoming);
+
+        Some(edge.edge_data)
+    }
+
+    fn unlink_edge(&mut self, id: Id, node_id: Id, next: Option<Id>, direction: Direction) {
+        if let Some(node) = self.nodes.get_mut(&node_id) {
+            let first = match direction {
+                Direction::Outgoing => &mut node.outgoing,
+                Direction::Incoming => &mut node.incoming,
+            };
+
+            if *first == Some(id) {
+                *first = next;
+                return;
+            }
+        }
+
+        for edge in self.edges.values_mut() {
+            let link = match direction {
+                Direction::Outgoing => &mut edge.next_out,
+                Direction::Incoming => &mut edge.next_in,
+            };
+
+            if *link == Some(id) {
+                *link = next;
+                return;
+            }
         }
     }
 
@@ -105,10 +163,23 @@ impl<NodeData, EdgeData> Graph<NodeData, EdgeData> {
     }
 
     pub fn remove_node(&mut self, id: Id) -> Option<NodeData> {
-        // TODO: Remove associated connections
+        while let Some(edge_id) = self
+            .edges
+            .iter()
+            .find(|(_, edge)| edge.from_node_id == id || edge.to_node_id == id)
+            .map(|(edge_id, _)| *edge_id)
+        {
+            self.remove_edge_with_id(edge_id);
+        }
+
+        self.node_order.retain(|node_id| *node_id != id);
         self.nodes.remove(&id).map(|node| node.node_data)
     }
 
+    pub fn contains_node(&self, id: Id) -> bool {
+        self.nodes.contains_key(&id)
+    }
+
     pub fn get_node_mut(&mut self, id: Id) -> Option<&mut NodeData> {
         self.nodes.get_mut(&id).map(|node| &mut node.node_data)
     }
@@ -120,6 +191,7 @@ impl<NodeData, EdgeData> Graph<NodeData, EdgeData> {
     pub fn add_node_with_id(&mut self, id: Id, node_data: NodeData) {
         assert!(!self.nodes.contains_key(&id));
         self.nodes.insert(id, Node::new(node_data));
+        self.node_order.push(id);
     }
 
     pub fn is_connected_to(&self, from_node_id: Id, to_node_id: Id) -> bool {
@@ -127,25 +199,78 @@ impl<NodeData, EdgeData> Graph<NodeData, EdgeData> {
             .any(|id| id == to_node_id)
     }
 
-    fn _edge_iter(&self, edge_id: Id, direction: Direction) -> EdgeIterator<EdgeData> {
+    fn _edge_iter(&self, edge_id: Id, direction: Direction) -> EdgeIterator<'_, EdgeData> {
         EdgeIterator::new(edge_id, direction, &self.edges)
     }
 
-    pub fn node_iter(&self, node_id: Id, direction: Direction) -> NodeIterator<NodeData, EdgeData> {
+    pub fn node_iter(
+        &self,
+        node_id: Id,
+        direction: Direction,
+    ) -> NodeIterator<'_, NodeData, EdgeData> {
         NodeIterator::new(node_id, direction, &self.nodes, &self.edges)
     }
 
+    #[cfg(test)]
     pub fn num_connections(&self, node_id: Id, direction: Direction) -> usize {
         self.node_iter(node_id, direction).count()
     }
 
-    pub fn all_node_ids(&self) -> Keys<Id, Node<NodeData>> {
-        self.nodes.keys()
+    pub fn nodes_mut(&mut self) -> impl Iterator<Item = &mut NodeData> {
+        self.nodes.values_mut().map(|node| &mut node.node_data)
+    }
+
+    pub fn all_node_ids(&self) -> std::slice::Iter<'_, Id> {
+        self.node_order.iter()
     }
 
     pub fn num_nodes(&self) -> usize {
         self.nodes.len()
     }
+
+    #[cfg(any(test, feature = "fuzzing"))]
+    pub fn capacities(&self) -> (usize, usize, usize) {
+        (
+            self.nodes.capacity(),
+            self.edges.capacity(),
+            self.node_order.capacity(),
+        )
+    }
+
+    #[cfg(any(test, feature = "fuzzing"))]
+    pub fn check_invariants(&self) {
+        for edge in self.edges.values() {
+            assert!(self.nodes.contains_key(&edge.from_node_id));
+            assert!(self.nodes.contains_key(&edge.to_node_id));
+        }
+
+        for direction in [Direction::Outgoing, Direction::Incoming] {
+            let mut num_linked_edges = 0;
+
+            for (node_id, node) in self.nodes.iter() {
+                let first = match direction {
+                    Direction::Outgoing => node.outgoing,
+                    Direction::Incoming => node.incoming,
+                };
+
+                if let Some(first) = first {
+                    let edge = &self.edges[&first];
+                    let linked_node_id = match direction {
+                        Direction::Outgoing => edge.from_node_id,
+                        Direction::Incoming => edge.to_node_id,
+                    };
+                    assert!(linked_node_id == *node_id);
+
+                    num_linked_edges +=
+                        1 + EdgeIterator::new(first, direction, &self.edges).count();
+                }
+            }
+
+            assert_eq!(num_linked_edges, self.edges.len());
+        }
+
+        assert_eq!(self.node_order.len(), self.nodes.len());
+    }
 }
 
 pub struct EdgeIterator<'a, EdgeData> {
@@ -287,6 +412,52 @@ mod tests {
         assert!(connected_nodes.contains(&node_d_id));
     }
 
+    #[test]
+    fn remove_edge_keeps_remaining_edges_linked() {
+        let mut graph = Graph::with_capacity(5, 5);
+
+        let node_a_id = graph._add_node(());
+        let node_b_id = graph._add_node(());
+        let node_c_id = graph._add_node(());
+        let node_d_id = graph._add_node(());
+
+        graph.add_edge(node_a_id, node_b_id, ());
+        graph.add_edge(node_a_id, node_c_id, ());
+        graph.add_edge(node_a_id, node_d_id, ());
+
+        graph.remove_edge(node_a_id, node_c_id);
+
+        let connected_nodes: Vec<Id> = graph.node_iter(node_a_id, Direction::Outgoing).collect();
+        assert_eq!(connected_nodes, vec![node_b_id, node_d_id]);
+
+        graph.remove_edge(node_a_id, node_b_id);
+
+        let connected_nodes: Vec<Id> = graph.node_iter(node_a_id, Direction::Outgoing).collect();
+        assert_eq!(connected_nodes, vec![node_d_id]);
+        assert_eq!(graph.num_connections(node_b_id, Direction::Incoming), 0);
+    }
+
+    #[test]
+    fn remove_node_removes_its_edges() {
+        let mut graph = Graph::with_capacity(5, 5);
+
+        let node_a_id = graph._add_node(());
+        let node_b_id = graph._add_node(());
+        let node_c_id = graph._add_node(());
+
+        graph.add_edge(node_a_id, node_b_id, ());
+        graph.add_edge(node_b_id, node_c_id, ());
+        graph.add_edge(node_a_id, node_c_id, ());
+
+        graph.remove_node(node_b_id);
+
+        let connected_nodes: Vec<Id> = graph.node_iter(node_c_id, Direction::Incoming).collect();
+        assert_eq!(connected_nodes, vec![node_a_id]);
+
+        let connected_nodes: Vec<Id> = graph.node_iter(node_a_id, Direction::Outgoing).collect();
+        assert_eq!(connected_nodes, vec![node_c_id]);
+    }
+
     #[test]
     fn iterate_incoming_nodes() {
         let mut graph = Graph::with_capacity(5, 5);
@@ -307,4 +478,24 @@ mod tests {
         assert!(connected_nodes.contains(&node_c_id));
         assert!(connected_nodes.contains(&node_d_id));
     }
+
+    #[test]
+    fn finds_edge_data() {
+        let mut graph = Graph::with_capacity(5, 5);
+
+        let node_a_id = graph._add_node(());
+        let node_b_id = graph._add_node(());
+        let node_c_id = graph._add_node(());
+
+        graph.add_edge(node_a_id, node_b_id, 1);
+        graph.add_edge(node_a_id, node_c_id, 2);
+
+        assert_eq!(graph.get_edge_data(node_a_id, node_c_id), Some(&2));
+        assert_eq!(graph.get_edge_data(node_b_id, node_a_id), None);
+
+        if let Some(data) = graph.get_edge_data_mut(node_a_id, node_b_id) {
+            *data = 3;
+        }
+        assert_eq!(graph.get_edge_data(node_a_id, node_b_id), Some(&3));
+    }
 }
@@ -1,8 +1,16 @@
-mod dsp_graph;
+mod budget_monitor;
+mod connection_fade;
+pub(crate) mod connection_schedule;
+pub(crate) mod dsp_graph;
 mod edge;
-mod garbage_collector;
+mod feedback_buffer;
+#[cfg(any(test, feature = "fuzzing"))]
+pub mod fuzzing;
+pub(crate) mod garbage_collector;
 mod graph;
+mod modulation_matrix;
 mod node;
 mod periodic_notification;
 pub(crate) mod processor;
+pub(crate) mod profiler;
 mod topological_sort;
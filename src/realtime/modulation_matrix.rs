@@ -0,0 +1,524 @@
+use std::time::Duration;
+
+use crate::{
+    commands::id::Id,
+    parameter::modulation::{
+        ModulationCommand, ModulationCurve, ModulationDestination, ModulationSource,
+    },
+    utility::realtime_log::{self, LogLevel},
+};
+
+use super::dsp_graph::DspGraph;
+
+const MAXIMUM_NUMBER_OF_SOURCES: usize = 64;
+const MAXIMUM_NUMBER_OF_SLOTS: usize = 256;
+const DEFAULT_CROSSFADE_TIME: Duration = Duration::from_millis(20);
+
+#[derive(Clone, Copy, PartialEq)]
+enum EnvelopeStage {
+    Idle,
+    Attack,
+    Decay,
+    Sustain,
+    Release,
+}
+
+struct SourceState {
+    id: Id,
+    source: ModulationSource,
+    phase: f64,
+    stage: EnvelopeStage,
+    level: f64,
+    release_level: f64,
+    removed: bool,
+}
+
+impl SourceState {
+    fn new(id: Id, source: ModulationSource) -> Self {
+        Self {
+            id,
+            source,
+            phase: 0.0,
+            stage: EnvelopeStage::Idle,
+            level: 0.0,
+            release_level: 0.0,
+            removed: false,
+        }
+    }
+
+    fn value(&self) -> f64 {
+        match self.source {
+            ModulationSource::Lfo { shape, .. } | ModulationSource::SyncedLfo { shape, .. } => {
+                shape.value_at_phase(self.phase)
+            }
+            ModulationSource::Envelope { .. } | ModulationSource::Midi(_) => self.level,
+        }
+    }
+
+    fn set_gate(&mut self, open: bool) {
+        if open {
+            self.stage = EnvelopeStage::Attack;
+        } else if self.stage != EnvelopeStage::Idle {
+            self.stage = EnvelopeStage::Release;
+            self.release_level = self.level;
+        }
+    }
+
+    fn advance(&mut self, elapsed: f64, beats_per_minute: f64) {
+        match self.source {
+            ModulationSource::Lfo { frequency, .. } => {
+                self.phase = (self.phase + frequency * elapsed).rem_euclid(1.0);
+            }
+            ModulationSource::SyncedLfo { division, .. } => {
+                let frequency = division.frequency_at_tempo(beats_per_minute);
+                self.phase = (self.phase + frequency * elapsed).rem_euclid(1.0);
+            }
+            ModulationSource::Envelope {
+                attack,
+                decay,
+                sustain,
+                release,
+            } => self.advance_envelope(elapsed, attack, decay, sustain, release),
+            ModulationSource::Midi(_) => (),
+        }
+    }
+
+    fn advance_envelope(
+        &mut self,
+        elapsed: f64,
+        attack: Duration,
+        decay: Duration,
+        sustain: f64,
+        release: Duration,
+    ) {
+        let rate = |duration: Duration, distance: f64| {
+            if duration.is_zero() {
+                f64::INFINITY
+            } else {
+                distance / duration.as_secs_f64()
+            }
+        };
+
+        match self.stage {
+            EnvelopeStage::Idle | EnvelopeStage::Sustain => (),
+            EnvelopeStage::Attack => {
+                self.level += rate(attack, 1.0) * elapsed;
+                if self.level >= 1.0 {
+                    self.level = 1.0;
+                    self.stage = EnvelopeStage::Decay;
+                }
+            }
+            EnvelopeStage::Decay => {
+                self.level -= rate(decay, 1.0 - sustain) * elapsed;
+                if self.level <= sustain {
+                    self.level = sustain;
+                    self.stage = EnvelopeStage::Sustain;
+                }
+            }
+            EnvelopeStage::Release => {
+                self.level -= rate(release, self.release_level) * elapsed;
+                if self.level <= 0.0 {
+                    self.level = 0.0;
+                    self.stage = EnvelopeStage::Idle;
+                }
+            }
+        }
+    }
+}
+
+struct SlotState {
+    id: Id,
+    source_id: Id,
+    destination: ModulationDestination,
+    depth_from: f64,
+    depth_to: f64,
+    curve_from: ModulationCurve,
+    curve_to: ModulationCurve,
+    fade: f64,
+    removing: bool,
+}
+
+impl SlotState {
+    fn value(&self, source_value: f64) -> f64 {
+        let from = self.curve_from.apply(source_value) * self.depth_from;
+        let to = self.curve_to.apply(source_value) * self.depth_to;
+        from + (to - from) * self.fade
+    }
+
+    fn crossfade_to(&mut self, depth: f64, curve: ModulationCurve) {
+        self.depth_from = self.depth_from + (self.depth_to - self.depth_from) * self.fade;
+        if self.fade >= 0.5 {
+            self.curve_from = self.curve_to;
+        }
+
+        self.depth_to = depth;
+        self.curve_to = curve;
+        self.fade = 0.0;
+    }
+
+    fn is_finished(&self) -> bool {
+        self.removing && self.fade >= 1.0
+    }
+}
+
+struct DestinationState {
+    destination: ModulationDestination,
+    offset: f64,
+    in_use: bool,
+}
+
+pub struct RealtimeModulationMatrix {
+    sources: Vec<SourceState>,
+    slots: Vec<SlotState>,
+    destinations: Vec<DestinationState>,
+    crossfade_time: Duration,
+}
+
+impl RealtimeModulationMatrix {
+    pub fn new() -> Self {
+        Self {
+            sources: Vec::with_capacity(MAXIMUM_NUMBER_OF_SOURCES),
+            slots: Vec::with_capacity(MAXIMUM_NUMBER_OF_SLOTS),
+            destinations: Vec::with_capacity(MAXIMUM_NUMBER_OF_SLOTS),
+            crossfade_time: DEFAULT_CROSSFADE_TIME,
+        }
+    }
+
+    pub fn handle_command(&mut self, command: ModulationCommand) {
+        match command {
+            ModulationCommand::AddSource(id, source) => {
+                if self.sources.len() == MAXIMUM_NUMBER_OF_SOURCES {
+                    realtime_log::log(LogLevel::Warning, "Modulation source limit reached");
+                    return;
+                }
+
+                self.sources.push(SourceState::new(id, source));
+            }
+            ModulationCommand::UpdateSource(id, source) => {
+                if let Some(state) = self.find_source(id) {
+                    state.source = source;
+                }
+            }
+            ModulationCommand::RemoveSource(id) => {
+                if let Some(state) = self.find_source(id) {
+                    state.removed = true;
+                }
+
+                for slot in self.slots.iter_mut().filter(|slot| slot.source_id == id) {
+                    slot.crossfade_to(0.0, slot.curve_to);
+                    slot.removing = true;
+                }
+            }
+            ModulationCommand::SetGate(id, open) => {
+                if let Some(state) = self.find_source(id) {
+                    state.set_gate(open);
+                }
+            }
+            ModulationCommand::SetSourceValue(id, value) => {
+                if let Some(state) = self.find_source(id) {
+                    state.level = value;
+                }
+            }
+            ModulationCommand::AddSlot {
+                slot_id,
+                source_id,
+                destination,
+                depth,
+                curve,
+            } => self.add_slot(slot_id, source_id, destination, depth, curve),
+            ModulationCommand::SetDepth(id, depth) => {
+                if let Some(slot) = self.find_slot(id) {
+                    slot.crossfade_to(depth, slot.curve_to);
+                }
+            }
+            ModulationCommand::SetCurve(id, curve) => {
+                if let Some(slot) = self.find_slot(id) {
+                    slot.crossfade_to(slot.depth_to, curve);
+                }
+            }
+            ModulationCommand::RemoveSlot(id) => {
+                if let Some(slot) = self.find_slot(id) {
+                    slot.crossfade_to(0.0, slot.curve_to);
+                    slot.removing = true;
+                }
+            }
+            ModulationCommand::SetCrossfadeTime(crossfade_time) => {
+                self.crossfade_time = crossfade_time
+            }
+        }
+    }
+
+    fn add_slot(
+        &mut self,
+        slot_id: Id,
+        source_id: Id,
+        destination: ModulationDestination,
+        depth: f64,
+        curve: ModulationCurve,
+    ) {
+        if self.slots.len() == MAXIMUM_NUMBER_OF_SLOTS {
+            realtime_log::log(LogLevel::Warning, "Modulation slot limit reached");
+            return;
+        }
+
+        if !self.destinations.iter().any(|state| {
+            state.destination.dsp_id == destination.dsp_id
+                && state.destination.parameter_id == destination.parameter_id
+        }) {
+            self.destinations.push(DestinationState {
+                destination,
+                offset: 0.0,
+                in_use: true,
+            });
+        }
+
+        self.slots.push(SlotState {
+            id: slot_id,
+            source_id,
+            destination,
+            depth_from: 0.0,
+            depth_to: depth,
+            curve_from: curve,
+            curve_to: curve,
+            fade: 0.0,
+            removing: false,
+        });
+    }
+
+    fn find_source(&mut self, id: Id) -> Option<&mut SourceState> {
+        self.sources.iter_mut().find(|state| state.id == id)
+    }
+
+    fn find_slot(&mut self, id: Id) -> Option<&mut SlotState> {
+        self.slots.iter_mut().find(|slot| slot.id == id)
+    }
+
+    pub fn process(
+        &mut self,
+        graph: &mut DspGraph,
+        num_frames: usize,
+        sample_rate: usize,
+        beats_per_minute: f64,
+    ) {
+        if self.sources.is_empty() && self.destinations.is_empty() {
+            return;
+        }
+
+        let elapsed = num_frames as f64 / sample_rate as f64;
+
+        self.advance_fades(elapsed);
+        self.accumulate_offsets();
+        self.apply_offsets(graph);
+        self.advance_sources(elapsed, beats_per_minute);
+    }
+
+    fn accumulate_offsets(&mut self) {
+        for state in self.destinations.iter_mut() {
+            state.offset = 0.0;
+            state.in_use = false;
+        }
+
+        for slot in self.slots.iter() {
+            let source_value = match self.sources.iter().find(|state| state.id == slot.source_id) {
+                Some(state) => state.value(),
+                None => continue,
+            };
+
+            if let Some(state) = self.destinations.iter_mut().find(|state| {
+                state.destination.dsp_id == slot.destination.dsp_id
+                    && state.destination.parameter_id == slot.destination.parameter_id
+            }) {
+                let range = state.destination.maximum - state.destination.minimum;
+                state.offset += slot.value(source_value) * range;
+                state.in_use = true;
+            }
+        }
+    }
+
+    fn apply_offsets(&mut self, graph: &mut DspGraph) {
+        for state in self.destinations.iter() {
+            graph.set_parameter_modulation(
+                state.destination.dsp_id,
+                state.destination.parameter_id,
+                state.offset,
+                state.destination.minimum,
+                state.destination.maximum,
+            );
+        }
+
+        self.destinations.retain(|state| state.in_use);
+    }
+
+    fn advance_fades(&mut self, elapsed: f64) {
+        let fade_step = if self.crossfade_time.is_zero() {
+            1.0
+        } else {
+            elapsed / self.crossfade_time.as_secs_f64()
+        };
+
+        for slot in self.slots.iter_mut() {
+            slot.fade = (slot.fade + fade_step).min(1.0);
+        }
+
+        self.slots.retain(|slot| !slot.is_finished());
+    }
+
+    fn advance_sources(&mut self, elapsed: f64, beats_per_minute: f64) {
+        for source in self.sources.iter_mut() {
+            source.advance(elapsed, beats_per_minute);
+        }
+
+        let slots = &self.slots;
+        self.sources.retain(|source| {
+            !source.removed || slots.iter().any(|slot| slot.source_id == source.id)
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use crate::parameter::{modulation::LfoShape, tempo_sync::NoteDivision};
+
+    use super::*;
+
+    fn destination() -> ModulationDestination {
+        ModulationDestination {
+            dsp_id: Id::generate(),
+            parameter_id: Id::generate(),
+            minimum: 0.0,
+            maximum: 10.0,
+        }
+    }
+
+    fn offset(matrix: &RealtimeModulationMatrix) -> f64 {
+        matrix
+            .destinations
+            .first()
+            .map_or(0.0, |state| state.offset)
+    }
+
+    #[test]
+    fn crossfades_depth_edits_and_removals() {
+        let mut matrix = RealtimeModulationMatrix::new();
+        let source_id = Id::generate();
+        let slot_id = Id::generate();
+
+        matrix.handle_command(ModulationCommand::SetCrossfadeTime(Duration::from_millis(
+            4,
+        )));
+        matrix.handle_command(ModulationCommand::AddSource(
+            source_id,
+            ModulationSource::Lfo {
+                frequency: 0.0,
+                shape: LfoShape::Square,
+            },
+        ));
+        matrix.handle_command(ModulationCommand::AddSlot {
+            slot_id,
+            source_id,
+            destination: destination(),
+            depth: 0.5,
+            curve: ModulationCurve::Linear,
+        });
+
+        let mut offsets = Vec::new();
+        for block in 0..12 {
+            match block {
+                6 => matrix.handle_command(ModulationCommand::SetDepth(slot_id, 1.0)),
+                9 => matrix.handle_command(ModulationCommand::RemoveSlot(slot_id)),
+                _ => (),
+            }
+
+            matrix.advance_fades(0.001);
+            matrix.accumulate_offsets();
+            offsets.push(offset(&matrix));
+            matrix.destinations.retain(|state| state.in_use);
+            matrix.advance_sources(0.001, 120.0);
+        }
+
+        let expected = [
+            1.25, 2.5, 3.75, 5.0, 5.0, 5.0, 6.25, 7.5, 8.75, 6.5625, 4.375, 2.1875,
+        ];
+        for (offset, expected) in offsets.iter().zip(expected) {
+            assert_relative_eq!(*offset, expected, epsilon = 1e-9);
+        }
+    }
+
+    #[test]
+    fn envelope_follows_gate() {
+        let mut source = SourceState::new(
+            Id::generate(),
+            ModulationSource::Envelope {
+                attack: Duration::from_millis(10),
+                decay: Duration::from_millis(10),
+                sustain: 0.5,
+                release: Duration::from_millis(10),
+            },
+        );
+
+        source.set_gate(true);
+        source.advance(0.005, 120.0);
+        assert_relative_eq!(source.value(), 0.5);
+        source.advance(0.005, 120.0);
+        assert_relative_eq!(source.value(), 1.0);
+        source.advance(0.020, 120.0);
+        assert_relative_eq!(source.value(), 0.5);
+
+        source.set_gate(false);
+        source.advance(0.005, 120.0);
+        assert_relative_eq!(source.value(), 0.25);
+        source.advance(0.010, 120.0);
+        assert_relative_eq!(source.value(), 0.0);
+        assert!(source.stage == EnvelopeStage::Idle);
+    }
+
+    #[test]
+    fn synced_lfo_follows_tempo() {
+        let mut source = SourceState::new(
+            Id::generate(),
+            ModulationSource::SyncedLfo {
+                division: NoteDivision::new(1, 4),
+                shape: LfoShape::Saw,
+            },
+        );
+
+        source.advance(0.25, 120.0);
+        assert_relative_eq!(source.phase, 0.5);
+
+        source.advance(0.25, 60.0);
+        assert_relative_eq!(source.phase, 0.75);
+    }
+
+    #[test]
+    fn removed_sources_fade_their_slots_out() {
+        let mut matrix = RealtimeModulationMatrix::new();
+        let source_id = Id::generate();
+
+        matrix.handle_command(ModulationCommand::SetCrossfadeTime(Duration::ZERO));
+        matrix.handle_command(ModulationCommand::AddSource(
+            source_id,
+            ModulationSource::Lfo {
+                frequency: 0.0,
+                shape: LfoShape::Square,
+            },
+        ));
+        matrix.handle_command(ModulationCommand::AddSlot {
+            slot_id: Id::generate(),
+            source_id,
+            destination: destination(),
+            depth: 1.0,
+            curve: ModulationCurve::Linear,
+        });
+        matrix.advance_fades(0.001);
+        matrix.advance_sources(0.001, 120.0);
+
+        matrix.handle_command(ModulationCommand::RemoveSource(source_id));
+        matrix.advance_fades(0.001);
+        matrix.advance_sources(0.001, 120.0);
+
+        assert!(matrix.slots.is_empty());
+        assert!(matrix.sources.is_empty());
+    }
+}
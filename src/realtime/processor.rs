@@ -1,27 +1,61 @@
 use crate::{
     audio_process::AudioProcess,
-    buffer::{audio_buffer::AudioBuffer, audio_buffer_slice::AudioBufferSlice},
-    commands::{command::Command, notification::Notification},
+    buffer::{
+        audio_buffer::AudioBuffer, audio_buffer_slice::AudioBufferSlice,
+        immutable_audio_buffer_slice::ImmutableAudioBufferSlice,
+    },
+    commands::{
+        command::{Command, ParameterChangeRequest},
+        notification::Notification,
+    },
+    graph::{
+        dsp::Dsp,
+        input_mapping::{InputMapping, MappedInput},
+    },
+    host_transport::HostTransport,
     timestamp::Timestamp,
+    utility::{
+        realtime_log::{self, LogLevel},
+        trace::{self, Category},
+    },
 };
+use std::time::Instant;
+
 use lockfree::channel::{mpsc::Receiver, spsc::Sender};
 
-use super::{dsp_graph::DspGraph, periodic_notification::PeriodicNotification};
+use super::{
+    budget_monitor::{BudgetAction, BudgetMonitor},
+    connection_schedule::ConnectionAction,
+    dsp_graph::DspGraph,
+    modulation_matrix::RealtimeModulationMatrix,
+    periodic_notification::PeriodicNotification,
+};
 
-const MAXIMUM_NUMBER_OF_FRAMES: usize = 512;
-const MAXIMUM_NUMBER_OF_CHANNELS: usize = 2;
+pub const MAXIMUM_NUMBER_OF_FRAMES: usize = 512;
+pub const MAXIMUM_NUMBER_OF_CHANNELS: usize = 2;
 const POSITION_INTERVAL_HZ: f64 = 30.0;
+const PROFILING_INTERVAL_HZ: f64 = 1.0;
+const HOST_JUMP_TOLERANCE_SAMPLES: usize = 1;
+pub const MAXIMUM_SCHEDULED_COMMANDS: usize = 256;
 
 pub struct Processor {
     started: bool,
+    suspended: bool,
+    recording_automation: bool,
     sample_rate: usize,
     command_rx: Receiver<Command>,
     notification_tx: Sender<Notification>,
 
     sample_position: usize,
+    host_transport: Option<HostTransport>,
+    scheduled_commands: Vec<(Timestamp, Box<Command>)>,
     graph: DspGraph,
+    input_mapping: Option<InputMapping>,
+    modulation: RealtimeModulationMatrix,
+    budget_monitor: BudgetMonitor,
 
     position_notification: PeriodicNotification,
+    profiling_notification: PeriodicNotification,
 }
 
 impl Processor {
@@ -32,76 +66,302 @@ impl Processor {
     ) -> Self {
         Self {
             started: false,
+            suspended: false,
+            recording_automation: false,
             sample_rate,
             command_rx,
             notification_tx,
             sample_position: 0,
+            host_transport: None,
+            scheduled_commands: Vec::with_capacity(MAXIMUM_SCHEDULED_COMMANDS),
             graph: DspGraph::new(
                 MAXIMUM_NUMBER_OF_FRAMES,
                 MAXIMUM_NUMBER_OF_CHANNELS,
                 sample_rate,
             ),
+            input_mapping: None,
+            modulation: RealtimeModulationMatrix::new(),
+            budget_monitor: BudgetMonitor::default(),
             position_notification: PeriodicNotification::new(sample_rate, POSITION_INTERVAL_HZ),
+            profiling_notification: PeriodicNotification::new(sample_rate, PROFILING_INTERVAL_HZ),
         }
     }
 
-    fn process_graph(&mut self, output_buffer: &mut dyn AudioBuffer) {
-        let current_time = self.current_time();
+    #[cfg(any(test, feature = "fuzzing"))]
+    pub fn graph(&self) -> &DspGraph {
+        &self.graph
+    }
 
+    fn process_graph(
+        &mut self,
+        input_buffer: Option<&dyn AudioBuffer>,
+        output_buffer: &mut dyn AudioBuffer,
+    ) {
         let mut offset = 0;
 
         while offset < output_buffer.num_frames() {
-            let num_frames = std::cmp::min(
+            self.apply_scheduled_commands();
+
+            if !self.is_clock_running() {
+                break;
+            }
+
+            let mut num_frames = std::cmp::min(
                 output_buffer.num_frames() - offset,
                 self.get_maximum_number_of_frames(),
             );
 
-            let mut audio_buffer = AudioBufferSlice::new(output_buffer, offset, num_frames);
+            if let Some((time, _)) = self.scheduled_commands.first() {
+                num_frames = num_frames.min(self.frames_until(time));
+            }
+
+            if self.started {
+                let mut audio_buffer = AudioBufferSlice::new(output_buffer, offset, num_frames);
+                let input_slice = input_buffer
+                    .filter(|input_buffer| offset < input_buffer.num_frames())
+                    .map(|input_buffer| ImmutableAudioBufferSlice::new(input_buffer, offset));
+                let mapped_input = input_slice
+                    .as_ref()
+                    .zip(self.input_mapping.as_ref())
+                    .map(|(slice, mapping)| MappedInput::new(slice, mapping));
+                let block_time = self.current_time();
+                let beats_per_minute = self.graph.tempo_at(&block_time);
+
+                self.modulation.process(
+                    &mut self.graph,
+                    num_frames,
+                    self.sample_rate,
+                    beats_per_minute,
+                );
 
-            self.graph.process(&mut audio_buffer, &current_time);
+                self.graph.process_with_input(
+                    mapped_input
+                        .as_ref()
+                        .map(|input| input as &dyn AudioBuffer)
+                        .or(input_slice.as_ref().map(|slice| slice as &dyn AudioBuffer)),
+                    &mut audio_buffer,
+                    &block_time,
+                );
+            }
 
+            self.update_position(num_frames);
             offset += num_frames;
         }
     }
+
+    fn is_clock_running(&self) -> bool {
+        self.started
+            || self
+                .scheduled_commands
+                .iter()
+                .any(|(_, command)| matches!(**command, Command::Start))
+    }
+
+    fn frames_until(&self, time: &Timestamp) -> usize {
+        let current_time = self.current_time();
+
+        if *time <= current_time {
+            0
+        } else {
+            Dsp::frame_at_time(&(*time - current_time), self.sample_rate)
+        }
+    }
+
+    fn schedule_command(&mut self, time: Timestamp, command: Box<Command>) {
+        if self.scheduled_commands.len() == self.scheduled_commands.capacity() {
+            realtime_log::log(LogLevel::Warning, "Command schedule full");
+            self.graph.dispose_command(command);
+            return;
+        }
+
+        let index = self
+            .scheduled_commands
+            .iter()
+            .position(|(scheduled_time, _)| *scheduled_time > time)
+            .unwrap_or(self.scheduled_commands.len());
+
+        self.scheduled_commands.insert(index, (time, command));
+    }
+
+    fn apply_scheduled_commands(&mut self) {
+        while self
+            .scheduled_commands
+            .first()
+            .is_some_and(|(time, _)| self.frames_until(time) == 0)
+        {
+            let (_, mut command) = self.scheduled_commands.remove(0);
+            let scheduled = std::mem::replace(&mut *command, Command::Start);
+            self.graph.dispose_command(command);
+            self.apply_command(scheduled);
+        }
+    }
 }
 
 impl AudioProcess for Processor {
     fn process(&mut self, output_buffer: &mut dyn AudioBuffer) {
+        self.process_block(None, output_buffer);
+    }
+
+    fn process_with_input(
+        &mut self,
+        input_buffer: &dyn AudioBuffer,
+        output_buffer: &mut dyn AudioBuffer,
+    ) {
+        self.process_block(Some(input_buffer), output_buffer);
+    }
+
+    fn set_host_transport(&mut self, transport: &HostTransport) {
+        self.started = transport.playing;
+        self.graph.set_host_tempo(transport.beats_per_minute);
+        self.graph.set_transport_loop(transport.loop_beats);
+
+        let host_position = transport.sample_position(self.sample_rate);
+        let jumped = host_position.abs_diff(self.sample_position) > HOST_JUMP_TOLERANCE_SAMPLES;
+        if jumped {
+            self.sample_position = host_position;
+        }
+
+        let changed = self.host_transport.is_none_or(|previous| {
+            HostTransport {
+                beat_position: transport.beat_position,
+                ..previous
+            } != *transport
+        });
+
+        if jumped || changed {
+            self.send_notficiation(Notification::HostTransport(*transport));
+        }
+
+        if jumped {
+            self.send_notficiation(Notification::Position(self.current_time()));
+        }
+
+        self.host_transport = Some(*transport);
+    }
+}
+
+impl Processor {
+    fn process_block(
+        &mut self,
+        input_buffer: Option<&dyn AudioBuffer>,
+        output_buffer: &mut dyn AudioBuffer,
+    ) {
+        let _span = trace::span("process", Category::Block, None);
+
         output_buffer.clear();
 
         self.process_commands();
 
-        if !self.started {
+        if self.suspended || !self.is_clock_running() {
             return;
         }
 
         let num_frames = output_buffer.num_frames();
-        self.process_graph(output_buffer);
-        self.update_position(num_frames);
+        let process_start = self.budget_monitor.is_enabled().then(Instant::now);
+        self.process_graph(input_buffer, output_buffer);
+
+        if let Some(process_start) = process_start {
+            self.check_budget(process_start, num_frames);
+        }
+
         self.notify_position(num_frames);
+        self.notify_profiling(num_frames);
+        self.notify_midi_output();
+        self.notify_quarantined();
+        self.notify_idle();
+        self.notify_degraded();
+    }
+
+    fn check_budget(&mut self, process_start: Instant, num_frames: usize) {
+        match self.budget_monitor.record_block(
+            process_start.elapsed(),
+            num_frames,
+            self.sample_rate,
+        ) {
+            BudgetAction::Degrade(degradation) => {
+                self.send_notficiation(Notification::BudgetOverrun);
+                self.graph.degrade_next_tier(degradation);
+            }
+            BudgetAction::Restore => self.graph.restore_degraded(),
+            BudgetAction::None => (),
+        }
     }
-}
 
-impl Processor {
     fn process_commands(&mut self) {
         while let Ok(command) = self.command_rx.recv() {
-            match command {
-                Command::Start => self.started = true,
-                Command::Stop => self.started = false,
+            trace::instant(command.name(), Category::Command, None);
+            self.apply_command(command);
+        }
+    }
 
-                Command::AddDsp(dsp) => self.graph.add_dsp(dsp),
-                Command::RemoveDsp(id) => self.graph.remove_dsp(id),
+    fn apply_command(&mut self, command: Command) {
+        match command {
+            Command::Start => self.started = true,
+            Command::Stop => self.started = false,
+            Command::Suspend => self.suspended = true,
+            Command::Resume => self.suspended = false,
 
-                Command::ParameterValueChange(change_request) => {
-                    self.graph.request_parameter_change(change_request)
-                }
+            Command::EnableProfiling => self.graph.set_profiling_enabled(true),
+            Command::DisableProfiling => self.graph.set_profiling_enabled(false),
+
+            Command::AddDsp(dsp) => self.graph.add_dsp(dsp),
+            Command::RemoveDsp(id) => self.graph.remove_dsp(id),
+            Command::DetachDsp(id) => self.graph.detach_dsp(id),
+            Command::ReattachDsp(id) => self.graph.reattach_dsp(id),
+            Command::StopDsp(id, time, allow_tail) => self.graph.stop_dsp(id, time, allow_tail),
+            Command::SetDspPriority(id, priority) => self.graph.set_dsp_priority(id, priority),
 
-                Command::AddConnection(connection) => self.graph.add_connection(connection),
-                Command::RemoveConnection(connection) => self.graph.remove_connection(connection),
-                Command::ConnectToOutput(output_connection) => {
-                    self.graph.connect_to_output(output_connection)
+            Command::ParameterValueChange(change_request) => {
+                self.check_for_late_change(&change_request);
+                self.record_parameter_change(&change_request);
+                self.graph.request_parameter_change(change_request)
+            }
+            Command::ParameterValueChanges(change_requests) => {
+                for change_request in change_requests.iter() {
+                    self.record_parameter_change(change_request);
+                }
+                self.graph.request_parameter_changes(change_requests)
+            }
+            Command::ParameterTempoSync(request) => self.graph.set_parameter_tempo_sync(request),
+            Command::ParameterGesture(gesture) => {
+                if self.recording_automation {
+                    self.send_notficiation(Notification::ParameterGesture(gesture));
                 }
             }
+            Command::SetAutomationRecording(enabled) => self.recording_automation = enabled,
+            Command::Modulation(command) => self.modulation.handle_command(command),
+            Command::SetTempoMap(tempo_map) => self.graph.set_tempo_map(tempo_map),
+            Command::SetTransportLoop(loop_beats) => self.graph.set_transport_loop(loop_beats),
+            Command::SetRenderQuality(quality) => self.graph.set_render_quality(quality),
+            Command::SetRealtimeBudget(budget) => {
+                if self.budget_monitor.set_budget(budget) == BudgetAction::Restore {
+                    self.graph.restore_degraded();
+                }
+            }
+
+            Command::AddConnection(connection) => self.graph.add_connection(connection),
+            Command::RemoveConnection(connection) => self.graph.remove_connection(connection),
+            Command::ScheduleConnection(connection, time) => {
+                self.graph
+                    .schedule_connection(connection, time, ConnectionAction::Connect)
+            }
+            Command::ScheduleDisconnection(connection, time) => {
+                self.graph
+                    .schedule_connection(connection, time, ConnectionAction::Disconnect)
+            }
+            Command::SetConnectionFadeTime(fade_time) => {
+                self.graph.set_connection_fade_time(fade_time)
+            }
+            Command::ConnectToOutput(output_connection) => {
+                self.graph.connect_to_output(output_connection)
+            }
+            Command::DisconnectFromOutput => self.graph.disconnect_from_output(),
+            Command::ConnectInput(id) => self.graph.connect_input(id),
+            Command::DisconnectInput(id) => self.graph.disconnect_input(id),
+            Command::ConnectOutputMix(id) => self.graph.connect_output_mix(id),
+            Command::DisconnectOutputMix(id) => self.graph.disconnect_output_mix(id),
+            Command::SetInputMapping(mapping) => self.input_mapping = mapping,
+            Command::At(time, command) => self.schedule_command(time, command),
         }
     }
 
@@ -109,8 +369,32 @@ impl Processor {
         MAXIMUM_NUMBER_OF_FRAMES
     }
 
+    fn record_parameter_change(&mut self, change_request: &ParameterChangeRequest) {
+        if self.recording_automation {
+            self.send_notficiation(Notification::ParameterChange(
+                change_request.parameter_id,
+                change_request.change,
+            ));
+        }
+    }
+
+    fn check_for_late_change(&self, change_request: &ParameterChangeRequest) {
+        let end_time = change_request.change.get_end_time();
+        let current_time = self.current_time();
+
+        if end_time > Timestamp::zero() && end_time < current_time {
+            realtime_log::log_value(
+                LogLevel::Warning,
+                "Late parameter change (seconds)",
+                (current_time - end_time).get_seconds(),
+            );
+        }
+    }
+
     fn send_notficiation(&mut self, notification: Notification) {
-        let _ = self.notification_tx.send(notification);
+        if self.notification_tx.send(notification).is_err() {
+            realtime_log::log(LogLevel::Debug, "Notification dropped");
+        }
     }
 
     fn update_position(&mut self, num_samples: usize) {
@@ -126,4 +410,67 @@ impl Processor {
             self.send_notficiation(Notification::Position(self.current_time()));
         }
     }
+
+    fn notify_midi_output(&mut self) {
+        let notification_tx = &mut self.notification_tx;
+        self.graph.drain_midi_output(|scheduled| {
+            if notification_tx
+                .send(Notification::MidiOutput(scheduled))
+                .is_err()
+            {
+                realtime_log::log(LogLevel::Warning, "MIDI output notification dropped");
+            }
+        });
+    }
+
+    fn notify_quarantined(&mut self) {
+        let notification_tx = &mut self.notification_tx;
+        self.graph.drain_quarantined(|id| {
+            if notification_tx
+                .send(Notification::NodeQuarantined(id))
+                .is_err()
+            {
+                realtime_log::log(LogLevel::Warning, "Quarantine notification dropped");
+            }
+        });
+    }
+
+    fn notify_idle(&mut self) {
+        let notification_tx = &mut self.notification_tx;
+        self.graph.drain_idle(|id| {
+            if notification_tx.send(Notification::NodeIdle(id)).is_err() {
+                realtime_log::log(LogLevel::Warning, "Idle notification dropped");
+            }
+        });
+    }
+
+    fn notify_degraded(&mut self) {
+        let notification_tx = &mut self.notification_tx;
+        self.graph.drain_degradation_changes(|id, degraded| {
+            if notification_tx
+                .send(Notification::NodeDegraded(id, degraded))
+                .is_err()
+            {
+                realtime_log::log(LogLevel::Warning, "Degradation notification dropped");
+            }
+        });
+    }
+
+    fn notify_profiling(&mut self, num_samples: usize) {
+        if !self.graph.is_profiling_enabled() {
+            return;
+        }
+
+        if self.profiling_notification.increment(num_samples) {
+            let notification_tx = &mut self.notification_tx;
+            self.graph.drain_profiling_report(|profile| {
+                if notification_tx
+                    .send(Notification::NodeProfile(profile))
+                    .is_err()
+                {
+                    realtime_log::log(LogLevel::Debug, "Profiling notification dropped");
+                }
+            });
+        }
+    }
 }
@@ -0,0 +1,138 @@
+use std::{collections::HashMap, time::Duration};
+
+use crate::commands::id::Id;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NodeProfile {
+    pub dsp_id: Id,
+    pub mean: Duration,
+    pub max: Duration,
+    pub num_blocks: usize,
+}
+
+#[derive(Default)]
+struct NodeTimings {
+    total: Duration,
+    max: Duration,
+    num_blocks: usize,
+}
+
+pub struct Profiler {
+    enabled: bool,
+    capacity: usize,
+    timings: HashMap<Id, NodeTimings>,
+}
+
+impl Profiler {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            enabled: false,
+            capacity,
+            timings: HashMap::with_capacity(capacity),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        self.timings.clear();
+    }
+
+    pub fn record(&mut self, dsp_id: Id, duration: Duration) {
+        if !self.enabled {
+            return;
+        }
+
+        if self.timings.len() >= self.capacity && !self.timings.contains_key(&dsp_id) {
+            return;
+        }
+
+        let timings = self.timings.entry(dsp_id).or_default();
+        timings.total += duration;
+        timings.max = std::cmp::max(timings.max, duration);
+        timings.num_blocks += 1;
+    }
+
+    pub fn remove(&mut self, dsp_id: Id) {
+        self.timings.remove(&dsp_id);
+    }
+
+    pub fn drain_report(&mut self, mut report: impl FnMut(NodeProfile)) {
+        for (dsp_id, timings) in self.timings.iter_mut() {
+            if timings.num_blocks == 0 {
+                continue;
+            }
+
+            report(NodeProfile {
+                dsp_id: *dsp_id,
+                mean: timings.total / timings.num_blocks as u32,
+                max: timings.max,
+                num_blocks: timings.num_blocks,
+            });
+
+            *timings = NodeTimings::default();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collect_report(profiler: &mut Profiler) -> Vec<NodeProfile> {
+        let mut profiles = Vec::new();
+        profiler.drain_report(|profile| profiles.push(profile));
+        profiles
+    }
+
+    #[test]
+    fn records_nothing_when_disabled() {
+        let mut profiler = Profiler::with_capacity(4);
+        profiler.record(Id::generate(), Duration::from_micros(10));
+        assert!(collect_report(&mut profiler).is_empty());
+    }
+
+    #[test]
+    fn reports_mean_and_max() {
+        let mut profiler = Profiler::with_capacity(4);
+        profiler.set_enabled(true);
+
+        let dsp_id = Id::generate();
+        profiler.record(dsp_id, Duration::from_micros(10));
+        profiler.record(dsp_id, Duration::from_micros(30));
+        profiler.record(dsp_id, Duration::from_micros(20));
+
+        let profiles = collect_report(&mut profiler);
+        assert_eq!(profiles.len(), 1);
+        assert_eq!(profiles[0].dsp_id, dsp_id);
+        assert_eq!(profiles[0].mean, Duration::from_micros(20));
+        assert_eq!(profiles[0].max, Duration::from_micros(30));
+        assert_eq!(profiles[0].num_blocks, 3);
+    }
+
+    #[test]
+    fn resets_after_report() {
+        let mut profiler = Profiler::with_capacity(4);
+        profiler.set_enabled(true);
+
+        let dsp_id = Id::generate();
+        profiler.record(dsp_id, Duration::from_micros(10));
+        assert_eq!(collect_report(&mut profiler).len(), 1);
+        assert!(collect_report(&mut profiler).is_empty());
+    }
+
+    #[test]
+    fn ignores_nodes_beyond_capacity() {
+        let mut profiler = Profiler::with_capacity(2);
+        profiler.set_enabled(true);
+
+        for _ in 0..3 {
+            profiler.record(Id::generate(), Duration::from_micros(10));
+        }
+
+        assert_eq!(collect_report(&mut profiler).len(), 2);
+    }
+}
@@ -23,22 +23,44 @@ impl TopologicalSort {
         &self.order
     }
 
-    pub fn sort<NodeData, EdgeData>(&mut self, graph: &Graph<NodeData, EdgeData>) -> &[Id] {
+    #[cfg(any(test, feature = "fuzzing"))]
+    pub fn capacities(&self) -> (usize, usize, usize) {
+        (
+            self.dependency_count.capacity(),
+            self.order.capacity(),
+            self.ready_to_process.capacity(),
+        )
+    }
+
+    pub fn sort<NodeData, EdgeData>(
+        &mut self,
+        graph: &Graph<NodeData, EdgeData>,
+        is_feedback: impl Fn(&EdgeData) -> bool,
+    ) -> &[Id] {
         self.dependency_count.clear();
         self.order.clear();
         self.ready_to_process.clear();
 
+        let is_dependency = |source_id: Id, destination_id: Id| {
+            graph
+                .get_edge_data(source_id, destination_id)
+                .is_some_and(|edge_data| !is_feedback(edge_data))
+        };
+
         for node_id in graph.all_node_ids() {
-            let num_incoming_nodes = graph.num_connections(*node_id, Direction::Incoming);
+            let num_incoming_nodes = graph
+                .node_iter(*node_id, Direction::Incoming)
+                .filter(|source_id| is_dependency(*source_id, *node_id))
+                .count();
             self.dependency_count.insert(*node_id, num_incoming_nodes);
         }
 
-        while let Some(next_node_id) = self.node_without_dependencies() {
+        while let Some(next_node_id) = self.node_without_dependencies(graph) {
             self.order.push(next_node_id);
             self.dependency_count.remove(&next_node_id);
 
             for node_id in graph.all_node_ids() {
-                if graph.is_connected_to(next_node_id, *node_id) {
+                if is_dependency(next_node_id, *node_id) {
                     let previous_value = self.dependency_count.get_mut(node_id).unwrap();
                     assert!(*previous_value > 0);
                     *previous_value -= 1;
@@ -51,11 +73,14 @@ impl TopologicalSort {
         &self.order
     }
 
-    fn node_without_dependencies(&self) -> Option<Id> {
-        self.dependency_count
-            .iter()
-            .find(|(_, count)| **count == 0)
-            .map(|(id, _)| *id)
+    fn node_without_dependencies<NodeData, EdgeData>(
+        &self,
+        graph: &Graph<NodeData, EdgeData>,
+    ) -> Option<Id> {
+        graph
+            .all_node_ids()
+            .find(|id| self.dependency_count.get(id) == Some(&0))
+            .copied()
     }
 }
 
@@ -87,7 +112,7 @@ mod tests {
         graph.add_edge(d_id, e_id, ());
 
         let mut topo_sort = TopologicalSort::with_capacity(5);
-        let sorted = topo_sort.sort(&graph);
+        let sorted = topo_sort.sort(&graph, |_| false);
 
         assert_eq!(sorted.len(), 5);
         assert_eq!(sorted[0], a_id);
@@ -96,4 +121,47 @@ mod tests {
         assert!(sorted[3] == c_id || sorted[3] == d_id);
         assert_eq!(sorted[4], e_id);
     }
+
+    #[test]
+    fn breaks_ties_by_insertion_order() {
+        let mut graph = Graph::with_capacity(6, 6);
+
+        let ids: Vec<Id> = (100..106)
+            .rev()
+            .map(|value| {
+                let id = Id::with_value(value);
+                graph.add_node_with_id(id, ());
+                id
+            })
+            .collect();
+
+        graph.add_edge(ids[0], ids[5], ());
+        graph.add_edge(ids[3], ids[1], ());
+
+        let mut topo_sort = TopologicalSort::with_capacity(6);
+        let sorted = topo_sort.sort(&graph, |_| false).to_vec();
+        assert_eq!(sorted, vec![ids[0], ids[2], ids[3], ids[1], ids[4], ids[5]]);
+
+        graph.remove_node(ids[2]);
+        graph.add_node_with_id(ids[2], ());
+        let sorted = topo_sort.sort(&graph, |_| false).to_vec();
+        assert_eq!(sorted, vec![ids[0], ids[3], ids[1], ids[4], ids[5], ids[2]]);
+    }
+
+    #[test]
+    fn ignores_feedback_edges() {
+        let mut graph = Graph::with_capacity(3, 3);
+
+        let a_id = graph._add_node(String::from("A"));
+        let b_id = graph._add_node(String::from("B"));
+        let c_id = graph._add_node(String::from("C"));
+
+        graph.add_edge(a_id, b_id, false);
+        graph.add_edge(b_id, c_id, false);
+        graph.add_edge(c_id, a_id, true);
+
+        let mut topo_sort = TopologicalSort::with_capacity(3);
+        let sorted = topo_sort.sort(&graph, |is_feedback| *is_feedback);
+        assert_eq!(sorted, [a_id, b_id, c_id]);
+    }
 }
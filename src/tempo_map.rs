@@ -0,0 +1,148 @@
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TempoChange {
+    pub beat: f64,
+    pub beats_per_minute: f64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TempoMap {
+    changes: Vec<TempoChange>,
+}
+
+impl Default for TempoMap {
+    fn default() -> Self {
+        Self::new(120.0)
+    }
+}
+
+impl TempoMap {
+    pub fn new(beats_per_minute: f64) -> Self {
+        assert!(beats_per_minute > 0.0);
+
+        Self {
+            changes: vec![TempoChange {
+                beat: 0.0,
+                beats_per_minute,
+            }],
+        }
+    }
+
+    pub fn add_tempo_change(&mut self, beat: f64, beats_per_minute: f64) {
+        assert!(beats_per_minute > 0.0);
+        assert!(beat >= 0.0);
+
+        self.changes.retain(|change| change.beat != beat);
+        self.changes.push(TempoChange {
+            beat,
+            beats_per_minute,
+        });
+        self.changes
+            .sort_by(|a, b| a.beat.partial_cmp(&b.beat).unwrap());
+    }
+
+    pub fn set_constant_tempo(&mut self, beats_per_minute: f64) {
+        assert!(beats_per_minute > 0.0);
+
+        self.changes.truncate(1);
+        self.changes[0] = TempoChange {
+            beat: 0.0,
+            beats_per_minute,
+        };
+    }
+
+    pub fn is_constant_tempo(&self, beats_per_minute: f64) -> bool {
+        self.changes.len() == 1 && self.changes[0].beats_per_minute == beats_per_minute
+    }
+
+    pub fn tempo_changes(&self) -> &[TempoChange] {
+        &self.changes
+    }
+
+    pub fn tempo_at_beat(&self, beat: f64) -> f64 {
+        self.changes
+            .iter()
+            .take_while(|change| change.beat <= beat)
+            .last()
+            .unwrap_or(&self.changes[0])
+            .beats_per_minute
+    }
+
+    pub fn seconds_at_beat(&self, beat: f64) -> f64 {
+        let mut seconds = 0.0;
+        let mut previous = self.changes[0];
+
+        for change in self.changes.iter().skip(1) {
+            if change.beat >= beat {
+                break;
+            }
+
+            seconds += (change.beat - previous.beat) * 60.0 / previous.beats_per_minute;
+            previous = *change;
+        }
+
+        seconds + (beat - previous.beat) * 60.0 / previous.beats_per_minute
+    }
+
+    pub fn beat_at_seconds(&self, seconds: f64) -> f64 {
+        let mut elapsed = 0.0;
+        let mut previous = self.changes[0];
+
+        for change in self.changes.iter().skip(1) {
+            let segment = (change.beat - previous.beat) * 60.0 / previous.beats_per_minute;
+            if elapsed + segment >= seconds {
+                break;
+            }
+
+            elapsed += segment;
+            previous = *change;
+        }
+
+        previous.beat + (seconds - elapsed) * previous.beats_per_minute / 60.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use super::*;
+
+    #[test]
+    fn constant_tempo() {
+        let tempo_map = TempoMap::new(120.0);
+        assert_relative_eq!(tempo_map.seconds_at_beat(4.0), 2.0);
+        assert_relative_eq!(tempo_map.beat_at_seconds(2.0), 4.0);
+    }
+
+    #[test]
+    fn honours_tempo_changes() {
+        let mut tempo_map = TempoMap::new(120.0);
+        tempo_map.add_tempo_change(4.0, 60.0);
+
+        assert_relative_eq!(tempo_map.seconds_at_beat(4.0), 2.0);
+        assert_relative_eq!(tempo_map.seconds_at_beat(6.0), 4.0);
+        assert_relative_eq!(tempo_map.beat_at_seconds(4.0), 6.0);
+        assert_relative_eq!(tempo_map.beat_at_seconds(1.0), 2.0);
+        assert_relative_eq!(tempo_map.tempo_at_beat(3.9), 120.0);
+        assert_relative_eq!(tempo_map.tempo_at_beat(4.0), 60.0);
+    }
+
+    #[test]
+    fn replaces_tempo_at_same_beat() {
+        let mut tempo_map = TempoMap::new(120.0);
+        tempo_map.add_tempo_change(0.0, 90.0);
+        assert_eq!(tempo_map.tempo_changes().len(), 1);
+        assert_relative_eq!(tempo_map.tempo_at_beat(0.0), 90.0);
+    }
+
+    #[test]
+    fn sets_constant_tempo_in_place() {
+        let mut tempo_map = TempoMap::new(120.0);
+        tempo_map.add_tempo_change(4.0, 60.0);
+        assert!(!tempo_map.is_constant_tempo(120.0));
+
+        tempo_map.set_constant_tempo(90.0);
+        assert!(tempo_map.is_constant_tempo(90.0));
+        assert_relative_eq!(tempo_map.seconds_at_beat(6.0), 4.0);
+    }
+}
@@ -0,0 +1,165 @@
+use std::fmt;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+const DROPPED_FRAMES_PER_MINUTE: u64 = 2;
+const DROP_FRAME_FRAMES_PER_MINUTE: u64 = 60 * 30 - DROPPED_FRAMES_PER_MINUTE;
+const DROP_FRAME_FRAMES_PER_TEN_MINUTES: u64 = 10 * DROP_FRAME_FRAMES_PER_MINUTE + 2;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FrameRate {
+    Fps23976,
+    Fps24,
+    Fps25,
+    Fps2997NonDrop,
+    Fps2997Drop,
+    Fps30,
+}
+
+impl FrameRate {
+    pub fn frames_per_second(&self) -> f64 {
+        match self {
+            FrameRate::Fps23976 => 24_000.0 / 1_001.0,
+            FrameRate::Fps24 => 24.0,
+            FrameRate::Fps25 => 25.0,
+            FrameRate::Fps2997NonDrop | FrameRate::Fps2997Drop => 30_000.0 / 1_001.0,
+            FrameRate::Fps30 => 30.0,
+        }
+    }
+
+    pub fn nominal_frames_per_second(&self) -> u64 {
+        match self {
+            FrameRate::Fps23976 | FrameRate::Fps24 => 24,
+            FrameRate::Fps25 => 25,
+            FrameRate::Fps2997NonDrop | FrameRate::Fps2997Drop | FrameRate::Fps30 => 30,
+        }
+    }
+
+    pub fn is_drop_frame(&self) -> bool {
+        *self == FrameRate::Fps2997Drop
+    }
+
+    pub fn frames_per_day(&self) -> u64 {
+        let frames = SECONDS_PER_DAY * self.nominal_frames_per_second();
+
+        if self.is_drop_frame() {
+            frames - DROPPED_FRAMES_PER_MINUTE * (24 * 60 - 24 * 6)
+        } else {
+            frames
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Timecode {
+    pub hours: u32,
+    pub minutes: u32,
+    pub seconds: u32,
+    pub frames: u32,
+    pub rate: FrameRate,
+}
+
+impl Timecode {
+    pub fn new(hours: u32, minutes: u32, seconds: u32, frames: u32, rate: FrameRate) -> Self {
+        Self {
+            hours,
+            minutes,
+            seconds,
+            frames,
+            rate,
+        }
+    }
+
+    pub fn from_frame_count(frame_count: u64, rate: FrameRate) -> Self {
+        let mut frame_count = frame_count % rate.frames_per_day();
+
+        if rate.is_drop_frame() {
+            let ten_minutes = frame_count / DROP_FRAME_FRAMES_PER_TEN_MINUTES;
+            let remainder = frame_count % DROP_FRAME_FRAMES_PER_TEN_MINUTES;
+
+            frame_count += 9 * DROPPED_FRAMES_PER_MINUTE * ten_minutes;
+
+            if remainder > DROPPED_FRAMES_PER_MINUTE {
+                frame_count += DROPPED_FRAMES_PER_MINUTE
+                    * ((remainder - DROPPED_FRAMES_PER_MINUTE) / DROP_FRAME_FRAMES_PER_MINUTE);
+            }
+        }
+
+        let frames_per_second = rate.nominal_frames_per_second();
+        let total_seconds = frame_count / frames_per_second;
+
+        Self {
+            hours: (total_seconds / 3600) as u32,
+            minutes: (total_seconds / 60 % 60) as u32,
+            seconds: (total_seconds % 60) as u32,
+            frames: (frame_count % frames_per_second) as u32,
+            rate,
+        }
+    }
+
+    pub fn frame_count(&self) -> u64 {
+        let total_seconds =
+            self.hours as u64 * 3600 + self.minutes as u64 * 60 + self.seconds as u64;
+        let frame_count =
+            total_seconds * self.rate.nominal_frames_per_second() + self.frames as u64;
+
+        if !self.rate.is_drop_frame() {
+            return frame_count;
+        }
+
+        let total_minutes = self.hours as u64 * 60 + self.minutes as u64;
+        frame_count - DROPPED_FRAMES_PER_MINUTE * (total_minutes - total_minutes / 10)
+    }
+
+    pub fn is_valid(&self) -> bool {
+        let dropped = self.rate.is_drop_frame()
+            && self.seconds == 0
+            && !self.minutes.is_multiple_of(10)
+            && (self.frames as u64) < DROPPED_FRAMES_PER_MINUTE;
+
+        self.hours < 24
+            && self.minutes < 60
+            && self.seconds < 60
+            && (self.frames as u64) < self.rate.nominal_frames_per_second()
+            && !dropped
+    }
+}
+
+impl fmt::Display for Timecode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let separator = if self.rate.is_drop_frame() { ';' } else { ':' };
+
+        write!(
+            f,
+            "{:02}:{:02}:{:02}{}{:02}",
+            self.hours, self.minutes, self.seconds, separator, self.frames
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drop_frame_skips_first_two_frames_of_most_minutes() {
+        let rate = FrameRate::Fps2997Drop;
+
+        let before = Timecode::new(0, 0, 59, 29, rate);
+        let after = Timecode::from_frame_count(before.frame_count() + 1, rate);
+        assert_eq!(after, Timecode::new(0, 1, 0, 2, rate));
+        assert_eq!(after.to_string(), "00:01:00;02");
+
+        let tenth =
+            Timecode::from_frame_count(Timecode::new(0, 9, 59, 29, rate).frame_count() + 1, rate);
+        assert_eq!(tenth, Timecode::new(0, 10, 0, 0, rate));
+
+        assert_eq!(Timecode::new(1, 0, 0, 0, rate).frame_count(), 107_892);
+        assert!(!Timecode::new(0, 1, 0, 1, rate).is_valid());
+
+        for frame_count in (0..rate.frames_per_day()).step_by(997) {
+            let timecode = Timecode::from_frame_count(frame_count, rate);
+            assert!(timecode.is_valid());
+            assert_eq!(timecode.frame_count(), frame_count);
+        }
+    }
+}
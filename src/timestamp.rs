@@ -1,7 +1,11 @@
 use std::ops::Sub;
 
+use crate::timecode::{FrameRate, Timecode};
+
 type FixedPoint = fixed::types::I32F32;
 
+const TIMECODE_FRAME_TOLERANCE: f64 = 1e-6;
+
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Timestamp {
     seconds: FixedPoint,
@@ -19,13 +23,13 @@ impl Eq for Timestamp {}
 
 impl PartialOrd for Timestamp {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        self.seconds.partial_cmp(&other.seconds)
+        Some(self.cmp(other))
     }
 }
 
 impl Ord for Timestamp {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.partial_cmp(other).unwrap_or(std::cmp::Ordering::Equal)
+        self.seconds.cmp(&other.seconds)
     }
 }
 
@@ -77,13 +81,48 @@ impl Timestamp {
             seconds: self.seconds + FixedPoint::from_num(num_seconds),
         }
     }
+
+    pub fn from_timecode(timecode: &Timecode) -> Self {
+        Self::from_seconds(timecode.frame_count() as f64 / timecode.rate.frames_per_second())
+    }
+
+    pub fn to_timecode(self, rate: FrameRate) -> Timecode {
+        let frames = self.get_seconds().max(0.0) * rate.frames_per_second();
+        Timecode::from_frame_count((frames + TIMECODE_FRAME_TOLERANCE).floor() as u64, rate)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use approx::assert_relative_eq;
+    use proptest::prelude::*;
 
     use super::*;
+
+    const RESOLUTION: f64 = 1.0 / 4_294_967_296.0;
+
+    fn sample_rate_strategy() -> impl Strategy<Value = usize> {
+        prop::sample::select(vec![8_000, 22_050, 44_100, 48_000, 88_200, 96_000, 192_000])
+    }
+
+    #[test]
+    fn converts_to_and_from_timecode() {
+        let timecode = Timecode::new(1, 2, 3, 4, FrameRate::Fps2997Drop);
+        let timestamp = Timestamp::from_timecode(&timecode);
+        assert_relative_eq!(
+            timestamp.get_seconds(),
+            111_582.0 * 1.001 / 30.0,
+            epsilon = 1e-6
+        );
+        assert_eq!(timestamp.to_timecode(FrameRate::Fps2997Drop), timecode);
+
+        let timestamp = Timestamp::from_seconds(90.5);
+        assert_eq!(
+            timestamp.to_timecode(FrameRate::Fps25),
+            Timecode::new(0, 1, 30, 12, FrameRate::Fps25)
+        );
+    }
+
     #[test]
     fn it_increments() {
         let sample_rate = 44_100;
@@ -91,4 +130,64 @@ mod tests {
         let after = before.incremented_by_samples(sample_rate, sample_rate);
         assert_relative_eq!(after.get_seconds() - before.get_seconds(), 1.0);
     }
+
+    proptest! {
+        #[test]
+        fn samples_round_trip(samples in 0u64..1_000_000_000, sample_rate in sample_rate_strategy()) {
+            let timestamp = Timestamp::from_samples(samples as f64, sample_rate);
+            let error = (timestamp.get_samples(sample_rate) - samples as f64).abs();
+            prop_assert!(error <= RESOLUTION * sample_rate as f64);
+        }
+
+        #[test]
+        fn increments_are_associative(
+            start in 0u64..100_000_000,
+            first in 0usize..1_000_000,
+            second in 0usize..1_000_000,
+            sample_rate in sample_rate_strategy(),
+        ) {
+            let start = Timestamp::from_samples(start as f64, sample_rate);
+            let in_steps = start
+                .incremented_by_samples(first, sample_rate)
+                .incremented_by_samples(second, sample_rate);
+            let at_once = start.incremented_by_samples(first + second, sample_rate);
+
+            prop_assert!((in_steps.get_seconds() - at_once.get_seconds()).abs() <= 2.0 * RESOLUTION);
+        }
+
+        #[test]
+        fn increments_are_monotonic(
+            start in 0.0f64..100_000.0,
+            first in 0usize..1_000_000,
+            second in 0usize..1_000_000,
+            sample_rate in sample_rate_strategy(),
+        ) {
+            let start = Timestamp::from_seconds(start);
+            let earlier = start.incremented_by_samples(first.min(second), sample_rate);
+            let later = start.incremented_by_samples(first.max(second), sample_rate);
+
+            prop_assert!(start <= earlier);
+            prop_assert!(earlier <= later);
+        }
+
+        #[test]
+        fn ordering_matches_seconds(first in -100_000.0f64..100_000.0, second in -100_000.0f64..100_000.0) {
+            let first = Timestamp::from_seconds(first);
+            let second = Timestamp::from_seconds(second);
+
+            prop_assert_eq!(
+                first.cmp(&second),
+                first.get_seconds().partial_cmp(&second.get_seconds()).unwrap()
+            );
+        }
+
+        #[test]
+        fn subtraction_inverts_increment(start in 0.0f64..100_000.0, offset in 0.0f64..100_000.0) {
+            let start = Timestamp::from_seconds(start);
+            let end = start.incremented_by_seconds(offset);
+
+            prop_assert_eq!(end - start, Timestamp::from_seconds(offset));
+            prop_assert_eq!(end - (end - start), start);
+        }
+    }
 }
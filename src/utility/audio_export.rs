@@ -0,0 +1,483 @@
+use std::io::{self, Write};
+
+use crate::buffer::{audio_buffer::AudioBuffer, sample_location::SampleLocation};
+
+const ENCODER_NAME: &str = "rust-audio-engine";
+const INTEGER_BITS_PER_SAMPLE: usize = 24;
+const FLAC_BLOCK_SIZE: usize = 4096;
+const FLAC_MAXIMUM_CHANNELS: usize = 8;
+const FLAC_MAXIMUM_FIXED_ORDER: usize = 4;
+const FLAC_MAXIMUM_RICE_PARAMETER: u32 = 14;
+const AIFF_COMMON_CHUNK_SIZE: u32 = 18;
+const CAF_DESCRIPTION_CHUNK_SIZE: u64 = 32;
+const CAF_FORMAT_FLAG_FLOAT: u32 = 1;
+const CAF_FORMAT_FLAG_LITTLE_ENDIAN: u32 = 2;
+
+fn invalid_input(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+fn integer_sample(buffer: &dyn AudioBuffer, channel: usize, frame: usize) -> i32 {
+    let full_scale = (1_i32 << (INTEGER_BITS_PER_SAMPLE - 1)) as f32;
+    let sample = buffer.get_sample(SampleLocation::new(channel, frame));
+    ((sample * full_scale).round() as i32).clamp(-(full_scale as i32), full_scale as i32 - 1)
+}
+
+struct BitWriter {
+    bytes: Vec<u8>,
+    accumulator: u64,
+    num_bits: u32,
+}
+
+impl BitWriter {
+    fn new() -> Self {
+        Self {
+            bytes: Vec::new(),
+            accumulator: 0,
+            num_bits: 0,
+        }
+    }
+
+    fn write(&mut self, value: u64, num_bits: u32) {
+        for bit in (0..num_bits).rev() {
+            self.accumulator = (self.accumulator << 1) | ((value >> bit) & 1);
+            self.num_bits += 1;
+
+            if self.num_bits == 8 {
+                self.bytes.push(self.accumulator as u8);
+                self.accumulator = 0;
+                self.num_bits = 0;
+            }
+        }
+    }
+
+    fn write_signed(&mut self, value: i64, num_bits: u32) {
+        self.write(value as u64 & ((1 << num_bits) - 1), num_bits);
+    }
+
+    fn write_unary(&mut self, zeros: u64) {
+        for _ in 0..zeros {
+            self.write(0, 1);
+        }
+        self.write(1, 1);
+    }
+
+    fn align(&mut self) {
+        if self.num_bits > 0 {
+            self.write(0, 8 - self.num_bits);
+        }
+    }
+
+    fn into_bytes(mut self) -> Vec<u8> {
+        self.align();
+        self.bytes
+    }
+}
+
+fn crc8(data: &[u8]) -> u8 {
+    data.iter().fold(0, |crc, byte| {
+        (0..8).fold(crc ^ byte, |crc, _| {
+            if crc & 0x80 != 0 {
+                (crc << 1) ^ 0x07
+            } else {
+                crc << 1
+            }
+        })
+    })
+}
+
+fn crc16(data: &[u8]) -> u16 {
+    data.iter().fold(0, |crc, byte| {
+        (0..8).fold(crc ^ ((*byte as u16) << 8), |crc, _| {
+            if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x8005
+            } else {
+                crc << 1
+            }
+        })
+    })
+}
+
+fn fixed_residual(samples: &[i64], order: usize) -> Vec<i64> {
+    let mut residual = samples.to_vec();
+    for _ in 0..order {
+        residual = residual.windows(2).map(|pair| pair[1] - pair[0]).collect();
+    }
+    residual
+}
+
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+fn rice_parameter(residual: &[i64]) -> (u32, u64) {
+    (0..=FLAC_MAXIMUM_RICE_PARAMETER)
+        .map(|parameter| {
+            let num_bits = residual
+                .iter()
+                .map(|value| (zigzag(*value) >> parameter) + 1 + parameter as u64)
+                .sum();
+            (parameter, num_bits)
+        })
+        .min_by_key(|(_, num_bits)| *num_bits)
+        .unwrap_or((0, 0))
+}
+
+fn write_subframe(writer: &mut BitWriter, samples: &[i64]) {
+    let bits_per_sample = INTEGER_BITS_PER_SAMPLE as u32;
+    let verbatim_bits = samples.len() as u64 * bits_per_sample as u64;
+
+    let best_fixed = (0..=FLAC_MAXIMUM_FIXED_ORDER.min(samples.len()))
+        .map(|order| {
+            let residual = fixed_residual(samples, order);
+            let (parameter, residual_bits) = rice_parameter(&residual);
+            let num_bits = order as u64 * bits_per_sample as u64 + 10 + residual_bits;
+            (order, residual, parameter, num_bits)
+        })
+        .min_by_key(|(.., num_bits)| *num_bits);
+
+    match best_fixed {
+        Some((order, residual, parameter, num_bits)) if num_bits < verbatim_bits => {
+            writer.write(0b0001_0000 | (order as u64) << 1, 8);
+            for sample in samples.iter().take(order) {
+                writer.write_signed(*sample, bits_per_sample);
+            }
+
+            writer.write(0, 2);
+            writer.write(0, 4);
+            writer.write(parameter as u64, 4);
+            for value in residual {
+                let value = zigzag(value);
+                writer.write_unary(value >> parameter);
+                writer.write(value, parameter);
+            }
+        }
+        _ => {
+            writer.write(0b0000_0010, 8);
+            for sample in samples {
+                writer.write_signed(*sample, bits_per_sample);
+            }
+        }
+    }
+}
+
+fn write_frame_number(writer: &mut BitWriter, number: u64) {
+    if number < 0x80 {
+        writer.write(number, 8);
+        return;
+    }
+
+    let num_continuation_bytes = (1..6)
+        .find(|count| number < 1 << (6 + 5 * count))
+        .unwrap_or(6);
+
+    let leading_ones = (0xff00_u64 >> (num_continuation_bytes + 1)) & 0xff;
+    writer.write(leading_ones | (number >> (6 * num_continuation_bytes)), 8);
+
+    for byte in (0..num_continuation_bytes).rev() {
+        writer.write(0x80 | ((number >> (6 * byte)) & 0x3f), 8);
+    }
+}
+
+fn flac_frame(buffer: &dyn AudioBuffer, frame_number: usize) -> Vec<u8> {
+    let start = frame_number * FLAC_BLOCK_SIZE;
+    let block_size = FLAC_BLOCK_SIZE.min(buffer.num_frames() - start);
+
+    let mut writer = BitWriter::new();
+    writer.write(0b1111_1111_1111_1000, 16);
+    writer.write(0b0111, 4);
+    writer.write(0b0000, 4);
+    writer.write(buffer.num_channels() as u64 - 1, 4);
+    writer.write(0b110, 3);
+    writer.write(0, 1);
+    write_frame_number(&mut writer, frame_number as u64);
+    writer.write(block_size as u64 - 1, 16);
+
+    let header_crc = crc8(&writer.bytes);
+    writer.write(header_crc as u64, 8);
+
+    for channel in 0..buffer.num_channels() {
+        let samples: Vec<i64> = (start..start + block_size)
+            .map(|frame| integer_sample(buffer, channel, frame) as i64)
+            .collect();
+        write_subframe(&mut writer, &samples);
+    }
+
+    let mut bytes = writer.into_bytes();
+    let frame_crc = crc16(&bytes);
+    bytes.extend_from_slice(&frame_crc.to_be_bytes());
+    bytes
+}
+
+fn write_metadata_block_header(
+    writer: &mut dyn Write,
+    is_last: bool,
+    block_type: u8,
+    length: usize,
+) -> io::Result<()> {
+    let header = ((is_last as u32) << 31) | ((block_type as u32) << 24) | length as u32;
+    writer.write_all(&header.to_be_bytes())
+}
+
+pub fn write_flac(writer: &mut dyn Write, buffer: &dyn AudioBuffer) -> io::Result<()> {
+    let num_channels = buffer.num_channels();
+    if num_channels == 0 || num_channels > FLAC_MAXIMUM_CHANNELS {
+        return Err(invalid_input(
+            "FLAC supports between one and eight channels",
+        ));
+    }
+
+    let num_frames = buffer.num_frames();
+    let frames: Vec<Vec<u8>> = (0..num_frames.div_ceil(FLAC_BLOCK_SIZE))
+        .map(|frame_number| flac_frame(buffer, frame_number))
+        .collect();
+
+    let minimum_frame_size = frames.iter().map(Vec::len).min().unwrap_or(0);
+    let maximum_frame_size = frames.iter().map(Vec::len).max().unwrap_or(0);
+
+    writer.write_all(b"fLaC")?;
+
+    let mut stream_info = BitWriter::new();
+    stream_info.write(FLAC_BLOCK_SIZE as u64, 16);
+    stream_info.write(FLAC_BLOCK_SIZE as u64, 16);
+    stream_info.write(minimum_frame_size as u64, 24);
+    stream_info.write(maximum_frame_size as u64, 24);
+    stream_info.write(buffer.sample_rate() as u64, 20);
+    stream_info.write(num_channels as u64 - 1, 3);
+    stream_info.write(INTEGER_BITS_PER_SAMPLE as u64 - 1, 5);
+    stream_info.write(num_frames as u64, 36);
+    stream_info.write(0, 64);
+    stream_info.write(0, 64);
+    let stream_info = stream_info.into_bytes();
+
+    write_metadata_block_header(writer, false, 0, stream_info.len())?;
+    writer.write_all(&stream_info)?;
+
+    write_metadata_block_header(writer, true, 4, 8 + ENCODER_NAME.len())?;
+    writer.write_all(&(ENCODER_NAME.len() as u32).to_le_bytes())?;
+    writer.write_all(ENCODER_NAME.as_bytes())?;
+    writer.write_all(&0_u32.to_le_bytes())?;
+
+    for frame in frames {
+        writer.write_all(&frame)?;
+    }
+
+    Ok(())
+}
+
+fn extended_sample_rate(sample_rate: usize) -> [u8; 10] {
+    let mut bytes = [0; 10];
+    if sample_rate == 0 {
+        return bytes;
+    }
+
+    let exponent = 63 - (sample_rate as u64).leading_zeros();
+    let mantissa = (sample_rate as u64) << (63 - exponent);
+
+    bytes[..2].copy_from_slice(&(16383 + exponent as u16).to_be_bytes());
+    bytes[2..].copy_from_slice(&mantissa.to_be_bytes());
+    bytes
+}
+
+pub fn write_aiff(writer: &mut dyn Write, buffer: &dyn AudioBuffer) -> io::Result<()> {
+    let bytes_per_sample = INTEGER_BITS_PER_SAMPLE / 8;
+    let data_size = buffer.num_frames() * buffer.num_channels() * bytes_per_sample;
+    let sound_chunk_size = 8 + data_size;
+    let padding = data_size % 2;
+    let form_size = 4 + (8 + AIFF_COMMON_CHUNK_SIZE as usize) + (8 + sound_chunk_size) + padding;
+
+    if form_size > u32::MAX as usize {
+        return Err(invalid_input("The buffer is too long for an AIFF file"));
+    }
+
+    writer.write_all(b"FORM")?;
+    writer.write_all(&(form_size as u32).to_be_bytes())?;
+    writer.write_all(b"AIFF")?;
+
+    writer.write_all(b"COMM")?;
+    writer.write_all(&AIFF_COMMON_CHUNK_SIZE.to_be_bytes())?;
+    writer.write_all(&(buffer.num_channels() as u16).to_be_bytes())?;
+    writer.write_all(&(buffer.num_frames() as u32).to_be_bytes())?;
+    writer.write_all(&(INTEGER_BITS_PER_SAMPLE as u16).to_be_bytes())?;
+    writer.write_all(&extended_sample_rate(buffer.sample_rate()))?;
+
+    writer.write_all(b"SSND")?;
+    writer.write_all(&(sound_chunk_size as u32).to_be_bytes())?;
+    writer.write_all(&0_u32.to_be_bytes())?;
+    writer.write_all(&0_u32.to_be_bytes())?;
+
+    for frame in 0..buffer.num_frames() {
+        for channel in 0..buffer.num_channels() {
+            let sample = integer_sample(buffer, channel, frame).to_be_bytes();
+            writer.write_all(&sample[4 - bytes_per_sample..])?;
+        }
+    }
+
+    if padding > 0 {
+        writer.write_all(&[0])?;
+    }
+
+    Ok(())
+}
+
+pub fn write_caf(writer: &mut dyn Write, buffer: &dyn AudioBuffer) -> io::Result<()> {
+    let bytes_per_frame = (buffer.num_channels() * std::mem::size_of::<f32>()) as u32;
+
+    writer.write_all(b"caff")?;
+    writer.write_all(&1_u16.to_be_bytes())?;
+    writer.write_all(&0_u16.to_be_bytes())?;
+
+    writer.write_all(b"desc")?;
+    writer.write_all(&CAF_DESCRIPTION_CHUNK_SIZE.to_be_bytes())?;
+    writer.write_all(&(buffer.sample_rate() as f64).to_be_bytes())?;
+    writer.write_all(b"lpcm")?;
+    writer.write_all(&(CAF_FORMAT_FLAG_FLOAT | CAF_FORMAT_FLAG_LITTLE_ENDIAN).to_be_bytes())?;
+    writer.write_all(&bytes_per_frame.to_be_bytes())?;
+    writer.write_all(&1_u32.to_be_bytes())?;
+    writer.write_all(&(buffer.num_channels() as u32).to_be_bytes())?;
+    writer.write_all(&32_u32.to_be_bytes())?;
+
+    let information = format!("encoder\0{}\0", ENCODER_NAME);
+    writer.write_all(b"info")?;
+    writer.write_all(&(4 + information.len() as u64).to_be_bytes())?;
+    writer.write_all(&1_u32.to_be_bytes())?;
+    writer.write_all(information.as_bytes())?;
+
+    let data_size = 4 + buffer.num_frames() as u64 * bytes_per_frame as u64;
+    writer.write_all(b"data")?;
+    writer.write_all(&data_size.to_be_bytes())?;
+    writer.write_all(&0_u32.to_be_bytes())?;
+
+    for frame in 0..buffer.num_frames() {
+        for channel in 0..buffer.num_channels() {
+            let sample = buffer.get_sample(SampleLocation::new(channel, frame));
+            writer.write_all(&sample.to_le_bytes())?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::owned_audio_buffer::OwnedAudioBuffer;
+
+    use super::*;
+
+    fn ramp(num_frames: usize, num_channels: usize) -> OwnedAudioBuffer {
+        let mut buffer = OwnedAudioBuffer::new(num_frames, num_channels, 44_100);
+        for frame in 0..num_frames {
+            for channel in 0..num_channels {
+                let value =
+                    (frame as f32 / num_frames as f32) * if channel == 0 { 1.0 } else { -0.5 };
+                buffer.set_sample(SampleLocation::new(channel, frame), value);
+            }
+        }
+        buffer
+    }
+
+    fn read_u32_be(bytes: &[u8], offset: usize) -> u32 {
+        u32::from_be_bytes(bytes[offset..offset + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn writes_flac_stream_with_checked_frames() {
+        let buffer = ramp(10_000, 2);
+        let mut data = Vec::new();
+        write_flac(&mut data, &buffer).unwrap();
+
+        assert_eq!(&data[..4], b"fLaC");
+        assert_eq!(data[4], 0);
+        let stream_info = &data[8..42];
+        assert_eq!(u16::from_be_bytes([stream_info[0], stream_info[1]]), 4096);
+        assert_eq!(read_u32_be(stream_info, 10) >> 12, 44_100);
+        assert_eq!((stream_info[12] >> 1) & 0x07, 1);
+        assert_eq!(read_u32_be(stream_info, 14) as usize, 10_000);
+
+        let vendor_offset = 42 + 4 + 4;
+        assert_eq!(data[42] & 0x80, 0x80);
+        assert_eq!(
+            &data[vendor_offset..vendor_offset + ENCODER_NAME.len()],
+            ENCODER_NAME.as_bytes()
+        );
+
+        let first_frame = vendor_offset + ENCODER_NAME.len() + 4;
+        assert_eq!(&data[first_frame..first_frame + 2], &[0xff, 0xf8]);
+
+        let mut frame_starts = vec![first_frame];
+        for offset in first_frame + 1..data.len() - 1 {
+            if data[offset] == 0xff
+                && data[offset + 1] == 0xf8
+                && crc8(&data[offset..offset + 7]) == data[offset + 7]
+            {
+                frame_starts.push(offset);
+            }
+        }
+        frame_starts.push(data.len());
+
+        assert_eq!(frame_starts.len(), 4);
+        for pair in frame_starts.windows(2) {
+            assert_eq!(crc16(&data[pair[0]..pair[1]]), 0);
+        }
+
+        assert!(data.len() < 10_000 * 2 * 3);
+
+        assert!(write_flac(&mut Vec::new(), &OwnedAudioBuffer::new(16, 9, 44_100)).is_err());
+    }
+
+    #[test]
+    fn round_trips_flac_through_a_reference_decoder() {
+        let mut buffer = ramp(10_000, 2);
+        for frame in (0..10_000).step_by(7) {
+            let value = (frame as f32 * 0.05).sin() * 0.8;
+            buffer.set_sample(SampleLocation::new(1, frame), value);
+        }
+        let mut data = Vec::new();
+        write_flac(&mut data, &buffer).unwrap();
+
+        let mut reader = claxon::FlacReader::new(io::Cursor::new(data)).unwrap();
+        let stream_info = reader.streaminfo();
+        assert_eq!(stream_info.sample_rate, 44_100);
+        assert_eq!(stream_info.channels, 2);
+        assert_eq!(stream_info.bits_per_sample, INTEGER_BITS_PER_SAMPLE as u32);
+        assert_eq!(stream_info.samples, Some(10_000));
+
+        let samples: Vec<i32> = reader.samples().map(Result::unwrap).collect();
+        assert_eq!(samples.len(), 10_000 * 2);
+        for (index, sample) in samples.into_iter().enumerate() {
+            assert_eq!(sample, integer_sample(&buffer, index % 2, index / 2));
+        }
+    }
+
+    #[test]
+    fn writes_aiff_and_caf_headers() {
+        let buffer = ramp(101, 1);
+
+        let mut aiff = Vec::new();
+        write_aiff(&mut aiff, &buffer).unwrap();
+        assert_eq!(&aiff[..4], b"FORM");
+        assert_eq!(read_u32_be(&aiff, 4) as usize, aiff.len() - 8);
+        assert_eq!(&aiff[8..16], b"AIFFCOMM");
+        assert_eq!(read_u32_be(&aiff, 22), 101);
+        assert_eq!(&aiff[28..38], &[0x40, 0x0e, 0xac, 0x44, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(&aiff[38..42], b"SSND");
+        assert_eq!(aiff.len() % 2, 0);
+
+        let mut caf = Vec::new();
+        write_caf(&mut caf, &buffer).unwrap();
+        assert_eq!(&caf[..8], b"caff\x00\x01\x00\x00");
+        assert_eq!(&caf[8..12], b"desc");
+        assert_eq!(
+            f64::from_be_bytes(caf[20..28].try_into().unwrap()),
+            44_100.0
+        );
+        assert_eq!(&caf[28..32], b"lpcm");
+
+        let data_offset = caf.len() - 101 * 4;
+        assert_eq!(&caf[data_offset - 16..data_offset - 12], b"data");
+        assert_eq!(
+            f32::from_le_bytes(caf[caf.len() - 4..].try_into().unwrap()),
+            buffer.get_sample(SampleLocation::new(0, 100))
+        );
+    }
+}
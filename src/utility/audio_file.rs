@@ -0,0 +1,302 @@
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Write},
+    path::Path,
+};
+
+use crate::buffer::{
+    audio_buffer::AudioBuffer, owned_audio_buffer::OwnedAudioBuffer,
+    sample_location::SampleLocation,
+};
+
+use super::audio_export::{write_aiff, write_caf, write_flac};
+
+#[derive(Clone, Debug)]
+pub enum AudioFileError {
+    Io(String),
+    UnsupportedFormat,
+    Decode(String),
+}
+
+impl From<std::io::Error> for AudioFileError {
+    fn from(error: std::io::Error) -> Self {
+        match error.kind() {
+            std::io::ErrorKind::InvalidInput => AudioFileError::UnsupportedFormat,
+            _ => AudioFileError::Io(error.to_string()),
+        }
+    }
+}
+
+impl From<hound::Error> for AudioFileError {
+    fn from(error: hound::Error) -> Self {
+        match error {
+            hound::Error::IoError(error) => AudioFileError::Io(error.to_string()),
+            hound::Error::Unsupported => AudioFileError::UnsupportedFormat,
+            error => AudioFileError::Decode(error.to_string()),
+        }
+    }
+}
+
+pub fn read_audio_file(path: &str) -> Result<OwnedAudioBuffer, AudioFileError> {
+    read_wav(open_wav_file(path)?)
+}
+
+pub fn open_wav_file(path: &str) -> Result<hound::WavReader<BufReader<File>>, AudioFileError> {
+    let reader = hound::WavReader::open(path)?;
+
+    if reader.spec().channels == 0 {
+        return Err(AudioFileError::UnsupportedFormat);
+    }
+
+    Ok(reader)
+}
+
+pub fn decode_wav_samples<R: std::io::Read>(
+    reader: &mut hound::WavReader<R>,
+) -> Box<dyn Iterator<Item = Result<f32, AudioFileError>> + '_> {
+    let specification = reader.spec();
+
+    match specification.sample_format {
+        hound::SampleFormat::Float => Box::new(
+            reader
+                .samples::<f32>()
+                .map(|sample| sample.map_err(AudioFileError::from)),
+        ),
+        hound::SampleFormat::Int => {
+            let scale = 1.0 / (1_i64 << (specification.bits_per_sample - 1)) as f32;
+            Box::new(reader.samples::<i32>().map(move |sample| {
+                sample
+                    .map(|sample| sample as f32 * scale)
+                    .map_err(AudioFileError::from)
+            }))
+        }
+    }
+}
+
+fn read_wav<R: std::io::Read>(
+    mut reader: hound::WavReader<R>,
+) -> Result<OwnedAudioBuffer, AudioFileError> {
+    let specification = reader.spec();
+    let num_channels = specification.channels as usize;
+    let sample_rate = specification.sample_rate as usize;
+
+    if num_channels == 0 {
+        return Err(AudioFileError::UnsupportedFormat);
+    }
+
+    let data = decode_wav_samples(&mut reader).collect::<Result<Vec<_>, _>>()?;
+
+    Ok(OwnedAudioBuffer::new_from_data(
+        data,
+        num_channels,
+        sample_rate,
+    ))
+}
+
+pub fn write_wav_file(path: &str, buffer: &dyn AudioBuffer) -> Result<(), AudioFileError> {
+    let specification = hound::WavSpec {
+        channels: buffer.num_channels() as u16,
+        sample_rate: buffer.sample_rate() as u32,
+        bits_per_sample: 32,
+        sample_format: hound::SampleFormat::Float,
+    };
+
+    let mut writer = hound::WavWriter::create(path, specification)?;
+
+    for frame in 0..buffer.num_frames() {
+        for channel in 0..buffer.num_channels() {
+            writer.write_sample(buffer.get_sample(SampleLocation::new(channel, frame)))?;
+        }
+    }
+
+    writer.finalize()?;
+    Ok(())
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ExportFormat {
+    #[default]
+    Wav,
+    Flac,
+    Aiff,
+    Caf,
+}
+
+impl ExportFormat {
+    pub fn from_path(path: &str) -> Option<Self> {
+        let extension = Path::new(path).extension()?.to_str()?.to_ascii_lowercase();
+
+        match extension.as_str() {
+            "wav" | "wave" => Some(ExportFormat::Wav),
+            "flac" => Some(ExportFormat::Flac),
+            "aif" | "aiff" => Some(ExportFormat::Aiff),
+            "caf" => Some(ExportFormat::Caf),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Wav => "wav",
+            ExportFormat::Flac => "flac",
+            ExportFormat::Aiff => "aiff",
+            ExportFormat::Caf => "caf",
+        }
+    }
+}
+
+fn write_encoded_file(
+    path: &str,
+    buffer: &dyn AudioBuffer,
+    encode: fn(&mut dyn Write, &dyn AudioBuffer) -> std::io::Result<()>,
+) -> Result<(), AudioFileError> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    encode(&mut writer, buffer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+pub fn write_audio_file(
+    path: &str,
+    buffer: &dyn AudioBuffer,
+    format: ExportFormat,
+) -> Result<(), AudioFileError> {
+    match format {
+        ExportFormat::Wav => write_wav_file(path, buffer),
+        ExportFormat::Flac => write_encoded_file(path, buffer, write_flac),
+        ExportFormat::Aiff => write_encoded_file(path, buffer, write_aiff),
+        ExportFormat::Caf => write_encoded_file(path, buffer, write_caf),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use approx::assert_relative_eq;
+
+    use super::*;
+
+    #[test]
+    fn reads_integer_wav_data() {
+        let specification = hound::WavSpec {
+            channels: 2,
+            sample_rate: 48000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+
+        let mut data = Cursor::new(Vec::new());
+        {
+            let mut writer = hound::WavWriter::new(&mut data, specification).unwrap();
+            for _ in 0..10 {
+                writer.write_sample(16384_i16).unwrap();
+                writer.write_sample(-32768_i16).unwrap();
+            }
+            writer.finalize().unwrap();
+        }
+
+        data.set_position(0);
+        let buffer = read_wav(hound::WavReader::new(data).unwrap()).unwrap();
+
+        assert_eq!(buffer.num_channels(), 2);
+        assert_eq!(buffer.num_frames(), 10);
+        assert_eq!(buffer.sample_rate(), 48000);
+        assert_relative_eq!(buffer.get_sample(SampleLocation::new(0, 3)), 0.5);
+        assert_relative_eq!(buffer.get_sample(SampleLocation::new(1, 3)), -1.0);
+    }
+
+    #[test]
+    fn reads_wav_files_in_each_sample_format() {
+        let formats = [
+            (24, hound::SampleFormat::Int, 1 << 22),
+            (32, hound::SampleFormat::Int, 1 << 30),
+            (32, hound::SampleFormat::Float, 0),
+        ];
+
+        for (bits_per_sample, sample_format, half_scale) in formats {
+            let path = std::env::temp_dir().join(format!(
+                "audio-file-{}-{:?}-{}.wav",
+                bits_per_sample,
+                sample_format,
+                std::process::id()
+            ));
+
+            let specification = hound::WavSpec {
+                channels: 1,
+                sample_rate: 44100,
+                bits_per_sample,
+                sample_format,
+            };
+            let mut writer = hound::WavWriter::create(&path, specification).unwrap();
+            for _ in 0..4 {
+                match sample_format {
+                    hound::SampleFormat::Int => writer.write_sample(-half_scale).unwrap(),
+                    hound::SampleFormat::Float => writer.write_sample(-0.5_f32).unwrap(),
+                }
+            }
+            writer.finalize().unwrap();
+
+            let buffer = OwnedAudioBuffer::from_wav_file(path.to_str().unwrap()).unwrap();
+            let _ = std::fs::remove_file(&path);
+
+            assert_eq!(buffer.num_frames(), 4);
+            assert_eq!(buffer.sample_rate(), 44100);
+            assert_relative_eq!(buffer.get_sample(SampleLocation::new(0, 2)), -0.5);
+        }
+
+        assert!(matches!(
+            OwnedAudioBuffer::from_wav_file("missing.wav"),
+            Err(AudioFileError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn writes_each_export_format() {
+        let mut buffer = OwnedAudioBuffer::new(1000, 2, 48000);
+        buffer.fill_with_value(0.25);
+
+        for format in [
+            ExportFormat::Wav,
+            ExportFormat::Flac,
+            ExportFormat::Aiff,
+            ExportFormat::Caf,
+        ] {
+            let path = std::env::temp_dir()
+                .join(format!(
+                    "export-{}.{}",
+                    std::process::id(),
+                    format.extension()
+                ))
+                .to_string_lossy()
+                .into_owned();
+
+            assert_eq!(ExportFormat::from_path(&path), Some(format));
+            write_audio_file(&path, &buffer, format).unwrap();
+            assert!(std::fs::metadata(&path).unwrap().len() > 0);
+
+            if format == ExportFormat::Wav {
+                assert_eq!(read_audio_file(&path).unwrap().num_frames(), 1000);
+            }
+
+            std::fs::remove_file(&path).unwrap();
+        }
+
+        assert_eq!(ExportFormat::from_path("mix.AIF"), Some(ExportFormat::Aiff));
+        assert_eq!(ExportFormat::from_path("mix.mp3"), None);
+
+        let path = std::env::temp_dir()
+            .join(format!("export-{}-channels.flac", std::process::id()))
+            .to_string_lossy()
+            .into_owned();
+        assert!(matches!(
+            write_audio_file(
+                &path,
+                &OwnedAudioBuffer::new(10, 9, 48000),
+                ExportFormat::Flac
+            ),
+            Err(AudioFileError::UnsupportedFormat)
+        ));
+        let _ = std::fs::remove_file(&path);
+    }
+}
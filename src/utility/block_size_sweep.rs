@@ -0,0 +1,326 @@
+use std::{f64::consts::TAU, sync::Arc};
+
+use atomic_float::AtomicF64;
+
+use crate::{
+    commands::id::Id,
+    graph::dsp::{Dsp, DspParameterMap},
+    parameter::realtime_parameter::RealtimeAudioParameter,
+    AudioBuffer, AudioBufferSlice, Context, ImmutableAudioBufferSlice, OwnedAudioBuffer,
+    SampleLocation, Timestamp,
+};
+
+use super::random::Random;
+
+pub const BLOCK_SIZES: [usize; 9] = [1, 17, 64, 128, 480, 512, 513, 777, 1024];
+
+#[derive(Clone, Copy, Debug)]
+pub struct SweepOptions {
+    pub num_frames: usize,
+    pub num_channels: usize,
+    pub sample_rate: usize,
+    pub tolerance: f32,
+}
+
+impl Default for SweepOptions {
+    fn default() -> Self {
+        Self {
+            num_frames: 4096,
+            num_channels: 2,
+            sample_rate: 48_000,
+            tolerance: 1e-6,
+        }
+    }
+}
+
+pub fn parameter(parameters: &mut DspParameterMap, value: f64) -> Id {
+    let id = Id::generate();
+    parameters.insert(
+        id,
+        RealtimeAudioParameter::new(id, Arc::new(AtomicF64::new(value))),
+    );
+    id
+}
+
+pub fn test_signal(options: &SweepOptions) -> OwnedAudioBuffer {
+    let mut buffer = OwnedAudioBuffer::new(
+        options.num_frames,
+        options.num_channels,
+        options.sample_rate,
+    );
+    let mut random = Random::with_seed(1);
+
+    for frame in 0..options.num_frames {
+        let time = frame as f64 / options.sample_rate as f64;
+        for channel in 0..options.num_channels {
+            let frequency = 220.0 * (channel + 1) as f64;
+            let value = 0.4 * (TAU * frequency * time).sin()
+                + 0.2 * (TAU * 5000.0 * time).sin()
+                + 0.05 * random.next_bipolar();
+            buffer.set_sample(SampleLocation::new(channel, frame), value as f32);
+        }
+    }
+
+    buffer
+}
+
+pub fn render_dsp(dsp: &mut Dsp, input: &OwnedAudioBuffer, block_size: usize) -> OwnedAudioBuffer {
+    let mut output = OwnedAudioBuffer::new(
+        input.num_frames(),
+        input.num_channels(),
+        input.sample_rate(),
+    );
+
+    let mut offset = 0;
+    while offset < input.num_frames() {
+        let num_frames = block_size.min(input.num_frames() - offset);
+        let start_time = Timestamp::from_samples(offset as f64, input.sample_rate());
+
+        dsp.process_audio(
+            &ImmutableAudioBufferSlice::new(input, offset),
+            &mut AudioBufferSlice::new(&mut output, offset, num_frames),
+            &start_time,
+        );
+
+        offset += num_frames;
+    }
+
+    output
+}
+
+pub fn render_context<S, K>(
+    setup: &S,
+    options: &SweepOptions,
+    block_size: usize,
+) -> OwnedAudioBuffer
+where
+    S: Fn(&mut Context) -> K,
+{
+    let mut context = Context::new(options.sample_rate);
+    let mut audio_process = context.get_audio_process();
+    let _nodes = setup(&mut context);
+    context.start();
+
+    let mut output = OwnedAudioBuffer::new(
+        options.num_frames,
+        options.num_channels,
+        options.sample_rate,
+    );
+
+    let mut offset = 0;
+    while offset < options.num_frames {
+        let num_frames = block_size.min(options.num_frames - offset);
+        audio_process.process(&mut AudioBufferSlice::new(&mut output, offset, num_frames));
+        offset += num_frames;
+    }
+
+    output
+}
+
+fn assert_matches(
+    reference: &OwnedAudioBuffer,
+    rendered: &OwnedAudioBuffer,
+    block_size: usize,
+    tolerance: f32,
+) {
+    for channel in 0..reference.num_channels() {
+        for frame in 0..reference.num_frames() {
+            let location = SampleLocation::new(channel, frame);
+            let expected = reference.get_sample(location);
+            let actual = rendered.get_sample(location);
+
+            assert!(
+                (expected - actual).abs() <= tolerance,
+                "block size {} differs at channel {} frame {}: {} != {}",
+                block_size,
+                channel,
+                frame,
+                actual,
+                expected
+            );
+        }
+    }
+}
+
+pub fn assert_dsp_block_size_invariant<F>(make_dsp: F, options: SweepOptions)
+where
+    F: Fn() -> Dsp,
+{
+    let input = test_signal(&options);
+    let reference = render_dsp(&mut make_dsp(), &input, options.num_frames);
+
+    for block_size in BLOCK_SIZES {
+        let rendered = render_dsp(&mut make_dsp(), &input, block_size);
+        assert_matches(&reference, &rendered, block_size, options.tolerance);
+    }
+}
+
+pub fn assert_context_block_size_invariant<S, K>(setup: S, options: SweepOptions)
+where
+    S: Fn(&mut Context) -> K,
+{
+    let reference = render_context(&setup, &options, options.num_frames);
+
+    for block_size in BLOCK_SIZES {
+        let rendered = render_context(&setup, &options, block_size);
+        assert_matches(&reference, &rendered, block_size, options.tolerance);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        dsp::{
+            amp_sim::processor::{AmpSimParameterIds, AmpSimProcessor},
+            de_esser::processor::DeEsserProcessor,
+            denoise::processor::DenoiseProcessor,
+            dynamic_eq::processor::{DynamicEqBandIds, DynamicEqBandType, DynamicEqProcessor},
+            gain::processor::GainProcessor,
+            leveler::processor::LevelerProcessor,
+        },
+        parameter::ParameterChange,
+        utility::gain_reduction,
+        Gain, Node, Oscillator,
+    };
+
+    use super::*;
+
+    fn ramp(parameters: &mut DspParameterMap, id: Id, value: f64, seconds: f64) {
+        if let Some(parameter) = parameters.get_mut(&id) {
+            parameter.add_parameter_change(ParameterChange::linear(
+                value,
+                Timestamp::from_seconds(seconds),
+            ));
+        }
+    }
+
+    #[test]
+    fn gain_with_ramps_and_jumps() {
+        assert_dsp_block_size_invariant(
+            || {
+                let mut parameters = DspParameterMap::new();
+                let gain_id = parameter(&mut parameters, 1.0);
+                ramp(&mut parameters, gain_id, 0.25, 0.03);
+                if let Some(parameter) = parameters.get_mut(&gain_id) {
+                    parameter.add_parameter_change(ParameterChange::immediate(
+                        0.75,
+                        Timestamp::from_samples(2500.0, 48_000),
+                    ));
+                }
+                Dsp::new(
+                    Id::generate(),
+                    Box::new(GainProcessor::new(gain_id)),
+                    parameters,
+                )
+            },
+            SweepOptions::default(),
+        );
+    }
+
+    #[test]
+    fn dynamics_processors() {
+        assert_dsp_block_size_invariant(
+            || {
+                let mut parameters = DspParameterMap::new();
+                let frequency_id = parameter(&mut parameters, 5000.0);
+                let threshold_id = parameter(&mut parameters, -30.0);
+                let amount_id = parameter(&mut parameters, 12.0);
+                let (meter, _) = gain_reduction::create();
+                let processor = DeEsserProcessor::new(frequency_id, threshold_id, amount_id, meter);
+                Dsp::new(Id::generate(), Box::new(processor), parameters)
+            },
+            SweepOptions::default(),
+        );
+
+        assert_dsp_block_size_invariant(
+            || {
+                let mut parameters = DspParameterMap::new();
+                let ids = DynamicEqBandIds {
+                    frequency_id: parameter(&mut parameters, 220.0),
+                    q_id: parameter(&mut parameters, 1.0),
+                    threshold_id: parameter(&mut parameters, -20.0),
+                    ratio_id: parameter(&mut parameters, 4.0),
+                    range_id: parameter(&mut parameters, -12.0),
+                };
+                ramp(&mut parameters, ids.frequency_id, 880.0, 0.05);
+                let processor = DynamicEqProcessor::new(&[(DynamicEqBandType::Bell, ids)]);
+                Dsp::new(Id::generate(), Box::new(processor), parameters)
+            },
+            SweepOptions::default(),
+        );
+
+        assert_dsp_block_size_invariant(
+            || {
+                let mut parameters = DspParameterMap::new();
+                let target_id = parameter(&mut parameters, -20.0);
+                let max_gain_id = parameter(&mut parameters, 12.0);
+                let processor = LevelerProcessor::new(
+                    target_id,
+                    max_gain_id,
+                    Default::default(),
+                    Default::default(),
+                );
+                Dsp::new(Id::generate(), Box::new(processor), parameters)
+            },
+            SweepOptions {
+                num_frames: 24_000,
+                ..Default::default()
+            },
+        );
+    }
+
+    #[test]
+    fn spectral_and_waveshaping_processors() {
+        assert_dsp_block_size_invariant(
+            || {
+                let mut parameters = DspParameterMap::new();
+                let reduction_id = parameter(&mut parameters, 1.0);
+                let floor_id = parameter(&mut parameters, 0.1);
+                let (_, event_receiver) = lockfree::channel::spsc::create();
+                let processor = DenoiseProcessor::new(reduction_id, floor_id, event_receiver);
+                Dsp::new(Id::generate(), Box::new(processor), parameters)
+            },
+            SweepOptions::default(),
+        );
+
+        assert_dsp_block_size_invariant(
+            || {
+                let mut parameters = DspParameterMap::new();
+                let ids = AmpSimParameterIds {
+                    input_gain_id: parameter(&mut parameters, 24.0),
+                    bass_id: parameter(&mut parameters, 3.0),
+                    middle_id: parameter(&mut parameters, -3.0),
+                    treble_id: parameter(&mut parameters, 6.0),
+                    output_gain_id: parameter(&mut parameters, -6.0),
+                };
+                let (_, event_receiver) = lockfree::channel::spsc::create();
+                let processor = AmpSimProcessor::new(ids, event_receiver);
+                Dsp::new(Id::generate(), Box::new(processor), parameters)
+            },
+            SweepOptions::default(),
+        );
+    }
+
+    #[test]
+    fn oscillators_into_gain_graph() {
+        assert_context_block_size_invariant(
+            |context| {
+                let oscillator = Oscillator::new(context.get_command_queue(), 440.0);
+                let second = Oscillator::new(context.get_command_queue(), 660.0);
+                let mut gain = Gain::new(context.get_command_queue());
+                gain.gain.set_value_at_time(0.5, Timestamp::zero());
+                gain.gain
+                    .linear_ramp_to_value(0.1, Timestamp::from_seconds(0.05));
+
+                gain.connect_to_output().unwrap();
+
+                let oscillator = context.add_node(oscillator).unwrap();
+                let second = context.add_node(second).unwrap();
+                let gain = context.add_node(gain).unwrap();
+                context.connect_nodes(&oscillator, &gain).unwrap();
+                context.connect_nodes(&second, &gain).unwrap();
+            },
+            SweepOptions::default(),
+        );
+    }
+}
@@ -0,0 +1,53 @@
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug)]
+pub struct EnvelopeFollower {
+    attack_time: Duration,
+    release_time: Duration,
+    sample_rate: usize,
+    attack: f64,
+    release: f64,
+    value: f64,
+}
+
+impl EnvelopeFollower {
+    pub fn new(attack_time: Duration, release_time: Duration) -> Self {
+        Self {
+            attack_time,
+            release_time,
+            sample_rate: 0,
+            attack: 0.0,
+            release: 0.0,
+            value: 0.0,
+        }
+    }
+
+    fn smoothing(duration: Duration, sample_rate: usize) -> f64 {
+        if duration.is_zero() {
+            return 0.0;
+        }
+
+        (-1.0 / (duration.as_secs_f64() * sample_rate as f64)).exp()
+    }
+
+    pub fn prepare(&mut self, sample_rate: usize) {
+        if sample_rate == self.sample_rate {
+            return;
+        }
+
+        self.sample_rate = sample_rate;
+        self.attack = Self::smoothing(self.attack_time, sample_rate);
+        self.release = Self::smoothing(self.release_time, sample_rate);
+    }
+
+    pub fn process(&mut self, level: f64) -> f64 {
+        let smoothing = if level > self.value {
+            self.attack
+        } else {
+            self.release
+        };
+
+        self.value = level + smoothing * (self.value - level);
+        self.value
+    }
+}
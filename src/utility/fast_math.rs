@@ -0,0 +1,166 @@
+use std::f64::consts::{FRAC_PI_2, LN_10, LN_2, LOG2_E, SQRT_2, TAU};
+
+use super::level::MINUS_INFINITY_DECIBELS;
+
+const SINE_TABLE_SIZE: usize = 4096;
+
+pub const SIN_MAX_ERROR: f64 = 1e-6;
+pub const EXP_MAX_RELATIVE_ERROR: f64 = 1e-6;
+pub const LOG_MAX_ERROR: f64 = 1e-6;
+pub const TANH_MAX_ERROR: f64 = 2e-6;
+pub const DB_MAX_ERROR: f64 = 1e-5;
+
+lazy_static! {
+    static ref SINE_TABLE: Vec<f64> = (0..=SINE_TABLE_SIZE)
+        .map(|index| (TAU * index as f64 / SINE_TABLE_SIZE as f64).sin())
+        .collect();
+}
+
+pub fn initialise() {
+    let _ = SINE_TABLE[0];
+}
+
+pub fn sin(x: f64) -> f64 {
+    let position = (x / TAU).rem_euclid(1.0) * SINE_TABLE_SIZE as f64;
+    let index = position as usize;
+    let fraction = position - index as f64;
+
+    let a = SINE_TABLE[index];
+    let b = SINE_TABLE[index + 1];
+    a + fraction * (b - a)
+}
+
+pub fn cos(x: f64) -> f64 {
+    sin(x + FRAC_PI_2)
+}
+
+pub fn exp(x: f64) -> f64 {
+    let x = x.clamp(-708.0, 709.0);
+    let scaled = x * LOG2_E;
+    let integer = scaled.round();
+    let f = (scaled - integer) * LN_2;
+
+    let polynomial = 1.0
+        + f * (1.0
+            + f * (1.0 / 2.0
+                + f * (1.0 / 6.0 + f * (1.0 / 24.0 + f * (1.0 / 120.0 + f * (1.0 / 720.0))))));
+
+    polynomial * f64::from_bits(((integer as i64 + 1023) as u64) << 52)
+}
+
+pub fn log(x: f64) -> f64 {
+    if x <= 0.0 {
+        return f64::NEG_INFINITY;
+    }
+
+    let bits = x.to_bits();
+    let mut exponent = ((bits >> 52) & 0x7ff) as i64 - 1023;
+    let mut mantissa = f64::from_bits((bits & 0x000f_ffff_ffff_ffff) | 0x3ff0_0000_0000_0000);
+
+    if mantissa > SQRT_2 {
+        mantissa *= 0.5;
+        exponent += 1;
+    }
+
+    let t = (mantissa - 1.0) / (mantissa + 1.0);
+    let t2 = t * t;
+    let series = 2.0 * t * (1.0 + t2 * (1.0 / 3.0 + t2 * (1.0 / 5.0 + t2 * (1.0 / 7.0))));
+
+    series + exponent as f64 * LN_2
+}
+
+pub fn tanh(x: f64) -> f64 {
+    let e = exp(2.0 * x.clamp(-20.0, 20.0));
+    (e - 1.0) / (e + 1.0)
+}
+
+pub fn db_to_gain(level_in_db: f64) -> f64 {
+    if level_in_db <= MINUS_INFINITY_DECIBELS {
+        return 0.0;
+    }
+
+    exp(level_in_db * LN_10 / 20.0)
+}
+
+pub fn gain_to_db(gain: f64) -> f64 {
+    if gain <= 1e-9 {
+        return MINUS_INFINITY_DECIBELS;
+    }
+
+    20.0 * log(gain) / LN_10
+}
+
+pub fn sin_in_place(values: &mut [f64]) {
+    for value in values.iter_mut() {
+        *value = sin(*value);
+    }
+}
+
+pub fn tanh_in_place(values: &mut [f64]) {
+    for value in values.iter_mut() {
+        *value = tanh(*value);
+    }
+}
+
+pub fn db_to_gain_in_place(values: &mut [f64]) {
+    for value in values.iter_mut() {
+        *value = db_to_gain(*value);
+    }
+}
+
+pub fn gain_to_db_in_place(values: &mut [f64]) {
+    for value in values.iter_mut() {
+        *value = gain_to_db(*value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sweep(minimum: f64, maximum: f64) -> impl Iterator<Item = f64> {
+        let steps = 100_000;
+        (0..=steps).map(move |step| minimum + (maximum - minimum) * step as f64 / steps as f64)
+    }
+
+    #[test]
+    fn stays_within_documented_error_bounds() {
+        for x in sweep(-20.0, 20.0) {
+            assert!((sin(x) - x.sin()).abs() < SIN_MAX_ERROR);
+            assert!((cos(x) - x.cos()).abs() < SIN_MAX_ERROR);
+            assert!((tanh(x) - x.tanh()).abs() < TANH_MAX_ERROR);
+        }
+
+        for x in sweep(-100.0, 100.0) {
+            assert!(((exp(x) - x.exp()) / x.exp()).abs() < EXP_MAX_RELATIVE_ERROR);
+        }
+
+        for x in sweep(1e-6, 1000.0) {
+            assert!((log(x) - x.ln()).abs() < LOG_MAX_ERROR);
+        }
+
+        for db in sweep(-120.0, 24.0) {
+            let gain = 10.0_f64.powf(db / 20.0);
+            assert!(((db_to_gain(db) - gain) / gain).abs() < DB_MAX_ERROR);
+            assert!((gain_to_db(gain) - db).abs() < DB_MAX_ERROR);
+        }
+
+        assert_eq!(db_to_gain(MINUS_INFINITY_DECIBELS), 0.0);
+        assert_eq!(gain_to_db(0.0), MINUS_INFINITY_DECIBELS);
+    }
+
+    #[test]
+    fn batch_variants_match_scalar_versions() {
+        let mut values: Vec<f64> = sweep(-3.0, 3.0).take(64).collect();
+        let expected: Vec<f64> = values.iter().map(|value| tanh(*value)).collect();
+        tanh_in_place(&mut values);
+        assert_eq!(values, expected);
+
+        let mut values = vec![-6.0, 0.0, 6.0];
+        db_to_gain_in_place(&mut values);
+        gain_to_db_in_place(&mut values);
+        for (value, expected) in values.iter().zip([-6.0, 0.0, 6.0]) {
+            assert!((value - expected).abs() < DB_MAX_ERROR);
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use std::f64::consts::TAU;
+
+pub struct Fft {
+    size: usize,
+    cosines: Vec<f32>,
+    sines: Vec<f32>,
+    reversed_indices: Vec<usize>,
+}
+
+impl Fft {
+    pub fn new(size: usize) -> Self {
+        assert!(size.is_power_of_two() && size >= 2);
+
+        let bits = size.trailing_zeros();
+
+        Self {
+            size,
+            cosines: (0..size / 2)
+                .map(|index| (TAU * index as f64 / size as f64).cos() as f32)
+                .collect(),
+            sines: (0..size / 2)
+                .map(|index| -(TAU * index as f64 / size as f64).sin() as f32)
+                .collect(),
+            reversed_indices: (0..size)
+                .map(|index| index.reverse_bits() >> (usize::BITS - bits))
+                .collect(),
+        }
+    }
+
+    pub fn forward(&self, real: &mut [f32], imaginary: &mut [f32]) {
+        self.transform(real, imaginary, false);
+    }
+
+    pub fn inverse(&self, real: &mut [f32], imaginary: &mut [f32]) {
+        self.transform(real, imaginary, true);
+
+        let scale = 1.0 / self.size as f32;
+        for index in 0..self.size {
+            real[index] *= scale;
+            imaginary[index] *= scale;
+        }
+    }
+
+    fn transform(&self, real: &mut [f32], imaginary: &mut [f32], inverse: bool) {
+        assert!(real.len() == self.size && imaginary.len() == self.size);
+
+        for index in 0..self.size {
+            let reversed = self.reversed_indices[index];
+            if reversed > index {
+                real.swap(index, reversed);
+                imaginary.swap(index, reversed);
+            }
+        }
+
+        let direction = if inverse { -1.0 } else { 1.0 };
+
+        let mut length = 2;
+        while length <= self.size {
+            let half = length / 2;
+            let stride = self.size / length;
+
+            for start in (0..self.size).step_by(length) {
+                for offset in 0..half {
+                    let cosine = self.cosines[offset * stride];
+                    let sine = direction * self.sines[offset * stride];
+
+                    let even = start + offset;
+                    let odd = even + half;
+
+                    let odd_real = real[odd] * cosine - imaginary[odd] * sine;
+                    let odd_imaginary = real[odd] * sine + imaginary[odd] * cosine;
+
+                    real[odd] = real[even] - odd_real;
+                    imaginary[odd] = imaginary[even] - odd_imaginary;
+                    real[even] += odd_real;
+                    imaginary[even] += odd_imaginary;
+                }
+            }
+
+            length *= 2;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use super::*;
+
+    #[test]
+    fn finds_sinusoid_bin_and_round_trips() {
+        let size = 64;
+        let fft = Fft::new(size);
+
+        let signal: Vec<f32> = (0..size)
+            .map(|index| (TAU * 4.0 * index as f64 / size as f64).cos() as f32)
+            .collect();
+
+        let mut real = signal.clone();
+        let mut imaginary = vec![0.0; size];
+        fft.forward(&mut real, &mut imaginary);
+
+        for bin in 0..size {
+            let magnitude = (real[bin] * real[bin] + imaginary[bin] * imaginary[bin]).sqrt();
+            let expected = if bin == 4 || bin == size - 4 {
+                size as f32 / 2.0
+            } else {
+                0.0
+            };
+            assert_relative_eq!(magnitude, expected, epsilon = 1e-3);
+        }
+
+        fft.inverse(&mut real, &mut imaginary);
+        for (actual, expected) in real.iter().zip(signal.iter()) {
+            assert_relative_eq!(actual, expected, epsilon = 1e-5);
+        }
+    }
+}
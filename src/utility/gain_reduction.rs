@@ -0,0 +1,108 @@
+use lockfree::channel::spsc::{self, Receiver, Sender};
+
+use crate::Timestamp;
+
+const METER_INTERVAL_HZ: f64 = 30.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GainReduction {
+    pub time: Timestamp,
+    pub reduction_db: f64,
+}
+
+pub struct GainReductionMeter {
+    transmitter: Sender<GainReduction>,
+    elapsed_samples: usize,
+    peak_reduction_db: f64,
+}
+
+pub struct GainReductionReceiver {
+    receiver: Receiver<GainReduction>,
+    latest_reduction_db: f64,
+}
+
+pub fn create() -> (GainReductionMeter, GainReductionReceiver) {
+    let (transmitter, receiver) = spsc::create();
+
+    (
+        GainReductionMeter {
+            transmitter,
+            elapsed_samples: 0,
+            peak_reduction_db: 0.0,
+        },
+        GainReductionReceiver {
+            receiver,
+            latest_reduction_db: 0.0,
+        },
+    )
+}
+
+impl GainReductionMeter {
+    pub fn record(&mut self, reduction_db: f64) {
+        self.peak_reduction_db = self.peak_reduction_db.max(reduction_db);
+    }
+
+    pub fn advance(&mut self, start_time: &Timestamp, num_samples: usize, sample_rate: usize) {
+        let interval_samples = (sample_rate as f64 / METER_INTERVAL_HZ) as usize;
+
+        self.elapsed_samples += num_samples;
+        if self.elapsed_samples < interval_samples {
+            return;
+        }
+
+        self.elapsed_samples %= interval_samples.max(1);
+
+        let _ = self.transmitter.send(GainReduction {
+            time: start_time.incremented_by_samples(num_samples, sample_rate),
+            reduction_db: self.peak_reduction_db,
+        });
+
+        self.peak_reduction_db = 0.0;
+    }
+}
+
+impl GainReductionReceiver {
+    pub fn take(&mut self) -> Vec<GainReduction> {
+        let mut readings = Vec::new();
+
+        while let Ok(reading) = self.receiver.recv() {
+            self.latest_reduction_db = reading.reduction_db;
+            readings.push(reading);
+        }
+
+        readings
+    }
+
+    pub fn latest_reduction_db(&mut self) -> f64 {
+        self.take();
+        self.latest_reduction_db
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_peak_reduction_at_meter_rate() {
+        let (mut meter, mut receiver) = create();
+
+        meter.record(3.0);
+        meter.advance(&Timestamp::zero(), 1000, 48_000);
+        assert!(receiver.take().is_empty());
+
+        meter.record(6.0);
+        meter.record(1.0);
+        meter.advance(&Timestamp::zero(), 1000, 48_000);
+        assert_eq!(
+            receiver.take(),
+            vec![GainReduction {
+                time: Timestamp::from_samples(1000.0, 48_000),
+                reduction_db: 6.0,
+            }]
+        );
+
+        meter.advance(&Timestamp::zero(), 1600, 48_000);
+        assert_eq!(receiver.latest_reduction_db(), 0.0);
+    }
+}
@@ -0,0 +1,10 @@
+pub fn interpolate(a: f64, b: f64, amount_of_b: f64) -> f64 {
+    (1.0 - amount_of_b) * a + amount_of_b * b
+}
+
+pub fn interpolate_cubic(previous: f64, a: f64, b: f64, next: f64, amount_of_b: f64) -> f64 {
+    let slope = 0.5 * (b - previous);
+    let curvature = previous - 2.5 * a + 2.0 * b - 0.5 * next;
+    let cubic = 0.5 * (next - previous) + 1.5 * (a - b);
+    ((cubic * amount_of_b + curvature) * amount_of_b + slope) * amount_of_b + a
+}
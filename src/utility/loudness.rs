@@ -0,0 +1,378 @@
+use std::f64::consts::PI;
+
+use crate::{AudioBuffer, SampleLocation};
+
+use super::level::{Level, MINUS_INFINITY_DECIBELS};
+
+const MAXIMUM_NUMBER_OF_CHANNELS: usize = 2;
+const BLOCK_DURATION_SECONDS: f64 = 0.1;
+const MOMENTARY_BLOCKS: usize = 4;
+const SHORT_TERM_BLOCKS: usize = 30;
+const LOUDNESS_OFFSET: f64 = -0.691;
+const ABSOLUTE_GATE_LUFS: f64 = -70.0;
+const RELATIVE_GATE_LU: f64 = -10.0;
+const TRUE_PEAK_OVERSAMPLING: usize = 4;
+const TRUE_PEAK_TAPS: usize = 12;
+const DEFAULT_TARGET_LUFS: f64 = -14.0;
+const DEFAULT_TARGET_TRUE_PEAK_DB: f64 = -1.0;
+
+#[derive(Clone, Copy, Default)]
+struct Biquad {
+    b0: f64,
+    b1: f64,
+    b2: f64,
+    a1: f64,
+    a2: f64,
+    z1: f64,
+    z2: f64,
+}
+
+impl Biquad {
+    fn process(&mut self, input: f64) -> f64 {
+        let output = self.b0 * input + self.z1;
+        self.z1 = self.b1 * input - self.a1 * output + self.z2;
+        self.z2 = self.b2 * input - self.a2 * output;
+        output
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+struct KWeightingFilter {
+    shelf: Biquad,
+    high_pass: Biquad,
+}
+
+impl KWeightingFilter {
+    fn new(sample_rate: usize) -> Self {
+        let sample_rate = sample_rate as f64;
+
+        let k = (PI * 1681.974450955533 / sample_rate).tan();
+        let q = 0.7071752369554196;
+        let high_gain = 10.0_f64.powf(3.999843853973347 / 20.0);
+        let band_gain = high_gain.powf(0.4996667741545416);
+        let a0 = 1.0 + k / q + k * k;
+
+        let shelf = Biquad {
+            b0: (high_gain + band_gain * k / q + k * k) / a0,
+            b1: 2.0 * (k * k - high_gain) / a0,
+            b2: (high_gain - band_gain * k / q + k * k) / a0,
+            a1: 2.0 * (k * k - 1.0) / a0,
+            a2: (1.0 - k / q + k * k) / a0,
+            ..Default::default()
+        };
+
+        let k = (PI * 38.13547087602444 / sample_rate).tan();
+        let q = 0.5003270373238773;
+        let a0 = 1.0 + k / q + k * k;
+
+        let high_pass = Biquad {
+            b0: 1.0,
+            b1: -2.0,
+            b2: 1.0,
+            a1: 2.0 * (k * k - 1.0) / a0,
+            a2: (1.0 - k / q + k * k) / a0,
+            ..Default::default()
+        };
+
+        Self { shelf, high_pass }
+    }
+
+    fn process(&mut self, input: f64) -> f64 {
+        self.high_pass.process(self.shelf.process(input))
+    }
+}
+
+pub struct LoudnessMeter {
+    filters: [KWeightingFilter; MAXIMUM_NUMBER_OF_CHANNELS],
+    block_size: usize,
+    block_position: usize,
+    block_sum: f64,
+    block_powers: [f64; SHORT_TERM_BLOCKS],
+    next_block: usize,
+    num_blocks: usize,
+}
+
+impl LoudnessMeter {
+    pub fn new(sample_rate: usize) -> Self {
+        Self {
+            filters: [KWeightingFilter::new(sample_rate); MAXIMUM_NUMBER_OF_CHANNELS],
+            block_size: (sample_rate as f64 * BLOCK_DURATION_SECONDS).round() as usize,
+            block_position: 0,
+            block_sum: 0.0,
+            block_powers: [0.0; SHORT_TERM_BLOCKS],
+            next_block: 0,
+            num_blocks: 0,
+        }
+    }
+
+    pub fn process_frame(&mut self, samples: &[f64]) -> bool {
+        for (filter, sample) in self.filters.iter_mut().zip(samples.iter()) {
+            let weighted = filter.process(*sample);
+            self.block_sum += weighted * weighted;
+        }
+
+        self.block_position += 1;
+        if self.block_position < self.block_size {
+            return false;
+        }
+
+        self.block_powers[self.next_block] = self.block_sum / self.block_size as f64;
+        self.next_block = (self.next_block + 1) % SHORT_TERM_BLOCKS;
+        self.num_blocks = (self.num_blocks + 1).min(SHORT_TERM_BLOCKS);
+        self.block_position = 0;
+        self.block_sum = 0.0;
+        true
+    }
+
+    pub fn process(&mut self, buffer: &dyn AudioBuffer) {
+        let num_channels = buffer.num_channels().min(MAXIMUM_NUMBER_OF_CHANNELS);
+        let mut samples = [0.0; MAXIMUM_NUMBER_OF_CHANNELS];
+
+        for frame in 0..buffer.num_frames() {
+            for (channel, sample) in samples.iter_mut().enumerate().take(num_channels) {
+                *sample = buffer.get_sample(SampleLocation::new(channel, frame)) as f64;
+            }
+
+            self.process_frame(&samples[..num_channels]);
+        }
+    }
+
+    fn power_over(&self, num_blocks: usize) -> f64 {
+        let num_blocks = num_blocks.min(self.num_blocks);
+        if num_blocks == 0 {
+            return 0.0;
+        }
+
+        (1..=num_blocks)
+            .map(|offset| {
+                self.block_powers
+                    [(self.next_block + SHORT_TERM_BLOCKS - offset) % SHORT_TERM_BLOCKS]
+            })
+            .sum::<f64>()
+            / num_blocks as f64
+    }
+
+    fn loudness_over(&self, num_blocks: usize) -> f64 {
+        power_to_lufs(self.power_over(num_blocks))
+    }
+
+    pub fn momentary_lufs(&self) -> f64 {
+        self.loudness_over(MOMENTARY_BLOCKS)
+    }
+
+    pub fn short_term_lufs(&self) -> f64 {
+        self.loudness_over(SHORT_TERM_BLOCKS)
+    }
+
+    pub fn reset(&mut self) {
+        self.block_position = 0;
+        self.block_sum = 0.0;
+        self.next_block = 0;
+        self.num_blocks = 0;
+    }
+}
+
+fn power_to_lufs(power: f64) -> f64 {
+    if power <= 0.0 {
+        return MINUS_INFINITY_DECIBELS;
+    }
+
+    (LOUDNESS_OFFSET + 10.0 * power.log10()).max(MINUS_INFINITY_DECIBELS)
+}
+
+fn lufs_to_power(lufs: f64) -> f64 {
+    10.0_f64.powf((lufs - LOUDNESS_OFFSET) / 10.0)
+}
+
+fn gated_mean(powers: &[f64], threshold: f64) -> f64 {
+    let (sum, count) = powers
+        .iter()
+        .filter(|power| **power > threshold)
+        .fold((0.0, 0), |(sum, count), power| (sum + power, count + 1));
+
+    if count == 0 {
+        0.0
+    } else {
+        sum / count as f64
+    }
+}
+
+pub fn integrated_lufs(buffer: &dyn AudioBuffer) -> f64 {
+    let mut meter = LoudnessMeter::new(buffer.sample_rate());
+    let num_channels = buffer.num_channels().min(MAXIMUM_NUMBER_OF_CHANNELS);
+    let mut samples = [0.0; MAXIMUM_NUMBER_OF_CHANNELS];
+    let mut gating_powers = Vec::new();
+
+    for frame in 0..buffer.num_frames() {
+        for (channel, sample) in samples.iter_mut().enumerate().take(num_channels) {
+            *sample = buffer.get_sample(SampleLocation::new(channel, frame)) as f64;
+        }
+
+        if meter.process_frame(&samples[..num_channels]) && meter.num_blocks >= MOMENTARY_BLOCKS {
+            gating_powers.push(meter.power_over(MOMENTARY_BLOCKS));
+        }
+    }
+
+    let absolute_mean = gated_mean(&gating_powers, lufs_to_power(ABSOLUTE_GATE_LUFS));
+    if absolute_mean <= 0.0 {
+        return MINUS_INFINITY_DECIBELS;
+    }
+
+    let relative_threshold = lufs_to_power(power_to_lufs(absolute_mean) + RELATIVE_GATE_LU);
+    power_to_lufs(gated_mean(
+        &gating_powers,
+        relative_threshold.max(lufs_to_power(ABSOLUTE_GATE_LUFS)),
+    ))
+}
+
+fn true_peak_coefficients() -> [[f64; TRUE_PEAK_TAPS]; TRUE_PEAK_OVERSAMPLING] {
+    let half_width = (TRUE_PEAK_TAPS / 2) as f64;
+    let mut coefficients = [[0.0; TRUE_PEAK_TAPS]; TRUE_PEAK_OVERSAMPLING];
+
+    for (phase, taps) in coefficients.iter_mut().enumerate() {
+        for (tap, coefficient) in taps.iter_mut().enumerate() {
+            let distance =
+                phase as f64 / TRUE_PEAK_OVERSAMPLING as f64 + half_width - 1.0 - tap as f64;
+
+            let sinc = if distance == 0.0 {
+                1.0
+            } else {
+                (PI * distance).sin() / (PI * distance)
+            };
+            let window = 0.5 * (1.0 + (PI * distance / half_width).cos());
+
+            *coefficient = sinc * window;
+        }
+    }
+
+    coefficients
+}
+
+pub fn true_peak_db(buffer: &dyn AudioBuffer) -> f64 {
+    let coefficients = true_peak_coefficients();
+    let first_offset = TRUE_PEAK_TAPS / 2 - 1;
+    let num_frames = buffer.num_frames();
+    let mut peak: f64 = 0.0;
+
+    for channel in 0..buffer.num_channels() {
+        for frame in 0..num_frames {
+            for taps in coefficients.iter() {
+                let value: f64 = taps
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(tap, coefficient)| {
+                        let source = (frame + tap).checked_sub(first_offset)?;
+                        (source < num_frames).then(|| {
+                            coefficient
+                                * buffer.get_sample(SampleLocation::new(channel, source)) as f64
+                        })
+                    })
+                    .sum();
+
+                peak = peak.max(value.abs());
+            }
+        }
+    }
+
+    Level::from_gain(peak).as_db()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LoudnessTarget {
+    pub integrated_lufs: f64,
+    pub true_peak_db: f64,
+}
+
+impl LoudnessTarget {
+    pub fn new(integrated_lufs: f64, true_peak_db: f64) -> Self {
+        Self {
+            integrated_lufs,
+            true_peak_db,
+        }
+    }
+}
+
+impl Default for LoudnessTarget {
+    fn default() -> Self {
+        Self::new(DEFAULT_TARGET_LUFS, DEFAULT_TARGET_TRUE_PEAK_DB)
+    }
+}
+
+pub fn normalize_loudness(buffer: &mut dyn AudioBuffer, target: &LoudnessTarget) -> f64 {
+    let loudness = integrated_lufs(buffer);
+    if loudness <= MINUS_INFINITY_DECIBELS {
+        return 0.0;
+    }
+
+    let gain_in_db =
+        (target.integrated_lufs - loudness).min(target.true_peak_db - true_peak_db(buffer));
+    let gain = Level::from_db(gain_in_db).as_gain() as f32;
+
+    for channel in 0..buffer.num_channels() {
+        for frame in 0..buffer.num_frames() {
+            let location = SampleLocation::new(channel, frame);
+            buffer.set_sample(location, gain * buffer.get_sample(location));
+        }
+    }
+
+    gain_in_db
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::TAU;
+
+    use crate::OwnedAudioBuffer;
+
+    use super::*;
+
+    fn sine(amplitude: f64, num_channels: usize, seconds: f64) -> OwnedAudioBuffer {
+        let sample_rate = 48_000;
+        let num_frames = (seconds * sample_rate as f64) as usize;
+        let mut buffer = OwnedAudioBuffer::new(num_frames, num_channels, sample_rate);
+
+        for frame in 0..num_frames {
+            let value = amplitude * (TAU * 997.0 * frame as f64 / sample_rate as f64).sin();
+            for channel in 0..num_channels {
+                buffer.set_sample(SampleLocation::new(channel, frame), value as f32);
+            }
+        }
+
+        buffer
+    }
+
+    #[test]
+    fn measures_reference_tone() {
+        let mut meter = LoudnessMeter::new(48_000);
+        assert_eq!(meter.short_term_lufs(), MINUS_INFINITY_DECIBELS);
+
+        meter.process(&sine(1.0, 1, 3.0));
+        assert!((meter.momentary_lufs() - -3.01).abs() < 0.05);
+        assert!((meter.short_term_lufs() - -3.01).abs() < 0.05);
+
+        let mut stereo_meter = LoudnessMeter::new(48_000);
+        stereo_meter.process(&sine(0.1, 2, 0.4));
+        assert!((stereo_meter.momentary_lufs() - -20.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn normalizes_to_loudness_and_true_peak_targets() {
+        let mut quiet = sine(0.1, 2, 3.0);
+        assert!((integrated_lufs(&quiet) - -20.0).abs() < 0.05);
+
+        let gain_in_db = normalize_loudness(&mut quiet, &LoudnessTarget::new(-14.0, 0.0));
+        assert!((gain_in_db - 6.0).abs() < 0.05);
+        assert!((integrated_lufs(&quiet) - -14.0).abs() < 0.05);
+
+        let mut loud = sine(0.5, 1, 3.0);
+        normalize_loudness(&mut loud, &LoudnessTarget::new(-3.0, -1.0));
+        assert!((true_peak_db(&loud) - -1.0).abs() < 0.05);
+        assert!(integrated_lufs(&loud) < -3.0);
+
+        let mut silence = OwnedAudioBuffer::new(48_000, 1, 48_000);
+        assert_eq!(
+            normalize_loudness(&mut silence, &LoudnessTarget::default()),
+            0.0
+        );
+    }
+}
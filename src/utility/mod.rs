@@ -1,2 +1,21 @@
+pub mod audio_export;
+pub mod audio_file;
+#[cfg(test)]
+pub(crate) mod block_size_sweep;
+pub mod envelope_follower;
+pub mod fast_math;
+pub mod fft;
+pub mod gain_reduction;
+pub mod interpolation;
 pub mod level;
+pub mod loudness;
+#[cfg(feature = "presets")]
+pub mod preset_format;
+pub mod random;
+pub mod realtime_log;
+pub mod sample_loader;
 pub mod scoped_time_measure;
+pub mod sound_bank;
+pub mod state_variable_filter;
+pub mod stft;
+pub mod trace;
@@ -0,0 +1,219 @@
+use std::{fmt, marker::PhantomData};
+
+use serde::{
+    de::{MapAccess, Visitor},
+    ser::SerializeMap,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use crate::parameter::preset::{NodePreset, PresetError, PresetValue};
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PresetFile {
+    node_type: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty", with = "named_values")]
+    parameters: Vec<(String, f64)>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty", with = "named_values")]
+    state: Vec<(String, PresetValue)>,
+}
+
+impl From<&NodePreset> for PresetFile {
+    fn from(preset: &NodePreset) -> Self {
+        Self {
+            node_type: String::from(preset.get_node_type()),
+            name: String::from(preset.get_name()),
+            parameters: preset.parameters().to_vec(),
+            state: preset.state().to_vec(),
+        }
+    }
+}
+
+impl From<PresetFile> for NodePreset {
+    fn from(file: PresetFile) -> Self {
+        let mut preset = NodePreset::new(&file.node_type, &file.name);
+
+        for (name, value) in file.parameters {
+            preset.set_parameter(&name, value);
+        }
+
+        for (key, value) in file.state {
+            preset.set_state(&key, value);
+        }
+
+        preset
+    }
+}
+
+impl Serialize for PresetValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            PresetValue::Number(value) => serializer.serialize_f64(*value),
+            PresetValue::Text(value) => serializer.serialize_str(value),
+            PresetValue::Boolean(value) => serializer.serialize_bool(*value),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for PresetValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct PresetValueVisitor;
+
+        impl Visitor<'_> for PresetValueVisitor {
+            type Value = PresetValue;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a number, string or boolean")
+            }
+
+            fn visit_bool<E>(self, value: bool) -> Result<Self::Value, E> {
+                Ok(PresetValue::Boolean(value))
+            }
+
+            fn visit_i64<E>(self, value: i64) -> Result<Self::Value, E> {
+                Ok(PresetValue::Number(value as f64))
+            }
+
+            fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E> {
+                Ok(PresetValue::Number(value as f64))
+            }
+
+            fn visit_f64<E>(self, value: f64) -> Result<Self::Value, E> {
+                Ok(PresetValue::Number(value))
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E> {
+                Ok(PresetValue::Text(String::from(value)))
+            }
+        }
+
+        deserializer.deserialize_any(PresetValueVisitor)
+    }
+}
+
+mod named_values {
+    use super::*;
+
+    pub fn serialize<S, V>(entries: &[(String, V)], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        V: Serialize,
+    {
+        let mut map = serializer.serialize_map(Some(entries.len()))?;
+        for (key, value) in entries {
+            map.serialize_entry(key, value)?;
+        }
+        map.end()
+    }
+
+    pub fn deserialize<'de, D, V>(deserializer: D) -> Result<Vec<(String, V)>, D::Error>
+    where
+        D: Deserializer<'de>,
+        V: Deserialize<'de>,
+    {
+        struct NamedValuesVisitor<V>(PhantomData<V>);
+
+        impl<'de, V: Deserialize<'de>> Visitor<'de> for NamedValuesVisitor<V> {
+            type Value = Vec<(String, V)>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a table of named values")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut entries = Vec::with_capacity(map.size_hint().unwrap_or(0));
+                while let Some(entry) = map.next_entry()? {
+                    entries.push(entry);
+                }
+                Ok(entries)
+            }
+        }
+
+        deserializer.deserialize_map(NamedValuesVisitor(PhantomData))
+    }
+}
+
+fn parse_error(line: usize, message: &str) -> PresetError {
+    PresetError::Parse {
+        line,
+        message: String::from(message),
+    }
+}
+
+pub fn write_toml(preset: &NodePreset) -> String {
+    toml::to_string(&PresetFile::from(preset)).unwrap_or_default()
+}
+
+pub fn write_json(preset: &NodePreset) -> String {
+    serde_json::to_string_pretty(&PresetFile::from(preset)).unwrap_or_default() + "\n"
+}
+
+pub fn parse_toml(text: &str) -> Result<NodePreset, PresetError> {
+    toml::from_str::<PresetFile>(text)
+        .map(NodePreset::from)
+        .map_err(|error| {
+            let line = error
+                .span()
+                .map(|span| text[..span.start].matches('\n').count() + 1)
+                .unwrap_or(0);
+            parse_error(line, error.message())
+        })
+}
+
+pub fn parse_json(text: &str) -> Result<NodePreset, PresetError> {
+    serde_json::from_str::<PresetFile>(text)
+        .map(NodePreset::from)
+        .map_err(|error| parse_error(error.line(), &error.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn preset() -> NodePreset {
+        NodePreset::new("oscillator", "Bright \"Lead\"")
+            .with_parameter("frequency", 440.0)
+            .with_parameter("gain", 0.5)
+            .with_state("waveform", PresetValue::Text(String::from("saw")))
+            .with_state("num_inputs", PresetValue::Number(3.0))
+            .with_state("enabled", PresetValue::Boolean(true))
+    }
+
+    #[test]
+    fn round_trips_toml_and_json() {
+        let preset = preset();
+
+        assert_eq!(parse_toml(&write_toml(&preset)), Ok(preset.clone()));
+        assert_eq!(parse_json(&write_json(&preset)), Ok(preset));
+    }
+
+    #[test]
+    fn parses_hand_written_presets() {
+        let toml = "# Factory preset\nnode_type = \"gain\"\nname = \"Half\"\n\n[parameters]\ngain = 0.5 # -6 dB\n\n[state]\nnum_inputs = 2\n";
+        let json = "{\"node_type\": \"gain\", \"name\": \"Half\", \"parameters\": {\"gain\": 5e-1}, \"state\": {\"num_inputs\": 2}}";
+
+        let expected = NodePreset::new("gain", "Half")
+            .with_parameter("gain", 0.5)
+            .with_state("num_inputs", PresetValue::Number(2.0));
+        assert_eq!(parse_toml(toml), Ok(expected.clone()));
+        assert_eq!(parse_json(json), Ok(expected));
+    }
+
+    #[test]
+    fn reports_errors_with_line_numbers() {
+        assert!(matches!(
+            parse_toml("node_type = \"gain\"\n[parameters]\ngain = \"loud\"\n"),
+            Err(PresetError::Parse { line: 3, .. })
+        ));
+        assert!(matches!(
+            parse_json("{\n\"name\": \"Missing type\"\n}"),
+            Err(PresetError::Parse { line: 3, .. })
+        ));
+        assert!(matches!(
+            parse_json("{\"node_type\": \"gain\",\n\"parameters\": {\"gain\" 0.5}}"),
+            Err(PresetError::Parse { line: 2, .. })
+        ));
+    }
+}
@@ -0,0 +1,49 @@
+pub struct Random {
+    state: u64,
+}
+
+impl Default for Random {
+    fn default() -> Self {
+        Self::with_seed(0x2545_F491_4F6C_DD1D)
+    }
+}
+
+impl Random {
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            state: if seed == 0 { 1 } else { seed },
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    pub fn next_unipolar(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1_u64 << 53) as f64
+    }
+
+    pub fn next_bipolar(&mut self) -> f64 {
+        2.0 * self.next_unipolar() - 1.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_are_in_range_and_repeatable() {
+        let mut first = Random::with_seed(42);
+        let mut second = Random::with_seed(42);
+
+        for _ in 0..1000 {
+            let value = first.next_bipolar();
+            assert!((-1.0..1.0).contains(&value));
+            assert_eq!(value, second.next_bipolar());
+        }
+    }
+}
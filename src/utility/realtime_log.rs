@@ -0,0 +1,259 @@
+use std::{
+    cell::UnsafeCell,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex, Once,
+    },
+    thread,
+    time::Duration,
+};
+
+const LOG_CAPACITY: usize = 1024;
+const LOGGER_INTERVAL: Duration = Duration::from_millis(50);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Warning,
+    Error,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LogRecord {
+    pub level: LogLevel,
+    pub message: &'static str,
+    pub value: Option<f64>,
+}
+
+impl Default for LogRecord {
+    fn default() -> Self {
+        Self {
+            level: LogLevel::Debug,
+            message: "",
+            value: None,
+        }
+    }
+}
+
+struct Slot {
+    sequence: AtomicUsize,
+    record: UnsafeCell<LogRecord>,
+}
+
+pub struct LogRing {
+    slots: Box<[Slot]>,
+    mask: usize,
+    write_position: AtomicUsize,
+    read_position: AtomicUsize,
+    dropped: AtomicUsize,
+}
+
+unsafe impl Sync for LogRing {}
+
+impl LogRing {
+    pub fn with_capacity(capacity: usize) -> Self {
+        let capacity = capacity.max(2).next_power_of_two();
+
+        let slots = (0..capacity)
+            .map(|index| Slot {
+                sequence: AtomicUsize::new(index),
+                record: UnsafeCell::new(LogRecord::default()),
+            })
+            .collect();
+
+        Self {
+            slots,
+            mask: capacity - 1,
+            write_position: AtomicUsize::new(0),
+            read_position: AtomicUsize::new(0),
+            dropped: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn push(&self, record: LogRecord) -> bool {
+        let mut position = self.write_position.load(Ordering::Relaxed);
+
+        loop {
+            let slot = &self.slots[position & self.mask];
+            let sequence = slot.sequence.load(Ordering::Acquire);
+            let difference = sequence as isize - position as isize;
+
+            if difference == 0 {
+                match self.write_position.compare_exchange_weak(
+                    position,
+                    position + 1,
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => {
+                        unsafe { *slot.record.get() = record };
+                        slot.sequence.store(position + 1, Ordering::Release);
+                        return true;
+                    }
+                    Err(current) => position = current,
+                }
+            } else if difference < 0 {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+                return false;
+            } else {
+                position = self.write_position.load(Ordering::Relaxed);
+            }
+        }
+    }
+
+    pub fn pop(&self) -> Option<LogRecord> {
+        let mut position = self.read_position.load(Ordering::Relaxed);
+
+        loop {
+            let slot = &self.slots[position & self.mask];
+            let sequence = slot.sequence.load(Ordering::Acquire);
+            let difference = sequence as isize - (position + 1) as isize;
+
+            if difference == 0 {
+                match self.read_position.compare_exchange_weak(
+                    position,
+                    position + 1,
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => {
+                        let record = unsafe { *slot.record.get() };
+                        slot.sequence
+                            .store(position + self.mask + 1, Ordering::Release);
+                        return Some(record);
+                    }
+                    Err(current) => position = current,
+                }
+            } else if difference < 0 {
+                return None;
+            } else {
+                position = self.read_position.load(Ordering::Relaxed);
+            }
+        }
+    }
+
+    pub fn take_dropped_count(&self) -> usize {
+        self.dropped.swap(0, Ordering::Relaxed)
+    }
+}
+
+pub type LogHandler = Box<dyn FnMut(&LogRecord) + Send>;
+
+lazy_static! {
+    static ref LOG: LogRing = LogRing::with_capacity(LOG_CAPACITY);
+    static ref HANDLER: Mutex<Option<LogHandler>> = Mutex::new(None);
+}
+
+static LOGGER_STARTED: Once = Once::new();
+
+pub fn log(level: LogLevel, message: &'static str) {
+    LOG.push(LogRecord {
+        level,
+        message,
+        value: None,
+    });
+}
+
+pub fn log_value(level: LogLevel, message: &'static str, value: f64) {
+    LOG.push(LogRecord {
+        level,
+        message,
+        value: Some(value),
+    });
+}
+
+pub fn set_log_handler(handler: Option<LogHandler>) {
+    if let Ok(mut current) = HANDLER.lock() {
+        *current = handler;
+    }
+}
+
+pub fn start_logger_thread() {
+    LOGGER_STARTED.call_once(|| {
+        thread::spawn(|| loop {
+            drain_log();
+            thread::sleep(LOGGER_INTERVAL);
+        });
+    });
+}
+
+pub fn drain_log() {
+    let mut handler = match HANDLER.lock() {
+        Ok(handler) => handler,
+        Err(_) => return,
+    };
+
+    while let Some(record) = LOG.pop() {
+        match handler.as_mut() {
+            Some(handler) => handler(&record),
+            None => print_record(&record),
+        }
+    }
+
+    let dropped = LOG.take_dropped_count();
+    if dropped > 0 {
+        let record = LogRecord {
+            level: LogLevel::Warning,
+            message: "Realtime log messages dropped",
+            value: Some(dropped as f64),
+        };
+
+        match handler.as_mut() {
+            Some(handler) => handler(&record),
+            None => print_record(&record),
+        }
+    }
+}
+
+fn print_record(record: &LogRecord) {
+    match record.value {
+        Some(value) => eprintln!("[{:?}] {}: {}", record.level, record.message, value),
+        None => eprintln!("[{:?}] {}", record.level, record.message),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn preserves_order_and_counts_dropped_records() {
+        let ring = LogRing::with_capacity(4);
+
+        for index in 0..6 {
+            ring.push(LogRecord {
+                level: LogLevel::Info,
+                message: "message",
+                value: Some(index as f64),
+            });
+        }
+
+        let values: Vec<Option<f64>> = std::iter::from_fn(|| ring.pop())
+            .map(|record| record.value)
+            .collect();
+
+        assert_eq!(values, vec![Some(0.0), Some(1.0), Some(2.0), Some(3.0)]);
+        assert_eq!(ring.take_dropped_count(), 2);
+        assert_eq!(ring.take_dropped_count(), 0);
+    }
+
+    #[test]
+    fn reuses_slots_after_reading() {
+        let ring = LogRing::with_capacity(2);
+
+        for index in 0..10 {
+            assert!(ring.push(LogRecord {
+                level: LogLevel::Warning,
+                message: "message",
+                value: Some(index as f64),
+            }));
+            assert_eq!(
+                ring.pop().and_then(|record| record.value),
+                Some(index as f64)
+            );
+        }
+
+        assert!(ring.pop().is_none());
+    }
+}
@@ -0,0 +1,161 @@
+use std::{sync::Arc, thread};
+
+use lockfree::channel::mpsc::Sender;
+
+use crate::{
+    buffer::{audio_buffer::AudioBuffer, streaming_audio_buffer::StreamingAudioBuffer},
+    commands::{id::Id, notification::Notification},
+    dsp::sampler::processor::SharedSample,
+};
+
+use super::audio_file::{decode_wav_samples, open_wav_file, AudioFileError};
+
+const FRAMES_PER_CHUNK: usize = 4096;
+const PROGRESS_NOTIFICATION_STEP: f64 = 0.01;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SampleLoadStatus {
+    Loading(f64),
+    Loaded,
+    Failed(String),
+}
+
+#[derive(Clone)]
+pub struct SampleHandle {
+    id: Id,
+    buffer: Arc<StreamingAudioBuffer>,
+}
+
+impl SampleHandle {
+    pub fn get_id(&self) -> Id {
+        self.id
+    }
+
+    pub fn get_sample(&self) -> SharedSample {
+        self.buffer.clone()
+    }
+
+    pub fn progress(&self) -> f64 {
+        self.buffer.progress()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.buffer.is_complete()
+    }
+
+    pub fn num_frames(&self) -> usize {
+        self.buffer.num_frames()
+    }
+}
+
+pub fn load_sample_async(
+    path: &str,
+    notification_tx: Sender<Notification>,
+) -> Result<SampleHandle, AudioFileError> {
+    let mut reader = open_wav_file(path)?;
+
+    let specification = reader.spec();
+    let num_channels = specification.channels as usize;
+    let num_frames = reader.len() as usize / num_channels;
+
+    let handle = SampleHandle {
+        id: Id::generate(),
+        buffer: Arc::new(StreamingAudioBuffer::new(
+            num_frames,
+            num_channels,
+            specification.sample_rate as usize,
+        )),
+    };
+
+    let id = handle.id;
+    let buffer = handle.buffer.clone();
+
+    thread::spawn(move || {
+        let mut chunk = Vec::with_capacity(FRAMES_PER_CHUNK * num_channels);
+        let mut last_notified_progress = 0.0;
+
+        for sample in decode_wav_samples(&mut reader) {
+            match sample {
+                Ok(sample) => chunk.push(sample),
+                Err(error) => {
+                    let _ = notification_tx
+                        .send(Notification::SampleLoadFailed(id, format!("{:?}", error)));
+                    return;
+                }
+            }
+
+            if chunk.len() == chunk.capacity() {
+                buffer.append_interleaved(&chunk);
+                chunk.clear();
+
+                if buffer.progress() - last_notified_progress >= PROGRESS_NOTIFICATION_STEP {
+                    last_notified_progress = buffer.progress();
+                    let _ = notification_tx
+                        .send(Notification::SampleLoadProgress(id, last_notified_progress));
+                }
+            }
+        }
+
+        buffer.append_interleaved(&chunk);
+        let _ = notification_tx.send(Notification::SampleLoaded(id));
+    });
+
+    Ok(handle)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use crate::{buffer::sample_location::SampleLocation, Context};
+
+    use super::*;
+
+    fn write_test_file(path: &str, num_frames: usize) {
+        let specification = hound::WavSpec {
+            channels: 1,
+            sample_rate: 44100,
+            bits_per_sample: 32,
+            sample_format: hound::SampleFormat::Float,
+        };
+
+        let mut writer = hound::WavWriter::create(path, specification).unwrap();
+        for frame in 0..num_frames {
+            writer.write_sample(frame as f32).unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+
+    #[test]
+    fn loads_sample_in_background() {
+        let path = std::env::temp_dir().join(format!("loader-{}.wav", std::process::id()));
+        let path = path.to_str().unwrap();
+        write_test_file(path, 3 * FRAMES_PER_CHUNK + 10);
+
+        let mut context = Context::new(44100);
+        let handle = context.load_sample_async(path).unwrap();
+        assert_eq!(handle.num_frames(), 3 * FRAMES_PER_CHUNK + 10);
+
+        let start = Instant::now();
+        while context.get_sample_load_status(&handle) != Some(&SampleLoadStatus::Loaded) {
+            assert!(start.elapsed() < Duration::from_secs(10));
+            thread::sleep(Duration::from_millis(1));
+            context.process_notifications();
+        }
+
+        assert!(handle.is_complete());
+        let sample = handle.get_sample();
+        assert_eq!(
+            sample.get_sample(SampleLocation::new(0, 3 * FRAMES_PER_CHUNK + 5)),
+            (3 * FRAMES_PER_CHUNK + 5) as f32
+        );
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn reports_missing_file() {
+        let mut context = Context::new(44100);
+        assert!(context.load_sample_async("/does/not/exist.wav").is_err());
+    }
+}
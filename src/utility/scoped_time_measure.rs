@@ -1,3 +1,4 @@
+#[allow(dead_code)]
 pub struct ScopedTimeMeasure {
     start: std::time::Instant,
 }
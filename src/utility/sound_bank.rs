@@ -0,0 +1,322 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    sync::Arc,
+};
+
+use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression};
+
+use crate::{
+    buffer::{
+        audio_buffer::AudioBuffer, owned_audio_buffer::OwnedAudioBuffer,
+        sample_location::SampleLocation, sample_store::SampleStore,
+    },
+    commands::id::Id,
+    parameter::snapshot::ParameterSnapshot,
+};
+
+const BANK_FILE_MAGIC: &[u8; 8] = b"RAEBANK\0";
+const BANK_FILE_VERSION: u32 = 1;
+const BYTES_PER_SAMPLE: usize = std::mem::size_of::<f32>();
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BankPreset {
+    values: Vec<(String, f64)>,
+}
+
+impl BankPreset {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_value(mut self, parameter_name: &str, value: f64) -> Self {
+        self.set_value(parameter_name, value);
+        self
+    }
+
+    pub fn set_value(&mut self, parameter_name: &str, value: f64) {
+        match self
+            .values
+            .iter_mut()
+            .find(|(name, _)| name == parameter_name)
+        {
+            Some((_, existing)) => *existing = value,
+            None => self.values.push((String::from(parameter_name), value)),
+        }
+    }
+
+    pub fn get_value(&self, parameter_name: &str) -> Option<f64> {
+        self.values
+            .iter()
+            .find(|(name, _)| name == parameter_name)
+            .map(|(_, value)| *value)
+    }
+
+    pub fn to_snapshot(&self, parameter_ids: &HashMap<String, Id>) -> ParameterSnapshot {
+        let mut snapshot = ParameterSnapshot::default();
+
+        for (name, value) in self.values.iter() {
+            if let Some(id) = parameter_ids.get(name) {
+                snapshot.set_value(*id, *value);
+            }
+        }
+
+        snapshot
+    }
+}
+
+#[derive(Default)]
+pub struct SoundBank {
+    samples: Vec<(String, OwnedAudioBuffer)>,
+    presets: Vec<(String, BankPreset)>,
+}
+
+impl SoundBank {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_sample(&mut self, name: &str, sample: OwnedAudioBuffer) {
+        self.samples.retain(|(existing, _)| existing != name);
+        self.samples.push((String::from(name), sample));
+    }
+
+    pub fn add_preset(&mut self, name: &str, preset: BankPreset) {
+        self.presets.retain(|(existing, _)| existing != name);
+        self.presets.push((String::from(name), preset));
+    }
+
+    pub fn sample_names(&self) -> impl Iterator<Item = &str> {
+        self.samples.iter().map(|(name, _)| name.as_str())
+    }
+
+    pub fn get_preset(&self, name: &str) -> Option<&BankPreset> {
+        self.presets
+            .iter()
+            .find(|(existing, _)| existing == name)
+            .map(|(_, preset)| preset)
+    }
+
+    pub fn register(self, store: &mut SampleStore) -> Vec<Arc<OwnedAudioBuffer>> {
+        self.samples
+            .into_iter()
+            .map(|(name, sample)| store.insert(&name, sample))
+            .collect()
+    }
+
+    pub fn write(&self, path: &str) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()
+    }
+
+    pub fn read(path: &str) -> io::Result<Self> {
+        Self::read_from(&mut BufReader::new(File::open(path)?))
+    }
+
+    pub fn write_to(&self, writer: &mut dyn Write) -> io::Result<()> {
+        writer.write_all(BANK_FILE_MAGIC)?;
+        write_u32(writer, BANK_FILE_VERSION)?;
+        write_u32(writer, self.samples.len() as u32)?;
+        write_u32(writer, self.presets.len() as u32)?;
+
+        for (name, sample) in self.samples.iter() {
+            write_string(writer, name)?;
+            write_u32(writer, sample.num_channels() as u32)?;
+            write_u32(writer, sample.sample_rate() as u32)?;
+            write_u32(writer, sample.num_frames() as u32)?;
+
+            let compressed = compress_sample(sample)?;
+            write_u32(writer, compressed.len() as u32)?;
+            writer.write_all(&compressed)?;
+        }
+
+        for (name, preset) in self.presets.iter() {
+            write_string(writer, name)?;
+            write_u32(writer, preset.values.len() as u32)?;
+
+            for (parameter_name, value) in preset.values.iter() {
+                write_string(writer, parameter_name)?;
+                writer.write_all(&value.to_le_bytes())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn read_from(reader: &mut dyn Read) -> io::Result<Self> {
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic)?;
+
+        if &magic != BANK_FILE_MAGIC {
+            return Err(invalid_data("Not a sound bank file"));
+        }
+
+        if read_u32(reader)? != BANK_FILE_VERSION {
+            return Err(invalid_data("Unsupported sound bank version"));
+        }
+
+        let num_samples = read_u32(reader)? as usize;
+        let num_presets = read_u32(reader)? as usize;
+
+        let mut bank = Self::new();
+
+        for _ in 0..num_samples {
+            let name = read_string(reader)?;
+            let num_channels = read_u32(reader)? as usize;
+            let sample_rate = read_u32(reader)? as usize;
+            let num_frames = read_u32(reader)? as usize;
+            let compressed_length = read_u32(reader)? as usize;
+
+            let mut compressed = vec![0u8; compressed_length];
+            reader.read_exact(&mut compressed)?;
+
+            let data = decompress_samples(&compressed, num_frames * num_channels)?;
+            bank.add_sample(
+                &name,
+                OwnedAudioBuffer::new_from_data(data, num_channels, sample_rate),
+            );
+        }
+
+        for _ in 0..num_presets {
+            let name = read_string(reader)?;
+            let num_values = read_u32(reader)? as usize;
+
+            let mut preset = BankPreset::new();
+            for _ in 0..num_values {
+                let parameter_name = read_string(reader)?;
+                let mut value = [0u8; 8];
+                reader.read_exact(&mut value)?;
+                preset.set_value(&parameter_name, f64::from_le_bytes(value));
+            }
+
+            bank.add_preset(&name, preset);
+        }
+
+        Ok(bank)
+    }
+}
+
+fn compress_sample(sample: &OwnedAudioBuffer) -> io::Result<Vec<u8>> {
+    let num_samples = sample.num_frames() * sample.num_channels();
+    let mut shuffled = vec![0u8; num_samples * BYTES_PER_SAMPLE];
+
+    let mut index = 0;
+    for frame in 0..sample.num_frames() {
+        for channel in 0..sample.num_channels() {
+            let bytes = sample
+                .get_sample(SampleLocation::new(channel, frame))
+                .to_le_bytes();
+
+            for (byte_index, byte) in bytes.iter().enumerate() {
+                shuffled[byte_index * num_samples + index] = *byte;
+            }
+
+            index += 1;
+        }
+    }
+
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&shuffled)?;
+    encoder.finish()
+}
+
+fn decompress_samples(compressed: &[u8], num_samples: usize) -> io::Result<Vec<f32>> {
+    let mut shuffled = vec![0u8; num_samples * BYTES_PER_SAMPLE];
+    DeflateDecoder::new(compressed).read_exact(&mut shuffled)?;
+
+    Ok((0..num_samples)
+        .map(|index| {
+            let mut bytes = [0u8; BYTES_PER_SAMPLE];
+            for (byte_index, byte) in bytes.iter_mut().enumerate() {
+                *byte = shuffled[byte_index * num_samples + index];
+            }
+            f32::from_le_bytes(bytes)
+        })
+        .collect())
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn write_u32(writer: &mut dyn Write, value: u32) -> io::Result<()> {
+    writer.write_all(&value.to_le_bytes())
+}
+
+fn read_u32(reader: &mut dyn Read) -> io::Result<u32> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn write_string(writer: &mut dyn Write, value: &str) -> io::Result<()> {
+    write_u32(writer, value.len() as u32)?;
+    writer.write_all(value.as_bytes())
+}
+
+fn read_string(reader: &mut dyn Read) -> io::Result<String> {
+    let length = read_u32(reader)? as usize;
+    let mut bytes = vec![0u8; length];
+    reader.read_exact(&mut bytes)?;
+    String::from_utf8(bytes).map_err(|_| invalid_data("Invalid string in sound bank"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_sample() -> OwnedAudioBuffer {
+        let data = (0..2000)
+            .map(|index| (index as f32 * 0.01).sin() * 0.5)
+            .collect();
+        OwnedAudioBuffer::new_from_data(data, 2, 48000)
+    }
+
+    #[test]
+    fn round_trips_samples_and_presets() {
+        let mut bank = SoundBank::new();
+        bank.add_sample("footstep", make_sample());
+        bank.add_preset(
+            "underwater",
+            BankPreset::new()
+                .with_value("gain", 0.5)
+                .with_value("cutoff", 400.0),
+        );
+
+        let mut bytes = Vec::new();
+        bank.write_to(&mut bytes).unwrap();
+
+        let loaded = SoundBank::read_from(&mut bytes.as_slice()).unwrap();
+        assert_eq!(loaded.sample_names().collect::<Vec<_>>(), vec!["footstep"]);
+
+        let preset = loaded.get_preset("underwater").unwrap();
+        assert_eq!(preset.get_value("cutoff"), Some(400.0));
+
+        let mut store = SampleStore::new();
+        let samples = loaded.register(&mut store);
+        assert!(store.contains("footstep"));
+
+        let original = make_sample();
+        assert_eq!(samples[0].num_channels(), 2);
+        assert_eq!(samples[0].sample_rate(), 48000);
+        assert_eq!(samples[0].num_frames(), original.num_frames());
+
+        for frame in 0..original.num_frames() {
+            for channel in 0..2 {
+                let location = SampleLocation::new(channel, frame);
+                assert_eq!(
+                    samples[0].get_sample(location),
+                    original.get_sample(location)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        let bytes = b"RAECACHE\x01\x00\x00\x00".to_vec();
+        assert!(SoundBank::read_from(&mut bytes.as_slice()).is_err());
+    }
+}
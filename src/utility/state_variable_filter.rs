@@ -0,0 +1,106 @@
+use std::f64::consts::PI;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FilterState {
+    first: f64,
+    second: f64,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FilterOutputs {
+    pub low: f64,
+    pub band: f64,
+    pub high: f64,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StateVariableFilter {
+    frequency: f64,
+    q: f64,
+    damping: f64,
+    a1: f64,
+    a2: f64,
+    a3: f64,
+}
+
+impl StateVariableFilter {
+    pub fn new(frequency: f64, q: f64, sample_rate: usize) -> Self {
+        let nyquist_limit = 0.49 * sample_rate as f64;
+        let g = (PI * frequency.clamp(1.0, nyquist_limit) / sample_rate as f64).tan();
+        let damping = 1.0 / q.max(1e-3);
+        let a1 = 1.0 / (1.0 + g * (g + damping));
+        let a2 = g * a1;
+
+        Self {
+            frequency,
+            q,
+            damping,
+            a1,
+            a2,
+            a3: g * a2,
+        }
+    }
+
+    pub fn update(&mut self, frequency: f64, q: f64, sample_rate: usize) {
+        if frequency != self.frequency || q != self.q {
+            *self = Self::new(frequency, q, sample_rate);
+        }
+    }
+
+    pub fn process(&self, state: &mut FilterState, input: f64) -> FilterOutputs {
+        let v3 = input - state.second;
+        let v1 = self.a1 * state.first + self.a2 * v3;
+        let v2 = state.second + self.a2 * state.first + self.a3 * v3;
+        state.first = 2.0 * v1 - state.first;
+        state.second = 2.0 * v2 - state.second;
+
+        let band = self.damping * v1;
+
+        FilterOutputs {
+            low: v2,
+            band,
+            high: input - band - v2,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::TAU;
+
+    use super::*;
+
+    fn peak_response(
+        filter: &StateVariableFilter,
+        hz: f64,
+        output: fn(FilterOutputs) -> f64,
+    ) -> f64 {
+        let mut state = FilterState::default();
+
+        (0..4800)
+            .map(|frame| {
+                let input = (TAU * hz * frame as f64 / 48_000.0).sin();
+                output(filter.process(&mut state, input)).abs()
+            })
+            .skip(2400)
+            .fold(0.0, f64::max)
+    }
+
+    #[test]
+    fn outputs_sum_to_input_and_separate_bands() {
+        let filter = StateVariableFilter::new(1000.0, 0.7, 48_000);
+        let mut state = FilterState::default();
+
+        for frame in 0..100 {
+            let input = ((frame * 7919) % 101) as f64 / 50.0 - 1.0;
+            let outputs = filter.process(&mut state, input);
+            assert!((outputs.low + outputs.band + outputs.high - input).abs() < 1e-12);
+        }
+
+        let band = StateVariableFilter::new(1000.0, 2.0, 48_000);
+        assert!((peak_response(&band, 1000.0, |outputs| outputs.band) - 1.0).abs() < 0.01);
+        assert!(peak_response(&band, 100.0, |outputs| outputs.band) < 0.1);
+        assert!(peak_response(&filter, 100.0, |outputs| outputs.low) > 0.95);
+        assert!(peak_response(&filter, 10000.0, |outputs| outputs.high) > 0.95);
+    }
+}
@@ -0,0 +1,260 @@
+use std::f64::consts::TAU;
+
+use crate::{buffer::sample_location::SampleLocation, AudioBuffer};
+
+use super::fft::Fft;
+
+pub const fn latency_in_samples(fft_size: usize) -> usize {
+    fft_size
+}
+
+pub trait SpectralProcessor {
+    fn process_spectrum(&mut self, channel: usize, real: &mut [f32], imaginary: &mut [f32]);
+
+    fn finish_hop(&mut self) {}
+}
+
+struct StftChannel {
+    input: Vec<f32>,
+    output: Vec<f32>,
+    accumulator: Vec<f32>,
+}
+
+impl StftChannel {
+    fn new(fft_size: usize, hop_size: usize) -> Self {
+        Self {
+            input: vec![0.0; fft_size],
+            output: vec![0.0; hop_size],
+            accumulator: vec![0.0; fft_size],
+        }
+    }
+}
+
+pub struct StftProcessor {
+    fft_size: usize,
+    hop_size: usize,
+    fft: Fft,
+    window: Vec<f32>,
+    overlap_scale: f32,
+    real: Vec<f32>,
+    imaginary: Vec<f32>,
+    channels: Vec<StftChannel>,
+    position: usize,
+}
+
+impl StftProcessor {
+    pub fn new(fft_size: usize, overlap: usize, num_channels: usize) -> Self {
+        assert!(overlap >= 2 && fft_size.is_multiple_of(overlap));
+
+        let hop_size = fft_size / overlap;
+        let window: Vec<f32> = (0..fft_size)
+            .map(|index| (0.5 - 0.5 * (TAU * index as f64 / fft_size as f64).cos()).sqrt() as f32)
+            .collect();
+        let window_energy: f32 = window.iter().map(|value| value * value).sum();
+
+        Self {
+            fft_size,
+            hop_size,
+            fft: Fft::new(fft_size),
+            window,
+            overlap_scale: hop_size as f32 / window_energy,
+            real: vec![0.0; fft_size],
+            imaginary: vec![0.0; fft_size],
+            channels: (0..num_channels)
+                .map(|_| StftChannel::new(fft_size, hop_size))
+                .collect(),
+            position: fft_size - hop_size,
+        }
+    }
+
+    pub fn hop_size(&self) -> usize {
+        self.hop_size
+    }
+
+    pub fn num_bins(&self) -> usize {
+        self.fft_size / 2 + 1
+    }
+
+    fn first_write_position(&self) -> usize {
+        self.fft_size - self.hop_size
+    }
+
+    pub fn process(
+        &mut self,
+        input_buffer: &dyn AudioBuffer,
+        output_buffer: &mut dyn AudioBuffer,
+        spectral_processor: &mut dyn SpectralProcessor,
+    ) {
+        let first_write_position = self.first_write_position();
+        let num_channels = output_buffer
+            .num_channels()
+            .min(input_buffer.num_channels())
+            .min(self.channels.len());
+
+        for frame in 0..output_buffer.num_frames() {
+            for channel in 0..num_channels {
+                let location = SampleLocation::new(channel, frame);
+                let state = &mut self.channels[channel];
+                state.input[self.position] = input_buffer.get_sample(location);
+                output_buffer
+                    .set_sample(location, state.output[self.position - first_write_position]);
+            }
+
+            self.position += 1;
+
+            if self.position == self.fft_size {
+                for channel in 0..num_channels {
+                    self.process_hop(channel, spectral_processor);
+                }
+
+                spectral_processor.finish_hop();
+                self.position = first_write_position;
+            }
+        }
+    }
+
+    fn process_hop(&mut self, channel: usize, spectral_processor: &mut dyn SpectralProcessor) {
+        let fft_size = self.fft_size;
+        let hop_size = self.hop_size;
+        let num_bins = self.num_bins();
+        let state = &mut self.channels[channel];
+
+        for index in 0..fft_size {
+            self.real[index] = state.input[index] * self.window[index];
+            self.imaginary[index] = 0.0;
+        }
+
+        self.fft.forward(&mut self.real, &mut self.imaginary);
+
+        spectral_processor.process_spectrum(
+            channel,
+            &mut self.real[..num_bins],
+            &mut self.imaginary[..num_bins],
+        );
+
+        for bin in 1..fft_size / 2 {
+            self.real[fft_size - bin] = self.real[bin];
+            self.imaginary[fft_size - bin] = -self.imaginary[bin];
+        }
+
+        self.fft.inverse(&mut self.real, &mut self.imaginary);
+
+        for index in 0..fft_size {
+            state.accumulator[index] += self.real[index] * self.window[index] * self.overlap_scale;
+        }
+
+        state.output.copy_from_slice(&state.accumulator[..hop_size]);
+        state.accumulator.copy_within(hop_size.., 0);
+        state.accumulator[fft_size - hop_size..].fill(0.0);
+        state.input.copy_within(hop_size.., 0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::OwnedAudioBuffer;
+
+    use super::*;
+
+    struct Passthrough {
+        num_hops: usize,
+    }
+
+    impl SpectralProcessor for Passthrough {
+        fn process_spectrum(&mut self, _channel: usize, _real: &mut [f32], _imaginary: &mut [f32]) {
+        }
+
+        fn finish_hop(&mut self) {
+            self.num_hops += 1;
+        }
+    }
+
+    struct KeepBins {
+        bins: std::ops::Range<usize>,
+    }
+
+    impl SpectralProcessor for KeepBins {
+        fn process_spectrum(&mut self, _channel: usize, real: &mut [f32], imaginary: &mut [f32]) {
+            for bin in 0..real.len() {
+                if !self.bins.contains(&bin) {
+                    real[bin] = 0.0;
+                    imaginary[bin] = 0.0;
+                }
+            }
+        }
+    }
+
+    fn sine(frame: usize, period: usize) -> f32 {
+        (TAU * frame as f64 / period as f64).sin() as f32
+    }
+
+    fn process(
+        stft: &mut StftProcessor,
+        spectral_processor: &mut dyn SpectralProcessor,
+        num_frames: usize,
+        signal: impl Fn(usize, usize) -> f32,
+    ) -> OwnedAudioBuffer {
+        let mut input_buffer = OwnedAudioBuffer::new(num_frames, 2, 48_000);
+        for frame in 0..num_frames {
+            for channel in 0..2 {
+                input_buffer
+                    .set_sample(SampleLocation::new(channel, frame), signal(channel, frame));
+            }
+        }
+
+        let mut output_buffer = OwnedAudioBuffer::new(num_frames, 2, 48_000);
+        for offset in (0..num_frames).step_by(100) {
+            let block_size = 100.min(num_frames - offset);
+            stft.process(
+                &crate::buffer::immutable_audio_buffer_slice::ImmutableAudioBufferSlice::new(
+                    &input_buffer,
+                    offset,
+                ),
+                &mut crate::buffer::audio_buffer_slice::AudioBufferSlice::new(
+                    &mut output_buffer,
+                    offset,
+                    block_size,
+                ),
+                spectral_processor,
+            );
+        }
+        output_buffer
+    }
+
+    #[test]
+    fn reconstructs_input_after_latency() {
+        let mut stft = StftProcessor::new(256, 4, 2);
+        let mut passthrough = Passthrough { num_hops: 0 };
+        let latency = latency_in_samples(256);
+        assert_eq!(latency, 256);
+
+        let output = process(&mut stft, &mut passthrough, 2048, |channel, frame| {
+            sine(frame, 50 + channel * 30)
+        });
+
+        assert_eq!(passthrough.num_hops, 2048 / 64);
+        for frame in latency + 256..2048 {
+            for channel in 0..2 {
+                let expected = sine(frame - latency, 50 + channel * 30);
+                let actual = output.get_sample(SampleLocation::new(channel, frame));
+                assert!((actual - expected).abs() < 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn processes_positive_frequency_bins() {
+        let mut stft = StftProcessor::new(256, 4, 2);
+        let mut keep_low = KeepBins { bins: 0..16 };
+        let output = process(&mut stft, &mut keep_low, 4096, |_, frame| {
+            sine(frame, 64) + sine(frame, 8)
+        });
+
+        let latency = latency_in_samples(256);
+        for frame in latency + 512..4096 {
+            let expected = sine(frame - latency, 64);
+            let actual = output.get_sample(SampleLocation::new(0, frame));
+            assert!((actual - expected).abs() < 1e-2);
+        }
+    }
+}
@@ -0,0 +1,198 @@
+#[cfg(feature = "trace")]
+mod recorder {
+    use std::{
+        io::Write,
+        sync::atomic::{AtomicBool, Ordering},
+        time::{Duration, Instant},
+    };
+
+    use lockfree::queue::Queue;
+
+    use crate::commands::id::Id;
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub enum Category {
+        Block,
+        Command,
+        Event,
+        Node,
+    }
+
+    impl Category {
+        fn name(&self) -> &'static str {
+            match self {
+                Category::Block => "block",
+                Category::Command => "command",
+                Category::Event => "event",
+                Category::Node => "node",
+            }
+        }
+    }
+
+    pub struct TraceEvent {
+        name: &'static str,
+        category: Category,
+        dsp_id: Option<Id>,
+        start: Duration,
+        duration: Option<Duration>,
+    }
+
+    lazy_static! {
+        static ref EPOCH: Instant = Instant::now();
+        static ref EVENTS: Queue<TraceEvent> = Queue::new();
+    }
+
+    static ENABLED: AtomicBool = AtomicBool::new(false);
+
+    pub struct Span {
+        name: &'static str,
+        category: Category,
+        dsp_id: Option<Id>,
+        start: Option<Instant>,
+    }
+
+    impl Drop for Span {
+        fn drop(&mut self) {
+            if let Some(start) = self.start {
+                EVENTS.push(TraceEvent {
+                    name: self.name,
+                    category: self.category,
+                    dsp_id: self.dsp_id,
+                    start: start.duration_since(*EPOCH),
+                    duration: Some(start.elapsed()),
+                });
+            }
+        }
+    }
+
+    pub fn start() {
+        let _ = *EPOCH;
+        while EVENTS.pop().is_some() {}
+        ENABLED.store(true, Ordering::Release);
+    }
+
+    pub fn stop() {
+        ENABLED.store(false, Ordering::Release);
+    }
+
+    fn is_enabled() -> bool {
+        ENABLED.load(Ordering::Acquire)
+    }
+
+    pub fn span(name: &'static str, category: Category, dsp_id: Option<Id>) -> Span {
+        Span {
+            name,
+            category,
+            dsp_id,
+            start: is_enabled().then(Instant::now),
+        }
+    }
+
+    pub fn instant(name: &'static str, category: Category, dsp_id: Option<Id>) {
+        if is_enabled() {
+            EVENTS.push(TraceEvent {
+                name,
+                category,
+                dsp_id,
+                start: EPOCH.elapsed(),
+                duration: None,
+            });
+        }
+    }
+
+    pub fn write_chrome_trace(writer: &mut dyn Write) -> std::io::Result<()> {
+        let events: Vec<TraceEvent> = EVENTS.pop_iter().collect();
+        write_events(&events, writer)
+    }
+
+    fn write_events(events: &[TraceEvent], writer: &mut dyn Write) -> std::io::Result<()> {
+        writeln!(writer, "{{\"traceEvents\":[")?;
+
+        for (index, event) in events.iter().enumerate() {
+            write!(
+                writer,
+                "{{\"name\":\"{}\",\"cat\":\"{}\",\"pid\":0,\"tid\":0,\"ts\":{}",
+                event.name,
+                event.category.name(),
+                event.start.as_micros()
+            )?;
+
+            match event.duration {
+                Some(duration) => write!(writer, ",\"ph\":\"X\",\"dur\":{}", duration.as_micros())?,
+                None => write!(writer, ",\"ph\":\"i\",\"s\":\"t\"")?,
+            }
+
+            if let Some(dsp_id) = event.dsp_id {
+                write!(writer, ",\"args\":{{\"dsp_id\":\"{:?}\"}}", dsp_id)?;
+            }
+
+            let separator = if index + 1 < events.len() { "," } else { "" };
+            writeln!(writer, "}}{}", separator)?;
+        }
+
+        writeln!(writer, "]}}")
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn writes_chrome_trace_json() {
+            let events = vec![
+                TraceEvent {
+                    name: "block",
+                    category: Category::Block,
+                    dsp_id: None,
+                    start: Duration::from_micros(10),
+                    duration: Some(Duration::from_micros(5)),
+                },
+                TraceEvent {
+                    name: "Start",
+                    category: Category::Command,
+                    dsp_id: None,
+                    start: Duration::from_micros(12),
+                    duration: None,
+                },
+            ];
+
+            let mut output = Vec::new();
+            write_events(&events, &mut output).unwrap();
+            let output = String::from_utf8(output).unwrap();
+
+            assert!(output.starts_with("{\"traceEvents\":["));
+            assert!(output.contains(
+                "{\"name\":\"block\",\"cat\":\"block\",\"pid\":0,\"tid\":0,\"ts\":10,\"ph\":\"X\",\"dur\":5},"
+            ));
+            assert!(output.contains(
+                "{\"name\":\"Start\",\"cat\":\"command\",\"pid\":0,\"tid\":0,\"ts\":12,\"ph\":\"i\",\"s\":\"t\"}\n"
+            ));
+            assert!(output.trim_end().ends_with("]}"));
+        }
+    }
+}
+
+#[cfg(not(feature = "trace"))]
+mod recorder {
+    use crate::commands::id::Id;
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub enum Category {
+        Block,
+        Command,
+        Event,
+        Node,
+    }
+
+    pub struct Span;
+
+    #[inline(always)]
+    pub fn span(_name: &'static str, _category: Category, _dsp_id: Option<Id>) -> Span {
+        Span
+    }
+
+    #[inline(always)]
+    pub fn instant(_name: &'static str, _category: Category, _dsp_id: Option<Id>) {}
+}
+
+pub use recorder::*;